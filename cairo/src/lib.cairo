@@ -144,6 +144,14 @@ pub mod AtomicLock {
         pub secret_hash: u32,
     }
 
+    /// Emitted when the depositor funds the lock.
+    #[derive(Drop, starknet::Event)]
+    pub struct Deposited {
+        #[key]
+        pub depositor: starknet::ContractAddress,
+        pub amount: u256,
+    }
+
     #[derive(Drop, starknet::Event)]
     pub struct Refunded {
         #[key]
@@ -206,6 +214,7 @@ pub mod AtomicLock {
     #[derive(Drop, starknet::Event)]
     pub enum Event {
         ContractDeployed: ContractDeployed,
+        Deposited: Deposited,
         Unlocked: Unlocked,
         Refunded: Refunded,
         SecretRevealed: SecretRevealed,
@@ -1043,6 +1052,7 @@ pub mod AtomicLock {
             let token = self.token.read();
             let ok = pull_from_depositor(token, caller, amount);
             assert(ok, Errors::TOKEN_TRANSFER_FAILED);
+            self.emit(Deposited { depositor: caller, amount });
             
             // PRODUCTION: End reentrancy guard protection
             self.reentrancy_guard.end();
@@ -1167,12 +1177,12 @@ pub mod AtomicLock {
     /// This uses a deterministic hash-to-curve approach to derive Y from a constant tag.
     /// The point Y must be fixed and known to both prover and verifier.
     /// 
-    /// Currently uses 2·G as placeholder until Python tool converts Edwards→Weierstrass.
-    /// In production, this will be replaced with hardcoded constant from:
-    /// hash_to_curve("DLEQ_SECOND_BASE_V1") → Edwards → Weierstrass → u384 limbs
+    /// Currently uses 2·G as placeholder. The replacement is
+    /// hash_to_curve("DLEQ_SECOND_BASE_V1") (RFC 9380, Elligator 2); its u384 limbs and
+    /// ED25519_SECOND_GENERATOR_COMPRESSED are printed by `cargo run --bin generate_second_base`.
     fn get_dleq_second_generator() -> G1Point {
         // Placeholder: using 2·G as second generator
-        // TODO: Replace with hardcoded constant from Rust hash-to-curve computation
+        // Migrate together with the Rust `h2c-second-generator` feature
         // The constant must match Rust's get_second_generator() exactly
         let G = get_G(ED25519_CURVE_INDEX);
         ec_safe_add(G, G, ED25519_CURVE_INDEX)
@@ -0,0 +1,156 @@
+//! Cost of the crypto core: DLEQ proofs, Cairo hints, key splitting, adaptor
+//! signatures and complete swap secrets.
+//!
+//! ```bash
+//! cargo bench --bench crypto_core
+//! cargo bench --bench crypto_core -- dleq   # one group
+//! ```
+//!
+//! Hint generation used to run in a Python subprocess; the `cairo_hints` and
+//! `swap_secret` groups show what it costs natively. Monero signing uses key
+//! splitting rather than CLSAG adaptor signatures, so there is no ring-size
+//! sweep: the `adaptor_signature` group covers the simplified Schnorr
+//! adaptor signature instead.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha256};
+use xmr_secret_gen::adaptor::{create_adaptor_signature, finalize_signature, verify_signature};
+use xmr_secret_gen::dleq::{generate_dleq_proof, verify_dleq_batch, verify_dleq_proof, DleqProof};
+use xmr_secret_gen::generate_swap_secret;
+use xmr_secret_gen::hints::{dleq_msm_hints, fake_glv_hint};
+use xmr_secret_gen::monero::SwapKeyPair;
+use zeroize::Zeroizing;
+
+/// Batch sizes for DLEQ batch verification
+const BATCH_SIZES: [usize; 3] = [1, 16, 64];
+
+/// Proof, adaptor point and hashlock for a secret derived from `seed`
+fn dleq_fixture(seed: u8) -> (DleqProof, EdwardsPoint, [u8; 32]) {
+    let secret_bytes = Scalar::from_bytes_mod_order([seed; 32]).to_bytes();
+    let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+    let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+    let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+    let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+        .expect("proof generation");
+    (proof, adaptor_point, hashlock)
+}
+
+fn bench_dleq(c: &mut Criterion) {
+    let secret_bytes = Scalar::from_bytes_mod_order([0x42; 32]).to_bytes();
+    let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+    let (proof, adaptor_point, hashlock) = dleq_fixture(0x42);
+
+    let mut group = c.benchmark_group("dleq");
+    group.bench_function("generate", |b| {
+        b.iter(|| {
+            generate_dleq_proof(&secret, &secret_bytes, black_box(&adaptor_point), &hashlock)
+                .expect("proof generation")
+        })
+    });
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            verify_dleq_proof(black_box(&proof), &adaptor_point, &hashlock).expect("valid proof")
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("dleq_batch_verify");
+    for size in BATCH_SIZES {
+        let batch: Vec<_> = (1..=size).map(|i| dleq_fixture(i as u8)).collect();
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_function(size.to_string(), |b| {
+            b.iter(|| verify_dleq_batch(black_box(&batch)).expect("valid batch"))
+        });
+    }
+    group.finish();
+}
+
+fn bench_hints(c: &mut Criterion) {
+    let (proof, adaptor_point, _) = dleq_fixture(0x42);
+    let scalar = Scalar::from(0x1234_5678_9abc_def0u64);
+
+    let mut group = c.benchmark_group("cairo_hints");
+    group.bench_function("fake_glv", |b| {
+        b.iter(|| fake_glv_hint(&ED25519_BASEPOINT_POINT, black_box(&scalar)).expect("hint"))
+    });
+    group.bench_function("dleq_msm", |b| {
+        b.iter(|| dleq_msm_hints(black_box(&proof), &adaptor_point).expect("hints"))
+    });
+    group.finish();
+}
+
+fn bench_key_splitting(c: &mut Criterion) {
+    let keys = SwapKeyPair::generate();
+
+    let mut group = c.benchmark_group("key_splitting");
+    group.bench_function("generate", |b| b.iter(SwapKeyPair::generate));
+    group.bench_function("verify", |b| b.iter(|| black_box(&keys).verify()));
+    group.bench_function("recover", |b| {
+        b.iter_batched(
+            || Zeroizing::new(keys.partial_key),
+            |partial_key| SwapKeyPair::recover(partial_key, black_box(keys.adaptor_scalar)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_adaptor_signature(c: &mut Criterion) {
+    let message = b"monero transaction prefix hash";
+    let base_key = Scalar::from(7u64);
+    let adaptor_scalar = Scalar::from(11u64);
+    let adaptor_point = ED25519_BASEPOINT_POINT * adaptor_scalar;
+    let public_key = ED25519_BASEPOINT_POINT * (base_key + adaptor_scalar);
+    let signature = create_adaptor_signature(&base_key, &adaptor_point, message);
+    let (s_final, _) = finalize_signature(&signature, &adaptor_scalar, message);
+    let challenge = {
+        let mut input = message.to_vec();
+        input.extend_from_slice(&signature.nonce_commitment.compress().to_bytes());
+        input.extend_from_slice(&adaptor_point.compress().to_bytes());
+        Scalar::from_bytes_mod_order(Sha256::digest(&input).into())
+    };
+    assert!(verify_signature(
+        &s_final,
+        &signature.nonce_commitment,
+        &challenge,
+        &public_key
+    ));
+
+    let mut group = c.benchmark_group("adaptor_signature");
+    group.bench_function("create", |b| {
+        b.iter(|| create_adaptor_signature(&base_key, black_box(&adaptor_point), message))
+    });
+    group.bench_function("finalize", |b| {
+        b.iter(|| finalize_signature(black_box(&signature), &adaptor_scalar, message))
+    });
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            verify_signature(
+                black_box(&s_final),
+                &signature.nonce_commitment,
+                &challenge,
+                &public_key,
+            )
+        })
+    });
+    group.finish();
+}
+
+fn bench_swap_secret(c: &mut Criterion) {
+    c.bench_function("swap_secret/generate", |b| {
+        b.iter(|| generate_swap_secret().expect("swap secret"))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_dleq,
+    bench_hints,
+    bench_key_splitting,
+    bench_adaptor_signature,
+    bench_swap_secret
+);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+//! Hash backend throughput and DLEQ verifications per second.
+//!
+//! Batch groups compare the portable backend with the multi-lane one when
+//! it is built:
+//!
+//! ```bash
+//! cargo bench --bench hash_backend --features multi-lane-hash
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha256};
+use xmr_secret_gen::dleq::{generate_dleq_proof, verify_dleq_batch_with, verify_dleq_proof_with};
+use xmr_secret_gen::encoding::PointCache;
+#[cfg(feature = "multi-lane-hash")]
+use xmr_secret_gen::hash_backend::MultiLaneBackend;
+use xmr_secret_gen::hash_backend::{HashBackend, PortableBackend};
+use zeroize::Zeroizing;
+
+/// Size of the DLEQ challenge preimage: tag + 6 points + hashlock
+const CHALLENGE_INPUT_LEN: usize = 4 + 6 * 32 + 32;
+
+fn bench_hashes(c: &mut Criterion) {
+    let backend = PortableBackend;
+    let message = [0x42u8; CHALLENGE_INPUT_LEN];
+
+    let mut group = c.benchmark_group("challenge_hash");
+    group.throughput(Throughput::Bytes(CHALLENGE_INPUT_LEN as u64));
+    group.bench_function("blake2s256", |b| {
+        b.iter(|| backend.blake2s256(&[black_box(&message)]))
+    });
+    group.bench_function("keccak256", |b| {
+        b.iter(|| backend.keccak256(&[black_box(&message)]))
+    });
+    group.finish();
+
+    let batch: Vec<Vec<&[u8]>> = (0..64).map(|_| vec![&message[..]]).collect();
+    let mut group = c.benchmark_group("challenge_hash_batch");
+    group.throughput(Throughput::Elements(batch.len() as u64));
+    group.bench_function("portable_x64", |b| {
+        b.iter(|| PortableBackend.blake2s256_batch(black_box(&batch)))
+    });
+    #[cfg(feature = "multi-lane-hash")]
+    group.bench_function("multi_lane_x64", |b| {
+        b.iter(|| MultiLaneBackend.blake2s256_batch(black_box(&batch)))
+    });
+    group.finish();
+}
+
+fn bench_dleq_verify(c: &mut Criterion) {
+    let secret_bytes = [0x42u8; 32];
+    let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+    let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+    let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+    let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+        .expect("proof generation");
+
+    let mut group = c.benchmark_group("dleq_verify");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("portable", |b| {
+        b.iter(|| {
+            verify_dleq_proof_with(
+                &PortableBackend,
+                black_box(&proof),
+                &adaptor_point,
+                &hashlock,
+            )
+            .expect("valid proof")
+        })
+    });
+    group.finish();
+
+    let batch = vec![(proof, adaptor_point, hashlock); 64];
+    let mut group = c.benchmark_group("dleq_batch_verify_x64");
+    group.throughput(Throughput::Elements(batch.len() as u64));
+    group.bench_function("portable", |b| {
+        b.iter(|| {
+            verify_dleq_batch_with(
+                &PortableBackend,
+                black_box(&batch),
+                &mut PointCache::default(),
+            )
+            .expect("valid batch")
+        })
+    });
+    #[cfg(feature = "multi-lane-hash")]
+    group.bench_function("multi_lane", |b| {
+        b.iter(|| {
+            verify_dleq_batch_with(
+                &MultiLaneBackend,
+                black_box(&batch),
+                &mut PointCache::default(),
+            )
+            .expect("valid batch")
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_hashes, bench_dleq_verify);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+//! Generate a swap secret and its DLEQ proof, then check the proof the way a
+//! taker does before depositing.
+//!
+//! 1. The maker picks `t`, publishes the hashlock `SHA-256(t)` and the
+//!    adaptor point `T = t·G`, and proves both hide the same `t`.
+//! 2. The maker encodes the AtomicLock constructor calldata.
+//! 3. The taker decodes that calldata and verifies the proof against it,
+//!    using the maker's full proof (the calldata only holds 128 bits of the
+//!    challenge and response).
+//!
+//! Run with `cargo run --example generate_and_verify_dleq`.
+
+use anyhow::{ensure, Result};
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use xmr_secret_gen::contracts::atomic_lock::{constructor_calldata, ConstructorArgs};
+use xmr_secret_gen::{generate_dleq_proof, verify_dleq_proof, DleqProof};
+use zeroize::Zeroizing;
+
+fn main() -> Result<()> {
+    // Maker: a canonical scalar, so its bytes are exactly what gets hashed
+    let mut raw = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut raw);
+    let secret = Zeroizing::new(Scalar::from_bytes_mod_order(raw));
+    let secret_bytes = Zeroizing::new(secret.to_bytes());
+    let hashlock: [u8; 32] = Sha256::digest(*secret_bytes).into();
+    let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+
+    let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)?;
+    verify_dleq_proof(&proof, &adaptor_point, &hashlock)?;
+    println!("hashlock:      {}", hex::encode(hashlock));
+    println!(
+        "adaptor point: {}",
+        hex::encode(adaptor_point.compress().as_bytes())
+    );
+
+    // Maker: constructor calldata for the AtomicLock deployment
+    let lock_until = 1_700_003_600;
+    let token = "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d";
+    let calldata = constructor_calldata(
+        &hashlock,
+        lock_until,
+        token,
+        10u128.pow(18),
+        &secret,
+        &proof,
+    )?;
+    println!("calldata:      {} felts", calldata.len());
+
+    // Taker: the proof travels as JSON next to the deployment
+    let proof_json = proof.to_json()?;
+    let received = DleqProof::from_json(&proof_json).map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    let args = ConstructorArgs::decode(&calldata)?;
+    ensure!(
+        args.is_truncated(),
+        "gen-hints style calldata carries 128-bit scalars"
+    );
+    let verified_point = args.verify(Some(&received))?;
+    ensure!(
+        verified_point == adaptor_point,
+        "contract is bound to another T"
+    );
+    ensure!(
+        args.hashlock() == hashlock,
+        "contract is bound to another hashlock"
+    );
+    println!("taker check:   proof binds T to the deployed hashlock");
+
+    // A proof for another hashlock is rejected
+    let mut other_hashlock = hashlock;
+    other_hashlock[0] ^= 1;
+    ensure!(
+        verify_dleq_proof(&proof, &adaptor_point, &other_hashlock).is_err(),
+        "proof must not verify against another hashlock"
+    );
+    println!("tampered:      rejected");
+    Ok(())
+}
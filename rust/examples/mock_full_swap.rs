@@ -0,0 +1,198 @@
+//! A complete swap against an in-memory AtomicLock, from key generation to
+//! the maker recovering the Monero spend key.
+//!
+//! Nothing here talks to a node. `MockLock` stands in for the deployed
+//! contract: it executes the calls built by [`AtomicLock`] and emits events
+//! in the `starknet_getEvents` JSON shape, which both parties decode with
+//! [`AtomicLockEvent::from_rpc`] and feed to their [`SwapOrchestrator`]. To
+//! run against Sepolia instead, send the same calls with
+//! `starknet_full::StarknetAccount` and read the events from the node.
+//!
+//! Run with `cargo run --example mock_full_swap`.
+
+use anyhow::{bail, ensure, Context, Result};
+use curve25519_dalek::scalar::Scalar;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use xmr_secret_gen::cairo_serde::byte_array_from_calldata;
+use xmr_secret_gen::contracts::atomic_lock::{
+    constructor_calldata, AtomicLock, AtomicLockEvent, ConstructorArgs, ContractCall, CLAIM_TOKENS,
+    DEPOSIT, REVEAL_SECRET,
+};
+use xmr_secret_gen::starknet::get_selector_from_name;
+use xmr_secret_gen::swap::orchestrator::SwapOrchestrator;
+use xmr_secret_gen::swap::reconcile::NextAction;
+use xmr_secret_gen::swap::{SwapPhase, SwapRole};
+use xmr_secret_gen::{generate_dleq_proof, SwapKeyPair};
+use zeroize::Zeroizing;
+
+const CONTRACT: &str = "0x5eed";
+const MAKER: &str = "0xa11ce";
+const TAKER: &str = "0xb0b";
+const TOKEN: &str = "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d";
+const AMOUNT: u128 = 1_000_000_000_000_000_000;
+/// Same as the contract's `GRACE_PERIOD`
+const GRACE_PERIOD: u64 = 7200;
+
+fn main() -> Result<()> {
+    let mut now = 1_700_000_000;
+
+    // Maker: split the spend key, prove T and the hashlock hide the same t
+    let keys = SwapKeyPair::generate();
+    let secret_bytes = keys.adaptor_scalar_bytes();
+    let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+    let secret = Zeroizing::new(keys.adaptor_scalar);
+    let proof = generate_dleq_proof(&secret, &secret_bytes, &keys.adaptor_point, &hashlock)?;
+    let lock_until = now + 3 * 3600;
+    let calldata = constructor_calldata(&hashlock, lock_until, TOKEN, AMOUNT, &secret, &proof)?;
+
+    let swap_id = hex::encode(hashlock);
+    let mut maker = SwapOrchestrator::new(&swap_id, SwapRole::Maker);
+    let mut taker = SwapOrchestrator::new(&swap_id, SwapRole::Taker);
+    maker.on_transition(|state, change| {
+        println!(
+            "maker: {:?} -> {:?} ({})",
+            change.from, change.to, state.swap_id
+        );
+    });
+    taker.on_transition(|state, change| {
+        println!(
+            "taker: {:?} -> {:?} ({})",
+            change.from, change.to, state.swap_id
+        );
+    });
+
+    // Deployment: the taker checks the proof before relying on the contract
+    let mut chain = MockLock::deploy(&calldata)?;
+    ConstructorArgs::decode(&calldata)?.verify(Some(&proof))?;
+    maker.deployed(CONTRACT, lock_until)?;
+    taker.deployed(CONTRACT, lock_until)?;
+
+    let lock = AtomicLock::new(CONTRACT);
+    ensure!(maker.next_action(now) == NextAction::Deposit);
+    chain.execute(MAKER, &lock.deposit(), now)?;
+    for event in chain.take_events() {
+        if let Some(AtomicLockEvent::Deposited(_)) = AtomicLockEvent::from_rpc(&event)? {
+            maker.deposited()?;
+            taker.deposited()?;
+        }
+    }
+
+    // Taker: reveal t (the XMR is locked to x_partial·G + T by now)
+    now += 60;
+    ensure!(taker.next_action(now) == NextAction::RevealSecret);
+    let reveal = lock.reveal_secret(&secret_bytes);
+    chain.execute(TAKER, &reveal, now)?;
+    for event in chain.take_events() {
+        if let Some(AtomicLockEvent::SecretRevealed(revealed)) = AtomicLockEvent::from_rpc(&event)?
+        {
+            maker.revealed(revealed.claimable_after)?;
+            taker.revealed(revealed.claimable_after)?;
+        }
+    }
+
+    // Maker: read t from the reveal calldata and recover x = x_partial + t
+    ensure!(maker.next_action(now) == NextAction::SweepMonero);
+    let (revealed, _) = byte_array_from_calldata(&reveal.calldata)?;
+    let revealed: [u8; 32] = revealed.try_into().ok().context("secret is 32 bytes")?;
+    let t = Option::<Scalar>::from(Scalar::from_canonical_bytes(revealed))
+        .context("revealed secret is not a canonical scalar")?;
+    let full_key = SwapKeyPair::recover(Zeroizing::new(keys.partial_key), t);
+    ensure!(
+        *full_key == keys.full_spend_key,
+        "recovered the wrong spend key"
+    );
+    maker.finalized()?;
+
+    // Taker: claim once the grace period is over
+    let claimable_after = match taker.next_action(now) {
+        NextAction::ClaimTokens { claimable_after } => claimable_after,
+        action => bail!("unexpected taker action {:?}", action),
+    };
+    ensure!(
+        chain.execute(TAKER, &lock.claim(), now).is_err(),
+        "claim before grace period"
+    );
+    now = claimable_after;
+    chain.execute(TAKER, &lock.claim(), now)?;
+    for event in chain.take_events() {
+        if let Some(AtomicLockEvent::TokensClaimed(_)) = AtomicLockEvent::from_rpc(&event)? {
+            taker.finalized()?;
+        }
+    }
+
+    ensure!(maker.phase() == SwapPhase::Finalized && taker.phase() == SwapPhase::Finalized);
+    println!("swap {} complete", &swap_id[..16]);
+    Ok(())
+}
+
+/// In-memory stand-in for a deployed AtomicLock.
+struct MockLock {
+    hashlock: [u8; 32],
+    claimable_after: Option<u64>,
+    events: Vec<Value>,
+}
+
+impl MockLock {
+    fn deploy(calldata: &[String]) -> Result<Self> {
+        let args = ConstructorArgs::decode(calldata)?;
+        Ok(Self {
+            hashlock: args.hashlock(),
+            claimable_after: None,
+            events: Vec::new(),
+        })
+    }
+
+    /// Run `call` from `caller` at block time `now`.
+    fn execute(&mut self, caller: &str, call: &ContractCall, now: u64) -> Result<()> {
+        ensure!(call.to == CONTRACT, "call to another contract");
+        match call.entrypoint {
+            DEPOSIT => self.emit("Deposited", caller, amount()),
+            REVEAL_SECRET => {
+                let (secret, _) = byte_array_from_calldata(&call.calldata)?;
+                let hash: [u8; 32] = Sha256::digest(&secret).into();
+                ensure!(hash == self.hashlock, "secret does not match the hashlock");
+                let claimable_after = now + GRACE_PERIOD;
+                self.claimable_after = Some(claimable_after);
+                let first_word = u32::from_be_bytes(hash[..4].try_into()?);
+                self.emit(
+                    "SecretRevealed",
+                    caller,
+                    vec![
+                        format!("{:#x}", first_word),
+                        format!("{:#x}", claimable_after),
+                    ],
+                );
+            }
+            CLAIM_TOKENS => {
+                let claimable_after = self.claimable_after.context("secret not revealed")?;
+                ensure!(now >= claimable_after, "grace period not over");
+                let mut data = amount();
+                data.extend([
+                    format!("{:#x}", claimable_after - GRACE_PERIOD),
+                    format!("{:#x}", now),
+                ]);
+                self.emit("TokensClaimed", caller, data);
+            }
+            entrypoint => bail!("{} is not mocked", entrypoint),
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, name: &str, key: &str, data: Vec<String>) {
+        self.events.push(json!({
+            "from_address": CONTRACT,
+            "keys": [get_selector_from_name(name), key],
+            "data": data,
+        }));
+    }
+
+    fn take_events(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// `u256` amount as (low, high) felts.
+fn amount() -> Vec<String> {
+    vec![format!("{:#x}", AMOUNT), "0x0".to_string()]
+}
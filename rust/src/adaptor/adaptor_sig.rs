@@ -20,11 +20,13 @@
 //! all CLSAG complexities. This module is a proof-of-concept demonstrating that the
 //! same `t` scalar used in Cairo's MSM verification works for Monero signature finalization.
 
-use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
 use curve25519_dalek::edwards::EdwardsPoint;
 use curve25519_dalek::scalar::Scalar;
 use sha2::{Digest, Sha256};
 
+use crate::encoding::{check_prime_order_point, decode_point, decode_scalar, EncodingError};
+
 /// An adaptor signature (simplified version).
 ///
 /// In a real CLSAG, this would contain ring signature components.
@@ -40,6 +42,42 @@ pub struct AdaptorSignature {
     pub nonce_commitment: EdwardsPoint,
 }
 
+impl AdaptorSignature {
+    /// Serialize as `adaptor_point || partial_sig || nonce_commitment` (96 bytes).
+    pub fn to_bytes(&self) -> [u8; 96] {
+        let mut out = [0u8; 96];
+        out[..32].copy_from_slice(self.adaptor_point.compress().as_bytes());
+        out[32..64].copy_from_slice(self.partial_sig.as_bytes());
+        out[64..].copy_from_slice(self.nonce_commitment.compress().as_bytes());
+        out
+    }
+
+    /// Deserialize an adaptor signature received from the counterparty.
+    ///
+    /// Rejects non-canonical scalars and non-canonical, torsioned or identity points.
+    pub fn from_bytes(bytes: &[u8; 96]) -> Result<Self, EncodingError> {
+        let field = |i: usize| -> [u8; 32] {
+            bytes[i * 32..(i + 1) * 32]
+                .try_into()
+                .expect("32-byte field")
+        };
+        Ok(Self {
+            adaptor_point: decode_point(field(0))?,
+            partial_sig: decode_scalar(field(1))?,
+            nonce_commitment: decode_point(field(2))?,
+        })
+    }
+
+    /// Check that T and R are torsion-free and not the identity.
+    ///
+    /// Use this for signatures handed over already decoded; `from_bytes`
+    /// performs the same checks.
+    pub fn validate(&self) -> Result<(), EncodingError> {
+        check_prime_order_point(&self.adaptor_point)?;
+        check_prime_order_point(&self.nonce_commitment)
+    }
+}
+
 /// Create an adaptor signature using base_key and adaptor_point.
 ///
 /// This simulates creating a Monero transaction signature where:
@@ -68,7 +106,7 @@ pub fn create_adaptor_signature(
     let nonce = Scalar::from_bytes_mod_order(Sha256::digest(message).into());
 
     // Nonce commitment: R = nonce·G
-    let nonce_commitment = &nonce * &ED25519_BASEPOINT_POINT;
+    let nonce_commitment = &nonce * ED25519_BASEPOINT_TABLE;
 
     // Challenge: H(message || R || adaptor_point)
     let mut challenge_input = Vec::new();
@@ -146,15 +184,15 @@ pub fn verify_signature(
     challenge: &Scalar,
     public_key: &EdwardsPoint,
 ) -> bool {
-    // Verify: s_final·G == R + challenge·public_key
-    let lhs = s_final * &ED25519_BASEPOINT_POINT;
-    let rhs = nonce_commitment + challenge * public_key;
-    lhs == rhs
+    // Verify: s_final·G - challenge·public_key == R (public data, so vartime)
+    EdwardsPoint::vartime_double_scalar_mul_basepoint(&-challenge, public_key, s_final)
+        == *nonce_commitment
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
 
     #[test]
     fn test_adaptor_signature_flow() {
@@ -195,4 +233,40 @@ mod tests {
         // In real CLSAG, extraction would be more complex
         assert_eq!(extracted_key, adaptor_scalar);
     }
+
+    #[test]
+    fn test_adaptor_signature_bytes_roundtrip() {
+        let base_key = Scalar::from_bytes_mod_order([2u8; 32]);
+        let adaptor_point = &Scalar::from(5u64) * &ED25519_BASEPOINT_POINT;
+        let sig = create_adaptor_signature(&base_key, &adaptor_point, b"msg");
+
+        let restored = AdaptorSignature::from_bytes(&sig.to_bytes()).unwrap();
+        assert_eq!(restored.adaptor_point, sig.adaptor_point);
+        assert_eq!(restored.partial_sig, sig.partial_sig);
+        assert_eq!(restored.nonce_commitment, sig.nonce_commitment);
+    }
+
+    #[test]
+    fn test_adaptor_signature_rejects_bad_encodings() {
+        use curve25519_dalek::constants::EIGHT_TORSION;
+
+        let base_key = Scalar::from_bytes_mod_order([2u8; 32]);
+        let adaptor_point = &Scalar::from(5u64) * &ED25519_BASEPOINT_POINT;
+        let sig = create_adaptor_signature(&base_key, &adaptor_point, b"msg");
+
+        let mut bytes = sig.to_bytes();
+        bytes[32..64].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            AdaptorSignature::from_bytes(&bytes).unwrap_err(),
+            EncodingError::NonCanonicalScalar
+        );
+
+        let mut bytes = sig.to_bytes();
+        let torsioned = adaptor_point + EIGHT_TORSION[2];
+        bytes[..32].copy_from_slice(torsioned.compress().as_bytes());
+        assert_eq!(
+            AdaptorSignature::from_bytes(&bytes).unwrap_err(),
+            EncodingError::TorsionedPoint
+        );
+    }
 }
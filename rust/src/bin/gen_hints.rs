@@ -0,0 +1,111 @@
+//! Generate contract-ready Cairo hints from a test vector.
+//!
+//! Reads a DLEQ test vector (`test_vectors.json` format) and writes the sqrt
+//! hints and fake-GLV MSM hints the AtomicLock constructor expects, keyed by
+//! constructor parameter name. Replaces `tools/generate_hints_from_test_vectors.py`.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use serde_json::{json, Value};
+use xmr_secret_gen::dleq::{DleqProof, DleqProofSerialized};
+use xmr_secret_gen::encoding::decode_point;
+use xmr_secret_gen::hints::{dleq_msm_hints, fake_glv_hint, sqrt_hint, truncate_128};
+
+#[derive(Parser)]
+#[command(name = "gen-hints")]
+#[command(about = "Generate Cairo sqrt and MSM hints from a DLEQ test vector")]
+struct Args {
+    /// Test vector JSON (rust/test_vectors.json format)
+    #[arg(long, default_value = "test_vectors.json")]
+    vector: PathBuf,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let content = std::fs::read_to_string(&args.vector)
+        .with_context(|| format!("Failed to read test vector: {}", args.vector.display()))?;
+    let vector: Value = serde_json::from_str(&content).context("Invalid test vector JSON")?;
+
+    let secret = field_bytes(&vector, &["secret", "secret_raw_bytes"])?;
+    let adaptor_point = decode_point(field_bytes(&vector, &["adaptor_point_compressed"])?)
+        .context("Invalid adaptor point")?;
+    let proof = DleqProof::from_serializable(DleqProofSerialized {
+        second_point: field_bytes(
+            &vector,
+            &["dleq_second_point_compressed", "second_point_compressed"],
+        )?,
+        challenge: field_bytes(&vector, &["challenge", "dleq_challenge"])?,
+        response: field_bytes(&vector, &["response", "dleq_response"])?,
+        r1: field_bytes(&vector, &["r1_compressed"])?,
+        r2: field_bytes(&vector, &["r2_compressed"])?,
+    })
+    .context("Invalid DLEQ proof in test vector")?;
+
+    // The contract reduces the raw secret mod ℓ before the MSM
+    let secret_scalar = Scalar::from_bytes_mod_order(secret);
+    if ED25519_BASEPOINT_POINT * secret_scalar != adaptor_point {
+        anyhow::bail!("Adaptor point does not match secret (expected T = t·G)");
+    }
+
+    let adaptor_hint =
+        fake_glv_hint(&ED25519_BASEPOINT_POINT, &secret_scalar).context("Adaptor point hint")?;
+    let msm = dleq_msm_hints(&proof, &adaptor_point).context("DLEQ MSM hints")?;
+
+    let output = json!({
+        "adaptor_point_sqrt_hint": u256_json(sqrt_hint(&adaptor_point)),
+        "dleq_second_point_sqrt_hint": u256_json(sqrt_hint(&proof.second_point)),
+        "dleq_r1_sqrt_hint": u256_json(sqrt_hint(&proof.r1)),
+        "dleq_r2_sqrt_hint": u256_json(sqrt_hint(&proof.r2)),
+        "dleq_challenge": format!("{:#x}", low_u128(&truncate_128(&proof.challenge))),
+        "dleq_response": format!("{:#x}", low_u128(&truncate_128(&proof.response))),
+        "fake_glv_hint": adaptor_hint.to_hex_felts(),
+        "dleq_s_hint_for_g": msm.s_hint_for_g.to_hex_felts(),
+        "dleq_s_hint_for_y": msm.s_hint_for_y.to_hex_felts(),
+        "dleq_c_neg_hint_for_t": msm.c_neg_hint_for_t.to_hex_felts(),
+        "dleq_c_neg_hint_for_u": msm.c_neg_hint_for_u.to_hex_felts(),
+    });
+    let rendered = serde_json::to_string_pretty(&output)?;
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("✅ Hints written to {}", path.display());
+        }
+        None => println!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+/// First present field among `names`, decoded as 32 hex bytes.
+fn field_bytes(vector: &Value, names: &[&str]) -> Result<[u8; 32]> {
+    let hex_str = names
+        .iter()
+        .find_map(|name| vector.get(*name).and_then(Value::as_str))
+        .with_context(|| format!("Missing field {}", names.join(" / ")))?;
+    hex::decode(hex_str.trim_start_matches("0x"))
+        .with_context(|| format!("Invalid hex in {}", names[0]))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{} must be 32 bytes", names[0]))
+}
+
+/// Cairo `u256 { low, high }` from little-endian bytes.
+fn u256_json(bytes: [u8; 32]) -> Value {
+    let low = u128::from_le_bytes(bytes[..16].try_into().expect("16 bytes"));
+    let high = u128::from_le_bytes(bytes[16..].try_into().expect("16 bytes"));
+    json!({ "low": format!("{:#x}", low), "high": format!("{:#x}", high) })
+}
+
+fn low_u128(scalar: &Scalar) -> u128 {
+    u128::from_le_bytes(scalar.as_bytes()[..16].try_into().expect("16 bytes"))
+}
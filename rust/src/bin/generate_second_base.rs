@@ -1,33 +1,50 @@
 //! Generate the second generator point Y for DLEQ proofs.
 //!
-//! Computes Y = hash_to_curve("DLEQ_SECOND_BASE_V1") and outputs Cairo-formatted u384 limbs.
+//! Computes Y = hash_to_curve("DLEQ_SECOND_BASE_V1") (RFC 9380, Elligator 2)
+//! and prints the Cairo constants needed to switch the contract away from the
+//! `2·G` placeholder: the compressed Edwards u256 used in the challenge hash
+//! and the Weierstrass u384 limbs for `get_dleq_second_generator()`.
+//!
+//! Rust switches with the `h2c-second-generator` feature; deploy both together.
 
-use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
-use curve25519_dalek::scalar::Scalar;
-use sha2::{Digest, Sha512};
+use xmr_secret_gen::dleq::{
+    hash_to_curve_second_generator, SECOND_GENERATOR_DST, SECOND_GENERATOR_TAG,
+};
+use xmr_secret_gen::weierstrass::to_cairo_limbs;
 
 fn main() {
-    // Hash-to-curve using SHA-512 (Ed25519 standard)
-    let mut hasher = Sha512::new();
-    hasher.update(b"DLEQ_SECOND_BASE_V1");
-    let hash = hasher.finalize();
-
-    // Use hash as scalar seed
-    let mut scalar_bytes = [0u8; 32];
-    scalar_bytes.copy_from_slice(&hash[..32]);
-    let scalar = Scalar::from_bytes_mod_order(scalar_bytes);
-
-    // Compute Y = scalar·G
-    let Y_edwards = ED25519_BASEPOINT_POINT * scalar;
-
-    println!("Edwards Point Y:");
-    println!("  Compressed: {:?}", Y_edwards.compress().to_bytes());
-    println!("  X: {}", Y_edwards.compress().to_bytes()[31] & 0x80 != 0);
+    let y = hash_to_curve_second_generator();
+    let compressed = y.compress().to_bytes();
+    let low = u128::from_le_bytes(compressed[..16].try_into().expect("16 bytes"));
+    let high = u128::from_le_bytes(compressed[16..].try_into().expect("16 bytes"));
+    let (x_limbs, y_limbs) = to_cairo_limbs(&y);
 
-    // Note: This outputs Edwards coordinates
-    // For Cairo, we need Weierstrass coordinates via Python tool
-    println!("\nTo get Weierstrass coordinates for Cairo:");
-    println!("1. Use Python tool to convert Edwards -> Weierstrass");
-    println!("2. Split Weierstrass coordinates into u384 limbs (4×96-bit)");
-    println!("3. Hardcode the limbs in Cairo get_dleq_second_generator()");
+    println!(
+        "Second generator Y = hash_to_curve({:?})",
+        String::from_utf8_lossy(SECOND_GENERATOR_TAG)
+    );
+    println!(
+        "  DST:        {}",
+        String::from_utf8_lossy(SECOND_GENERATOR_DST)
+    );
+    println!("  Compressed: {}", hex::encode(compressed));
+    println!();
+    println!("// Cairo (lib.cairo)");
+    println!("const ED25519_SECOND_GENERATOR_COMPRESSED: u256 = u256 {{");
+    println!("    low: {:#x},", low);
+    println!("    high: {:#x},", high);
+    println!("}};");
+    println!();
+    println!("fn get_dleq_second_generator() -> G1Point {{");
+    println!("    G1Point {{");
+    println!(
+        "        x: u384 {{ limb0: {}, limb1: {}, limb2: {}, limb3: {} }},",
+        x_limbs[0], x_limbs[1], x_limbs[2], x_limbs[3]
+    );
+    println!(
+        "        y: u384 {{ limb0: {}, limb1: {}, limb2: {}, limb3: {} }},",
+        y_limbs[0], y_limbs[1], y_limbs[2], y_limbs[3]
+    );
+    println!("    }}");
+    println!("}}");
 }
@@ -3,29 +3,64 @@
 //! This command:
 //! 1. Generates a secret scalar `t`
 //! 2. Creates adaptor signature for Monero stagenet
-//! 3. Deploys AtomicLock contract on Starknet Sepolia
-//! 4. Waits for `t` to be revealed (via Unlocked event)
-//! 5. Finalizes Monero signature and broadcasts on stagenet
+//! 3. Builds the AtomicLock constructor calldata (DLEQ proof and hints)
+//! 4. Saves the swap state
+//! 5. Declares and deploys AtomicLock through the Universal Deployer
+//!    (`--starknet-account`, full-integration feature), showing the expected
+//!    fee first and aborting above `--max-fee`
+//!
+//! `maker resume --state swap_state.json` reloads an existing swap, checks the
+//! contract to find where it actually is, and continues from that step.
+//! `maker refund` recovers the deposit once the timelock expired unrevealed.
+//! `maker import-batch swaps.csv` creates one swap per spreadsheet row and
+//! writes a per-row status report.
 
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
 use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::EdwardsPoint;
 use curve25519_dalek::scalar::Scalar;
-use serde_json::json;
-use std::path::PathBuf;
-use xmr_secret_gen::adaptor::{create_adaptor_signature, split_monero_key};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use xmr_secret_gen::adaptor::{
+    create_adaptor_signature, finalize_signature, split_monero_key, AdaptorSignature,
+};
+use xmr_secret_gen::encoding::{decode_point, decode_scalar};
+use xmr_secret_gen::format::{AmountFormat, STRK, TOKEN};
+use xmr_secret_gen::monero_daemon::DaemonClient;
+use xmr_secret_gen::store::SwapStore;
+use xmr_secret_gen::swap::batch::{parse_batch, BatchReport, BatchRow, RowReport, RowStatus};
+use xmr_secret_gen::swap::liveness::{
+    read_heartbeat, LivenessConfig, LivenessMonitor, DEFAULT_LIVENESS_TIMEOUT_SECS,
+};
+use xmr_secret_gen::swap::orchestrator::SwapOrchestrator;
+use xmr_secret_gen::swap::reconcile::{reconcile, NextAction};
+use xmr_secret_gen::swap::state_file::{read_passphrase_file, read_state, write_state};
+use xmr_secret_gen::swap::{SwapPhase, SwapRole};
+#[cfg(feature = "full-integration")]
+use xmr_secret_gen::{
+    contracts::atomic_lock::AtomicLock,
+    format::Unit,
+    starknet_full::{Call, StarknetAccount},
+};
 use xmr_secret_gen::{
     generate_swap_secret,
-    starknet::StarknetClient,
+    starknet::{Finality, Network, StarknetClient},
     // monero::MoneroClient,  // Removed - using key splitting approach instead
 };
-#[cfg(feature = "full-integration")]
-use xmr_secret_gen::{monero_full::MoneroRpcClient, starknet_full::StarknetAccount};
+use zeroize::Zeroizing;
+
+/// Message signed by the Monero adaptor signature (demo transaction)
+const MONERO_TX_MESSAGE: &[u8] = b"Monero stagenet transaction for atomic swap";
 
 #[derive(Parser)]
 #[command(name = "maker")]
 #[command(about = "Maker (Alice) side of XMR↔Starknet atomic swap")]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Starknet RPC URL (default: Sepolia testnet)
     #[arg(
         long,
@@ -37,10 +72,49 @@ struct Args {
     #[arg(long)]
     starknet_account: Option<PathBuf>,
 
+    /// Network the Starknet RPC node must be on (sepolia or mainnet)
+    #[arg(long, global = true, default_value = "sepolia")]
+    network: Network,
+
+    /// Finality to wait for after submitting a transaction (l2 or l1)
+    #[arg(long, global = true, default_value = "l2")]
+    finality: Finality,
+
+    /// Give up waiting for a submitted transaction after this many seconds
+    #[arg(long, global = true)]
+    tx_timeout: Option<u64>,
+
+    /// Abort any transaction whose estimated fee exceeds this many STRK
+    #[arg(long, global = true, value_parser = parse_max_fee)]
+    max_fee: Option<u128>,
+
+    /// AtomicLock Sierra class, declared if the network lacks it
+    #[arg(
+        long,
+        default_value = "../cairo/target/dev/atomic_lock_AtomicLock.contract_class.json"
+    )]
+    contract_class: PathBuf,
+
+    /// AtomicLock CASM class (for the compiled class hash)
+    #[arg(
+        long,
+        default_value = "../cairo/target/dev/atomic_lock_AtomicLock.compiled_contract_class.json"
+    )]
+    compiled_class: PathBuf,
+
+    /// Declare with this compiled class hash instead of computing it (Poseidon)
+    /// from --compiled-class
+    #[arg(long)]
+    compiled_class_hash: Option<String>,
+
     /// Monero stagenet RPC URL
     #[arg(long, default_value = "http://stagenet.community.rino.io:38081")]
     monero_rpc: String,
 
+    /// Start swaps without checking that monerod at --monero-rpc is synced
+    #[arg(long)]
+    skip_daemon_check: bool,
+
     /// Lock duration in seconds (default: 1 hour)
     #[arg(long, default_value = "3600")]
     lock_duration: u64,
@@ -56,73 +130,374 @@ struct Args {
     /// Output file for swap state (JSON)
     #[arg(long, default_value = "swap_state.json")]
     output: PathBuf,
+
+    /// Encrypt the state file with the passphrase in this file
+    #[arg(long)]
+    passphrase_file: Option<PathBuf>,
+
+    /// Also record the swap in this SQLite swap store
+    #[arg(long)]
+    db: Option<PathBuf>,
+
+    /// Have the watchtower HTTP API at this URL watch each deployed contract
+    /// (bearer token from WATCHTOWER_API_TOKEN)
+    #[arg(long)]
+    watchtower_url: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Continue a swap from its state file instead of starting a new one
+    Resume {
+        /// Swap state file written by a previous `maker` run
+        #[arg(long, default_value = "swap_state.json")]
+        state: PathBuf,
+
+        /// Passphrase file for an encrypted state file
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+
+        /// Override the Starknet RPC URL stored in the state file
+        #[arg(long)]
+        starknet_rpc: Option<String>,
+
+        /// Record the address of a contract deployed manually
+        #[arg(long)]
+        contract_address: Option<String>,
+
+        /// Record that the tokens were deposited
+        #[arg(long)]
+        deposited: bool,
+
+        /// Seconds between contract polls while waiting for the reveal
+        #[arg(long, default_value = "30")]
+        poll_interval: u64,
+
+        /// Seconds of silence from the taker before suggesting the safe abort path
+        #[arg(long, default_value_t = DEFAULT_LIVENESS_TIMEOUT_SECS)]
+        liveness_timeout: u64,
+
+        /// File holding the taker's latest heartbeat (JSON), kept up to date
+        /// by the channel to the taker
+        #[arg(long)]
+        heartbeat_file: Option<PathBuf>,
+    },
+    /// Refund the deposit after the timelock expired without a reveal
+    Refund {
+        /// Swap state file written by a previous `maker` run
+        #[arg(long, default_value = "swap_state.json")]
+        state: PathBuf,
+
+        /// Passphrase file for an encrypted state file
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+
+        /// Override the Starknet RPC URL stored in the state file
+        #[arg(long)]
+        starknet_rpc: Option<String>,
+
+        /// Account JSON (`{"address", "private_key"}`) submitting the refund
+        #[arg(long)]
+        starknet_account: Option<PathBuf>,
+
+        /// Refund transaction already submitted by other means; only wait for it
+        #[arg(long)]
+        tx_hash: Option<String>,
+    },
+    /// Create one swap per row of a CSV file (amount, taker_contact, timelock),
+    /// deploying each contract if --starknet-account is given
+    ImportBatch {
+        /// CSV with an `amount,taker_contact,timelock` header
+        csv: PathBuf,
+
+        /// Directory receiving one state file per swap
+        #[arg(long, default_value = "batch")]
+        out_dir: PathBuf,
+
+        /// Status report (CSV), by default `<out-dir>/report.csv`
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    match args.command.take() {
+        Some(Command::Resume {
+            state,
+            passphrase_file,
+            starknet_rpc,
+            contract_address,
+            deposited,
+            poll_interval,
+            liveness_timeout,
+            heartbeat_file,
+        }) => {
+            let passphrase = passphrase_file
+                .map(read_passphrase_file)
+                .transpose()
+                .context("Failed to read passphrase file")?;
+            return resume(ResumeOptions {
+                state_path: state,
+                passphrase,
+                starknet_rpc,
+                contract_address,
+                deposited,
+                poll_interval: Duration::from_secs(poll_interval),
+                liveness_timeout,
+                heartbeat_file,
+                network: args.network,
+            })
+            .await;
+        }
+        Some(Command::Refund {
+            state,
+            passphrase_file,
+            starknet_rpc,
+            starknet_account,
+            tx_hash,
+        }) => {
+            let passphrase = passphrase_file
+                .map(read_passphrase_file)
+                .transpose()
+                .context("Failed to read passphrase file")?;
+            return refund(
+                &state,
+                passphrase.as_deref().map(String::as_str),
+                starknet_rpc,
+                starknet_account.as_deref(),
+                tx_hash,
+                TxWait::from_args(&args),
+                args.max_fee,
+            )
+            .await;
+        }
+        Some(Command::ImportBatch {
+            csv,
+            out_dir,
+            report,
+        }) => {
+            let report = report.unwrap_or_else(|| out_dir.join("report.csv"));
+            check_monero_daemon(&args).await?;
+            return import_batch(&args, &csv, &out_dir, &report).await;
+        }
+        None => {}
+    }
 
     println!("🔐 Maker (Alice) - Starting atomic swap setup...");
+    check_monero_daemon(&args).await?;
+    let amount = match args.amount.as_deref() {
+        Some(amount) => parse_amount(amount)?,
+        None => 0,
+    };
+    let NewSwap {
+        swap_id,
+        secret_hex,
+        hash_words,
+        adaptor_point,
+        lock_until,
+        constructor_calldata,
+        state: mut swap_state,
+    } = new_swap(&args, args.lock_duration, amount)?;
 
     // Step 1: Generate secret and swap data
     println!("\n📝 Step 1: Generating secret scalar `t`...");
-    let swap_secret = generate_swap_secret();
-    let secret_bytes: [u8; 32] = hex::decode(&swap_secret.secret_hex)
+    println!("   Secret: {}", secret_hex);
+    println!("   Hash: {:?}", hash_words);
+
+    // Step 2: Split Monero key and create adaptor signature
+    println!("\n🔑 Step 2: Creating Monero adaptor signature...");
+    println!(
+        "   Adaptor point: {:?}",
+        adaptor_point.compress().to_bytes()
+    );
+    println!("   Adaptor signature created (ready for Monero stagenet)");
+
+    // Step 3: Prepare contract deployment data
+    println!("\n📄 Step 3: Preparing Starknet contract deployment...");
+    println!(
+        "   Lock until: {} ({} seconds from now)",
+        lock_until, args.lock_duration
+    );
+    if amount > 0 {
+        println!(
+            "   Amount: {}",
+            AmountFormat::from_env().amount(amount, TOKEN)
+        );
+    }
+    println!("   Contract data prepared");
+
+    // Step 4: Save swap state
+    println!("\n💾 Step 4: Saving swap state...");
+    let passphrase = args
+        .passphrase_file
+        .as_ref()
+        .map(read_passphrase_file)
+        .transpose()
+        .context("Failed to read passphrase file")?;
+    write_state(
+        &args.output,
+        &swap_state,
+        passphrase.as_deref().map(String::as_str),
+    )
+    .context("Failed to write swap state file")?;
+
+    println!("   Swap state saved to: {}", args.output.display());
+    if passphrase.is_some() {
+        println!("   State file encrypted (ChaCha20-Poly1305, Argon2id)");
+    } else {
+        println!("   ⚠️  State file holds the secret in plaintext; use --passphrase-file");
+    }
+
+    if let Some(ref db) = args.db {
+        let store = SwapStore::open(db).context("Failed to open swap store")?;
+        let swap = SwapOrchestrator::new(swap_id.clone(), SwapRole::Maker);
+        store
+            .create(swap.state(), Some(&secret_hex))
+            .context("Failed to record swap")?;
+        println!("   Swap recorded in: {}", db.display());
+    }
+    println!("   Swap ID: {}", swap_id);
+
+    // Step 5: Deploy contract (if account provided)
+    let contract_address = if let Some(account_path) = args.starknet_account.as_deref() {
+        println!("\n🚀 Step 5: Deploying AtomicLock to Starknet...");
+        let (contract_address, deploy_tx_hash) =
+            deploy_lock(&args, account_path, constructor_calldata).await?;
+        println!("   ✅ Deployed at {}", contract_address);
+        register_with_watchtower(&args, &contract_address).await;
+
+        swap_state["contract_address"] = json!(contract_address);
+        swap_state["deploy_tx_hash"] = json!(deploy_tx_hash);
+        swap_state["phase"] = serde_json::to_value(SwapPhase::Deployed)?;
+        write_state(
+            &args.output,
+            &swap_state,
+            passphrase.as_deref().map(String::as_str),
+        )
+        .context("Failed to update swap state file")?;
+        Some(contract_address)
+    } else {
+        println!("\n📋 Step 5: Manual contract deployment required");
+        println!(
+            "   Constructor calldata saved in: {}",
+            args.output.display()
+        );
+        println!("   Deploy with scripts/deploy.sh, or pass --starknet-account");
+        println!("   (with --features full-integration) to deploy automatically");
+        None
+    };
+
+    // Step 6: Deposit, then wait for the reveal with `resume`
+    match contract_address {
+        Some(contract_address) => {
+            println!(
+                "\n💰 Step 6: Deposit the tokens into {}, then run:",
+                contract_address
+            );
+            println!(
+                "   maker resume --state {} --deposited",
+                args.output.display()
+            );
+        }
+        None => {
+            println!("\n⏭️  Step 6: After deployment, run:");
+            println!(
+                "   maker resume --state {} --contract-address <address>",
+                args.output.display()
+            );
+        }
+    }
+
+    println!("\n✅ Maker setup complete!");
+    println!("   Next steps:");
+    println!("   1. Share adaptor signature/terms and the DLEQ proof out-of-band with taker");
+    println!("   2. Wait for taker to call verify_and_unlock on Starknet");
+    println!("   3. Monitor for Unlocked event to detect secret reveal");
+    println!("   4. Finalize Monero signature and broadcast");
+
+    Ok(())
+}
+
+/// Secrets, adaptor signature, DLEQ proof and constructor calldata of a new
+/// swap, with the state file contents recording them.
+struct NewSwap {
+    swap_id: String,
+    secret_hex: String,
+    hash_words: [u32; 8],
+    adaptor_point: EdwardsPoint,
+    lock_until: u64,
+    constructor_calldata: Vec<String>,
+    state: Value,
+}
+
+/// Generate a swap locking `amount` tokens for `lock_duration` seconds.
+/// Refuse to start swaps against a monerod that is not synchronized: its
+/// stale height would make every confirmation count wrong.
+async fn check_monero_daemon(args: &Args) -> Result<()> {
+    if args.skip_daemon_check {
+        return Ok(());
+    }
+    let daemon = DaemonClient::new(args.monero_rpc.clone()).await?;
+    let info = daemon
+        .ensure_synced()
+        .await
+        .context("Refusing to start a swap (pass --skip-daemon-check to override)")?;
+    println!("   monerod synchronized at height {}", info.height);
+    Ok(())
+}
+
+fn new_swap(args: &Args, lock_duration: u64, amount: u128) -> Result<NewSwap> {
+    let swap_secret = generate_swap_secret()?;
+    let secret_bytes: [u8; 32] = hex::decode(swap_secret.expose_secret())
         .context("Failed to decode secret hex")?
         .try_into()
         .map_err(|_| anyhow::anyhow!("Invalid secret length"))?;
     let adaptor_scalar = Scalar::from_bytes_mod_order(secret_bytes);
 
-    println!("   Secret: {}", swap_secret.secret_hex);
-    println!("   Hash: {:?}", swap_secret.hash_u32_words);
-
-    // Step 2: Split Monero key and create adaptor signature
-    println!("\n🔑 Step 2: Creating Monero adaptor signature...");
     let full_monero_key = Scalar::from_bytes_mod_order([0x42u8; 32]); // Demo key
                                                                       // Note: In production, use the same adaptor_scalar from swap_secret
                                                                       // For demo, we'll use a different approach - split with the generated adaptor_scalar
     let base_key = full_monero_key - adaptor_scalar;
     let adaptor_point = &adaptor_scalar * &ED25519_BASEPOINT_POINT;
 
-    let message = b"Monero stagenet transaction for atomic swap";
-    let adaptor_sig = create_adaptor_signature(&base_key, &adaptor_point, message);
-
-    println!(
-        "   Adaptor point: {:?}",
-        adaptor_point.compress().to_bytes()
-    );
-    println!("   Adaptor signature created (ready for Monero stagenet)");
+    let adaptor_sig = create_adaptor_signature(&base_key, &adaptor_point, MONERO_TX_MESSAGE);
 
-    // Step 3: Prepare contract deployment data
-    println!("\n📄 Step 3: Preparing Starknet contract deployment...");
     let lock_until = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_secs()
-        + args.lock_duration;
+        + lock_duration;
+
+    let token = args.token_address.as_deref().unwrap_or("0x0");
+    let constructor_calldata = swap_secret
+        .constructor_calldata(lock_until, token, amount)
+        .context("Failed to generate constructor hints")?;
 
     let deployment_data = json!({
         "hash_words": swap_secret.hash_u32_words,
         "lock_until": lock_until,
-        "token": args.token_address.as_ref().map(|s| s.as_str()).unwrap_or("0x0"),
-        "amount": args.amount.as_ref().map(|s| s.as_str()).unwrap_or("0"),
-        "adaptor_point_x": swap_secret.adaptor_point_x_limbs,
-        "adaptor_point_y": swap_secret.adaptor_point_y_limbs,
-        "dleq": ["0x0", "0x0"], // Placeholder for now
-        "fake_glv_hint": swap_secret.fake_glv_hint,
+        "token": token,
+        "amount": amount.to_string(),
+        "constructor_calldata": constructor_calldata,
+        // Full proof for the taker: the calldata only carries 128-bit c and s
+        "dleq_proof": swap_secret.dleq_proof,
     });
 
-    println!(
-        "   Lock until: {} ({} seconds from now)",
-        lock_until, args.lock_duration
-    );
-    println!("   Contract data prepared");
-
-    // Step 4: Save swap state
-    println!("\n💾 Step 4: Saving swap state...");
-    let swap_state = json!({
+    // Swap ID: hex SHA-256 hashlock (public, unique per secret)
+    let swap_id: String = swap_secret
+        .hash_u32_words
+        .iter()
+        .map(|w| format!("{:08x}", w))
+        .collect();
+    let state = json!({
+        "swap_id": swap_id,
         "role": "maker",
-        "secret_hex": swap_secret.secret_hex,
+        "phase": "created",
+        "contract_address": null,
+        "secret_hex": swap_secret.expose_secret(),
         "adaptor_scalar_hex": hex::encode(adaptor_scalar.to_bytes()),
         "adaptor_point": hex::encode(adaptor_point.compress().to_bytes()),
         "adaptor_signature": {
@@ -135,89 +510,669 @@ async fn main() -> Result<()> {
         "lock_until": lock_until,
     });
 
-    std::fs::write(&args.output, serde_json::to_string_pretty(&swap_state)?)
-        .context("Failed to write swap state file")?;
+    Ok(NewSwap {
+        swap_id,
+        secret_hex: swap_secret.expose_secret().to_string(),
+        hash_words: swap_secret.hash_u32_words,
+        adaptor_point,
+        lock_until,
+        constructor_calldata,
+        state,
+    })
+}
 
-    println!("   Swap state saved to: {}", args.output.display());
+/// Create the swaps listed in `csv`, then write the status report.
+///
+/// A failing row is reported and the batch goes on; the command fails at
+/// the end if any row did.
+async fn import_batch(args: &Args, csv: &Path, out_dir: &Path, report_path: &Path) -> Result<()> {
+    let text = std::fs::read_to_string(csv)
+        .with_context(|| format!("Failed to read {}", csv.display()))?;
+    let rows =
+        parse_batch(&text).with_context(|| format!("Invalid batch file {}", csv.display()))?;
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let passphrase = args
+        .passphrase_file
+        .as_ref()
+        .map(read_passphrase_file)
+        .transpose()
+        .context("Failed to read passphrase file")?;
+    let store = args
+        .db
+        .as_ref()
+        .map(SwapStore::open)
+        .transpose()
+        .context("Failed to open swap store")?;
 
-    // Step 5: Deploy contract (if account provided)
-    let contract_address: Option<String> = if let Some(account_path) = args.starknet_account {
-        println!("\n🚀 Step 5: Deploying contract to Starknet Sepolia...");
-        println!("   Account: {}", account_path.display());
-        println!("   ⚠️  Contract deployment requires starknet-rs integration");
-        println!("   ⚠️  For now, use manual deployment:");
-        println!("     1. Use Starknet CLI: starknet deploy");
-        println!("     2. Use Starknet.js");
-        println!("     3. Or implement automatic deployment");
-        None
-    } else {
-        println!("\n📋 Step 5: Manual contract deployment required");
-        println!("   Deployment data saved in: {}", args.output.display());
-        println!("   Deploy using:");
-        println!("     - Starknet CLI");
-        println!("     - Starknet.js");
-        println!("     - Or provide --starknet-account for auto-deployment");
-        None
+    println!(
+        "📦 Maker (Alice) - Creating {} swaps from {}",
+        rows.len(),
+        csv.display()
+    );
+    let format = AmountFormat::from_env();
+    let mut report = BatchReport::default();
+    for row in rows {
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                println!("   ❌ {}", e);
+                report.push(RowReport {
+                    line: e.line().unwrap_or_default(),
+                    taker_contact: String::new(),
+                    swap_id: None,
+                    state_file: None,
+                    status: RowStatus::Failed(e.to_string()),
+                });
+                continue;
+            }
+        };
+        println!(
+            "\n   Line {}: {} for {}, locked {}s",
+            row.line,
+            format.amount(row.amount, TOKEN),
+            row.taker_contact,
+            row.lock_duration
+        );
+        let mut entry = RowReport {
+            line: row.line,
+            taker_contact: row.taker_contact.clone(),
+            swap_id: None,
+            state_file: None,
+            status: RowStatus::Created,
+        };
+        let passphrase = passphrase.as_deref().map(String::as_str);
+        match create_batch_swap(args, &row, out_dir, passphrase, store.as_ref(), &mut entry).await {
+            Ok(()) => println!("   ✅ Swap {}", entry.swap_id.as_deref().unwrap_or("?")),
+            Err(e) => {
+                println!("   ❌ {:#}", e);
+                entry.status = RowStatus::Failed(format!("{:#}", e));
+            }
+        }
+        report.push(entry);
+    }
+
+    std::fs::write(report_path, report.to_csv())
+        .with_context(|| format!("Failed to write {}", report_path.display()))?;
+    let failed = report.failed();
+    println!(
+        "\n📋 {} created, {} failed; report: {}",
+        report.rows.len() - failed,
+        failed,
+        report_path.display()
+    );
+    if failed > 0 {
+        bail!("{} of {} rows failed", failed, report.rows.len());
+    }
+    Ok(())
+}
+
+/// Create, save and (with an account) deploy the swap of one batch row,
+/// filling in `entry` as it goes.
+async fn create_batch_swap(
+    args: &Args,
+    row: &BatchRow,
+    out_dir: &Path,
+    passphrase: Option<&str>,
+    store: Option<&SwapStore>,
+    entry: &mut RowReport,
+) -> Result<()> {
+    let NewSwap {
+        swap_id,
+        secret_hex,
+        constructor_calldata,
+        mut state,
+        ..
+    } = new_swap(args, row.lock_duration, row.amount)?;
+    state["taker_contact"] = json!(row.taker_contact);
+    entry.swap_id = Some(swap_id.clone());
+
+    let path = out_dir.join(format!("{}.json", swap_id));
+    write_state(&path, &state, passphrase).context("Failed to write swap state file")?;
+    entry.state_file = Some(path.display().to_string());
+    if let Some(store) = store {
+        let swap = SwapOrchestrator::new(swap_id, SwapRole::Maker);
+        store
+            .create(swap.state(), Some(&secret_hex))
+            .context("Failed to record swap")?;
+    }
+
+    if let Some(account_path) = args.starknet_account.as_deref() {
+        let (contract_address, deploy_tx_hash) =
+            deploy_lock(args, account_path, constructor_calldata).await?;
+        state["contract_address"] = json!(contract_address);
+        state["deploy_tx_hash"] = json!(deploy_tx_hash);
+        state["phase"] = serde_json::to_value(SwapPhase::Deployed)?;
+        write_state(&path, &state, passphrase).context("Failed to update swap state file")?;
+        register_with_watchtower(args, &contract_address).await;
+        entry.status = RowStatus::Deployed(contract_address);
+    }
+    Ok(())
+}
+
+/// Add a deployed contract to the watchtower at --watchtower-url.
+///
+/// The contract is deployed either way, so a failure is only reported.
+async fn register_with_watchtower(args: &Args, contract_address: &str) {
+    let Some(url) = args.watchtower_url.as_deref() else {
+        return;
     };
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/contracts", url.trim_end_matches('/')))
+        .json(&json!({ "address": contract_address }));
+    if let Ok(token) = std::env::var("WATCHTOWER_API_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+    match request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(_) => println!("   👀 Watchtower at {} now watches the contract", url),
+        Err(e) => println!(
+            "   ⚠️  Watchtower registration failed ({}); add {} to WATCHED_CONTRACTS",
+            e, contract_address
+        ),
+    }
+}
 
-    // Step 6: Wait for unlock event (if contract deployed)
-    if let Some(contract_addr) = contract_address {
-        println!("\n👀 Step 6: Waiting for secret reveal (Unlocked event)...");
-
-        #[cfg(feature = "full-integration")]
-        {
-            if let Some(account_path) = args.starknet_account {
-                // Use full integration if account provided
-                let account = StarknetAccount::new(
-                    args.starknet_rpc.clone(),
-                    "0x0".to_string(), // Account address - should be loaded from file
-                    "0x0".to_string(), // Private key - should be loaded from file
-                );
+struct ResumeOptions {
+    state_path: PathBuf,
+    passphrase: Option<Zeroizing<String>>,
+    starknet_rpc: Option<String>,
+    contract_address: Option<String>,
+    deposited: bool,
+    poll_interval: Duration,
+    liveness_timeout: u64,
+    heartbeat_file: Option<PathBuf>,
+    network: Network,
+}
+
+/// Continue a saved swap from whatever step it reached.
+async fn resume(options: ResumeOptions) -> Result<()> {
+    let passphrase = options.passphrase.as_deref().map(String::as_str);
+    let mut state: Value = read_state(&options.state_path, passphrase).with_context(|| {
+        format!(
+            "Failed to read swap state: {}",
+            options.state_path.display()
+        )
+    })?;
+    if state["role"].as_str() != Some("maker") {
+        bail!("{} is not a maker swap", options.state_path.display());
+    }
+    println!(
+        "🔄 Maker (Alice) - Resuming swap {}",
+        state["swap_id"].as_str().unwrap_or("?")
+    );
+
+    let mut local_phase: SwapPhase = match state.get("phase") {
+        Some(phase) => serde_json::from_value(phase.clone()).context("Invalid phase")?,
+        None => SwapPhase::Created,
+    };
+
+    // Steps done by hand since the last run
+    let mut dirty = options.contract_address.is_some() || options.deposited;
+    if let Some(address) = options.contract_address {
+        state["contract_address"] = json!(address);
+        if local_phase == SwapPhase::Created {
+            local_phase = SwapPhase::Deployed;
+        }
+    }
+    if options.deposited {
+        if local_phase == SwapPhase::Created {
+            bail!("Cannot record a deposit before the contract address is known");
+        }
+        if local_phase == SwapPhase::Deployed {
+            local_phase = SwapPhase::Deposited;
+        }
+    }
+
+    let rpc_url = options
+        .starknet_rpc
+        .or_else(|| state["starknet_rpc"].as_str().map(str::to_string))
+        .context("No Starknet RPC URL in state; pass --starknet-rpc")?;
+    let client = StarknetClient::new(rpc_url).with_network(options.network);
+    client
+        .check_network()
+        .await
+        .context("Wrong Starknet RPC node (see --network)")?;
+    let mut waiting = false;
+
+    // Silence is counted from this run: earlier heartbeats are not on record
+    let mut liveness = LivenessMonitor::new(
+        state["swap_id"].as_str().unwrap_or_default().to_string(),
+        SwapRole::Maker,
+        LivenessConfig {
+            timeout_secs: options.liveness_timeout,
+            ..LivenessConfig::default()
+        },
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs(),
+    );
+    let mut liveness_warned = false;
 
-                println!("   Watching contract: {}", contract_addr);
-                let revealed_secret_hash = account
-                    .watch_unlocked_events(&contract_addr, 5)
+    loop {
+        let contract_address = state["contract_address"].as_str().map(str::to_string);
+        let chain_status = match contract_address {
+            Some(ref address) => Some(
+                client
+                    .get_lock_status(address)
                     .await
-                    .context("Failed to watch events")?;
+                    .context("Failed to read AtomicLock contract state")?,
+            ),
+            None => None,
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let reconciliation = reconcile(SwapRole::Maker, local_phase, chain_status.as_ref(), now);
+
+        local_phase = reconciliation.phase;
+        let phase = serde_json::to_value(local_phase)?;
+        if state["phase"] != phase {
+            println!("   Phase: {:?}", local_phase);
+            state["phase"] = phase;
+            dirty = true;
+        }
+        if dirty {
+            write_state(&options.state_path, &state, passphrase)
+                .context("Failed to update swap state file")?;
+            dirty = false;
+        }
 
-                println!("   ✅ Secret revealed! Hash: {}", revealed_secret_hash);
+        match reconciliation.next_action {
+            NextAction::DeployContract => {
+                println!("\n📄 Contract not deployed yet. Deployment data:");
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&state["deployment_data"])?
+                );
+                println!("\n   After deploying, run:");
+                println!(
+                    "   maker resume --state {} --contract-address <address>",
+                    options.state_path.display()
+                );
+                return Ok(());
+            }
+            NextAction::Deposit => {
+                println!(
+                    "\n💰 Contract {} deployed; deposit the tokens, then run:",
+                    contract_address.unwrap_or_default()
+                );
+                println!(
+                    "   maker resume --state {} --deposited",
+                    options.state_path.display()
+                );
+                return Ok(());
+            }
+            NextAction::WaitForReveal { lock_until } => {
+                if !waiting {
+                    println!("\n👀 Waiting for the taker to reveal the secret...");
+                    println!(
+                        "   Refund available at {} (in {}s)",
+                        lock_until,
+                        lock_until.saturating_sub(now)
+                    );
+                    waiting = true;
+                }
+                let heartbeat = options.heartbeat_file.as_deref().and_then(read_heartbeat);
+                if let Some(heartbeat) = heartbeat {
+                    if liveness.record(&heartbeat, now) {
+                        liveness_warned = false;
+                    }
+                }
+                if let Some(warning) = liveness.check(local_phase, now) {
+                    if !liveness_warned {
+                        println!(
+                            "\n⚠️  No heartbeat from the taker for {}s.",
+                            warning.silent_for_secs
+                        );
+                        println!("   {}", warning.abort_path.suggestion());
+                        liveness_warned = true;
+                    }
+                }
+                tokio::time::sleep(options.poll_interval).await;
+            }
+            NextAction::Refund => {
+                println!("\n⏰ Timelock expired without a reveal. Recover the deposit with:");
+                println!("   maker refund --state {}", options.state_path.display());
+                return Ok(());
+            }
+            NextAction::SweepMonero => {
+                println!("\n🔑 Secret revealed on Starknet: finalizing the Monero signature...");
+                let s_final = finalize_from_state(&state)?;
+                println!(
+                    "   Finalized signature: {}",
+                    hex::encode(s_final.to_bytes())
+                );
+                println!(
+                    "   Broadcast the sweep, then record it by setting phase to \"finalized\"."
+                );
+                return Ok(());
+            }
+            NextAction::Done => {
+                println!("\n✅ Swap complete ({:?}), nothing to do.", local_phase);
+                return Ok(());
+            }
+            NextAction::RevealSecret | NextAction::ClaimTokens { .. } => {
+                bail!("Unexpected taker action for a maker swap")
+            }
+        }
+    }
+}
 
-                // Step 7: Finalize and broadcast Monero transaction
-                println!("\n💰 Step 7: Finalizing Monero signature and broadcasting...");
-                let monero_client = MoneroRpcClient::new(args.monero_rpc.clone());
+/// Refund an expired, unrevealed swap and mark it refunded.
+async fn refund(
+    state_path: &Path,
+    passphrase: Option<&str>,
+    starknet_rpc: Option<String>,
+    starknet_account: Option<&Path>,
+    tx_hash: Option<String>,
+    wait: TxWait,
+    max_fee: Option<u128>,
+) -> Result<()> {
+    let mut state: Value = read_state(state_path, passphrase)
+        .with_context(|| format!("Failed to read swap state: {}", state_path.display()))?;
+    if state["role"].as_str() != Some("maker") {
+        bail!("{} is not a maker swap", state_path.display());
+    }
+    let contract_address = state["contract_address"]
+        .as_str()
+        .map(str::to_string)
+        .context("No contract address in state; nothing to refund")?;
+    let rpc_url = starknet_rpc
+        .or_else(|| state["starknet_rpc"].as_str().map(str::to_string))
+        .context("No Starknet RPC URL in state; pass --starknet-rpc")?;
+    let client = wait.client(rpc_url.clone());
 
-                // Finalize signature using revealed secret
-                use xmr_secret_gen::adaptor::finalize_signature;
-                let finalized_sig = finalize_signature(&adaptor_sig, &adaptor_scalar)
-                    .context("Failed to finalize signature")?;
+    println!(
+        "↩️  Maker (Alice) - Refunding contract {}",
+        contract_address
+    );
+    let status = client
+        .get_lock_status(&contract_address)
+        .await
+        .context("Failed to read AtomicLock contract state")?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
 
-                println!("   ✅ Signature finalized");
-                println!("   ⚠️  Transaction broadcasting requires full Monero wallet integration");
-                println!("   ⚠️  In production, use monero-rs to broadcast finalized transaction");
-            } else {
-                println!("   ⚠️  Full event watching requires --starknet-account");
-                println!("   ⚠️  For now, monitor manually or use Starknet explorer");
+    if status.secret_revealed {
+        bail!("Secret already revealed: sweep the XMR (`maker resume`) instead of refunding");
+    }
+    let tx_hash = if status.unlocked {
+        println!("   Contract already refunded");
+        tx_hash
+    } else {
+        if now < status.lock_until {
+            bail!(
+                "Timelock has not expired: refund available at {} (in {}s)",
+                status.lock_until,
+                status.lock_until - now
+            );
+        }
+        let tx_hash = match tx_hash {
+            Some(tx_hash) => tx_hash,
+            None => {
+                submit_refund(
+                    &rpc_url,
+                    starknet_account,
+                    &contract_address,
+                    max_fee,
+                    wait.network,
+                )
+                .await?
             }
+        };
+        println!("   Waiting for refund transaction {}...", tx_hash);
+        print_fee(&client.wait_for_transaction(&tx_hash, wait.finality).await?);
+
+        let status = client.get_lock_status(&contract_address).await?;
+        if !status.unlocked {
+            bail!(
+                "Refund transaction {} included but the contract is still locked",
+                tx_hash
+            );
         }
+        Some(tx_hash)
+    };
+
+    state["phase"] = serde_json::to_value(SwapPhase::Refunded)?;
+    if let Some(tx_hash) = tx_hash {
+        state["refund_tx_hash"] = json!(tx_hash);
+    }
+    write_state(state_path, &state, passphrase).context("Failed to update swap state file")?;
+    println!(
+        "   ✅ Refunded; state file marked refunded: {}",
+        state_path.display()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "full-integration")]
+async fn submit_refund(
+    rpc_url: &str,
+    starknet_account: Option<&Path>,
+    contract_address: &str,
+    max_fee: Option<u128>,
+    network: Network,
+) -> Result<String> {
+    let Some(account_path) = starknet_account else {
+        bail!(
+            "Submitting the refund requires --starknet-account.\n\
+             Or submit it manually, then pass the hash with --tx-hash:\n\
+             starknet invoke --address {} --function refund",
+            contract_address
+        );
+    };
+    let account = load_account(rpc_url, account_path, max_fee, network)?;
+    println!("   Submitting refund from {}...", account.address());
+    print_estimate(&account, AtomicLock::new(contract_address).refund().into()).await?;
+    account.refund(contract_address).await
+}
+
+#[cfg(not(feature = "full-integration"))]
+async fn submit_refund(
+    _rpc_url: &str,
+    _starknet_account: Option<&Path>,
+    contract_address: &str,
+    _max_fee: Option<u128>,
+    _network: Network,
+) -> Result<String> {
+    bail!(
+        "Submitting the refund requires the full-integration feature.\n\
+         Submit it manually, then pass the hash with --tx-hash:\n\
+         starknet invoke --address {} --function refund",
+        contract_address
+    )
+}
 
-        #[cfg(not(feature = "full-integration"))]
-        {
-            let starknet_client = StarknetClient::new(args.starknet_rpc.clone());
-            println!("   Watching contract: {}", contract_addr);
-            println!("   ⚠️  Event watching requires full-integration feature");
-            println!("   ⚠️  Build with: cargo build --features full-integration");
+/// Declare AtomicLock if needed and deploy it with the swap's constructor
+/// calldata; returns the contract address and deployment transaction.
+#[cfg(feature = "full-integration")]
+async fn deploy_lock(
+    args: &Args,
+    account_path: &Path,
+    constructor_calldata: Vec<String>,
+) -> Result<(String, String)> {
+    let read_class = |path: &Path| -> Result<Value> {
+        let json = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read {} (run `scarb build` in cairo/)",
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&json).with_context(|| format!("Invalid JSON in {}", path.display()))
+    };
+    let account = load_account(&args.starknet_rpc, account_path, args.max_fee, args.network)?;
+    let wait = TxWait::from_args(args);
+    let client = wait.client(args.starknet_rpc.clone());
+    println!("   Account: {}", account.address());
+
+    let sierra = read_class(&args.contract_class)?;
+    let declared = match args.compiled_class_hash.as_deref() {
+        Some(compiled_class_hash) => {
+            account
+                .declare_with_compiled_class_hash(&sierra, compiled_class_hash)
+                .await
         }
-    } else {
-        println!("\n⏭️  Steps 6-7: Waiting for contract deployment...");
-        println!("   After deployment, run maker again with --contract-address");
+        None => {
+            account
+                .declare(&sierra, &read_class(&args.compiled_class)?)
+                .await
+        }
+    }
+    .context(
+        "Declare failed (if the network expects another compiled class hash, \
+         pass it with --compiled-class-hash)",
+    )?;
+    match declared.transaction_hash {
+        Some(ref tx_hash) => {
+            println!(
+                "   Declaring class {} (tx {})...",
+                declared.class_hash, tx_hash
+            );
+            print_fee(&client.wait_for_transaction(tx_hash, wait.finality).await?);
+        }
+        None => println!("   Class {} already declared", declared.class_hash),
     }
 
-    println!("\n✅ Maker setup complete!");
-    println!("   Next steps:");
-    println!("   1. Share adaptor signature/terms out-of-band with taker");
-    println!("   2. Wait for taker to call verify_and_unlock on Starknet");
-    println!("   3. Monitor for Unlocked event to detect secret reveal");
-    println!("   4. Finalize Monero signature and broadcast");
+    let salt = format!("{:#x}", rand::random::<u128>());
+    let deploy = Call::deploy_via_udc(&declared.class_hash, constructor_calldata.clone(), &salt);
+    print_estimate(&account, deploy).await?;
+    let deployed = account
+        .deploy_via_udc(&declared.class_hash, constructor_calldata, &salt)
+        .await
+        .context("Deployment failed")?;
+    println!("   Deploying (tx {})...", deployed.transaction_hash);
+    print_fee(
+        &client
+            .wait_for_transaction(&deployed.transaction_hash, wait.finality)
+            .await?,
+    );
+    Ok((deployed.contract_address, deployed.transaction_hash))
+}
+
+#[cfg(not(feature = "full-integration"))]
+async fn deploy_lock(
+    _args: &Args,
+    _account_path: &Path,
+    _constructor_calldata: Vec<String>,
+) -> Result<(String, String)> {
+    bail!(
+        "Deploying the contract requires the full-integration feature.\n\
+         Deploy it manually, then record it with `maker resume --contract-address`"
+    )
+}
+
+/// How submitted transactions are awaited (`--finality`, `--tx-timeout`), and
+/// on which network (`--network`).
+#[derive(Clone, Copy)]
+struct TxWait {
+    finality: Finality,
+    timeout: Option<Duration>,
+    network: Network,
+}
+
+impl TxWait {
+    fn from_args(args: &Args) -> Self {
+        Self {
+            finality: args.finality,
+            timeout: args.tx_timeout.map(Duration::from_secs),
+            network: args.network,
+        }
+    }
+
+    fn client(&self, rpc_url: String) -> StarknetClient {
+        let client = StarknetClient::new(rpc_url).with_network(self.network);
+        match self.timeout {
+            Some(timeout) => client.with_transaction_timeout(timeout),
+            None => client,
+        }
+    }
+}
+
+/// Load the account submitting transactions, capped at `max_fee` (FRI) and
+/// bound to `network`.
+#[cfg(feature = "full-integration")]
+fn load_account(
+    rpc_url: &str,
+    account_path: &Path,
+    max_fee: Option<u128>,
+    network: Network,
+) -> Result<StarknetAccount> {
+    let account = StarknetAccount::from_account_file(rpc_url.to_string(), account_path)?
+        .with_network(network);
+    Ok(match max_fee {
+        Some(max_fee) => account.with_max_fee(max_fee),
+        None => account,
+    })
+}
 
+/// Print the expected fee of `call`; fails if it exceeds `--max-fee`.
+#[cfg(feature = "full-integration")]
+async fn print_estimate(account: &StarknetAccount, call: Call) -> Result<()> {
+    let estimate = account
+        .estimate_fee(&[call])
+        .await
+        .context("Fee estimation failed")?;
+    let unit = Unit::from_fee_unit(&estimate.unit).unwrap_or(STRK);
+    println!(
+        "   Expected fee: {}",
+        AmountFormat::from_env().amount(estimate.overall_fee, unit)
+    );
+    account.check_fee(&estimate)?;
     Ok(())
 }
+
+/// `--max-fee` in STRK, as FRI.
+fn parse_max_fee(max_fee: &str) -> Result<u128, String> {
+    STRK.parse(max_fee)
+        .ok_or_else(|| format!("Invalid STRK amount: {}", max_fee))
+}
+
+/// Print the fee paid by an included transaction.
+fn print_fee(receipt: &Value) {
+    if let Some(fee) = AmountFormat::from_env().receipt_fee(receipt) {
+        println!("   Fee paid: {}", fee);
+    }
+}
+
+/// Token amount as a decimal or `0x` hex integer (at most 128 bits).
+fn parse_amount(amount: &str) -> Result<u128> {
+    match amount.strip_prefix("0x") {
+        Some(hex_str) => u128::from_str_radix(hex_str, 16),
+        None => amount.parse(),
+    }
+    .with_context(|| format!("Invalid amount: {}", amount))
+}
+
+/// Rebuild the adaptor signature saved by the first run and complete it with `t`.
+fn finalize_from_state(state: &Value) -> Result<Scalar> {
+    let bytes32 = |value: &Value, name: &str| -> Result<[u8; 32]> {
+        let hex_str = value
+            .as_str()
+            .with_context(|| format!("Missing {} in state", name))?;
+        hex::decode(hex_str)
+            .with_context(|| format!("Invalid {} hex", name))?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("{} must be 32 bytes", name))
+    };
+
+    let adaptor_scalar =
+        decode_scalar(bytes32(&state["adaptor_scalar_hex"], "adaptor_scalar_hex")?)?;
+    let adaptor_sig = AdaptorSignature {
+        adaptor_point: decode_point(bytes32(&state["adaptor_point"], "adaptor_point")?)?,
+        partial_sig: decode_scalar(bytes32(
+            &state["adaptor_signature"]["partial_sig"],
+            "partial_sig",
+        )?)?,
+        nonce_commitment: decode_point(bytes32(
+            &state["adaptor_signature"]["nonce_commitment"],
+            "nonce_commitment",
+        )?)?,
+    };
+    let (s_final, _) = finalize_signature(&adaptor_sig, &adaptor_scalar, MONERO_TX_MESSAGE);
+    Ok(s_final)
+}
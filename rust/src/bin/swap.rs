@@ -0,0 +1,223 @@
+//! Swap operations CLI.
+//!
+//! `swap resume <swap_id>` reloads a saved swap, inspects the AtomicLock
+//! contract on Starknet, determines the actual phase (even if the local state
+//! file is stale), saves the reconciled phase and prints the next step,
+//! recommending a refund when the timelock has expired.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+use xmr_secret_gen::monero_daemon::DaemonClient;
+use xmr_secret_gen::monero_wallet::{lock_tx_restore_height, DEFAULT_RESTORE_BUFFER};
+use xmr_secret_gen::starknet::{Network, StarknetClient};
+use xmr_secret_gen::swap::reconcile::{reconcile, NextAction};
+use xmr_secret_gen::swap::state_file::{read_passphrase_file, read_state, write_state};
+use xmr_secret_gen::swap::{SwapPhase, SwapRole};
+
+#[derive(Parser)]
+#[command(name = "swap")]
+#[command(about = "Inspect and resume XMR↔Starknet atomic swaps")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Resume a swap from its saved state
+    Resume {
+        /// Swap identifier (hex SHA-256 hashlock, as written by `maker`)
+        swap_id: String,
+
+        /// Swap state file written by `maker`
+        #[arg(long, default_value = "swap_state.json")]
+        state: PathBuf,
+
+        /// Override the Starknet RPC URL stored in the state file
+        #[arg(long)]
+        starknet_rpc: Option<String>,
+
+        /// Network the Starknet RPC node must be on (sepolia or mainnet)
+        #[arg(long, default_value = "sepolia")]
+        network: Network,
+
+        /// Passphrase file for an encrypted state file
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Resume {
+            swap_id,
+            state,
+            starknet_rpc,
+            network,
+            passphrase_file,
+        } => {
+            let passphrase = passphrase_file
+                .map(read_passphrase_file)
+                .transpose()
+                .context("Failed to read passphrase file")?;
+            let passphrase = passphrase.as_deref().map(String::as_str);
+            resume(&swap_id, &state, starknet_rpc, network, passphrase).await
+        }
+    }
+}
+
+async fn resume(
+    swap_id: &str,
+    state_path: &Path,
+    starknet_rpc: Option<String>,
+    network: Network,
+    passphrase: Option<&str>,
+) -> Result<()> {
+    println!("🔄 Resuming swap {}...", swap_id);
+
+    let mut state: Value = read_state(state_path, passphrase)
+        .with_context(|| format!("Failed to read swap state: {}", state_path.display()))?;
+
+    let stored_id = state
+        .get("swap_id")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if !stored_id.eq_ignore_ascii_case(swap_id.trim_start_matches("0x")) {
+        anyhow::bail!(
+            "Swap {} not found in {} (file contains swap {:?})",
+            swap_id,
+            state_path.display(),
+            stored_id
+        );
+    }
+
+    let role: SwapRole = serde_json::from_value(state["role"].clone())
+        .context("Missing or invalid role in swap state")?;
+    let local_phase: SwapPhase = match state.get("phase") {
+        Some(phase) => serde_json::from_value(phase.clone()).context("Invalid phase")?,
+        None => SwapPhase::Created,
+    };
+    let contract_address = state
+        .get("contract_address")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    println!("   Role: {:?}", role);
+    println!("   Local phase: {:?}", local_phase);
+
+    // Inspect Starknet (the contract is the source of truth)
+    let chain_status = if let Some(ref address) = contract_address {
+        let rpc_url = starknet_rpc
+            .or_else(|| state["starknet_rpc"].as_str().map(str::to_string))
+            .context("No Starknet RPC URL in state; pass --starknet-rpc")?;
+        let client = StarknetClient::new(rpc_url).with_network(network);
+        let status = client
+            .get_lock_status(address)
+            .await
+            .context("Failed to read AtomicLock contract state")?;
+        println!("\n📡 Starknet contract {}", address);
+        println!("   Secret revealed: {}", status.secret_revealed);
+        println!("   Unlocked: {}", status.unlocked);
+        println!("   Lock until: {}", status.lock_until);
+        if status.claimable_after != 0 {
+            println!("   Claimable after: {}", status.claimable_after);
+        }
+        Some(status)
+    } else {
+        println!("\n📡 No contract address recorded; skipping Starknet inspection");
+        None
+    };
+
+    // Monero side: the lock TX is recorded by the operator
+    if let Some(txid) = state
+        .get("monero_lock_txid")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    {
+        println!("\n🪙 Monero lock TX: {}", txid);
+        println!("   Check confirmations with monero-wallet-rpc get_transfer_by_txid");
+
+        // Restore height for recovering the swap wallet elsewhere
+        let stored = state.get("monero_restore_height").and_then(Value::as_u64);
+        let daemon_url = state["monero_rpc"].as_str().map(str::to_string);
+        let restore_height = match (stored, daemon_url) {
+            (Some(height), _) => Some(height),
+            (None, Some(daemon_url)) => {
+                let lookup = async {
+                    let daemon = DaemonClient::new(daemon_url).await?;
+                    lock_tx_restore_height(&daemon, &txid, DEFAULT_RESTORE_BUFFER).await
+                };
+                match lookup.await {
+                    Ok(height) => {
+                        state["monero_restore_height"] = json!(height);
+                        write_state(state_path, &state, passphrase)
+                            .context("Failed to update swap state file")?;
+                        Some(height)
+                    }
+                    Err(e) => {
+                        println!("   ⚠️  Could not determine restore height: {:#}", e);
+                        None
+                    }
+                }
+            }
+            (None, None) => None,
+        };
+        if let Some(height) = restore_height {
+            println!("   Wallet restore height: {}", height);
+        }
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let reconciliation = reconcile(role, local_phase, chain_status.as_ref(), now);
+
+    println!("\n🧭 Actual phase: {:?}", reconciliation.phase);
+    if reconciliation.local_was_stale {
+        println!("   ⚠️  Local state was stale ({:?})", local_phase);
+        state["phase"] = serde_json::to_value(reconciliation.phase)?;
+        write_state(state_path, &state, passphrase).context("Failed to update swap state file")?;
+        println!("   State file updated: {}", state_path.display());
+    }
+
+    println!("\n➡️  Next step:");
+    match reconciliation.next_action {
+        NextAction::DeployContract => {
+            println!("   Deploy the AtomicLock contract with the saved deployment_data,");
+            println!("   then record contract_address in the state file.");
+        }
+        NextAction::Deposit => println!("   Call deposit() on the contract."),
+        NextAction::WaitForReveal { lock_until } => {
+            println!("   Wait for the taker to reveal the secret.");
+            println!(
+                "   Refund becomes available at {} (in {}s).",
+                lock_until,
+                lock_until.saturating_sub(now)
+            );
+        }
+        NextAction::Refund => {
+            println!("   ⏰ Timelock expired without a reveal: run `maker refund`.");
+        }
+        NextAction::RevealSecret => println!("   Reveal the secret with reveal_secret(secret)."),
+        NextAction::SweepMonero => {
+            println!("   🔑 Secret is public: extract t, recover x = x_partial + t");
+            println!("   and sweep the locked XMR now.");
+        }
+        NextAction::ClaimTokens { claimable_after } => {
+            println!(
+                "   Call claim_tokens() after {} (in {}s).",
+                claimable_after,
+                claimable_after.saturating_sub(now)
+            );
+        }
+        NextAction::Done => println!("   ✅ Swap complete, nothing to do."),
+    }
+
+    Ok(())
+}
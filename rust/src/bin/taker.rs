@@ -2,21 +2,40 @@
 //!
 //! This command:
 //! 1. Watches for AtomicLock contracts on Starknet Sepolia
-//! 2. When conditions are met, calls verify_and_unlock(secret)
+//! 2. When conditions are met, calls verify_and_unlock(secret), showing the
+//!    expected fee first and aborting above `--max-fee`
 //! 3. Reveals the secret `t` via the Unlocked event
 //! 4. Maker can then finalize Monero signature
+//!
+//! Refusals carry an `AbortReason`, so the error output names a stable code
+//! (`swap aborted: dleq_invalid`, ...).
 
 use anyhow::{Context, Result};
-use clap::Parser;
-use serde_json::json;
-use xmr_secret_gen::starknet::StarknetClient;
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use xmr_secret_gen::contracts::atomic_lock::ConstructorArgs;
+use xmr_secret_gen::dleq::DleqProof;
+use xmr_secret_gen::format::{AmountFormat, STRK, TOKEN};
+use xmr_secret_gen::monero_daemon::DaemonClient;
+use xmr_secret_gen::starknet::{Finality, Network, StarknetClient};
+use xmr_secret_gen::store::SwapStore;
+use xmr_secret_gen::swap::abort::AbortReason;
+use xmr_secret_gen::swap::risk::{RiskConfig, RiskDecision, RiskEngine};
+use xmr_secret_gen::swap::state_file::{read_passphrase_file, read_state};
 #[cfg(feature = "full-integration")]
-use xmr_secret_gen::starknet_full::StarknetAccount;
+use xmr_secret_gen::{
+    contracts::atomic_lock::AtomicLock, format::Unit, starknet_full::StarknetAccount,
+};
 
 #[derive(Parser)]
 #[command(name = "taker")]
 #[command(about = "Taker (Bob) side of XMR↔Starknet atomic swap")]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Starknet RPC URL (default: Sepolia testnet)
     #[arg(
         long,
@@ -28,6 +47,22 @@ struct Args {
     #[arg(long)]
     starknet_account: Option<String>,
 
+    /// Network the Starknet RPC node must be on (sepolia or mainnet)
+    #[arg(long, global = true, default_value = "sepolia")]
+    network: Network,
+
+    /// Finality to wait for after submitting a transaction (l2 or l1)
+    #[arg(long, default_value = "l2")]
+    finality: Finality,
+
+    /// Give up waiting for a submitted transaction after this many seconds
+    #[arg(long)]
+    tx_timeout: Option<u64>,
+
+    /// Abort the unlock if its estimated fee exceeds this many STRK
+    #[arg(long, value_parser = parse_max_fee)]
+    max_fee: Option<u128>,
+
     /// Contract address to watch/unlock
     #[arg(long)]
     contract_address: Option<String>,
@@ -36,18 +71,133 @@ struct Args {
     #[arg(long)]
     secret: Option<String>,
 
+    /// Read the secret from this swap state file instead of --secret
+    #[arg(long)]
+    state: Option<PathBuf>,
+
+    /// Passphrase file for an encrypted state file
+    #[arg(long)]
+    passphrase_file: Option<PathBuf>,
+
+    /// Swap store holding counterparty history (enables the risk check)
+    #[arg(long)]
+    db: Option<PathBuf>,
+
+    /// Maker identifier to check against the swap store
+    #[arg(long, requires = "db")]
+    counterparty: Option<String>,
+
+    /// Offered XMR amount in piconero, checked against the maker's limit
+    #[arg(long, requires = "counterparty")]
+    amount_piconero: Option<u64>,
+
+    /// Risk engine settings (JSON, see `RiskConfig`)
+    #[arg(long)]
+    risk_config: Option<PathBuf>,
+
     /// Watch mode: continuously monitor for new contracts
     #[arg(long)]
     watch: bool,
+
+    /// monerod the taker watches the Monero side with; the swap is refused
+    /// if it is not synchronized
+    #[arg(long, global = true)]
+    monero_rpc: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check a deployed contract's DLEQ proof before depositing
+    Verify {
+        /// AtomicLock contract address
+        #[arg(long)]
+        contract_address: String,
+
+        /// Transaction that deployed the contract through the Universal Deployer
+        #[arg(long)]
+        deploy_tx: String,
+
+        /// Maker's full DLEQ proof (`DleqProof::to_json`), required when the
+        /// calldata only carries 128-bit challenge/response
+        #[arg(long)]
+        proof: Option<PathBuf>,
+
+        /// Expected SHA-256 hashlock (hex) from the maker's offer
+        #[arg(long)]
+        hashlock: Option<String>,
+
+        /// Expected adaptor point T (compressed hex) from the maker's offer
+        #[arg(long)]
+        adaptor_point: Option<String>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    if let Some(Command::Verify {
+        contract_address,
+        deploy_tx,
+        proof,
+        hashlock,
+        adaptor_point,
+    }) = &args.command
+    {
+        check_monero_daemon(args.monero_rpc.as_deref()).await?;
+        let client = StarknetClient::new(args.starknet_rpc.clone()).with_network(args.network);
+        return verify_contract(
+            &client,
+            contract_address,
+            deploy_tx,
+            proof.as_deref(),
+            hashlock.as_deref(),
+            adaptor_point.as_deref(),
+        )
+        .await;
+    }
+
     println!("🔓 Taker (Bob) - Starting atomic swap participation...");
+    check_monero_daemon(args.monero_rpc.as_deref()).await?;
+
+    let mut starknet_client =
+        StarknetClient::new(args.starknet_rpc.clone()).with_network(args.network);
+    starknet_client
+        .check_network()
+        .await
+        .context("Wrong Starknet RPC node (see --network)")
+        .context(AbortReason::NodeUnavailable)?;
+    if let Some(timeout) = args.tx_timeout {
+        starknet_client = starknet_client.with_transaction_timeout(Duration::from_secs(timeout));
+    }
 
-    let starknet_client = StarknetClient::new(args.starknet_rpc.clone());
+    if let (Some(db), Some(counterparty)) = (&args.db, &args.counterparty) {
+        check_counterparty(
+            db,
+            counterparty,
+            args.amount_piconero,
+            args.risk_config.as_deref(),
+        )?;
+    }
+
+    let secret = match (args.secret.clone(), &args.state) {
+        (Some(secret), _) => Some(secret),
+        (None, Some(state_path)) => {
+            let passphrase = args
+                .passphrase_file
+                .as_ref()
+                .map(read_passphrase_file)
+                .transpose()
+                .context("Failed to read passphrase file")?;
+            let state: Value = read_state(state_path, passphrase.as_deref().map(String::as_str))
+                .with_context(|| format!("Failed to read swap state: {}", state_path.display()))?;
+            let secret = state["secret_hex"]
+                .as_str()
+                .context("State file has no secret_hex")?;
+            Some(secret.to_string())
+        }
+        (None, None) => None,
+    };
 
     if args.watch {
         println!("\n👀 Watch mode: Monitoring for AtomicLock contracts...");
@@ -57,7 +207,7 @@ async fn main() -> Result<()> {
     } else if let Some(contract_addr) = args.contract_address {
         println!("\n🔓 Unlocking contract: {}", contract_addr);
 
-        if let Some(secret_hex) = args.secret {
+        if let Some(secret_hex) = secret {
             println!("   Secret provided: {}", secret_hex);
 
             // Convert secret to ByteArray format for Cairo
@@ -66,24 +216,44 @@ async fn main() -> Result<()> {
             #[cfg(feature = "full-integration")]
             {
                 if let Some(account_path) = args.starknet_account {
-                    // Use full integration if account provided
-                    let account = StarknetAccount::new(
+                    let mut account = StarknetAccount::from_account_file(
                         args.starknet_rpc.clone(),
-                        "0x0".to_string(), // Account address - should be loaded from file
-                        "0x0".to_string(), // Private key - should be loaded from file
+                        &account_path,
+                    )?
+                    .with_network(args.network);
+                    if let Some(max_fee) = args.max_fee {
+                        account = account.with_max_fee(max_fee);
+                    }
+
+                    let unlock = AtomicLock::new(&contract_addr).verify_and_unlock(&secret_bytes);
+                    let estimate = account
+                        .estimate_fee(&[unlock.into()])
+                        .await
+                        .context("Fee estimation failed")?;
+                    let unit = Unit::from_fee_unit(&estimate.unit).unwrap_or(STRK);
+                    println!(
+                        "   Expected fee: {}",
+                        AmountFormat::from_env().amount(estimate.overall_fee, unit)
                     );
+                    account
+                        .check_fee(&estimate)
+                        .context(AbortReason::FeeExceeded)?;
 
-                    println!("   Calling verify_and_unlock...");
+                    println!("   Calling verify_and_unlock from {}...", account.address());
                     let tx_hash = account
                         .verify_and_unlock(&contract_addr, &secret_bytes)
                         .await
                         .context("Failed to call contract")?;
 
                     println!("   ✅ Transaction submitted! Hash: {}", tx_hash);
-                    println!("   Waiting for confirmation...");
-
-                    // In production, wait for transaction receipt
-                    println!("   ⚠️  Transaction confirmation requires full implementation");
+                    println!("   Waiting for {}...", args.finality);
+                    let receipt = starknet_client
+                        .wait_for_transaction(&tx_hash, args.finality)
+                        .await?;
+                    if let Some(fee) = AmountFormat::from_env().receipt_fee(&receipt) {
+                        println!("   Fee paid: {}", fee);
+                    }
+                    println!("   ✅ Contract unlocked");
                 } else {
                     println!("   ⚠️  Full contract interaction requires --starknet-account");
                     println!("\n   Manual unlock command:");
@@ -106,7 +276,7 @@ async fn main() -> Result<()> {
             }
         } else {
             println!("   ⚠️  Secret required for unlock");
-            println!("   ⚠️  Provide --secret <hex>");
+            println!("   ⚠️  Provide --secret <hex> or --state <file>");
             println!("   ⚠️  Secret should be 32 bytes (64 hex chars)");
         }
     } else {
@@ -124,3 +294,175 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Refuse to accept a swap if the given monerod is not synchronized.
+async fn check_monero_daemon(monero_rpc: Option<&str>) -> Result<()> {
+    let Some(url) = monero_rpc else {
+        return Ok(());
+    };
+    let daemon = DaemonClient::new(url.to_string())
+        .await
+        .context(AbortReason::NodeUnavailable)?;
+    let info = daemon
+        .ensure_synced()
+        .await
+        .context("Refusing to accept the swap")
+        .context(AbortReason::NodeUnavailable)?;
+    println!("   monerod synchronized at height {}", info.height);
+    Ok(())
+}
+
+/// Verify the DLEQ proof a contract was deployed with, off-chain.
+///
+/// The constructor does not fully check the proof (see `swap::verify`), so
+/// the taker must not deposit until this passes.
+async fn verify_contract(
+    client: &StarknetClient,
+    contract_address: &str,
+    deploy_tx: &str,
+    proof_path: Option<&Path>,
+    expected_hashlock: Option<&str>,
+    expected_adaptor_point: Option<&str>,
+) -> Result<()> {
+    println!("\n🔍 Verifying contract {}", contract_address);
+
+    let calldata = client
+        .get_deploy_calldata(contract_address, deploy_tx)
+        .await?;
+    let args = ConstructorArgs::decode(&calldata)?;
+
+    // The constructor stores these; a mismatch means the wrong deployment
+    let status = client.get_lock_status(contract_address).await?;
+    if status.lock_until != args.lock_until {
+        return Err(anyhow::anyhow!(
+            "Deployment calldata lock_until {} does not match the contract ({})",
+            args.lock_until,
+            status.lock_until
+        )
+        .context(AbortReason::TermsMismatch));
+    }
+    if status.secret_revealed || status.unlocked {
+        return Err(
+            anyhow::anyhow!("Contract is already revealed or unlocked; do not deposit")
+                .context(AbortReason::TermsMismatch),
+        );
+    }
+
+    let full_proof = match proof_path {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read DLEQ proof: {}", path.display()))?;
+            Some(
+                DleqProof::from_json(&json)
+                    .map_err(|e| anyhow::anyhow!("Invalid DLEQ proof: {}", e))
+                    .context(AbortReason::DleqInvalid)?,
+            )
+        }
+        None => None,
+    };
+    let adaptor_point = args
+        .verify(full_proof.as_ref())
+        .context("DLEQ verification failed; do not deposit")
+        .context(AbortReason::DleqInvalid)?;
+
+    let hashlock_hex = hex::encode(args.hashlock());
+    let adaptor_hex = hex::encode(adaptor_point.compress().to_bytes());
+    if let Some(expected) = expected_hashlock {
+        if !expected
+            .trim_start_matches("0x")
+            .eq_ignore_ascii_case(&hashlock_hex)
+        {
+            return Err(anyhow::anyhow!(
+                "Contract hashlock {} is not the agreed {}",
+                hashlock_hex,
+                expected
+            )
+            .context(AbortReason::TermsMismatch));
+        }
+    }
+    if let Some(expected) = expected_adaptor_point {
+        if !expected
+            .trim_start_matches("0x")
+            .eq_ignore_ascii_case(&adaptor_hex)
+        {
+            return Err(anyhow::anyhow!(
+                "Contract adaptor point {} is not the agreed {}",
+                adaptor_hex,
+                expected
+            )
+            .context(AbortReason::TermsMismatch));
+        }
+    }
+
+    println!("   ✅ DLEQ proof valid: adaptor point is bound to the hashlock");
+    println!("   Hashlock:      {}", hashlock_hex);
+    println!("   Adaptor point: {}", adaptor_hex);
+    println!("   Lock until:    {}", args.lock_until);
+    println!("   Token:         {}", args.token);
+    println!(
+        "   Amount:        {}",
+        AmountFormat::from_env().u256(args.amount_low, args.amount_high, TOKEN)
+    );
+    Ok(())
+}
+
+/// Refuse to proceed with a maker the risk engine rejects.
+fn check_counterparty(
+    db: &Path,
+    counterparty: &str,
+    amount_piconero: Option<u64>,
+    risk_config: Option<&Path>,
+) -> Result<()> {
+    let config = match risk_config {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read risk config: {}", path.display()))?;
+            serde_json::from_str(&content).context("Invalid risk config")?
+        }
+        None => RiskConfig::default(),
+    };
+    let engine = RiskEngine::new(config);
+
+    let store = SwapStore::open(db).context("Failed to open swap store")?;
+    let history = store
+        .counterparty_history(counterparty)
+        .context("Failed to read counterparty history")?;
+
+    println!("\n🛡️  Counterparty {}", counterparty);
+    println!(
+        "   History: {} completed, {} aborted, {} timed out",
+        history.completed, history.aborted, history.timed_out
+    );
+    let format = AmountFormat::from_env();
+    let decision = engine.evaluate(&history, amount_piconero.unwrap_or(0));
+    println!("   Risk score: {:.2}", decision.score());
+    match decision {
+        RiskDecision::Accept { .. } => {
+            if let Some(limit) = engine.limit(&history) {
+                println!(
+                    "   New counterparty: swaps limited to {}",
+                    format.piconero(limit)
+                );
+            }
+            Ok(())
+        }
+        RiskDecision::ExceedsLimit { max_piconero, .. } => Err(anyhow::anyhow!(
+            "Offer of {} exceeds the {} limit for this counterparty",
+            format.piconero(amount_piconero.unwrap_or(0)),
+            format.piconero(max_piconero)
+        )
+        .context(AbortReason::RiskRejected)),
+        RiskDecision::Reject { score } => Err(anyhow::anyhow!(
+            "Counterparty rejected: risk score {:.2} >= {:.2}",
+            score,
+            engine.config().reject_score
+        )
+        .context(AbortReason::RiskRejected)),
+    }
+}
+
+/// `--max-fee` in STRK, as FRI.
+fn parse_max_fee(max_fee: &str) -> Result<u128, String> {
+    STRK.parse(max_fee)
+        .ok_or_else(|| format!("Invalid STRK amount: {}", max_fee))
+}
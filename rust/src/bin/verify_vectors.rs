@@ -0,0 +1,76 @@
+//! Check the protocol test vectors in `vectors/` against this crate.
+//!
+//! Independent implementations run the same vectors; this binary keeps the
+//! shipped files and the reference implementation in sync. `--regenerate`
+//! rewrites the directory from `vectors::default_inputs()`.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use xmr_secret_gen::vectors::{default_inputs, generate, verify, ProtocolVector, VECTORS_VERSION};
+
+#[derive(Parser)]
+#[command(name = "verify-vectors")]
+#[command(about = "Verify (or regenerate) the protocol test vectors")]
+struct Args {
+    /// Vector directory (defaults to ../vectors/v<version>)
+    dir: Option<PathBuf>,
+
+    /// Rewrite the vectors from this implementation instead of checking them
+    #[arg(long)]
+    regenerate: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let dir = args
+        .dir
+        .unwrap_or_else(|| PathBuf::from(format!("../vectors/v{}", VECTORS_VERSION)));
+
+    if args.regenerate {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        for (name, inputs) in default_inputs() {
+            let vector = generate(name, &inputs).with_context(|| format!("Vector {}", name))?;
+            let path = dir.join(format!("{}.json", name));
+            std::fs::write(&path, serde_json::to_string_pretty(&vector)? + "\n")
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("✅ Wrote {}", path.display());
+        }
+        return Ok(());
+    }
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+    if paths.is_empty() {
+        bail!("No vectors in {}", dir.display());
+    }
+
+    let mut failed = 0;
+    for path in &paths {
+        let result = std::fs::read_to_string(path)
+            .context("Failed to read vector")
+            .and_then(|json| {
+                serde_json::from_str::<ProtocolVector>(&json).context("Invalid vector JSON")
+            })
+            .and_then(|vector| verify(&vector).map_err(Into::into));
+        match result {
+            Ok(()) => println!("✅ {}", path.display()),
+            Err(e) => {
+                failed += 1;
+                println!("❌ {}: {:#}", path.display(), e);
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} vectors failed", failed, paths.len());
+    }
+    println!("All {} vectors match", paths.len());
+    Ok(())
+}
@@ -0,0 +1,195 @@
+//! Cairo Serde encoding of values passed to and read from contracts.
+//!
+//! Felts are `0x`-prefixed hex strings, as in JSON-RPC calldata.
+//!
+//! A `ByteArray` serializes as
+//! `[data.len(), data (bytes31 words)..., pending_word, pending_word_len]`:
+//! full 31-byte big-endian words, then the remaining 0 to 30 bytes in
+//! `pending_word`. A length that is a multiple of 31 has an empty pending
+//! word (`0x0`, length 0), never a full one.
+
+use thiserror::Error;
+
+/// Bytes in a `bytes31` word of a `ByteArray`.
+pub const BYTES_IN_WORD: usize = 31;
+
+/// Errors decoding Cairo Serde calldata.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CairoSerdeError {
+    #[error("calldata ends early")]
+    Truncated,
+
+    #[error("invalid felt {0:?}")]
+    InvalidFelt(String),
+
+    #[error("ByteArray word {word} exceeds {max} bytes")]
+    WordTooLong { word: String, max: usize },
+
+    #[error("ByteArray pending word length {0} must be below 31")]
+    PendingLength(u64),
+}
+
+/// Serialize bytes as a Cairo `ByteArray`.
+pub fn byte_array_to_calldata(bytes: &[u8]) -> Vec<String> {
+    let mut words = bytes.chunks_exact(BYTES_IN_WORD);
+    let mut felts = vec![format!("{:#x}", bytes.len() / BYTES_IN_WORD)];
+    felts.extend(
+        words
+            .by_ref()
+            .map(|word| format!("0x{}", hex::encode(word))),
+    );
+    let pending = words.remainder();
+    if pending.is_empty() {
+        felts.push("0x0".to_string());
+    } else {
+        felts.push(format!("0x{}", hex::encode(pending)));
+    }
+    felts.push(format!("{:#x}", pending.len()));
+    felts
+}
+
+/// Decode a Cairo `ByteArray` at the start of `felts` (inverse of
+/// [`byte_array_to_calldata`]).
+///
+/// Returns the bytes and the number of felts consumed.
+pub fn byte_array_from_calldata(felts: &[String]) -> Result<(Vec<u8>, usize), CairoSerdeError> {
+    let words = felt_to_u64(felts.first().ok_or(CairoSerdeError::Truncated)?)?;
+    let words = usize::try_from(words).map_err(|_| CairoSerdeError::Truncated)?;
+    let end = words.checked_add(3).ok_or(CairoSerdeError::Truncated)?;
+    let data = felts.get(1..end).ok_or(CairoSerdeError::Truncated)?;
+    let (data, tail) = data.split_at(words);
+    let pending_len = felt_to_u64(&tail[1])?;
+    if pending_len >= BYTES_IN_WORD as u64 {
+        return Err(CairoSerdeError::PendingLength(pending_len));
+    }
+
+    let mut bytes = Vec::with_capacity(words * BYTES_IN_WORD + pending_len as usize);
+    for word in data {
+        bytes.extend(felt_to_bytes(word, BYTES_IN_WORD)?);
+    }
+    bytes.extend(felt_to_bytes(&tail[0], pending_len as usize)?);
+    Ok((bytes, end))
+}
+
+/// The `len` low-order bytes of a hex felt, big-endian; fails if any
+/// higher byte is set.
+fn felt_to_bytes(felt: &str, len: usize) -> Result<Vec<u8>, CairoSerdeError> {
+    let invalid = || CairoSerdeError::InvalidFelt(felt.to_string());
+    let digits = felt.strip_prefix("0x").ok_or_else(invalid)?;
+    if digits.len() > 64 {
+        return Err(invalid());
+    }
+    let bytes = hex::decode(format!("{:0>64}", digits)).map_err(|_| invalid())?;
+    if bytes[..32 - len].iter().any(|&b| b != 0) {
+        return Err(CairoSerdeError::WordTooLong {
+            word: felt.to_string(),
+            max: len,
+        });
+    }
+    Ok(bytes[32 - len..].to_vec())
+}
+
+/// A hex or decimal felt as u64.
+pub fn felt_to_u64(felt: &str) -> Result<u64, CairoSerdeError> {
+    match felt.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => felt.parse(),
+    }
+    .map_err(|_| CairoSerdeError::InvalidFelt(felt.to_string()))
+}
+
+/// A hex or decimal felt as u128 (the halves of a `u256`).
+pub fn felt_to_u128(felt: &str) -> Result<u128, CairoSerdeError> {
+    match felt.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16),
+        None => felt.parse(),
+    }
+    .map_err(|_| CairoSerdeError::InvalidFelt(felt.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn felts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_byte_array_layout() {
+        // Short string: no full word, everything pending
+        assert_eq!(
+            byte_array_to_calldata(b"hello"),
+            felts(&["0x0", "0x68656c6c6f", "0x5"])
+        );
+        assert_eq!(byte_array_to_calldata(&[]), felts(&["0x0", "0x0", "0x0"]));
+        // Exactly one word: the pending word stays empty
+        let word = [0xab; 31];
+        assert_eq!(
+            byte_array_to_calldata(&word),
+            vec![
+                "0x1".to_string(),
+                format!("0x{}", hex::encode(word)),
+                "0x0".into(),
+                "0x0".into()
+            ]
+        );
+        // A 32-byte secret: one word and a 1-byte pending word
+        let secret: Vec<u8> = (1..=32).collect();
+        assert_eq!(
+            byte_array_to_calldata(&secret),
+            vec![
+                "0x1".to_string(),
+                format!("0x{}", hex::encode(&secret[..31])),
+                "0x20".into(),
+                "0x1".into()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_byte_array_round_trip() {
+        for len in 0..=100 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 7 + 1) as u8).collect();
+            let mut calldata = byte_array_to_calldata(&bytes);
+            assert_eq!(calldata.len(), 3 + len / BYTES_IN_WORD);
+            // Decoding stops at the end of the ByteArray
+            calldata.push("0x99".into());
+            assert_eq!(
+                byte_array_from_calldata(&calldata).unwrap(),
+                (bytes, calldata.len() - 1),
+                "length {}",
+                len
+            );
+        }
+        // Leading zero bytes are kept by the pending length
+        let (bytes, _) = byte_array_from_calldata(&felts(&["0x0", "0x1", "0x3"])).unwrap();
+        assert_eq!(bytes, [0, 0, 1]);
+    }
+
+    #[test]
+    fn test_byte_array_rejects_malformed_calldata() {
+        let decode = |values: &[&str]| byte_array_from_calldata(&felts(values));
+        assert_eq!(decode(&[]), Err(CairoSerdeError::Truncated));
+        assert_eq!(
+            decode(&["0x2", "0x1", "0x0"]),
+            Err(CairoSerdeError::Truncated)
+        );
+        assert_eq!(
+            decode(&["0x0", "0x0", "0x1f"]),
+            Err(CairoSerdeError::PendingLength(31))
+        );
+        assert!(matches!(
+            decode(&["0x0", "0x1234", "0x1"]),
+            Err(CairoSerdeError::WordTooLong { max: 1, .. })
+        ));
+        assert!(matches!(
+            decode(&["0x0", "xyz", "0x1"]),
+            Err(CairoSerdeError::InvalidFelt(_))
+        ));
+        assert!(matches!(
+            decode(&["0xffffffffffffffffff", "0x0", "0x0"]),
+            Err(CairoSerdeError::InvalidFelt(_))
+        ));
+    }
+}
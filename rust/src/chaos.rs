@@ -0,0 +1,234 @@
+//! Fault injection for the RPC clients (`chaos` feature).
+//!
+//! A [`Chaos`] layer attached to [`StarknetClient`](crate::starknet::StarknetClient),
+//! `StarknetAccount` or [`DaemonClient`](crate::monero_daemon::DaemonClient)
+//! sits between the HTTP transport and response parsing and, per call:
+//!
+//! - delays the request (random latency),
+//! - drops the response after the request was sent (the node may have acted
+//!   on it, as with a real timeout),
+//! - truncates the JSON body,
+//! - answers a read with the previous response to the same request (a node
+//!   lagging behind: stale heights and contract state).
+//!
+//! Faults are drawn from a seeded RNG so a failing run can be replayed.
+//! `tests/chaos.rs` drives swaps against a simulated node under each fault
+//! class and checks they still end in a safe terminal phase.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use thiserror::Error;
+
+/// Kinds of injected faults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fault {
+    Latency,
+    DroppedResponse,
+    CorruptedJson,
+    StaleRead,
+}
+
+/// Error returned in place of a dropped response.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("chaos: response to {method} dropped")]
+pub struct DroppedResponse {
+    pub method: String,
+}
+
+/// Probability of each fault per call (0.0 to 1.0).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    pub latency_rate: f64,
+    pub max_latency: Duration,
+    pub drop_rate: f64,
+    pub corrupt_rate: f64,
+    pub stale_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for ChaosConfig {
+    /// No faults.
+    fn default() -> Self {
+        Self {
+            latency_rate: 0.0,
+            max_latency: Duration::ZERO,
+            drop_rate: 0.0,
+            corrupt_rate: 0.0,
+            stale_rate: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+impl ChaosConfig {
+    pub fn with_latency(mut self, rate: f64, max_latency: Duration) -> Self {
+        self.latency_rate = rate;
+        self.max_latency = max_latency;
+        self
+    }
+
+    pub fn with_dropped_responses(mut self, rate: f64) -> Self {
+        self.drop_rate = rate;
+        self
+    }
+
+    pub fn with_corrupted_json(mut self, rate: f64) -> Self {
+        self.corrupt_rate = rate;
+        self
+    }
+
+    pub fn with_stale_reads(mut self, rate: f64) -> Self {
+        self.stale_rate = rate;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// Fault injector shared by the clients of one test run.
+#[derive(Debug)]
+pub struct Chaos {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+    /// Last genuine response per read request, served when a read goes stale
+    previous: Mutex<HashMap<String, String>>,
+    injected: Mutex<HashMap<Fault, u64>>,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(config.seed)),
+            config,
+            previous: Mutex::new(HashMap::new()),
+            injected: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of `fault`s injected so far.
+    pub fn injected(&self, fault: Fault) -> u64 {
+        self.injected
+            .lock()
+            .unwrap()
+            .get(&fault)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Before sending a request: maybe wait.
+    pub async fn before_request(&self) {
+        if !self.roll(self.config.latency_rate) {
+            return;
+        }
+        self.record(Fault::Latency);
+        let max = self.config.max_latency.as_millis() as u64;
+        let delay = Duration::from_millis(self.rng.lock().unwrap().gen_range(0..=max));
+        tokio::time::sleep(delay).await;
+    }
+
+    /// After receiving the body of a `method` call with `params` (serialized):
+    /// maybe drop it, corrupt it or replace it with a stale one.
+    pub fn after_response(
+        &self,
+        method: &str,
+        params: &str,
+        body: String,
+    ) -> Result<String, DroppedResponse> {
+        if self.roll(self.config.drop_rate) {
+            self.record(Fault::DroppedResponse);
+            return Err(DroppedResponse {
+                method: method.to_string(),
+            });
+        }
+        if self.roll(self.config.corrupt_rate) && !body.is_empty() {
+            self.record(Fault::CorruptedJson);
+            let cut = self.rng.lock().unwrap().gen_range(0..body.len());
+            let mut body = body.into_bytes();
+            body.truncate(cut);
+            return Ok(String::from_utf8_lossy(&body).into_owned());
+        }
+        if !is_read(method) {
+            return Ok(body);
+        }
+        let request = format!("{} {}", method, params);
+        let mut previous = self.previous.lock().unwrap();
+        if self.roll(self.config.stale_rate) {
+            if let Some(stale) = previous.get(&request) {
+                self.record(Fault::StaleRead);
+                return Ok(stale.clone());
+            }
+        }
+        previous.insert(request, body.clone());
+        Ok(body)
+    }
+
+    fn roll(&self, rate: f64) -> bool {
+        rate > 0.0 && self.rng.lock().unwrap().gen_bool(rate.min(1.0))
+    }
+
+    fn record(&self, fault: Fault) {
+        *self.injected.lock().unwrap().entry(fault).or_default() += 1;
+    }
+}
+
+/// Methods that only read chain state (safe to answer from an older view).
+fn is_read(method: &str) -> bool {
+    method == "starknet_call"
+        || method == "starknet_blockNumber"
+        || method.starts_with("starknet_get")
+        || method.starts_with("get_")
+        || method == "is_key_image_spent"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_reads_replay_previous_response() {
+        let chaos = Chaos::new(ChaosConfig::default().with_stale_reads(1.0));
+        let read = |body: &str| chaos.after_response("starknet_blockNumber", "k", body.into());
+        // Nothing to replay on the first read
+        assert_eq!(read("\"0x1\"").unwrap(), "\"0x1\"");
+        assert_eq!(read("\"0x2\"").unwrap(), "\"0x1\"");
+        assert_eq!(chaos.injected(Fault::StaleRead), 1);
+
+        // Writes are never stale
+        let write = chaos.after_response("starknet_addInvokeTransaction", "w", "a".into());
+        assert_eq!(write.unwrap(), "a");
+    }
+
+    #[test]
+    fn test_drops_and_corruption() {
+        let chaos = Chaos::new(ChaosConfig::default().with_dropped_responses(1.0));
+        assert!(chaos.after_response("get_info", "k", "{}".into()).is_err());
+
+        let chaos = Chaos::new(ChaosConfig::default().with_corrupted_json(1.0).with_seed(7));
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#;
+        let corrupted = chaos
+            .after_response("starknet_call", "k", body.into())
+            .unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&corrupted).is_err());
+        assert_eq!(chaos.injected(Fault::CorruptedJson), 1);
+    }
+
+    #[test]
+    fn test_no_faults_by_default() {
+        let chaos = Chaos::new(ChaosConfig::default());
+        for _ in 0..100 {
+            assert_eq!(
+                chaos
+                    .after_response("starknet_call", "k", "x".into())
+                    .unwrap(),
+                "x"
+            );
+        }
+    }
+}
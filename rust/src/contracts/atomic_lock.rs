@@ -0,0 +1,670 @@
+//! Typed bindings for the AtomicLock contract (`cairo/src/lib.cairo`).
+//!
+//! Mirrors the `IAtomicLock` interface and the contract's `Event` enum, so
+//! callers build calls and read events by name instead of assembling felt
+//! vectors by hand:
+//!
+//! - [`constructor_calldata`]: deployment arguments (decoded again by
+//!   [`ConstructorArgs::decode`])
+//! - [`AtomicLock`]: one deployed lock; its methods return [`ContractCall`]s
+//!   for an account to sign (`starknet_full::Call` converts from them)
+//! - [`AtomicLockEvent`]: the contract's events, decoded from RPC `keys` and
+//!   `data`
+//!
+//! `tests::test_bindings_match_contract_abi` checks the names against the Cairo
+//! source, so renaming an entrypoint or event there fails here.
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::cairo_serde::{byte_array_to_calldata, felt_to_u128, felt_to_u64, CairoSerdeError};
+use crate::starknet::{felt_eq, get_selector_from_name};
+
+pub use crate::swap::verify::{
+    encode_constructor_calldata as constructor_calldata, ConstructorArgs, CONSTRUCTOR_CALLDATA_LEN,
+};
+
+/// `reveal_secret(secret: ByteArray)`: start the grace period.
+pub const REVEAL_SECRET: &str = "reveal_secret";
+/// `claim_tokens()`: collect the tokens once the grace period is over.
+pub const CLAIM_TOKENS: &str = "claim_tokens";
+/// `verify_and_unlock(secret: ByteArray)`: legacy one-step unlock.
+pub const VERIFY_AND_UNLOCK: &str = "verify_and_unlock";
+/// `refund()`: return the tokens to the depositor after `lock_until`.
+pub const REFUND: &str = "refund";
+/// `deposit()`: pull `amount` of `token` from the depositor.
+pub const DEPOSIT: &str = "deposit";
+
+/// `get_target_hash() -> Span<u32>`
+pub const GET_TARGET_HASH: &str = "get_target_hash";
+/// `is_unlocked() -> bool`
+pub const IS_UNLOCKED: &str = "is_unlocked";
+/// `get_lock_until() -> u64`
+pub const GET_LOCK_UNTIL: &str = "get_lock_until";
+/// `is_secret_revealed() -> bool`
+pub const IS_SECRET_REVEALED: &str = "is_secret_revealed";
+/// `get_claimable_after() -> u64`
+pub const GET_CLAIMABLE_AFTER: &str = "get_claimable_after";
+
+/// One entrypoint invocation, before signing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractCall {
+    pub to: String,
+    pub entrypoint: &'static str,
+    pub calldata: Vec<String>,
+}
+
+impl ContractCall {
+    /// Entry point selector (`sn_keccak(entrypoint)`).
+    pub fn selector(&self) -> String {
+        get_selector_from_name(self.entrypoint)
+    }
+}
+
+/// A deployed AtomicLock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomicLock {
+    address: String,
+}
+
+impl AtomicLock {
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// `deposit()`. The contract pulls the tokens with `transfer_from`, so
+    /// the depositor must [`approve`](Self::approve) the lock first (in the
+    /// same multicall is fine).
+    pub fn deposit(&self) -> ContractCall {
+        self.call(DEPOSIT, Vec::new())
+    }
+
+    /// ERC-20 `approve(spender: lock, amount: u256)` on `token`.
+    pub fn approve(&self, token: &str, amount: u128) -> ContractCall {
+        ContractCall {
+            to: token.to_string(),
+            entrypoint: "approve",
+            calldata: vec![
+                self.address.clone(),
+                format!("{:#x}", amount),
+                "0x0".to_string(),
+            ],
+        }
+    }
+
+    /// `reveal_secret(secret: ByteArray)`.
+    pub fn reveal_secret(&self, secret: &[u8]) -> ContractCall {
+        self.call(REVEAL_SECRET, byte_array_to_calldata(secret))
+    }
+
+    /// `verify_and_unlock(secret: ByteArray)`.
+    pub fn verify_and_unlock(&self, secret: &[u8]) -> ContractCall {
+        self.call(VERIFY_AND_UNLOCK, byte_array_to_calldata(secret))
+    }
+
+    /// `claim_tokens()`.
+    pub fn claim(&self) -> ContractCall {
+        self.call(CLAIM_TOKENS, Vec::new())
+    }
+
+    /// `refund()`.
+    pub fn refund(&self) -> ContractCall {
+        self.call(REFUND, Vec::new())
+    }
+
+    fn call(&self, entrypoint: &'static str, calldata: Vec<String>) -> ContractCall {
+        ContractCall {
+            to: self.address.clone(),
+            entrypoint,
+            calldata,
+        }
+    }
+}
+
+/// Errors decoding an AtomicLock event.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EventError {
+    #[error("{event} event: {source}")]
+    Decode {
+        event: &'static str,
+        source: CairoSerdeError,
+    },
+
+    #[error("{event} event: {extra} unexpected felts")]
+    Trailing { event: &'static str, extra: usize },
+
+    #[error("event has no selector key")]
+    NoSelector,
+}
+
+/// An event of the AtomicLock contract, with its `#[key]` fields first.
+pub trait LockEvent: Sized {
+    /// Variant name in the contract's `Event` enum.
+    const NAME: &'static str;
+
+    /// Decode from the event keys after the selector, and the data.
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError>;
+
+    /// Event selector (`keys[0]`).
+    fn selector() -> String {
+        get_selector_from_name(Self::NAME)
+    }
+}
+
+/// `ContractDeployed`: emitted by the constructor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractDeployed {
+    pub deployer: String,
+    /// Short string felt
+    pub version: String,
+    pub lock_until: u64,
+}
+
+/// `Deposited`: the depositor funded the lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deposited {
+    pub depositor: String,
+    pub amount_low: u128,
+    pub amount_high: u128,
+}
+
+/// `Unlocked`: tokens released by `verify_and_unlock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unlocked {
+    pub unlocker: String,
+    /// First hashlock word
+    pub secret_hash: u32,
+}
+
+/// `Refunded`: tokens returned to the depositor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refunded {
+    pub depositor: String,
+    pub amount_low: u128,
+    pub amount_high: u128,
+}
+
+/// `SecretRevealed`: the secret is public, claiming opens at `claimable_after`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRevealed {
+    pub revealer: String,
+    /// First hashlock word
+    pub secret_hash: u32,
+    pub claimable_after: u64,
+}
+
+/// `TokensClaimed`: tokens collected after the grace period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokensClaimed {
+    pub claimer: String,
+    pub amount_low: u128,
+    pub amount_high: u128,
+    pub reveal_timestamp: u64,
+    pub claim_timestamp: u64,
+}
+
+/// `DleqVerified`: the constructor accepted the DLEQ proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DleqVerified {
+    /// Adaptor point coordinates as 4 limbs each
+    pub adaptor_point_x: [String; 4],
+    pub adaptor_point_y: [String; 4],
+    pub challenge: String,
+}
+
+/// `DleqVerificationFailed`: the constructor rejected the DLEQ proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DleqVerificationFailed {
+    pub adaptor_point_x: [String; 4],
+    pub adaptor_point_y: [String; 4],
+    /// Short string error code, e.g. `'challenge_mismatch'`
+    pub reason: String,
+}
+
+impl LockEvent for ContractDeployed {
+    const NAME: &'static str = "ContractDeployed";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let event = Self {
+            deployer: keys.felt()?,
+            version: data.felt()?,
+            lock_until: data.u64()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl LockEvent for Deposited {
+    const NAME: &'static str = "Deposited";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let depositor = keys.felt()?;
+        let (amount_low, amount_high) = data.u256()?;
+        keys.finish()?;
+        data.finish()?;
+        Ok(Self {
+            depositor,
+            amount_low,
+            amount_high,
+        })
+    }
+}
+
+impl LockEvent for Unlocked {
+    const NAME: &'static str = "Unlocked";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let event = Self {
+            unlocker: keys.felt()?,
+            secret_hash: data.u32()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl LockEvent for Refunded {
+    const NAME: &'static str = "Refunded";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let depositor = keys.felt()?;
+        let (amount_low, amount_high) = data.u256()?;
+        keys.finish()?;
+        data.finish()?;
+        Ok(Self {
+            depositor,
+            amount_low,
+            amount_high,
+        })
+    }
+}
+
+impl LockEvent for SecretRevealed {
+    const NAME: &'static str = "SecretRevealed";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let event = Self {
+            revealer: keys.felt()?,
+            secret_hash: data.u32()?,
+            claimable_after: data.u64()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl LockEvent for TokensClaimed {
+    const NAME: &'static str = "TokensClaimed";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let claimer = keys.felt()?;
+        let (amount_low, amount_high) = data.u256()?;
+        let event = Self {
+            claimer,
+            amount_low,
+            amount_high,
+            reveal_timestamp: data.u64()?,
+            claim_timestamp: data.u64()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl LockEvent for DleqVerified {
+    const NAME: &'static str = "DleqVerified";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let event = Self {
+            adaptor_point_x: keys.limbs()?,
+            adaptor_point_y: keys.limbs()?,
+            challenge: data.felt()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl LockEvent for DleqVerificationFailed {
+    const NAME: &'static str = "DleqVerificationFailed";
+
+    fn decode(keys: &[String], data: &[String]) -> Result<Self, EventError> {
+        let (mut keys, mut data) = (Felts::new(Self::NAME, keys), Felts::new(Self::NAME, data));
+        let event = Self {
+            adaptor_point_x: keys.limbs()?,
+            adaptor_point_y: keys.limbs()?,
+            reason: data.felt()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+/// Any event the AtomicLock contract emits itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AtomicLockEvent {
+    ContractDeployed(ContractDeployed),
+    Deposited(Deposited),
+    Unlocked(Unlocked),
+    Refunded(Refunded),
+    SecretRevealed(SecretRevealed),
+    TokensClaimed(TokensClaimed),
+    DleqVerified(DleqVerified),
+    DleqVerificationFailed(DleqVerificationFailed),
+}
+
+impl AtomicLockEvent {
+    /// Decode an event from its full `keys` (selector first) and `data`.
+    ///
+    /// Returns `None` for events this contract does not declare (e.g. the
+    /// flattened ReentrancyGuard events).
+    pub fn decode(keys: &[String], data: &[String]) -> Result<Option<Self>, EventError> {
+        let (selector, keys) = keys.split_first().ok_or(EventError::NoSelector)?;
+        let is = |name: &str| felt_eq(selector, &get_selector_from_name(name));
+        let event = if is(ContractDeployed::NAME) {
+            Self::ContractDeployed(ContractDeployed::decode(keys, data)?)
+        } else if is(Deposited::NAME) {
+            Self::Deposited(Deposited::decode(keys, data)?)
+        } else if is(Unlocked::NAME) {
+            Self::Unlocked(Unlocked::decode(keys, data)?)
+        } else if is(Refunded::NAME) {
+            Self::Refunded(Refunded::decode(keys, data)?)
+        } else if is(SecretRevealed::NAME) {
+            Self::SecretRevealed(SecretRevealed::decode(keys, data)?)
+        } else if is(TokensClaimed::NAME) {
+            Self::TokensClaimed(TokensClaimed::decode(keys, data)?)
+        } else if is(DleqVerified::NAME) {
+            Self::DleqVerified(DleqVerified::decode(keys, data)?)
+        } else if is(DleqVerificationFailed::NAME) {
+            Self::DleqVerificationFailed(DleqVerificationFailed::decode(keys, data)?)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(event))
+    }
+
+    /// Decode an event object from `starknet_getEvents` or a receipt.
+    pub fn from_rpc(event: &Value) -> Result<Option<Self>, EventError> {
+        let felts = |field: &str| -> Result<Vec<String>, EventError> {
+            event[field]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|v| {
+                    v.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| EventError::Decode {
+                            event: "RPC",
+                            source: CairoSerdeError::InvalidFelt(v.to_string()),
+                        })
+                })
+                .collect()
+        };
+        Self::decode(&felts("keys")?, &felts("data")?)
+    }
+}
+
+/// Cursor over the keys or data of one event.
+struct Felts<'a> {
+    event: &'static str,
+    felts: &'a [String],
+}
+
+impl<'a> Felts<'a> {
+    fn new(event: &'static str, felts: &'a [String]) -> Self {
+        Self { event, felts }
+    }
+
+    fn error(&self, source: CairoSerdeError) -> EventError {
+        EventError::Decode {
+            event: self.event,
+            source,
+        }
+    }
+
+    fn felt(&mut self) -> Result<String, EventError> {
+        let (first, rest) = self
+            .felts
+            .split_first()
+            .ok_or_else(|| self.error(CairoSerdeError::Truncated))?;
+        self.felts = rest;
+        Ok(first.clone())
+    }
+
+    fn u64(&mut self) -> Result<u64, EventError> {
+        let felt = self.felt()?;
+        felt_to_u64(&felt).map_err(|e| self.error(e))
+    }
+
+    fn u32(&mut self) -> Result<u32, EventError> {
+        let felt = self.felt()?;
+        felt_to_u64(&felt)
+            .ok()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| self.error(CairoSerdeError::InvalidFelt(felt)))
+    }
+
+    /// `u256` as `(low, high)`.
+    fn u256(&mut self) -> Result<(u128, u128), EventError> {
+        let low = self.felt()?;
+        let high = self.felt()?;
+        let parse = |felt: &str| felt_to_u128(felt).map_err(|e| self.error(e));
+        Ok((parse(&low)?, parse(&high)?))
+    }
+
+    fn limbs(&mut self) -> Result<[String; 4], EventError> {
+        Ok([self.felt()?, self.felt()?, self.felt()?, self.felt()?])
+    }
+
+    fn finish(self) -> Result<(), EventError> {
+        if self.felts.is_empty() {
+            Ok(())
+        } else {
+            Err(EventError::Trailing {
+                event: self.event,
+                extra: self.felts.len(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn felts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn keys<E: LockEvent>(rest: &[&str]) -> Vec<String> {
+        let mut keys = vec![E::selector()];
+        keys.extend(felts(rest));
+        keys
+    }
+
+    #[test]
+    fn test_bindings_match_contract_abi() {
+        let source = include_str!("../../../cairo/src/lib.cairo");
+        let interface = &source[source.find("trait IAtomicLock").unwrap()..];
+        let interface = &interface[..interface.find('}').unwrap()];
+        for entrypoint in [
+            REVEAL_SECRET,
+            CLAIM_TOKENS,
+            VERIFY_AND_UNLOCK,
+            REFUND,
+            DEPOSIT,
+            GET_TARGET_HASH,
+            IS_UNLOCKED,
+            GET_LOCK_UNTIL,
+            IS_SECRET_REVEALED,
+            GET_CLAIMABLE_AFTER,
+        ] {
+            assert!(
+                interface.contains(&format!("fn {}(", entrypoint)),
+                "IAtomicLock has no {}",
+                entrypoint
+            );
+        }
+        for event in [
+            ContractDeployed::NAME,
+            Deposited::NAME,
+            Unlocked::NAME,
+            Refunded::NAME,
+            SecretRevealed::NAME,
+            TokensClaimed::NAME,
+            DleqVerified::NAME,
+            DleqVerificationFailed::NAME,
+        ] {
+            assert!(
+                source.contains(&format!("{0}: {0},", event)),
+                "Event enum has no {}",
+                event
+            );
+        }
+    }
+
+    #[test]
+    fn test_calls() {
+        let lock = AtomicLock::new("0x123");
+        assert_eq!(lock.refund().calldata, Vec::<String>::new());
+        assert_eq!(lock.claim().entrypoint, "claim_tokens");
+        assert_eq!(lock.deposit().selector(), get_selector_from_name("deposit"));
+        assert_eq!(
+            lock.verify_and_unlock(b"hello").calldata,
+            byte_array_to_calldata(b"hello")
+        );
+        assert_eq!(
+            lock.approve("0x49d", 1000),
+            ContractCall {
+                to: "0x49d".into(),
+                entrypoint: "approve",
+                calldata: felts(&["0x123", "0x3e8", "0x0"]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let decode = |keys: Vec<String>, data: &[&str]| {
+            AtomicLockEvent::decode(&keys, &felts(data))
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(
+            decode(
+                keys::<SecretRevealed>(&["0xb0b"]),
+                &["0xdeadbeef", "0x65f0a1b0"]
+            ),
+            AtomicLockEvent::SecretRevealed(SecretRevealed {
+                revealer: "0xb0b".into(),
+                secret_hash: 0xdeadbeef,
+                claimable_after: 0x65f0a1b0,
+            })
+        );
+        assert_eq!(
+            decode(
+                keys::<TokensClaimed>(&["0xb0b"]),
+                &["0x64", "0x1", "0x10", "0x20"]
+            ),
+            AtomicLockEvent::TokensClaimed(TokensClaimed {
+                claimer: "0xb0b".into(),
+                amount_low: 100,
+                amount_high: 1,
+                reveal_timestamp: 16,
+                claim_timestamp: 32,
+            })
+        );
+        assert_eq!(
+            decode(keys::<Deposited>(&["0xa11ce"]), &["0x64", "0x0"]),
+            AtomicLockEvent::Deposited(Deposited {
+                depositor: "0xa11ce".into(),
+                amount_low: 100,
+                amount_high: 0,
+            })
+        );
+        assert_eq!(
+            decode(keys::<Refunded>(&["0xa11ce"]), &["0x64", "0x0"]),
+            AtomicLockEvent::Refunded(Refunded {
+                depositor: "0xa11ce".into(),
+                amount_low: 100,
+                amount_high: 0,
+            })
+        );
+        let limbs = ["0x1", "0x2", "0x3", "0x4", "0x5", "0x6", "0x7", "0x8"];
+        match decode(keys::<DleqVerified>(&limbs), &["0xc"]) {
+            AtomicLockEvent::DleqVerified(event) => {
+                assert_eq!(event.adaptor_point_y[3], "0x8");
+                assert_eq!(event.challenge, "0xc");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Other contracts' events on the same address are skipped
+        let keys = vec![get_selector_from_name("ReentrancyGuardEvent")];
+        assert_eq!(AtomicLockEvent::decode(&keys, &[]), Ok(None));
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_layouts() {
+        let decode =
+            |keys: Vec<String>, data: &[&str]| AtomicLockEvent::decode(&keys, &felts(data));
+        assert!(matches!(
+            decode(keys::<Unlocked>(&["0xb0b"]), &[]),
+            Err(EventError::Decode {
+                event: "Unlocked",
+                source: CairoSerdeError::Truncated
+            })
+        ));
+        assert_eq!(
+            decode(keys::<Unlocked>(&["0xb0b"]), &["0x1", "0x2"]),
+            Err(EventError::Trailing {
+                event: "Unlocked",
+                extra: 1
+            })
+        );
+        assert!(matches!(
+            decode(keys::<Unlocked>(&["0xb0b"]), &["0x100000000"]),
+            Err(EventError::Decode {
+                source: CairoSerdeError::InvalidFelt(_),
+                ..
+            })
+        ));
+        assert_eq!(decode(Vec::new(), &[]), Err(EventError::NoSelector));
+    }
+
+    #[test]
+    fn test_from_rpc() {
+        let event = serde_json::json!({
+            "from_address": "0x123",
+            "keys": [Unlocked::selector(), "0xb0b"],
+            "data": ["0x2a"],
+        });
+        assert_eq!(
+            AtomicLockEvent::from_rpc(&event),
+            Ok(Some(AtomicLockEvent::Unlocked(Unlocked {
+                unlocker: "0xb0b".into(),
+                secret_hash: 42,
+            })))
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! Typed bindings for the Cairo contracts in `cairo/`.
+//!
+//! - atomic_lock: calls, constructor calldata and events of `AtomicLock`
+
+pub mod atomic_lock;
@@ -15,16 +15,25 @@
 //! - 8x cheaper proving cost than Poseidon
 //! - Native Cairo stdlib support via core::blake
 
-use blake2::{Blake2s256, Digest as Blake2Digest};
-use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
-use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, ED25519_BASEPOINT_TABLE};
+use curve25519_dalek::edwards::{EdwardsBasepointTable, EdwardsPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{BasepointTable, IsIdentity, VartimeMultiscalarMul};
 use hex;
+use rand::rngs::OsRng;
+use rand::RngCore;
 use sha2::{Digest, Sha256};
 use std::ops::Deref;
+use std::sync::OnceLock;
 use thiserror::Error;
 use zeroize::{Zeroize, Zeroizing};
 
+use crate::encoding::{
+    check_prime_order_point, decode_point, decode_scalar, EncodingError, PointCache,
+};
+use crate::hash_backend::{HashBackend, PortableBackend};
+use crate::hash_to_curve::hash_to_curve;
+
 // TODO: Uncomment when Poseidon is fully implemented
 // mod poseidon;
 // use poseidon::compute_poseidon_challenge;
@@ -42,6 +51,22 @@ pub enum DleqError {
     NonceGenerationFailed,
     #[error("Invalid proof data (decompression or deserialization failed)")]
     InvalidProof,
+    #[error("Invalid proof encoding: {0}")]
+    Encoding(#[from] EncodingError),
+    #[error("Invalid {point} point: {reason}")]
+    InvalidPoint {
+        point: &'static str,
+        reason: EncodingError,
+    },
+    #[error("Challenge mismatch: proof is not bound to this adaptor point and hashlock")]
+    ChallengeMismatch,
+    #[error("DLEQ verification failed: {0} does not hold")]
+    VerificationFailed(&'static str),
+    #[error("Proof {index} in batch failed: {reason}")]
+    BatchItem {
+        index: usize,
+        reason: Box<DleqError>,
+    },
 }
 
 /// DLEQ proof structure containing the second point, challenge, response, and commitments.
@@ -138,19 +163,19 @@ pub fn generate_dleq_proof(
     hashlock: &[u8; 32],
 ) -> Result<DleqProof, DleqError> {
     // SECURITY: Validate inputs before generating proof
-    
+
     // 1. Check secret is non-zero (use double deref for Zeroizing)
     if **secret == Scalar::ZERO {
         return Err(DleqError::ZeroScalar);
     }
-    
+
     // 2. Verify adaptor_point = secret * G (use deref() for Zeroizing)
     let G = ED25519_BASEPOINT_POINT;
-    let computed_point = G * secret.deref();
+    let computed_point = ED25519_BASEPOINT_TABLE * secret.deref();
     if computed_point != *adaptor_point {
         return Err(DleqError::PointMismatch);
     }
-    
+
     // 3. Verify hashlock = SHA256(raw_secret_bytes) for Cairo compatibility
     // AUDIT: Warn if scalar reduction changed the bytes (could cause hashlock mismatch)
     let scalar_bytes = secret.to_bytes();
@@ -160,17 +185,17 @@ pub fn generate_dleq_proof(
         eprintln!("    Scalar: {}", hex::encode(scalar_bytes));
         eprintln!("    Using raw bytes for hashlock (Cairo-compatible)");
     }
-    
+
     let computed_hash: [u8; 32] = Sha256::digest(secret_bytes).into();
     if computed_hash != *hashlock {
         return Err(DleqError::HashlockMismatch);
     }
-    
+
     // 4. Get generators
     let Y = get_second_generator(); // Derived second base
 
     // 5. Compute U = t·Y (use deref() for Zeroizing)
-    let U = Y * secret.deref();
+    let U = second_generator_table() * secret.deref();
 
     // 6. Generate nonce k (deterministic for reproducibility in tests)
     // Using RFC6979-style deterministic nonce generation with domain separation
@@ -178,8 +203,8 @@ pub fn generate_dleq_proof(
     let k = generate_deterministic_nonce(secret, hashlock)?;
 
     // 7. Compute commitments (use deref() for Zeroizing)
-    let R1 = G * k.deref(); // k·G
-    let R2 = Y * k.deref(); // k·Y
+    let R1 = ED25519_BASEPOINT_TABLE * k.deref(); // k·G
+    let R2 = second_generator_table() * k.deref(); // k·Y
 
     // 8. Compute Fiat-Shamir challenge
     let c = compute_challenge(&G, &Y, adaptor_point, &U, &R1, &R2, hashlock);
@@ -200,10 +225,204 @@ pub fn generate_dleq_proof(
     })
 }
 
+/// Verify a DLEQ proof against the adaptor point and hashlock.
+///
+/// Checks, in order:
+/// 1. T, U, R1, R2 are prime-order points (see [`DleqProof::validate_points`])
+/// 2. c = H(tag || G || Y || T || U || R1 || R2 || hashlock), recomputed with BLAKE2s
+/// 3. s·G = R1 + c·T
+/// 4. s·Y = R2 + c·U
+///
+/// This is the Rust counterpart of the contract's `_verify_dleq_proof`, so a
+/// taker can check a maker's proof before locking funds.
+///
+/// # Errors
+///
+/// Returns `DleqError::InvalidPoint` if a point is the identity or torsioned,
+/// `DleqError::ChallengeMismatch` if the challenge does not match the inputs,
+/// and `DleqError::VerificationFailed` naming the equation that does not hold.
+pub fn verify_dleq_proof(
+    proof: &DleqProof,
+    adaptor_point: &EdwardsPoint,
+    hashlock: &[u8; 32],
+) -> Result<(), DleqError> {
+    verify_dleq_proof_with(&PortableBackend, proof, adaptor_point, hashlock)
+}
+
+/// [`verify_dleq_proof`] with the challenge hashed by `backend`.
+pub fn verify_dleq_proof_with<H: HashBackend>(
+    backend: &H,
+    proof: &DleqProof,
+    adaptor_point: &EdwardsPoint,
+    hashlock: &[u8; 32],
+) -> Result<(), DleqError> {
+    proof.validate_points(adaptor_point)?;
+
+    let G = ED25519_BASEPOINT_POINT;
+    let Y = get_second_generator();
+    let c = compute_challenge_with(
+        backend,
+        &G,
+        &Y,
+        adaptor_point,
+        &proof.second_point,
+        &proof.r1,
+        &proof.r2,
+        hashlock,
+    );
+    if c != proof.challenge {
+        return Err(DleqError::ChallengeMismatch);
+    }
+
+    // Verification only involves public data, so variable time is fine:
+    // s·G - c·T = R1 and s·Y - c·U = R2
+    let minus_c = -c;
+    if EdwardsPoint::vartime_double_scalar_mul_basepoint(&minus_c, adaptor_point, &proof.response)
+        != proof.r1
+    {
+        return Err(DleqError::VerificationFailed("s·G = R1 + c·T"));
+    }
+    if EdwardsPoint::vartime_multiscalar_mul([proof.response, minus_c], [Y, proof.second_point])
+        != proof.r2
+    {
+        return Err(DleqError::VerificationFailed("s·Y = R2 + c·U"));
+    }
+
+    Ok(())
+}
+
+/// Verify many DLEQ proofs with a single multi-scalar multiplication.
+///
+/// Each proof contributes its two verification equations weighted by fresh
+/// random scalars (z_i, w_i), and the batch checks
+///
+/// ```text
+/// (Σ z_i·s_i)·G + (Σ w_i·s_i)·Y - Σ z_i·(R1_i + c_i·T_i) - Σ w_i·(R2_i + c_i·U_i) = 0
+/// ```
+///
+/// so a forged proof only passes with negligible probability. Point
+/// validation and challenge recomputation still run per proof. If the
+/// combined check fails, proofs are re-verified individually to report the
+/// offending index.
+///
+/// Entries are `(proof, adaptor_point, hashlock)`; an empty batch verifies.
+///
+/// # Errors
+///
+/// Returns `DleqError::BatchItem` with the index of the first invalid proof
+/// and the error [`verify_dleq_proof`] reports for it.
+pub fn verify_dleq_batch(batch: &[(DleqProof, EdwardsPoint, [u8; 32])]) -> Result<(), DleqError> {
+    verify_dleq_batch_cached(batch, &mut PointCache::default())
+}
+
+/// [`verify_dleq_batch`] with point validation answered from `cache`.
+///
+/// Keep one cache across calls when the same points (e.g. a swap's T) are
+/// verified repeatedly; only points that passed validation are cached.
+pub fn verify_dleq_batch_cached(
+    batch: &[(DleqProof, EdwardsPoint, [u8; 32])],
+    cache: &mut PointCache,
+) -> Result<(), DleqError> {
+    verify_dleq_batch_with(&PortableBackend, batch, cache)
+}
+
+/// [`verify_dleq_batch_cached`] with the challenges hashed by `backend`.
+///
+/// All challenges go through one [`HashBackend::blake2s256_batch`] call, so
+/// a multi-lane backend (`multi-lane-hash` feature) hashes several proofs at
+/// once.
+pub fn verify_dleq_batch_with<H: HashBackend>(
+    backend: &H,
+    batch: &[(DleqProof, EdwardsPoint, [u8; 32])],
+    cache: &mut PointCache,
+) -> Result<(), DleqError> {
+    let G = ED25519_BASEPOINT_POINT;
+    let Y = get_second_generator();
+    let item_err = |index, reason| DleqError::BatchItem {
+        index,
+        reason: Box::new(reason),
+    };
+
+    let validated: Vec<Result<(), DleqError>> = batch
+        .iter()
+        .map(|(proof, adaptor_point, _)| {
+            proof.validate_points_with(adaptor_point, |point| cache.check_prime_order_point(point))
+        })
+        .collect();
+    let preimage_points: Vec<[[u8; 32]; 6]> = batch
+        .iter()
+        .map(|(proof, adaptor_point, _)| {
+            challenge_points([
+                &G,
+                &Y,
+                adaptor_point,
+                &proof.second_point,
+                &proof.r1,
+                &proof.r2,
+            ])
+        })
+        .collect();
+    let messages: Vec<Vec<&[u8]>> = preimage_points
+        .iter()
+        .zip(batch)
+        .map(|(points, (_, _, hashlock))| challenge_parts(points, hashlock).to_vec())
+        .collect();
+    let challenges = backend.blake2s256_batch(&messages);
+
+    let mut g_weight = Scalar::ZERO;
+    let mut y_weight = Scalar::ZERO;
+    let mut scalars = Vec::with_capacity(4 * batch.len() + 2);
+    let mut points = Vec::with_capacity(4 * batch.len() + 2);
+
+    for (index, ((proof, adaptor_point, _), (valid, hash))) in batch
+        .iter()
+        .zip(validated.into_iter().zip(challenges))
+        .enumerate()
+    {
+        valid.map_err(|e| item_err(index, e))?;
+        let c = Scalar::from_bytes_mod_order(hash);
+        if c != proof.challenge {
+            return Err(item_err(index, DleqError::ChallengeMismatch));
+        }
+
+        let z = random_weight();
+        let w = random_weight();
+        g_weight += z * proof.response;
+        y_weight += w * proof.response;
+        scalars.extend([-z, -(z * c), -w, -(w * c)]);
+        points.extend([proof.r1, *adaptor_point, proof.r2, proof.second_point]);
+    }
+
+    // Verification only involves public data, so variable time is fine
+    scalars.extend([g_weight, y_weight]);
+    points.extend([G, Y]);
+    if EdwardsPoint::vartime_multiscalar_mul(&scalars, &points).is_identity() {
+        return Ok(());
+    }
+
+    for (index, (proof, adaptor_point, hashlock)) in batch.iter().enumerate() {
+        verify_dleq_proof(proof, adaptor_point, hashlock).map_err(|e| item_err(index, e))?;
+    }
+    // Unreachable unless the random weights cancelled a failure; be conservative
+    Err(DleqError::VerificationFailed("batch equation"))
+}
+
+/// Uniformly random non-zero scalar for batch verification weights.
+fn random_weight() -> Scalar {
+    loop {
+        let mut wide = [0u8; 64];
+        OsRng.fill_bytes(&mut wide);
+        let weight = Scalar::from_bytes_mod_order_wide(&wide);
+        if weight != Scalar::ZERO {
+            return weight;
+        }
+    }
+}
 
 /// Convert an Edwards point to compressed format and sqrt hint.
 ///
-/// The sqrt hint is the x-coordinate of the point, stored as a u256 (32 bytes, little-endian).
+/// The sqrt hint is the twisted Edwards x-coordinate of the point, stored as a u256
+/// (32 bytes, little-endian).
 /// This is needed by Cairo's `decompress_edwards_pt_from_y_compressed_le_into_weirstrass_point`.
 ///
 /// # Arguments
@@ -219,15 +438,8 @@ fn edwards_point_to_cairo_format(point: &EdwardsPoint) -> ([u8; 32], [u8; 32]) {
     // Compress the point (standard Ed25519 format: y-coordinate + sign bit)
     let compressed = point.compress().to_bytes();
 
-    // Extract x-coordinate for sqrt hint
-    // Convert point to Montgomery form to get x-coordinate
-    // The Montgomery form's x-coordinate corresponds to the Edwards x-coordinate
-    let montgomery = point.to_montgomery();
-    let x_bytes = montgomery.to_bytes();
-
-    // The sqrt hint is the x-coordinate as u256 (little-endian, 32 bytes)
-    let mut sqrt_hint = [0u8; 32];
-    sqrt_hint.copy_from_slice(&x_bytes);
+    // Sqrt hint is the twisted Edwards x-coordinate (NOT the Montgomery u-coordinate)
+    let sqrt_hint = crate::hints::sqrt_hint(point);
 
     (compressed, sqrt_hint)
 }
@@ -268,6 +480,10 @@ impl DleqProof {
 
     /// Reconstruct DLEQ proof from serializable format.
     ///
+    /// **Security**: This is a trust boundary (proofs come from the counterparty).
+    /// Scalars must be canonical and points canonical and torsion-free; no
+    /// silent reduction is performed.
+    ///
     /// # Arguments
     ///
     /// * `ser` - The serialized proof data
@@ -276,26 +492,14 @@ impl DleqProof {
     ///
     /// A `Result` containing either:
     /// - `Ok(DleqProof)` - Valid reconstructed proof
-    /// - `Err(DleqError)` - Invalid proof data (decompression failed)
+    /// - `Err(DleqError::Encoding)` - Non-canonical, off-curve or torsioned encoding
     pub fn from_serializable(ser: DleqProofSerialized) -> Result<Self, DleqError> {
-        let second_point = CompressedEdwardsY(ser.second_point)
-            .decompress()
-            .ok_or(DleqError::PointMismatch)?;
-        
-        let r1 = CompressedEdwardsY(ser.r1)
-            .decompress()
-            .ok_or(DleqError::PointMismatch)?;
-        
-        let r2 = CompressedEdwardsY(ser.r2)
-            .decompress()
-            .ok_or(DleqError::PointMismatch)?;
-        
-        let challenge: Option<Scalar> = Scalar::from_canonical_bytes(ser.challenge).into();
-        let challenge = challenge.ok_or(DleqError::InvalidProof)?;
-        
-        let response: Option<Scalar> = Scalar::from_canonical_bytes(ser.response).into();
-        let response = response.ok_or(DleqError::InvalidProof)?;
-        
+        let second_point = decode_point(ser.second_point)?;
+        let r1 = decode_point(ser.r1)?;
+        let r2 = decode_point(ser.r2)?;
+        let challenge = decode_scalar(ser.challenge)?;
+        let response = decode_scalar(ser.response)?;
+
         Ok(DleqProof {
             second_point,
             challenge,
@@ -305,6 +509,37 @@ impl DleqProof {
         })
     }
 
+    /// Check that the adaptor point T and the proof points U, R1, R2 are
+    /// torsion-free and not the identity.
+    ///
+    /// **Security**: Call this before accepting T and a proof from the
+    /// counterparty. A torsioned or identity T can pass Cairo's verification
+    /// while not matching the key that finalizes the Monero side.
+    ///
+    /// # Returns
+    ///
+    /// `Err(DleqError::InvalidPoint)` naming the first offending point.
+    pub fn validate_points(&self, adaptor_point: &EdwardsPoint) -> Result<(), DleqError> {
+        self.validate_points_with(adaptor_point, check_prime_order_point)
+    }
+
+    fn validate_points_with(
+        &self,
+        adaptor_point: &EdwardsPoint,
+        mut check: impl FnMut(&EdwardsPoint) -> Result<(), EncodingError>,
+    ) -> Result<(), DleqError> {
+        let points = [
+            ("adaptor (T)", adaptor_point),
+            ("second (U)", &self.second_point),
+            ("commitment R1", &self.r1),
+            ("commitment R2", &self.r2),
+        ];
+        for (point, value) in points {
+            check(value).map_err(|reason| DleqError::InvalidPoint { point, reason })?;
+        }
+        Ok(())
+    }
+
     /// Convert DLEQ proof to JSON string.
     ///
     /// # Returns
@@ -372,21 +607,56 @@ impl DleqProof {
     }
 }
 
-/// Get the second generator point Y for DLEQ proofs.
+/// Message hashed to derive the DLEQ second generator.
+pub const SECOND_GENERATOR_TAG: &[u8] = b"DLEQ_SECOND_BASE_V1";
+
+/// RFC 9380 domain separation tag for the DLEQ second generator.
+pub const SECOND_GENERATOR_DST: &[u8] =
+    b"XMR-STARKNET-SWAP-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_RO_";
+
+/// Compressed `hash_to_curve(SECOND_GENERATOR_TAG)`.
 ///
-/// CRITICAL: Must match Cairo's `get_dleq_second_generator()` exactly!
+/// Cairo: `ED25519_SECOND_GENERATOR_COMPRESSED = u256 { low: 0x5782376cb4a8b2bd5eeb3f7b9b08c86c,
+/// high: 0x3dc7d62d3baea7450e047ed3f78f21ba }`. Run `cargo run --bin generate_second_base`
+/// for the matching Weierstrass limbs.
+pub const HASH_TO_CURVE_SECOND_GENERATOR_COMPRESSED: [u8; 32] = [
+    0x6c, 0xc8, 0x08, 0x9b, 0x7b, 0x3f, 0xeb, 0x5e, 0xbd, 0xb2, 0xa8, 0xb4, 0x6c, 0x37, 0x82, 0x57,
+    0xba, 0x21, 0x8f, 0xf7, 0xd3, 0x7e, 0x04, 0x0e, 0x45, 0xa7, 0xae, 0x3b, 0x2d, 0xd6, 0xc7, 0x3d,
+];
+
+/// Second generator derived by hash-to-curve (nobody knows log_G(Y)).
 ///
-/// Currently uses `2·G` as placeholder to match Cairo's implementation.
-/// This ensures Rust-generated proofs verify correctly in Cairo.
+/// Elligator 2 over `SECOND_GENERATOR_TAG` (RFC 9380,
+/// `edwards25519_XMD:SHA-512_ELL2_RO_`). Cached after the first call.
+pub fn hash_to_curve_second_generator() -> EdwardsPoint {
+    static GENERATOR: OnceLock<EdwardsPoint> = OnceLock::new();
+    *GENERATOR.get_or_init(|| hash_to_curve(SECOND_GENERATOR_TAG, SECOND_GENERATOR_DST))
+}
+
+/// Get the second generator point Y for DLEQ proofs.
 ///
-/// TODO: Once Python tool generates hash-to-curve constant for Cairo,
-/// update both Rust and Cairo to use the same hash-to-curve point.
+/// CRITICAL: Must match Cairo's `get_dleq_second_generator()` exactly!
 ///
-/// Production path: Use hash-to-curve("DLEQ_SECOND_BASE_V1") → Edwards → Weierstrass → u384 limbs
+/// By default this is still `2·G`, matching the deployed Cairo contract and
+/// existing test vectors. `2·G` has a known discrete log and does not give a
+/// sound DLEQ binding; enable the `h2c-second-generator` feature to use
+/// [`hash_to_curve_second_generator`] once the Cairo constants are updated
+/// from `generate_second_base` (both sides must switch together).
 pub(crate) fn get_second_generator() -> EdwardsPoint {
-    // Current implementation: Y = 2·G (matches Cairo placeholder)
-    // This ensures compatibility between Rust proof generation and Cairo verification
-    ED25519_BASEPOINT_POINT * Scalar::from(2u64)
+    if cfg!(feature = "h2c-second-generator") {
+        hash_to_curve_second_generator()
+    } else {
+        ED25519_BASEPOINT_POINT * Scalar::from(2u64)
+    }
+}
+
+/// Precomputed multiples of [`get_second_generator`], built on first use.
+///
+/// Constant-time like `ED25519_BASEPOINT_TABLE`, so it is safe for the
+/// secret and nonce multiplications in proof generation.
+fn second_generator_table() -> &'static EdwardsBasepointTable {
+    static TABLE: OnceLock<EdwardsBasepointTable> = OnceLock::new();
+    TABLE.get_or_init(|| EdwardsBasepointTable::create(&get_second_generator()))
 }
 
 /// Generate a deterministic nonce k for DLEQ proof generation.
@@ -430,18 +700,18 @@ fn generate_deterministic_nonce(
     hashlock: &[u8; 32],
 ) -> Result<Zeroizing<Scalar>, DleqError> {
     let mut counter = 0u32;
-    
+
     loop {
-    let mut hasher = Sha256::new();
+        let mut hasher = Sha256::new();
         // Domain separation: prevents hash collisions with other protocol hashes
         hasher.update(b"DLEQ_NONCE_V1");
         hasher.update(secret.deref().to_bytes()); // Use deref() for Zeroizing
-    hasher.update(hashlock);
+        hasher.update(hashlock);
         hasher.update(&counter.to_le_bytes()); // Counter for retry if k is invalid
 
-    let hash = hasher.finalize();
-    let mut scalar_bytes = [0u8; 32];
-    scalar_bytes.copy_from_slice(&hash);
+        let hash = hasher.finalize();
+        let mut scalar_bytes = [0u8; 32];
+        scalar_bytes.copy_from_slice(&hash);
         let k = Scalar::from_bytes_mod_order(scalar_bytes);
 
         // Validate nonce is non-zero
@@ -484,40 +754,56 @@ fn compute_challenge(
     R2: &EdwardsPoint,
     hashlock: &[u8; 32],
 ) -> Scalar {
-    // Use BLAKE2s (Starknet's official standard, matches Cairo)
-    let mut hasher = Blake2s256::new();
+    compute_challenge_with(&PortableBackend, G, Y, T, U, R1, R2, hashlock)
+}
+
+/// [`compute_challenge`] with BLAKE2s provided by `backend`.
+#[allow(clippy::too_many_arguments)]
+fn compute_challenge_with<H: HashBackend>(
+    backend: &H,
+    G: &EdwardsPoint,
+    Y: &EdwardsPoint,
+    T: &EdwardsPoint,
+    U: &EdwardsPoint,
+    R1: &EdwardsPoint,
+    R2: &EdwardsPoint,
+    hashlock: &[u8; 32],
+) -> Scalar {
+    let points = challenge_points([G, Y, T, U, R1, R2]);
+    let hash = backend.blake2s256(&challenge_parts(&points, hashlock));
 
+    // Reduce hash to scalar mod curve order
+    Scalar::from_bytes_mod_order(hash)
+}
+
+/// Compressed points of the challenge preimage: G, Y, T, U, R1, R2.
+fn challenge_points(points: [&EdwardsPoint; 6]) -> [[u8; 32]; 6] {
+    points.map(|point| point.compress().to_bytes())
+}
+
+/// BLAKE2s input of the challenge: tag, points and hashlock.
+fn challenge_parts<'a>(points: &'a [[u8; 32]; 6], hashlock: &'a [u8; 32]) -> [&'a [u8]; 8] {
     // Tag: "DLEQ" (4 bytes) for domain separation
     // This matches Cairo's tag: 0x444c4551
-    hasher.update(b"DLEQ");
-
-    // Serialize points in compressed format (32 bytes each)
+    //
+    // Points in compressed format (32 bytes each)
     // Order: G, Y, T, U, R1, R2 (must match Cairo exactly)
-    hasher.update(G.compress().as_bytes());
-    hasher.update(Y.compress().as_bytes());
-    hasher.update(T.compress().as_bytes());
-    hasher.update(U.compress().as_bytes());
-    hasher.update(R1.compress().as_bytes());
-    hasher.update(R2.compress().as_bytes());
-
-    // Add hashlock (32 bytes)
+    //
     // NOTE: Rust's hashlock is already a [u8; 32] byte array, so BLAKE2s sees it correctly.
     // Cairo needs byte-swapping because it stores hashlock as Big-Endian u32 words.
     // The byte-swap fix is in Cairo, not here.
-    hasher.update(hashlock);
-
-    // Reduce hash to scalar mod curve order
-    let hash = hasher.finalize();
-    let mut scalar_bytes = [0u8; 32];
-    scalar_bytes.copy_from_slice(&hash);
-    Scalar::from_bytes_mod_order(scalar_bytes)
+    [
+        b"DLEQ", &points[0], &points[1], &points[2], &points[3], &points[4], &points[5], hashlock,
+    ]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use zeroize::Zeroizing;
+    #[cfg(feature = "multi-lane-hash")]
+    use crate::hash_backend::{MultiLaneBackend, LANES};
     use std::ops::Deref;
+    use zeroize::Zeroizing;
 
     #[test]
     fn test_dleq_proof_generation() {
@@ -533,8 +819,9 @@ mod tests {
         let adaptor_point = ED25519_BASEPOINT_POINT * *secret_zeroizing;
 
         // Generate DLEQ proof
-        let proof = generate_dleq_proof(&secret_zeroizing, &secret_bytes, &adaptor_point, &hashlock)
-            .expect("Proof generation should succeed for valid inputs");
+        let proof =
+            generate_dleq_proof(&secret_zeroizing, &secret_bytes, &adaptor_point, &hashlock)
+                .expect("Proof generation should succeed for valid inputs");
 
         // Verify proof structure: U should equal t·Y
         let Y = get_second_generator();
@@ -550,6 +837,17 @@ mod tests {
         assert_eq!(Y1, Y2, "Second generator should be deterministic");
     }
 
+    #[test]
+    fn test_precomputed_tables_match_point_multiplication() {
+        let Y = get_second_generator();
+        for k in [1u64, 2, 7, u64::MAX] {
+            let k = Scalar::from(k);
+            assert_eq!(ED25519_BASEPOINT_TABLE * &k, ED25519_BASEPOINT_POINT * k);
+            assert_eq!(second_generator_table() * &k, Y * k);
+        }
+        assert_eq!(second_generator_table().basepoint(), Y);
+    }
+
     #[test]
     fn test_dleq_validation_zero_scalar() {
         use zeroize::Zeroizing;
@@ -559,13 +857,17 @@ mod tests {
         let hashlock = [0u8; 32]; // arbitrary
 
         let result = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock);
-        assert_eq!(result, Err(DleqError::ZeroScalar), "Zero scalar must be rejected");
+        assert_eq!(
+            result,
+            Err(DleqError::ZeroScalar),
+            "Zero scalar must be rejected"
+        );
     }
 
     #[test]
     fn test_dleq_validation_point_mismatch() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         let secret = Zeroizing::new(Scalar::from(42u64));
         let secret_bytes = secret.deref().to_bytes(); // Use scalar bytes for test
         let wrong_point = ED25519_BASEPOINT_POINT * Scalar::from(99u64); // wrong!
@@ -581,8 +883,8 @@ mod tests {
 
     #[test]
     fn test_dleq_validation_hashlock_mismatch() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         let secret = Zeroizing::new(Scalar::from(42u64));
         let secret_bytes = secret.deref().to_bytes(); // Use scalar bytes for test
         let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
@@ -598,8 +900,8 @@ mod tests {
 
     #[test]
     fn test_nonce_generation_deterministic() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         let secret = Zeroizing::new(Scalar::from(42u64));
         let hashlock: [u8; 32] = Sha256::digest(secret.deref().to_bytes()).into();
 
@@ -614,8 +916,8 @@ mod tests {
 
     #[test]
     fn test_nonce_generation_different_inputs_produce_different_nonces() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         let secret1 = Zeroizing::new(Scalar::from(42u64));
         let secret2 = Zeroizing::new(Scalar::from(99u64));
         let hashlock1: [u8; 32] = Sha256::digest(secret1.deref().to_bytes()).into();
@@ -627,13 +929,16 @@ mod tests {
             .expect("Nonce generation should succeed");
 
         // Different inputs should produce different nonces (with high probability)
-        assert_ne!(*nonce1, *nonce2, "Different inputs should produce different nonces");
+        assert_ne!(
+            *nonce1, *nonce2,
+            "Different inputs should produce different nonces"
+        );
     }
 
     #[test]
     fn test_dleq_validation_scalar_one() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         // Test edge case: Scalar::ONE (smallest non-zero scalar)
         let secret = Zeroizing::new(Scalar::ONE);
         let secret_bytes = secret.deref().to_bytes(); // Use scalar bytes for test
@@ -647,8 +952,8 @@ mod tests {
 
     #[test]
     fn test_dleq_validation_max_scalar() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         // Test edge case: Maximum scalar value (order - 1)
         // Ed25519 order is 2^252 + 27742317777372353535851937790883648493
         // Maximum scalar is order - 1
@@ -669,8 +974,8 @@ mod tests {
 
     #[test]
     fn test_nonce_generation_counter_boundary() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         // Test that nonce generation handles counter retries correctly
         // This tests the boundary condition where k might be zero multiple times
         // (though statistically unlikely, we should handle it)
@@ -687,8 +992,8 @@ mod tests {
 
     #[test]
     fn test_nonce_generation_max_attempts() {
-        use zeroize::Zeroizing;
         use std::ops::Deref;
+        use zeroize::Zeroizing;
         // Test that nonce generation doesn't loop infinitely
         // Even if we hit zero nonces, we should fail gracefully after max attempts
         // Note: This is a theoretical test - hitting zero 100 times is cryptographically impossible
@@ -698,6 +1003,301 @@ mod tests {
 
         // This should succeed (hitting zero 100 times is impossible)
         let result = generate_deterministic_nonce(&secret, &hashlock);
-        assert!(result.is_ok(), "Nonce generation should succeed for valid inputs");
+        assert!(
+            result.is_ok(),
+            "Nonce generation should succeed for valid inputs"
+        );
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let secret_bytes = [0x42u8; 32];
+        let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+        let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+        let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+        let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+            .expect("Proof generation should succeed");
+
+        let restored = DleqProof::from_serializable(proof.to_serializable())
+            .expect("Canonical proof must deserialize");
+        assert_eq!(restored, proof);
+    }
+
+    #[test]
+    fn test_deserialization_rejects_bad_encodings() {
+        use curve25519_dalek::constants::EIGHT_TORSION;
+
+        let secret_bytes = [0x42u8; 32];
+        let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+        let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+        let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+        let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+            .expect("Proof generation should succeed");
+
+        // Non-canonical response (s + ℓ would be accepted by from_bytes_mod_order)
+        let mut ser = proof.to_serializable();
+        ser.response = [0xff; 32];
+        assert_eq!(
+            DleqProof::from_serializable(ser),
+            Err(DleqError::Encoding(EncodingError::NonCanonicalScalar))
+        );
+
+        // Torsioned commitment R1 + T8
+        let mut ser = proof.to_serializable();
+        ser.r1 = (proof.r1 + EIGHT_TORSION[1]).compress().to_bytes();
+        assert_eq!(
+            DleqProof::from_serializable(ser),
+            Err(DleqError::Encoding(EncodingError::TorsionedPoint))
+        );
+
+        // Non-canonical y (p + 1) for U
+        let mut ser = proof.to_serializable();
+        let mut bad = [0xffu8; 32];
+        bad[0] = 0xee;
+        bad[31] = 0x7f;
+        ser.second_point = bad;
+        assert_eq!(
+            DleqProof::from_serializable(ser),
+            Err(DleqError::Encoding(EncodingError::NonCanonicalPoint))
+        );
+    }
+
+    #[test]
+    fn test_validate_points_rejects_torsion_and_identity() {
+        use curve25519_dalek::constants::EIGHT_TORSION;
+        use curve25519_dalek::traits::Identity;
+
+        let secret_bytes = [0x42u8; 32];
+        let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+        let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+        let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+        let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+            .expect("Proof generation should succeed");
+
+        assert_eq!(proof.validate_points(&adaptor_point), Ok(()));
+
+        let torsioned_t = adaptor_point + EIGHT_TORSION[4];
+        assert_eq!(
+            proof.validate_points(&torsioned_t),
+            Err(DleqError::InvalidPoint {
+                point: "adaptor (T)",
+                reason: EncodingError::TorsionedPoint,
+            })
+        );
+
+        assert_eq!(
+            proof.validate_points(&EdwardsPoint::identity()),
+            Err(DleqError::InvalidPoint {
+                point: "adaptor (T)",
+                reason: EncodingError::IdentityPoint,
+            })
+        );
+
+        let mut bad = proof.clone();
+        bad.r2 = EdwardsPoint::identity();
+        assert_eq!(
+            bad.validate_points(&adaptor_point),
+            Err(DleqError::InvalidPoint {
+                point: "commitment R2",
+                reason: EncodingError::IdentityPoint,
+            })
+        );
+    }
+
+    fn test_proof() -> (DleqProof, EdwardsPoint, [u8; 32]) {
+        let secret_bytes = [0x42u8; 32];
+        let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+        let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+        let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+        let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+            .expect("Proof generation should succeed");
+        (proof, adaptor_point, hashlock)
+    }
+
+    #[test]
+    fn test_verify_dleq_proof_accepts_valid_proof() {
+        let (proof, adaptor_point, hashlock) = test_proof();
+        assert_eq!(verify_dleq_proof(&proof, &adaptor_point, &hashlock), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_dleq_proof_rejects_wrong_inputs() {
+        let (proof, adaptor_point, hashlock) = test_proof();
+
+        // Challenge binds the hashlock and T
+        assert_eq!(
+            verify_dleq_proof(&proof, &adaptor_point, &[0xFF; 32]),
+            Err(DleqError::ChallengeMismatch)
+        );
+        let other_point = ED25519_BASEPOINT_POINT * Scalar::from(99u64);
+        assert_eq!(
+            verify_dleq_proof(&proof, &other_point, &hashlock),
+            Err(DleqError::ChallengeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_dleq_proof_rejects_tampered_response() {
+        let (proof, adaptor_point, hashlock) = test_proof();
+
+        let mut bad = proof.clone();
+        bad.response += Scalar::ONE;
+        assert_eq!(
+            verify_dleq_proof(&bad, &adaptor_point, &hashlock),
+            Err(DleqError::VerificationFailed("s·G = R1 + c·T"))
+        );
+
+        // U not equal to t·Y: recompute the challenge so only the second equation fails
+        let mut bad = proof.clone();
+        bad.second_point = proof.second_point + ED25519_BASEPOINT_POINT;
+        bad.challenge = compute_challenge(
+            &ED25519_BASEPOINT_POINT,
+            &get_second_generator(),
+            &adaptor_point,
+            &bad.second_point,
+            &bad.r1,
+            &bad.r2,
+            &hashlock,
+        );
+        let t = Scalar::from_bytes_mod_order([0x42u8; 32]);
+        let k = proof.response - proof.challenge * t;
+        bad.response = k + bad.challenge * t;
+        assert_eq!(
+            verify_dleq_proof(&bad, &adaptor_point, &hashlock),
+            Err(DleqError::VerificationFailed("s·Y = R2 + c·U"))
+        );
+    }
+
+    #[test]
+    fn test_verify_with_custom_backend() {
+        use std::cell::Cell;
+
+        /// Delegates to the portable backend and counts BLAKE2s calls
+        struct CountingBackend(Cell<usize>);
+        impl HashBackend for CountingBackend {
+            fn blake2s256(&self, parts: &[&[u8]]) -> [u8; 32] {
+                self.0.set(self.0.get() + 1);
+                PortableBackend.blake2s256(parts)
+            }
+            fn keccak256(&self, parts: &[&[u8]]) -> [u8; 32] {
+                PortableBackend.keccak256(parts)
+            }
+        }
+
+        /// Not BLAKE2s: must never verify
+        struct Sha256Backend;
+        impl HashBackend for Sha256Backend {
+            fn blake2s256(&self, parts: &[&[u8]]) -> [u8; 32] {
+                Sha256::digest(parts.concat()).into()
+            }
+            fn keccak256(&self, parts: &[&[u8]]) -> [u8; 32] {
+                PortableBackend.keccak256(parts)
+            }
+        }
+
+        let (proof, adaptor_point, hashlock) = test_proof();
+        let counting = CountingBackend(Cell::new(0));
+        assert_eq!(
+            verify_dleq_proof_with(&counting, &proof, &adaptor_point, &hashlock),
+            Ok(())
+        );
+        assert_eq!(counting.0.get(), 1);
+        assert_eq!(
+            verify_dleq_proof_with(&Sha256Backend, &proof, &adaptor_point, &hashlock),
+            Err(DleqError::ChallengeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_dleq_batch() {
+        let batch: Vec<_> = (1u8..=5)
+            .map(|i| {
+                let secret_bytes = [i; 32];
+                let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+                let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+                let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+                let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+                    .expect("Proof generation should succeed");
+                (proof, adaptor_point, hashlock)
+            })
+            .collect();
+
+        assert_eq!(verify_dleq_batch(&[]), Ok(()));
+        assert_eq!(verify_dleq_batch(&batch), Ok(()));
+
+        // Forged response in the middle of the batch is located
+        let mut forged = batch.clone();
+        forged[3].0.response += Scalar::ONE;
+        assert_eq!(
+            verify_dleq_batch(&forged),
+            Err(DleqError::BatchItem {
+                index: 3,
+                reason: Box::new(DleqError::VerificationFailed("s·G = R1 + c·T")),
+            })
+        );
+
+        // A shared cache keeps the validated points across batches
+        let mut cache = PointCache::default();
+        assert_eq!(verify_dleq_batch_cached(&batch, &mut cache), Ok(()));
+        let cached = cache.len();
+        assert!(cached > 0 && cached <= 4 * batch.len());
+        assert_eq!(verify_dleq_batch_cached(&batch, &mut cache), Ok(()));
+        assert_eq!(cache.len(), cached);
+        assert_eq!(
+            verify_dleq_batch_cached(&forged, &mut cache),
+            verify_dleq_batch(&forged)
+        );
+
+        // Proof paired with the wrong hashlock
+        let mut swapped = batch.clone();
+        swapped[1].2 = batch[2].2;
+        assert_eq!(
+            verify_dleq_batch(&swapped),
+            Err(DleqError::BatchItem {
+                index: 1,
+                reason: Box::new(DleqError::ChallengeMismatch),
+            })
+        );
+    }
+
+    #[cfg(feature = "multi-lane-hash")]
+    #[test]
+    fn test_multi_lane_batch_matches_portable() {
+        let batch: Vec<_> = (1u8..=3)
+            .map(|i| {
+                let secret_bytes = [i; 32];
+                let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+                let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+                let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+                let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+                    .expect("Proof generation should succeed");
+                (proof, adaptor_point, hashlock)
+            })
+            .collect();
+        let mut forged = batch.clone();
+        forged[1].0.response += Scalar::ONE;
+
+        // Every backend reports the same outcome, across more proofs than lanes
+        let large: Vec<_> = batch.iter().cycle().take(2 * LANES + 3).cloned().collect();
+        let mut large_swapped = large.clone();
+        large_swapped[LANES + 1].2 = batch[1].2;
+        for entries in [&batch, &forged, &large, &large_swapped] {
+            assert_eq!(
+                verify_dleq_batch_with(&PortableBackend, entries, &mut PointCache::default()),
+                verify_dleq_batch_with(&MultiLaneBackend, entries, &mut PointCache::default())
+            );
+        }
+        assert!(verify_dleq_batch(&large_swapped).is_err());
+    }
+
+    #[test]
+    fn test_hash_to_curve_second_generator() {
+        let Y = hash_to_curve_second_generator();
+        assert_eq!(
+            Y.compress().to_bytes(),
+            HASH_TO_CURVE_SECOND_GENERATOR_COMPRESSED
+        );
+        assert!(check_prime_order_point(&Y).is_ok());
+        assert_ne!(Y, ED25519_BASEPOINT_POINT * Scalar::from(2u64));
     }
 }
@@ -0,0 +1,309 @@
+//! Strict decoding of scalars and Ed25519 points received from peers.
+//!
+//! Every 32-byte scalar or point that crosses a trust boundary (DLEQ proofs,
+//! adaptor signatures, protocol messages) must go through this module.
+//! `Scalar::from_bytes_mod_order` and plain `decompress()` silently accept
+//! encodings that Cairo and Monero treat differently, so we require:
+//!
+//! - Scalars: canonical (< ℓ), no modular reduction
+//! - Points: on the curve, canonical (re-compressing gives the same bytes,
+//!   which rejects y ≥ p and x = 0 with the sign bit set), torsion-free
+//!   (no small-order component that Cairo's prime-order checks would miss),
+//!   and not the identity
+//!
+//! Points that are already decoded (e.g. handed over in-process) can be
+//! checked with [`check_prime_order_point`]. A torsioned or identity adaptor
+//! point `T` would break the binding between the Starknet DLEQ verification
+//! and the Monero-side finalization, so neither is ever accepted.
+//!
+//! Verifiers that see the same points over and over (G, Y, a swap's T) can
+//! keep a [`PointCache`] to skip repeated decompression and torsion checks.
+
+use std::collections::{HashMap, VecDeque};
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use thiserror::Error;
+
+/// Encoding errors for peer-supplied scalars and points.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingError {
+    #[error("Non-canonical scalar encoding (value >= group order)")]
+    NonCanonicalScalar,
+    #[error("Point encoding is not on the Ed25519 curve")]
+    PointNotOnCurve,
+    #[error("Non-canonical point encoding")]
+    NonCanonicalPoint,
+    #[error("Point has a torsion component (not in the prime-order subgroup)")]
+    TorsionedPoint,
+    #[error("Point is the identity")]
+    IdentityPoint,
+}
+
+/// Decode a canonical scalar (rejects values ≥ ℓ instead of reducing them).
+pub fn decode_scalar(bytes: [u8; 32]) -> Result<Scalar, EncodingError> {
+    Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes))
+        .ok_or(EncodingError::NonCanonicalScalar)
+}
+
+/// Decode a canonical, torsion-free Edwards point.
+pub fn decode_point(bytes: [u8; 32]) -> Result<EdwardsPoint, EncodingError> {
+    let point = CompressedEdwardsY(bytes)
+        .decompress()
+        .ok_or(EncodingError::PointNotOnCurve)?;
+
+    if point.compress().to_bytes() != bytes {
+        return Err(EncodingError::NonCanonicalPoint);
+    }
+
+    check_prime_order_point(&point)?;
+    Ok(point)
+}
+
+/// Check that a point is a non-identity element of the prime-order subgroup.
+pub fn check_prime_order_point(point: &EdwardsPoint) -> Result<(), EncodingError> {
+    if point.is_identity() {
+        return Err(EncodingError::IdentityPoint);
+    }
+    if !point.is_torsion_free() {
+        return Err(EncodingError::TorsionedPoint);
+    }
+    Ok(())
+}
+
+/// Default number of points kept by [`PointCache::default`].
+pub const DEFAULT_POINT_CACHE_CAPACITY: usize = 256;
+
+/// Small LRU of points that already passed [`decode_point`], keyed by their
+/// canonical compressed encoding.
+///
+/// Only valid points are cached, so a hit skips decompression and the
+/// torsion check without changing what is accepted. Failures are always
+/// recomputed.
+#[derive(Debug, Clone)]
+pub struct PointCache {
+    capacity: usize,
+    points: HashMap<[u8; 32], EdwardsPoint>,
+    /// Least recently used first
+    order: VecDeque<[u8; 32]>,
+}
+
+impl Default for PointCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_POINT_CACHE_CAPACITY)
+    }
+}
+
+impl PointCache {
+    /// Cache holding at most `capacity` points (0 disables caching).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            points: HashMap::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// [`decode_point`], answered from the cache when `bytes` was seen before.
+    pub fn decode_point(&mut self, bytes: [u8; 32]) -> Result<EdwardsPoint, EncodingError> {
+        if let Some(point) = self.get(&bytes) {
+            return Ok(point);
+        }
+        let point = decode_point(bytes)?;
+        self.insert(bytes, point);
+        Ok(point)
+    }
+
+    /// [`check_prime_order_point`], answered from the cache when the point
+    /// was seen before.
+    pub fn check_prime_order_point(&mut self, point: &EdwardsPoint) -> Result<(), EncodingError> {
+        let bytes = point.compress().to_bytes();
+        if self.get(&bytes).is_some() {
+            return Ok(());
+        }
+        check_prime_order_point(point)?;
+        self.insert(bytes, *point);
+        Ok(())
+    }
+
+    /// Number of cached points.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    fn get(&mut self, bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+        let point = *self.points.get(bytes)?;
+        if let Some(position) = self.order.iter().position(|key| key == bytes) {
+            self.order.remove(position);
+        }
+        self.order.push_back(*bytes);
+        Some(point)
+    }
+
+    fn insert(&mut self, bytes: [u8; 32], point: EdwardsPoint) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.points.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.points.remove(&oldest);
+            }
+        }
+        self.points.insert(bytes, point);
+        self.order.push_back(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, EIGHT_TORSION};
+
+    /// Group order ℓ (little-endian).
+    const ORDER_BYTES: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10,
+    ];
+
+    fn field_element(low: u8) -> [u8; 32] {
+        // p + k encodings: 0xed..0xff, ff..ff, 0x7f
+        let mut bytes = [0xffu8; 32];
+        bytes[0] = low;
+        bytes[31] = 0x7f;
+        bytes
+    }
+
+    #[test]
+    fn test_canonical_scalar_accepted() {
+        let s = Scalar::from(42u64);
+        assert_eq!(decode_scalar(s.to_bytes()), Ok(s));
+    }
+
+    #[test]
+    fn test_group_order_scalar_rejected() {
+        assert_eq!(
+            decode_scalar(ORDER_BYTES),
+            Err(EncodingError::NonCanonicalScalar)
+        );
+        assert_eq!(
+            decode_scalar([0xff; 32]),
+            Err(EncodingError::NonCanonicalScalar)
+        );
+    }
+
+    #[test]
+    fn test_valid_point_accepted() {
+        let p = ED25519_BASEPOINT_POINT * Scalar::from(7u64);
+        assert_eq!(decode_point(p.compress().to_bytes()), Ok(p));
+    }
+
+    #[test]
+    fn test_off_curve_point_rejected() {
+        // y = 2 has no matching x on Ed25519
+        let mut bytes = [0u8; 32];
+        bytes[0] = 2;
+        assert_eq!(decode_point(bytes), Err(EncodingError::PointNotOnCurve));
+    }
+
+    #[test]
+    fn test_non_canonical_y_rejected() {
+        // y = p + 1 ≡ 1 (identity) encoded without reduction
+        assert_eq!(
+            decode_point(field_element(0xee)),
+            Err(EncodingError::NonCanonicalPoint)
+        );
+    }
+
+    #[test]
+    fn test_negative_zero_x_rejected() {
+        // identity (x = 0, y = 1) with the sign bit set
+        let mut bytes = [0u8; 32];
+        bytes[0] = 1;
+        bytes[31] = 0x80;
+        assert!(decode_point(bytes).is_err());
+    }
+
+    #[test]
+    fn test_small_order_point_rejected() {
+        // (0, -1): canonical encoding of the order-2 point
+        assert_eq!(
+            decode_point(field_element(0xec)),
+            Err(EncodingError::TorsionedPoint)
+        );
+    }
+
+    #[test]
+    fn test_mixed_torsion_point_rejected() {
+        let torsioned = ED25519_BASEPOINT_POINT + EIGHT_TORSION[1];
+        assert_eq!(
+            decode_point(torsioned.compress().to_bytes()),
+            Err(EncodingError::TorsionedPoint)
+        );
+    }
+
+    #[test]
+    fn test_identity_point_rejected() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 1;
+        assert_eq!(decode_point(bytes), Err(EncodingError::IdentityPoint));
+        assert_eq!(
+            check_prime_order_point(&EdwardsPoint::default()),
+            Err(EncodingError::IdentityPoint)
+        );
+    }
+
+    #[test]
+    fn test_pure_torsion_point_rejected() {
+        for (i, point) in EIGHT_TORSION.iter().enumerate().skip(1) {
+            assert_eq!(
+                check_prime_order_point(point),
+                Err(EncodingError::TorsionedPoint),
+                "torsion point {} must be rejected",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_point_cache_evicts_least_recently_used() {
+        let point = |k: u64| ED25519_BASEPOINT_POINT * Scalar::from(k);
+        let mut cache = PointCache::new(2);
+        assert_eq!(
+            cache.decode_point(point(1).compress().to_bytes()),
+            Ok(point(1))
+        );
+        assert_eq!(cache.check_prime_order_point(&point(2)), Ok(()));
+        // Touch 1, so 2 is evicted by 3
+        assert_eq!(
+            cache.decode_point(point(1).compress().to_bytes()),
+            Ok(point(1))
+        );
+        assert_eq!(cache.check_prime_order_point(&point(3)), Ok(()));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.points.contains_key(&point(1).compress().to_bytes()));
+        assert!(!cache.points.contains_key(&point(2).compress().to_bytes()));
+
+        // Invalid points are rejected every time and never cached
+        let torsioned = ED25519_BASEPOINT_POINT + EIGHT_TORSION[1];
+        for _ in 0..2 {
+            assert_eq!(
+                cache.decode_point(torsioned.compress().to_bytes()),
+                Err(EncodingError::TorsionedPoint)
+            );
+            assert_eq!(
+                cache.check_prime_order_point(&EdwardsPoint::default()),
+                Err(EncodingError::IdentityPoint)
+            );
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(PointCache::new(0)
+            .decode_point(point(1).compress().to_bytes())
+            .is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+//! Pluggable hash backends for challenge computation.
+//!
+//! DLEQ challenges use BLAKE2s-256 and Starknet selectors use Keccak-256. Both
+//! go through [`HashBackend`] so high-volume verifiers can substitute an
+//! accelerated implementation (SIMD, hardware offload) without forking the
+//! protocol code. Any backend must be bit-for-bit identical to
+//! [`PortableBackend`]: a different digest means a different challenge, and
+//! Cairo will reject the proof.
+//!
+//! Batch DLEQ verification ([`crate::dleq::verify_dleq_batch_with`]) hashes
+//! all of its challenges through [`HashBackend::blake2s256_batch`]. With the
+//! `multi-lane-hash` feature, `MultiLaneBackend` hashes `LANES` independent
+//! messages side by side (AVX2 on x86-64 CPUs that have it, detected at
+//! runtime). It is opt-in: it carries its own BLAKE2s compression function
+//! instead of the audited RustCrypto one, so the default verification path
+//! never uses it.
+
+use blake2::{Blake2s256, Digest};
+
+#[cfg(feature = "multi-lane-hash")]
+mod multi_lane;
+#[cfg(feature = "multi-lane-hash")]
+pub use multi_lane::{MultiLaneBackend, LANES};
+
+/// Hash functions used when computing and checking protocol challenges.
+pub trait HashBackend {
+    /// BLAKE2s-256 over the concatenation of `parts`.
+    fn blake2s256(&self, parts: &[&[u8]]) -> [u8; 32];
+
+    /// Original Keccak-256 (not SHA3-256) over the concatenation of `parts`.
+    fn keccak256(&self, parts: &[&[u8]]) -> [u8; 32];
+
+    /// BLAKE2s-256 of many independent messages.
+    ///
+    /// The default hashes one message at a time; multi-lane backends should
+    /// override this to hash several messages per instruction.
+    fn blake2s256_batch(&self, messages: &[Vec<&[u8]>]) -> Vec<[u8; 32]> {
+        messages
+            .iter()
+            .map(|parts| self.blake2s256(parts))
+            .collect()
+    }
+}
+
+/// RustCrypto-based backend (default).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PortableBackend;
+
+impl HashBackend for PortableBackend {
+    fn blake2s256(&self, parts: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Blake2s256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+
+    fn keccak256(&self, parts: &[&[u8]]) -> [u8; 32] {
+        match parts {
+            [single] => crate::starknet::keccak256(single),
+            _ => crate::starknet::keccak256(&parts.concat()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blake2s_parts_equal_concatenation() {
+        let backend = PortableBackend;
+        assert_eq!(
+            backend.blake2s256(&[b"DLEQ", b"abc"]),
+            backend.blake2s256(&[b"DLEQabc"])
+        );
+        // RFC 7693 test vector: BLAKE2s-256("abc")
+        assert_eq!(
+            hex::encode(backend.blake2s256(&[b"abc"])),
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+        );
+    }
+
+    #[test]
+    fn test_keccak_matches_starknet_module() {
+        let backend = PortableBackend;
+        assert_eq!(
+            backend.keccak256(&[b"Secret", b"Revealed"]),
+            crate::starknet::keccak256(b"SecretRevealed")
+        );
+    }
+
+    #[test]
+    fn test_default_batch_matches_single() {
+        let backend = PortableBackend;
+        let messages: Vec<Vec<&[u8]>> = vec![vec![b"a"], vec![b"b", b"c"], vec![]];
+        let batch = backend.blake2s256_batch(&messages);
+        for (digest, parts) in batch.iter().zip(&messages) {
+            assert_eq!(*digest, backend.blake2s256(parts));
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Hash-to-curve for Ed25519 (RFC 9380, `edwards25519_XMD:SHA-512_ELL2_RO_`).
+//!
+//! Used to derive the DLEQ second generator Y from a fixed tag, so that nobody
+//! knows log_G(Y). (The previous `Y = 2·G` placeholder has a known discrete
+//! log, which lets a prover forge the DLEQ binding.)
+//!
+//! Pipeline: `expand_message_xmd` (SHA-512) → two field elements → Elligator 2
+//! on curve25519 → rational map to edwards25519 → add → clear cofactor (×8).
+//! Field arithmetic reuses the `BigUint` helpers from [`crate::weierstrass`];
+//! this runs once per process, so speed does not matter.
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use sha2::{Digest, Sha512};
+
+use crate::weierstrass::{field_prime, inv, sub};
+
+/// Curve25519 Montgomery coefficient A
+const MONTGOMERY_A: u32 = 486662;
+
+/// Bytes per field element: ceil((ceil(log2(p)) + k) / 8) with k = 128
+const FIELD_ELEMENT_BYTES: usize = 48;
+
+/// Hash `msg` to a prime-order Ed25519 point under domain separation tag `dst`.
+///
+/// # Panics
+///
+/// Panics if `dst` is longer than 255 bytes (RFC 9380 limit).
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> EdwardsPoint {
+    let uniform = expand_message_xmd(msg, dst);
+    let [q0, q1] = [0, 1].map(|i| {
+        let chunk = &uniform[i * FIELD_ELEMENT_BYTES..(i + 1) * FIELD_ELEMENT_BYTES];
+        map_to_curve(&(BigUint::from_bytes_be(chunk) % field_prime()))
+    });
+    (q0 + q1).mul_by_cofactor()
+}
+
+/// `expand_message_xmd` with SHA-512, producing two field elements' worth of bytes.
+fn expand_message_xmd(msg: &[u8], dst: &[u8]) -> [u8; 2 * FIELD_ELEMENT_BYTES] {
+    const LEN: usize = 2 * FIELD_ELEMENT_BYTES;
+    const BLOCK_BYTES: usize = 128;
+    const DIGEST_BYTES: usize = 64;
+
+    let dst_len = u8::try_from(dst.len()).expect("DST must be at most 255 bytes");
+    let dst_prime = |hasher: Sha512| hasher.chain_update(dst).chain_update([dst_len]);
+
+    let b0 = dst_prime(
+        Sha512::new()
+            .chain_update([0u8; BLOCK_BYTES])
+            .chain_update(msg)
+            .chain_update((LEN as u16).to_be_bytes())
+            .chain_update([0u8]),
+    )
+    .finalize();
+
+    let mut out = [0u8; LEN];
+    let mut prev = dst_prime(Sha512::new().chain_update(b0).chain_update([1u8])).finalize();
+    for (i, chunk) in out.chunks_mut(DIGEST_BYTES).enumerate() {
+        if i > 0 {
+            let mixed: Vec<u8> = b0.iter().zip(prev.iter()).map(|(a, b)| a ^ b).collect();
+            prev = dst_prime(
+                Sha512::new()
+                    .chain_update(mixed)
+                    .chain_update([i as u8 + 1]),
+            )
+            .finalize();
+        }
+        chunk.copy_from_slice(&prev[..chunk.len()]);
+    }
+    out
+}
+
+/// Elligator 2 onto curve25519, then the rational map to edwards25519.
+fn map_to_curve(u: &BigUint) -> EdwardsPoint {
+    let p = field_prime();
+    let a = BigUint::from(MONTGOMERY_A);
+    let rhs = |x: &BigUint| (x * x * x + &a * x * x + x) % &p;
+
+    // x1 = -A / (1 + Z·u²) with Z = 2 (inv0 semantics: x1 = -A if the denominator is 0)
+    let den = (BigUint::one() + 2u32 * u * u) % &p;
+    let mut x1 = sub(&BigUint::zero(), &(&a * inv(&den, &p)), &p);
+    if x1.is_zero() {
+        x1 = sub(&BigUint::zero(), &a, &p);
+    }
+    let x2 = sub(&BigUint::zero(), &(&x1 + &a), &p);
+
+    let (s, t) = match sqrt(&rhs(&x1), &p) {
+        Some(y) => (x1, with_sign(y, true, &p)),
+        None => {
+            let y = sqrt(&rhs(&x2), &p).expect("exactly one of g(x1), g(x2) is square");
+            (x2, with_sign(y, false, &p))
+        }
+    };
+
+    // (v, w) = (sqrt(-486664)·s/t, (s - 1)/(s + 1)); exceptional cases map to the identity
+    let s_plus_one = (&s + 1u32) % &p;
+    if t.is_zero() || s_plus_one.is_zero() {
+        return edwards_from_affine(&BigUint::zero(), &BigUint::one());
+    }
+    let c1 = with_sign(
+        sqrt(
+            &sub(&BigUint::zero(), &BigUint::from(MONTGOMERY_A + 2), &p),
+            &p,
+        )
+        .expect("-486664 is square mod p"),
+        false,
+        &p,
+    );
+    let v = c1 * &s % &p * inv(&t, &p) % &p;
+    let w = sub(&s, &BigUint::one(), &p) * inv(&s_plus_one, &p) % &p;
+    edwards_from_affine(&v, &w)
+}
+
+fn edwards_from_affine(x: &BigUint, y: &BigUint) -> EdwardsPoint {
+    let mut bytes = [0u8; 32];
+    let y_bytes = y.to_bytes_le();
+    bytes[..y_bytes.len()].copy_from_slice(&y_bytes);
+    if x.bit(0) {
+        bytes[31] |= 0x80;
+    }
+    CompressedEdwardsY(bytes)
+        .decompress()
+        .expect("rational map output is on edwards25519")
+}
+
+/// Square root mod p (p ≡ 5 mod 8), if one exists.
+fn sqrt(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let mut x = a.modpow(&((p + 3u32) >> 3), p);
+    if &x * &x % p != *a {
+        let sqrt_m1 = BigUint::from(2u32).modpow(&((p - 1u32) >> 2), p);
+        x = x * sqrt_m1 % p;
+    }
+    (&x * &x % p == *a).then_some(x)
+}
+
+/// The root of `y` whose `sgn0` (parity) is `odd`.
+fn with_sign(y: BigUint, odd: bool, p: &BigUint) -> BigUint {
+    if y.bit(0) == odd {
+        y
+    } else {
+        (p - y) % p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weierstrass::edwards_affine;
+
+    const RFC_DST: &[u8] = b"QUUX-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_RO_";
+
+    fn affine_hex(point: &EdwardsPoint) -> (String, String) {
+        let (x, y) = edwards_affine(point);
+        (format!("{:064x}", x), format!("{:064x}", y))
+    }
+
+    #[test]
+    fn test_rfc9380_vectors() {
+        // RFC 9380, Appendix J.5.1
+        assert_eq!(
+            affine_hex(&hash_to_curve(b"", RFC_DST)),
+            (
+                "3c3da6925a3c3c268448dcabb47ccde5439559d9599646a8260e47b1e4822fc6".to_string(),
+                "09a6c8561a0b22bef63124c588ce4c62ea83a3c899763af26d795302e115dc21".to_string(),
+            )
+        );
+        assert_eq!(
+            affine_hex(&hash_to_curve(b"abc", RFC_DST)),
+            (
+                "608040b42285cc0d72cbb3985c6b04c935370c7361f4b7fbdb1ae7f8c1a8ecad".to_string(),
+                "1a8395b88338f22e435bbd301183e7f20a5f9de643f11882fb237f88268a5531".to_string(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_output_is_prime_order() {
+        let point = hash_to_curve(b"DLEQ_SECOND_BASE_V1", RFC_DST);
+        assert!(crate::encoding::check_prime_order_point(&point).is_ok());
+    }
+}
@@ -0,0 +1,279 @@
+//! Hint generation for the Cairo AtomicLock verifier.
+//!
+//! Rust port of the Python hint tools (`tools/generate_hints_from_test_vectors.py`,
+//! `tools/generate_dleq_hints.py`, `tools/generate_correct_sqrt_hints.py`).
+//! Produces hints in the exact felt layout the contract expects:
+//!
+//! - **Sqrt hints**: the twisted Edwards x-coordinate of a point (u256), used by
+//!   Garaga's `decompress_edwards_pt_from_y_compressed_le_into_weirstrass_point`
+//! - **Fake-GLV hints**: 10 felts `[Q.x limbs (4), Q.y limbs (4), s1, s2_encoded]`
+//!   where Q is the Weierstrass point `scalar·P` as u384 (4×96-bit limbs) and
+//!   `s1 + scalar·s2 ≡ 0 (mod ℓ)` with `|s1|, |s2| < √ℓ`. A negative `s2` is
+//!   encoded as `2^128 + |s2|`.
+//!
+//! DLEQ MSM hints are computed for the low 128 bits of `c` and `s`, matching
+//! the truncation in the contract's `_verify_dleq_proof`.
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Zero};
+use thiserror::Error;
+
+use crate::dleq::{get_second_generator, DleqProof};
+use crate::encoding::{check_prime_order_point, EncodingError};
+use crate::weierstrass::{edwards_affine, to_weierstrass, u384_limbs};
+
+/// Ed25519 group order ℓ (big-endian hex).
+const GROUP_ORDER_HEX: &[u8] = b"1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed";
+
+/// Hint generation errors.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum HintError {
+    #[error("Invalid point for hint generation: {0}")]
+    InvalidPoint(#[from] EncodingError),
+    #[error("Hint scalar cannot be zero")]
+    ZeroScalar,
+}
+
+/// Fake-GLV hint for a single-scalar MSM `scalar·P`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeGlvHint {
+    /// Q = scalar·P (Weierstrass x)
+    pub q_x: BigUint,
+    /// Q = scalar·P (Weierstrass y)
+    pub q_y: BigUint,
+    /// First decomposition scalar
+    pub s1: BigUint,
+    /// Second decomposition scalar with its sign folded into bit 128
+    pub s2_encoded: BigUint,
+}
+
+impl FakeGlvHint {
+    /// The 10 hint felts in contract order.
+    pub fn felts(&self) -> [BigUint; 10] {
+        let [x0, x1, x2, x3] = u384_limbs(&self.q_x);
+        let [y0, y1, y2, y3] = u384_limbs(&self.q_y);
+        [
+            x0,
+            x1,
+            x2,
+            x3,
+            y0,
+            y1,
+            y2,
+            y3,
+            self.s1.clone(),
+            self.s2_encoded.clone(),
+        ]
+    }
+
+    /// The 10 hint felts as `0x`-prefixed hex strings.
+    pub fn to_hex_felts(&self) -> [String; 10] {
+        self.felts().map(|felt| format!("{:#x}", felt))
+    }
+}
+
+/// Fake-GLV hints for the four single-scalar MSMs of DLEQ verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DleqMsmHints {
+    /// Hint for s·G
+    pub s_hint_for_g: FakeGlvHint,
+    /// Hint for s·Y
+    pub s_hint_for_y: FakeGlvHint,
+    /// Hint for (-c)·T
+    pub c_neg_hint_for_t: FakeGlvHint,
+    /// Hint for (-c)·U
+    pub c_neg_hint_for_u: FakeGlvHint,
+}
+
+/// Sqrt hint for Edwards decompression: the x-coordinate as u256 (little-endian).
+pub fn sqrt_hint(point: &EdwardsPoint) -> [u8; 32] {
+    let (x, _) = edwards_affine(point);
+    let mut out = [0u8; 32];
+    let bytes = x.to_bytes_le();
+    out[..bytes.len()].copy_from_slice(&bytes);
+    out
+}
+
+/// Fake-GLV hint for `scalar·point`.
+///
+/// # Errors
+///
+/// Returns `HintError::InvalidPoint` if `point` is the identity or has a
+/// torsion component, and `HintError::ZeroScalar` if `scalar` is zero.
+pub fn fake_glv_hint(point: &EdwardsPoint, scalar: &Scalar) -> Result<FakeGlvHint, HintError> {
+    check_prime_order_point(point)?;
+    if *scalar == Scalar::ZERO {
+        return Err(HintError::ZeroScalar);
+    }
+
+    let (q_x, q_y) = to_weierstrass(&(point * scalar));
+    let (s1, s2) = fake_glv_decompose(&BigUint::from_bytes_le(scalar.as_bytes()));
+    let s2_encoded = match s2.sign() {
+        Sign::Minus => (BigUint::one() << 128u32) + s2.magnitude(),
+        _ => s2.magnitude().clone(),
+    };
+
+    Ok(FakeGlvHint {
+        q_x,
+        q_y,
+        s1,
+        s2_encoded,
+    })
+}
+
+/// MSM hints for verifying `proof` against `adaptor_point` in Cairo.
+pub fn dleq_msm_hints(
+    proof: &DleqProof,
+    adaptor_point: &EdwardsPoint,
+) -> Result<DleqMsmHints, HintError> {
+    let s = truncate_128(&proof.response);
+    let c_neg = -truncate_128(&proof.challenge);
+
+    Ok(DleqMsmHints {
+        s_hint_for_g: fake_glv_hint(&ED25519_BASEPOINT_POINT, &s)?,
+        s_hint_for_y: fake_glv_hint(&get_second_generator(), &s)?,
+        c_neg_hint_for_t: fake_glv_hint(adaptor_point, &c_neg)?,
+        c_neg_hint_for_u: fake_glv_hint(&proof.second_point, &c_neg)?,
+    })
+}
+
+/// Low 128 bits of a scalar, as the contract uses it.
+pub fn truncate_128(scalar: &Scalar) -> Scalar {
+    let low: [u8; 16] = scalar.as_bytes()[..16].try_into().expect("16 bytes");
+    Scalar::from(u128::from_le_bytes(low))
+}
+
+/// Half-GCD decomposition: s1 ≡ -scalar·s2 (mod ℓ) with |s1|, |s2| < √ℓ.
+fn fake_glv_decompose(scalar: &BigUint) -> (BigUint, BigInt) {
+    let n = group_order();
+    let bound = n.sqrt();
+
+    let (mut r0, mut r1) = (n, scalar.clone());
+    let (mut t0, mut t1) = (BigInt::zero(), BigInt::one());
+    while r1 >= bound {
+        let q = &r0 / &r1;
+        let r2 = &r0 - &q * &r1;
+        let t2 = &t0 - BigInt::from(q) * &t1;
+        r0 = std::mem::replace(&mut r1, r2);
+        t0 = std::mem::replace(&mut t1, t2);
+    }
+    // r1 = t1·scalar (mod ℓ), so s1 = r1, s2 = -t1
+    (r1, -t1)
+}
+
+fn group_order() -> BigUint {
+    BigUint::parse_bytes(GROUP_ORDER_HEX, 16).expect("valid group order")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+
+    fn hex_felts(felts: &[&str]) -> Vec<String> {
+        felts.iter().map(|f| f.to_string()).collect()
+    }
+
+    /// T from rust/test_vectors.json
+    fn test_vector_t() -> EdwardsPoint {
+        let bytes: [u8; 32] =
+            hex::decode("85ce3cf603efcf45b599cce75369e854823864e471ad297d955f32db0ade7d42")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        CompressedEdwardsY(bytes).decompress().unwrap()
+    }
+
+    #[test]
+    fn test_sqrt_hint_matches_cairo_vector() {
+        // TEST_ADAPTOR_POINT_SQRT_HINT in cairo/tests/test_e2e_dleq.cairo
+        let hint = sqrt_hint(&test_vector_t());
+        assert_eq!(
+            u128::from_le_bytes(hint[..16].try_into().unwrap()),
+            0x448c18dcf34127e112ff945a65defbfc
+        );
+        assert_eq!(
+            u128::from_le_bytes(hint[16..].try_into().unwrap()),
+            0x17611da35f39a2a5e3a9fddb8d978e4f
+        );
+    }
+
+    #[test]
+    fn test_s_hint_for_g_matches_cairo_vector() {
+        // s_hint_for_g in cairo/tests/test_e2e_dleq.cairo (negative s2)
+        let s = Scalar::from(0xc09b9a31d72db277d1bb402e80ef5008u128);
+        let hint = fake_glv_hint(&ED25519_BASEPOINT_POINT, &s).unwrap();
+        assert_eq!(
+            hint.to_hex_felts().to_vec(),
+            hex_felts(&[
+                "0xa82b6800cf6fafb9e422ff00",
+                "0xa9d32170fa1d6e70ce9f5875",
+                "0x38d522e54f3cc905",
+                "0x0",
+                "0x6632b6936c8a0092f2fa8193",
+                "0x48849326ffd29b0fd452c82e",
+                "0x1cb22722b8aeac6d",
+                "0x0",
+                "0x3ce8213ee078382bd7862b141d23a01e",
+                "0x12a88328ee6fe07c656e9f1f11921d2ff",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_c_neg_hint_for_t_matches_cairo_vector() {
+        let c_neg = -Scalar::from(0xff93d53eda6f2910e3a1313a226533c5u128);
+        let hint = fake_glv_hint(&test_vector_t(), &c_neg).unwrap();
+        let felts = hint.to_hex_felts();
+        assert_eq!(felts[0], "0xcc7bbab2a86720f06fa72b5a");
+        assert_eq!(felts[8], "0xba5f45d69eaafbaaa06091a65e2873d");
+        assert_eq!(felts[9], "0x1301450999c6615fa5bded0ada7e22902");
+    }
+
+    #[test]
+    fn test_adaptor_hint_matches_python_tool() {
+        // tools/ed25519_test_data.json (positive s2)
+        let scalar_bytes: [u8; 32] =
+            hex::decode("d8f5bb93d38b04c6c239cf2464fd33a611bb056fbecc85d2cfde4d8c5885be01")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let scalar = Scalar::from_canonical_bytes(scalar_bytes).unwrap();
+        let hint = fake_glv_hint(&ED25519_BASEPOINT_POINT, &scalar).unwrap();
+        assert_eq!(hint.s1.to_string(), "1959240553663291438546986681775096666");
+        assert_eq!(
+            hint.s2_encoded.to_string(),
+            "78290750389515040380094360278291046925"
+        );
+        assert_eq!(hint.felts()[0].to_string(), "31832034048510174251095831294");
+    }
+
+    #[test]
+    fn test_decomposition_relation_holds() {
+        let n = group_order();
+        for k in [1u64, 2, 12345, u64::MAX] {
+            let scalar = Scalar::from(k) * Scalar::from(0x1234_5678_9abc_def0u64);
+            let s = BigUint::from_bytes_le(scalar.as_bytes());
+            let (s1, s2) = fake_glv_decompose(&s);
+            let lhs = (BigInt::from(s1.clone()) + BigInt::from(s) * &s2) % BigInt::from(n.clone());
+            assert!(lhs.is_zero(), "s1 + s·s2 must vanish mod ℓ");
+            assert!(s1 < n.sqrt());
+            assert!(s2.magnitude() < &(BigUint::one() << 128u32));
+        }
+    }
+
+    #[test]
+    fn test_rejects_zero_scalar_and_identity() {
+        assert_eq!(
+            fake_glv_hint(&ED25519_BASEPOINT_POINT, &Scalar::ZERO),
+            Err(HintError::ZeroScalar)
+        );
+        assert_eq!(
+            fake_glv_hint(&EdwardsPoint::default(), &Scalar::ONE),
+            Err(HintError::InvalidPoint(EncodingError::IdentityPoint))
+        );
+    }
+}
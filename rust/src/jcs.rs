@@ -0,0 +1,253 @@
+//! RFC 8785 JSON Canonicalization Scheme (JCS).
+//!
+//! Signed payloads (offers, receipts) are signed over their canonical form,
+//! so a signature made here verifies in any other JCS implementation no
+//! matter how the JSON was re-encoded in transit:
+//!
+//! - object members sorted by the UTF-16 code units of their names
+//! - no insignificant whitespace
+//! - strings escaped exactly as ECMAScript `JSON.stringify` does
+//! - numbers formatted as ECMAScript `Number.prototype.toString`
+//!
+//! JCS numbers are IEEE 754 doubles. Integers beyond ±2^53 cannot be
+//! represented exactly and are rejected; send amounts and felts as strings.
+
+use std::fmt::Write;
+
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+/// Largest integer a double holds exactly (2^53).
+pub const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// Canonicalization errors.
+#[derive(Debug, Error)]
+pub enum JcsError {
+    #[error("serialization failed: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("number {0} is not exactly representable as an IEEE 754 double")]
+    UnsafeInteger(String),
+}
+
+/// Canonical JSON bytes of `value`, the input to signing and verification.
+pub fn to_canonical_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, JcsError> {
+    Ok(to_canonical_string(value)?.into_bytes())
+}
+
+/// Canonical JSON text of `value`.
+pub fn to_canonical_string<T: Serialize + ?Sized>(value: &T) -> Result<String, JcsError> {
+    let value = serde_json::to_value(value)?;
+    let mut out = String::new();
+    write_value(&mut out, &value)?;
+    Ok(out)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), JcsError> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(out, n)?,
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(members) => write_object(out, members)?,
+    }
+    Ok(())
+}
+
+fn write_object(out: &mut String, members: &Map<String, Value>) -> Result<(), JcsError> {
+    let mut sorted: Vec<(&String, &Value)> = members.iter().collect();
+    sorted.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+    out.push('{');
+    for (i, (name, value)) in sorted.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(out, name);
+        out.push(':');
+        write_value(out, value)?;
+    }
+    out.push('}');
+    Ok(())
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_number(out: &mut String, n: &Number) -> Result<(), JcsError> {
+    let value = if let Some(u) = n.as_u64() {
+        if u > MAX_SAFE_INTEGER {
+            return Err(JcsError::UnsafeInteger(n.to_string()));
+        }
+        u as f64
+    } else if let Some(i) = n.as_i64() {
+        if i.unsigned_abs() > MAX_SAFE_INTEGER {
+            return Err(JcsError::UnsafeInteger(n.to_string()));
+        }
+        i as f64
+    } else {
+        // serde_json never holds NaN or infinities
+        n.as_f64().unwrap_or_default()
+    };
+    out.push_str(&format_double(value));
+    Ok(())
+}
+
+/// ECMAScript `Number.prototype.toString` for a finite double.
+fn format_double(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string(); // also -0
+    }
+
+    // Rust's `{:e}` yields the shortest round-tripping digits, as ES requires
+    let sci = format!("{:e}", value.abs());
+    let (mantissa, exponent) = sci.split_once('e').expect("`{:e}` always has an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().expect("`{:e}` exponent is an integer");
+
+    // value = 0.digits × 10^n
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+    let mut out = String::new();
+    if value < 0.0 {
+        out.push('-');
+    }
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat((-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}{}", if n > 0 { '+' } else { '-' }, (n - 1).abs());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rfc8785_examples() {
+        // RFC 8785 §3.2.2
+        let value = json!({
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/",
+            "literals": [null, true, false]
+        });
+        assert_eq!(
+            to_canonical_string(&value).unwrap(),
+            "{\"literals\":[null,true,false],\
+             \"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\
+             \"string\":\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}"
+        );
+
+        // RFC 8785 §3.2.3: UTF-16 order puts the emoji (D83D) before U+FB33
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{0080}": "Control",
+            "\u{00f6}": "Latin Small Letter O With Diaeresis"
+        });
+        let keys: Vec<String> = to_canonical_string(&value)
+            .unwrap()
+            .split(",\"")
+            .map(|member| {
+                member
+                    .trim_start_matches("{\"")
+                    .split('"')
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "\\r",
+                "1",
+                "\u{0080}",
+                "\u{00f6}",
+                "\u{20ac}",
+                "\u{1f600}",
+                "\u{fb33}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_number_formatting() {
+        let cases: [(f64, &str); 10] = [
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e+21"),
+            (1e-6, "0.000001"),
+            (1e-7, "1e-7"),
+            (123456.789, "123456.789"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(format_double(value), expected, "{value:e}");
+        }
+
+        assert_eq!(
+            to_canonical_string(&json!([9007199254740992u64])).unwrap(),
+            "[9007199254740992]"
+        );
+        assert!(matches!(
+            to_canonical_string(&json!({"amount": 9007199254740993u64})),
+            Err(JcsError::UnsafeInteger(_))
+        ));
+        assert_eq!(
+            to_canonical_string(&json!({"b": [], "a": {}})).unwrap(),
+            "{\"a\":{},\"b\":[]}"
+        );
+    }
+}
@@ -4,42 +4,89 @@
 //! SHA-256 digest, and format outputs for Cairo tests.
 //!
 //! Also includes adaptor signature support for Monero atomic swaps.
+//!
+//! Wallet integrators should start from [`prelude`], which re-exports the
+//! types needed to run a swap.
 
 pub mod adaptor;
+pub mod cairo_serde;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod contracts;
 pub mod dleq;
+pub mod encoding;
+pub mod hash_backend;
+pub mod hash_to_curve;
+pub mod hints;
+pub mod jcs;
 pub mod monero;
+pub mod monero_daemon;
 pub mod monero_wallet;
+pub mod prelude;
 pub mod starknet;
+pub mod store;
+pub mod swap;
+pub mod vectors;
+pub mod weierstrass;
 // TODO: Uncomment when Poseidon is fully implemented
 // pub mod poseidon;
 
-pub use dleq::{generate_dleq_proof, DleqProof, DleqError};
+pub use dleq::{
+    generate_dleq_proof, verify_dleq_batch, verify_dleq_batch_cached, verify_dleq_proof, DleqError,
+    DleqProof,
+};
+#[cfg(feature = "multi-lane-hash")]
+pub use hash_backend::MultiLaneBackend;
+pub use hash_backend::{HashBackend, PortableBackend};
 pub use monero::SwapKeyPair;
+pub use swap_common::format;
 #[cfg(feature = "full-integration")]
 pub mod monero_full;
 #[cfg(feature = "full-integration")]
 pub mod starknet_full;
 
-use std::env;
-use std::path::PathBuf;
-use std::process::Command;
-
 use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
-use curve25519_dalek::edwards::EdwardsPoint;
 use curve25519_dalek::scalar::Scalar;
 use rand::rngs::OsRng;
-use rand::RngCore;
-use serde::{Deserialize, Serialize};
+use rand::{CryptoRng, RngCore};
+use serde::Serialize;
 use sha2::{Digest, Sha256};
-use zeroize::Zeroizing;
+use std::fmt;
+use thiserror::Error;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::dleq::DleqProofSerialized;
+use crate::encoding::{decode_scalar, EncodingError};
+use crate::hints::HintError;
+
+/// Swap secret generation errors.
+#[derive(Debug, Error)]
+pub enum SwapSecretError {
+    #[error("secret scalar is zero")]
+    ZeroScalar,
+
+    #[error("invalid secret: {0}")]
+    Encoding(#[from] EncodingError),
+
+    #[error("DLEQ proof generation failed: {0}")]
+    Dleq(#[from] DleqError),
+
+    #[error("hint generation failed: {0}")]
+    Hint(#[from] HintError),
+}
 
 /// Output structure for JSON serialization.
-#[derive(Serialize)]
+///
+/// The secret itself is only reachable through [`SwapSecret::expose_secret`]
+/// and [`SwapSecret::expose_cairo_secret_literal`]; `Debug` redacts it and
+/// every field is zeroized on drop. Serializing still writes the secret, for
+/// the CLI's JSON output.
+#[derive(Serialize, Zeroize, ZeroizeOnDrop)]
 pub struct SwapSecret {
-    pub secret_hex: String,
+    secret_hex: String,
     pub hash_u32_words: [u32; 8],
     pub cairo_hash_literal: String,
-    pub cairo_secret_literal: String,
+    cairo_secret_literal: String,
     pub adaptor_point_x_limbs: [String; 4],
     pub adaptor_point_y_limbs: [String; 4],
     pub dleq_second_point_x_limbs: [String; 4],
@@ -47,284 +94,414 @@ pub struct SwapSecret {
     pub dleq_challenge: String,
     pub dleq_response: String,
     pub fake_glv_hint: [String; 10],
+    /// Compressed adaptor point T = t·G (hex)
+    pub adaptor_point_compressed: String,
+    /// Sqrt hint for decompressing T in Cairo (hex, u256 little-endian)
+    pub adaptor_point_sqrt_hint: String,
+    /// Full DLEQ proof binding T to the hashlock, for the taker
+    #[zeroize(skip)]
+    pub dleq_proof: DleqProofSerialized,
+    /// Sqrt hints for U, R1 and R2, in that order (hex)
+    pub dleq_sqrt_hints: [String; 3],
 }
 
-/// Python tool output structure (partial, for adaptor point/hint extraction).
-#[derive(Deserialize)]
-struct PythonToolOutput {
-    adaptor_point: AdaptorPointData,
-    fake_glv_hint: FakeGlvHintData,
-}
+impl SwapSecret {
+    /// Cairo inputs for an existing secret `t` (e.g. generated elsewhere or
+    /// restored from a backup), given as its canonical little-endian bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SwapSecretError::Encoding` if the bytes are not a canonical
+    /// scalar (≥ ℓ), since the hashlock covers the raw bytes and the
+    /// contract would disagree with the reduced scalar,
+    /// `SwapSecretError::ZeroScalar` for t = 0, or the DLEQ or hint error if
+    /// deriving the contract inputs fails.
+    pub fn from_secret_bytes(secret_bytes: [u8; 32]) -> Result<Self, SwapSecretError> {
+        let scalar = decode_scalar(secret_bytes)?;
+        if scalar == Scalar::ZERO {
+            return Err(SwapSecretError::ZeroScalar);
+        }
 
-#[derive(Deserialize)]
-struct AdaptorPointData {
-    cairo_x: String,
-    cairo_y: String,
-}
+        // Fake-GLV hint for the contract's MSM check t·G == T (computed natively).
+        let fake_glv_hint = hints::fake_glv_hint(&ED25519_BASEPOINT_POINT, &scalar)?.to_hex_felts();
 
-#[derive(Deserialize)]
-struct FakeGlvHintData {
-    #[serde(deserialize_with = "deserialize_felts")]
-    felts: Vec<String>,
-}
+        // SHA-256 hash.
+        let hash_bytes: [u8; 32] = Sha256::digest(&secret_bytes).into();
+        let hashlock: [u8; 32] = hash_bytes;
+
+        // Convert to 8 x u32 (big-endian).
+        let hash_words: [u32; 8] = core::array::from_fn(|i| {
+            let start = i * 4;
+            u32::from_be_bytes(hash_bytes[start..start + 4].try_into().unwrap())
+        });
+
+        // Generate DLEQ proof (wrap scalar in Zeroizing for memory safety)
+        // Note: secret_bytes is already raw bytes here, which is correct for Cairo compatibility
+        let secret_zeroizing = Zeroizing::new(scalar);
+        let adaptor_point_edwards = ED25519_BASEPOINT_POINT * *secret_zeroizing;
+        let (adaptor_point_x_limbs, adaptor_point_y_limbs) =
+            weierstrass::to_cairo_limbs(&adaptor_point_edwards);
+        let dleq_proof = generate_dleq_proof(
+            &secret_zeroizing,
+            &secret_bytes,
+            &adaptor_point_edwards,
+            &hashlock,
+        )?;
+
+        // Convert DLEQ second point to Weierstrass u384 limbs
+        let (dleq_second_point_x_limbs, dleq_second_point_y_limbs) =
+            weierstrass::to_cairo_limbs(&dleq_proof.second_point);
+
+        // Format DLEQ challenge and response as hex strings (felt252 in Cairo)
+        // Convert scalar bytes to hex, then format as felt252 (big-endian u256)
+        let challenge_bytes = dleq_proof.challenge.to_bytes();
+        let response_bytes = dleq_proof.response.to_bytes();
+        let dleq_challenge = format!("0x{}", hex::encode(challenge_bytes));
+        let dleq_response = format!("0x{}", hex::encode(response_bytes));
+        let dleq_sqrt_hints = [dleq_proof.second_point, dleq_proof.r1, dleq_proof.r2]
+            .map(|point| hex::encode(hints::sqrt_hint(&point)));
 
-fn deserialize_felts<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    use serde::de::Error;
-    // Parse as Value first to get raw access
-    let value = serde_json::Value::deserialize(deserializer)?;
-    
-    // Extract the raw JSON string and parse manually to preserve large integers
-    let _json_str = serde_json::to_string(&value).map_err(D::Error::custom)?;
-    
-    // Parse the array manually from raw JSON
-    // This is a workaround for serde_json converting large integers to f64
-    // We'll use a simpler approach: parse as Value and handle each element
-    let array = value
-        .as_array()
-        .ok_or_else(|| D::Error::custom("Expected array"))?;
-    
-    array
-        .iter()
-        .map(|v| {
-            match v {
-                serde_json::Value::String(s) => {
-                    if s.starts_with("0x") {
-                        Ok(s.clone())
-                    } else {
-                        Ok(format!("0x{}", s))
-                    }
-                }
-                serde_json::Value::Number(n) => {
-                    // Try u64 first
-                    if let Some(u) = n.as_u64() {
-                        Ok(format!("0x{:x}", u))
-                    } else {
-                        // For very large numbers, Python now outputs as strings
-                        // This branch shouldn't be reached, but handle it anyway
-                        let num_str = n.to_string();
-                        if num_str.contains('e') || num_str.contains('E') || num_str.contains('.') {
-                            Err(D::Error::custom(format!(
-                                "Number lost precision: {}. Large numbers should be strings in JSON.",
-                                num_str
-                            )))
-                        } else {
-                            // Large numbers should be strings in JSON (Python outputs them as strings)
-                            // If we get here, it's a number that doesn't fit in u64 but isn't scientific notation
-                            // Return error - Python tool should output as string
-                            Err(D::Error::custom(format!(
-                                "Number too large: {}. Python tool should output large numbers as strings.",
-                                num_str
-                            )))
-                        }
-                    }
-                }
-                _ => Err(D::Error::custom("Felt must be string or number")),
-            }
+        // Format for Cairo.
+        let cairo_hash_literal = format!(
+            "array![{}].span()",
+            hash_words
+                .iter()
+                .map(|w| format!("{}_u32", w))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let cairo_secret_literal = format!(
+            "\"{}\"",
+            secret_bytes
+                .iter()
+                .map(|b| format!("\\x{:02x}", b))
+                .collect::<String>()
+        );
+
+        Ok(SwapSecret {
+            secret_hex: hex::encode(secret_bytes),
+            hash_u32_words: hash_words,
+            cairo_hash_literal,
+            cairo_secret_literal,
+            adaptor_point_x_limbs,
+            adaptor_point_y_limbs,
+            dleq_second_point_x_limbs,
+            dleq_second_point_y_limbs,
+            dleq_challenge,
+            dleq_response,
+            fake_glv_hint,
+            adaptor_point_compressed: hex::encode(adaptor_point_edwards.compress().to_bytes()),
+            adaptor_point_sqrt_hint: hex::encode(hints::sqrt_hint(&adaptor_point_edwards)),
+            dleq_proof: dleq_proof.to_serializable(),
+            dleq_sqrt_hints,
         })
-        .collect()
-}
+    }
 
-/// Call Python tool to generate adaptor point and fake-GLV hint from secret.
-/// Returns (x_limbs, y_limbs, fake_glv_hint) or error if Python tool unavailable.
-fn generate_adaptor_point_from_python(
-    secret_hex: &str,
-) -> Result<([String; 4], [String; 4], [String; 10]), String> {
-    // Find tools directory relative to Cargo.toml
-    let mut tools_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    tools_dir.pop(); // Go up from rust/ to repo root
-    tools_dir.push("tools");
-    
-    let script_path = tools_dir.join("generate_ed25519_test_data.py");
-    if !script_path.exists() {
-        return Err("Python tool not found".to_string());
+    /// AtomicLock constructor calldata locking `amount` of `token` until
+    /// `lock_until`, as hex felts.
+    ///
+    /// # Errors
+    ///
+    /// Returns the DLEQ or hint error if the embedded proof cannot be
+    /// decoded or its MSM hints cannot be derived.
+    pub fn constructor_calldata(
+        &self,
+        lock_until: u64,
+        token: &str,
+        amount: u128,
+    ) -> Result<Vec<String>, SwapSecretError> {
+        let secret_bytes: [u8; 32] = hex::decode(&self.secret_hex)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .expect("secret_hex holds 32 hex-encoded bytes");
+        let secret = Zeroizing::new(decode_scalar(secret_bytes)?);
+        let proof = DleqProof::from_serializable(self.dleq_proof.clone())?;
+        let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+        Ok(contracts::atomic_lock::constructor_calldata(
+            &hashlock, lock_until, token, amount, &secret, &proof,
+        )?)
     }
 
-    // Call Python tool: uv run python generate_ed25519_test_data.py <secret_hex> --save
-    let output = Command::new("uv")
-        .args(&[
-            "run",
-            "python",
-            script_path.to_str().unwrap(),
-            secret_hex,
-            "--save",
-        ])
-        .current_dir(&tools_dir)
-        .output()
-        .map_err(|e| format!("Failed to run Python tool: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Python tool failed: {}", stderr));
+    /// [`SwapSecret::constructor_calldata`] as a Cairo `array![...]` literal,
+    /// ready to paste into a contract test.
+    pub fn cairo_constructor_calldata(
+        &self,
+        lock_until: u64,
+        token: &str,
+        amount: u128,
+    ) -> Result<String, SwapSecretError> {
+        let calldata = self.constructor_calldata(lock_until, token, amount)?;
+        Ok(format!("array![{}]", calldata.join(", ")))
+    }
+
+    /// A Cairo test fixture module with the secret, hashlock, points, sqrt
+    /// hints, DLEQ challenge/response and fake-GLV hint of this secret.
+    ///
+    /// Points and hints are `u256` constants split like the constructor
+    /// arguments (little-endian bytes, low half first); the challenge and
+    /// response are their low 128 bits, as the contract compares them.
+    pub fn cairo_fixture(&self) -> String {
+        let adaptor_point: [u8; 32] = hex::decode(&self.adaptor_point_compressed)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .expect("adaptor_point_compressed holds 32 hex-encoded bytes");
+        let sqrt_hint = |hint: &str| -> [u8; 32] {
+            hex::decode(hint)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .expect("sqrt hints hold 32 hex-encoded bytes")
+        };
+        let proof = &self.dleq_proof;
+
+        let mut out = String::from(
+            "//! Swap secret fixture generated by `xmr-secret-gen --format cairo-file`.\n\
+             //!\n\
+             //! Holds a real secret: only use it in tests.\n\n\
+             use core::integer::u256;\n",
+        );
+        let points = [
+            (
+                "T",
+                &adaptor_point,
+                sqrt_hint(&self.adaptor_point_sqrt_hint),
+            ),
+            (
+                "U",
+                &proof.second_point,
+                sqrt_hint(&self.dleq_sqrt_hints[0]),
+            ),
+            ("R1", &proof.r1, sqrt_hint(&self.dleq_sqrt_hints[1])),
+            ("R2", &proof.r2, sqrt_hint(&self.dleq_sqrt_hints[2])),
+        ];
+        for (name, compressed, hint) in points {
+            out += &format!(
+                "\npub const {name}_COMPRESSED: u256 = {};\npub const {name}_SQRT_HINT: u256 = {};\n",
+                cairo_u256(compressed),
+                cairo_u256(&hint),
+            );
+        }
+        out += &format!(
+            "\npub const CHALLENGE_LOW: felt252 = {};\npub const RESPONSE_LOW: felt252 = {};\n",
+            low_u128_hex(&proof.challenge),
+            low_u128_hex(&proof.response),
+        );
+        out += &format!(
+            "\npub fn secret() -> ByteArray {{\n    {}\n}}\n",
+            self.cairo_secret_literal
+        );
+        out += &format!(
+            "\npub fn hashlock() -> Span<u32> {{\n    {}\n}}\n",
+            self.cairo_hash_literal
+        );
+        out += &format!(
+            "\npub fn fake_glv_hint() -> Span<felt252> {{\n    array![{}].span()\n}}\n",
+            self.fake_glv_hint.join(", ")
+        );
+        out
     }
 
-    // Read JSON file written by Python tool
-    let json_path = tools_dir.join("ed25519_test_data.json");
-    let json_content = std::fs::read_to_string(&json_path)
-        .map_err(|e| format!("Failed to read JSON file: {}", e))?;
-    
-    let data: PythonToolOutput = serde_json::from_str(&json_content)
-        .map_err(|e| format!("Failed to parse Python tool output: {}", e))?;
-
-    // Extract x_limbs from cairo_x: "(0x..., 0x..., 0x..., 0x...)"
-    let x_str = data
-        .adaptor_point
-        .cairo_x
-        .trim_matches(|c| c == '(' || c == ')');
-    let x_limbs: Vec<String> = x_str.split(',').map(|s| s.trim().to_string()).collect();
-    if x_limbs.len() != 4 {
-        return Err("Invalid x_limbs length".to_string());
+    /// The secret scalar t as 64 hex characters; whoever holds it can claim
+    /// the Starknet lock.
+    pub fn expose_secret(&self) -> &str {
+        &self.secret_hex
     }
 
-    // Extract y_limbs from cairo_y
-    let y_str = data
-        .adaptor_point
-        .cairo_y
-        .trim_matches(|c| c == '(' || c == ')');
-    let y_limbs: Vec<String> = y_str.split(',').map(|s| s.trim().to_string()).collect();
-    if y_limbs.len() != 4 {
-        return Err("Invalid y_limbs length".to_string());
+    /// The secret as a Cairo byte string literal, for contract tests.
+    pub fn expose_cairo_secret_literal(&self) -> &str {
+        &self.cairo_secret_literal
     }
+}
 
-    // Extract fake_glv_hint (should be 10 felts, already converted to hex strings by deserializer)
-    if data.fake_glv_hint.felts.len() != 10 {
-        return Err("Invalid fake_glv_hint length".to_string());
+impl fmt::Debug for SwapSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwapSecret")
+            .field("secret_hex", &"<redacted>")
+            .field("hash_u32_words", &self.hash_u32_words)
+            .field("cairo_hash_literal", &self.cairo_hash_literal)
+            .field("cairo_secret_literal", &"<redacted>")
+            .field("adaptor_point_x_limbs", &self.adaptor_point_x_limbs)
+            .field("adaptor_point_y_limbs", &self.adaptor_point_y_limbs)
+            .field("dleq_second_point_x_limbs", &self.dleq_second_point_x_limbs)
+            .field("dleq_second_point_y_limbs", &self.dleq_second_point_y_limbs)
+            .field("dleq_challenge", &self.dleq_challenge)
+            .field("dleq_response", &self.dleq_response)
+            .field("fake_glv_hint", &self.fake_glv_hint)
+            .field("adaptor_point_compressed", &self.adaptor_point_compressed)
+            .field("adaptor_point_sqrt_hint", &self.adaptor_point_sqrt_hint)
+            .field("dleq_proof", &self.dleq_proof)
+            .field("dleq_sqrt_hints", &self.dleq_sqrt_hints)
+            .finish()
     }
+}
 
-    Ok((
-        [
-            x_limbs[0].clone(),
-            x_limbs[1].clone(),
-            x_limbs[2].clone(),
-            x_limbs[3].clone(),
-        ],
-        [
-            y_limbs[0].clone(),
-            y_limbs[1].clone(),
-            y_limbs[2].clone(),
-            y_limbs[3].clone(),
-        ],
-        [
-            data.fake_glv_hint.felts[0].clone(),
-            data.fake_glv_hint.felts[1].clone(),
-            data.fake_glv_hint.felts[2].clone(),
-            data.fake_glv_hint.felts[3].clone(),
-            data.fake_glv_hint.felts[4].clone(),
-            data.fake_glv_hint.felts[5].clone(),
-            data.fake_glv_hint.felts[6].clone(),
-            data.fake_glv_hint.felts[7].clone(),
-            data.fake_glv_hint.felts[8].clone(),
-            data.fake_glv_hint.felts[9].clone(),
-        ],
-    ))
+/// Cairo `u256` literal for 32 little-endian bytes.
+fn cairo_u256(bytes: &[u8; 32]) -> String {
+    format!(
+        "u256 {{ low: {}, high: {} }}",
+        low_u128_hex(bytes),
+        low_u128_hex(bytes[16..].try_into().expect("16 bytes")),
+    )
+}
+
+/// Low 128 bits of little-endian bytes as a hex felt.
+fn low_u128_hex(bytes: &[u8]) -> String {
+    let low: [u8; 16] = bytes[..16].try_into().expect("16 bytes");
+    format!("{:#x}", u128::from_le_bytes(low))
 }
 
 /// Generate a Monero-compatible scalar and compute its SHA-256 hash.
-pub fn generate_swap_secret() -> SwapSecret {
-    let mut csprng = OsRng;
-    let mut raw_bytes = [0u8; 32];
-    csprng.fill_bytes(&mut raw_bytes);
+///
+/// # Errors
+///
+/// Returns `SwapSecretError::ZeroScalar` if the sampled scalar is zero
+/// (probability ~2^-252), or the DLEQ or hint error if deriving the
+/// contract inputs fails.
+pub fn generate_swap_secret() -> Result<SwapSecret, SwapSecretError> {
+    generate_swap_secret_with_rng(&mut OsRng)
+}
 
-    // Reduce to a valid scalar and keep the canonical 32-byte representation.
-    let scalar = Scalar::from_bytes_mod_order(raw_bytes);
-    let secret_bytes = scalar.to_bytes();
-
-    // Compute adaptor point T = t·G on Edwards curve (for Monero compatibility check).
-    let _adaptor_point: EdwardsPoint = &scalar * &ED25519_BASEPOINT_POINT;
-
-    // Generate real adaptor point and fake-GLV hint using Python tool for consistency with Cairo.
-    let secret_hex = hex::encode(secret_bytes);
-    let (adaptor_point_x_limbs, adaptor_point_y_limbs, fake_glv_hint) = 
-        generate_adaptor_point_from_python(&secret_hex).unwrap_or_else(|e| {
-            // Fallback to placeholder if Python tool unavailable (e.g., in tests without Python env)
-            // In production, ensure Python tool is available or use pre-generated values
-            eprintln!(
-                "Warning: Python tool unavailable ({}), using placeholder adaptor point/hint",
-                e
-            );
-            (
-                ["0x0", "0x0", "0x0", "0x0"].map(str::to_string),
-                ["0x0", "0x0", "0x0", "0x0"].map(str::to_string),
-                ["0x0"; 10].map(str::to_string),
-            )
-        });
+/// [`generate_swap_secret`] with the secret sampled from `rng`.
+///
+/// A seeded RNG gives reproducible fixtures; anyone who knows the seed
+/// knows the secret, so use `OsRng` for real swaps.
+pub fn generate_swap_secret_with_rng<R: CryptoRng + RngCore>(
+    rng: &mut R,
+) -> Result<SwapSecret, SwapSecretError> {
+    let mut raw_bytes = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(raw_bytes.as_mut());
 
-    // SHA-256 hash.
-    let hash_bytes: [u8; 32] = Sha256::digest(&secret_bytes).into();
-    let hashlock: [u8; 32] = hash_bytes;
-
-    // Convert to 8 x u32 (big-endian).
-    let hash_words: [u32; 8] = core::array::from_fn(|i| {
-        let start = i * 4;
-        u32::from_be_bytes(hash_bytes[start..start + 4].try_into().unwrap())
-    });
-
-    // Generate DLEQ proof (wrap scalar in Zeroizing for memory safety)
-    // Note: secret_bytes is already raw bytes here, which is correct for Cairo compatibility
-    let secret_zeroizing = Zeroizing::new(scalar);
-    let adaptor_point_edwards = ED25519_BASEPOINT_POINT * *secret_zeroizing;
-    let dleq_proof = generate_dleq_proof(&secret_zeroizing, &secret_bytes, &adaptor_point_edwards, &hashlock)
-        .expect("DLEQ proof generation should succeed for valid test inputs");
-
-    // Convert DLEQ second point to Weierstrass and get limbs
-    // TODO: Use Python tool to convert Edwards to Weierstrass for consistency
-    // For now, use placeholder - in production, call Python tool similar to adaptor point
-    let dleq_second_point_x_limbs = ["0x0", "0x0", "0x0", "0x0"].map(str::to_string);
-    let dleq_second_point_y_limbs = ["0x0", "0x0", "0x0", "0x0"].map(str::to_string);
-    
-    // Format DLEQ challenge and response as hex strings (felt252 in Cairo)
-    // Convert scalar bytes to hex, then format as felt252 (big-endian u256)
-    let challenge_bytes = dleq_proof.challenge.to_bytes();
-    let response_bytes = dleq_proof.response.to_bytes();
-    let dleq_challenge = format!("0x{}", hex::encode(challenge_bytes));
-    let dleq_response = format!("0x{}", hex::encode(response_bytes));
-
-    // Format for Cairo.
-    let cairo_hash_literal = format!(
-        "array![{}].span()",
-        hash_words
-            .iter()
-            .map(|w| format!("{}_u32", w))
-            .collect::<Vec<_>>()
-            .join(", ")
-    );
-
-    let cairo_secret_literal = format!(
-        "\"{}\"",
-        secret_bytes
-            .iter()
-            .map(|b| format!("\\x{:02x}", b))
-            .collect::<String>()
-    );
-
-    SwapSecret {
-        secret_hex: hex::encode(secret_bytes),
-        hash_u32_words: hash_words,
-        cairo_hash_literal,
-        cairo_secret_literal,
-        adaptor_point_x_limbs,
-        adaptor_point_y_limbs,
-        dleq_second_point_x_limbs,
-        dleq_second_point_y_limbs,
-        dleq_challenge,
-        dleq_response,
-        fake_glv_hint,
-    }
+    // Reduce to a valid scalar and keep the canonical 32-byte representation.
+    let scalar = Zeroizing::new(Scalar::from_bytes_mod_order(*raw_bytes));
+    SwapSecret::from_secret_bytes(scalar.to_bytes())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::encoding::decode_point;
     use sha2::Sha256;
 
     #[test]
     fn test_hash_word_count() {
-        let secret = generate_swap_secret();
+        let secret = generate_swap_secret().unwrap();
         assert_eq!(secret.hash_u32_words.len(), 8);
     }
 
+    #[test]
+    fn test_seeded_rng_reproduces_secret() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let first = generate_swap_secret_with_rng(&mut StdRng::seed_from_u64(7)).unwrap();
+        let again = generate_swap_secret_with_rng(&mut StdRng::seed_from_u64(7)).unwrap();
+        let other = generate_swap_secret_with_rng(&mut StdRng::seed_from_u64(8)).unwrap();
+        assert_eq!(first.expose_secret(), again.expose_secret());
+        assert_eq!(first.fake_glv_hint, again.fake_glv_hint);
+        assert_eq!(first.dleq_response, again.dleq_response);
+        assert_ne!(first.expose_secret(), other.expose_secret());
+    }
+
+    #[test]
+    fn test_from_secret_bytes_restores_secret() {
+        let secret = generate_swap_secret().unwrap();
+        let bytes: [u8; 32] = hex::decode(secret.expose_secret())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let restored = SwapSecret::from_secret_bytes(bytes).unwrap();
+        assert_eq!(restored.expose_secret(), secret.expose_secret());
+        assert_eq!(restored.hash_u32_words, secret.hash_u32_words);
+        assert_eq!(restored.adaptor_point_x_limbs, secret.adaptor_point_x_limbs);
+        assert_eq!(
+            restored.dleq_second_point_y_limbs,
+            secret.dleq_second_point_y_limbs
+        );
+        assert_eq!(restored.dleq_response, secret.dleq_response);
+        assert_eq!(restored.fake_glv_hint, secret.fake_glv_hint);
+    }
+
+    #[test]
+    fn test_from_secret_bytes_rejects_invalid_scalars() {
+        assert!(matches!(
+            SwapSecret::from_secret_bytes([0u8; 32]),
+            Err(SwapSecretError::ZeroScalar)
+        ));
+        assert!(matches!(
+            SwapSecret::from_secret_bytes([0xffu8; 32]),
+            Err(SwapSecretError::Encoding(EncodingError::NonCanonicalScalar))
+        ));
+    }
+
+    #[test]
+    fn test_embedded_proof_and_calldata() {
+        let secret = generate_swap_secret().unwrap();
+        let proof = DleqProof::from_serializable(secret.dleq_proof.clone()).unwrap();
+        let adaptor_point = decode_point(
+            hex::decode(&secret.adaptor_point_compressed)
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        )
+        .unwrap();
+        let hashlock: [u8; 32] =
+            Sha256::digest(hex::decode(secret.expose_secret()).unwrap()).into();
+        verify_dleq_proof(&proof, &adaptor_point, &hashlock).unwrap();
+        assert_eq!(
+            secret.adaptor_point_sqrt_hint,
+            hex::encode(hints::sqrt_hint(&adaptor_point))
+        );
+
+        let calldata = secret
+            .constructor_calldata(1_700_000_000, "0x0", 1000)
+            .unwrap();
+        let args = swap::verify::ConstructorArgs::decode(&calldata).unwrap();
+        assert_eq!(args.hash_words, secret.hash_u32_words);
+        assert_eq!(
+            hex::encode(args.adaptor_point),
+            secret.adaptor_point_compressed
+        );
+        let literal = secret
+            .cairo_constructor_calldata(1_700_000_000, "0x0", 1000)
+            .unwrap();
+        assert_eq!(literal, format!("array![{}]", calldata.join(", ")));
+    }
+
+    #[test]
+    fn test_cairo_fixture_matches_calldata() {
+        let secret = generate_swap_secret().unwrap();
+        let fixture = secret.cairo_fixture();
+        let calldata = secret
+            .constructor_calldata(1_700_000_000, "0x0", 1000)
+            .unwrap();
+        // Constructor calldata: hashlock length and 8 words, lock_until, token,
+        // amount (low, high), then T and U as (compressed, sqrt hint) u256s
+        assert!(fixture.contains(&format!(
+            "T_COMPRESSED: u256 = u256 {{ low: {}, high: {} }};",
+            calldata[13], calldata[14]
+        )));
+        assert!(fixture.contains(&format!(
+            "U_SQRT_HINT: u256 = u256 {{ low: {}, high: {} }};",
+            calldata[19], calldata[20]
+        )));
+        assert!(fixture.contains(&format!("CHALLENGE_LOW: felt252 = {};", calldata[21])));
+        assert!(fixture.contains(&format!("RESPONSE_LOW: felt252 = {};", calldata[22])));
+        assert!(fixture.contains(secret.expose_cairo_secret_literal()));
+        assert!(fixture.contains(&secret.cairo_hash_literal));
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let secret = generate_swap_secret().unwrap();
+        let debug = format!("{:?}", secret);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(secret.expose_secret()));
+        assert!(!debug.contains(secret.expose_cairo_secret_literal()));
+        assert!(debug.contains(&secret.cairo_hash_literal));
+        assert!(debug.contains(&secret.adaptor_point_compressed));
+        assert!(debug.contains(&secret.dleq_sqrt_hints[0]));
+    }
+
     #[test]
     fn test_deterministic_hash() {
         // Given a known scalar, hash should be deterministic.
@@ -332,4 +509,22 @@ mod tests {
         let hash: [u8; 32] = Sha256::digest(&scalar.to_bytes()).into();
         assert_eq!(hash.len(), 32);
     }
+
+    #[test]
+    fn test_fake_glv_hint_is_native() {
+        let secret = generate_swap_secret().unwrap();
+        // No placeholder zeros: Q (= T) and both decomposition scalars are set
+        assert!(secret.fake_glv_hint.iter().take(3).any(|f| f != "0x0"));
+        assert_ne!(secret.fake_glv_hint[8], "0x0");
+        assert_ne!(secret.fake_glv_hint[9], "0x0");
+        assert_eq!(secret.adaptor_point_x_limbs[..], secret.fake_glv_hint[..4]);
+        assert_eq!(secret.adaptor_point_y_limbs[..], secret.fake_glv_hint[4..8]);
+    }
+
+    #[test]
+    fn test_dleq_second_point_limbs_are_set() {
+        let secret = generate_swap_secret().unwrap();
+        assert!(secret.dleq_second_point_x_limbs.iter().any(|l| l != "0x0"));
+        assert!(secret.dleq_second_point_y_limbs.iter().any(|l| l != "0x0"));
+    }
 }
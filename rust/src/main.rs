@@ -1,28 +1,124 @@
 //! Monero Atomic Swap - Secret Generator CLI.
 //!
 //! Generates a Monero-compatible scalar and its SHA-256 hash formatted for
-//! consumption by the Cairo AtomicLock contract/tests.
+//! consumption by the Cairo AtomicLock contract/tests. With `--secret`, the
+//! same outputs are re-derived for an existing secret; with `--count`, many
+//! independent secrets are written to `--out-dir` with an index manifest.
 
-use clap::Parser;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser, ValueEnum};
+use serde_json::json;
 use xmr_secret_gen::{generate_swap_secret, SwapSecret};
 
+/// Output format for a single secret.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Labelled hex and Cairo literals on stdout.
+    Human,
+    /// The full `SwapSecret` as pretty JSON on stdout.
+    Json,
+    /// A Cairo test fixture module written to `--out`.
+    CairoFile,
+}
+
 /// CLI arguments.
 #[derive(Parser, Debug)]
 #[command(name = "xmr-secret-gen")]
 #[command(about = "Generate Monero scalar + SHA-256 hash for atomic swaps")]
 struct Args {
-    /// Output format: "human" or "json".
-    #[arg(short, long, default_value = "human")]
-    format: String,
+    /// Output format; `cairo-file` writes `--out`.
+    #[arg(short, long, value_enum, default_value_t = Format::Human)]
+    format: Format,
+
+    /// Re-derive the outputs for this secret (64 hex chars, canonical scalar)
+    /// instead of generating one. Ends up in the shell history: tests only.
+    #[arg(long, conflicts_with = "count")]
+    secret: Option<String>,
+
+    /// Generate this many independent secrets as JSON files in `--out-dir`.
+    #[arg(long, requires = "out_dir", conflicts_with_all = ["format", "out"])]
+    count: Option<usize>,
+
+    /// Directory for `--count` (created if missing).
+    #[arg(long, requires = "count")]
+    out_dir: Option<PathBuf>,
+
+    /// File written by `--format cairo-file`
+    /// (default: `swap_secret_fixture.cairo`).
+    #[arg(long)]
+    out: Option<PathBuf>,
 }
 
-fn main() {
+fn main() -> Result<()> {
     let args = Args::parse();
-    let secret = generate_swap_secret();
-    match args.format.as_str() {
-        "json" => print_json(&secret),
-        _ => print_human_readable(&secret),
+    if args.out.is_some() && args.format != Format::CairoFile {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--out is only used with --format cairo-file",
+            )
+            .exit();
+    }
+    if let (Some(count), Some(out_dir)) = (args.count, &args.out_dir) {
+        return write_batch(count, out_dir);
+    }
+    let secret = match &args.secret {
+        Some(hex) => SwapSecret::from_secret_bytes(parse_secret(hex)?)?,
+        None => generate_swap_secret()?,
+    };
+    match args.format {
+        Format::Human => print_human_readable(&secret),
+        Format::Json => print_json(&secret),
+        Format::CairoFile => {
+            let out = args
+                .out
+                .unwrap_or_else(|| PathBuf::from("swap_secret_fixture.cairo"));
+            std::fs::write(&out, secret.cairo_fixture())
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+            println!("Wrote Cairo fixture to {}", out.display());
+        }
     }
+    Ok(())
+}
+
+/// Write `count` secrets as `secret_NNNN.json` plus a `manifest.json`
+/// listing each file with its hashlock.
+fn write_batch(count: usize, out_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let mut entries = Vec::with_capacity(count);
+    for index in 0..count {
+        let secret = generate_swap_secret()?;
+        let file = format!("secret_{:04}.json", index);
+        let path = out_dir.join(&file);
+        std::fs::write(&path, serde_json::to_string_pretty(&secret)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        let hashlock: String = secret
+            .hash_u32_words
+            .iter()
+            .map(|w| format!("{:08x}", w))
+            .collect();
+        entries.push(json!({ "index": index, "file": file, "hashlock": hashlock }));
+    }
+    let manifest = json!({ "count": count, "secrets": entries });
+    let path = out_dir.join("manifest.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!(
+        "Wrote {} secrets and manifest.json to {}",
+        count,
+        out_dir.display()
+    );
+    Ok(())
+}
+
+fn parse_secret(hex: &str) -> Result<[u8; 32]> {
+    hex::decode(hex.trim().trim_start_matches("0x"))
+        .context("--secret is not hex")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("--secret must be 32 bytes (64 hex chars)"))
 }
 
 fn print_json(secret: &SwapSecret) {
@@ -34,9 +130,12 @@ fn print_json(secret: &SwapSecret) {
 
 fn print_human_readable(secret: &SwapSecret) {
     println!("[1] SECRET SCALAR (save securely)");
-    println!("    hex: {}\n", secret.secret_hex);
+    println!("    hex: {}\n", secret.expose_secret());
     println!("[2] CAIRO HASH");
     println!("    let expected_hash = {};\n", secret.cairo_hash_literal);
     println!("[3] CAIRO SECRET");
-    println!("    let secret_input = {};", secret.cairo_secret_literal);
+    println!(
+        "    let secret_input = {};",
+        secret.expose_cairo_secret_literal()
+    );
 }
@@ -0,0 +1,301 @@
+//! Ring member (decoy) selection following Monero's wallet2 gamma picker.
+//!
+//! Spends are much more likely to be recent than old, so wallet2 samples
+//! output *ages* from a gamma distribution fitted to real spend behaviour
+//! (Möser et al., shape 19.28, rate 1.61 over log-seconds) and maps them to
+//! global output indices via the per-block RingCT output distribution
+//! (`get_output_distribution`). Rings built any other way (e.g. uniform
+//! random outputs) stand out on chain, so this reproduces wallet2's
+//! `gamma_picker` closely, including its quirks.
+//!
+//! The caller supplies the cumulative RingCT output counts per block and the
+//! real output's global index; the result is a sorted list of global indices
+//! whose keys are then fetched with `get_outs`.
+
+use std::collections::BTreeSet;
+
+use rand::rngs::StdRng;
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use thiserror::Error;
+
+/// Ring size enforced by consensus since the v15 hard fork.
+pub const RING_SIZE: usize = 16;
+
+/// Gamma shape parameter (wallet2 `GAMMA_SHAPE`).
+pub const GAMMA_SHAPE: f64 = 19.28;
+/// Gamma scale parameter (wallet2 `GAMMA_SCALE`, 1 / rate).
+pub const GAMMA_SCALE: f64 = 1.0 / 1.61;
+
+/// Target block time in seconds.
+const DIFFICULTY_TARGET: u64 = 120;
+/// Blocks before a new output can be spent (`CRYPTONOTE_DEFAULT_TX_SPENDABLE_AGE`).
+const SPENDABLE_AGE: usize = 10;
+/// Seconds corresponding to the spendable age.
+const DEFAULT_UNLOCK_TIME: f64 = (SPENDABLE_AGE as u64 * DIFFICULTY_TARGET) as f64;
+/// Ages below the unlock time are redrawn uniformly from this window (seconds).
+const RECENT_SPEND_WINDOW: u64 = 15 * DIFFICULTY_TARGET;
+/// Blocks used to estimate the average time between outputs.
+const BLOCKS_IN_A_YEAR: usize = (86_400 * 365 / DIFFICULTY_TARGET) as usize;
+/// Picks per ring member before giving up (wallet2 retries bad picks too).
+const MAX_PICKS_PER_MEMBER: usize = 100;
+
+/// Errors from decoy selection.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DecoyError {
+    #[error("output distribution too short: {blocks} blocks, need more than {SPENDABLE_AGE}")]
+    DistributionTooShort { blocks: usize },
+
+    #[error("output distribution is not cumulative at block {0}")]
+    NotCumulative(usize),
+
+    #[error("only {available} spendable outputs, ring needs {required}")]
+    NotEnoughOutputs { available: u64, required: usize },
+
+    #[error("real output {real} is not spendable yet ({spendable} spendable outputs)")]
+    RealOutputNotSpendable { real: u64, spendable: u64 },
+
+    #[error("gave up after {0} picks without filling the ring")]
+    TooManyPicks(usize),
+}
+
+/// wallet2's gamma picker over a RingCT output distribution.
+#[derive(Debug, Clone)]
+pub struct GammaPicker {
+    /// Cumulative RingCT output count at the end of each block.
+    rct_offsets: Vec<u64>,
+    /// Outputs old enough to be spent (and thus to be decoys).
+    num_spendable: u64,
+    /// Average seconds between outputs over the last year.
+    average_output_time: f64,
+}
+
+impl GammaPicker {
+    /// Build a picker from cumulative per-block RingCT output counts, as
+    /// returned by `get_output_distribution` with `cumulative = true`.
+    pub fn new(rct_offsets: Vec<u64>) -> Result<Self, DecoyError> {
+        let blocks = rct_offsets.len();
+        if blocks <= SPENDABLE_AGE {
+            return Err(DecoyError::DistributionTooShort { blocks });
+        }
+        if let Some(i) = rct_offsets.windows(2).position(|w| w[1] < w[0]) {
+            return Err(DecoyError::NotCumulative(i + 1));
+        }
+
+        let blocks_to_consider = blocks.min(BLOCKS_IN_A_YEAR);
+        let outputs_before = if blocks_to_consider < blocks {
+            rct_offsets[blocks - blocks_to_consider - 1]
+        } else {
+            0
+        };
+        let outputs_to_consider = rct_offsets[blocks - 1] - outputs_before;
+        let num_spendable = rct_offsets[blocks - SPENDABLE_AGE - 1];
+        let average_output_time = (DIFFICULTY_TARGET as f64) * blocks_to_consider as f64
+            / outputs_to_consider.max(1) as f64;
+
+        Ok(Self {
+            rct_offsets,
+            num_spendable,
+            average_output_time,
+        })
+    }
+
+    /// Number of outputs that can be picked.
+    pub fn num_spendable(&self) -> u64 {
+        self.num_spendable
+    }
+
+    /// Draw one global output index, or `None` for a bad pick (too old, or
+    /// landed in a block without RingCT outputs); callers just draw again.
+    pub fn pick<R: RngCore + ?Sized>(&self, rng: &mut R) -> Option<u64> {
+        let mut age = sample_gamma(rng, GAMMA_SHAPE, GAMMA_SCALE).exp();
+        if age > DEFAULT_UNLOCK_TIME {
+            age -= DEFAULT_UNLOCK_TIME;
+        } else {
+            age = rng.gen_range(0..RECENT_SPEND_WINDOW) as f64;
+        }
+
+        let offset = (age / self.average_output_time) as u64;
+        if offset >= self.num_spendable {
+            return None;
+        }
+        let target = self.num_spendable - 1 - offset;
+
+        // wallet2 uses lower_bound over the spendable blocks, then picks
+        // uniformly among that block's outputs
+        let spendable_blocks = &self.rct_offsets[..self.rct_offsets.len() - SPENDABLE_AGE];
+        let block = spendable_blocks.partition_point(|&offset| offset < target);
+        let first = if block == 0 {
+            0
+        } else {
+            self.rct_offsets[block - 1]
+        };
+        let count = self.rct_offsets[block] - first;
+        if count == 0 {
+            return None;
+        }
+        Some(first + rng.gen_range(0..count))
+    }
+
+    /// Select a ring of `ring_size` distinct global indices containing
+    /// `real_output`, sorted ascending.
+    pub fn select_ring<R: RngCore + CryptoRng>(
+        &self,
+        real_output: u64,
+        ring_size: usize,
+        rng: &mut R,
+    ) -> Result<Vec<u64>, DecoyError> {
+        self.select_ring_with(real_output, ring_size, rng)
+    }
+
+    /// Deterministic ring selection from `seed`.
+    ///
+    /// For tests and reproducible fixtures only: anyone who knows the seed
+    /// can tell the real output from the decoys.
+    pub fn select_ring_deterministic(
+        &self,
+        real_output: u64,
+        ring_size: usize,
+        seed: [u8; 32],
+    ) -> Result<Vec<u64>, DecoyError> {
+        self.select_ring_with(real_output, ring_size, &mut StdRng::from_seed(seed))
+    }
+
+    fn select_ring_with<R: RngCore>(
+        &self,
+        real_output: u64,
+        ring_size: usize,
+        rng: &mut R,
+    ) -> Result<Vec<u64>, DecoyError> {
+        if real_output >= self.num_spendable {
+            return Err(DecoyError::RealOutputNotSpendable {
+                real: real_output,
+                spendable: self.num_spendable,
+            });
+        }
+        if self.num_spendable < ring_size as u64 {
+            return Err(DecoyError::NotEnoughOutputs {
+                available: self.num_spendable,
+                required: ring_size,
+            });
+        }
+
+        let mut ring = BTreeSet::from([real_output]);
+        let max_picks = ring_size * MAX_PICKS_PER_MEMBER;
+        let mut picks = 0;
+        while ring.len() < ring_size {
+            if picks == max_picks {
+                return Err(DecoyError::TooManyPicks(picks));
+            }
+            picks += 1;
+            if let Some(index) = self.pick(rng) {
+                ring.insert(index);
+            }
+        }
+        Ok(ring.into_iter().collect())
+    }
+}
+
+/// Gamma(shape, scale) sample via Marsaglia–Tsang (valid for shape >= 1).
+fn sample_gamma<R: RngCore + ?Sized>(rng: &mut R, shape: f64, scale: f64) -> f64 {
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = sample_standard_normal(rng);
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u: f64 = rng.gen();
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v * scale;
+        }
+    }
+}
+
+/// Standard normal sample via Box–Muller.
+fn sample_standard_normal<R: RngCore + ?Sized>(rng: &mut R) -> f64 {
+    // 1 - gen() is in (0, 1], so the log is finite
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    /// Two years of blocks with 30 RingCT outputs each.
+    fn distribution() -> Vec<u64> {
+        (1..=2 * BLOCKS_IN_A_YEAR as u64).map(|b| b * 30).collect()
+    }
+
+    #[test]
+    fn test_ring_contains_real_output() {
+        let picker = GammaPicker::new(distribution()).unwrap();
+        let real = picker.num_spendable() - 500;
+        let ring = picker.select_ring(real, RING_SIZE, &mut OsRng).unwrap();
+
+        assert_eq!(ring.len(), RING_SIZE);
+        assert!(ring.contains(&real));
+        assert!(ring.windows(2).all(|w| w[0] < w[1]));
+        assert!(ring.iter().all(|&i| i < picker.num_spendable()));
+    }
+
+    #[test]
+    fn test_deterministic_mode() {
+        let picker = GammaPicker::new(distribution()).unwrap();
+        let a = picker
+            .select_ring_deterministic(1000, RING_SIZE, [7u8; 32])
+            .unwrap();
+        let b = picker
+            .select_ring_deterministic(1000, RING_SIZE, [7u8; 32])
+            .unwrap();
+        let c = picker
+            .select_ring_deterministic(1000, RING_SIZE, [8u8; 32])
+            .unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_picks_favour_recent_outputs() {
+        let picker = GammaPicker::new(distribution()).unwrap();
+        let mut rng = StdRng::from_seed([1u8; 32]);
+        let picks: Vec<u64> = (0..2000).filter_map(|_| picker.pick(&mut rng)).collect();
+
+        // wallet2's distribution puts roughly half of all picks within ~2 days
+        let two_days = 2 * 720 * 30;
+        let recent = picks
+            .iter()
+            .filter(|&&i| i + two_days >= picker.num_spendable())
+            .count();
+        assert!(
+            recent * 4 > picks.len(),
+            "{} of {} picks recent",
+            recent,
+            picks.len()
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert_eq!(
+            GammaPicker::new(vec![1, 2, 3]).unwrap_err(),
+            DecoyError::DistributionTooShort { blocks: 3 }
+        );
+        let mut offsets = distribution();
+        offsets[5] = 0;
+        assert_eq!(
+            GammaPicker::new(offsets).unwrap_err(),
+            DecoyError::NotCumulative(5)
+        );
+
+        let picker = GammaPicker::new(distribution()).unwrap();
+        let unspendable = picker.num_spendable();
+        assert!(matches!(
+            picker.select_ring(unspendable, RING_SIZE, &mut OsRng),
+            Err(DecoyError::RealOutputNotSpendable { .. })
+        ));
+    }
+}
@@ -1,11 +1,18 @@
 //! Monero integration for atomic swaps.
 //!
 //! Uses KEY SPLITTING approach (not CLSAG modification):
+//! - decoys: Ring member selection (wallet2 gamma picker)
 //! - key_splitting: Split/recover spend keys
+//! - policy: Lock policy (no reliance on deprecated `unlock_time`)
 //! - transaction: Create Monero transactions using Serai's audited code
+//! - txid: Transaction hashes computed locally before broadcast
 
+pub mod decoys;
 pub mod key_splitting;
+pub mod policy;
 pub mod transaction;
+pub mod txid;
 
 // Re-export main types
 pub use key_splitting::SwapKeyPair;
+pub use policy::{LockMechanism, PolicyError};
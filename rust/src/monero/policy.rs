@@ -0,0 +1,94 @@
+//! Lock policy for the Monero side of the swap.
+//!
+//! Monero is phasing out consensus-enforced `unlock_time` (it is already
+//! non-standard for relay and slated for removal), so a lock that depends on
+//! it can silently become spendable early. The swap's security must come from
+//! KEY SPLITTING alone:
+//!
+//! - XMR is sent to the joint key P = (x_partial + t)·G with `unlock_time = 0`
+//! - Nobody can spend it until `t` is revealed on Starknet
+//!
+//! This module is the single place that decides whether a given `unlock_time`
+//! is acceptable. The legacy timelocked transfer path is only compiled with the
+//! `legacy-unlock-time` feature and is never treated as a security mechanism.
+
+use thiserror::Error;
+
+/// `unlock_time` value meaning "no timelock" (the only value we rely on).
+pub const NO_UNLOCK_TIME: u64 = 0;
+
+/// How the XMR side of a swap is locked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMechanism {
+    /// Funds sent to the split key P = (x_partial + t)·G, spendable only with `t`.
+    KeySplitting,
+    /// Legacy: funds additionally carry a consensus `unlock_time`.
+    ///
+    /// Kept for backward compatibility only. The timelock is NOT relied upon
+    /// for security; key splitting still protects the funds.
+    #[cfg(feature = "legacy-unlock-time")]
+    LegacyUnlockTime,
+}
+
+/// Lock policy violations.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    #[error(
+        "unlock_time {0} is not supported: Monero timelocks are deprecated and must not be relied \
+         on for swap security (use key splitting with unlock_time = 0)"
+    )]
+    UnlockTimeNotAllowed(u64),
+}
+
+/// Lock mechanism used by this build.
+pub const fn lock_mechanism() -> LockMechanism {
+    LockMechanism::KeySplitting
+}
+
+/// Check that a transfer does not rely on `unlock_time`.
+///
+/// # Errors
+///
+/// Returns `PolicyError::UnlockTimeNotAllowed` for any non-zero `unlock_time`.
+pub fn ensure_no_unlock_time(unlock_time: u64) -> Result<(), PolicyError> {
+    if unlock_time != NO_UNLOCK_TIME {
+        return Err(PolicyError::UnlockTimeNotAllowed(unlock_time));
+    }
+    Ok(())
+}
+
+/// Check an `unlock_time` observed on an existing transfer.
+///
+/// Incoming lock transactions with a timelock are rejected: even if the lock
+/// is still honoured today, the counterparty could use it to delay our ability
+/// to sweep the funds after `t` is revealed.
+pub fn check_observed_unlock_time(unlock_time: u64) -> Result<(), PolicyError> {
+    ensure_no_unlock_time(unlock_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_unlock_time_allowed() {
+        assert_eq!(ensure_no_unlock_time(0), Ok(()));
+    }
+
+    #[test]
+    fn test_nonzero_unlock_time_rejected() {
+        assert_eq!(
+            ensure_no_unlock_time(1_000_000),
+            Err(PolicyError::UnlockTimeNotAllowed(1_000_000))
+        );
+        assert_eq!(
+            check_observed_unlock_time(1),
+            Err(PolicyError::UnlockTimeNotAllowed(1))
+        );
+    }
+
+    #[test]
+    fn test_lock_mechanism_is_key_splitting() {
+        assert_eq!(lock_mechanism(), LockMechanism::KeySplitting);
+    }
+}
@@ -0,0 +1,192 @@
+//! Monero transaction hashes computed from the serialized transaction.
+//!
+//! Submitting a transaction is only safe to retry if we can tell whether an
+//! earlier attempt reached the daemon. The txid is known before broadcast:
+//!
+//! - v1: `keccak(tx)`
+//! - v2 (RingCT): `keccak(keccak(prefix) || keccak(rct_base) || keccak(prunable))`,
+//!   with a zero prunable hash for `RCTTypeNull` (coinbase)
+//!
+//! Only the prefix and the fixed-size RingCT base are parsed to find the
+//! split points; the prunable part (ring signatures, range proofs) is
+//! hashed as the rest of the blob.
+
+use thiserror::Error;
+
+use crate::starknet::keccak256;
+
+/// Errors parsing a serialized transaction.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TxidError {
+    #[error("transaction ends early")]
+    Truncated,
+
+    #[error("varint overflows u64")]
+    VarintOverflow,
+
+    #[error("unsupported input type {0:#04x}")]
+    InputType(u8),
+
+    #[error("unsupported output type {0:#04x}")]
+    OutputType(u8),
+
+    #[error("unsupported RingCT type {0}")]
+    RctType(u8),
+
+    #[error("trailing bytes after a v1 or coinbase transaction")]
+    TrailingBytes,
+}
+
+/// Transaction hash (txid) of a serialized transaction.
+pub fn transaction_hash(tx: &[u8]) -> Result<[u8; 32], TxidError> {
+    let mut reader = Reader { data: tx, pos: 0 };
+    let version = reader.varint()?;
+    reader.varint()?; // unlock_time
+
+    let inputs = reader.varint()?;
+    for _ in 0..inputs {
+        match reader.byte()? {
+            // txin_gen: height
+            0xff => {
+                reader.varint()?;
+            }
+            // txin_to_key: amount, key offsets, key image
+            0x02 => {
+                reader.varint()?;
+                let offsets = reader.varint()?;
+                for _ in 0..offsets {
+                    reader.varint()?;
+                }
+                reader.skip(32)?;
+            }
+            tag => return Err(TxidError::InputType(tag)),
+        }
+    }
+
+    let outputs = reader.varint()?;
+    for _ in 0..outputs {
+        reader.varint()?; // amount
+        match reader.byte()? {
+            0x02 => reader.skip(32)?,     // txout_to_key
+            0x03 => reader.skip(32 + 1)?, // txout_to_tagged_key (view tag)
+            tag => return Err(TxidError::OutputType(tag)),
+        }
+    }
+
+    let extra = reader.varint()?;
+    reader.skip(extra)?;
+    let prefix_end = reader.pos;
+
+    if version == 1 {
+        // Ring signatures follow the prefix; the txid covers everything
+        return Ok(keccak256(tx));
+    }
+
+    let rct_type = reader.byte()?;
+    let prunable_hash = if rct_type == 0 {
+        if reader.pos != tx.len() {
+            return Err(TxidError::TrailingBytes);
+        }
+        [0u8; 32]
+    } else {
+        reader.varint()?; // fee
+        let ecdh_info_len = match rct_type {
+            // Full, Simple, Bulletproof: 32-byte mask and amount
+            1..=3 => 64,
+            // Bulletproof2, CLSAG, BulletproofPlus: 8-byte amount
+            4..=6 => 8,
+            other => return Err(TxidError::RctType(other)),
+        };
+        if rct_type == 2 {
+            // pseudo outputs (moved to the prunable part from Bulletproof on)
+            reader.skip(inputs.checked_mul(32).ok_or(TxidError::Truncated)?)?;
+        }
+        // ecdhInfo, outPk
+        let base_tail = outputs.checked_mul(ecdh_info_len + 32);
+        reader.skip(base_tail.ok_or(TxidError::Truncated)?)?;
+        keccak256(&tx[reader.pos..])
+    };
+    let base_end = reader.pos;
+
+    let mut hashes = Vec::with_capacity(96);
+    hashes.extend_from_slice(&keccak256(&tx[..prefix_end]));
+    hashes.extend_from_slice(&keccak256(&tx[prefix_end..base_end]));
+    hashes.extend_from_slice(&prunable_hash);
+    Ok(keccak256(&hashes))
+}
+
+/// Transaction hash of a hex-encoded transaction, as hex (daemon format).
+pub fn transaction_hash_hex(tx_hex: &str) -> Result<String, TxidError> {
+    let tx = hex::decode(tx_hex).map_err(|_| TxidError::Truncated)?;
+    Ok(hex::encode(transaction_hash(&tx)?))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, TxidError> {
+        let byte = *self.data.get(self.pos).ok_or(TxidError::Truncated)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn skip(&mut self, n: u64) -> Result<(), TxidError> {
+        let end = usize::try_from(n)
+            .ok()
+            .and_then(|n| self.pos.checked_add(n))
+            .filter(|&end| end <= self.data.len())
+            .ok_or(TxidError::Truncated)?;
+        self.pos = end;
+        Ok(())
+    }
+
+    /// Little-endian base-128 varint.
+    fn varint(&mut self) -> Result<u64, TxidError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f)
+                .checked_shl(shift)
+                .filter(|shifted| shifted >> shift == u64::from(byte & 0x7f))
+                .ok_or(TxidError::VarintOverflow)?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(TxidError::VarintOverflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vectors from monero-rs (`deserialize_transaction_prefix`, `transaction_hash`)
+    const COINBASE_V1: &str = "01f18d0601ffb58d0605efefead70202eb72f82bd8bdda51e0bdc25f04e99ffb90c6214e11b455abca7b116c7857738880e497d01202e87c65a22b78f4b7686ef3a30113674659a4fe769a7ded73d60e6f7c556a19858090dfc04a022ee52dca8845438995eb6d7af985ca07186cc34a7eb696937f78fc0fd9008e2280c0f9decfae0102cec392ffdcae05a370dc3c447465798d3688677f4a5937f1fef9661df99ac2fb80c0caf384a30202e2b6ce11475c2312d2de5c9f26fbd88b7fcac0dbbb7b31f49abe9bd631ed49e42b0104d46cf1a204ae727c14473d67ea95da3e97b250f3c63e0997198bfc812d7a81020800000000d8111b25";
+
+    #[test]
+    fn test_v1_transaction_hash() {
+        let tx = hex::decode(COINBASE_V1).unwrap();
+        assert_eq!(
+            hex::encode(transaction_hash(&tx).unwrap()),
+            "3bc7ff015b227e7313cc2e8668bfbb3f3acbee274a9c201d6211cf681b5f6bb1"
+        );
+    }
+
+    #[test]
+    fn test_ringct_transaction_hash() {
+        assert_eq!(
+            transaction_hash_hex(include_str!("testdata/rct_tx.hex").trim()).unwrap(),
+            "5a420317e377d3d95b652fb93e65cfe97ef7d89e04be329a2ca94e73ec57b74e"
+        );
+    }
+
+    #[test]
+    fn test_truncated_transaction_is_rejected() {
+        let tx = hex::decode(include_str!("testdata/rct_tx.hex").trim()).unwrap();
+        assert_eq!(transaction_hash(&tx[..40]), Err(TxidError::Truncated));
+    }
+}
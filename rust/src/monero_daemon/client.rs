@@ -0,0 +1,456 @@
+//! Monero Daemon RPC Client
+//!
+//! monerod exposes two kinds of endpoints: JSON-RPC methods under
+//! `/json_rpc` (`get_info`, `get_output_distribution`) and plain JSON
+//! endpoints at their own paths (`/get_outs`, `/send_raw_transaction`,
+//! `/get_transactions`). Both report failures through a `status` field.
+
+use anyhow::{Context, Result};
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::monero::decoys::GammaPicker;
+use crate::monero::txid;
+use crate::monero_daemon::error::MoneroDaemonError;
+use crate::monero_daemon::types::{
+    DaemonInfo, KeyImageStatus, OutputDistribution, OutputEntry, OutputRequest, SendRawTxResult,
+    TransactionEntry, TransactionPool,
+};
+
+/// monerod `status` value for success
+const STATUS_OK: &str = "OK";
+
+/// Relay attempts made by [`DaemonClient::submit_raw_transaction`]
+const SUBMIT_ATTEMPTS: u32 = 3;
+
+/// Pause between relay attempts
+const SUBMIT_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Monero daemon RPC client
+#[derive(Clone)]
+pub struct DaemonClient {
+    /// HTTP client for RPC calls
+    http_client: HttpClient,
+    /// Daemon base URL (e.g., http://localhost:38081)
+    daemon_url: String,
+    /// Fault injection (chaos tests only)
+    #[cfg(feature = "chaos")]
+    chaos: Option<std::sync::Arc<crate::chaos::Chaos>>,
+}
+
+impl DaemonClient {
+    /// Create new daemon client and check the daemon is reachable
+    pub async fn new(daemon_url: String) -> Result<Self> {
+        let http_client = HttpClient::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        let client = Self {
+            http_client,
+            daemon_url: daemon_url.trim_end_matches('/').to_string(),
+            #[cfg(feature = "chaos")]
+            chaos: None,
+        };
+
+        let info = client
+            .get_info()
+            .await
+            .context("Failed to connect to monerod")?;
+        info!("Connected to monerod at height {}", info.height);
+
+        Ok(client)
+    }
+
+    /// Inject faults into every RPC call (chaos tests only).
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, chaos: std::sync::Arc<crate::chaos::Chaos>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Daemon height, sync state and network
+    pub async fn get_info(&self) -> Result<DaemonInfo> {
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(flatten)]
+            info: DaemonInfo,
+        }
+
+        let resp: Response = self
+            .call_json_rpc("get_info", serde_json::json!({}))
+            .await?;
+        check_status("get_info", &resp.status)?;
+        Ok(resp.info)
+    }
+
+    /// [`get_info`](Self::get_info), failing unless the daemon is synced
+    /// (see [`DaemonInfo::check_synced`]).
+    ///
+    /// Call before starting or accepting a swap: confirmation counts from a
+    /// lagging daemon are wrong.
+    pub async fn ensure_synced(&self) -> Result<DaemonInfo> {
+        let info = self.get_info().await?;
+        info.check_synced()?;
+        Ok(info)
+    }
+
+    /// Keys and commitments of RingCT outputs by global index
+    pub async fn get_outs(&self, indices: &[u64]) -> Result<Vec<OutputEntry>> {
+        #[derive(Serialize)]
+        struct Request {
+            outputs: Vec<OutputRequest>,
+            get_txid: bool,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(default)]
+            outs: Vec<OutputEntry>,
+        }
+
+        let req = Request {
+            outputs: indices
+                .iter()
+                .map(|&index| OutputRequest { amount: 0, index })
+                .collect(),
+            get_txid: true,
+        };
+
+        let resp: Response = self.call_other("get_outs", &req).await?;
+        check_status("get_outs", &resp.status)?;
+        if resp.outs.len() != indices.len() {
+            return Err(MoneroDaemonError::InvalidResponse(format!(
+                "get_outs returned {} outputs for {} indices",
+                resp.outs.len(),
+                indices.len()
+            ))
+            .into());
+        }
+        Ok(resp.outs)
+    }
+
+    /// Cumulative per-block RingCT output counts from `from_height` to the tip
+    pub async fn get_output_distribution(&self, from_height: u64) -> Result<OutputDistribution> {
+        #[derive(Serialize)]
+        struct Params {
+            amounts: Vec<u64>,
+            from_height: u64,
+            cumulative: bool,
+            binary: bool,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(default)]
+            distributions: Vec<OutputDistribution>,
+        }
+
+        let resp: Response = self
+            .call_json_rpc(
+                "get_output_distribution",
+                Params {
+                    amounts: vec![0],
+                    from_height,
+                    cumulative: true,
+                    binary: false,
+                },
+            )
+            .await?;
+        check_status("get_output_distribution", &resp.status)?;
+        resp.distributions
+            .into_iter()
+            .find(|d| d.amount == 0)
+            .ok_or_else(|| {
+                MoneroDaemonError::InvalidResponse("no RingCT output distribution".to_string())
+                    .into()
+            })
+    }
+
+    /// Decoy picker over the full RingCT output distribution
+    pub async fn gamma_picker(&self) -> Result<GammaPicker> {
+        let distribution = self.get_output_distribution(0).await?;
+        debug!(
+            "Output distribution: {} blocks from height {}",
+            distribution.distribution.len(),
+            distribution.start_height
+        );
+        Ok(GammaPicker::new(distribution.distribution)?)
+    }
+
+    /// Relay a signed transaction; returns the daemon's verdict on success
+    pub async fn send_raw_transaction(&self, tx_hex: &str) -> Result<SendRawTxResult> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            tx_as_hex: &'a str,
+            do_not_relay: bool,
+        }
+
+        let resp: SendRawTxResult = self
+            .call_other(
+                "send_raw_transaction",
+                &Request {
+                    tx_as_hex: tx_hex,
+                    do_not_relay: false,
+                },
+            )
+            .await?;
+
+        if resp.status != STATUS_OK || resp.not_relayed {
+            let mut reasons = resp.rejection_reasons().join(", ");
+            if !resp.reason.is_empty() {
+                reasons = format!("{} ({})", resp.reason, reasons);
+            }
+            return Err(MoneroDaemonError::TransactionRejected(reasons).into());
+        }
+        info!("Transaction relayed to monerod");
+        Ok(resp)
+    }
+
+    /// Relay a signed transaction at most once; returns its txid (hex)
+    ///
+    /// The txid is computed locally before broadcast. Every attempt first
+    /// asks the daemon whether it already has the transaction, so a relay
+    /// that timed out but landed is not sent again. A rejection is only
+    /// final once the transaction is confirmed absent: an earlier attempt
+    /// that made it to the pool makes the retry look like a double spend.
+    pub async fn submit_raw_transaction(&self, tx_hex: &str) -> Result<String> {
+        let tx_hash = txid::transaction_hash_hex(tx_hex).map_err(MoneroDaemonError::from)?;
+        let mut last_error = None;
+
+        for attempt in 1..=SUBMIT_ATTEMPTS {
+            if attempt > 1 {
+                tokio::time::sleep(SUBMIT_RETRY_DELAY).await;
+            }
+            match self.transaction_known(&tx_hash).await {
+                Ok(true) => {
+                    info!("Transaction {} already known to monerod", tx_hash);
+                    return Ok(tx_hash);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    warn!(
+                        "Lookup of {} failed (attempt {}): {:#}",
+                        tx_hash, attempt, e
+                    );
+                    last_error = Some(e);
+                    continue;
+                }
+            }
+
+            match self.send_raw_transaction(tx_hex).await {
+                Ok(_) => return Ok(tx_hash),
+                Err(e)
+                    if matches!(
+                        e.downcast_ref::<MoneroDaemonError>(),
+                        Some(MoneroDaemonError::TransactionRejected(_))
+                    ) =>
+                {
+                    if self.transaction_known(&tx_hash).await.unwrap_or(false) {
+                        return Ok(tx_hash);
+                    }
+                    return Err(e);
+                }
+                Err(e) => {
+                    warn!("Relay of {} failed (attempt {}): {:#}", tx_hash, attempt, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one attempt").context(format!(
+            "Transaction {} not relayed after {} attempts",
+            tx_hash, SUBMIT_ATTEMPTS
+        )))
+    }
+
+    /// Whether the daemon has the transaction in its chain or pool
+    async fn transaction_known(&self, tx_hash: &str) -> Result<bool> {
+        let txs = self.get_transactions(&[tx_hash.to_string()]).await?;
+        Ok(txs.iter().any(|tx| tx.tx_hash == tx_hash))
+    }
+
+    /// Look up transactions in the chain or pool; missing hashes are omitted
+    pub async fn get_transactions(&self, tx_hashes: &[String]) -> Result<Vec<TransactionEntry>> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            txs_hashes: &'a [String],
+            decode_as_json: bool,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(default)]
+            txs: Vec<TransactionEntry>,
+            #[serde(default)]
+            missed_tx: Vec<String>,
+        }
+
+        let resp: Response = self
+            .call_other(
+                "get_transactions",
+                &Request {
+                    txs_hashes: tx_hashes,
+                    decode_as_json: false,
+                },
+            )
+            .await?;
+        check_status("get_transactions", &resp.status)?;
+        if !resp.missed_tx.is_empty() {
+            debug!(
+                "Daemon does not know {} transaction(s)",
+                resp.missed_tx.len()
+            );
+        }
+        Ok(resp.txs)
+    }
+
+    /// Current transaction pool, with the key images each pool tx spends
+    pub async fn get_transaction_pool(&self) -> Result<TransactionPool> {
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(flatten)]
+            pool: TransactionPool,
+        }
+
+        let resp: Response = self
+            .call_other("get_transaction_pool", &serde_json::json!({}))
+            .await?;
+        check_status("get_transaction_pool", &resp.status)?;
+        Ok(resp.pool)
+    }
+
+    /// Spent state of each key image (hex), in request order
+    pub async fn is_key_image_spent(&self, key_images: &[String]) -> Result<Vec<KeyImageStatus>> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            key_images: &'a [String],
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(default)]
+            spent_status: Vec<u8>,
+        }
+
+        let resp: Response = self
+            .call_other("is_key_image_spent", &Request { key_images })
+            .await?;
+        check_status("is_key_image_spent", &resp.status)?;
+        resp.spent_status
+            .into_iter()
+            .map(|status| {
+                KeyImageStatus::try_from(status).map_err(|s| {
+                    MoneroDaemonError::InvalidResponse(format!("unknown key image status {}", s))
+                        .into()
+                })
+            })
+            .collect()
+    }
+
+    /// JSON-RPC call under `/json_rpc`
+    async fn call_json_rpc<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R> {
+        #[derive(Serialize)]
+        struct Request<P> {
+            jsonrpc: String,
+            id: String,
+            method: String,
+            params: P,
+        }
+
+        #[derive(Deserialize)]
+        struct RpcError {
+            code: i32,
+            message: String,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum JsonRpcResponse<R> {
+            Success { result: R },
+            Error { error: RpcError },
+        }
+
+        let req = Request {
+            jsonrpc: "2.0".to_string(),
+            id: "0".to_string(),
+            method: method.to_string(),
+            params,
+        };
+
+        let url = format!("{}/json_rpc", self.daemon_url);
+        let resp: JsonRpcResponse<R> = self.post(method, &url, &req).await?;
+
+        match resp {
+            JsonRpcResponse::Success { result } => Ok(result),
+            JsonRpcResponse::Error { error } => Err(MoneroDaemonError::RpcCallFailed(format!(
+                "RPC error {}: {}",
+                error.code, error.message
+            ))
+            .into()),
+        }
+    }
+
+    /// Plain JSON endpoint at `/<path>`
+    async fn call_other<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        body: &P,
+    ) -> Result<R> {
+        self.post(path, &format!("{}/{}", self.daemon_url, path), body)
+            .await
+    }
+
+    /// POST `body` as JSON to `url` and parse the JSON response of `method`
+    async fn post<B: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        url: &str,
+        body: &B,
+    ) -> Result<R> {
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.chaos {
+            chaos.before_request().await;
+        }
+
+        let text = self
+            .http_client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .context(format!("Failed to call {}", method))?
+            .text()
+            .await
+            .context(format!("Failed to read {} response", method))?;
+        #[cfg(feature = "chaos")]
+        let text = match &self.chaos {
+            Some(chaos) => chaos.after_response(method, &serde_json::to_string(body)?, text)?,
+            None => text,
+        };
+        serde_json::from_str(&text).context(format!("Failed to parse {} response", method))
+    }
+}
+
+fn check_status(method: &str, status: &str) -> Result<(), MoneroDaemonError> {
+    if status == STATUS_OK {
+        Ok(())
+    } else {
+        Err(MoneroDaemonError::BadStatus {
+            method: method.to_string(),
+            status: status.to_string(),
+        })
+    }
+}
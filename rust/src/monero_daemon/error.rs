@@ -0,0 +1,30 @@
+//! Monero Daemon RPC Error Types
+
+use thiserror::Error;
+
+use crate::monero::txid::TxidError;
+
+#[derive(Debug, Error)]
+pub enum MoneroDaemonError {
+    #[error("RPC call failed: {0}")]
+    RpcCallFailed(String),
+
+    #[error("{method} returned status '{status}'")]
+    BadStatus { method: String, status: String },
+
+    #[error("Transaction rejected: {0}")]
+    TransactionRejected(String),
+
+    #[error("Invalid response format: {0}")]
+    InvalidResponse(String),
+
+    #[error("monerod is not synchronized: {reason} (height {height}, target {target_height})")]
+    NotSynchronized {
+        reason: &'static str,
+        height: u64,
+        target_height: u64,
+    },
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(#[from] TxidError),
+}
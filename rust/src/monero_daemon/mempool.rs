@@ -0,0 +1,128 @@
+//! Mempool watcher for the counterparty's spend of the locked output.
+//!
+//! The taker should learn the maker's sweep as soon as it is relayed, not
+//! one block (~2 min) later: the signed transaction is already public in
+//! the pool, and every second counts if a fallback path needs it. The
+//! watcher polls `get_transaction_pool` for a transaction spending the
+//! locked output's key image, and falls back to `is_key_image_spent` so a
+//! spend that was mined between polls is still reported.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::time::sleep;
+use tracing::{debug, info};
+
+use crate::monero_daemon::client::DaemonClient;
+use crate::monero_daemon::types::{KeyImageStatus, TransactionPool};
+
+/// Default poll interval (pool propagation is a few seconds)
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A pool transaction spending the watched key image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolSpend {
+    pub tx_hash: String,
+    /// Serialized transaction (hex), carrying the finalized signature
+    pub tx_blob: String,
+}
+
+/// How the spend was observed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObservedSpend {
+    /// Seen in the pool, before mining
+    InPool(PoolSpend),
+    /// Already mined when first seen; monerod has no key image to tx index,
+    /// so the caller has to find the transaction by scanning blocks
+    Mined,
+}
+
+/// Polling watcher for the spend of one key image
+pub struct MempoolWatcher {
+    daemon: DaemonClient,
+    poll_interval: Duration,
+}
+
+impl MempoolWatcher {
+    pub fn new(daemon: DaemonClient) -> Self {
+        Self {
+            daemon,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Set how often the pool is polled
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Wait until a transaction spending `key_image` (hex) is in the pool or
+    /// the chain
+    pub async fn wait_for_spend(&self, key_image: &str) -> Result<ObservedSpend> {
+        info!("Watching mempool for spend of key image {}", key_image);
+        let key_images = [key_image.to_string()];
+        loop {
+            let pool = self.daemon.get_transaction_pool().await?;
+            if let Some(spend) = find_spend(&pool, key_image) {
+                info!("Spend {} seen in mempool", spend.tx_hash);
+                return Ok(ObservedSpend::InPool(spend));
+            }
+
+            // Mined between two polls
+            let status = self.daemon.is_key_image_spent(&key_images).await?;
+            if status.first() == Some(&KeyImageStatus::SpentInChain) {
+                info!("Key image {} already spent on chain", key_image);
+                return Ok(ObservedSpend::Mined);
+            }
+
+            debug!("Key image {} not spent yet", key_image);
+            sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// The pool transaction spending `key_image`, if any
+pub fn find_spend(pool: &TransactionPool, key_image: &str) -> Option<PoolSpend> {
+    let spent = pool
+        .spent_key_images
+        .iter()
+        .find(|spent| spent.key_image.eq_ignore_ascii_case(key_image))?;
+    spent.txs_hashes.iter().find_map(|hash| {
+        pool.transactions
+            .iter()
+            .find(|tx| &tx.id_hash == hash)
+            .map(|tx| PoolSpend {
+                tx_hash: tx.id_hash.clone(),
+                tx_blob: tx.tx_blob.clone(),
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_spend_in_pool() {
+        let pool: TransactionPool = serde_json::from_str(
+            r#"{
+                "transactions": [
+                    {"id_hash": "aa11", "tx_blob": "0201", "receive_time": 1700000000},
+                    {"id_hash": "bb22", "tx_blob": "0202", "receive_time": 1700000001}
+                ],
+                "spent_key_images": [
+                    {"id_hash": "ki01", "txs_hashes": ["aa11"]},
+                    {"id_hash": "KI02", "txs_hashes": ["bb22"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let spend = find_spend(&pool, "ki02").unwrap();
+        assert_eq!(spend.tx_hash, "bb22");
+        assert_eq!(spend.tx_blob, "0202");
+        assert_eq!(find_spend(&pool, "ki03"), None);
+        assert_eq!(find_spend(&TransactionPool::default(), "ki01"), None);
+    }
+}
@@ -0,0 +1,17 @@
+//! Monero Daemon (monerod) RPC Integration
+//!
+//! Talks to monerod directly, without a wallet-rpc process, for the pieces
+//! of ring construction and broadcasting the swap tooling does itself:
+//! output distribution and ring member keys for decoy selection, raw
+//! transaction relay, and transaction lookup. `mempool` watches the pool for
+//! the counterparty's spend of the locked output.
+
+pub mod client;
+pub mod error;
+pub mod mempool;
+pub mod types;
+
+pub use client::DaemonClient;
+pub use error::MoneroDaemonError;
+pub use mempool::{MempoolWatcher, ObservedSpend};
+pub use types::*;
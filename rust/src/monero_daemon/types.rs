@@ -0,0 +1,301 @@
+//! Monero Daemon RPC Types
+
+use serde::{Deserialize, Serialize};
+
+use crate::monero_daemon::error::MoneroDaemonError;
+
+/// Daemon status (`get_info`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DaemonInfo {
+    pub height: u64,
+    pub target_height: u64,
+    #[serde(default)]
+    pub synchronized: bool,
+    /// Still downloading blocks from peers
+    #[serde(default)]
+    pub busy_syncing: bool,
+    /// Running without network access (`--offline`)
+    #[serde(default)]
+    pub offline: bool,
+    #[serde(default)]
+    pub mainnet: bool,
+    #[serde(default)]
+    pub stagenet: bool,
+    #[serde(default)]
+    pub testnet: bool,
+    #[serde(default)]
+    pub top_block_hash: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+/// Monero network a daemon or wallet runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoneroNetwork {
+    Mainnet,
+    Stagenet,
+    Testnet,
+}
+
+impl std::fmt::Display for MoneroNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MoneroNetwork::Mainnet => "mainnet",
+            MoneroNetwork::Stagenet => "stagenet",
+            MoneroNetwork::Testnet => "testnet",
+        })
+    }
+}
+
+impl std::str::FromStr for MoneroNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(MoneroNetwork::Mainnet),
+            "stagenet" => Ok(MoneroNetwork::Stagenet),
+            "testnet" => Ok(MoneroNetwork::Testnet),
+            other => Err(format!(
+                "unknown Monero network '{}' (mainnet, stagenet or testnet)",
+                other
+            )),
+        }
+    }
+}
+
+impl DaemonInfo {
+    /// Network the daemon reports (`None` if it sets no flag)
+    pub fn network(&self) -> Option<MoneroNetwork> {
+        if self.mainnet {
+            Some(MoneroNetwork::Mainnet)
+        } else if self.stagenet {
+            Some(MoneroNetwork::Stagenet)
+        } else if self.testnet {
+            Some(MoneroNetwork::Testnet)
+        } else {
+            None
+        }
+    }
+
+    /// Fail unless the daemon is synchronized with the network.
+    ///
+    /// A daemon behind the tip reports a stale `height`, which makes every
+    /// confirmation count wrong. `target_height` is 0 when the daemon has no
+    /// better chain to sync to.
+    pub fn check_synced(&self) -> Result<(), MoneroDaemonError> {
+        let reason = if self.offline {
+            "daemon is offline"
+        } else if self.busy_syncing || !self.synchronized {
+            "daemon is still syncing"
+        } else if self.target_height > self.height {
+            "daemon is behind its peers"
+        } else {
+            return Ok(());
+        };
+        Err(MoneroDaemonError::NotSynchronized {
+            reason,
+            height: self.height,
+            target_height: self.target_height,
+        })
+    }
+}
+
+/// Output to look up with `get_outs` (amount 0 = RingCT)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OutputRequest {
+    pub amount: u64,
+    pub index: u64,
+}
+
+/// Ring member data (`get_outs`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutputEntry {
+    /// One-time public key (hex)
+    pub key: String,
+    /// Pedersen commitment (hex)
+    pub mask: String,
+    pub unlocked: bool,
+    pub height: u64,
+    #[serde(default)]
+    pub txid: String,
+}
+
+/// Per-block output counts for one amount (`get_output_distribution`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutputDistribution {
+    pub amount: u64,
+    /// Height of the first entry in `distribution`
+    pub start_height: u64,
+    /// Outputs per block, cumulative if requested
+    pub distribution: Vec<u64>,
+    /// Outputs created before `start_height`
+    #[serde(default)]
+    pub base: u64,
+}
+
+/// Relay result (`send_raw_transaction`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SendRawTxResult {
+    pub status: String,
+    pub reason: String,
+    pub not_relayed: bool,
+    pub double_spend: bool,
+    pub fee_too_low: bool,
+    pub invalid_input: bool,
+    pub invalid_output: bool,
+    pub low_mixin: bool,
+    pub overspend: bool,
+    pub too_big: bool,
+    pub sanity_check_failed: bool,
+}
+
+impl SendRawTxResult {
+    /// Human-readable rejection reasons reported by the daemon
+    pub fn rejection_reasons(&self) -> Vec<&'static str> {
+        [
+            (self.double_spend, "double spend"),
+            (self.fee_too_low, "fee too low"),
+            (self.invalid_input, "invalid input"),
+            (self.invalid_output, "invalid output"),
+            (self.low_mixin, "ring too small"),
+            (self.overspend, "overspend"),
+            (self.too_big, "transaction too big"),
+            (self.sanity_check_failed, "sanity check failed"),
+            (self.not_relayed, "not relayed"),
+        ]
+        .into_iter()
+        .filter_map(|(flag, reason)| flag.then_some(reason))
+        .collect()
+    }
+}
+
+/// Transaction lookup result (`get_transactions`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionEntry {
+    pub tx_hash: String,
+    /// Serialized transaction (hex)
+    pub as_hex: String,
+    #[serde(default)]
+    pub in_pool: bool,
+    /// Absent while in the pool
+    #[serde(default)]
+    pub block_height: u64,
+    #[serde(default)]
+    pub double_spend_seen: bool,
+}
+
+/// Transaction in the daemon's pool (`get_transaction_pool`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolTransaction {
+    pub id_hash: String,
+    /// Serialized transaction (hex)
+    pub tx_blob: String,
+    #[serde(default)]
+    pub receive_time: u64,
+    #[serde(default)]
+    pub double_spend_seen: bool,
+}
+
+/// Pool transactions spending a key image
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpentKeyImage {
+    /// Key image (hex); monerod calls it `id_hash`
+    #[serde(rename = "id_hash")]
+    pub key_image: String,
+    pub txs_hashes: Vec<String>,
+}
+
+/// Snapshot of the daemon's transaction pool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TransactionPool {
+    #[serde(default)]
+    pub transactions: Vec<PoolTransaction>,
+    #[serde(default)]
+    pub spent_key_images: Vec<SpentKeyImage>,
+}
+
+/// Key image state (`is_key_image_spent`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyImageStatus {
+    Unspent,
+    SpentInChain,
+    SpentInPool,
+}
+
+impl TryFrom<u8> for KeyImageStatus {
+    type Error = u8;
+
+    fn try_from(status: u8) -> Result<Self, u8> {
+        match status {
+            0 => Ok(Self::Unspent),
+            1 => Ok(Self::SpentInChain),
+            2 => Ok(Self::SpentInPool),
+            other => Err(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_daemon_responses() {
+        let outs: Vec<OutputEntry> = serde_json::from_str(
+            r#"[{"height":1500000,"key":"aa","mask":"bb","txid":"cc","unlocked":true}]"#,
+        )
+        .unwrap();
+        assert!(outs[0].unlocked);
+
+        let dist: OutputDistribution = serde_json::from_str(
+            r#"{"amount":0,"base":0,"distribution":[5,9,12],"start_height":1220516}"#,
+        )
+        .unwrap();
+        assert_eq!(dist.distribution, vec![5, 9, 12]);
+
+        let sent: SendRawTxResult = serde_json::from_str(
+            r#"{"status":"Failed","double_spend":true,"fee_too_low":false,"reason":""}"#,
+        )
+        .unwrap();
+        assert_eq!(sent.rejection_reasons(), vec!["double spend"]);
+
+        let pooled: TransactionEntry =
+            serde_json::from_str(r#"{"tx_hash":"dd","as_hex":"02","in_pool":true}"#).unwrap();
+        assert_eq!(pooled.block_height, 0);
+    }
+
+    #[test]
+    fn test_check_synced() {
+        let info = |json: &str| serde_json::from_str::<DaemonInfo>(json).unwrap();
+        let synced = info(r#"{"height":1500000,"target_height":0,"synchronized":true}"#);
+        assert!(synced.check_synced().is_ok());
+        let at_tip = info(r#"{"height":1500000,"target_height":1500000,"synchronized":true}"#);
+        assert!(at_tip.check_synced().is_ok());
+
+        for (json, expected) in [
+            (
+                r#"{"height":1200000,"target_height":1500000,"synchronized":false,"busy_syncing":true}"#,
+                "still syncing",
+            ),
+            (
+                r#"{"height":1499990,"target_height":1500000,"synchronized":true}"#,
+                "behind its peers",
+            ),
+            (
+                r#"{"height":1500000,"target_height":0,"synchronized":true,"offline":true}"#,
+                "offline",
+            ),
+            // Daemons too old to report sync state are not trusted
+            (r#"{"height":1500000,"target_height":0}"#, "still syncing"),
+        ] {
+            let err = info(json).check_synced().unwrap_err();
+            assert!(
+                matches!(err, MoneroDaemonError::NotSynchronized { .. }),
+                "{}",
+                json
+            );
+            assert!(err.to_string().contains(expected), "{}: {}", json, err);
+        }
+    }
+}
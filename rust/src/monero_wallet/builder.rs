@@ -0,0 +1,202 @@
+//! Builder for [`MoneroWallet`]
+//!
+//! Every option has a setter, so new ones can be added without breaking
+//! callers. [`MoneroWalletBuilder::build`] checks the options before any
+//! connection is made, then connects like [`MoneroWallet::new`].
+
+use anyhow::{Context, Result};
+use reqwest::{Client as HttpClient, Proxy, Url};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::monero_daemon::MoneroNetwork;
+use crate::monero_wallet::client::MoneroWallet;
+use crate::monero_wallet::error::MoneroWalletError;
+
+/// Default timeout of each wallet and daemon RPC call
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Options for a [`MoneroWallet`] (see [`MoneroWallet::builder`])
+#[derive(Clone, Default)]
+pub struct MoneroWalletBuilder {
+    wallet_rpc: Option<String>,
+    daemon: Option<String>,
+    wallet_name: Option<String>,
+    auth: Option<(String, String)>,
+    timeout: Option<Duration>,
+    proxy: Option<String>,
+    network: Option<MoneroNetwork>,
+}
+
+/// Checked options, ready to connect
+pub(crate) struct WalletConfig {
+    pub wallet_rpc_url: String,
+    pub daemon_rpc_url: String,
+    pub wallet_name: String,
+    pub auth: Option<(String, String)>,
+    pub network: Option<MoneroNetwork>,
+    pub http_client: HttpClient,
+}
+
+impl MoneroWalletBuilder {
+    /// wallet-rpc JSON-RPC endpoint (e.g. `http://localhost:38088/json_rpc`)
+    pub fn wallet_rpc(mut self, url: impl Into<String>) -> Self {
+        self.wallet_rpc = Some(url.into());
+        self
+    }
+
+    /// monerod base URL (e.g. `http://localhost:38081`)
+    pub fn daemon(mut self, url: impl Into<String>) -> Self {
+        self.daemon = Some(url.into());
+        self
+    }
+
+    /// Wallet file opened or created by wallet-rpc
+    pub fn wallet_name(mut self, name: impl Into<String>) -> Self {
+        self.wallet_name = Some(name.into());
+        self
+    }
+
+    /// HTTP basic auth for wallet-rpc (e.g. behind a TLS proxy).
+    ///
+    /// monero-wallet-rpc's own `--rpc-login` uses digest auth, which is not
+    /// supported; run it with `--disable-rpc-login` on a trusted interface.
+    pub fn auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Timeout of each RPC call (default 30 s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Route wallet and daemon calls through a proxy (`socks5h://` for Tor)
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Refuse a daemon on another network
+    pub fn network(mut self, network: MoneroNetwork) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Check the options and connect: wallet-rpc must answer `get_version`
+    /// and, with [`Self::network`], the daemon must be on that network.
+    pub async fn build(self) -> Result<MoneroWallet> {
+        let config = self.validate()?;
+        if let Some(expected) = config.network {
+            let actual = daemon_network(&config.http_client, &config.daemon_rpc_url).await?;
+            if actual != Some(expected) {
+                return Err(MoneroWalletError::WrongNetwork {
+                    expected: expected.to_string(),
+                    actual: actual.map_or("an unknown network".to_string(), |n| n.to_string()),
+                }
+                .into());
+            }
+        }
+        MoneroWallet::connect(config).await
+    }
+
+    pub(crate) fn validate(self) -> Result<WalletConfig, MoneroWalletError> {
+        let invalid = |message: String| MoneroWalletError::InvalidConfig(message);
+        let wallet_rpc_url = checked_url("wallet_rpc", self.wallet_rpc)?;
+        let daemon_rpc_url = checked_url("daemon", self.daemon)?;
+        let wallet_name = self
+            .wallet_name
+            .filter(|name| !name.trim().is_empty())
+            .ok_or_else(|| invalid("wallet_name is required".to_string()))?;
+        let timeout = self.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        if timeout.is_zero() {
+            return Err(invalid("timeout must be positive".to_string()));
+        }
+
+        let mut http_client = HttpClient::builder().timeout(timeout);
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy).map_err(|e| invalid(format!("proxy: {}", e)))?;
+            http_client = http_client.proxy(proxy);
+        }
+        let http_client = http_client
+            .build()
+            .map_err(|e| invalid(format!("HTTP client: {}", e)))?;
+
+        Ok(WalletConfig {
+            wallet_rpc_url,
+            daemon_rpc_url: daemon_rpc_url.trim_end_matches('/').to_string(),
+            wallet_name,
+            auth: self.auth,
+            network: self.network,
+            http_client,
+        })
+    }
+}
+
+/// `url`, if present and an http(s) URL
+fn checked_url(name: &str, url: Option<String>) -> Result<String, MoneroWalletError> {
+    let url =
+        url.ok_or_else(|| MoneroWalletError::InvalidConfig(format!("{} is required", name)))?;
+    match Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+        _ => Err(MoneroWalletError::InvalidConfig(format!(
+            "{} must be an http(s) URL, got '{}'",
+            name, url
+        ))),
+    }
+}
+
+/// Network flags from the daemon's `get_info`
+async fn daemon_network(client: &HttpClient, daemon_url: &str) -> Result<Option<MoneroNetwork>> {
+    #[derive(Deserialize)]
+    struct Response {
+        result: crate::monero_daemon::DaemonInfo,
+    }
+
+    let resp: Response = client
+        .post(format!("{}/json_rpc", daemon_url))
+        .json(&serde_json::json!({"jsonrpc": "2.0", "id": "0", "method": "get_info"}))
+        .send()
+        .await
+        .context("Failed to call daemon get_info")?
+        .json()
+        .await
+        .context("Failed to parse daemon get_info response")?;
+    Ok(resp.result.network())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> MoneroWalletBuilder {
+        MoneroWallet::builder()
+            .wallet_rpc("http://localhost:38088/json_rpc")
+            .daemon("http://localhost:38081/")
+            .wallet_name("swap")
+    }
+
+    #[test]
+    fn test_builder_validates_options() {
+        let config = builder()
+            .auth("user", "pass")
+            .proxy("socks5h://127.0.0.1:9050")
+            .network(MoneroNetwork::Stagenet)
+            .validate()
+            .unwrap();
+        assert_eq!(config.daemon_rpc_url, "http://localhost:38081");
+        assert_eq!(config.network, Some(MoneroNetwork::Stagenet));
+
+        let invalid = |builder: MoneroWalletBuilder| {
+            matches!(builder.validate(), Err(MoneroWalletError::InvalidConfig(_)))
+        };
+        assert!(invalid(
+            MoneroWallet::builder().daemon("http://localhost:38081")
+        ));
+        assert!(invalid(builder().wallet_rpc("localhost:38088")));
+        assert!(invalid(builder().wallet_name(" ")));
+        assert!(invalid(builder().timeout(Duration::ZERO)));
+        assert!(invalid(builder().proxy("not a proxy")));
+    }
+}
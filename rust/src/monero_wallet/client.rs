@@ -1,5 +1,5 @@
 //! Production-grade Monero Wallet RPC Client
-//! 
+//!
 //! Based on COMIT Network's battle-tested implementation for atomic swaps.
 //! Provides secure wallet operations for Monero atomic swap protocol.
 
@@ -8,13 +8,18 @@ use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::time::sleep;
+#[cfg(feature = "legacy-unlock-time")]
+use tracing::warn;
 use tracing::{debug, info};
 
+use crate::monero::policy::{check_observed_unlock_time, NO_UNLOCK_TIME};
+use crate::monero_wallet::builder::{MoneroWalletBuilder, WalletConfig};
 use crate::monero_wallet::error::MoneroWalletError;
 use crate::monero_wallet::types::{TransferInfo, TransferResult};
+use crate::swap::confirmations::required_confirmations;
 
 /// Production-grade Monero wallet RPC client
-/// 
+///
 /// Based on COMIT Network's 3+ years of mainnet atomic swap experience.
 /// Provides secure wallet operations for atomic swap protocol.
 pub struct MoneroWallet {
@@ -26,34 +31,66 @@ pub struct MoneroWallet {
     daemon_rpc_url: String,
     /// Wallet name (for multi-wallet support)
     wallet_name: String,
+    /// HTTP basic auth for wallet-rpc
+    auth: Option<(String, String)>,
 }
 
 impl MoneroWallet {
     /// Create new wallet client
-    /// 
+    ///
     /// # Production Requirements
     /// 1. wallet-rpc must be running: `monero-wallet-rpc --stagenet --rpc-bind-port 38088`
     /// 2. Daemon must be synced and accessible
     /// 3. Wallet must be opened or created
+    ///
+    /// Shorthand for [`Self::builder`] with the three required options.
     pub async fn new(
         wallet_rpc_url: String,
         daemon_rpc_url: String,
         wallet_name: String,
     ) -> Result<Self> {
-        let http_client = HttpClient::builder()
-            .timeout(Duration::from_secs(30))
+        Self::builder()
+            .wallet_rpc(wallet_rpc_url)
+            .daemon(daemon_rpc_url)
+            .wallet_name(wallet_name)
             .build()
-            .context("Failed to create HTTP client")?;
+            .await
+    }
 
+    /// Options (auth, timeout, proxy, network) checked at `build()`:
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use xmr_secret_gen::monero_daemon::MoneroNetwork;
+    /// use xmr_secret_gen::monero_wallet::MoneroWallet;
+    ///
+    /// let wallet = MoneroWallet::builder()
+    ///     .wallet_rpc("http://localhost:38088/json_rpc")
+    ///     .daemon("http://localhost:38081")
+    ///     .wallet_name("swap")
+    ///     .network(MoneroNetwork::Stagenet)
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> MoneroWalletBuilder {
+        MoneroWalletBuilder::default()
+    }
+
+    pub(crate) async fn connect(config: WalletConfig) -> Result<Self> {
         let wallet = Self {
-            http_client,
-            wallet_rpc_url,
-            daemon_rpc_url,
-            wallet_name,
+            http_client: config.http_client,
+            wallet_rpc_url: config.wallet_rpc_url,
+            daemon_rpc_url: config.daemon_rpc_url,
+            wallet_name: config.wallet_name,
+            auth: config.auth,
         };
 
         // Verify wallet-rpc is reachable
-        wallet.get_version().await
+        wallet
+            .get_version()
+            .await
             .context("Failed to connect to monero-wallet-rpc")?;
 
         Ok(wallet)
@@ -84,8 +121,8 @@ impl MoneroWallet {
             method: "get_version".to_string(),
         };
 
-        let resp: Response = self.http_client
-            .post(&self.wallet_rpc_url)
+        let resp: Response = self
+            .wallet_rpc()
             .json(&req)
             .send()
             .await
@@ -109,10 +146,15 @@ impl MoneroWallet {
         #[derive(Deserialize)]
         struct EmptyResponse {}
 
-        let _: EmptyResponse = self.call_wallet_rpc("open_wallet", Params {
-            filename: self.wallet_name.clone(),
-            password: password.to_string(),
-        }).await?;
+        let _: EmptyResponse = self
+            .call_wallet_rpc(
+                "open_wallet",
+                Params {
+                    filename: self.wallet_name.clone(),
+                    password: password.to_string(),
+                },
+            )
+            .await?;
 
         Ok(())
     }
@@ -129,15 +171,109 @@ impl MoneroWallet {
         #[derive(Deserialize)]
         struct EmptyResponse {}
 
-        let _: EmptyResponse = self.call_wallet_rpc("create_wallet", Params {
-            filename: self.wallet_name.clone(),
-            password: password.to_string(),
-            language: "English".to_string(),
-        }).await?;
+        let _: EmptyResponse = self
+            .call_wallet_rpc(
+                "create_wallet",
+                Params {
+                    filename: self.wallet_name.clone(),
+                    password: password.to_string(),
+                    language: "English".to_string(),
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Restore a wallet from its keys, scanning from `restore_height`
+    ///
+    /// Used to recover a swap wallet (e.g. on a new machine); take the
+    /// height from `restore::lock_tx_restore_height` to skip the blocks
+    /// before the lock transaction.
+    pub async fn generate_from_keys(
+        &self,
+        address: &str,
+        spend_key_hex: &str,
+        view_key_hex: &str,
+        password: &str,
+        restore_height: u64,
+    ) -> Result<()> {
+        #[derive(Serialize)]
+        struct Params {
+            filename: String,
+            address: String,
+            spendkey: String,
+            viewkey: String,
+            password: String,
+            restore_height: u64,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            address: String,
+        }
 
+        let resp: Response = self
+            .call_wallet_rpc(
+                "generate_from_keys",
+                Params {
+                    filename: self.wallet_name.clone(),
+                    address: address.to_string(),
+                    spendkey: spend_key_hex.to_string(),
+                    viewkey: view_key_hex.to_string(),
+                    password: password.to_string(),
+                    restore_height,
+                },
+            )
+            .await?;
+
+        if resp.address != address {
+            return Err(MoneroWalletError::WalletOperationFailed(format!(
+                "Restored wallet address {} does not match {}",
+                resp.address, address
+            ))
+            .into());
+        }
+        info!(
+            "Restored wallet {} from height {}",
+            self.wallet_name, restore_height
+        );
         Ok(())
     }
 
+    /// Scan for new blocks starting at `height`; returns the blocks fetched
+    ///
+    /// wallet2 only honors `start_height` above what it has already scanned,
+    /// so this is cheap on a wallet restored near `height` and a no-op for
+    /// blocks it already has.
+    pub async fn rescan_from(&self, height: u64) -> Result<u64> {
+        #[derive(Serialize)]
+        struct Params {
+            start_height: u64,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            blocks_fetched: u64,
+            #[allow(dead_code)]
+            received_money: bool,
+        }
+
+        let resp: Response = self
+            .call_wallet_rpc(
+                "refresh",
+                Params {
+                    start_height: height,
+                },
+            )
+            .await?;
+        debug!(
+            "Fetched {} blocks from height {}",
+            resp.blocks_fetched, height
+        );
+        Ok(resp.blocks_fetched)
+    }
+
     /// Get primary address
     pub async fn get_address(&self) -> Result<String> {
         #[derive(Serialize)]
@@ -150,9 +286,9 @@ impl MoneroWallet {
             address: String,
         }
 
-        let resp: Response = self.call_wallet_rpc("get_address", Params {
-            account_index: 0,
-        }).await?;
+        let resp: Response = self
+            .call_wallet_rpc("get_address", Params { account_index: 0 })
+            .await?;
 
         Ok(resp.address)
     }
@@ -171,9 +307,9 @@ impl MoneroWallet {
             unlocked_balance: u64,
         }
 
-        let resp: Response = self.call_wallet_rpc("get_balance", Params {
-            account_index: 0,
-        }).await?;
+        let resp: Response = self
+            .call_wallet_rpc("get_balance", Params { account_index: 0 })
+            .await?;
 
         Ok((resp.balance, resp.unlocked_balance))
     }
@@ -192,20 +328,59 @@ impl MoneroWallet {
         Ok(resp.height)
     }
 
-    /// Create locked transaction (CRITICAL FOR ATOMIC SWAPS)
-    /// 
-    /// This is the CORE method for atomic swap implementation
-    /// COMIT pattern: Lock XMR with timelock + view key
-    /// 
+    /// Send XMR to the swap lock address (CRITICAL FOR ATOMIC SWAPS)
+    ///
+    /// KEY SPLITTING lock: `destination` is the address of the joint key
+    /// P = (x_partial + t)·G. The funds are locked because nobody can sign
+    /// for P until `t` is revealed on Starknet, NOT because of a timelock.
+    /// The transfer is always created with `unlock_time = 0`.
+    ///
+    /// # Arguments
+    /// * `destination` - Monero address as string
+    /// * `amount_piconero` - Amount in piconero (atomic units, 1 XMR = 10^12 piconero)
+    pub async fn transfer(
+        &self,
+        destination: &str,
+        amount_piconero: u64,
+    ) -> Result<TransferResult> {
+        self.transfer_with_unlock_time(destination, amount_piconero, NO_UNLOCK_TIME)
+            .await
+    }
+
+    /// Create locked transaction with a consensus `unlock_time` (LEGACY)
+    ///
+    /// Monero is deprecating enforceable `unlock_time`, so the timelock is NOT
+    /// a security guarantee. Only available with the `legacy-unlock-time`
+    /// feature for interoperating with older tooling; use [`Self::transfer`].
+    ///
     /// # Arguments
     /// * `destination` - Monero address as string
     /// * `amount_piconero` - Amount in piconero (atomic units, 1 XMR = 10^12 piconero)
     /// * `unlock_time` - Block height when funds unlock
+    #[cfg(feature = "legacy-unlock-time")]
+    #[deprecated(note = "unlock_time is deprecated in Monero; use `transfer` (key splitting)")]
     pub async fn transfer_locked(
         &self,
         destination: &str,
         amount_piconero: u64,
         unlock_time: u64,
+    ) -> Result<TransferResult> {
+        if let Err(e) = crate::monero::policy::ensure_no_unlock_time(unlock_time) {
+            warn!(
+                "{} (legacy path, timelock is not relied on for security)",
+                e
+            );
+        }
+        self.transfer_with_unlock_time(destination, amount_piconero, unlock_time)
+            .await
+    }
+
+    /// Shared `transfer` RPC call
+    async fn transfer_with_unlock_time(
+        &self,
+        destination: &str,
+        amount_piconero: u64,
+        unlock_time: u64,
     ) -> Result<TransferResult> {
         #[derive(Serialize)]
         struct Params {
@@ -231,16 +406,21 @@ impl MoneroWallet {
             fee: u64,
         }
 
-        let resp: Response = self.call_wallet_rpc("transfer", Params {
-            destinations: vec![Destination {
-                address: destination.to_string(),
-                amount: amount_piconero,
-            }],
-            account_index: 0,
-            unlock_time,
-            get_tx_key: true,
-            get_tx_hex: true,
-        }).await?;
+        let resp: Response = self
+            .call_wallet_rpc(
+                "transfer",
+                Params {
+                    destinations: vec![Destination {
+                        address: destination.to_string(),
+                        amount: amount_piconero,
+                    }],
+                    account_index: 0,
+                    unlock_time,
+                    get_tx_key: true,
+                    get_tx_hex: true,
+                },
+            )
+            .await?;
 
         Ok(TransferResult {
             tx_hash: resp.tx_hash,
@@ -251,7 +431,7 @@ impl MoneroWallet {
     }
 
     /// Get transaction information (PREVENTS DOUBLE-SPENDING)
-    /// 
+    ///
     /// Key images are CRITICAL for atomic swap security
     /// COMIT uses this to verify XMR is truly locked
     pub async fn get_transfer_by_txid(&self, txid: &str) -> Result<TransferInfo> {
@@ -273,9 +453,14 @@ impl MoneroWallet {
             unlock_time: u64,
         }
 
-        let resp: Response = self.call_wallet_rpc("get_transfer_by_txid", Params {
-            txid: txid.to_string(),
-        }).await?;
+        let resp: Response = self
+            .call_wallet_rpc(
+                "get_transfer_by_txid",
+                Params {
+                    txid: txid.to_string(),
+                },
+            )
+            .await?;
 
         Ok(TransferInfo {
             amount: resp.transfer.amount,
@@ -294,27 +479,43 @@ impl MoneroWallet {
         loop {
             let info = self.get_transfer_by_txid(txid).await?;
 
-            if info.confirmations >= required_confirmations {
+            if lock_confirmed(&info, required_confirmations)? {
                 info!(
                     "Transaction {} has {} confirmations (required: {})",
-                    txid,
-                    info.confirmations,
-                    required_confirmations
+                    txid, info.confirmations, required_confirmations
                 );
                 return Ok(());
             }
 
             debug!(
                 "Waiting for confirmations: {}/{} for tx {}",
-                info.confirmations,
-                required_confirmations,
-                txid
+                info.confirmations, required_confirmations, txid
             );
 
             sleep(Duration::from_secs(120)).await; // ~2 min per block
         }
     }
 
+    /// Wait for the confirmations the shared policy requires for a swap of
+    /// `amount_piconero` (see `swap::confirmations`)
+    pub async fn wait_for_swap_confirmations(
+        &self,
+        txid: &str,
+        amount_piconero: u64,
+    ) -> Result<()> {
+        let required = required_confirmations(amount_piconero).monero_confirmations;
+        self.wait_for_confirmations(txid, required).await
+    }
+
+    /// POST to wallet-rpc, with basic auth when configured
+    fn wallet_rpc(&self) -> reqwest::RequestBuilder {
+        let request = self.http_client.post(&self.wallet_rpc_url);
+        match &self.auth {
+            Some((username, password)) => request.basic_auth(username, Some(password)),
+            None => request,
+        }
+    }
+
     /// Generic JSON-RPC call helper
     async fn call_wallet_rpc<P: Serialize, R: for<'de> Deserialize<'de>>(
         &self,
@@ -338,12 +539,8 @@ impl MoneroWallet {
         #[derive(Deserialize)]
         #[serde(untagged)]
         enum JsonRpcResponse<R> {
-            Success {
-                result: R,
-            },
-            Error {
-                error: RpcError,
-            },
+            Success { result: R },
+            Error { error: RpcError },
         }
 
         let req = Request {
@@ -353,8 +550,8 @@ impl MoneroWallet {
             params,
         };
 
-        let resp: JsonRpcResponse<R> = self.http_client
-            .post(&self.wallet_rpc_url)
+        let resp: JsonRpcResponse<R> = self
+            .wallet_rpc()
             .json(&req)
             .send()
             .await
@@ -365,13 +562,52 @@ impl MoneroWallet {
 
         match resp {
             JsonRpcResponse::Success { result } => Ok(result),
-            JsonRpcResponse::Error { error } => {
-                Err(MoneroWalletError::RpcCallFailed(format!(
-                    "RPC error {}: {}",
-                    error.code, error.message
-                )).into())
-            }
+            JsonRpcResponse::Error { error } => Err(MoneroWalletError::RpcCallFailed(format!(
+                "RPC error {}: {}",
+                error.code, error.message
+            ))
+            .into()),
         }
     }
 }
 
+/// Whether the lock transfer has `required` confirmations.
+///
+/// A transfer carrying an `unlock_time` is rejected outright (see
+/// `monero::policy`): waiting on it would let the counterparty delay the
+/// sweep after `t` is revealed.
+fn lock_confirmed(info: &TransferInfo, required: u64) -> Result<bool, MoneroWalletError> {
+    check_observed_unlock_time(info.unlock_time)?;
+    Ok(info.confirmations >= required)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monero::policy::PolicyError;
+
+    fn transfer(confirmations: u64, unlock_time: u64) -> TransferInfo {
+        TransferInfo {
+            amount: 1_000_000_000_000,
+            confirmations,
+            height: 1_500_000,
+            unlock_time,
+        }
+    }
+
+    #[test]
+    fn test_lock_confirmed_counts_confirmations() {
+        assert!(!lock_confirmed(&transfer(9, NO_UNLOCK_TIME), 10).unwrap());
+        assert!(lock_confirmed(&transfer(10, NO_UNLOCK_TIME), 10).unwrap());
+    }
+
+    #[test]
+    fn test_timelocked_lock_aborts_swap() {
+        // Even fully confirmed, a timelocked transfer never counts as locked
+        let err = lock_confirmed(&transfer(20, 1_600_000), 10).unwrap_err();
+        assert!(matches!(
+            err,
+            MoneroWalletError::Policy(PolicyError::UnlockTimeNotAllowed(1_600_000))
+        ));
+    }
+}
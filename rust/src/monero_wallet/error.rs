@@ -2,25 +2,31 @@
 
 use thiserror::Error;
 
+use crate::monero::policy::PolicyError;
+
 #[derive(Debug, Error)]
 pub enum MoneroWalletError {
     #[error("RPC connection failed: {0}")]
     ConnectionFailed(String),
-    
+
     #[error("RPC call failed: {0}")]
     RpcCallFailed(String),
-    
+
     #[error("Invalid response format: {0}")]
     InvalidResponse(String),
-    
+
     #[error("Wallet operation failed: {0}")]
     WalletOperationFailed(String),
-    
+
     #[error("Insufficient balance: required {required}, available {available}")]
-    InsufficientBalance {
-        required: u64,
-        available: u64,
-    },
-}
+    InsufficientBalance { required: u64, available: u64 },
 
+    #[error("Invalid wallet configuration: {0}")]
+    InvalidConfig(String),
 
+    #[error("Daemon is on {actual}, expected {expected}")]
+    WrongNetwork { expected: String, actual: String },
+
+    #[error("Lock policy violation: {0}")]
+    Policy(#[from] PolicyError),
+}
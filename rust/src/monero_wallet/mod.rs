@@ -1,14 +1,16 @@
 //! Monero Wallet RPC Integration
-//! 
+//!
 //! Production-grade wallet RPC client based on COMIT Network's
 //! battle-tested implementation for atomic swaps.
 
+pub mod builder;
 pub mod client;
 pub mod error;
+pub mod restore;
 pub mod types;
 
+pub use builder::MoneroWalletBuilder;
 pub use client::MoneroWallet;
 pub use error::MoneroWalletError;
+pub use restore::{lock_tx_restore_height, restore_height, DEFAULT_RESTORE_BUFFER};
 pub use types::*;
-
-
@@ -0,0 +1,48 @@
+//! Restore heights for swap wallets.
+//!
+//! A swap wallet (the joint key P = (x_partial + t)·G) only ever receives
+//! the lock transaction, so nothing before it needs scanning. Restoring
+//! from height 0 takes hours on mainnet; restoring from the lock height
+//! minus a buffer takes seconds. The buffer covers a reorg of the lock
+//! block and a height recorded slightly late.
+
+use anyhow::{Context, Result};
+
+use crate::monero_daemon::DaemonClient;
+
+/// Blocks scanned before the lock transaction (~1 day of 2-minute blocks)
+pub const DEFAULT_RESTORE_BUFFER: u64 = 720;
+
+/// Restore height for a wallet whose first transaction is at `lock_tx_height`
+pub fn restore_height(lock_tx_height: u64, buffer: u64) -> u64 {
+    lock_tx_height.saturating_sub(buffer)
+}
+
+/// Restore height for the swap wallet of lock transaction `txid`
+///
+/// A lock transaction still in the pool will be mined at or after the
+/// current height, so that height is used instead.
+pub async fn lock_tx_restore_height(daemon: &DaemonClient, txid: &str, buffer: u64) -> Result<u64> {
+    let entries = daemon.get_transactions(&[txid.to_string()]).await?;
+    let entry = entries
+        .first()
+        .with_context(|| format!("Lock transaction {} not found by the daemon", txid))?;
+
+    let height = if entry.in_pool {
+        daemon.get_info().await?.height
+    } else {
+        entry.block_height
+    };
+    Ok(restore_height(height, buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_height_buffer() {
+        assert_eq!(restore_height(3_000_000, DEFAULT_RESTORE_BUFFER), 2_999_280);
+        assert_eq!(restore_height(100, DEFAULT_RESTORE_BUFFER), 0);
+    }
+}
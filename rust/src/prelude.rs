@@ -0,0 +1,26 @@
+//! The types a wallet integrator needs, in one import.
+//!
+//! ```ignore
+//! use xmr_secret_gen::prelude::*;
+//! ```
+//!
+//! - Secrets: `SwapSecret` / `generate_swap_secret` (t, its hashlock and
+//!   the Cairo calldata for the AtomicLock constructor), `SwapSecretError`
+//! - Proofs: `DleqProof` binding the adaptor point `T = t·G` to the hashlock
+//! - Keys: `SwapKeyPair`, the Monero spend key split around `t`
+//! - Signatures: `AdaptorSignature`, finalized once `t` is revealed
+//! - Lifecycle: `SwapOrchestrator` and its serializable `SwapState`
+//!
+//! Adaptor points are plain `EdwardsPoint`s, and this tree has no CLSAG
+//! adaptor session yet; the Ed25519-style adaptor signature is the signing
+//! API. Everything else (hint generation, limb encodings, RPC clients) stays
+//! reachable through its module but is not part of this set.
+
+pub use crate::adaptor::{create_adaptor_signature, finalize_signature, AdaptorSignature};
+pub use crate::dleq::{generate_dleq_proof, verify_dleq_proof, DleqError, DleqProof};
+pub use crate::monero::SwapKeyPair;
+pub use crate::swap::confirmations::{required_confirmations, ConfirmationRequirement};
+pub use crate::swap::orchestrator::{OrchestratorError, PhaseChange, SwapOrchestrator, SwapState};
+pub use crate::swap::reconcile::NextAction;
+pub use crate::swap::{SwapPhase, SwapRole};
+pub use crate::{generate_swap_secret, generate_swap_secret_with_rng, SwapSecret, SwapSecretError};
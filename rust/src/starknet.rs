@@ -4,14 +4,185 @@
 //! - Deploy AtomicLock contracts on Sepolia
 //! - Watch for Unlocked events
 //! - Call verify_and_unlock
+//! - Recover the revealed secret from the reveal transaction's calldata
+//! - Wait for submitted transactions to reach L2 or L1 finality
+//! - Refuse nodes on another network than the configured one
 
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::cairo_serde::byte_array_from_calldata;
+use crate::contracts::atomic_lock::{self, AtomicLockEvent, LockEvent, SecretRevealed, Unlocked};
+
+/// On-chain view of an AtomicLock contract (read via `starknet_call`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtomicLockStatus {
+    /// `is_secret_revealed()`: `t` has been revealed (grace period started).
+    pub secret_revealed: bool,
+    /// `is_unlocked()`: tokens left the contract (claimed or refunded).
+    pub unlocked: bool,
+    /// `get_lock_until()`: refund timelock (unix seconds).
+    pub lock_until: u64,
+    /// `get_claimable_after()`: end of the grace period, 0 if not revealed.
+    pub claimable_after: u64,
+}
+
+/// Finality a transaction must reach before [`StarknetClient::wait_for_transaction`]
+/// returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finality {
+    /// Included in an L2 block (`ACCEPTED_ON_L2`).
+    AcceptedOnL2,
+    /// The block was proven on Ethereum (`ACCEPTED_ON_L1`), hours later.
+    AcceptedOnL1,
+}
+
+impl Finality {
+    /// Finality reached by a receipt; `None` while only pre-confirmed.
+    fn of_receipt(receipt: &Value) -> Option<Self> {
+        match receipt["finality_status"].as_str()? {
+            "ACCEPTED_ON_L2" => Some(Self::AcceptedOnL2),
+            "ACCEPTED_ON_L1" => Some(Self::AcceptedOnL1),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Finality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::AcceptedOnL2 => "ACCEPTED_ON_L2",
+            Self::AcceptedOnL1 => "ACCEPTED_ON_L1",
+        })
+    }
+}
+
+impl FromStr for Finality {
+    type Err = String;
+
+    /// `l2` / `l1`, or the RPC names (`ACCEPTED_ON_L2`, `ACCEPTED_ON_L1`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "L2" | "ACCEPTED_ON_L2" => Ok(Self::AcceptedOnL2),
+            "L1" | "ACCEPTED_ON_L1" => Ok(Self::AcceptedOnL1),
+            _ => Err(format!("unknown finality '{}' (expected l2 or l1)", s)),
+        }
+    }
+}
+
+/// Why [`StarknetClient::wait_for_transaction`] gave up on a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TransactionError {
+    /// Included, but execution failed: fees were paid, no state changed.
+    #[error("Transaction {tx_hash} reverted: {reason}")]
+    Reverted { tx_hash: String, reason: String },
+
+    /// Never included (failed validation in the sequencer).
+    #[error("Transaction {tx_hash} rejected: {reason}")]
+    Rejected { tx_hash: String, reason: String },
+
+    #[error("Transaction {tx_hash} not {finality} after {}s", waited.as_secs())]
+    Timeout {
+        tx_hash: String,
+        finality: Finality,
+        waited: Duration,
+    },
+}
+
+/// Starknet network a client is meant to talk to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Sepolia,
+}
+
+impl Network {
+    /// Chain id the network's nodes report (`starknet_chainId`, decoded).
+    pub fn chain_id(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "SN_MAIN",
+            Self::Sepolia => "SN_SEPOLIA",
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.chain_id())
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    /// `mainnet` / `sepolia`, or the chain ids (`SN_MAIN`, `SN_SEPOLIA`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "MAINNET" | "SN_MAIN" => Ok(Self::Mainnet),
+            "SEPOLIA" | "SN_SEPOLIA" => Ok(Self::Sepolia),
+            _ => Err(format!(
+                "unknown network '{}' (expected mainnet or sepolia)",
+                s
+            )),
+        }
+    }
+}
+
+/// The RPC node serves a different chain than the client was configured for.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("RPC node is on chain {actual}, but the client is configured for {expected}")]
+pub struct ChainMismatch {
+    pub expected: Network,
+    pub actual: String,
+}
+
+impl ChainMismatch {
+    /// Compare the node's chain id (a short string felt) with `expected`.
+    pub fn check(expected: Network, chain_id_felt: &str) -> Result<(), Self> {
+        let actual = decode_short_string(chain_id_felt);
+        if actual == expected.chain_id() {
+            Ok(())
+        } else {
+            Err(Self { expected, actual })
+        }
+    }
+}
+
+/// Cairo short string from a hex felt; the hex itself if it is not one.
+fn decode_short_string(felt: &str) -> String {
+    let digits = felt.trim_start_matches("0x").trim_start_matches('0');
+    let digits = if digits.len() % 2 == 1 {
+        format!("0{}", digits)
+    } else {
+        digits.to_string()
+    };
+    hex::decode(&digits)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .filter(|text| text.chars().all(|c| c.is_ascii_graphic()))
+        .unwrap_or_else(|| felt.to_string())
+}
+
+/// Default pause between receipt polls.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Starknet RPC client (simplified, using HTTP JSON-RPC).
 pub struct StarknetClient {
     rpc_url: String,
     client: reqwest::Client,
+    poll_interval: Duration,
+    transaction_timeout: Option<Duration>,
+    network: Option<Network>,
+    /// Set once the node's chain id matched `network`
+    network_checked: OnceLock<()>,
+    #[cfg(feature = "chaos")]
+    chaos: Option<std::sync::Arc<crate::chaos::Chaos>>,
 }
 
 impl StarknetClient {
@@ -19,11 +190,70 @@ impl StarknetClient {
         Self {
             rpc_url,
             client: reqwest::Client::new(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            transaction_timeout: None,
+            network: None,
+            network_checked: OnceLock::new(),
+            #[cfg(feature = "chaos")]
+            chaos: None,
         }
     }
 
+    /// Inject faults into every RPC call (chaos tests only).
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, chaos: std::sync::Arc<crate::chaos::Chaos>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Pause between polls in [`Self::wait_for_transaction`].
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Give up waiting for a transaction after `timeout` (default: wait forever).
+    pub fn with_transaction_timeout(mut self, timeout: Duration) -> Self {
+        self.transaction_timeout = Some(timeout);
+        self
+    }
+
+    /// Refuse to talk to a node on another network: before the first request,
+    /// `starknet_chainId` must match `network` (else [`ChainMismatch`]).
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Chain id of the node, as a hex short string felt.
+    pub async fn chain_id(&self) -> Result<String> {
+        let result = self.request("starknet_chainId", json!([])).await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .context("Invalid chain id format")
+    }
+
+    /// Check the node against the configured network, once.
+    pub async fn check_network(&self) -> Result<()> {
+        let Some(network) = self.network else {
+            return Ok(());
+        };
+        if self.network_checked.get().is_none() {
+            ChainMismatch::check(network, &self.chain_id().await?)?;
+            let _ = self.network_checked.set(());
+        }
+        Ok(())
+    }
+
     /// Call Starknet JSON-RPC method.
     async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        self.check_network().await?;
+        self.request(method, params).await
+    }
+
+    /// Send one JSON-RPC request, without the network check.
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
         let payload = json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -31,6 +261,11 @@ impl StarknetClient {
             "params": params,
         });
 
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.chaos {
+            chaos.before_request().await;
+        }
+
         let response = self
             .client
             .post(&self.rpc_url)
@@ -39,10 +274,16 @@ impl StarknetClient {
             .await
             .context("Failed to send RPC request")?;
 
-        let result: Value = response
-            .json()
+        let body = response
+            .text()
             .await
-            .context("Failed to parse RPC response")?;
+            .context("Failed to read RPC response")?;
+        #[cfg(feature = "chaos")]
+        let body = match &self.chaos {
+            Some(chaos) => chaos.after_response(method, &payload["params"].to_string(), body)?,
+            None => body,
+        };
+        let result: Value = serde_json::from_str(&body).context("Failed to parse RPC response")?;
 
         if let Some(error) = result.get("error") {
             anyhow::bail!("RPC error: {}", error);
@@ -81,6 +322,291 @@ impl StarknetClient {
         Ok(result.as_array().cloned().unwrap_or_default())
     }
 
+    /// Call a view function (`starknet_call` against the latest block).
+    ///
+    /// Returns the raw felts of the result as hex strings.
+    pub async fn call_view(
+        &self,
+        contract_address: &str,
+        function: &str,
+        calldata: Vec<String>,
+    ) -> Result<Vec<String>> {
+        self.call_view_at(contract_address, function, calldata, json!("latest"))
+            .await
+    }
+
+    /// Call a view function against `block_id` (`"latest"` or
+    /// `{"block_number": n}`).
+    async fn call_view_at(
+        &self,
+        contract_address: &str,
+        function: &str,
+        calldata: Vec<String>,
+        block_id: Value,
+    ) -> Result<Vec<String>> {
+        let request = json!({
+            "contract_address": contract_address,
+            "entry_point_selector": get_selector_from_name(function),
+            "calldata": calldata,
+        });
+
+        let result = self
+            .call(
+                "starknet_call",
+                json!({ "request": request, "block_id": block_id }),
+            )
+            .await
+            .with_context(|| format!("Failed to call {}", function))?;
+
+        result
+            .as_array()
+            .context("Invalid starknet_call result format")?
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .context("Invalid felt in starknet_call result")
+            })
+            .collect()
+    }
+
+    /// Read the lifecycle state of an AtomicLock contract.
+    ///
+    /// All views are read at one block: reading each at `latest` could mix
+    /// states from before and after a reveal or claim (e.g. "unlocked but
+    /// never revealed", which looks like a refund).
+    pub async fn get_lock_status(&self, contract_address: &str) -> Result<AtomicLockStatus> {
+        let block_id = json!({ "block_number": self.get_block_number().await? });
+        let view =
+            |function| self.call_view_at(contract_address, function, vec![], block_id.clone());
+        let secret_revealed = view(atomic_lock::IS_SECRET_REVEALED).await?;
+        let unlocked = view(atomic_lock::IS_UNLOCKED).await?;
+        let lock_until = view(atomic_lock::GET_LOCK_UNTIL).await?;
+        let claimable_after = view(atomic_lock::GET_CLAIMABLE_AFTER).await?;
+
+        Ok(AtomicLockStatus {
+            secret_revealed: parse_felt_u64(first_felt(&secret_revealed)?)? != 0,
+            unlocked: parse_felt_u64(first_felt(&unlocked)?)? != 0,
+            lock_until: parse_felt_u64(first_felt(&lock_until)?)?,
+            claimable_after: parse_felt_u64(first_felt(&claimable_after)?)?,
+        })
+    }
+
+    /// Poll `starknet_getTransactionReceipt` until `tx_hash` reaches `finality`.
+    ///
+    /// Returns the raw receipt. A revert is reported as soon as the
+    /// transaction is included, whatever finality was asked for. Failures
+    /// are [`TransactionError`]s (reachable with `downcast_ref`), so callers
+    /// can tell a revert from a timeout.
+    pub async fn wait_for_transaction(&self, tx_hash: &str, finality: Finality) -> Result<Value> {
+        let started = Instant::now();
+        loop {
+            match self
+                .call(
+                    "starknet_getTransactionReceipt",
+                    json!({ "transaction_hash": tx_hash }),
+                )
+                .await
+            {
+                Ok(receipt) => {
+                    if receipt["execution_status"].as_str() == Some("REVERTED") {
+                        return Err(TransactionError::Reverted {
+                            tx_hash: tx_hash.to_string(),
+                            reason: receipt["revert_reason"]
+                                .as_str()
+                                .unwrap_or("unknown reason")
+                                .to_string(),
+                        }
+                        .into());
+                    }
+                    if Finality::of_receipt(&receipt).is_some_and(|reached| reached >= finality) {
+                        return Ok(receipt);
+                    }
+                }
+                // TXN_HASH_NOT_FOUND (code 29) until the sequencer picks it up,
+                // and forever if it rejected the transaction
+                Err(e) if e.to_string().contains("\"code\":29") => {
+                    let status = self
+                        .call(
+                            "starknet_getTransactionStatus",
+                            json!({ "transaction_hash": tx_hash }),
+                        )
+                        .await
+                        .unwrap_or_default();
+                    if status["finality_status"].as_str() == Some("REJECTED") {
+                        return Err(TransactionError::Rejected {
+                            tx_hash: tx_hash.to_string(),
+                            reason: status["failure_reason"]
+                                .as_str()
+                                .unwrap_or("unknown reason")
+                                .to_string(),
+                        }
+                        .into());
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+
+            let waited = started.elapsed();
+            let pause = match self.transaction_timeout {
+                Some(timeout) if waited >= timeout => {
+                    return Err(TransactionError::Timeout {
+                        tx_hash: tx_hash.to_string(),
+                        finality,
+                        waited,
+                    }
+                    .into())
+                }
+                Some(timeout) => self.poll_interval.min(timeout - waited),
+                None => self.poll_interval,
+            };
+            tokio::time::sleep(pause).await;
+        }
+    }
+
+    /// Constructor calldata of a contract deployed through the Universal
+    /// Deployer in transaction `deploy_tx_hash`.
+    ///
+    /// Read from the UDC's `ContractDeployed` event. Only events emitted by
+    /// a known UDC are trusted: any contract can emit an event with that name.
+    pub async fn get_deploy_calldata(
+        &self,
+        contract_address: &str,
+        deploy_tx_hash: &str,
+    ) -> Result<Vec<String>> {
+        let receipt = self
+            .call(
+                "starknet_getTransactionReceipt",
+                json!({ "transaction_hash": deploy_tx_hash }),
+            )
+            .await
+            .context("Failed to fetch deployment receipt")?;
+        let deployed = get_selector_from_name("ContractDeployed");
+
+        let event = receipt["events"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|event| {
+                let is =
+                    |value: &Value, felt: &str| value.as_str().is_some_and(|v| felt_eq(v, felt));
+                UNIVERSAL_DEPLOYERS
+                    .iter()
+                    .any(|udc| is(&event["from_address"], udc))
+                    && is(&event["keys"][0], &deployed)
+                    && is(&event["data"][0], contract_address)
+            })
+            .with_context(|| {
+                format!(
+                    "Transaction {} did not deploy {} through the Universal Deployer",
+                    deploy_tx_hash, contract_address
+                )
+            })?;
+
+        // data: address, deployer, unique, class_hash, calldata_len, calldata..., salt
+        let data: Vec<String> = event["data"]
+            .as_array()
+            .context("Invalid ContractDeployed event data")?
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .context("Invalid felt in event data")
+            })
+            .collect::<Result<_>>()?;
+        let len = data
+            .get(4)
+            .map(String::as_str)
+            .map(parse_felt_u64)
+            .transpose()?
+            .context("ContractDeployed event has no calldata")? as usize;
+        data.get(5..5 + len)
+            .map(<[String]>::to_vec)
+            .context("ContractDeployed event calldata is truncated")
+    }
+
+    /// Secret `t` revealed to `contract_address` by transaction `tx_hash`.
+    ///
+    /// Events only carry the secret hash; the secret itself is the ByteArray
+    /// argument of `reveal_secret` / `verify_and_unlock` in the invoke
+    /// calldata (directly or inside a SNIP-9 outside execution). The result
+    /// is checked against the contract's hashlock.
+    pub async fn get_revealed_secret(
+        &self,
+        contract_address: &str,
+        tx_hash: &str,
+    ) -> Result<[u8; 32]> {
+        let tx = self
+            .call(
+                "starknet_getTransactionByHash",
+                json!({ "transaction_hash": tx_hash }),
+            )
+            .await
+            .context("Failed to fetch reveal transaction")?;
+        if tx["type"].as_str() != Some("INVOKE") {
+            anyhow::bail!("Transaction {} is not an invoke", tx_hash);
+        }
+        let calldata: Vec<String> = tx["calldata"]
+            .as_array()
+            .context("Invoke transaction has no calldata")?
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .context("Invalid felt in calldata")
+            })
+            .collect::<Result<_>>()?;
+
+        let secret = find_reveal_argument(&decode_multicall(&calldata)?, contract_address)?
+            .with_context(|| {
+                format!(
+                    "Transaction {} does not reveal a secret to {}",
+                    tx_hash, contract_address
+                )
+            })?;
+        let (bytes, _) = byte_array_from_calldata(&secret)?;
+        let secret: [u8; 32] = bytes.try_into().map_err(|b: Vec<u8>| {
+            anyhow::anyhow!("Revealed secret is {} bytes, expected 32", b.len())
+        })?;
+
+        let target = self
+            .call_view(contract_address, atomic_lock::GET_TARGET_HASH, vec![])
+            .await?;
+        let words = target
+            .get(1..)
+            .context("Empty get_target_hash result")?
+            .iter()
+            .map(|w| parse_felt_u64(w).map(|w| w as u32))
+            .collect::<Result<Vec<_>>>()?;
+        let digest = Sha256::digest(secret);
+        let expected: Vec<u32> = digest
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().expect("4 bytes")))
+            .collect();
+        if words != expected {
+            anyhow::bail!("Revealed secret does not match the contract hashlock");
+        }
+        Ok(secret)
+    }
+
+    /// Transaction that revealed the secret to `contract_address`, from its
+    /// `SecretRevealed` (or legacy `Unlocked`) event.
+    pub async fn find_reveal_transaction(
+        &self,
+        contract_address: &str,
+        from_block: Option<u64>,
+    ) -> Result<Option<String>> {
+        let selectors = [SecretRevealed::selector(), Unlocked::selector()];
+        let events = self.get_events(contract_address, from_block).await?;
+        Ok(events.iter().find_map(|event| {
+            let key = event["keys"][0].as_str()?;
+            if !selectors.iter().any(|selector| felt_eq(key, selector)) {
+                return None;
+            }
+            event["transaction_hash"].as_str().map(str::to_string)
+        }))
+    }
+
     /// Call contract function (simplified - requires account signing in production).
     pub async fn call_contract(
         &self,
@@ -95,7 +621,148 @@ impl StarknetClient {
     }
 }
 
-/// Watch for Unlocked events from an AtomicLock contract.
+/// Universal Deployer Contract addresses (legacy and Cairo 1), same on all networks.
+pub const UNIVERSAL_DEPLOYERS: [&str; 2] = [
+    "0x041a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf",
+    "0x02ceed65a4bd731034c01113685c831b01c15d7d432f71afb1cf1634b53a2125",
+];
+
+/// Compute the Starknet entry point / event selector for `name`.
+///
+/// `sn_keccak(name)`: Keccak-256 truncated to the low 250 bits, as hex.
+pub fn get_selector_from_name(name: &str) -> String {
+    format!("0x{}", hex::encode(starknet_keccak(name.as_bytes())))
+}
+
+/// Keccak-256 truncated to 250 bits (Starknet's `sn_keccak`).
+pub fn starknet_keccak(data: &[u8]) -> [u8; 32] {
+    let mut hash = keccak256(data);
+    hash[0] &= 0x03;
+    hash
+}
+
+/// Original Keccak-256 (NOT SHA3-256: padding byte is 0x01).
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    const RATE: usize = 136;
+
+    fn absorb(state: &mut [u64; 25], block: &[u8]) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("8-byte lane"));
+        }
+        keccak::f1600(state);
+    }
+
+    let mut state = [0u64; 25];
+    let mut blocks = data.chunks_exact(RATE);
+    for block in &mut blocks {
+        absorb(&mut state, block);
+    }
+
+    let remainder = blocks.remainder();
+    let mut last = [0u8; RATE];
+    last[..remainder.len()].copy_from_slice(remainder);
+    last[remainder.len()] ^= 0x01;
+    last[RATE - 1] ^= 0x80;
+    absorb(&mut state, &last);
+
+    let mut out = [0u8; 32];
+    for (chunk, lane) in out.chunks_exact_mut(8).zip(state.iter()) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+    out
+}
+
+/// Parse a hex (`0x`-prefixed) or decimal felt string into a u64.
+pub(crate) fn parse_felt_u64(felt: &str) -> Result<u64> {
+    let value = if let Some(hex_str) = felt.strip_prefix("0x") {
+        u64::from_str_radix(hex_str, 16)
+    } else {
+        felt.parse()
+    };
+    value.with_context(|| format!("Felt does not fit in u64: {}", felt))
+}
+
+/// A call decoded from account `__execute__` calldata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedCall {
+    pub to: String,
+    pub selector: String,
+    pub calldata: Vec<String>,
+}
+
+/// Decode Cairo 1 account `__execute__` calldata:
+/// `[n_calls, (to, selector, calldata_len, calldata...)*]`.
+pub fn decode_multicall(calldata: &[String]) -> Result<Vec<DecodedCall>> {
+    let count = parse_felt_u64(calldata.first().context("Empty calldata")?)? as usize;
+    let mut calls = Vec::with_capacity(count.min(calldata.len()));
+    let mut pos = 1;
+    for _ in 0..count {
+        let header = calldata
+            .get(pos..pos + 3)
+            .context("Multicall calldata ends early")?;
+        let len = parse_felt_u64(&header[2])? as usize;
+        let data = calldata
+            .get(pos + 3..pos + 3 + len)
+            .context("Multicall calldata ends early")?;
+        calls.push(DecodedCall {
+            to: header[0].clone(),
+            selector: header[1].clone(),
+            calldata: data.to_vec(),
+        });
+        pos += 3 + len;
+    }
+    Ok(calls)
+}
+
+/// The ByteArray argument of a reveal call to `contract_address`, looking
+/// inside `execute_from_outside(_v2)` calls too.
+fn find_reveal_argument(
+    calls: &[DecodedCall],
+    contract_address: &str,
+) -> Result<Option<Vec<String>>> {
+    let reveal = [
+        get_selector_from_name(atomic_lock::REVEAL_SECRET),
+        get_selector_from_name(atomic_lock::VERIFY_AND_UNLOCK),
+    ];
+    let outside = [
+        get_selector_from_name("execute_from_outside"),
+        get_selector_from_name("execute_from_outside_v2"),
+    ];
+    for call in calls {
+        if felt_eq(&call.to, contract_address) && reveal.iter().any(|s| felt_eq(&call.selector, s))
+        {
+            return Ok(Some(call.calldata.clone()));
+        }
+        // OutsideExecution: caller, nonce, execute_after, execute_before, calls...
+        if outside.iter().any(|s| felt_eq(&call.selector, s)) && call.calldata.len() > 4 {
+            let inner = decode_multicall(&call.calldata[4..])?;
+            if let Some(found) = find_reveal_argument(&inner, contract_address)? {
+                return Ok(Some(found));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Compare hex felts ignoring case and leading zeros.
+pub(crate) fn felt_eq(a: &str, b: &str) -> bool {
+    let normalize = |felt: &str| {
+        felt.trim_start_matches("0x")
+            .trim_start_matches('0')
+            .to_ascii_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
+
+fn first_felt(felts: &[String]) -> Result<&str> {
+    felts
+        .first()
+        .map(String::as_str)
+        .context("Empty starknet_call result")
+}
+
+/// Watch for the reveal (`SecretRevealed`, or legacy `Unlocked`) of an
+/// AtomicLock contract. Returns the first hashlock word.
 pub async fn watch_unlocked_events(
     client: &StarknetClient,
     contract_address: &str,
@@ -120,24 +787,138 @@ pub async fn watch_unlocked_events(
             .context("Failed to fetch events")?;
 
         for event in events {
-            // Look for Unlocked event (event key = hash of "Unlocked")
-            // In production, decode event using contract ABI
-            if let Some(data) = event.get("data") {
-                if let Some(data_array) = data.as_array() {
-                    if data_array.len() >= 2 {
-                        // First element is unlocker, second is secret_hash
-                        // Extract secret_hash (h0) from event
-                        if let Some(secret_hash) = data_array.get(1).and_then(|v| v.as_str()) {
-                            println!("✅ Unlocked event detected!");
-                            println!("   Secret hash: {}", secret_hash);
-                            // In production, extract full secret from transaction calldata
-                            return Ok(secret_hash.to_string());
-                        }
-                    }
-                }
-            }
+            let secret_hash = match AtomicLockEvent::from_rpc(&event)? {
+                Some(AtomicLockEvent::Unlocked(unlocked)) => unlocked.secret_hash,
+                Some(AtomicLockEvent::SecretRevealed(revealed)) => revealed.secret_hash,
+                _ => continue,
+            };
+            println!("✅ Unlocked event detected!");
+            println!("   Secret hash: {:#x}", secret_hash);
+            // The secret itself is in the calldata (see get_revealed_secret)
+            return Ok(format!("{:#x}", secret_hash));
         }
 
         last_block = current_block;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finality_ordering_and_parsing() {
+        assert!(Finality::AcceptedOnL1 > Finality::AcceptedOnL2);
+        assert_eq!("l1".parse(), Ok(Finality::AcceptedOnL1));
+        assert_eq!("ACCEPTED_ON_L2".parse(), Ok(Finality::AcceptedOnL2));
+        assert!("pending".parse::<Finality>().is_err());
+        assert_eq!(Finality::AcceptedOnL2.to_string(), "ACCEPTED_ON_L2");
+
+        let receipt = |status: &str| json!({ "finality_status": status });
+        assert_eq!(
+            Finality::of_receipt(&receipt("ACCEPTED_ON_L1")),
+            Some(Finality::AcceptedOnL1)
+        );
+        assert_eq!(Finality::of_receipt(&receipt("PRE_CONFIRMED")), None);
+    }
+
+    #[test]
+    fn test_keccak256_empty() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_selector_matches_known_value() {
+        // Well-known ERC20 `transfer` entry point selector
+        assert_eq!(
+            get_selector_from_name("transfer"),
+            "0x0083afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e"
+        );
+    }
+
+    #[test]
+    fn test_find_reveal_in_multicall_and_outside_execution() {
+        let reveal_args = vec!["0x0".to_string(), "0x2a".to_string(), "0x1".to_string()];
+        let reveal = |contract: &str| {
+            let mut felts = vec![
+                contract.to_string(),
+                get_selector_from_name("verify_and_unlock"),
+                format!("{:#x}", reveal_args.len()),
+            ];
+            felts.extend(reveal_args.clone());
+            felts
+        };
+
+        let mut direct = vec!["0x1".to_string()];
+        direct.extend(reveal("0xabc"));
+        let calls = decode_multicall(&direct).unwrap();
+        assert_eq!(
+            find_reveal_argument(&calls, "0x0abc").unwrap(),
+            Some(reveal_args.clone())
+        );
+        assert_eq!(find_reveal_argument(&calls, "0xdef").unwrap(), None);
+
+        // Wrapped in execute_from_outside_v2 on the revealer's account
+        let mut inner: Vec<String> = ["0xfee", "0x7", "0x0", "0x64", "0x1"]
+            .map(String::from)
+            .to_vec();
+        inner.extend(reveal("0xabc"));
+        inner.push("0x0".to_string()); // empty signature
+        let mut outside = vec![
+            "0x1".to_string(),
+            "0xaaa".to_string(),
+            get_selector_from_name("execute_from_outside_v2"),
+            format!("{:#x}", inner.len()),
+        ];
+        outside.extend(inner);
+        let calls = decode_multicall(&outside).unwrap();
+        assert_eq!(
+            find_reveal_argument(&calls, "0xabc").unwrap(),
+            Some(reveal_args)
+        );
+    }
+
+    #[test]
+    fn test_network_chain_id_check() {
+        assert_eq!("sepolia".parse(), Ok(Network::Sepolia));
+        assert_eq!("SN_MAIN".parse(), Ok(Network::Mainnet));
+        assert!("goerli".parse::<Network>().is_err());
+
+        // starknet_chainId returns the short string as a hex felt
+        let sepolia = "0x534e5f5345504f4c4941";
+        assert!(ChainMismatch::check(Network::Sepolia, sepolia).is_ok());
+        assert_eq!(
+            ChainMismatch::check(Network::Mainnet, sepolia),
+            Err(ChainMismatch {
+                expected: Network::Mainnet,
+                actual: "SN_SEPOLIA".into()
+            })
+        );
+        assert!(ChainMismatch::check(Network::Mainnet, "0x534e5f4d41494e").is_ok());
+        // Devnets with a numeric chain id are reported as hex
+        assert_eq!(
+            ChainMismatch::check(Network::Sepolia, "0x1")
+                .unwrap_err()
+                .actual,
+            "0x1"
+        );
+    }
+
+    #[test]
+    fn test_felt_eq() {
+        assert!(felt_eq("0x0041A7", "0x41a7"));
+        assert!(felt_eq("0x0", "0x000"));
+        assert!(!felt_eq("0x41a7", "0x41a8"));
+    }
+
+    #[test]
+    fn test_parse_felt_u64() {
+        assert_eq!(parse_felt_u64("0x1").unwrap(), 1);
+        assert_eq!(parse_felt_u64("0x65f0a1b0").unwrap(), 0x65f0a1b0);
+        assert_eq!(parse_felt_u64("42").unwrap(), 42);
+        assert!(parse_felt_u64("0x10000000000000000").is_err());
+    }
+}
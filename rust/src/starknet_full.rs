@@ -1,16 +1,45 @@
 //! Full Starknet integration using direct JSON-RPC calls.
 //!
 //! This module provides complete integration for:
-//! - Contract deployment
+//! - Signed invoke v3 transactions from an account (unlock, refund, any multicall)
+//! - Declaring contract classes (declare v3) and deploying them through the
+//!   Universal Deployer
 //! - Event watching
 //! - Contract function calls
 //!
-//! Uses direct JSON-RPC calls for maximum compatibility and stability.
+//! Uses direct JSON-RPC calls for maximum compatibility and stability. Invoke
+//! transactions need a node speaking RPC v0.8 or later (`l1_data_gas` bounds).
+//!
+//! Submission is idempotent: the transaction hash is computed before
+//! broadcast, and a retry after a timeout first asks the node whether the
+//! earlier attempt landed.
+//!
+//! Nonces come from a [`NonceManager`]: transactions sent in quick
+//! succession get consecutive nonces before the first is included, and a
+//! transaction rejected for its nonce is re-signed with a fresh one.
+//!
+//! Every transaction is estimated first ([`StarknetAccount::estimate_fee`]);
+//! with [`StarknetAccount::with_max_fee`] one whose estimate exceeds the
+//! maximum is never signed ([`FeeTooHigh`]).
 
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use starknet_crypto::{
+    pedersen_hash, poseidon_hash_many, rfc6979_generate_k, sign, FieldElement, SignError,
+};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
 use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+use crate::contracts::atomic_lock::{
+    AtomicLock, AtomicLockEvent, ContractCall, LockEvent, SecretRevealed, Unlocked,
+};
+use crate::starknet::{
+    get_selector_from_name, starknet_keccak, ChainMismatch, Finality, Network, StarknetClient,
+    UNIVERSAL_DEPLOYERS,
+};
 
 /// Starknet JSON-RPC client with account support.
 pub struct StarknetAccount {
@@ -18,6 +47,12 @@ pub struct StarknetAccount {
     account_address: String,
     private_key: String,
     client: reqwest::Client,
+    timeout: Duration,
+    max_fee: Option<u128>,
+    network: Option<Network>,
+    nonces: NonceManager,
+    #[cfg(feature = "chaos")]
+    chaos: Option<std::sync::Arc<crate::chaos::Chaos>>,
 }
 
 impl StarknetAccount {
@@ -28,9 +63,83 @@ impl StarknetAccount {
             account_address,
             private_key,
             client: reqwest::Client::new(),
+            timeout: RPC_TIMEOUT,
+            max_fee: None,
+            network: None,
+            nonces: NonceManager::new(),
+            #[cfg(feature = "chaos")]
+            chaos: None,
         }
     }
 
+    /// Options (timeout, proxy, network, fee cap) checked at `build()`:
+    ///
+    /// ```
+    /// use xmr_secret_gen::starknet::Network;
+    /// use xmr_secret_gen::starknet_full::StarknetAccount;
+    ///
+    /// let account = StarknetAccount::builder()
+    ///     .rpc_url("https://starknet-sepolia.public.blastapi.io/rpc/v0_8")
+    ///     .address("0x123")
+    ///     .private_key("0x1234567890abcdef")
+    ///     .network(Network::Sepolia)
+    ///     .build()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn builder() -> StarknetAccountBuilder {
+        StarknetAccountBuilder::default()
+    }
+
+    /// Refuse transactions whose estimated fee exceeds `max_fee` (in the
+    /// fee token's smallest unit, FRI for v3 transactions).
+    pub fn with_max_fee(mut self, max_fee: u128) -> Self {
+        self.max_fee = Some(max_fee);
+        self
+    }
+
+    /// Refuse to sign for a node on another network: [`Self::chain_id`], read
+    /// before every transaction, fails with [`ChainMismatch`].
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Inject faults into every RPC call (chaos tests only).
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, chaos: std::sync::Arc<crate::chaos::Chaos>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Load the account from a JSON file: `{"address": "0x...", "private_key": "0x..."}`.
+    ///
+    /// The private key may be left out of the file and set in `STARKNET_PRIVATE_KEY`.
+    pub fn from_account_file(rpc_url: String, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read account file {}", path.display()))?;
+        let account: Value = serde_json::from_str(&json).context("Invalid account JSON")?;
+        let address = account["address"]
+            .as_str()
+            .context("Account file has no address")?
+            .to_string();
+        let private_key = match account["private_key"].as_str() {
+            Some(key) => key.to_string(),
+            None => std::env::var("STARKNET_PRIVATE_KEY")
+                .context("No private_key in account file and STARKNET_PRIVATE_KEY is unset")?,
+        };
+        Self::builder()
+            .rpc_url(rpc_url)
+            .address(address)
+            .private_key(private_key)
+            .build()
+    }
+
+    /// Account contract address.
+    pub fn address(&self) -> &str {
+        &self.account_address
+    }
+
     /// Call Starknet JSON-RPC method.
     async fn call(&self, method: &str, params: Value) -> Result<Value> {
         let payload = json!({
@@ -40,91 +149,428 @@ impl StarknetAccount {
             "params": params,
         });
 
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.chaos {
+            chaos.before_request().await;
+        }
+
         let response = self
             .client
             .post(&self.rpc_url)
+            .timeout(self.timeout)
             .json(&payload)
             .send()
             .await
             .context("Failed to send RPC request")?;
 
-        let result: Value = response
-            .json()
+        let body = response
+            .text()
             .await
-            .context("Failed to parse RPC response")?;
+            .context("Failed to read RPC response")?;
+        #[cfg(feature = "chaos")]
+        let body = match &self.chaos {
+            Some(chaos) => chaos.after_response(method, &payload["params"].to_string(), body)?,
+            None => body,
+        };
+        let result: Value = serde_json::from_str(&body).context("Failed to parse RPC response")?;
 
         if let Some(error) = result.get("error") {
-            anyhow::bail!("RPC error: {}", error);
+            return Err(RpcError::from_json(error).into());
         }
 
         Ok(result.get("result").cloned().unwrap_or(result))
     }
 
-    /// Deploy a contract (simplified - requires full implementation with account signing).
-    pub async fn deploy_contract(
+    /// Declare a Sierra class (`*.contract_class.json`) with its CASM
+    /// (`*.compiled_contract_class.json`), both as written by Scarb.
+    ///
+    /// Classes already declared are not declared again (`transaction_hash` is
+    /// then `None`).
+    pub async fn declare(&self, sierra: &Value, casm: &Value) -> Result<Declared> {
+        let compiled_class_hash = compiled_class_hash(casm).context("Invalid CASM class")?;
+        self.declare_with_compiled_class_hash(sierra, &format!("{:#x}", compiled_class_hash))
+            .await
+    }
+
+    /// Declare a Sierra class whose compiled class hash was computed elsewhere
+    /// (for networks expecting another CASM hash than [`compiled_class_hash`]).
+    pub async fn declare_with_compiled_class_hash(
         &self,
-        contract_class: &Value, // Sierra/CASM contract class
-        constructor_calldata: Vec<String>,
-    ) -> Result<String> {
-        // In production, this would:
-        // 1. Sign the deployment transaction
-        // 2. Broadcast via addInvokeTransaction
-        // 3. Wait for confirmation
+        sierra: &Value,
+        compiled_class_hash: &str,
+    ) -> Result<Declared> {
+        let compiled_class_hash =
+            parse_felt(compiled_class_hash).context("Invalid compiled class hash")?;
+        let class_hash = sierra_class_hash(sierra).context("Invalid Sierra class")?;
+        let class_hash_hex = format!("{:#x}", class_hash);
+        // Any error (CLASS_HASH_NOT_FOUND or otherwise) falls through to the
+        // declaration, which reports real failures itself
+        if self
+            .call(
+                "starknet_getClass",
+                json!({ "block_id": "latest", "class_hash": class_hash_hex }),
+            )
+            .await
+            .is_ok()
+        {
+            return Ok(Declared {
+                class_hash: class_hash_hex,
+                transaction_hash: None,
+            });
+        }
 
-        println!("⚠️  Contract deployment requires account signing");
-        println!("   Use Starknet CLI or implement full signing flow");
+        let sender_address =
+            parse_felt(&self.account_address).context("Invalid account address")?;
+        let contract_class = rpc_contract_class(sierra)?;
+        let chain_id = self.chain_id().await?;
+        let tx_hash = self
+            .with_nonce(|nonce| {
+                let mut tx = DeclareTransaction {
+                    sender_address,
+                    class_hash,
+                    compiled_class_hash,
+                    contract_class: contract_class.clone(),
+                    nonce,
+                    resource_bounds: ResourceBoundsMapping::default(),
+                    is_query: true,
+                };
+                async move {
+                    tx.resource_bounds = self.checked_resource_bounds(tx.to_rpc(&[])).await?;
+                    tx.is_query = false;
 
-        // Placeholder for now
-        Ok("0x0".to_string())
+                    let hash = tx.hash(chain_id);
+                    let signature = self.sign_hash(&hash)?;
+                    self.submit(
+                        "starknet_addDeclareTransaction",
+                        json!({ "declare_transaction": tx.to_rpc(&signature) }),
+                        hash,
+                    )
+                    .await
+                }
+            })
+            .await
+            .context("Declare transaction rejected")?;
+        Ok(Declared {
+            class_hash: class_hash_hex,
+            transaction_hash: Some(tx_hash.to_string()),
+        })
+    }
+
+    /// Deploy a declared class through the Universal Deployer (not unique:
+    /// the address only depends on the class, salt and calldata).
+    ///
+    /// Returns the computed contract address and the deployment transaction.
+    pub async fn deploy_via_udc(
+        &self,
+        class_hash: &str,
+        constructor_calldata: Vec<String>,
+        salt: &str,
+    ) -> Result<Deployed> {
+        let calldata = constructor_calldata
+            .iter()
+            .map(|felt| parse_felt(felt))
+            .collect::<Result<Vec<_>>>()
+            .context("Invalid constructor calldata")?;
+        let contract_address = udc_deployed_address(
+            parse_felt(class_hash).context("Invalid class hash")?,
+            parse_felt(salt).context("Invalid salt")?,
+            &calldata,
+        );
+        let transaction_hash = self
+            .execute(&[Call::deploy_via_udc(class_hash, constructor_calldata, salt)])
+            .await?;
+        Ok(Deployed {
+            contract_address: format!("{:#x}", contract_address),
+            transaction_hash,
+        })
     }
 
-    /// Call a contract function (verify_and_unlock).
+    /// Call `verify_and_unlock(secret: ByteArray)` on an AtomicLock contract.
+    ///
+    /// Returns the transaction hash.
     pub async fn verify_and_unlock(
         &self,
         contract_address: &str,
         secret_bytes: &[u8],
     ) -> Result<String> {
-        // Convert secret to ByteArray format
-        let secret_hex = hex::encode(secret_bytes);
+        let call = AtomicLock::new(contract_address).verify_and_unlock(secret_bytes);
+        self.execute(&[call.into()]).await
+    }
+
+    /// Call `refund()` on an AtomicLock contract whose timelock expired.
+    ///
+    /// Returns the transaction hash.
+    pub async fn refund(&self, contract_address: &str) -> Result<String> {
+        self.execute(&[AtomicLock::new(contract_address).refund().into()])
+            .await
+    }
 
-        // Create calldata for verify_and_unlock(secret: ByteArray)
-        // ByteArray format: [length, ...bytes as felts]
-        let mut calldata = Vec::new();
-        calldata.push(format!("0x{:x}", secret_bytes.len()));
+    /// Sign and broadcast an invoke v3 transaction running `calls` from the account.
+    ///
+    /// Fee bounds are the node's estimate plus [`FEE_MARGIN_PERCENT`]. Returns the
+    /// transaction hash.
+    pub async fn execute(&self, calls: &[Call]) -> Result<String> {
+        let chain_id = self.chain_id().await?;
+        self.with_nonce(|nonce| async move {
+            let mut tx = self.invoke_query(calls, nonce)?;
+            tx.resource_bounds = self.checked_resource_bounds(tx.to_rpc(&[])).await?;
+            tx.is_query = false;
 
-        // Add secret bytes (simplified - proper ByteArray serialization needed)
-        for chunk in secret_bytes.chunks(31) {
-            let chunk_hex = hex::encode(chunk);
-            calldata.push(format!("0x{}", chunk_hex));
+            let hash = tx.hash(chain_id);
+            let signature = self.sign_hash(&hash)?;
+            self.submit(
+                "starknet_addInvokeTransaction",
+                json!({ "invoke_transaction": tx.to_rpc(&signature) }),
+                hash,
+            )
+            .await
+        })
+        .await
+        .context("Invoke transaction rejected")
+    }
+
+    /// Estimate the fee of running `calls` from the account, without sending
+    /// anything.
+    pub async fn estimate_fee(&self, calls: &[Call]) -> Result<FeeEstimate> {
+        let nonce = self.nonces.peek(self.get_nonce().await?);
+        let tx = self.invoke_query(calls, nonce)?;
+        self.estimate(tx.to_rpc(&[])).await
+    }
+
+    /// Unsigned fee-estimation query running `calls` at `nonce`.
+    fn invoke_query(&self, calls: &[Call], nonce: FieldElement) -> Result<InvokeTransaction> {
+        Ok(InvokeTransaction {
+            sender_address: parse_felt(&self.account_address).context("Invalid account address")?,
+            calldata: encode_multicall(calls)?,
+            nonce,
+            resource_bounds: ResourceBoundsMapping::default(),
+            is_query: true,
+        })
+    }
+
+    /// Sign and send a transaction with `send`, given a nonce reserved from
+    /// the [`NonceManager`].
+    ///
+    /// A nonce rejected by the node (used meanwhile, or ahead of a
+    /// transaction that was dropped) is retried with the next one, after the
+    /// node caught up. Any other failure returns the nonce for reuse.
+    async fn with_nonce<F, Fut>(&self, mut send: F) -> Result<String>
+    where
+        F: FnMut(FieldElement) -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let nonce = self.nonces.reserve(self.get_nonce().await?);
+            match send(nonce).await {
+                Ok(tx_hash) => return Ok(tx_hash),
+                Err(e) if is_nonce_error(&e) && attempt < NONCE_ATTEMPTS => {
+                    warn!("Nonce {:#x} rejected ({}), retrying", nonce, e);
+                    sleep(SUBMIT_RETRY_DELAY).await;
+                }
+                Err(e) => {
+                    if is_nonce_error(&e) {
+                        // Local tracking disagrees with the node: start over
+                        self.nonces.reset();
+                    } else {
+                        self.nonces.release(nonce);
+                    }
+                    return Err(e);
+                }
+            }
         }
+    }
 
-        // In production, this would:
-        // 1. Create invoke transaction
-        // 2. Sign with account
-        // 3. Broadcast via addInvokeTransaction
-        // 4. Return transaction hash
+    /// Broadcast a signed transaction whose hash is `tx_hash`, at most once.
+    ///
+    /// The same signed transaction is resent when an attempt fails in
+    /// transport (timeout, dropped connection), but only after the node
+    /// confirms it does not know the hash yet. A node rejection is final
+    /// unless the hash turns out to be known: a duplicate of an attempt
+    /// that did land is rejected for its nonce.
+    async fn submit(&self, method: &str, params: Value, tx_hash: FieldElement) -> Result<String> {
+        let tx_hash_hex = format!("{:#x}", tx_hash);
+        let mut last_error = None;
 
-        println!("⚠️  Contract call requires account signing");
-        println!("   Function: verify_and_unlock");
-        println!("   Contract: {}", contract_address);
-        println!("   Secret: {}...", &secret_hex[..16]);
-        println!("   Calldata: {:?}", calldata);
+        for attempt in 1..=SUBMIT_ATTEMPTS {
+            if attempt > 1 {
+                sleep(SUBMIT_RETRY_DELAY).await;
+                match self.transaction_known(&tx_hash_hex).await {
+                    Ok(true) => return Ok(tx_hash_hex),
+                    Ok(false) => {}
+                    Err(e) => {
+                        last_error = Some(e);
+                        continue;
+                    }
+                }
+            }
 
-        // Placeholder
-        Ok("0x0".to_string())
+            match self.call(method, params.clone()).await {
+                Ok(result) => {
+                    let returned = result["transaction_hash"]
+                        .as_str()
+                        .context("Missing transaction_hash in response")?;
+                    if parse_felt(returned)? != tx_hash {
+                        warn!(
+                            "Node returned transaction hash {} (computed {})",
+                            returned, tx_hash_hex
+                        );
+                    }
+                    return Ok(returned.to_string());
+                }
+                Err(e) if e.downcast_ref::<RpcError>().is_some() => {
+                    if self.transaction_known(&tx_hash_hex).await.unwrap_or(false) {
+                        return Ok(tx_hash_hex);
+                    }
+                    return Err(e);
+                }
+                Err(e) => {
+                    warn!(
+                        "Submitting {} failed (attempt {}/{}): {:#}",
+                        tx_hash_hex, attempt, SUBMIT_ATTEMPTS, e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one attempt").context(format!(
+            "Transaction {} not submitted after {} attempts",
+            tx_hash_hex, SUBMIT_ATTEMPTS
+        )))
+    }
+
+    /// Whether the node has seen the transaction (pending, included or
+    /// reverted); rejected transactions count as absent.
+    async fn transaction_known(&self, tx_hash: &str) -> Result<bool> {
+        match self
+            .call(
+                "starknet_getTransactionStatus",
+                json!({ "transaction_hash": tx_hash }),
+            )
+            .await
+        {
+            Ok(status) => Ok(status["finality_status"] != "REJECTED"),
+            Err(e) if e.downcast_ref::<RpcError>().map(|e| e.code) == Some(TXN_HASH_NOT_FOUND) => {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
     }
 
-    /// Watch for Unlocked events from a contract.
+    /// Wait until a transaction sent from this account reaches `finality`,
+    /// giving up after `timeout` (see [`StarknetClient::wait_for_transaction`]).
+    pub async fn wait_for_transaction(
+        &self,
+        tx_hash: &str,
+        finality: Finality,
+        timeout: Option<Duration>,
+    ) -> Result<Value> {
+        let client = StarknetClient::new(self.rpc_url.clone());
+        let client = match self.network {
+            Some(network) => client.with_network(network),
+            None => client,
+        };
+        #[cfg(feature = "chaos")]
+        let client = match &self.chaos {
+            Some(chaos) => client.with_chaos(std::sync::Arc::clone(chaos)),
+            None => client,
+        };
+        let client = match timeout {
+            Some(timeout) => client.with_transaction_timeout(timeout),
+            None => client,
+        };
+        client.wait_for_transaction(tx_hash, finality).await
+    }
+
+    /// Chain id of the node (e.g. `SN_SEPOLIA` as a short string felt),
+    /// checked against the configured network.
+    pub async fn chain_id(&self) -> Result<FieldElement> {
+        let result = self.call("starknet_chainId", json!([])).await?;
+        let chain_id = result.as_str().context("Invalid chain id format")?;
+        if let Some(network) = self.network {
+            ChainMismatch::check(network, chain_id)?;
+        }
+        parse_felt(chain_id)
+    }
+
+    /// Next nonce of the account.
+    pub async fn get_nonce(&self) -> Result<FieldElement> {
+        let result = self
+            .call(
+                "starknet_getNonce",
+                json!({ "block_id": "latest", "contract_address": self.account_address }),
+            )
+            .await
+            .context("Failed to fetch account nonce")?;
+        parse_felt(result.as_str().context("Invalid nonce format")?)
+    }
+
+    /// Fee bounds for a query transaction (RPC object), refused with
+    /// [`FeeTooHigh`] if the estimate exceeds the maximum fee.
+    async fn checked_resource_bounds(&self, tx: Value) -> Result<ResourceBoundsMapping> {
+        let estimate = self.estimate(tx).await?;
+        self.check_fee(&estimate)?;
+        Ok(estimate.resource_bounds)
+    }
+
+    /// Fail if `estimate` exceeds the maximum fee set with [`Self::with_max_fee`].
+    pub fn check_fee(&self, estimate: &FeeEstimate) -> Result<(), FeeTooHigh> {
+        match self.max_fee {
+            Some(max) if estimate.overall_fee > max => Err(FeeTooHigh {
+                estimated: estimate.overall_fee,
+                max,
+                unit: estimate.unit.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Estimate a query transaction (RPC object, sent without signature
+    /// validation).
+    async fn estimate(&self, tx: Value) -> Result<FeeEstimate> {
+        let result = self
+            .call(
+                "starknet_estimateFee",
+                json!({
+                    "request": [tx],
+                    "simulation_flags": ["SKIP_VALIDATE"],
+                    "block_id": "latest",
+                }),
+            )
+            .await
+            .context("Fee estimation failed")?;
+        FeeEstimate::from_rpc(result.get(0).context("Empty fee estimate")?)
+    }
+
+    /// Sign a transaction hash with the account key: `[r, s]`.
+    fn sign_hash(&self, hash: &FieldElement) -> Result<[FieldElement; 2]> {
+        let private_key = parse_felt(&self.private_key).context("Invalid private key")?;
+        let mut seed = None;
+        loop {
+            let k = rfc6979_generate_k(hash, &private_key, seed.as_ref());
+            match sign(&private_key, hash, &k) {
+                Ok(signature) => return Ok([signature.r, signature.s]),
+                // Astronomically unlikely; retry with the next RFC 6979 nonce
+                Err(SignError::InvalidK) => {
+                    seed = Some(seed.unwrap_or(FieldElement::ZERO) + FieldElement::ONE)
+                }
+                Err(e) => anyhow::bail!("Failed to sign transaction: {:?}", e),
+            }
+        }
+    }
+
+    /// Watch for the reveal (`SecretRevealed`, or legacy `Unlocked`) of an
+    /// AtomicLock contract. Returns the first hashlock word.
     pub async fn watch_unlocked_events(
         &self,
         contract_address: &str,
         poll_interval_secs: u64,
     ) -> Result<String> {
-        println!("👀 Watching for Unlocked events from: {}", contract_address);
+        debug!("Watching for reveal events from {}", contract_address);
 
-        // Get Unlocked event key (hash of "Unlocked")
-        // In production, compute: pedersen_hash("Unlocked")
-        let unlocked_event_key = "0x0"; // Placeholder
+        let reveal_keys = [SecretRevealed::selector(), Unlocked::selector()];
 
         let mut last_block = self.get_block_number().await?;
 
@@ -136,7 +582,7 @@ impl StarknetAccount {
             // Query events
             let filter = json!({
                 "address": contract_address,
-                "keys": [unlocked_event_key],
+                "keys": [reveal_keys],
                 "from_block": format!("0x{:x}", last_block),
                 "to_block": format!("0x{:x}", current_block),
             });
@@ -146,19 +592,17 @@ impl StarknetAccount {
                 .await;
 
             if let Ok(events) = events_result {
-                if let Some(events_array) = events.get("events").and_then(|v| v.as_array()) {
-                    for event in events_array {
-                        if let Some(data) = event.get("data").and_then(|v| v.as_array()) {
-                            if data.len() >= 2 {
-                                // First element is unlocker, second is secret_hash
-                                if let Some(secret_hash) = data.get(1).and_then(|v| v.as_str()) {
-                                    println!("✅ Unlocked event detected!");
-                                    println!("   Secret hash: {}", secret_hash);
-                                    return Ok(secret_hash.to_string());
-                                }
-                            }
-                        }
-                    }
+                for event in events["events"].as_array().into_iter().flatten() {
+                    let secret_hash = match AtomicLockEvent::from_rpc(event)? {
+                        Some(AtomicLockEvent::SecretRevealed(e)) => e.secret_hash,
+                        Some(AtomicLockEvent::Unlocked(e)) => e.secret_hash,
+                        _ => continue,
+                    };
+                    info!(
+                        "Reveal detected on {}: secret hash {:#x}",
+                        contract_address, secret_hash
+                    );
+                    return Ok(format!("{:#x}", secret_hash));
                 }
             }
 
@@ -183,53 +627,1083 @@ impl StarknetAccount {
     }
 }
 
-/// Helper to create AtomicLock contract deployment calldata.
-pub fn create_atomic_lock_calldata(
-    hash_words: [u32; 8],
-    lock_until: u64,
-    token: &str,
-    amount_low: u128,
-    amount_high: u128,
-    adaptor_point_x: &[String; 4],
-    adaptor_point_y: &[String; 4],
-    dleq: (&str, &str),
-    fake_glv_hint: &[String; 10],
-) -> Vec<String> {
-    let mut calldata = Vec::new();
+/// Options for a [`StarknetAccount`] (see [`StarknetAccount::builder`]).
+///
+/// Every option has a setter, so new ones can be added without breaking
+/// callers.
+#[derive(Clone, Default)]
+pub struct StarknetAccountBuilder {
+    rpc_url: Option<String>,
+    address: Option<String>,
+    private_key: Option<String>,
+    timeout: Option<Duration>,
+    proxy: Option<String>,
+    network: Option<Network>,
+    max_fee: Option<u128>,
+}
+
+impl StarknetAccountBuilder {
+    /// Starknet JSON-RPC endpoint (v0.8 or later)
+    pub fn rpc_url(mut self, url: impl Into<String>) -> Self {
+        self.rpc_url = Some(url.into());
+        self
+    }
+
+    /// Account contract address
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Signing key of the account
+    pub fn private_key(mut self, private_key: impl Into<String>) -> Self {
+        self.private_key = Some(private_key.into());
+        self
+    }
+
+    /// Timeout of each RPC request (default 30 s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send every RPC request through a proxy (`socks5h://` for Tor)
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// See [`StarknetAccount::with_network`]
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// See [`StarknetAccount::with_max_fee`]
+    pub fn max_fee(mut self, max_fee: u128) -> Self {
+        self.max_fee = Some(max_fee);
+        self
+    }
+
+    /// Check the options; no request is made.
+    pub fn build(self) -> Result<StarknetAccount> {
+        let rpc_url = self.rpc_url.context("rpc_url is required")?;
+        match reqwest::Url::parse(&rpc_url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            _ => anyhow::bail!("rpc_url must be an http(s) URL, got '{}'", rpc_url),
+        }
+        let address = self.address.context("address is required")?;
+        parse_felt(&address).context("Invalid account address")?;
+        let private_key = self.private_key.context("private_key is required")?;
+        if parse_felt(&private_key).context("Invalid private key")? == FieldElement::ZERO {
+            anyhow::bail!("private_key must not be zero");
+        }
+        let timeout = self.timeout.unwrap_or(RPC_TIMEOUT);
+        anyhow::ensure!(!timeout.is_zero(), "timeout must be positive");
+
+        let mut client = reqwest::Client::builder();
+        if let Some(proxy) = &self.proxy {
+            client = client.proxy(reqwest::Proxy::all(proxy).context("Invalid proxy")?);
+        }
+
+        let mut account = StarknetAccount::new(rpc_url, address, private_key);
+        account.client = client.build().context("Failed to create HTTP client")?;
+        account.timeout = timeout;
+        account.max_fee = self.max_fee;
+        account.network = self.network;
+        Ok(account)
+    }
+}
+
+/// Percentage added to the estimated gas amounts and prices for the fee bounds.
+pub const FEE_MARGIN_PERCENT: u128 = 50;
+
+/// Timeout of a single RPC request.
+const RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Broadcast attempts made for one signed transaction.
+const SUBMIT_ATTEMPTS: u32 = 3;
+
+/// Pause before checking for and resending a transaction.
+const SUBMIT_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// `TXN_HASH_NOT_FOUND` error code of the Starknet JSON-RPC spec.
+const TXN_HASH_NOT_FOUND: i64 = 29;
+
+/// `INVALID_TRANSACTION_NONCE` error code of the Starknet JSON-RPC spec.
+const INVALID_TRANSACTION_NONCE: i64 = 52;
+
+/// Nonces tried for one transaction before giving up.
+const NONCE_ATTEMPTS: u32 = 3;
+
+/// Whether the node rejected a transaction for its nonce: the dedicated
+/// error code, or a validation failure that names the nonce (nodes differ).
+fn is_nonce_error(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<RpcError>().is_some_and(|error| {
+        error.code == INVALID_TRANSACTION_NONCE
+            || error.message.to_lowercase().contains("nonce")
+            || error
+                .data
+                .as_ref()
+                .is_some_and(|data| data.to_string().to_lowercase().contains("nonce"))
+    })
+}
+
+/// Next nonce of an account, tracked locally across pending transactions.
+///
+/// `starknet_getNonce` only counts included transactions, so two
+/// transactions sent before the first is included would share a nonce.
+/// The manager hands out the highest of the account nonce on chain and the
+/// one after the last nonce it reserved.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next: Mutex<Option<FieldElement>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nonce the next transaction would use, given the account nonce on chain.
+    pub fn peek(&self, chain_nonce: FieldElement) -> FieldElement {
+        match *self.next.lock().unwrap() {
+            Some(next) if next > chain_nonce => next,
+            _ => chain_nonce,
+        }
+    }
+
+    /// Reserve a nonce for a new transaction.
+    pub fn reserve(&self, chain_nonce: FieldElement) -> FieldElement {
+        let mut next = self.next.lock().unwrap();
+        let nonce = match *next {
+            Some(local) if local > chain_nonce => local,
+            _ => chain_nonce,
+        };
+        *next = Some(nonce + FieldElement::ONE);
+        nonce
+    }
+
+    /// The transaction reserving `nonce` was not sent. The nonce is handed
+    /// out again if it was the last one reserved; otherwise later
+    /// transactions now wait on it, and tracking restarts from the chain.
+    pub fn release(&self, nonce: FieldElement) {
+        let mut next = self.next.lock().unwrap();
+        *next = if *next == Some(nonce + FieldElement::ONE) {
+            Some(nonce)
+        } else {
+            None
+        };
+    }
+
+    /// Forget local state; the next nonce comes from the chain.
+    pub fn reset(&self) {
+        *self.next.lock().unwrap() = None;
+    }
+}
+
+/// Error object returned by the node (as opposed to a transport failure).
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("RPC error {code}: {message}{}", .data.as_ref().map(|d| format!(" ({})", d)).unwrap_or_default())]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    fn from_json(error: &Value) -> Self {
+        Self {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string()),
+            data: error.get("data").cloned(),
+        }
+    }
+}
+
+/// Fee estimate (`starknet_estimateFee`) of one transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// Expected fee, in the smallest unit of the fee token
+    pub overall_fee: u128,
+    /// Fee token unit: `FRI` (STRK) or `WEI` (ETH)
+    pub unit: String,
+    /// Bounds signed with the transaction: the estimate plus
+    /// [`FEE_MARGIN_PERCENT`]
+    pub resource_bounds: ResourceBoundsMapping,
+}
+
+impl FeeEstimate {
+    /// Parse one `FEE_ESTIMATE` object.
+    pub fn from_rpc(estimate: &Value) -> Result<Self> {
+        let field = |name: &str| -> Result<u128> {
+            parse_felt_u128(
+                estimate[name]
+                    .as_str()
+                    .with_context(|| format!("Missing {} in fee estimate", name))?,
+            )
+        };
+        let bounds = |resource: &str| -> Result<ResourceBounds> {
+            let consumed = field(&format!("{}_consumed", resource))?;
+            let price = field(&format!("{}_price", resource))?;
+            Ok(ResourceBounds {
+                max_amount: u64::try_from(with_margin(consumed))
+                    .context("Gas estimate exceeds u64")?,
+                max_price_per_unit: with_margin(price),
+            })
+        };
+        Ok(Self {
+            overall_fee: field("overall_fee")?,
+            unit: estimate["unit"].as_str().unwrap_or("FRI").to_string(),
+            resource_bounds: ResourceBoundsMapping {
+                l1_gas: bounds("l1_gas")?,
+                l2_gas: bounds("l2_gas")?,
+                l1_data_gas: bounds("l1_data_gas")?,
+            },
+        })
+    }
+}
+
+/// A transaction was not signed because its estimated fee exceeds the
+/// maximum set with [`StarknetAccount::with_max_fee`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Estimated fee {estimated} {unit} exceeds the maximum of {max} {unit}")]
+pub struct FeeTooHigh {
+    pub estimated: u128,
+    pub max: u128,
+    pub unit: String,
+}
+
+fn with_margin(value: u128) -> u128 {
+    value.saturating_add(value.saturating_mul(FEE_MARGIN_PERCENT) / 100)
+}
+
+/// Result of [`StarknetAccount::declare`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declared {
+    pub class_hash: String,
+    /// `None` if the class was already declared
+    pub transaction_hash: Option<String>,
+}
+
+/// Result of [`StarknetAccount::deploy_via_udc`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployed {
+    pub contract_address: String,
+    pub transaction_hash: String,
+}
+
+/// A contract call executed by the account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub to: String,
+    pub selector: String,
+    pub calldata: Vec<String>,
+}
+
+impl Call {
+    /// Call `entrypoint` (by name) on `to`.
+    pub fn new(to: &str, entrypoint: &str, calldata: Vec<String>) -> Self {
+        Self {
+            to: to.to_string(),
+            selector: get_selector_from_name(entrypoint),
+            calldata,
+        }
+    }
 
-    // Hash words (8 u32)
-    for word in hash_words {
-        calldata.push(format!("0x{:x}", word));
+    /// `deployContract` on the Universal Deployer (not unique).
+    pub fn deploy_via_udc(class_hash: &str, constructor_calldata: Vec<String>, salt: &str) -> Self {
+        // deployContract(classHash, salt, unique, calldata: Array<felt252>)
+        let mut calldata = vec![
+            class_hash.to_string(),
+            salt.to_string(),
+            "0x0".to_string(),
+            format!("{:#x}", constructor_calldata.len()),
+        ];
+        calldata.extend(constructor_calldata);
+        Self::new(UNIVERSAL_DEPLOYERS[0], "deployContract", calldata)
     }
+}
 
-    // Lock until (u64)
-    calldata.push(format!("0x{:x}", lock_until));
+impl From<ContractCall> for Call {
+    fn from(call: ContractCall) -> Self {
+        Self::new(&call.to, call.entrypoint, call.calldata)
+    }
+}
 
-    // Token address
-    calldata.push(token.to_string());
+/// Encode calls as Cairo 1 account `__execute__` calldata:
+/// `[n_calls, (to, selector, calldata_len, calldata...)*]`.
+pub fn encode_multicall(calls: &[Call]) -> Result<Vec<FieldElement>> {
+    let mut calldata = vec![FieldElement::from(calls.len() as u64)];
+    for call in calls {
+        calldata.push(parse_felt(&call.to).context("Invalid call target")?);
+        calldata.push(parse_felt(&call.selector).context("Invalid call selector")?);
+        calldata.push(FieldElement::from(call.calldata.len() as u64));
+        for felt in &call.calldata {
+            calldata.push(parse_felt(felt)?);
+        }
+    }
+    Ok(calldata)
+}
 
-    // Amount (u256: low, high)
-    calldata.push(format!("0x{:x}", amount_low));
-    calldata.push(format!("0x{:x}", amount_high));
+/// Limit on one resource of a v3 transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceBounds {
+    pub max_amount: u64,
+    pub max_price_per_unit: u128,
+}
 
-    // Adaptor point x (4 felts)
-    for x in adaptor_point_x {
-        calldata.push(x.clone());
+impl ResourceBounds {
+    /// `0 | name (7 bytes) | max_amount (8 bytes) | max_price_per_unit (16 bytes)`
+    fn to_felt(self, name: &[u8; 7]) -> FieldElement {
+        let mut bytes = [0u8; 32];
+        bytes[1..8].copy_from_slice(name);
+        bytes[8..16].copy_from_slice(&self.max_amount.to_be_bytes());
+        bytes[16..].copy_from_slice(&self.max_price_per_unit.to_be_bytes());
+        FieldElement::from_bytes_be(&bytes).expect("top byte is zero")
     }
 
-    // Adaptor point y (4 felts)
-    for y in adaptor_point_y {
-        calldata.push(y.clone());
+    fn to_rpc(self) -> Value {
+        json!({
+            "max_amount": format!("{:#x}", self.max_amount),
+            "max_price_per_unit": format!("{:#x}", self.max_price_per_unit),
+        })
     }
+}
 
-    // DLEQ (2 felts)
-    calldata.push(dleq.0.to_string());
-    calldata.push(dleq.1.to_string());
+/// Fee bounds of a v3 transaction (fees paid in STRK).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceBoundsMapping {
+    pub l1_gas: ResourceBounds,
+    pub l2_gas: ResourceBounds,
+    pub l1_data_gas: ResourceBounds,
+}
 
-    // Fake GLV hint (10 felts)
-    for hint in fake_glv_hint {
-        calldata.push(hint.clone());
+impl ResourceBoundsMapping {
+    /// Hash of the tip (zero) and resource bounds in v3 transaction hashes.
+    fn hash(&self) -> FieldElement {
+        poseidon_hash_many(&[
+            FieldElement::ZERO,
+            self.l1_gas.to_felt(b"\0L1_GAS"),
+            self.l2_gas.to_felt(b"\0L2_GAS"),
+            self.l1_data_gas.to_felt(b"L1_DATA"),
+        ])
     }
 
-    calldata
+    fn to_rpc(self) -> Value {
+        json!({
+            "l1_gas": self.l1_gas.to_rpc(),
+            "l2_gas": self.l2_gas.to_rpc(),
+            "l1_data_gas": self.l1_data_gas.to_rpc(),
+        })
+    }
+}
+
+/// Transaction version 3, or `2^128 + 3` for fee-estimation queries.
+fn version_3(is_query: bool) -> FieldElement {
+    let mut bytes = [0u8; 32];
+    bytes[15] = is_query as u8;
+    bytes[31] = 3;
+    FieldElement::from_bytes_be(&bytes).expect("below the field modulus")
+}
+
+/// An invoke v3 transaction with zero tip, no paymaster and L1 data availability.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvokeTransaction {
+    pub sender_address: FieldElement,
+    /// `__execute__` calldata (see [`encode_multicall`])
+    pub calldata: Vec<FieldElement>,
+    pub nonce: FieldElement,
+    pub resource_bounds: ResourceBoundsMapping,
+    /// Fee-estimation query (version `2^128 + 3`) that can never be replayed
+    /// as a real transaction
+    pub is_query: bool,
+}
+
+impl InvokeTransaction {
+    fn version(&self) -> FieldElement {
+        version_3(self.is_query)
+    }
+
+    /// Transaction hash signed by the account (Poseidon, as in the Starknet
+    /// protocol for v3 transactions).
+    pub fn hash(&self, chain_id: FieldElement) -> FieldElement {
+        // Nonce and fee data availability modes (both L1 = 0)
+        let data_availability_modes = FieldElement::ZERO;
+        poseidon_hash_many(&[
+            short_string("invoke"),
+            self.version(),
+            self.sender_address,
+            self.resource_bounds.hash(),
+            poseidon_hash_many(&[]), // paymaster_data
+            chain_id,
+            self.nonce,
+            data_availability_modes,
+            poseidon_hash_many(&[]), // account_deployment_data
+            poseidon_hash_many(&self.calldata),
+        ])
+    }
+
+    /// JSON-RPC `INVOKE_TXN_V3` object.
+    pub fn to_rpc(&self, signature: &[FieldElement]) -> Value {
+        let hex = |felts: &[FieldElement]| -> Vec<String> {
+            felts.iter().map(|felt| format!("{:#x}", felt)).collect()
+        };
+        json!({
+            "type": "INVOKE",
+            "version": format!("{:#x}", self.version()),
+            "sender_address": format!("{:#x}", self.sender_address),
+            "calldata": hex(&self.calldata),
+            "signature": hex(signature),
+            "nonce": format!("{:#x}", self.nonce),
+            "resource_bounds": self.resource_bounds.to_rpc(),
+            "tip": "0x0",
+            "paymaster_data": [],
+            "account_deployment_data": [],
+            "nonce_data_availability_mode": "L1",
+            "fee_data_availability_mode": "L1",
+        })
+    }
+}
+
+/// A declare v3 transaction with zero tip, no paymaster and L1 data availability.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclareTransaction {
+    pub sender_address: FieldElement,
+    pub class_hash: FieldElement,
+    pub compiled_class_hash: FieldElement,
+    /// RPC `contract_class` (see [`rpc_contract_class`])
+    pub contract_class: Value,
+    pub nonce: FieldElement,
+    pub resource_bounds: ResourceBoundsMapping,
+    /// Fee-estimation query (version `2^128 + 3`)
+    pub is_query: bool,
+}
+
+impl DeclareTransaction {
+    /// Transaction hash signed by the account.
+    pub fn hash(&self, chain_id: FieldElement) -> FieldElement {
+        poseidon_hash_many(&[
+            short_string("declare"),
+            version_3(self.is_query),
+            self.sender_address,
+            self.resource_bounds.hash(),
+            poseidon_hash_many(&[]), // paymaster_data
+            chain_id,
+            self.nonce,
+            FieldElement::ZERO,      // data availability modes
+            poseidon_hash_many(&[]), // account_deployment_data
+            self.class_hash,
+            self.compiled_class_hash,
+        ])
+    }
+
+    /// JSON-RPC `DECLARE_TXN_V3` object.
+    pub fn to_rpc(&self, signature: &[FieldElement]) -> Value {
+        let signature: Vec<String> = signature
+            .iter()
+            .map(|felt| format!("{:#x}", felt))
+            .collect();
+        json!({
+            "type": "DECLARE",
+            "version": format!("{:#x}", version_3(self.is_query)),
+            "sender_address": format!("{:#x}", self.sender_address),
+            "compiled_class_hash": format!("{:#x}", self.compiled_class_hash),
+            "signature": signature,
+            "nonce": format!("{:#x}", self.nonce),
+            "contract_class": self.contract_class,
+            "resource_bounds": self.resource_bounds.to_rpc(),
+            "tip": "0x0",
+            "paymaster_data": [],
+            "account_deployment_data": [],
+            "nonce_data_availability_mode": "L1",
+            "fee_data_availability_mode": "L1",
+        })
+    }
+}
+
+/// Class hash of a Sierra class as written by Scarb (`abi` as a JSON array)
+/// or returned by the RPC (`abi` as a string).
+pub fn sierra_class_hash(sierra: &Value) -> Result<FieldElement> {
+    let version = sierra["contract_class_version"]
+        .as_str()
+        .context("Missing contract_class_version")?;
+    let entry_points = |kind: &str| -> Result<FieldElement> {
+        let mut felts = Vec::new();
+        for entry_point in entry_points(sierra, kind)? {
+            felts.push(felt_field(entry_point, "selector")?);
+            felts.push(FieldElement::from(u64_field(entry_point, "function_idx")?));
+        }
+        Ok(poseidon_hash_many(&felts))
+    };
+    let abi = abi_string(sierra)?;
+    let abi_hash = FieldElement::from_bytes_be(&starknet_keccak(abi.as_bytes()))
+        .expect("starknet_keccak is 250 bits");
+    Ok(poseidon_hash_many(&[
+        short_string(&format!("CONTRACT_CLASS_V{}", version)),
+        entry_points("EXTERNAL")?,
+        entry_points("L1_HANDLER")?,
+        entry_points("CONSTRUCTOR")?,
+        abi_hash,
+        poseidon_hash_many(&felt_array(&sierra["sierra_program"], "sierra_program")?),
+    ]))
+}
+
+/// Compiled class hash of a CASM class (Poseidon over the entry points and
+/// the bytecode, segmented by `bytecode_segment_lengths` if present).
+///
+/// Networks that hash CASM with Blake2s (Starknet 0.14.1 and later) reject
+/// this hash; declare there with
+/// [`StarknetAccount::declare_with_compiled_class_hash`].
+pub fn compiled_class_hash(casm: &Value) -> Result<FieldElement> {
+    let entry_points = |kind: &str| -> Result<FieldElement> {
+        let mut felts = Vec::new();
+        for entry_point in entry_points(casm, kind)? {
+            felts.push(felt_field(entry_point, "selector")?);
+            felts.push(FieldElement::from(u64_field(entry_point, "offset")?));
+            let builtins = entry_point["builtins"]
+                .as_array()
+                .context("Missing entry point builtins")?
+                .iter()
+                .map(|builtin| builtin.as_str().map(short_string))
+                .collect::<Option<Vec<_>>>()
+                .context("Invalid entry point builtin")?;
+            felts.push(poseidon_hash_many(&builtins));
+        }
+        Ok(poseidon_hash_many(&felts))
+    };
+    let bytecode = felt_array(&casm["bytecode"], "bytecode")?;
+    let bytecode_hash = match casm.get("bytecode_segment_lengths") {
+        Some(lengths) => {
+            let (len, hash) = bytecode_segment_hash(&bytecode, lengths)?;
+            anyhow::ensure!(
+                len == bytecode.len(),
+                "Bytecode segments do not cover the bytecode"
+            );
+            hash
+        }
+        None => poseidon_hash_many(&bytecode),
+    };
+    Ok(poseidon_hash_many(&[
+        short_string("COMPILED_CLASS_V1"),
+        entry_points("EXTERNAL")?,
+        entry_points("L1_HANDLER")?,
+        entry_points("CONSTRUCTOR")?,
+        bytecode_hash,
+    ]))
+}
+
+/// Hash of a (nested) bytecode segment: Poseidon of a leaf, or
+/// `1 + poseidon(len_0, hash_0, len_1, hash_1, ...)` of a node.
+fn bytecode_segment_hash(
+    bytecode: &[FieldElement],
+    lengths: &Value,
+) -> Result<(usize, FieldElement)> {
+    if let Some(len) = lengths.as_u64() {
+        let segment = bytecode
+            .get(..len as usize)
+            .context("Bytecode segment exceeds the bytecode")?;
+        return Ok((segment.len(), poseidon_hash_many(segment)));
+    }
+    let mut offset = 0;
+    let mut felts = Vec::new();
+    for node in lengths
+        .as_array()
+        .context("Invalid bytecode_segment_lengths")?
+    {
+        let (len, hash) = bytecode_segment_hash(&bytecode[offset..], node)?;
+        felts.push(FieldElement::from(len as u64));
+        felts.push(hash);
+        offset += len;
+    }
+    Ok((offset, poseidon_hash_many(&felts) + FieldElement::ONE))
+}
+
+/// The Sierra class in the shape `starknet_addDeclareTransaction` expects.
+pub fn rpc_contract_class(sierra: &Value) -> Result<Value> {
+    Ok(json!({
+        "sierra_program": sierra["sierra_program"],
+        "contract_class_version": sierra["contract_class_version"],
+        "entry_points_by_type": sierra["entry_points_by_type"],
+        "abi": abi_string(sierra)?,
+    }))
+}
+
+/// The ABI string the class hash commits to.
+///
+/// Scarb writes the ABI as JSON; the class commits to it serialized the way
+/// Python's `json.dumps` does (`", "` and `": "` separators, ASCII escapes)
+/// with keys in the compiler's order.
+fn abi_string(sierra: &Value) -> Result<String> {
+    match &sierra["abi"] {
+        Value::String(abi) => Ok(abi.clone()),
+        abi @ Value::Array(_) => {
+            let mut out = String::new();
+            write_pythonic(&mut out, abi, ABI_ITEM_KEYS);
+            Ok(out)
+        }
+        _ => anyhow::bail!("Missing abi"),
+    }
+}
+
+/// Key order of ABI items (functions, structs, events, interfaces, ...).
+const ABI_ITEM_KEYS: &[&str] = &[
+    "type",
+    "name",
+    "kind",
+    "inputs",
+    "outputs",
+    "state_mutability",
+    "members",
+    "variants",
+    "items",
+    "interface_name",
+];
+/// Key order of parameters, struct members and event fields.
+const ABI_MEMBER_KEYS: &[&str] = &["name", "type", "kind"];
+
+fn write_pythonic(out: &mut String, value: &Value, keys: &[&str]) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_pythonic(out, item, keys);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut fields: Vec<_> = map.iter().collect();
+            fields.sort_by_key(|(key, _)| keys.iter().position(|k| k == key).unwrap_or(keys.len()));
+            out.push('{');
+            for (i, (key, value)) in fields.into_iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_pythonic_string(out, key);
+                out.push_str(": ");
+                let nested = match key.as_str() {
+                    "inputs" | "outputs" | "members" | "variants" => ABI_MEMBER_KEYS,
+                    _ => ABI_ITEM_KEYS,
+                };
+                write_pythonic(out, value, nested);
+            }
+            out.push('}');
+        }
+        Value::String(text) => write_pythonic_string(out, text),
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// JSON string with everything outside printable ASCII escaped as `\uXXXX`.
+fn write_pythonic_string(out: &mut String, text: &str) {
+    let json = serde_json::to_string(text).expect("strings serialize");
+    for c in json.chars() {
+        if c <= '~' {
+            out.push(c);
+        } else {
+            let mut units = [0u16; 2];
+            for unit in c.encode_utf16(&mut units) {
+                out.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+}
+
+fn entry_points<'a>(class: &'a Value, kind: &str) -> Result<&'a Vec<Value>> {
+    class["entry_points_by_type"][kind]
+        .as_array()
+        .with_context(|| format!("Missing {} entry points", kind))
+}
+
+fn felt_field(object: &Value, name: &str) -> Result<FieldElement> {
+    parse_felt(
+        object[name]
+            .as_str()
+            .with_context(|| format!("Missing {}", name))?,
+    )
+}
+
+fn u64_field(object: &Value, name: &str) -> Result<u64> {
+    object[name]
+        .as_u64()
+        .with_context(|| format!("Missing {}", name))
+}
+
+fn felt_array(value: &Value, name: &str) -> Result<Vec<FieldElement>> {
+    value
+        .as_array()
+        .with_context(|| format!("Missing {}", name))?
+        .iter()
+        .map(|felt| parse_felt(felt.as_str().with_context(|| format!("Invalid {}", name))?))
+        .collect()
+}
+
+/// Address of a contract deployed through the Universal Deployer with
+/// `unique = false` (deployer address zero).
+pub fn udc_deployed_address(
+    class_hash: FieldElement,
+    salt: FieldElement,
+    constructor_calldata: &[FieldElement],
+) -> FieldElement {
+    // 2^251 - 256
+    let address_bound = FieldElement::from_hex_be(
+        "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00",
+    )
+    .expect("valid felt");
+    let address = pedersen_hash_on_elements(&[
+        short_string("STARKNET_CONTRACT_ADDRESS"),
+        FieldElement::ZERO,
+        salt,
+        class_hash,
+        pedersen_hash_on_elements(constructor_calldata),
+    ]);
+    address % address_bound
+}
+
+/// `h(h(...h(h(0, a_1), a_2)..., a_n), n)` with the Pedersen hash.
+fn pedersen_hash_on_elements(felts: &[FieldElement]) -> FieldElement {
+    let hash = felts
+        .iter()
+        .fold(FieldElement::ZERO, |hash, felt| pedersen_hash(&hash, felt));
+    pedersen_hash(&hash, &FieldElement::from(felts.len() as u64))
+}
+
+/// Parse a hex (`0x`-prefixed) or decimal felt.
+fn parse_felt(felt: &str) -> Result<FieldElement> {
+    let value = if felt.starts_with("0x") {
+        FieldElement::from_hex_be(felt).ok()
+    } else {
+        FieldElement::from_dec_str(felt).ok()
+    };
+    value.with_context(|| format!("Invalid felt: {}", felt))
+}
+
+fn parse_felt_u128(felt: &str) -> Result<u128> {
+    let value = if let Some(hex_str) = felt.strip_prefix("0x") {
+        u128::from_str_radix(hex_str, 16)
+    } else {
+        felt.parse()
+    };
+    value.with_context(|| format!("Felt does not fit in u128: {}", felt))
+}
+
+/// Cairo short string (up to 31 ASCII bytes) as a felt.
+fn short_string(text: &str) -> FieldElement {
+    let mut bytes = [0u8; 32];
+    bytes[32 - text.len()..].copy_from_slice(text.as_bytes());
+    FieldElement::from_bytes_be(&bytes).expect("short string fits in a felt")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_crypto::{get_public_key, verify};
+
+    fn felt(hex: &str) -> FieldElement {
+        FieldElement::from_hex_be(hex).unwrap()
+    }
+
+    #[test]
+    fn test_resource_bounds_felt_layout() {
+        let bounds = ResourceBounds {
+            max_amount: 0x10,
+            max_price_per_unit: 0x20,
+        };
+        assert_eq!(
+            bounds.to_felt(b"\0L1_GAS"),
+            felt("0x4c315f474153000000000000001000000000000000000000000000000020")
+        );
+    }
+
+    #[test]
+    fn test_query_version_differs_from_transaction() {
+        let mut tx = InvokeTransaction {
+            sender_address: felt("0x123"),
+            calldata: encode_multicall(&[Call::new("0x456", "refund", vec![])]).unwrap(),
+            nonce: FieldElement::ONE,
+            resource_bounds: ResourceBoundsMapping::default(),
+            is_query: true,
+        };
+        assert_eq!(tx.version(), felt("0x100000000000000000000000000000003"));
+        let query_hash = tx.hash(short_string("SN_SEPOLIA"));
+        tx.is_query = false;
+        assert_eq!(tx.version(), felt("0x3"));
+        assert_ne!(tx.hash(short_string("SN_SEPOLIA")), query_hash);
+        assert_eq!(tx.to_rpc(&[])["version"], "0x3");
+    }
+
+    #[test]
+    fn test_multicall_encoding() {
+        let calls = [
+            AtomicLock::new("0xabc").verify_and_unlock(&[0x42]).into(),
+            AtomicLock::new("0xdef").refund().into(),
+        ];
+        let calldata = encode_multicall(&calls).unwrap();
+        assert_eq!(
+            calldata,
+            vec![
+                felt("0x2"),
+                felt("0xabc"),
+                felt(&get_selector_from_name("verify_and_unlock")),
+                felt("0x3"),
+                felt("0x0"),
+                felt("0x42"),
+                felt("0x1"),
+                felt("0xdef"),
+                felt(&get_selector_from_name("refund")),
+                felt("0x0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_abi_is_serialized_like_python() {
+        let sierra = json!({
+            "abi": [
+                {
+                    "state_mutability": "view",
+                    "outputs": [{ "type": "core::bool" }],
+                    "inputs": [{ "type": "core::felt252", "name": "a" }],
+                    "name": "get",
+                    "type": "function"
+                },
+                {
+                    "members": [{ "kind": "key", "type": "core::felt252", "name": "x" }],
+                    "kind": "struct",
+                    "name": "Café",
+                    "type": "event"
+                }
+            ]
+        });
+        assert_eq!(
+            abi_string(&sierra).unwrap(),
+            r#"[{"type": "function", "name": "get", "inputs": [{"name": "a", "type": "core::felt252"}], "outputs": [{"type": "core::bool"}], "state_mutability": "view"}, {"type": "event", "name": "Caf\u00e9", "kind": "struct", "members": [{"name": "x", "type": "core::felt252", "kind": "key"}]}]"#
+        );
+    }
+
+    #[test]
+    fn test_sierra_class_hash_matches_rpc_class() {
+        let sierra = json!({
+            "sierra_program": ["0x1", "0x2"],
+            "contract_class_version": "0.1.0",
+            "entry_points_by_type": {
+                "EXTERNAL": [{ "selector": "0x10", "function_idx": 0 }],
+                "L1_HANDLER": [],
+                "CONSTRUCTOR": [{ "selector": "0x20", "function_idx": 1 }]
+            },
+            "abi": [{ "type": "constructor", "name": "constructor", "inputs": [] }]
+        });
+        let rpc_class = rpc_contract_class(&sierra).unwrap();
+        assert_eq!(
+            rpc_class["abi"],
+            r#"[{"type": "constructor", "name": "constructor", "inputs": []}]"#
+        );
+        assert_eq!(
+            sierra_class_hash(&rpc_class).unwrap(),
+            sierra_class_hash(&sierra).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compiled_class_hash_segments_bytecode() {
+        let casm = |lengths: Option<Value>| {
+            let mut casm = json!({
+                "bytecode": ["0x1", "0x2", "0x3"],
+                "entry_points_by_type": {
+                    "EXTERNAL": [{ "selector": "0x10", "offset": 0, "builtins": ["range_check"] }],
+                    "L1_HANDLER": [],
+                    "CONSTRUCTOR": []
+                }
+            });
+            if let Some(lengths) = lengths {
+                casm["bytecode_segment_lengths"] = lengths;
+            }
+            casm
+        };
+        let class_hash = |bytecode_hash| {
+            poseidon_hash_many(&[
+                short_string("COMPILED_CLASS_V1"),
+                poseidon_hash_many(&[
+                    felt("0x10"),
+                    FieldElement::ZERO,
+                    poseidon_hash_many(&[short_string("range_check")]),
+                ]),
+                poseidon_hash_many(&[]),
+                poseidon_hash_many(&[]),
+                bytecode_hash,
+            ])
+        };
+        let [a, b, c] = [felt("0x1"), felt("0x2"), felt("0x3")];
+
+        assert_eq!(
+            compiled_class_hash(&casm(None)).unwrap(),
+            class_hash(poseidon_hash_many(&[a, b, c]))
+        );
+        let inner = poseidon_hash_many(&[
+            FieldElement::ONE,
+            poseidon_hash_many(&[b]),
+            FieldElement::ONE,
+            poseidon_hash_many(&[c]),
+        ]) + FieldElement::ONE;
+        let outer = poseidon_hash_many(&[
+            FieldElement::ONE,
+            poseidon_hash_many(&[a]),
+            FieldElement::TWO,
+            inner,
+        ]) + FieldElement::ONE;
+        assert_eq!(
+            compiled_class_hash(&casm(Some(json!([1, [1, 1]])))).unwrap(),
+            class_hash(outer)
+        );
+        assert!(compiled_class_hash(&casm(Some(json!([1, 1])))).is_err());
+    }
+
+    #[test]
+    fn test_udc_deployed_address() {
+        // Vector from starknet-rs (`get_udc_deployed_address`, not unique)
+        let address = udc_deployed_address(
+            felt("0x0562fc1d911530d18a86ea3ef4be50018923898d3c573288c5abb9c2344459ed"),
+            felt("0x06df0e9a9842d97ff3f4c6de7494d6e69d0a107a72150f9c53d59515b91ed9cb"),
+            &[felt("0x1234")],
+        );
+        assert_eq!(
+            address,
+            felt("0x0288e5952d2f2f0e897ea0c5401c6e9f584a89eebfb08b5b26f090a8bbf67eb6")
+        );
+    }
+
+    #[test]
+    fn test_signature_verifies_with_account_key() {
+        let account = StarknetAccount::new(
+            "http://localhost:5050".to_string(),
+            "0x123".to_string(),
+            "0x1234567890abcdef".to_string(),
+        );
+        let hash = felt("0x2a");
+        let [r, s] = account.sign_hash(&hash).unwrap();
+        let public_key = get_public_key(&felt("0x1234567890abcdef"));
+        assert!(verify(&public_key, &hash, &r, &s).unwrap());
+    }
+
+    #[test]
+    fn test_account_builder_validates_options() {
+        let builder = || {
+            StarknetAccount::builder()
+                .rpc_url("http://localhost:5050")
+                .address("0x123")
+                .private_key("0x1234567890abcdef")
+        };
+        let account = builder()
+            .timeout(Duration::from_secs(5))
+            .proxy("socks5h://127.0.0.1:9050")
+            .max_fee(1000)
+            .build()
+            .unwrap();
+        assert_eq!(account.address(), "0x123");
+        assert_eq!(account.timeout, Duration::from_secs(5));
+        assert_eq!(account.max_fee, Some(1000));
+
+        assert!(StarknetAccount::builder().address("0x123").build().is_err());
+        assert!(builder().rpc_url("localhost:5050").build().is_err());
+        assert!(builder().address("0xzz").build().is_err());
+        assert!(builder().private_key("0x0").build().is_err());
+        assert!(builder().timeout(Duration::ZERO).build().is_err());
+    }
+
+    #[test]
+    fn test_rpc_error_from_json() {
+        let error = RpcError::from_json(&json!({
+            "code": 29,
+            "message": "Transaction hash not found",
+        }));
+        assert_eq!(error.code, TXN_HASH_NOT_FOUND);
+        assert_eq!(
+            error.to_string(),
+            "RPC error 29: Transaction hash not found"
+        );
+
+        let error = RpcError::from_json(&json!({
+            "code": 55,
+            "message": "Account validation failed",
+            "data": "invalid signature",
+        }));
+        assert_eq!(
+            error.to_string(),
+            "RPC error 55: Account validation failed (\"invalid signature\")"
+        );
+        // Callers tell node errors from transport errors by downcasting
+        let error = anyhow::Error::from(error).context("Invoke transaction rejected");
+        assert_eq!(error.downcast_ref::<RpcError>().map(|e| e.code), Some(55));
+    }
+
+    #[test]
+    fn test_nonce_manager_tracks_pending_transactions() {
+        let nonces = NonceManager::new();
+        // Two transactions before the first is included
+        assert_eq!(nonces.reserve(felt("0x5")), felt("0x5"));
+        assert_eq!(nonces.reserve(felt("0x5")), felt("0x6"));
+        assert_eq!(nonces.peek(felt("0x5")), felt("0x7"));
+        // The chain moved past local state (another client, same account)
+        assert_eq!(nonces.reserve(felt("0x9")), felt("0x9"));
+
+        // An unsent transaction gives its nonce back
+        nonces.release(felt("0x9"));
+        assert_eq!(nonces.reserve(felt("0x7")), felt("0x9"));
+        // ...unless a later one was reserved meanwhile
+        nonces.reserve(felt("0x7"));
+        nonces.release(felt("0x9"));
+        assert_eq!(nonces.peek(felt("0x7")), felt("0x7"));
+    }
+
+    #[test]
+    fn test_nonce_errors() {
+        let rpc = |code: i64, message: &str, data: Option<Value>| {
+            anyhow::Error::from(RpcError {
+                code,
+                message: message.to_string(),
+                data,
+            })
+        };
+        assert!(is_nonce_error(&rpc(52, "Invalid transaction nonce", None)));
+        assert!(is_nonce_error(&rpc(
+            55,
+            "Account validation failed",
+            Some(json!("Invalid transaction nonce of contract: 0x5 < 0x6"))
+        )));
+        assert!(!is_nonce_error(&rpc(55, "Account validation failed", None)));
+        assert!(!is_nonce_error(&anyhow::anyhow!(
+            "connection reset (nonce)"
+        )));
+    }
+
+    #[test]
+    fn test_fee_estimate_from_rpc() {
+        let estimate = FeeEstimate::from_rpc(&json!({
+            "l1_gas_consumed": "0x0",
+            "l1_gas_price": "0x2",
+            "l2_gas_consumed": "0xc8",
+            "l2_gas_price": "0x64",
+            "l1_data_gas_consumed": "0x80",
+            "l1_data_gas_price": "0xa",
+            "overall_fee": "0x5280",
+            "unit": "FRI",
+        }))
+        .unwrap();
+        assert_eq!(estimate.overall_fee, 0x5280);
+        assert_eq!(estimate.unit, "FRI");
+        assert_eq!(
+            estimate.resource_bounds.l2_gas,
+            ResourceBounds {
+                max_amount: 300,
+                max_price_per_unit: 150,
+            }
+        );
+        assert!(FeeEstimate::from_rpc(&json!({ "unit": "FRI" })).is_err());
+    }
 }
@@ -0,0 +1,638 @@
+//! Persistent swap store backed by SQLite.
+//!
+//! Replaces the single `swap_state.json` with one database holding any number
+//! of swaps. Each row is a `SwapState` (as driven by `SwapOrchestrator`) plus
+//! the secret and timestamps; transaction hashes go to a separate table so a
+//! swap can accumulate as many as it needs (deploy, deposit, reveal, sweep...).
+//!
+//! The database runs in WAL mode with `synchronous=FULL`: a write that
+//! returned is on disk, so after a crash `unfinished()` lists exactly the
+//! swaps that still need attention. Several processes may share the file;
+//! writers wait up to `BUSY_TIMEOUT` for each other.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::swap::abort::AbortReason;
+use crate::swap::orchestrator::SwapState;
+use crate::swap::{SwapPhase, SwapRole};
+
+/// How long a writer waits for a lock held by another connection.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS swaps (
+        swap_id          TEXT PRIMARY KEY,
+        role             TEXT NOT NULL,
+        phase            TEXT NOT NULL,
+        secret_hex       TEXT,
+        contract_address TEXT,
+        lock_until       INTEGER,
+        claimable_after  INTEGER,
+        monero_lock_txid TEXT,
+        monero_restore_height INTEGER,
+        abort_reason     TEXT,
+        history          TEXT NOT NULL,
+        created_at       INTEGER NOT NULL,
+        updated_at       INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS swap_transactions (
+        swap_id     TEXT NOT NULL REFERENCES swaps(swap_id),
+        chain       TEXT NOT NULL,
+        kind        TEXT NOT NULL,
+        tx_hash     TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        PRIMARY KEY (swap_id, chain, tx_hash)
+    );
+    CREATE TABLE IF NOT EXISTS counterparty_outcomes (
+        counterparty TEXT NOT NULL,
+        swap_id      TEXT NOT NULL,
+        outcome      TEXT NOT NULL,
+        recorded_at  INTEGER NOT NULL,
+        PRIMARY KEY (counterparty, swap_id)
+    );
+";
+
+const SWAP_COLUMNS: &str = "swap_id, role, phase, secret_hex, contract_address, lock_until, \
+                            claimable_after, monero_lock_txid, history, created_at, updated_at, \
+                            monero_restore_height, abort_reason";
+
+/// Store errors.
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("invalid stored value: {0}")]
+    InvalidValue(#[from] serde_json::Error),
+
+    #[error("swap {0} not found")]
+    NotFound(String),
+
+    #[error("swap {0} already exists")]
+    AlreadyExists(String),
+}
+
+/// Chain a recorded transaction lives on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Chain {
+    Monero,
+    Starknet,
+}
+
+/// A transaction recorded for a swap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxRecord {
+    pub chain: Chain,
+    /// What the transaction did (`deploy`, `deposit`, `reveal`, `lock`, ...).
+    pub kind: String,
+    pub tx_hash: String,
+    /// Unix seconds.
+    pub recorded_at: u64,
+}
+
+/// How a swap with a counterparty ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapOutcome {
+    Completed,
+    /// The counterparty walked away before locking funds.
+    Aborted,
+    /// The counterparty stopped responding after funds were locked.
+    TimedOut,
+}
+
+/// Outcome counts for one counterparty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CounterpartyHistory {
+    pub completed: u32,
+    pub aborted: u32,
+    pub timed_out: u32,
+}
+
+impl CounterpartyHistory {
+    pub fn total(&self) -> u32 {
+        self.completed + self.aborted + self.timed_out
+    }
+}
+
+/// One swap as stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredSwap {
+    pub state: SwapState,
+    /// Hex secret scalar `t` (maker only).
+    pub secret_hex: Option<String>,
+    /// Unix seconds.
+    pub created_at: u64,
+    /// Unix seconds, bumped on every save.
+    pub updated_at: u64,
+}
+
+/// SQLite-backed store of all local swaps.
+pub struct SwapStore {
+    conn: Mutex<Connection>,
+}
+
+impl SwapStore {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::init(conn)
+    }
+
+    /// Private in-memory database, for tests and dry runs.
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, StoreError> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "synchronous", "FULL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA)?;
+        migrate(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Add a new swap. Fails if `state.swap_id` is already stored.
+    pub fn create(&self, state: &SwapState, secret_hex: Option<&str>) -> Result<(), StoreError> {
+        let now = unix_now();
+        let conn = self.conn();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO swaps (swap_id, role, phase, secret_hex, contract_address,
+                 lock_until, claimable_after, monero_lock_txid, history, created_at, updated_at,
+                 monero_restore_height, abort_reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10, ?11, ?12)",
+            params![
+                state.swap_id,
+                to_sql_text(&state.role)?,
+                to_sql_text(&state.phase)?,
+                secret_hex,
+                state.contract_address,
+                state.lock_until.map(to_sql_int),
+                state.claimable_after.map(to_sql_int),
+                state.monero_lock_txid,
+                serde_json::to_string(&state.history)?,
+                to_sql_int(now),
+                state.monero_restore_height.map(to_sql_int),
+                state.abort_reason.as_ref().map(to_sql_text).transpose()?,
+            ],
+        )?;
+        if inserted == 0 {
+            return Err(StoreError::AlreadyExists(state.swap_id.clone()));
+        }
+        Ok(())
+    }
+
+    /// Overwrite the stored state of an existing swap (the secret is kept).
+    pub fn save(&self, state: &SwapState) -> Result<(), StoreError> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            "UPDATE swaps SET role = ?2, phase = ?3, contract_address = ?4, lock_until = ?5,
+                 claimable_after = ?6, monero_lock_txid = ?7, history = ?8, updated_at = ?9,
+                 monero_restore_height = ?10, abort_reason = ?11
+             WHERE swap_id = ?1",
+            params![
+                state.swap_id,
+                to_sql_text(&state.role)?,
+                to_sql_text(&state.phase)?,
+                state.contract_address,
+                state.lock_until.map(to_sql_int),
+                state.claimable_after.map(to_sql_int),
+                state.monero_lock_txid,
+                serde_json::to_string(&state.history)?,
+                to_sql_int(unix_now()),
+                state.monero_restore_height.map(to_sql_int),
+                state.abort_reason.as_ref().map(to_sql_text).transpose()?,
+            ],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::NotFound(state.swap_id.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, swap_id: &str) -> Result<Option<StoredSwap>, StoreError> {
+        let conn = self.conn();
+        let raw = conn
+            .query_row(
+                &format!("SELECT {} FROM swaps WHERE swap_id = ?1", SWAP_COLUMNS),
+                [swap_id],
+                RawSwap::from_row,
+            )
+            .optional()?;
+        raw.map(RawSwap::decode).transpose()
+    }
+
+    /// All swaps, oldest first.
+    pub fn list(&self) -> Result<Vec<StoredSwap>, StoreError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM swaps ORDER BY created_at, swap_id",
+            SWAP_COLUMNS
+        ))?;
+        let raws = stmt
+            .query_map([], RawSwap::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        raws.into_iter().map(RawSwap::decode).collect()
+    }
+
+    /// Swaps not yet finalized or refunded: what to resume after a crash.
+    pub fn unfinished(&self) -> Result<Vec<StoredSwap>, StoreError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|swap| !swap.state.phase.is_terminal())
+            .collect())
+    }
+
+    /// Record a transaction sent or observed for `swap_id`. Recording the
+    /// same hash twice is a no-op.
+    pub fn record_tx(
+        &self,
+        swap_id: &str,
+        chain: Chain,
+        kind: &str,
+        tx_hash: &str,
+    ) -> Result<(), StoreError> {
+        let conn = self.conn();
+        let result = conn.execute(
+            "INSERT OR IGNORE INTO swap_transactions (swap_id, chain, kind, tx_hash, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                swap_id,
+                to_sql_text(&chain)?,
+                kind,
+                tx_hash,
+                to_sql_int(unix_now())
+            ],
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                Err(StoreError::NotFound(swap_id.to_string()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Transactions recorded for `swap_id`, oldest first.
+    pub fn transactions(&self, swap_id: &str) -> Result<Vec<TxRecord>, StoreError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT chain, kind, tx_hash, recorded_at FROM swap_transactions
+             WHERE swap_id = ?1 ORDER BY recorded_at, rowid",
+        )?;
+        let rows = stmt
+            .query_map([swap_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(|(chain, kind, tx_hash, recorded_at)| {
+                Ok(TxRecord {
+                    chain: from_sql_text(&chain)?,
+                    kind,
+                    tx_hash,
+                    recorded_at: recorded_at as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// Record how a swap with `counterparty` ended. A later outcome for the
+    /// same swap replaces the earlier one.
+    pub fn record_outcome(
+        &self,
+        counterparty: &str,
+        swap_id: &str,
+        outcome: SwapOutcome,
+    ) -> Result<(), StoreError> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO counterparty_outcomes (counterparty, swap_id, outcome, recorded_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![counterparty, swap_id, to_sql_text(&outcome)?, to_sql_int(unix_now())],
+        )?;
+        Ok(())
+    }
+
+    /// Outcomes recorded for `counterparty` (all zero if unknown).
+    pub fn counterparty_history(
+        &self,
+        counterparty: &str,
+    ) -> Result<CounterpartyHistory, StoreError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT outcome, COUNT(*) FROM counterparty_outcomes
+             WHERE counterparty = ?1 GROUP BY outcome",
+        )?;
+        let counts = stmt
+            .query_map([counterparty], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut history = CounterpartyHistory::default();
+        for (outcome, count) in counts {
+            match from_sql_text(&outcome)? {
+                SwapOutcome::Completed => history.completed = count,
+                SwapOutcome::Aborted => history.aborted = count,
+                SwapOutcome::TimedOut => history.timed_out = count,
+            }
+        }
+        Ok(history)
+    }
+
+    /// Number of stored swaps per abort reason, most frequent first.
+    pub fn abort_reasons(&self) -> Result<Vec<(AbortReason, u32)>, StoreError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT abort_reason, COUNT(*) FROM swaps WHERE abort_reason IS NOT NULL
+             GROUP BY abort_reason ORDER BY COUNT(*) DESC, abort_reason",
+        )?;
+        let counts = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        counts
+            .into_iter()
+            .map(|(reason, count)| Ok((from_sql_text(&reason)?, count)))
+            .collect()
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // A panic mid-statement leaves no partial write (SQLite rolls it back)
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Row as read, before decoding the enum and JSON columns.
+struct RawSwap {
+    swap_id: String,
+    role: String,
+    phase: String,
+    secret_hex: Option<String>,
+    contract_address: Option<String>,
+    lock_until: Option<i64>,
+    claimable_after: Option<i64>,
+    monero_lock_txid: Option<String>,
+    history: String,
+    created_at: i64,
+    updated_at: i64,
+    monero_restore_height: Option<i64>,
+    abort_reason: Option<String>,
+}
+
+impl RawSwap {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            swap_id: row.get(0)?,
+            role: row.get(1)?,
+            phase: row.get(2)?,
+            secret_hex: row.get(3)?,
+            contract_address: row.get(4)?,
+            lock_until: row.get(5)?,
+            claimable_after: row.get(6)?,
+            monero_lock_txid: row.get(7)?,
+            history: row.get(8)?,
+            created_at: row.get(9)?,
+            updated_at: row.get(10)?,
+            monero_restore_height: row.get(11)?,
+            abort_reason: row.get(12)?,
+        })
+    }
+
+    fn decode(self) -> Result<StoredSwap, StoreError> {
+        let role: SwapRole = from_sql_text(&self.role)?;
+        let phase: SwapPhase = from_sql_text(&self.phase)?;
+        Ok(StoredSwap {
+            state: SwapState {
+                swap_id: self.swap_id,
+                role,
+                phase,
+                contract_address: self.contract_address,
+                lock_until: self.lock_until.map(|v| v as u64),
+                claimable_after: self.claimable_after.map(|v| v as u64),
+                monero_lock_txid: self.monero_lock_txid,
+                monero_restore_height: self.monero_restore_height.map(|v| v as u64),
+                history: serde_json::from_str(&self.history)?,
+                abort_reason: self
+                    .abort_reason
+                    .as_deref()
+                    .map(from_sql_text)
+                    .transpose()?,
+            },
+            secret_hex: self.secret_hex,
+            created_at: self.created_at as u64,
+            updated_at: self.updated_at as u64,
+        })
+    }
+}
+
+/// Bring a database created by an older version up to `SCHEMA`.
+fn migrate(conn: &Connection) -> Result<(), StoreError> {
+    let has_restore_height: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('swaps') WHERE name = 'monero_restore_height'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_restore_height {
+        conn.execute(
+            "ALTER TABLE swaps ADD COLUMN monero_restore_height INTEGER",
+            [],
+        )?;
+    }
+    let has_abort_reason: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('swaps') WHERE name = 'abort_reason'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_abort_reason {
+        conn.execute("ALTER TABLE swaps ADD COLUMN abort_reason TEXT", [])?;
+    }
+    Ok(())
+}
+
+/// Store a unit enum as its serde name (`maker`, `deposited`, ...).
+fn to_sql_text<T: Serialize>(value: &T) -> Result<String, StoreError> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(s) => Ok(s),
+        other => Ok(other.to_string()),
+    }
+}
+
+fn from_sql_text<T: for<'de> Deserialize<'de>>(text: &str) -> Result<T, StoreError> {
+    Ok(serde_json::from_value(serde_json::Value::String(
+        text.to_string(),
+    ))?)
+}
+
+/// SQLite integers are signed; unix timestamps fit comfortably.
+fn to_sql_int(value: u64) -> i64 {
+    value.min(i64::MAX as u64) as i64
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap::orchestrator::SwapOrchestrator;
+
+    #[test]
+    fn test_create_save_and_resume() {
+        let store = SwapStore::open_in_memory().unwrap();
+        let mut maker = SwapOrchestrator::new("aa11", SwapRole::Maker);
+        let taker = SwapOrchestrator::new("bb22", SwapRole::Taker);
+        store.create(maker.state(), Some("0badc0de")).unwrap();
+        store.create(taker.state(), None).unwrap();
+        assert!(matches!(
+            store.create(maker.state(), None),
+            Err(StoreError::AlreadyExists(_))
+        ));
+
+        maker.deployed("0x123", 10_000).unwrap();
+        maker.deposited().unwrap();
+        maker.set_monero_restore_height(2_999_280);
+        store.save(maker.state()).unwrap();
+
+        let stored = store.get("aa11").unwrap().unwrap();
+        assert_eq!(&stored.state, maker.state());
+        assert_eq!(stored.secret_hex.as_deref(), Some("0badc0de"));
+        assert_eq!(store.get("cc33").unwrap(), None);
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        let mut taker = SwapOrchestrator::from_state(store.get("bb22").unwrap().unwrap().state);
+        taker.deployed("0x456", 10_000).unwrap();
+        taker.refunded().unwrap();
+        store.save(taker.state()).unwrap();
+        let unfinished = store.unfinished().unwrap();
+        assert_eq!(unfinished.len(), 1);
+        assert_eq!(unfinished[0].state.swap_id, "aa11");
+
+        let missing = SwapOrchestrator::new("cc33", SwapRole::Maker);
+        assert!(matches!(
+            store.save(missing.state()),
+            Err(StoreError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_abort_reasons() {
+        let store = SwapStore::open_in_memory().unwrap();
+        let reasons = [
+            ("aa11", Some(AbortReason::DleqInvalid)),
+            ("bb22", Some(AbortReason::CounterpartyTimeout)),
+            ("cc33", Some(AbortReason::DleqInvalid)),
+            ("dd44", None),
+        ];
+        for (swap_id, reason) in reasons {
+            let mut swap = SwapOrchestrator::new(swap_id, SwapRole::Taker);
+            store.create(swap.state(), None).unwrap();
+            if let Some(reason) = reason {
+                swap.aborted(reason);
+                store.save(swap.state()).unwrap();
+            }
+        }
+
+        let stored = store.get("bb22").unwrap().unwrap();
+        assert_eq!(
+            stored.state.abort_reason,
+            Some(AbortReason::CounterpartyTimeout)
+        );
+        assert_eq!(
+            store.abort_reasons().unwrap(),
+            vec![
+                (AbortReason::DleqInvalid, 2),
+                (AbortReason::CounterpartyTimeout, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_transactions() {
+        let store = SwapStore::open_in_memory().unwrap();
+        store
+            .create(SwapOrchestrator::new("aa11", SwapRole::Maker).state(), None)
+            .unwrap();
+
+        store
+            .record_tx("aa11", Chain::Starknet, "deploy", "0xfeed")
+            .unwrap();
+        store
+            .record_tx("aa11", Chain::Monero, "lock", "beef")
+            .unwrap();
+        store
+            .record_tx("aa11", Chain::Monero, "lock", "beef")
+            .unwrap();
+        assert!(matches!(
+            store.record_tx("cc33", Chain::Monero, "lock", "beef"),
+            Err(StoreError::NotFound(_))
+        ));
+
+        let txs = store.transactions("aa11").unwrap();
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].chain, Chain::Starknet);
+        assert_eq!(txs[0].kind, "deploy");
+        assert_eq!(txs[1].tx_hash, "beef");
+    }
+
+    #[test]
+    fn test_counterparty_history() {
+        let store = SwapStore::open_in_memory().unwrap();
+        assert_eq!(
+            store.counterparty_history("maker-1").unwrap(),
+            CounterpartyHistory::default()
+        );
+
+        store
+            .record_outcome("maker-1", "aa11", SwapOutcome::Completed)
+            .unwrap();
+        store
+            .record_outcome("maker-1", "bb22", SwapOutcome::TimedOut)
+            .unwrap();
+        store
+            .record_outcome("maker-1", "cc33", SwapOutcome::Aborted)
+            .unwrap();
+        // Timed out swap eventually completed
+        store
+            .record_outcome("maker-1", "bb22", SwapOutcome::Completed)
+            .unwrap();
+        store
+            .record_outcome("maker-2", "dd44", SwapOutcome::TimedOut)
+            .unwrap();
+
+        let history = store.counterparty_history("maker-1").unwrap();
+        assert_eq!(
+            history,
+            CounterpartyHistory {
+                completed: 2,
+                aborted: 1,
+                timed_out: 0
+            }
+        );
+        assert_eq!(history.total(), 3);
+    }
+}
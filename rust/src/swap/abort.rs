@@ -0,0 +1,147 @@
+//! Why a swap was abandoned, as stable machine-readable codes.
+//!
+//! An [`AbortReason`] is recorded in the swap state (and the swap store) when
+//! a swap stops short of `Finalized`, so post-mortems and support tooling can
+//! group failed swaps without parsing error messages. The serde name is the
+//! code (`dleq_invalid`, `fee_exceeded`, ...) and never changes once
+//! released; add variants instead of renaming them.
+//!
+//! The CLIs attach the reason as `anyhow` context, so the code shows up in
+//! the error output and can be recovered with `downcast_ref::<AbortReason>()`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::store::SwapOutcome;
+
+/// Machine-readable cause of an aborted swap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbortReason {
+    /// The counterparty stopped responding after funds were locked.
+    CounterpartyTimeout,
+    /// The counterparty walked away before locking funds.
+    CounterpartyWalkedAway,
+    /// The DLEQ proof (or the deployment calldata carrying it) is invalid.
+    DleqInvalid,
+    /// On-chain terms (hashlock, adaptor point, timelock, amount) differ
+    /// from the agreed ones.
+    TermsMismatch,
+    /// The Monero lock transaction did not reach its required depth in time.
+    InsufficientConfirmations,
+    /// The estimated Starknet fee is above the configured maximum.
+    FeeExceeded,
+    /// The counterparty was rejected by the risk check.
+    RiskRejected,
+    /// The timelock expired without a reveal and the deposit was refunded.
+    TimelockExpired,
+    /// A Monero or Starknet node was unreachable, unsynced or on the wrong
+    /// network.
+    NodeUnavailable,
+    /// Aborted by hand.
+    Operator,
+}
+
+impl AbortReason {
+    /// Every reason, in declaration order.
+    pub const ALL: [AbortReason; 10] = [
+        AbortReason::CounterpartyTimeout,
+        AbortReason::CounterpartyWalkedAway,
+        AbortReason::DleqInvalid,
+        AbortReason::TermsMismatch,
+        AbortReason::InsufficientConfirmations,
+        AbortReason::FeeExceeded,
+        AbortReason::RiskRejected,
+        AbortReason::TimelockExpired,
+        AbortReason::NodeUnavailable,
+        AbortReason::Operator,
+    ];
+
+    /// Stable failure code (the serde name).
+    pub fn code(self) -> &'static str {
+        match self {
+            AbortReason::CounterpartyTimeout => "counterparty_timeout",
+            AbortReason::CounterpartyWalkedAway => "counterparty_walked_away",
+            AbortReason::DleqInvalid => "dleq_invalid",
+            AbortReason::TermsMismatch => "terms_mismatch",
+            AbortReason::InsufficientConfirmations => "insufficient_confirmations",
+            AbortReason::FeeExceeded => "fee_exceeded",
+            AbortReason::RiskRejected => "risk_rejected",
+            AbortReason::TimelockExpired => "timelock_expired",
+            AbortReason::NodeUnavailable => "node_unavailable",
+            AbortReason::Operator => "operator",
+        }
+    }
+
+    /// How the abort counts in the counterparty's history (see
+    /// [`crate::swap::risk`]); `None` when it was not the counterparty's doing.
+    pub fn counterparty_outcome(self) -> Option<SwapOutcome> {
+        match self {
+            AbortReason::CounterpartyTimeout | AbortReason::TimelockExpired => {
+                Some(SwapOutcome::TimedOut)
+            }
+            AbortReason::CounterpartyWalkedAway
+            | AbortReason::DleqInvalid
+            | AbortReason::TermsMismatch => Some(SwapOutcome::Aborted),
+            AbortReason::InsufficientConfirmations
+            | AbortReason::FeeExceeded
+            | AbortReason::RiskRejected
+            | AbortReason::NodeUnavailable
+            | AbortReason::Operator => None,
+        }
+    }
+}
+
+impl fmt::Display for AbortReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "swap aborted: {}", self.code())
+    }
+}
+
+impl FromStr for AbortReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AbortReason::ALL
+            .into_iter()
+            .find(|reason| reason.code() == s.trim())
+            .ok_or_else(|| format!("unknown abort reason '{}'", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_match_serde_and_parse_back() {
+        for reason in AbortReason::ALL {
+            assert_eq!(
+                serde_json::to_value(reason).unwrap(),
+                serde_json::Value::String(reason.code().to_string())
+            );
+            assert_eq!(reason.code().parse::<AbortReason>(), Ok(reason));
+        }
+        assert_eq!(
+            AbortReason::DleqInvalid.to_string(),
+            "swap aborted: dleq_invalid"
+        );
+        assert!("dleq".parse::<AbortReason>().is_err());
+    }
+
+    #[test]
+    fn test_context_is_recoverable_from_anyhow() {
+        let error = anyhow::anyhow!("fee 3 STRK > max 1 STRK").context(AbortReason::FeeExceeded);
+        assert_eq!(
+            error.downcast_ref::<AbortReason>(),
+            Some(&AbortReason::FeeExceeded)
+        );
+        assert_eq!(AbortReason::FeeExceeded.counterparty_outcome(), None);
+        assert_eq!(
+            AbortReason::TimelockExpired.counterparty_outcome(),
+            Some(SwapOutcome::TimedOut)
+        );
+    }
+}
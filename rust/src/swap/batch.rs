@@ -0,0 +1,326 @@
+//! Batch swap creation from a spreadsheet (`maker import-batch`).
+//!
+//! The input is a CSV export with a header naming the columns, in any order:
+//!
+//! ```text
+//! amount,taker_contact,timelock
+//! 1000000000000000000,desk-a@example.com,3600
+//! 0x2386f26fc10000,"Bob, OTC",7200
+//! ```
+//!
+//! - `amount`: token base units, decimal or `0x` hex (as `maker --amount`)
+//! - `taker_contact`: free text identifying the counterparty
+//! - `timelock`: lock duration in seconds (as `maker --lock-duration`)
+//!
+//! Rows are parsed independently so one bad row does not stop the batch;
+//! [`BatchReport`] records what happened to each row.
+
+use thiserror::Error;
+
+/// Columns `import-batch` reads (extra columns are ignored).
+pub const COLUMNS: [&str; 3] = ["amount", "taker_contact", "timelock"];
+
+/// Errors reading a batch file.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BatchError {
+    #[error("missing column {0:?} in header")]
+    MissingColumn(&'static str),
+
+    #[error("line {line}: unterminated quoted field")]
+    Unterminated { line: usize },
+
+    #[error("line {line}: expected {expected} fields, found {found}")]
+    FieldCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("line {line}: invalid amount {value:?}")]
+    Amount { line: usize, value: String },
+
+    #[error("line {line}: invalid timelock {value:?} (seconds)")]
+    Timelock { line: usize, value: String },
+
+    #[error("line {line}: empty taker_contact")]
+    Contact { line: usize },
+}
+
+impl BatchError {
+    /// CSV line of a row error (`None` for header errors).
+    pub fn line(&self) -> Option<usize> {
+        match *self {
+            BatchError::MissingColumn(_) => None,
+            BatchError::Unterminated { line }
+            | BatchError::FieldCount { line, .. }
+            | BatchError::Amount { line, .. }
+            | BatchError::Timelock { line, .. }
+            | BatchError::Contact { line } => Some(line),
+        }
+    }
+}
+
+/// One swap to create.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchRow {
+    /// 1-based line in the CSV file
+    pub line: usize,
+    pub amount: u128,
+    pub taker_contact: String,
+    /// Lock duration in seconds
+    pub lock_duration: u64,
+}
+
+/// Parse a batch CSV. Fails only if the header is unusable; each data row
+/// is returned parsed or with its error. Blank lines are skipped.
+pub fn parse_batch(csv: &str) -> Result<Vec<Result<BatchRow, BatchError>>, BatchError> {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let header = match lines.next() {
+        Some((line, header)) => split_fields(header, line)?,
+        None => return Err(BatchError::MissingColumn(COLUMNS[0])),
+    };
+    let column = |name: &'static str| {
+        header
+            .iter()
+            .position(|field| field.trim().eq_ignore_ascii_case(name))
+            .ok_or(BatchError::MissingColumn(name))
+    };
+    let (amount, contact, timelock) = (
+        column(COLUMNS[0])?,
+        column(COLUMNS[1])?,
+        column(COLUMNS[2])?,
+    );
+
+    Ok(lines
+        .map(|(line, text)| {
+            let fields = split_fields(text, line)?;
+            if fields.len() < header.len() {
+                return Err(BatchError::FieldCount {
+                    line,
+                    expected: header.len(),
+                    found: fields.len(),
+                });
+            }
+            let value = fields[amount].trim();
+            let parsed = match value.strip_prefix("0x") {
+                Some(hex) => u128::from_str_radix(hex, 16),
+                None => value.parse(),
+            };
+            let amount = parsed.map_err(|_| BatchError::Amount {
+                line,
+                value: value.to_string(),
+            })?;
+
+            let value = fields[timelock].trim();
+            let lock_duration = value
+                .parse()
+                .ok()
+                .filter(|&seconds: &u64| seconds > 0)
+                .ok_or_else(|| BatchError::Timelock {
+                    line,
+                    value: value.to_string(),
+                })?;
+
+            let taker_contact = fields[contact].trim().to_string();
+            if taker_contact.is_empty() {
+                return Err(BatchError::Contact { line });
+            }
+            Ok(BatchRow {
+                line,
+                amount,
+                taker_contact,
+                lock_duration,
+            })
+        })
+        .collect())
+}
+
+/// Split one CSV line. Fields may be double-quoted, with `""` for a quote.
+fn split_fields(text: &str, line: usize) -> Result<Vec<String>, BatchError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(BatchError::Unterminated { line });
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Outcome of one row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowStatus {
+    /// State file written; the contract still has to be deployed
+    Created,
+    /// Contract deployed at this address
+    Deployed(String),
+    /// Failed; `state_file` is set if the swap was saved before the error
+    Failed(String),
+}
+
+/// One line of the status report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowReport {
+    pub line: usize,
+    pub taker_contact: String,
+    pub swap_id: Option<String>,
+    pub state_file: Option<String>,
+    pub status: RowStatus,
+}
+
+/// Per-row status of an `import-batch` run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    pub rows: Vec<RowReport>,
+}
+
+impl BatchReport {
+    pub fn push(&mut self, row: RowReport) {
+        self.rows.push(row);
+    }
+
+    /// Rows that failed.
+    pub fn failed(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| matches!(row.status, RowStatus::Failed(_)))
+            .count()
+    }
+
+    /// The report as CSV:
+    /// `line,taker_contact,swap_id,state_file,status,contract_address,error`.
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("line,taker_contact,swap_id,state_file,status,contract_address,error\n");
+        for row in &self.rows {
+            let (status, contract_address, error) = match &row.status {
+                RowStatus::Created => ("created", "", ""),
+                RowStatus::Deployed(address) => ("deployed", address.as_str(), ""),
+                RowStatus::Failed(error) => ("failed", "", error.as_str()),
+            };
+            let fields = [
+                row.line.to_string(),
+                quote(&row.taker_contact),
+                row.swap_id.clone().unwrap_or_default(),
+                quote(row.state_file.as_deref().unwrap_or_default()),
+                status.to_string(),
+                contract_address.to_string(),
+                quote(error),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field if it needs it.
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_batch() {
+        let csv = "Timelock,amount,taker_contact,note\r\n\
+                   3600,1000,desk-a@example.com,first\r\n\
+                   \r\n\
+                   7200,0x10,\"Bob \"\"OTC\"\", London\",\n\
+                   60,12.5,carol,\n\
+                   0,1,dave,\n\
+                   60,1,\"\",\n\
+                   60,1\n";
+        let rows = parse_batch(csv).unwrap();
+        assert_eq!(
+            rows[0],
+            Ok(BatchRow {
+                line: 2,
+                amount: 1000,
+                taker_contact: "desk-a@example.com".into(),
+                lock_duration: 3600,
+            })
+        );
+        assert_eq!(
+            rows[1].as_ref().unwrap().taker_contact,
+            "Bob \"OTC\", London"
+        );
+        assert_eq!(rows[1].as_ref().unwrap().amount, 16);
+        assert_eq!(
+            rows[2],
+            Err(BatchError::Amount {
+                line: 5,
+                value: "12.5".into()
+            })
+        );
+        assert!(matches!(rows[3], Err(BatchError::Timelock { line: 6, .. })));
+        assert_eq!(rows[4], Err(BatchError::Contact { line: 7 }));
+        assert_eq!(rows[4].as_ref().unwrap_err().line(), Some(7));
+        assert!(matches!(
+            rows[5],
+            Err(BatchError::FieldCount { line: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_header_errors() {
+        assert_eq!(
+            parse_batch("amount,timelock\n1,60\n"),
+            Err(BatchError::MissingColumn("taker_contact"))
+        );
+        assert_eq!(parse_batch(""), Err(BatchError::MissingColumn("amount")));
+    }
+
+    #[test]
+    fn test_report_csv() {
+        let mut report = BatchReport::default();
+        report.push(RowReport {
+            line: 2,
+            taker_contact: "Bob, OTC".into(),
+            swap_id: Some("ab12".into()),
+            state_file: Some("batch/ab12.json".into()),
+            status: RowStatus::Deployed("0x123".into()),
+        });
+        report.push(RowReport {
+            line: 3,
+            taker_contact: "carol".into(),
+            swap_id: None,
+            state_file: None,
+            status: RowStatus::Failed("line 3: invalid amount \"x\"".into()),
+        });
+        assert_eq!(report.failed(), 1);
+        assert_eq!(
+            report.to_csv(),
+            "line,taker_contact,swap_id,state_file,status,contract_address,error\n\
+             2,\"Bob, OTC\",ab12,batch/ab12.json,deployed,0x123,\n\
+             3,carol,,,failed,,\"line 3: invalid amount \"\"x\"\"\"\n"
+        );
+    }
+}
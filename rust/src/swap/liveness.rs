@@ -0,0 +1,296 @@
+//! Counterparty liveness tracking during an active swap.
+//!
+//! Both parties periodically exchange a small [`Heartbeat`] while a swap is
+//! in progress. If the counterparty goes silent for longer than the configured
+//! timeout before a critical step, [`LivenessMonitor::check`] returns a
+//! [`LivenessWarning`] carrying the safe abort path for the current phase.
+//!
+//! The heartbeat is a plain serde message so it can be carried by whatever
+//! transport connects the two parties; this module only tracks timing. The
+//! maker's `resume` loop reads the latest heartbeat from a file kept up to
+//! date by that transport ([`read_heartbeat`]).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{SwapPhase, SwapRole};
+
+/// Default interval between outgoing heartbeats (seconds).
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 30;
+
+/// Default silence after which the counterparty is considered unresponsive (seconds).
+pub const DEFAULT_LIVENESS_TIMEOUT_SECS: u64 = 300;
+
+/// Heartbeat message exchanged during an active swap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    /// Swap identifier both parties agreed on.
+    pub swap_id: String,
+    /// Monotonically increasing per sender (stale/replayed heartbeats are ignored).
+    pub sequence: u64,
+    /// Sender's view of the current phase.
+    pub phase: SwapPhase,
+    /// Sender's unix timestamp (seconds).
+    pub timestamp: u64,
+}
+
+/// Liveness timing configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivenessConfig {
+    /// How often we send heartbeats (seconds).
+    pub heartbeat_interval_secs: u64,
+    /// Silence after which we warn (seconds).
+    pub timeout_secs: u64,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval_secs: DEFAULT_HEARTBEAT_INTERVAL_SECS,
+            timeout_secs: DEFAULT_LIVENESS_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// Safe way to leave a swap whose counterparty stopped responding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbortPath {
+    /// Nothing of ours is locked: simply stop.
+    WalkAway,
+    /// Tokens are in the contract: wait for `lock_until`, then call `refund()`.
+    RefundAfterTimelock,
+    /// `t` is public: do NOT abort, finish our side (sweep XMR / claim tokens).
+    CompleteOwnSide,
+    /// Swap already finished, nothing to do.
+    None,
+}
+
+impl AbortPath {
+    /// Safe abort path for `role` in `phase`.
+    pub fn for_phase(role: SwapRole, phase: SwapPhase) -> Self {
+        match (role, phase) {
+            (_, SwapPhase::Finalized | SwapPhase::Refunded) => AbortPath::None,
+            (_, SwapPhase::Revealed) => AbortPath::CompleteOwnSide,
+            (SwapRole::Maker, SwapPhase::Deposited) => AbortPath::RefundAfterTimelock,
+            (SwapRole::Maker, SwapPhase::Created | SwapPhase::Deployed) => AbortPath::WalkAway,
+            (SwapRole::Taker, SwapPhase::Created | SwapPhase::Deployed | SwapPhase::Deposited) => {
+                AbortPath::WalkAway
+            }
+        }
+    }
+
+    /// Human-readable suggestion for CLI output and alerts.
+    pub fn suggestion(self) -> &'static str {
+        match self {
+            AbortPath::WalkAway => "No funds are locked on our side; it is safe to abort the swap.",
+            AbortPath::RefundAfterTimelock => {
+                "Tokens are locked in the contract; wait for lock_until and call refund()."
+            }
+            AbortPath::CompleteOwnSide => {
+                "The secret is public; do not abort. Finish our side of the swap now."
+            }
+            AbortPath::None => "Swap already finished; nothing to do.",
+        }
+    }
+}
+
+/// Warning raised when the counterparty has been silent too long.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LivenessWarning {
+    /// Seconds since the last heartbeat (or since monitoring started).
+    pub silent_for_secs: u64,
+    /// Our current phase.
+    pub phase: SwapPhase,
+    /// Recommended way out.
+    pub abort_path: AbortPath,
+}
+
+/// Tracks heartbeats received from the counterparty of one swap.
+#[derive(Debug, Clone)]
+pub struct LivenessMonitor {
+    swap_id: String,
+    role: SwapRole,
+    config: LivenessConfig,
+    last_seen: u64,
+    last_sequence: Option<u64>,
+    next_sequence: u64,
+}
+
+impl LivenessMonitor {
+    /// Start monitoring at unix time `now`.
+    pub fn new(swap_id: String, role: SwapRole, config: LivenessConfig, now: u64) -> Self {
+        Self {
+            swap_id,
+            role,
+            config,
+            last_seen: now,
+            last_sequence: None,
+            next_sequence: 0,
+        }
+    }
+
+    /// Build our next outgoing heartbeat.
+    pub fn next_heartbeat(&mut self, phase: SwapPhase, now: u64) -> Heartbeat {
+        let heartbeat = Heartbeat {
+            swap_id: self.swap_id.clone(),
+            sequence: self.next_sequence,
+            phase,
+            timestamp: now,
+        };
+        self.next_sequence += 1;
+        heartbeat
+    }
+
+    /// Record a heartbeat received at unix time `now`.
+    ///
+    /// Returns `false` (and ignores it) if it belongs to another swap or its
+    /// sequence number is not newer than the last accepted one.
+    pub fn record(&mut self, heartbeat: &Heartbeat, now: u64) -> bool {
+        if heartbeat.swap_id != self.swap_id {
+            return false;
+        }
+        if matches!(self.last_sequence, Some(last) if heartbeat.sequence <= last) {
+            return false;
+        }
+        self.last_sequence = Some(heartbeat.sequence);
+        self.last_seen = now;
+        true
+    }
+
+    /// Seconds since the counterparty was last heard from.
+    pub fn silent_for(&self, now: u64) -> u64 {
+        now.saturating_sub(self.last_seen)
+    }
+
+    /// Check liveness before acting in `phase`.
+    ///
+    /// Returns a warning when the counterparty has been silent for longer
+    /// than the configured timeout and the swap is not finished.
+    pub fn check(&self, phase: SwapPhase, now: u64) -> Option<LivenessWarning> {
+        if phase.is_terminal() {
+            return None;
+        }
+        let silent_for_secs = self.silent_for(now);
+        if silent_for_secs <= self.config.timeout_secs {
+            return None;
+        }
+        Some(LivenessWarning {
+            silent_for_secs,
+            phase,
+            abort_path: AbortPath::for_phase(self.role, phase),
+        })
+    }
+}
+
+/// Latest heartbeat written to `path` by the transport to the counterparty.
+///
+/// A missing or partially written file counts as no news rather than an
+/// error: the monitor keeps counting the silence.
+pub fn read_heartbeat(path: &Path) -> Option<Heartbeat> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(role: SwapRole) -> LivenessMonitor {
+        let config = LivenessConfig {
+            heartbeat_interval_secs: 10,
+            timeout_secs: 60,
+        };
+        LivenessMonitor::new("swap-1".to_string(), role, config, 1_000)
+    }
+
+    #[test]
+    fn test_no_warning_within_timeout() {
+        let m = monitor(SwapRole::Maker);
+        assert_eq!(m.check(SwapPhase::Deposited, 1_060), None);
+    }
+
+    #[test]
+    fn test_warning_after_timeout_suggests_refund() {
+        let m = monitor(SwapRole::Maker);
+        let warning = m.check(SwapPhase::Deposited, 1_061).expect("should warn");
+        assert_eq!(warning.silent_for_secs, 61);
+        assert_eq!(warning.abort_path, AbortPath::RefundAfterTimelock);
+    }
+
+    #[test]
+    fn test_heartbeat_resets_timer() {
+        let mut m = monitor(SwapRole::Taker);
+        let hb = Heartbeat {
+            swap_id: "swap-1".to_string(),
+            sequence: 0,
+            phase: SwapPhase::Deployed,
+            timestamp: 1_050,
+        };
+        assert!(m.record(&hb, 1_050));
+        assert_eq!(m.check(SwapPhase::Deployed, 1_100), None);
+    }
+
+    #[test]
+    fn test_stale_and_foreign_heartbeats_ignored() {
+        let mut m = monitor(SwapRole::Taker);
+        let mut hb = Heartbeat {
+            swap_id: "swap-1".to_string(),
+            sequence: 5,
+            phase: SwapPhase::Deployed,
+            timestamp: 1_010,
+        };
+        assert!(m.record(&hb, 1_010));
+        hb.sequence = 5;
+        assert!(!m.record(&hb, 1_020), "replayed sequence must be ignored");
+        hb.sequence = 6;
+        hb.swap_id = "other".to_string();
+        assert!(
+            !m.record(&hb, 1_020),
+            "heartbeat for another swap must be ignored"
+        );
+    }
+
+    #[test]
+    fn test_revealed_never_suggests_abort() {
+        assert_eq!(
+            AbortPath::for_phase(SwapRole::Maker, SwapPhase::Revealed),
+            AbortPath::CompleteOwnSide
+        );
+        assert_eq!(
+            AbortPath::for_phase(SwapRole::Taker, SwapPhase::Revealed),
+            AbortPath::CompleteOwnSide
+        );
+    }
+
+    #[test]
+    fn test_terminal_phase_no_warning() {
+        let m = monitor(SwapRole::Maker);
+        assert_eq!(m.check(SwapPhase::Finalized, 10_000), None);
+    }
+
+    #[test]
+    fn test_read_heartbeat_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("heartbeat.json");
+        assert_eq!(read_heartbeat(&path), None);
+
+        std::fs::write(&path, "{\"swap_id\": \"swap-1\", \"seq").unwrap();
+        assert_eq!(read_heartbeat(&path), None, "partial write is no news");
+
+        let mut m = monitor(SwapRole::Maker);
+        let mut peer = monitor(SwapRole::Taker);
+        let hb = peer.next_heartbeat(SwapPhase::Deposited, 1_050);
+        std::fs::write(&path, serde_json::to_string(&hb).unwrap()).unwrap();
+        assert_eq!(read_heartbeat(&path), Some(hb.clone()));
+        assert!(m.record(&hb, 1_050));
+        assert_eq!(m.check(SwapPhase::Deposited, 1_100), None);
+    }
+
+    #[test]
+    fn test_outgoing_sequence_increments() {
+        let mut m = monitor(SwapRole::Maker);
+        assert_eq!(m.next_heartbeat(SwapPhase::Created, 1_000).sequence, 0);
+        assert_eq!(m.next_heartbeat(SwapPhase::Created, 1_010).sequence, 1);
+    }
+}
@@ -0,0 +1,65 @@
+//! Swap lifecycle types shared by the maker/taker tooling.
+//!
+//! - `SwapPhase`: where a swap is in the protocol (see docs/PROTOCOL.md)
+//! - abort: `AbortReason`, machine-readable codes for abandoned swaps
+//! - batch: CSV input and status report of `maker import-batch`
+//! - liveness: counterparty heartbeats and safe-abort suggestions
+//! - orchestrator: `SwapOrchestrator`, the transition-checked swap driver
+//! - reconcile: recover the actual phase from chain state when resuming
+//! - risk: counterparty scoring for the taker, from outcomes in the swap store
+//! - spec: allowed transitions, loaded from `spec/protocol.json`
+//! - state_file: the CLIs' swap state file, optionally passphrase-encrypted
+//! - confirmations: required confirmations by swap value (`spec/confirmations.json`)
+//! - verify: taker-side DLEQ check of a deployed contract's constructor calldata
+
+pub mod abort;
+pub mod batch;
+pub mod liveness;
+pub mod orchestrator;
+pub mod reconcile;
+pub mod risk;
+pub mod spec;
+pub mod state_file;
+pub mod verify;
+
+pub use swap_common::confirmations;
+
+use serde::{Deserialize, Serialize};
+
+/// Which side of the swap we are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapRole {
+    /// Alice: holds XMR, deploys the AtomicLock contract and deposits tokens.
+    Maker,
+    /// Bob: reveals `t` on Starknet and claims the tokens.
+    Taker,
+}
+
+/// Protocol phase of a single swap.
+///
+/// `Created → Deployed → Deposited → Revealed → Finalized`, with `Refunded`
+/// reachable from `Deployed`/`Deposited` once the timelock expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapPhase {
+    /// Secrets and DLEQ proof generated, nothing on chain yet.
+    Created,
+    /// AtomicLock contract deployed (DLEQ verified by the constructor).
+    Deployed,
+    /// Tokens deposited into the contract.
+    Deposited,
+    /// `t` revealed on Starknet, grace period running.
+    Revealed,
+    /// Tokens claimed and XMR swept with the recovered key.
+    Finalized,
+    /// Timelock expired and the depositor was refunded.
+    Refunded,
+}
+
+impl SwapPhase {
+    /// Terminal phases cannot transition any further.
+    pub fn is_terminal(self) -> bool {
+        matches!(self, SwapPhase::Finalized | SwapPhase::Refunded)
+    }
+}
@@ -0,0 +1,298 @@
+//! Library-level swap state machine.
+//!
+//! `SwapOrchestrator` owns a serializable `SwapState` and moves it through
+//! `Created → Deployed → Deposited → Revealed → Finalized/Refunded` with one
+//! method per transition. Every transition is checked against the protocol
+//! spec (`spec/protocol.json`) and reported to registered callbacks, so a
+//! CLI can print progress while a daemon persists state, from the same code.
+//!
+//! The orchestrator records facts ("the contract was deployed at X"); it does
+//! not talk to any chain itself. Callers perform the action, then report it.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::abort::AbortReason;
+use super::reconcile::{reconcile, NextAction};
+use super::spec::{protocol_spec, SpecViolation};
+use super::{SwapPhase, SwapRole};
+use crate::starknet::AtomicLockStatus;
+
+/// Everything needed to resume a swap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapState {
+    /// Hex SHA-256 hashlock (public, unique per secret).
+    pub swap_id: String,
+    pub role: SwapRole,
+    pub phase: SwapPhase,
+    #[serde(default)]
+    pub contract_address: Option<String>,
+    /// Refund timelock (unix seconds), known once deployed.
+    #[serde(default)]
+    pub lock_until: Option<u64>,
+    /// End of the grace period (unix seconds), known once revealed.
+    #[serde(default)]
+    pub claimable_after: Option<u64>,
+    #[serde(default)]
+    pub monero_lock_txid: Option<String>,
+    /// Height to restore the swap wallet from (lock height minus a buffer).
+    #[serde(default)]
+    pub monero_restore_height: Option<u64>,
+    /// Transitions so far, oldest first.
+    #[serde(default)]
+    pub history: Vec<PhaseChange>,
+    /// Why the swap was abandoned, if it was.
+    #[serde(default)]
+    pub abort_reason: Option<AbortReason>,
+}
+
+/// One recorded transition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseChange {
+    pub from: SwapPhase,
+    pub to: SwapPhase,
+    /// Spec trigger (`ContractDeployed`, `deposit`, ...).
+    pub on: String,
+}
+
+/// Orchestrator errors.
+#[derive(Debug, Error)]
+pub enum OrchestratorError {
+    #[error("invalid transition: {0}")]
+    InvalidTransition(#[from] SpecViolation),
+
+    #[error("invalid swap state: {0}")]
+    InvalidState(#[from] serde_json::Error),
+}
+
+type Callback = Box<dyn FnMut(&SwapState, &PhaseChange) + Send>;
+
+/// Drives one swap through its phases.
+pub struct SwapOrchestrator {
+    state: SwapState,
+    callbacks: Vec<Callback>,
+}
+
+impl SwapOrchestrator {
+    /// Start a new swap in `Created`.
+    pub fn new(swap_id: impl Into<String>, role: SwapRole) -> Self {
+        Self::from_state(SwapState {
+            swap_id: swap_id.into(),
+            role,
+            phase: protocol_spec().initial,
+            contract_address: None,
+            lock_until: None,
+            claimable_after: None,
+            monero_lock_txid: None,
+            monero_restore_height: None,
+            history: Vec::new(),
+            abort_reason: None,
+        })
+    }
+
+    /// Resume from saved state.
+    pub fn from_state(state: SwapState) -> Self {
+        Self {
+            state,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Resume from `to_json` output.
+    pub fn from_json(json: &str) -> Result<Self, OrchestratorError> {
+        Ok(Self::from_state(serde_json::from_str(json)?))
+    }
+
+    pub fn to_json(&self) -> Result<String, OrchestratorError> {
+        Ok(serde_json::to_string_pretty(&self.state)?)
+    }
+
+    pub fn state(&self) -> &SwapState {
+        &self.state
+    }
+
+    pub fn phase(&self) -> SwapPhase {
+        self.state.phase
+    }
+
+    /// Call `callback` after every successful transition.
+    pub fn on_transition<F>(&mut self, callback: F)
+    where
+        F: FnMut(&SwapState, &PhaseChange) + Send + 'static,
+    {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// The AtomicLock contract was deployed at `contract_address`.
+    pub fn deployed(
+        &mut self,
+        contract_address: impl Into<String>,
+        lock_until: u64,
+    ) -> Result<(), OrchestratorError> {
+        self.transition("ContractDeployed", |state| {
+            state.contract_address = Some(contract_address.into());
+            state.lock_until = Some(lock_until);
+        })
+    }
+
+    /// Tokens were deposited into the contract.
+    pub fn deposited(&mut self) -> Result<(), OrchestratorError> {
+        self.transition("deposit", |_| {})
+    }
+
+    /// `t` was revealed on Starknet; tokens are claimable after `claimable_after`.
+    pub fn revealed(&mut self, claimable_after: u64) -> Result<(), OrchestratorError> {
+        self.transition("SecretRevealed", |state| {
+            state.claimable_after = Some(claimable_after);
+        })
+    }
+
+    /// Tokens claimed (taker) or XMR swept (maker).
+    pub fn finalized(&mut self) -> Result<(), OrchestratorError> {
+        self.transition("TokensClaimed", |_| {})
+    }
+
+    /// Timelock expired and the depositor was refunded.
+    pub fn refunded(&mut self) -> Result<(), OrchestratorError> {
+        self.transition("Refunded", |_| {})
+    }
+
+    /// Record the Monero lock transaction (any phase).
+    pub fn set_monero_lock_txid(&mut self, txid: impl Into<String>) {
+        self.state.monero_lock_txid = Some(txid.into());
+    }
+
+    /// Record the swap wallet's restore height (any phase).
+    pub fn set_monero_restore_height(&mut self, height: u64) {
+        self.state.monero_restore_height = Some(height);
+    }
+
+    /// Record why the swap is being abandoned (any phase). The phase is
+    /// unchanged: a refund, if one is due, is still reported with
+    /// [`Self::refunded`].
+    pub fn aborted(&mut self, reason: AbortReason) {
+        self.state.abort_reason = Some(reason);
+    }
+
+    /// Recommended next step, from local state only.
+    pub fn next_action(&self, now: u64) -> NextAction {
+        let phase = self.state.phase;
+        let chain = self.state.lock_until.map(|lock_until| AtomicLockStatus {
+            secret_revealed: matches!(phase, SwapPhase::Revealed | SwapPhase::Finalized),
+            unlocked: phase.is_terminal(),
+            lock_until,
+            claimable_after: self.state.claimable_after.unwrap_or(0),
+        });
+        reconcile(self.state.role, phase, chain.as_ref(), now).next_action
+    }
+
+    fn transition(
+        &mut self,
+        trigger: &str,
+        update: impl FnOnce(&mut SwapState),
+    ) -> Result<(), OrchestratorError> {
+        let from = self.state.phase;
+        let to = protocol_spec()
+            .next(from, trigger)
+            .ok_or_else(|| SpecViolation {
+                phase: from,
+                trigger: trigger.to_string(),
+                step: self.state.history.len(),
+            })?;
+
+        update(&mut self.state);
+        let change = PhaseChange {
+            from,
+            to,
+            on: trigger.to_string(),
+        };
+        self.state.phase = to;
+        self.state.history.push(change.clone());
+
+        for callback in &mut self.callbacks {
+            callback(&self.state, &change);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_happy_path_with_callbacks() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut swap = SwapOrchestrator::new("ab12", SwapRole::Taker);
+        let sink = Arc::clone(&seen);
+        swap.on_transition(move |_, change| sink.lock().unwrap().push(change.to));
+
+        swap.deployed("0x123", 10_000).unwrap();
+        swap.deposited().unwrap();
+        assert_eq!(swap.next_action(1_000), NextAction::RevealSecret);
+        swap.revealed(5_000).unwrap();
+        assert_eq!(
+            swap.next_action(1_000),
+            NextAction::ClaimTokens {
+                claimable_after: 5_000
+            }
+        );
+        swap.finalized().unwrap();
+
+        assert_eq!(swap.phase(), SwapPhase::Finalized);
+        assert_eq!(swap.next_action(6_000), NextAction::Done);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                SwapPhase::Deployed,
+                SwapPhase::Deposited,
+                SwapPhase::Revealed,
+                SwapPhase::Finalized
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_transitions() {
+        let mut swap = SwapOrchestrator::new("ab12", SwapRole::Maker);
+        assert!(matches!(
+            swap.revealed(5_000),
+            Err(OrchestratorError::InvalidTransition(_))
+        ));
+        assert_eq!(swap.phase(), SwapPhase::Created);
+        assert_eq!(swap.state().claimable_after, None);
+
+        swap.deployed("0x123", 10_000).unwrap();
+        swap.deposited().unwrap();
+        swap.revealed(5_000).unwrap();
+        assert!(swap.refunded().is_err());
+    }
+
+    #[test]
+    fn test_state_roundtrip_and_refund_hint() {
+        let mut swap = SwapOrchestrator::new("ab12", SwapRole::Maker);
+        swap.deployed("0x123", 10_000).unwrap();
+        swap.deposited().unwrap();
+        swap.set_monero_lock_txid("feed");
+
+        swap.aborted(AbortReason::CounterpartyTimeout);
+
+        let resumed = SwapOrchestrator::from_json(&swap.to_json().unwrap()).unwrap();
+        assert_eq!(resumed.state(), swap.state());
+        assert_eq!(
+            resumed.next_action(9_999),
+            NextAction::WaitForReveal { lock_until: 10_000 }
+        );
+        assert_eq!(resumed.next_action(10_000), NextAction::Refund);
+        assert_eq!(
+            resumed.state().abort_reason,
+            Some(AbortReason::CounterpartyTimeout)
+        );
+
+        // State saved before abort reasons existed
+        let legacy = r#"{"swap_id": "ab12", "role": "maker", "phase": "created"}"#;
+        let legacy = SwapOrchestrator::from_json(legacy).unwrap();
+        assert_eq!(legacy.state().abort_reason, None);
+    }
+}
@@ -0,0 +1,209 @@
+//! Reconcile a (possibly stale) local swap phase with what the chain says.
+//!
+//! Used when resuming a swap after a crash or restart: the local state file
+//! may say `Deposited` while the taker already revealed `t`, or the timelock
+//! may have expired in the meantime. The on-chain AtomicLock state always
+//! wins; the local phase only fills in what the contract views can't tell us
+//! (e.g. whether the deposit happened).
+
+use serde::{Deserialize, Serialize};
+
+use super::{SwapPhase, SwapRole};
+use crate::starknet::AtomicLockStatus;
+
+/// What to do next after reconciling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "action")]
+pub enum NextAction {
+    /// Deploy the AtomicLock contract (nothing on chain yet).
+    DeployContract,
+    /// Deposit the tokens into the deployed contract.
+    Deposit,
+    /// Wait for the taker to reveal `t` (refund possible after `lock_until`).
+    WaitForReveal { lock_until: u64 },
+    /// Timelock expired without a reveal: call `refund()`.
+    Refund,
+    /// Reveal `t` on Starknet (taker).
+    RevealSecret,
+    /// `t` is public: extract it and sweep the XMR with x = x_partial + t (maker).
+    SweepMonero,
+    /// Claim the tokens once the grace period ends (taker).
+    ClaimTokens { claimable_after: u64 },
+    /// Nothing left to do.
+    Done,
+}
+
+/// Result of reconciling local and on-chain state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reconciliation {
+    /// Actual current phase.
+    pub phase: SwapPhase,
+    /// Whether the local phase was out of date.
+    pub local_was_stale: bool,
+    /// Recommended next step.
+    pub next_action: NextAction,
+}
+
+/// Determine the actual phase from the local phase and the contract status.
+///
+/// `chain` is `None` when no contract address is known yet.
+pub fn reconcile(
+    role: SwapRole,
+    local: SwapPhase,
+    chain: Option<&AtomicLockStatus>,
+    now: u64,
+) -> Reconciliation {
+    let phase = match chain {
+        None => local,
+        // unlocked without reveal can only mean refund()
+        Some(status) if status.unlocked && !status.secret_revealed => SwapPhase::Refunded,
+        // revealed: the taker is done once tokens are claimed; the maker only
+        // once the XMR sweep is recorded locally
+        Some(status) if status.secret_revealed => {
+            if status.unlocked && (role == SwapRole::Taker || local == SwapPhase::Finalized) {
+                SwapPhase::Finalized
+            } else {
+                SwapPhase::Revealed
+            }
+        }
+        // contract exists: at least Deployed; deposit is only known locally
+        Some(_) => match local {
+            SwapPhase::Created => SwapPhase::Deployed,
+            SwapPhase::Deployed | SwapPhase::Deposited => local,
+            // local claims a later phase the chain does not back up
+            SwapPhase::Revealed | SwapPhase::Finalized | SwapPhase::Refunded => {
+                SwapPhase::Deposited
+            }
+        },
+    };
+
+    let next_action = match (role, phase) {
+        (_, SwapPhase::Finalized | SwapPhase::Refunded) => NextAction::Done,
+        (SwapRole::Maker, SwapPhase::Created) => NextAction::DeployContract,
+        (SwapRole::Maker, SwapPhase::Deployed) => NextAction::Deposit,
+        (SwapRole::Maker, SwapPhase::Deposited) => match chain {
+            Some(status) if now >= status.lock_until => NextAction::Refund,
+            Some(status) => NextAction::WaitForReveal {
+                lock_until: status.lock_until,
+            },
+            None => NextAction::WaitForReveal { lock_until: 0 },
+        },
+        (SwapRole::Maker, SwapPhase::Revealed) => NextAction::SweepMonero,
+        (SwapRole::Taker, SwapPhase::Created | SwapPhase::Deployed | SwapPhase::Deposited) => {
+            NextAction::RevealSecret
+        }
+        (SwapRole::Taker, SwapPhase::Revealed) => match chain {
+            Some(status) => NextAction::ClaimTokens {
+                claimable_after: status.claimable_after,
+            },
+            None => NextAction::ClaimTokens { claimable_after: 0 },
+        },
+    };
+
+    Reconciliation {
+        phase,
+        local_was_stale: phase != local,
+        next_action,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(secret_revealed: bool, unlocked: bool) -> AtomicLockStatus {
+        AtomicLockStatus {
+            secret_revealed,
+            unlocked,
+            lock_until: 10_000,
+            claimable_after: if secret_revealed { 5_000 } else { 0 },
+        }
+    }
+
+    #[test]
+    fn test_stale_local_state_detects_reveal() {
+        let r = reconcile(
+            SwapRole::Maker,
+            SwapPhase::Deposited,
+            Some(&status(true, false)),
+            1_000,
+        );
+        assert_eq!(r.phase, SwapPhase::Revealed);
+        assert!(r.local_was_stale);
+        assert_eq!(r.next_action, NextAction::SweepMonero);
+    }
+
+    #[test]
+    fn test_expired_timelock_recommends_refund() {
+        let r = reconcile(
+            SwapRole::Maker,
+            SwapPhase::Deposited,
+            Some(&status(false, false)),
+            10_000,
+        );
+        assert_eq!(r.phase, SwapPhase::Deposited);
+        assert!(!r.local_was_stale);
+        assert_eq!(r.next_action, NextAction::Refund);
+    }
+
+    #[test]
+    fn test_waiting_before_timelock() {
+        let r = reconcile(
+            SwapRole::Maker,
+            SwapPhase::Deposited,
+            Some(&status(false, false)),
+            9_999,
+        );
+        assert_eq!(
+            r.next_action,
+            NextAction::WaitForReveal { lock_until: 10_000 }
+        );
+    }
+
+    #[test]
+    fn test_refunded_on_chain() {
+        let r = reconcile(
+            SwapRole::Maker,
+            SwapPhase::Deposited,
+            Some(&status(false, true)),
+            20_000,
+        );
+        assert_eq!(r.phase, SwapPhase::Refunded);
+        assert_eq!(r.next_action, NextAction::Done);
+    }
+
+    #[test]
+    fn test_created_with_contract_is_deployed() {
+        let r = reconcile(
+            SwapRole::Maker,
+            SwapPhase::Created,
+            Some(&status(false, false)),
+            0,
+        );
+        assert_eq!(r.phase, SwapPhase::Deployed);
+        assert_eq!(r.next_action, NextAction::Deposit);
+    }
+
+    #[test]
+    fn test_taker_claims_after_reveal() {
+        let r = reconcile(
+            SwapRole::Taker,
+            SwapPhase::Deposited,
+            Some(&status(true, false)),
+            1_000,
+        );
+        assert_eq!(
+            r.next_action,
+            NextAction::ClaimTokens {
+                claimable_after: 5_000
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_contract_keeps_local_phase() {
+        let r = reconcile(SwapRole::Maker, SwapPhase::Created, None, 0);
+        assert_eq!(r.phase, SwapPhase::Created);
+        assert_eq!(r.next_action, NextAction::DeployContract);
+    }
+}
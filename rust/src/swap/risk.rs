@@ -0,0 +1,185 @@
+//! Taker-side counterparty risk scoring.
+//!
+//! Before engaging with a maker the taker looks up the maker's history in
+//! the swap store (`SwapStore::counterparty_history`) and asks the engine
+//! whether to go ahead. The score is a smoothed failure rate: prior
+//! pseudo-counts make an unknown maker score in the middle, each completed
+//! swap pulls it down, and aborts and timeouts push it up. Timeouts weigh
+//! more than aborts because they leave the taker's funds locked until the
+//! timelock expires.
+//!
+//! Unknown makers are not rejected outright, but their swaps are capped at
+//! `first_swap_limit_piconero` until they have completed `trusted_after`
+//! swaps.
+
+use serde::{Deserialize, Serialize};
+
+use crate::store::CounterpartyHistory;
+
+/// Risk engine settings. Every field has a default, so a config file only
+/// needs the values it changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    /// Weight of one aborted swap in the failure count.
+    pub abort_weight: f64,
+    /// Weight of one timed-out swap in the failure count.
+    pub timeout_weight: f64,
+    /// Pseudo-count of successes assumed for every counterparty.
+    pub prior_successes: f64,
+    /// Pseudo-count of failures assumed for every counterparty.
+    pub prior_failures: f64,
+    /// Reject counterparties scoring at or above this (0.0 - 1.0).
+    pub reject_score: f64,
+    /// Largest swap with a counterparty that is not yet trusted.
+    pub first_swap_limit_piconero: u64,
+    /// Completed swaps after which the first-swap limit no longer applies.
+    pub trusted_after: u32,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            abort_weight: 1.0,
+            timeout_weight: 2.0,
+            prior_successes: 1.0,
+            prior_failures: 1.0,
+            reject_score: 0.7,
+            // 0.1 XMR
+            first_swap_limit_piconero: 100_000_000_000,
+            trusted_after: 3,
+        }
+    }
+}
+
+/// Outcome of evaluating an offer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskDecision {
+    Accept {
+        score: f64,
+    },
+    /// Acceptable counterparty, but the amount exceeds its current limit.
+    ExceedsLimit {
+        score: f64,
+        max_piconero: u64,
+    },
+    Reject {
+        score: f64,
+    },
+}
+
+impl RiskDecision {
+    pub fn is_accept(&self) -> bool {
+        matches!(self, RiskDecision::Accept { .. })
+    }
+
+    pub fn score(&self) -> f64 {
+        match self {
+            RiskDecision::Accept { score }
+            | RiskDecision::ExceedsLimit { score, .. }
+            | RiskDecision::Reject { score } => *score,
+        }
+    }
+}
+
+/// Scores counterparties and gates offers.
+#[derive(Debug, Clone, Default)]
+pub struct RiskEngine {
+    config: RiskConfig,
+}
+
+impl RiskEngine {
+    pub fn new(config: RiskConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &RiskConfig {
+        &self.config
+    }
+
+    /// Risk score in [0, 1]; higher is riskier.
+    pub fn score(&self, history: &CounterpartyHistory) -> f64 {
+        let c = &self.config;
+        let failures =
+            history.aborted as f64 * c.abort_weight + history.timed_out as f64 * c.timeout_weight;
+        let total = history.completed as f64 + failures + c.prior_successes + c.prior_failures;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        ((failures + c.prior_failures) / total).clamp(0.0, 1.0)
+    }
+
+    /// Largest swap allowed with this counterparty, `None` if unlimited.
+    pub fn limit(&self, history: &CounterpartyHistory) -> Option<u64> {
+        (history.completed < self.config.trusted_after)
+            .then_some(self.config.first_swap_limit_piconero)
+    }
+
+    /// Decide on an offer of `amount_piconero` from a counterparty.
+    pub fn evaluate(&self, history: &CounterpartyHistory, amount_piconero: u64) -> RiskDecision {
+        let score = self.score(history);
+        if score >= self.config.reject_score {
+            return RiskDecision::Reject { score };
+        }
+        match self.limit(history) {
+            Some(max_piconero) if amount_piconero > max_piconero => RiskDecision::ExceedsLimit {
+                score,
+                max_piconero,
+            },
+            _ => RiskDecision::Accept { score },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(completed: u32, aborted: u32, timed_out: u32) -> CounterpartyHistory {
+        CounterpartyHistory {
+            completed,
+            aborted,
+            timed_out,
+        }
+    }
+
+    #[test]
+    fn test_unknown_maker_is_limited() {
+        let engine = RiskEngine::default();
+        let unknown = history(0, 0, 0);
+        assert_eq!(engine.score(&unknown), 0.5);
+        assert!(engine.evaluate(&unknown, 50_000_000_000).is_accept());
+        assert_eq!(
+            engine.evaluate(&unknown, 500_000_000_000),
+            RiskDecision::ExceedsLimit {
+                score: 0.5,
+                max_piconero: 100_000_000_000
+            }
+        );
+
+        let trusted = history(3, 0, 0);
+        assert!(engine.score(&trusted) < 0.25);
+        assert!(engine.evaluate(&trusted, 10_000_000_000_000).is_accept());
+    }
+
+    #[test]
+    fn test_failures_raise_score() {
+        let engine = RiskEngine::default();
+        // One timeout from an unknown maker: (2 + 1) / (2 + 2) = 0.75
+        assert!(matches!(
+            engine.evaluate(&history(0, 0, 1), 1),
+            RiskDecision::Reject { .. }
+        ));
+        // One abort is tolerated, but weighs less than a timeout
+        assert!(engine.evaluate(&history(0, 1, 0), 1).is_accept());
+        assert!(engine.score(&history(5, 1, 0)) < engine.score(&history(5, 0, 1)));
+        // A long good record absorbs an occasional timeout
+        assert!(engine.evaluate(&history(10, 0, 1), 1).is_accept());
+
+        let strict = RiskEngine::new(RiskConfig {
+            reject_score: 0.5,
+            ..RiskConfig::default()
+        });
+        assert!(!strict.evaluate(&history(0, 0, 0), 1).is_accept());
+    }
+}
@@ -0,0 +1,160 @@
+//! Machine-readable protocol specification (`spec/protocol.json`).
+//!
+//! The allowed `SwapPhase` transitions and their triggers live in one JSON
+//! file shared with the watchtower's anomaly detector, so the two cannot
+//! drift apart. The file also carries conformance traces that both sides
+//! replay in their tests.
+//!
+//! Triggers are either on-chain events (`ContractDeployed`, `SecretRevealed`,
+//! `TokensClaimed`, `Refunded`) or local actions (`deposit`) that emit no
+//! event; the latter are marked `observable: false`.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::SwapPhase;
+
+/// Raw contents of `spec/protocol.json`.
+pub const PROTOCOL_SPEC_JSON: &str = include_str!("../../../spec/protocol.json");
+
+/// One allowed phase transition.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Transition {
+    pub from: SwapPhase,
+    pub to: SwapPhase,
+    /// Event or action name that causes the transition.
+    pub on: String,
+    /// Whether the trigger is an on-chain event a watcher can see.
+    pub observable: bool,
+}
+
+/// A trace every implementation must accept or reject.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ConformanceCase {
+    pub name: String,
+    pub trace: Vec<String>,
+    pub valid: bool,
+    /// Expected phase after a valid trace.
+    #[serde(default, rename = "final")]
+    pub final_phase: Option<SwapPhase>,
+    /// Verdict for an observer that only sees observable triggers.
+    #[serde(default)]
+    pub watchtower_valid: Option<bool>,
+}
+
+/// The protocol state machine.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProtocolSpec {
+    pub version: u32,
+    pub phases: Vec<SwapPhase>,
+    pub initial: SwapPhase,
+    pub terminal: Vec<SwapPhase>,
+    pub transitions: Vec<Transition>,
+    #[serde(default)]
+    pub conformance: Vec<ConformanceCase>,
+}
+
+/// A trigger that the spec does not allow in the current phase.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("'{trigger}' is not allowed in phase {phase:?} (step {step})")]
+pub struct SpecViolation {
+    pub phase: SwapPhase,
+    pub trigger: String,
+    pub step: usize,
+}
+
+impl ProtocolSpec {
+    /// Phase reached from `from` on `trigger`, if allowed.
+    pub fn next(&self, from: SwapPhase, trigger: &str) -> Option<SwapPhase> {
+        self.transitions
+            .iter()
+            .find(|t| t.from == from && t.on == trigger)
+            .map(|t| t.to)
+    }
+
+    /// Whether some trigger moves `from` directly to `to`.
+    pub fn allows(&self, from: SwapPhase, to: SwapPhase) -> bool {
+        self.transitions
+            .iter()
+            .any(|t| t.from == from && t.to == to)
+    }
+
+    /// Replay `trace` from the initial phase.
+    pub fn run<S: AsRef<str>>(&self, trace: &[S]) -> Result<SwapPhase, SpecViolation> {
+        trace
+            .iter()
+            .enumerate()
+            .try_fold(self.initial, |phase, (step, trigger)| {
+                self.next(phase, trigger.as_ref())
+                    .ok_or_else(|| SpecViolation {
+                        phase,
+                        trigger: trigger.as_ref().to_string(),
+                        step,
+                    })
+            })
+    }
+}
+
+/// The embedded protocol spec (parsed once).
+pub fn protocol_spec() -> &'static ProtocolSpec {
+    static SPEC: OnceLock<ProtocolSpec> = OnceLock::new();
+    SPEC.get_or_init(|| {
+        serde_json::from_str(PROTOCOL_SPEC_JSON).expect("spec/protocol.json is valid")
+    })
+}
+
+impl SwapPhase {
+    /// Whether the protocol spec allows moving from `self` to `next`.
+    pub fn can_transition_to(self, next: SwapPhase) -> bool {
+        protocol_spec().allows(self, next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conformance_traces() {
+        let spec = protocol_spec();
+        assert!(!spec.conformance.is_empty());
+        for case in &spec.conformance {
+            let result = spec.run(&case.trace);
+            assert_eq!(
+                result.is_ok(),
+                case.valid,
+                "case '{}': {:?}",
+                case.name,
+                result
+            );
+            if let (Ok(phase), Some(expected)) = (result, case.final_phase) {
+                assert_eq!(phase, expected, "case '{}'", case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_terminal_phases_match_code() {
+        let spec = protocol_spec();
+        for phase in &spec.phases {
+            assert_eq!(
+                phase.is_terminal(),
+                spec.terminal.contains(phase),
+                "{:?}",
+                phase
+            );
+            if phase.is_terminal() {
+                assert!(spec.transitions.iter().all(|t| t.from != *phase));
+            }
+        }
+    }
+
+    #[test]
+    fn test_can_transition_to() {
+        assert!(SwapPhase::Deposited.can_transition_to(SwapPhase::Revealed));
+        assert!(!SwapPhase::Revealed.can_transition_to(SwapPhase::Refunded));
+        assert!(!SwapPhase::Finalized.can_transition_to(SwapPhase::Created));
+    }
+}
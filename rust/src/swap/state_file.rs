@@ -0,0 +1,243 @@
+//! Swap state file, optionally encrypted with a passphrase.
+//!
+//! The maker's state file holds the secret scalar `t`; anyone who reads it
+//! before the reveal can take both sides of the swap. With a passphrase the
+//! JSON is sealed with ChaCha20-Poly1305 under a key derived by Argon2id:
+//!
+//! ```text
+//! {"encryption": "chacha20poly1305-argon2id", "kdf": {...}, "salt": hex,
+//!  "nonce": hex, "ciphertext": hex}
+//! ```
+//!
+//! Files without the `encryption` field are plaintext state, so existing
+//! `swap_state.json` files keep working. A wrong passphrase and a tampered
+//! file are indistinguishable and both fail authentication.
+
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Value of the `encryption` field.
+pub const ENCRYPTION_SCHEME: &str = "chacha20poly1305-argon2id";
+
+/// State file errors.
+#[derive(Debug, Error)]
+pub enum StateFileError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid state file: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error("state file is encrypted; a passphrase is required")]
+    PassphraseRequired,
+
+    #[error("wrong passphrase or corrupted state file")]
+    DecryptionFailed,
+
+    #[error("unsupported encryption scheme {0:?}")]
+    UnsupportedScheme(String),
+
+    #[error("invalid encrypted state file: {0}")]
+    InvalidEnvelope(String),
+}
+
+/// Argon2id cost parameters, stored with each file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory in KiB.
+    pub m_cost: u32,
+    /// Passes.
+    pub t_cost: u32,
+    /// Lanes.
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// OWASP minimum for Argon2id: 19 MiB, 2 passes, 1 lane.
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+/// On-disk form of an encrypted state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Envelope {
+    encryption: String,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Serialize `state` to `path`, encrypted if a passphrase is given.
+///
+/// On Unix the file is created with mode 0600.
+pub fn write_state<T: Serialize>(
+    path: impl AsRef<Path>,
+    state: &T,
+    passphrase: Option<&str>,
+) -> Result<(), StateFileError> {
+    let json = Zeroizing::new(serde_json::to_vec_pretty(state)?);
+    let contents = match passphrase {
+        Some(passphrase) => {
+            serde_json::to_vec_pretty(&encrypt(&json, passphrase, KdfParams::default())?)?
+        }
+        None => json.to_vec(),
+    };
+    write_private(path.as_ref(), &contents)?;
+    Ok(())
+}
+
+/// Read a state written by `write_state`, plaintext or encrypted.
+pub fn read_state<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    passphrase: Option<&str>,
+) -> Result<T, StateFileError> {
+    let contents = Zeroizing::new(std::fs::read(path)?);
+    let value: Value = serde_json::from_slice(&contents)?;
+    if value.get("encryption").is_none() {
+        return Ok(serde_json::from_value(value)?);
+    }
+
+    let envelope: Envelope = serde_json::from_value(value)?;
+    let passphrase = passphrase.ok_or(StateFileError::PassphraseRequired)?;
+    let plaintext = decrypt(&envelope, passphrase)?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Whether the file at `path` is an encrypted state.
+pub fn is_encrypted(path: impl AsRef<Path>) -> Result<bool, StateFileError> {
+    let value: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    Ok(value.get("encryption").is_some())
+}
+
+/// Read a passphrase from a file, dropping the trailing newline.
+pub fn read_passphrase_file(path: impl AsRef<Path>) -> Result<Zeroizing<String>, StateFileError> {
+    let mut passphrase = Zeroizing::new(std::fs::read_to_string(path)?);
+    let len = passphrase.trim_end_matches(['\r', '\n']).len();
+    passphrase.truncate(len);
+    Ok(passphrase)
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str, kdf: KdfParams) -> Result<Envelope, StateFileError> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, kdf)?;
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| StateFileError::InvalidEnvelope("encryption failed".to_string()))?;
+
+    Ok(Envelope {
+        encryption: ENCRYPTION_SCHEME.to_string(),
+        kdf,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn decrypt(envelope: &Envelope, passphrase: &str) -> Result<Zeroizing<Vec<u8>>, StateFileError> {
+    if envelope.encryption != ENCRYPTION_SCHEME {
+        return Err(StateFileError::UnsupportedScheme(
+            envelope.encryption.clone(),
+        ));
+    }
+    let salt = decode_hex("salt", &envelope.salt)?;
+    let nonce = decode_hex("nonce", &envelope.nonce)?;
+    let ciphertext = decode_hex("ciphertext", &envelope.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(StateFileError::InvalidEnvelope(
+            "nonce must be 12 bytes".to_string(),
+        ));
+    }
+
+    let key = derive_key(passphrase, &salt, envelope.kdf)?;
+    ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map(Zeroizing::new)
+        .map_err(|_| StateFileError::DecryptionFailed)
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    kdf: KdfParams,
+) -> Result<Zeroizing<[u8; 32]>, StateFileError> {
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| StateFileError::InvalidEnvelope(format!("KDF parameters: {}", e)))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| StateFileError::InvalidEnvelope(format!("key derivation: {}", e)))?;
+    Ok(key)
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, StateFileError> {
+    hex::decode(value).map_err(|e| StateFileError::InvalidEnvelope(format!("{}: {}", field, e)))
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let path = std::env::temp_dir().join(format!("swap-state-{}.json", std::process::id()));
+        let state = json!({"swap_id": "ab12", "secret_hex": "0badc0de"});
+
+        write_state(&path, &state, Some("correct horse")).unwrap();
+        assert!(is_encrypted(&path).unwrap());
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("0badc0de"));
+
+        let read: Value = read_state(&path, Some("correct horse")).unwrap();
+        assert_eq!(read, state);
+        assert!(matches!(
+            read_state::<Value>(&path, Some("wrong")),
+            Err(StateFileError::DecryptionFailed)
+        ));
+        assert!(matches!(
+            read_state::<Value>(&path, None),
+            Err(StateFileError::PassphraseRequired)
+        ));
+
+        // Plaintext files still load, with or without a passphrase
+        write_state(&path, &state, None).unwrap();
+        assert!(!is_encrypted(&path).unwrap());
+        let read: Value = read_state(&path, Some("ignored")).unwrap();
+        assert_eq!(read, state);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,473 @@
+//! Taker-side check of a deployed AtomicLock's DLEQ proof.
+//!
+//! The constructor recomputes the challenge but only compares its low 128
+//! bits, and the MSM results `s·G - c·T` / `s·Y - c·U` are never compared
+//! with R1/R2 (`_verify_dleq_proof` in lib.cairo). A successful deployment
+//! therefore does not show that the adaptor point T is bound to the hashlock.
+//! Before depositing, the taker decodes the constructor calldata, rebuilds
+//! the proof and runs the full [`verify_dleq_proof`].
+//!
+//! The calldata carries the challenge and response as felts. Makers using
+//! `gen-hints` pass only their low 128 bits, which cannot be verified on
+//! their own; the maker then hands over the full proof (`DleqProof::to_json`)
+//! and it must match the calldata.
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use thiserror::Error;
+
+use crate::dleq::{verify_dleq_proof, DleqError, DleqProof};
+use crate::encoding::{decode_point, decode_scalar};
+use crate::hints::{dleq_msm_hints, fake_glv_hint, sqrt_hint, truncate_128, HintError};
+
+/// Constructor calldata length: 86 felts for the current AtomicLock.
+pub const CONSTRUCTOR_CALLDATA_LEN: usize = 86;
+
+/// Contract verification errors.
+#[derive(Debug, Error, PartialEq)]
+pub enum VerifyError {
+    #[error("invalid constructor calldata: {0}")]
+    Calldata(String),
+
+    #[error(
+        "calldata only carries 128-bit challenge/response; the maker's full proof is required"
+    )]
+    FullProofRequired,
+
+    #[error("full proof does not match the deployed contract: {0} differs")]
+    ProofMismatch(&'static str),
+
+    #[error("DLEQ proof rejected: {0}")]
+    Dleq(#[from] DleqError),
+}
+
+/// The DLEQ-relevant constructor arguments of an AtomicLock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstructorArgs {
+    /// SHA-256 hashlock as 8 big-endian u32 words.
+    pub hash_words: [u32; 8],
+    pub lock_until: u64,
+    pub token: String,
+    pub amount_low: u128,
+    pub amount_high: u128,
+    /// Compressed Edwards points (u256 calldata, little-endian bytes).
+    pub adaptor_point: [u8; 32],
+    pub second_point: [u8; 32],
+    pub r1: [u8; 32],
+    pub r2: [u8; 32],
+    /// Challenge and response felts as little-endian scalar bytes.
+    pub challenge: [u8; 32],
+    pub response: [u8; 32],
+}
+
+impl ConstructorArgs {
+    /// Decode the constructor calldata (hex felts, serialized arguments).
+    pub fn decode(calldata: &[String]) -> Result<Self, VerifyError> {
+        if calldata.len() != CONSTRUCTOR_CALLDATA_LEN {
+            return Err(VerifyError::Calldata(format!(
+                "expected {} felts, got {}",
+                CONSTRUCTOR_CALLDATA_LEN,
+                calldata.len()
+            )));
+        }
+        let mut felts = Felts { calldata, pos: 0 };
+
+        if felts.u128()? != 8 {
+            return Err(VerifyError::Calldata(
+                "hashlock must be 8 words".to_string(),
+            ));
+        }
+        let mut hash_words = [0u32; 8];
+        for word in &mut hash_words {
+            *word = u32::try_from(felts.u128()?)
+                .map_err(|_| VerifyError::Calldata("hashlock word exceeds u32".to_string()))?;
+        }
+        let lock_until = u64::try_from(felts.u128()?)
+            .map_err(|_| VerifyError::Calldata("lock_until exceeds u64".to_string()))?;
+        let token = felts.felt()?.to_string();
+        let amount_low = felts.u128()?;
+        let amount_high = felts.u128()?;
+        let adaptor_point = felts.u256()?;
+        felts.skip(2); // adaptor_point_sqrt_hint
+        let second_point = felts.u256()?;
+        felts.skip(2); // dleq_second_point_sqrt_hint
+        let challenge = felts.scalar()?;
+        let response = felts.scalar()?;
+        for _ in 0..5 {
+            // fake_glv_hint and the four DLEQ MSM hints (Span<felt252>)
+            let len = felts.u128()? as usize;
+            felts.skip(len);
+        }
+        let r1 = felts.u256()?;
+        felts.skip(2); // dleq_r1_sqrt_hint
+        let r2 = felts.u256()?;
+        felts.skip(2); // dleq_r2_sqrt_hint
+        if felts.pos != calldata.len() {
+            return Err(VerifyError::Calldata("unexpected hint lengths".to_string()));
+        }
+
+        Ok(Self {
+            hash_words,
+            lock_until,
+            token,
+            amount_low,
+            amount_high,
+            adaptor_point,
+            second_point,
+            r1,
+            r2,
+            challenge,
+            response,
+        })
+    }
+
+    /// The 32-byte SHA-256 hashlock.
+    pub fn hashlock(&self) -> [u8; 32] {
+        let mut hashlock = [0u8; 32];
+        for (chunk, word) in hashlock.chunks_exact_mut(4).zip(self.hash_words) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        hashlock
+    }
+
+    /// Whether the challenge and response were truncated to 128 bits.
+    pub fn is_truncated(&self) -> bool {
+        self.challenge[16..] == [0u8; 16] && self.response[16..] == [0u8; 16]
+    }
+
+    /// Rebuild the DLEQ proof, taking the scalars from `full` if given.
+    pub fn proof(&self, full: Option<&DleqProof>) -> Result<DleqProof, VerifyError> {
+        let from_calldata = |bytes: [u8; 32], name| {
+            decode_point(bytes).map_err(|reason| DleqError::InvalidPoint {
+                point: name,
+                reason,
+            })
+        };
+        let second_point = from_calldata(self.second_point, "U")?;
+        let r1 = from_calldata(self.r1, "R1")?;
+        let r2 = from_calldata(self.r2, "R2")?;
+
+        let full = match full {
+            Some(full) => full,
+            None if self.is_truncated() => return Err(VerifyError::FullProofRequired),
+            None => {
+                return Ok(DleqProof {
+                    second_point,
+                    challenge: decode_scalar(self.challenge).map_err(DleqError::from)?,
+                    response: decode_scalar(self.response).map_err(DleqError::from)?,
+                    r1,
+                    r2,
+                })
+            }
+        };
+
+        if full.second_point != second_point {
+            return Err(VerifyError::ProofMismatch("U"));
+        }
+        if full.r1 != r1 {
+            return Err(VerifyError::ProofMismatch("R1"));
+        }
+        if full.r2 != r2 {
+            return Err(VerifyError::ProofMismatch("R2"));
+        }
+        if !matches_calldata(
+            &full.challenge.to_bytes(),
+            &self.challenge,
+            self.is_truncated(),
+        ) {
+            return Err(VerifyError::ProofMismatch("challenge"));
+        }
+        if !matches_calldata(
+            &full.response.to_bytes(),
+            &self.response,
+            self.is_truncated(),
+        ) {
+            return Err(VerifyError::ProofMismatch("response"));
+        }
+        Ok(full.clone())
+    }
+
+    /// Verify the proof binds the adaptor point to the hashlock; returns T.
+    pub fn verify(&self, full: Option<&DleqProof>) -> Result<EdwardsPoint, VerifyError> {
+        let adaptor_point =
+            decode_point(self.adaptor_point).map_err(|reason| DleqError::InvalidPoint {
+                point: "adaptor",
+                reason,
+            })?;
+        let proof = self.proof(full)?;
+        verify_dleq_proof(&proof, &adaptor_point, &self.hashlock())?;
+        Ok(adaptor_point)
+    }
+}
+
+/// Encode the constructor calldata of an AtomicLock (the inverse of
+/// [`ConstructorArgs::decode`]).
+///
+/// `secret` is the adaptor scalar t with `proof` binding `t·G` to
+/// `hashlock`; the fake-GLV and MSM hints are derived from them. The
+/// challenge and response are passed as their low 128 bits, as the contract
+/// compares them, so takers need the full proof to verify the deployment.
+pub fn encode_constructor_calldata(
+    hashlock: &[u8; 32],
+    lock_until: u64,
+    token: &str,
+    amount: u128,
+    secret: &Scalar,
+    proof: &DleqProof,
+) -> Result<Vec<String>, HintError> {
+    let adaptor_point = ED25519_BASEPOINT_POINT * secret;
+
+    let mut calldata = vec!["0x8".to_string()];
+    for word in hashlock.chunks_exact(4) {
+        calldata.push(format!(
+            "{:#x}",
+            u32::from_be_bytes(word.try_into().expect("4 bytes"))
+        ));
+    }
+    calldata.push(format!("{:#x}", lock_until));
+    calldata.push(token.to_string());
+    calldata.extend([format!("{:#x}", amount), "0x0".to_string()]);
+    push_point(&mut calldata, &adaptor_point);
+    push_point(&mut calldata, &proof.second_point);
+    calldata.push(low_u128_felt(&proof.challenge));
+    calldata.push(low_u128_felt(&proof.response));
+    let msm = dleq_msm_hints(proof, &adaptor_point)?;
+    for hint in [
+        fake_glv_hint(&ED25519_BASEPOINT_POINT, secret)?,
+        msm.s_hint_for_g,
+        msm.s_hint_for_y,
+        msm.c_neg_hint_for_t,
+        msm.c_neg_hint_for_u,
+    ] {
+        let felts = hint.to_hex_felts();
+        calldata.push(format!("{:#x}", felts.len()));
+        calldata.extend(felts);
+    }
+    push_point(&mut calldata, &proof.r1);
+    push_point(&mut calldata, &proof.r2);
+    Ok(calldata)
+}
+
+/// Compressed point and sqrt hint as two Cairo u256 (little-endian bytes).
+fn push_point(calldata: &mut Vec<String>, point: &EdwardsPoint) {
+    for bytes in [point.compress().to_bytes(), sqrt_hint(point)] {
+        let low = u128::from_le_bytes(bytes[..16].try_into().expect("16 bytes"));
+        let high = u128::from_le_bytes(bytes[16..].try_into().expect("16 bytes"));
+        calldata.extend([format!("{:#x}", low), format!("{:#x}", high)]);
+    }
+}
+
+fn low_u128_felt(scalar: &Scalar) -> String {
+    let low = truncate_128(scalar).to_bytes();
+    format!(
+        "{:#x}",
+        u128::from_le_bytes(low[..16].try_into().expect("16 bytes"))
+    )
+}
+
+fn matches_calldata(full: &[u8; 32], calldata: &[u8; 32], truncated: bool) -> bool {
+    if truncated {
+        full[..16] == calldata[..16]
+    } else {
+        full == calldata
+    }
+}
+
+/// Cursor over hex felts.
+struct Felts<'a> {
+    calldata: &'a [String],
+    pos: usize,
+}
+
+impl<'a> Felts<'a> {
+    fn felt(&mut self) -> Result<&'a str, VerifyError> {
+        let felt = self
+            .calldata
+            .get(self.pos)
+            .ok_or_else(|| VerifyError::Calldata("calldata ends early".to_string()))?;
+        self.pos += 1;
+        Ok(felt)
+    }
+
+    fn skip(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    /// Felt as 32 big-endian bytes.
+    fn bytes(&mut self) -> Result<[u8; 32], VerifyError> {
+        let felt = self.felt()?;
+        let digits = felt
+            .strip_prefix("0x")
+            .ok_or_else(|| VerifyError::Calldata(format!("felt {} is not hex", felt)))?;
+        if digits.len() > 64 {
+            return Err(VerifyError::Calldata(format!("felt {} is too long", felt)));
+        }
+        let padded = format!("{:0>64}", digits);
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(&padded, &mut bytes)
+            .map_err(|e| VerifyError::Calldata(format!("felt {}: {}", felt, e)))?;
+        Ok(bytes)
+    }
+
+    fn u128(&mut self) -> Result<u128, VerifyError> {
+        let bytes = self.bytes()?;
+        if bytes[..16] != [0u8; 16] {
+            return Err(VerifyError::Calldata("felt exceeds u128".to_string()));
+        }
+        Ok(u128::from_be_bytes(
+            bytes[16..].try_into().expect("16 bytes"),
+        ))
+    }
+
+    /// `u256 { low, high }` as little-endian bytes (compressed points).
+    fn u256(&mut self) -> Result<[u8; 32], VerifyError> {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&self.u128()?.to_le_bytes());
+        bytes[16..].copy_from_slice(&self.u128()?.to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// felt252 scalar as little-endian bytes.
+    fn scalar(&mut self) -> Result<[u8; 32], VerifyError> {
+        let mut bytes = self.bytes()?;
+        bytes.reverse();
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dleq::generate_dleq_proof;
+    use sha2::{Digest, Sha256};
+    use zeroize::Zeroizing;
+
+    fn test_proof() -> (DleqProof, EdwardsPoint, [u8; 32]) {
+        let secret_bytes = [0x42u8; 32];
+        let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+        let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+        let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+        let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+            .expect("Proof generation should succeed");
+        (proof, adaptor_point, hashlock)
+    }
+
+    fn u256_felts(bytes: [u8; 32]) -> [String; 2] {
+        let low = u128::from_le_bytes(bytes[..16].try_into().unwrap());
+        let high = u128::from_le_bytes(bytes[16..].try_into().unwrap());
+        [format!("{:#x}", low), format!("{:#x}", high)]
+    }
+
+    fn scalar_felt(scalar: &Scalar) -> String {
+        let mut bytes = scalar.to_bytes();
+        bytes.reverse();
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    /// Constructor calldata with dummy sqrt and MSM hints.
+    fn calldata(
+        proof: &DleqProof,
+        adaptor_point: &EdwardsPoint,
+        hashlock: &[u8; 32],
+        c: &Scalar,
+        s: &Scalar,
+    ) -> Vec<String> {
+        let mut felts = vec!["0x8".to_string()];
+        for word in hashlock.chunks_exact(4) {
+            felts.push(format!(
+                "{:#x}",
+                u32::from_be_bytes(word.try_into().unwrap())
+            ));
+        }
+        felts.extend(["0x65f0a1b0", "0x4718", "0x64", "0x0"].map(String::from));
+        felts.extend(u256_felts(adaptor_point.compress().to_bytes()));
+        felts.extend(["0x1", "0x0"].map(String::from));
+        felts.extend(u256_felts(proof.second_point.compress().to_bytes()));
+        felts.extend(["0x1", "0x0"].map(String::from));
+        felts.push(scalar_felt(c));
+        felts.push(scalar_felt(s));
+        for _ in 0..5 {
+            felts.push("0xa".to_string());
+            felts.extend(vec!["0x1".to_string(); 10]);
+        }
+        felts.extend(u256_felts(proof.r1.compress().to_bytes()));
+        felts.extend(["0x1", "0x0"].map(String::from));
+        felts.extend(u256_felts(proof.r2.compress().to_bytes()));
+        felts.extend(["0x1", "0x0"].map(String::from));
+        felts
+    }
+
+    #[test]
+    fn test_verify_full_scalars_from_calldata() {
+        let (proof, adaptor_point, hashlock) = test_proof();
+        let felts = calldata(
+            &proof,
+            &adaptor_point,
+            &hashlock,
+            &proof.challenge,
+            &proof.response,
+        );
+        let args = ConstructorArgs::decode(&felts).unwrap();
+        assert_eq!(args.hashlock(), hashlock);
+        assert_eq!(args.lock_until, 0x65f0a1b0);
+        assert!(!args.is_truncated());
+        assert_eq!(args.verify(None).unwrap(), adaptor_point);
+
+        // A proof for another hashlock is rejected
+        let mut other = args.clone();
+        other.hash_words[0] ^= 1;
+        assert_eq!(
+            other.verify(None),
+            Err(VerifyError::Dleq(DleqError::ChallengeMismatch))
+        );
+    }
+
+    #[test]
+    fn test_verify_truncated_scalars_needs_full_proof() {
+        let (proof, adaptor_point, hashlock) = test_proof();
+        let felts = calldata(
+            &proof,
+            &adaptor_point,
+            &hashlock,
+            &truncate_128(&proof.challenge),
+            &truncate_128(&proof.response),
+        );
+        let args = ConstructorArgs::decode(&felts).unwrap();
+        assert!(args.is_truncated());
+        assert_eq!(args.verify(None), Err(VerifyError::FullProofRequired));
+        assert_eq!(args.verify(Some(&proof)).unwrap(), adaptor_point);
+
+        let mut forged = proof.clone();
+        forged.response += Scalar::ONE;
+        assert_eq!(
+            args.verify(Some(&forged)),
+            Err(VerifyError::ProofMismatch("response"))
+        );
+    }
+
+    #[test]
+    fn test_encoded_calldata_round_trips() {
+        let (proof, adaptor_point, hashlock) = test_proof();
+        let secret = Scalar::from_bytes_mod_order([0x42u8; 32]);
+        let felts =
+            encode_constructor_calldata(&hashlock, 1_700_003_600, "0x4718", 100, &secret, &proof)
+                .unwrap();
+        assert_eq!(felts.len(), CONSTRUCTOR_CALLDATA_LEN);
+
+        let args = ConstructorArgs::decode(&felts).unwrap();
+        assert_eq!(args.lock_until, 1_700_003_600);
+        assert_eq!(args.token, "0x4718");
+        assert_eq!((args.amount_low, args.amount_high), (100, 0));
+        assert!(args.is_truncated());
+        assert_eq!(args.verify(Some(&proof)).unwrap(), adaptor_point);
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_calldata() {
+        assert!(matches!(
+            ConstructorArgs::decode(&["0x8".to_string()]),
+            Err(VerifyError::Calldata(_))
+        ));
+    }
+}
@@ -0,0 +1,417 @@
+//! Versioned protocol test vectors for independent implementations.
+//!
+//! `vectors/v1/*.json` at the repository root holds one [`ProtocolVector`]
+//! per file: the inputs of a swap (secret, lock terms, Monero base key and
+//! message) and everything this crate derives from them — hashlock, adaptor
+//! point, DLEQ proof, adaptor signature, the AtomicLock constructor and
+//! `verify_and_unlock` calldata, and the values the Cairo contract is
+//! expected to compute. A JS taker or Python tool passes if it reproduces
+//! every field from `inputs`.
+//!
+//! The `verify-vectors` binary checks the shipped files against this crate
+//! (`--regenerate` rewrites them). Any change to an output is a protocol
+//! change and needs a new [`VECTORS_VERSION`] directory instead of an edit.
+//!
+//! Vectors use the default second generator; builds with
+//! `h2c-second-generator` do not reproduce them.
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::adaptor::{
+    create_adaptor_signature, finalize_signature, verify_signature, AdaptorSignature,
+};
+use crate::cairo_serde::{byte_array_from_calldata, byte_array_to_calldata};
+use crate::dleq::{
+    generate_dleq_proof, get_second_generator, verify_dleq_proof, DleqError, DleqProof,
+    DleqProofSerialized,
+};
+use crate::encoding::decode_point;
+use crate::hints::{fake_glv_hint, truncate_128, HintError};
+use crate::swap::verify::{encode_constructor_calldata, ConstructorArgs, VerifyError};
+
+/// Format version; vectors live in `vectors/v{VECTORS_VERSION}/`.
+pub const VECTORS_VERSION: u32 = 1;
+
+/// Test vector errors.
+#[derive(Debug, Error)]
+pub enum VectorError {
+    #[error("unsupported vector version {0} (expected {VECTORS_VERSION})")]
+    Version(u32),
+
+    #[error("invalid input {0}")]
+    Input(&'static str),
+
+    #[error("{0} differs from this implementation")]
+    Mismatch(String),
+
+    #[error("DLEQ proof rejected: {0}")]
+    Dleq(#[from] DleqError),
+
+    #[error("hint generation failed: {0}")]
+    Hint(#[from] HintError),
+
+    #[error("constructor calldata rejected: {0}")]
+    Calldata(#[from] VerifyError),
+
+    #[error("{0}")]
+    Check(&'static str),
+}
+
+/// Inputs of a vector (hex strings without `0x` unless a felt).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorInputs {
+    /// Raw 32-byte secret revealed on Starknet (hashlock preimage)
+    pub secret: String,
+    pub lock_until: u64,
+    /// ERC20 token contract (felt)
+    pub token: String,
+    /// Locked amount, low 128 bits (felt; high bits are zero)
+    pub amount: String,
+    /// Maker's Monero key share (little-endian, reduced mod ℓ)
+    pub monero_base_key: String,
+    /// Message the adaptor signature signs
+    pub monero_message: String,
+}
+
+/// DLEQ proof fields, compressed points and little-endian scalars.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorDleq {
+    pub second_generator: String,
+    pub second_point: String,
+    pub challenge: String,
+    pub response: String,
+    pub r1: String,
+    pub r2: String,
+}
+
+/// Adaptor signature over `monero_message` with adaptor point T.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorAdaptorSignature {
+    /// `T || partial_sig || R` (96 bytes)
+    pub signature: String,
+    /// `SHA-256(message || R || T) mod ℓ`
+    pub challenge: String,
+    /// `base_key·G + T`
+    pub public_key: String,
+    /// `partial_sig + challenge·t`
+    pub finalized: String,
+}
+
+/// Values the AtomicLock contract computes for this vector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorCairo {
+    /// `get_target_hash()`: SHA-256 of the secret as 8 big-endian u32 words
+    pub target_hash: [u32; 8],
+    /// Challenge and response as the contract compares them (low 128 bits)
+    pub challenge_low: String,
+    pub response_low: String,
+    /// Fake-GLV hint for `t·G` (the `fake_glv_hint` constructor argument)
+    pub fake_glv_hint: [String; 10],
+    /// Whether `verify_and_unlock(unlock_calldata)` succeeds
+    pub unlock_succeeds: bool,
+}
+
+/// One conformance vector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolVector {
+    pub version: u32,
+    pub name: String,
+    pub inputs: VectorInputs,
+    /// `secret mod ℓ` (little-endian)
+    pub secret_scalar: String,
+    /// `SHA-256(secret)` of the raw bytes, not of the reduced scalar
+    pub hashlock: String,
+    /// `T = (secret mod ℓ)·G`, compressed
+    pub adaptor_point: String,
+    pub dleq: VectorDleq,
+    pub adaptor_signature: VectorAdaptorSignature,
+    /// AtomicLock constructor calldata (hex felts)
+    pub constructor_calldata: Vec<String>,
+    /// `verify_and_unlock` calldata: the secret as a Cairo ByteArray
+    pub unlock_calldata: Vec<String>,
+    pub cairo: VectorCairo,
+}
+
+/// The named inputs shipped in `vectors/v1/`.
+pub fn default_inputs() -> Vec<(&'static str, VectorInputs)> {
+    let inputs = |secret: [u8; 32], base_key: u8| VectorInputs {
+        secret: hex::encode(secret),
+        lock_until: 1_700_003_600,
+        token: "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d".to_string(),
+        amount: "0xde0b6b3a7640000".to_string(),
+        monero_base_key: hex::encode([base_key; 32]),
+        monero_message: hex::encode(b"Monero stagenet transaction for atomic swap"),
+    };
+    let mut small = [0u8; 32];
+    small[0] = 1;
+    vec![
+        // Canonical vector of canonical_test_vectors.json (not reduced mod ℓ)
+        ("canonical", inputs([0x12; 32], 0x42)),
+        // Already a canonical scalar: raw bytes and scalar bytes agree
+        ("canonical-scalar", inputs([0x07; 32], 0x24)),
+        // Smallest secret
+        ("small-secret", inputs(small, 0x11)),
+        // All-ones secret: the hashlock uses the raw bytes, T the reduced scalar
+        ("reduced-secret", inputs([0xff; 32], 0x33)),
+    ]
+}
+
+/// Derive every output of a vector from `inputs`.
+pub fn generate(name: &str, inputs: &VectorInputs) -> Result<ProtocolVector, VectorError> {
+    let secret_bytes = bytes32(&inputs.secret, "secret")?;
+    let base_key =
+        Scalar::from_bytes_mod_order(bytes32(&inputs.monero_base_key, "monero_base_key")?);
+    let message =
+        hex::decode(&inputs.monero_message).map_err(|_| VectorError::Input("monero_message"))?;
+    let amount = parse_u128(&inputs.amount).ok_or(VectorError::Input("amount"))?;
+
+    let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+    let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+    let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+    let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)?;
+
+    let adaptor_sig = create_adaptor_signature(&base_key, &adaptor_point, &message);
+    let (finalized, _) = finalize_signature(&adaptor_sig, &secret, &message);
+    let sig_challenge =
+        signature_challenge(&message, &adaptor_sig.nonce_commitment, &adaptor_point);
+    let public_key = ED25519_BASEPOINT_POINT * base_key + adaptor_point;
+
+    let target_hash: [u32; 8] = core::array::from_fn(|i| {
+        u32::from_be_bytes(hashlock[i * 4..i * 4 + 4].try_into().expect("4 bytes"))
+    });
+    let challenge_low = format!("{:#x}", low_u128(&proof.challenge));
+    let response_low = format!("{:#x}", low_u128(&proof.response));
+    let fake_glv = fake_glv_hint(&ED25519_BASEPOINT_POINT, &secret)?.to_hex_felts();
+
+    let calldata = encode_constructor_calldata(
+        &hashlock,
+        inputs.lock_until,
+        &inputs.token,
+        amount,
+        &secret,
+        &proof,
+    )?;
+
+    Ok(ProtocolVector {
+        version: VECTORS_VERSION,
+        name: name.to_string(),
+        inputs: inputs.clone(),
+        secret_scalar: hex::encode(secret.to_bytes()),
+        hashlock: hex::encode(hashlock),
+        adaptor_point: compressed(&adaptor_point),
+        dleq: VectorDleq {
+            second_generator: compressed(&get_second_generator()),
+            second_point: compressed(&proof.second_point),
+            challenge: hex::encode(proof.challenge.to_bytes()),
+            response: hex::encode(proof.response.to_bytes()),
+            r1: compressed(&proof.r1),
+            r2: compressed(&proof.r2),
+        },
+        adaptor_signature: VectorAdaptorSignature {
+            signature: hex::encode(adaptor_sig.to_bytes()),
+            challenge: hex::encode(sig_challenge.to_bytes()),
+            public_key: compressed(&public_key),
+            finalized: hex::encode(finalized.to_bytes()),
+        },
+        constructor_calldata: calldata,
+        unlock_calldata: byte_array_to_calldata(&secret_bytes),
+        cairo: VectorCairo {
+            target_hash,
+            challenge_low,
+            response_low,
+            fake_glv_hint: fake_glv,
+            unlock_succeeds: true,
+        },
+    })
+}
+
+/// Check `vector` against this implementation.
+///
+/// Every field must equal the one regenerated from `vector.inputs`; the
+/// error names the first that differs. The proof, signature and calldata are
+/// also checked on their own so a failure points at the broken step.
+pub fn verify(vector: &ProtocolVector) -> Result<(), VectorError> {
+    if vector.version != VECTORS_VERSION {
+        return Err(VectorError::Version(vector.version));
+    }
+    let expected = generate(&vector.name, &vector.inputs)?;
+
+    let args = ConstructorArgs::decode(&vector.constructor_calldata)?;
+    let proof = DleqProof::from_serializable(DleqProofSerialized {
+        second_point: bytes32(&vector.dleq.second_point, "dleq.second_point")?,
+        challenge: bytes32(&vector.dleq.challenge, "dleq.challenge")?,
+        response: bytes32(&vector.dleq.response, "dleq.response")?,
+        r1: bytes32(&vector.dleq.r1, "dleq.r1")?,
+        r2: bytes32(&vector.dleq.r2, "dleq.r2")?,
+    })?;
+    let adaptor_point = args.verify(Some(&proof))?;
+    verify_dleq_proof(&proof, &adaptor_point, &args.hashlock())?;
+
+    let (secret, _) = byte_array_from_calldata(&vector.unlock_calldata)
+        .map_err(|_| VectorError::Check("unlock calldata is not a ByteArray"))?;
+    let secret_hash: [u8; 32] = Sha256::digest(&secret).into();
+    if (secret_hash == args.hashlock()) != vector.cairo.unlock_succeeds {
+        return Err(VectorError::Check(
+            "unlock calldata does not match the hashlock",
+        ));
+    }
+
+    let challenge = Scalar::from_bytes_mod_order(bytes32(
+        &vector.adaptor_signature.challenge,
+        "adaptor_signature.challenge",
+    )?);
+    let finalized = Scalar::from_bytes_mod_order(bytes32(
+        &vector.adaptor_signature.finalized,
+        "adaptor_signature.finalized",
+    )?);
+    let signature = hex::decode(&vector.adaptor_signature.signature)
+        .ok()
+        .and_then(|bytes| <[u8; 96]>::try_from(bytes).ok())
+        .ok_or(VectorError::Input("adaptor_signature.signature"))?;
+    let public_key = decode_point(bytes32(
+        &vector.adaptor_signature.public_key,
+        "adaptor_signature.public_key",
+    )?)
+    .map_err(|_| VectorError::Input("adaptor_signature.public_key"))?;
+    let adaptor_sig = AdaptorSignature::from_bytes(&signature)
+        .map_err(|_| VectorError::Input("adaptor_signature.signature"))?;
+    if !verify_signature(
+        &finalized,
+        &adaptor_sig.nonce_commitment,
+        &challenge,
+        &public_key,
+    ) {
+        return Err(VectorError::Check(
+            "finalized adaptor signature does not verify",
+        ));
+    }
+
+    let actual = serde_json::to_value(vector).expect("vectors serialize");
+    let expected = serde_json::to_value(&expected).expect("vectors serialize");
+    match first_difference(&actual, &expected, "") {
+        Some(field) => Err(VectorError::Mismatch(field)),
+        None => Ok(()),
+    }
+}
+
+/// Path of the first differing JSON field, e.g. `dleq.r1` or `unlock_calldata[2]`.
+fn first_difference(actual: &Value, expected: &Value, path: &str) -> Option<String> {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (actual, expected) {
+        (Value::Object(a), Value::Object(e)) => e
+            .iter()
+            .find_map(|(key, value)| match a.get(key) {
+                Some(actual) => first_difference(actual, value, &child(key)),
+                None => Some(child(key)),
+            })
+            .or_else(|| {
+                a.keys()
+                    .find(|key| !e.contains_key(*key))
+                    .map(|key| child(key))
+            }),
+        (Value::Array(a), Value::Array(e)) if a.len() == e.len() => a
+            .iter()
+            .zip(e)
+            .enumerate()
+            .find_map(|(i, (a, e))| first_difference(a, e, &format!("{}[{}]", path, i))),
+        _ if actual == expected => None,
+        _ => Some(path.to_string()),
+    }
+}
+
+/// `SHA-256(message || R || T) mod ℓ`, as in `create_adaptor_signature`.
+fn signature_challenge(
+    message: &[u8],
+    nonce_commitment: &EdwardsPoint,
+    adaptor_point: &EdwardsPoint,
+) -> Scalar {
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    hasher.update(nonce_commitment.compress().as_bytes());
+    hasher.update(adaptor_point.compress().as_bytes());
+    Scalar::from_bytes_mod_order(hasher.finalize().into())
+}
+
+fn compressed(point: &EdwardsPoint) -> String {
+    hex::encode(point.compress().as_bytes())
+}
+
+fn low_u128(scalar: &Scalar) -> u128 {
+    u128::from_le_bytes(
+        truncate_128(scalar).as_bytes()[..16]
+            .try_into()
+            .expect("16 bytes"),
+    )
+}
+
+fn bytes32(hex_str: &str, field: &'static str) -> Result<[u8; 32], VectorError> {
+    hex::decode(hex_str)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(VectorError::Input(field))
+}
+
+fn parse_u128(felt: &str) -> Option<u128> {
+    match felt.strip_prefix("0x") {
+        Some(hex_str) => u128::from_str_radix(hex_str, 16).ok(),
+        None => felt.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shipped_vectors() -> Vec<ProtocolVector> {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../vectors/v1");
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .expect("vectors/v1 exists")
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    #[cfg(not(feature = "h2c-second-generator"))]
+    fn test_shipped_vectors_verify() {
+        let vectors = shipped_vectors();
+        assert_eq!(vectors.len(), default_inputs().len());
+        for vector in &vectors {
+            verify(vector).unwrap_or_else(|e| panic!("{}: {}", vector.name, e));
+        }
+    }
+
+    #[test]
+    fn test_tampered_vector_names_the_field() {
+        let (name, inputs) = &default_inputs()[0];
+        let mut vector = generate(name, inputs).unwrap();
+        verify(&vector).unwrap();
+
+        vector.cairo.challenge_low = "0x1".to_string();
+        assert!(
+            matches!(verify(&vector), Err(VectorError::Mismatch(f)) if f == "cairo.challenge_low")
+        );
+
+        let mut vector = generate(name, inputs).unwrap();
+        vector.unlock_calldata[1] = "0x0".to_string();
+        assert!(matches!(verify(&vector), Err(VectorError::Check(_))));
+    }
+}
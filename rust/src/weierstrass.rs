@@ -0,0 +1,169 @@
+//! Edwards → Weierstrass conversion for the Cairo verifier.
+//!
+//! Garaga (and therefore the AtomicLock contract) operates on Ed25519 in short
+//! Weierstrass form `y² = x³ + A·x + B` over p = 2^255 - 19, with coordinates
+//! stored as `u384` values split into 4×96-bit limbs. This module maps any
+//! Edwards point to that representation, replacing the Python tool previously
+//! needed for `adaptor_point_x_limbs`/`adaptor_point_y_limbs` and the DLEQ
+//! second point limbs in `SwapSecret`.
+//!
+//! The identity and the order-2 point have no affine Weierstrass image; callers
+//! must reject them first (see [`crate::encoding::check_prime_order_point`]).
+
+use curve25519_dalek::edwards::EdwardsPoint;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Cairo `u384` limbs (x, y) of a point as `0x`-prefixed hex strings,
+/// least significant limb first.
+pub fn to_cairo_limbs(point: &EdwardsPoint) -> ([String; 4], [String; 4]) {
+    let (x, y) = to_weierstrass(point);
+    (
+        u384_limbs(&x).map(|limb| format!("{:#x}", limb)),
+        u384_limbs(&y).map(|limb| format!("{:#x}", limb)),
+    )
+}
+
+/// Convert an Edwards point to Garaga's short Weierstrass model of Ed25519.
+pub(crate) fn to_weierstrass(point: &EdwardsPoint) -> (BigUint, BigUint) {
+    let p = field_prime();
+    let (x, y) = edwards_affine(point);
+    let a = &p - 1u32;
+    let d = edwards_d(&p);
+
+    // x_w = (5a + a·y - 5d·y - d) / (12 - 12y)
+    let num_x = sub(&(5u32 * &a + &a * &y), &(5u32 * &d * &y + &d), &p);
+    let den_x = sub(&BigUint::from(12u32), &(12u32 * &y), &p);
+    // y_w = (a + a·y - d·y - d) / (4x - 4x·y)
+    let num_y = sub(&(&a + &a * &y), &(&d * &y + &d), &p);
+    let den_y = sub(&(4u32 * &x), &(4u32 * &x * &y), &p);
+
+    (num_x * inv(&den_x, &p) % &p, num_y * inv(&den_y, &p) % &p)
+}
+
+/// Split a u384 value into 4×96-bit limbs (least significant first).
+pub(crate) fn u384_limbs(value: &BigUint) -> [BigUint; 4] {
+    let mask = (BigUint::one() << 96u32) - 1u32;
+    core::array::from_fn(|i| (value >> (96 * i)) & &mask)
+}
+
+/// Affine twisted Edwards coordinates (x, y) of a point.
+pub(crate) fn edwards_affine(point: &EdwardsPoint) -> (BigUint, BigUint) {
+    let p = field_prime();
+    let mut bytes = point.compress().to_bytes();
+    let sign = bytes[31] >> 7 == 1;
+    bytes[31] &= 0x7f;
+    let y = BigUint::from_bytes_le(&bytes);
+
+    // x² = (y² - 1) / (d·y² + 1)
+    let y2 = &y * &y % &p;
+    let x2 = sub(&y2, &BigUint::one(), &p) * inv(&((edwards_d(&p) * &y2 + 1u32) % &p), &p) % &p;
+
+    // p ≡ 5 (mod 8): candidate x = x2^((p+3)/8), fixed up by √-1 if needed
+    let mut x = x2.modpow(&((&p + 3u32) >> 3), &p);
+    if &x * &x % &p != x2 {
+        let sqrt_m1 = BigUint::from(2u32).modpow(&((&p - 1u32) >> 2), &p);
+        x = x * sqrt_m1 % &p;
+    }
+    if x.bit(0) != sign && !x.is_zero() {
+        x = &p - x;
+    }
+    (x, y)
+}
+
+pub(crate) fn field_prime() -> BigUint {
+    (BigUint::one() << 255u32) - 19u32
+}
+
+/// Twisted Edwards d = -121665 / 121666.
+fn edwards_d(p: &BigUint) -> BigUint {
+    sub(
+        &BigUint::zero(),
+        &(BigUint::from(121665u32) * inv(&BigUint::from(121666u32), p)),
+        p,
+    )
+}
+
+pub(crate) fn sub(a: &BigUint, b: &BigUint, p: &BigUint) -> BigUint {
+    (a % p + p - b % p) % p
+}
+
+pub(crate) fn inv(a: &BigUint, p: &BigUint) -> BigUint {
+    a.modpow(&(p - 2u32), p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+    use curve25519_dalek::scalar::Scalar;
+
+    #[test]
+    fn test_adaptor_limbs_match_python_tool() {
+        // tools/ed25519_test_data.json: cairo_x / cairo_y for this scalar
+        let scalar_bytes: [u8; 32] =
+            hex::decode("d8f5bb93d38b04c6c239cf2464fd33a611bb056fbecc85d2cfde4d8c5885be01")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let scalar = Scalar::from_canonical_bytes(scalar_bytes).unwrap();
+        let (x, y) = to_cairo_limbs(&(ED25519_BASEPOINT_POINT * scalar));
+        assert_eq!(
+            x,
+            [
+                "0x66dad767805962698e1e32fe",
+                "0xff9dcc1849a5379f6444df12",
+                "0x5e6656e640a4f4d6",
+                "0x0"
+            ]
+        );
+        assert_eq!(
+            y,
+            [
+                "0x836170c97a7481e2a4abd28e",
+                "0x69a071877f7f930679339c32",
+                "0x428b5990d11b767d",
+                "0x0"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_image_is_on_weierstrass_curve() {
+        let p = field_prime();
+        let a = &p - 1u32;
+        let d = edwards_d(&p);
+        let inv48 = inv(&BigUint::from(48u32), &p);
+        let inv864 = inv(&BigUint::from(864u32), &p);
+        // A = -(a² + 14ad + d²)/48, B = (a + d)(-a² + 34ad - d²)/864
+        let big_a = sub(
+            &BigUint::zero(),
+            &((&a * &a + 14u32 * &a * &d + &d * &d) * &inv48),
+            &p,
+        );
+        let big_b = (&a + &d) * sub(&(34u32 * &a * &d), &(&a * &a + &d * &d), &p) * &inv864 % &p;
+
+        for k in [1u64, 2, 7, 1_000_003] {
+            let (x, y) = to_weierstrass(&(ED25519_BASEPOINT_POINT * Scalar::from(k)));
+            let lhs = &y * &y % &p;
+            let rhs = (&x * &x * &x + &big_a * &x + &big_b) % &p;
+            assert_eq!(
+                lhs, rhs,
+                "k·G must map onto the Weierstrass curve (k = {})",
+                k
+            );
+        }
+    }
+
+    #[test]
+    fn test_limbs_are_96_bit() {
+        let (x, _) = to_weierstrass(&ED25519_BASEPOINT_POINT);
+        let limbs = u384_limbs(&x);
+        let recombined = limbs
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |acc, limb| (acc << 96u32) + limb);
+        assert_eq!(recombined, x);
+        assert!(limbs.iter().all(|l| l.bits() <= 96));
+    }
+}
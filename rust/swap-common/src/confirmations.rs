@@ -0,0 +1,99 @@
+//! Value-based confirmation requirements (`spec/confirmations.json`).
+//!
+//! Small swaps should not wait as long as large ones: a reorg deep enough to
+//! double-spend 0.05 XMR is not worth an attacker's hashpower, 50 XMR may be.
+//! The tiers are a data file shared with the watchtower so both apply the
+//! same numbers.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// Raw contents of `spec/confirmations.json`.
+pub const CONFIRMATION_POLICY_JSON: &str = include_str!("../../../spec/confirmations.json");
+
+/// Piconero per XMR.
+pub const PICONERO_PER_XMR: u64 = 1_000_000_000_000;
+
+/// Confirmations required for one value tier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ConfirmationRequirement {
+    /// Monero blocks on top of the lock transaction.
+    pub monero_confirmations: u64,
+    /// Starknet blocks on top of a contract event before acting on it.
+    pub starknet_depth: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+struct Tier {
+    /// Exclusive upper bound (None = unbounded).
+    max_piconero: Option<u64>,
+    #[serde(flatten)]
+    requirement: ConfirmationRequirement,
+}
+
+/// Mapping from swap value to required confirmations.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ConfirmationPolicy {
+    tiers: Vec<Tier>,
+}
+
+impl ConfirmationPolicy {
+    /// The shared policy from `spec/confirmations.json`.
+    pub fn shared() -> &'static ConfirmationPolicy {
+        static POLICY: OnceLock<ConfirmationPolicy> = OnceLock::new();
+        POLICY.get_or_init(|| {
+            serde_json::from_str(CONFIRMATION_POLICY_JSON)
+                .expect("spec/confirmations.json is valid")
+        })
+    }
+
+    /// Requirements for a swap worth `amount_piconero`.
+    ///
+    /// Values above every bounded tier get the last (strictest) tier.
+    pub fn requirement(&self, amount_piconero: u64) -> ConfirmationRequirement {
+        self.tiers
+            .iter()
+            .find(|tier| tier.max_piconero.is_none_or(|max| amount_piconero < max))
+            .or(self.tiers.last())
+            .map(|tier| tier.requirement)
+            .expect("confirmation policy has at least one tier")
+    }
+}
+
+/// Requirements for a swap worth `amount_piconero` under the shared policy.
+pub fn required_confirmations(amount_piconero: u64) -> ConfirmationRequirement {
+    ConfirmationPolicy::shared().requirement(amount_piconero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers() {
+        let small = required_confirmations(PICONERO_PER_XMR / 20);
+        assert_eq!(small.monero_confirmations, 3);
+
+        let medium = required_confirmations(PICONERO_PER_XMR / 2);
+        assert_eq!(medium.monero_confirmations, 6);
+
+        let large = required_confirmations(5 * PICONERO_PER_XMR);
+        assert_eq!(large.monero_confirmations, 10);
+        assert_eq!(required_confirmations(u64::MAX), large);
+    }
+
+    #[test]
+    fn test_requirements_grow_with_value() {
+        let policy = ConfirmationPolicy::shared();
+        for pair in policy.tiers.windows(2) {
+            let (lower, upper) = (&pair[0], &pair[1]);
+            let lower_max = lower.max_piconero.expect("only the last tier is unbounded");
+            assert!(upper.max_piconero.is_none_or(|max| lower_max < max));
+            assert!(
+                lower.requirement.monero_confirmations <= upper.requirement.monero_confirmations
+            );
+            assert!(lower.requirement.starknet_depth <= upper.requirement.starknet_depth);
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! Human-readable amounts for CLI output, alerts and receipts.
+//!
+//! Amounts stay integers in base units (piconero, token wei/fri as u256)
+//! everywhere else; this module is the only place they become decimals.
+//! [`AmountFormat`] picks the digit grouping from the locale, rounds to a
+//! configurable precision and, given a price (e.g. from an oracle), appends
+//! an approximate fiat value:
+//!
+//! ```text
+//! 1,234.5 XMR (≈ 197,520.00 USD)
+//! ```
+
+use serde_json::Value;
+
+/// Environment variable overriding the locale used for digit grouping.
+pub const LOCALE_ENV: &str = "AMOUNT_LOCALE";
+
+/// Environment variable setting the number of fraction digits shown.
+pub const PRECISION_ENV: &str = "AMOUNT_PRECISION";
+
+/// A currency and the number of decimals of its base unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub decimals: u32,
+}
+
+/// Monero (piconero base unit).
+pub const XMR: Unit = Unit {
+    symbol: "XMR",
+    decimals: 12,
+};
+
+/// Starknet fee token (fri base unit).
+pub const STRK: Unit = Unit {
+    symbol: "STRK",
+    decimals: 18,
+};
+
+/// Ether on Starknet (wei base unit).
+pub const ETH: Unit = Unit {
+    symbol: "ETH",
+    decimals: 18,
+};
+
+/// The ERC20 locked in an AtomicLock, assumed to use 18 decimals.
+pub const TOKEN: Unit = Unit {
+    symbol: "tokens",
+    decimals: 18,
+};
+
+impl Unit {
+    /// Unit of a Starknet fee (`actual_fee.unit` in receipts).
+    pub fn from_fee_unit(unit: &str) -> Option<Self> {
+        match unit {
+            "FRI" => Some(STRK),
+            "WEI" => Some(ETH),
+            _ => None,
+        }
+    }
+
+    /// Parse a decimal amount (`.` as decimal mark, e.g. `0.25`) into base
+    /// units; `None` if malformed, too precise or out of range.
+    pub fn parse(&self, text: &str) -> Option<u128> {
+        let (int_part, frac_part) = text.trim().split_once('.').unwrap_or((text.trim(), ""));
+        let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (int_part.is_empty() && frac_part.is_empty()) || !digits(int_part) || !digits(frac_part)
+        {
+            return None;
+        }
+        let decimals = self.decimals as usize;
+        if frac_part.len() > decimals {
+            return None;
+        }
+        format!("{}{:0<decimals$}", int_part, frac_part)
+            .parse()
+            .ok()
+    }
+}
+
+/// Digit grouping and decimal mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub thousands: &'static str,
+    pub decimal: char,
+}
+
+impl Locale {
+    /// `1,234.5`
+    pub const EN: Self = Self {
+        thousands: ",",
+        decimal: '.',
+    };
+    /// `1.234,5`
+    pub const DE: Self = Self {
+        thousands: ".",
+        decimal: ',',
+    };
+    /// `1 234,5` (narrow no-break space)
+    pub const FR: Self = Self {
+        thousands: "\u{202f}",
+        decimal: ',',
+    };
+    /// `1'234.5`
+    pub const CH: Self = Self {
+        thousands: "'",
+        decimal: '.',
+    };
+    /// `1234.5`, for machine-read output
+    pub const PLAIN: Self = Self {
+        thousands: "",
+        decimal: '.',
+    };
+
+    /// Locale for a POSIX or BCP 47 tag (`de_DE.UTF-8`, `fr-CA`, `C`).
+    ///
+    /// Unknown languages get [`Locale::EN`].
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let mut parts = tag.split(['_', '-']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts.next().unwrap_or_default().to_ascii_uppercase();
+        match (language.as_str(), region.as_str()) {
+            ("c" | "posix", _) => Self::PLAIN,
+            ("de" | "it", "CH") | (_, "LI") => Self::CH,
+            ("de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr", _) => Self::DE,
+            ("fr" | "ru" | "uk" | "pl" | "cs" | "sv" | "nb" | "fi", _) => Self::FR,
+            _ => Self::EN,
+        }
+    }
+
+    /// Locale from [`LOCALE_ENV`], then `LC_ALL`, `LC_NUMERIC` and `LANG`.
+    pub fn from_env() -> Self {
+        [LOCALE_ENV, "LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .map_or(Self::EN, |tag| Self::from_tag(&tag))
+    }
+}
+
+/// Price of one whole `symbol` in a fiat currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiatRate {
+    pub symbol: &'static str,
+    pub currency: String,
+    pub price: f64,
+}
+
+/// How amounts are rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmountFormat {
+    locale: Locale,
+    precision: Option<u32>,
+    fiat: Vec<FiatRate>,
+}
+
+impl Default for AmountFormat {
+    fn default() -> Self {
+        Self {
+            locale: Locale::EN,
+            precision: None,
+            fiat: Vec::new(),
+        }
+    }
+}
+
+impl AmountFormat {
+    /// English grouping, every significant digit, no fiat values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locale from the environment ([`Locale::from_env`]) and precision from
+    /// [`PRECISION_ENV`] when set.
+    pub fn from_env() -> Self {
+        let format = Self::new().with_locale(Locale::from_env());
+        match std::env::var(PRECISION_ENV)
+            .ok()
+            .and_then(|p| p.trim().parse().ok())
+        {
+            Some(precision) => format.with_precision(precision),
+            None => format,
+        }
+    }
+
+    /// Group digits as in `locale`.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Show exactly `digits` fraction digits, rounding half up (by default
+    /// every significant digit is shown and trailing zeros are dropped).
+    pub fn with_precision(mut self, digits: u32) -> Self {
+        self.precision = Some(digits);
+        self
+    }
+
+    /// Append `≈ <value> <currency>` to amounts of `rate.symbol`.
+    pub fn with_fiat(mut self, rate: FiatRate) -> Self {
+        self.fiat.retain(|r| r.symbol != rate.symbol);
+        self.fiat.push(rate);
+        self
+    }
+
+    /// `amount` piconero as XMR.
+    pub fn piconero(&self, amount: u64) -> String {
+        self.amount(u128::from(amount), XMR)
+    }
+
+    /// `amount` base units of `unit`.
+    pub fn amount(&self, amount: u128, unit: Unit) -> String {
+        self.u256(amount, 0, unit)
+    }
+
+    /// A u256 amount (`low`, `high` 128-bit halves, as in Cairo) of `unit`.
+    pub fn u256(&self, low: u128, high: u128, unit: Unit) -> String {
+        let digits = u256_to_decimal(low, high);
+        let mut text = format!(
+            "{} {}",
+            self.decimal(&digits, unit.decimals, self.precision),
+            unit.symbol
+        );
+        if let Some(rate) = self.fiat.iter().find(|r| r.symbol == unit.symbol) {
+            // Display only: f64 is plenty for an approximate value
+            let whole =
+                digits.parse::<f64>().unwrap_or_default() / 10f64.powi(unit.decimals as i32);
+            let value = format!("{:.2}", whole * rate.price).replace('.', "");
+            text.push_str(&format!(
+                " (≈ {} {})",
+                self.decimal(&value, 2, Some(2)),
+                rate.currency
+            ));
+        }
+        text
+    }
+
+    /// Fee paid by a Starknet transaction, from its receipt (`actual_fee`).
+    pub fn receipt_fee(&self, receipt: &Value) -> Option<String> {
+        let fee = &receipt["actual_fee"];
+        let unit = Unit::from_fee_unit(fee["unit"].as_str()?)?;
+        let amount = fee["amount"].as_str()?;
+        let amount = match amount.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok()?,
+            None => amount.parse().ok()?,
+        };
+        Some(self.amount(amount, unit))
+    }
+
+    /// Place the decimal mark `decimals` digits from the right of an integer
+    /// digit string, round to `precision` and group the integer part.
+    fn decimal(&self, digits: &str, decimals: u32, precision: Option<u32>) -> String {
+        let decimals = decimals as usize;
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (int_part, frac_part) = padded.split_at(padded.len() - decimals);
+        let (int_part, frac_part) = match precision.map(|p| p as usize) {
+            Some(p) if p < decimals => {
+                let kept = format!("{}{}", int_part, &frac_part[..p]);
+                let kept = if frac_part.as_bytes()[p] >= b'5' {
+                    increment_decimal(&kept)
+                } else {
+                    kept
+                };
+                let split = kept.len() - p;
+                (kept[..split].to_string(), kept[split..].to_string())
+            }
+            Some(p) => (int_part.to_string(), format!("{:0<p$}", frac_part)),
+            None => (
+                int_part.to_string(),
+                frac_part.trim_end_matches('0').to_string(),
+            ),
+        };
+
+        let mut text = group_thousands(&int_part, self.locale.thousands);
+        if !frac_part.is_empty() {
+            text.push(self.locale.decimal);
+            text.push_str(&frac_part);
+        }
+        text
+    }
+}
+
+/// Decimal digits of a u256 given as 128-bit halves.
+fn u256_to_decimal(low: u128, high: u128) -> String {
+    if high == 0 {
+        return low.to_string();
+    }
+    // Little-endian 64-bit limbs, divided by 10^19 until zero
+    const CHUNK: u64 = 10_000_000_000_000_000_000;
+    let mut limbs = [
+        low as u64,
+        (low >> 64) as u64,
+        high as u64,
+        (high >> 64) as u64,
+    ];
+    let mut chunks = Vec::new();
+    while limbs.iter().any(|&limb| limb != 0) {
+        let mut remainder = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let value = (remainder << 64) | u128::from(*limb);
+            *limb = (value / u128::from(CHUNK)) as u64;
+            remainder = value % u128::from(CHUNK);
+        }
+        chunks.push(remainder as u64);
+    }
+    let mut text = chunks.pop().map(|c| c.to_string()).unwrap_or_default();
+    for chunk in chunks.iter().rev() {
+        text.push_str(&format!("{:019}", chunk));
+    }
+    text
+}
+
+/// Add one to a decimal digit string.
+fn increment_decimal(digits: &str) -> String {
+    let mut bytes = digits.as_bytes().to_vec();
+    for byte in bytes.iter_mut().rev() {
+        if *byte == b'9' {
+            *byte = b'0';
+        } else {
+            *byte += 1;
+            return String::from_utf8(bytes).expect("ASCII digits");
+        }
+    }
+    format!("1{}", String::from_utf8(bytes).expect("ASCII digits"))
+}
+
+fn group_thousands(digits: &str, separator: &str) -> String {
+    let mut text = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            text.push_str(separator);
+        }
+        text.push(c);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::confirmations::PICONERO_PER_XMR;
+    use serde_json::json;
+
+    #[test]
+    fn test_piconero_grouping_and_trimming() {
+        let format = AmountFormat::new();
+        assert_eq!(
+            format.piconero(1_234 * PICONERO_PER_XMR + PICONERO_PER_XMR / 2),
+            "1,234.5 XMR"
+        );
+        assert_eq!(format.piconero(1), "0.000000000001 XMR");
+        assert_eq!(format.piconero(0), "0 XMR");
+        assert_eq!(
+            format
+                .with_locale(Locale::DE)
+                .piconero(1_234_567 * PICONERO_PER_XMR),
+            "1.234.567 XMR"
+        );
+    }
+
+    #[test]
+    fn test_precision_rounds_half_up() {
+        let format = AmountFormat::new().with_precision(2);
+        assert_eq!(format.piconero(1_994_999_999_999), "1.99 XMR");
+        assert_eq!(format.piconero(999_995_000_000_000), "1,000.00 XMR");
+        assert_eq!(
+            format.with_precision(0).piconero(PICONERO_PER_XMR / 2),
+            "1 XMR"
+        );
+    }
+
+    #[test]
+    fn test_u256_beyond_u128() {
+        // 2^128 wei
+        let format = AmountFormat::new().with_locale(Locale::PLAIN);
+        assert_eq!(
+            format.u256(0, 1, ETH),
+            "340282366920938463463.374607431768211456 ETH"
+        );
+        assert_eq!(u256_to_decimal(u128::MAX, u128::MAX).len(), 78);
+    }
+
+    #[test]
+    fn test_fiat_value() {
+        let format = AmountFormat::new()
+            .with_locale(Locale::FR)
+            .with_precision(3)
+            .with_fiat(FiatRate {
+                symbol: "XMR",
+                currency: "EUR".to_string(),
+                price: 160.0,
+            });
+        assert_eq!(
+            format.piconero(1_234 * PICONERO_PER_XMR + PICONERO_PER_XMR / 2),
+            "1\u{202f}234,500 XMR (≈ 197\u{202f}520,00 EUR)"
+        );
+        // Other units have no rate
+        assert_eq!(
+            format.amount(1_234 * 10u128.pow(18), STRK),
+            "1\u{202f}234,000 STRK"
+        );
+    }
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("de_DE.UTF-8"), Locale::DE);
+        assert_eq!(Locale::from_tag("de_CH"), Locale::CH);
+        assert_eq!(Locale::from_tag("fr-CA"), Locale::FR);
+        assert_eq!(Locale::from_tag("C"), Locale::PLAIN);
+        assert_eq!(Locale::from_tag("en_US.UTF-8"), Locale::EN);
+        assert_eq!(Locale::from_tag("ja_JP"), Locale::EN);
+    }
+
+    #[test]
+    fn test_unit_parse() {
+        assert_eq!(STRK.parse("0.25"), Some(250_000_000_000_000_000));
+        assert_eq!(XMR.parse("2"), Some(2 * PICONERO_PER_XMR as u128));
+        assert_eq!(XMR.parse(".5"), Some(PICONERO_PER_XMR as u128 / 2));
+        assert_eq!(XMR.parse("0.0000000000001"), None);
+        assert_eq!(STRK.parse("1,5"), None);
+        assert_eq!(STRK.parse(""), None);
+    }
+
+    #[test]
+    fn test_receipt_fee() {
+        let receipt = json!({ "actual_fee": { "amount": "0x2386f26fc10000", "unit": "FRI" } });
+        assert_eq!(
+            AmountFormat::new().receipt_fee(&receipt).as_deref(),
+            Some("0.01 STRK")
+        );
+        assert_eq!(AmountFormat::new().receipt_fee(&json!({})), None);
+    }
+}
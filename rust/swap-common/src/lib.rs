@@ -0,0 +1,11 @@
+//! Code shared by the swap CLIs (`xmr_secret_gen`) and the watchtower.
+//!
+//! Both must render amounts and pick confirmation counts the same way, and
+//! the watchtower cannot depend on the Monero/Starknet tooling crate, so the
+//! shared modules live here with no dependency on either.
+//!
+//! - format: human-readable amounts for CLI output, alerts and receipts
+//! - confirmations: required confirmations by swap value (`spec/confirmations.json`)
+
+pub mod confirmations;
+pub mod format;
@@ -0,0 +1,296 @@
+//! Chaos tests: a maker drives its swap against a simulated Starknet node
+//! while the RPC client delays, drops, corrupts or serves stale responses.
+//!
+//! Whatever the fault class, the swap must end in the phase the chain
+//! backs: `Finalized` (XMR swept) once the taker revealed `t`, `Refunded`
+//! otherwise. A maker that ends up `Refunded` after a reveal has lost its
+//! XMR. Run with `cargo test --features chaos --test chaos`.
+
+#![cfg(feature = "chaos")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use xmr_secret_gen::chaos::{Chaos, ChaosConfig, Fault};
+use xmr_secret_gen::starknet::{get_selector_from_name, StarknetClient};
+use xmr_secret_gen::swap::orchestrator::SwapOrchestrator;
+use xmr_secret_gen::swap::reconcile::{reconcile, NextAction};
+use xmr_secret_gen::swap::{SwapPhase, SwapRole};
+
+const CONTRACT: &str = "0x123";
+const START: u64 = 1_000_000;
+const LOCK_DURATION: u64 = 1_000;
+const TICK: u64 = 10;
+const MAX_TICKS: u64 = 1_000;
+
+/// AtomicLock state after one block.
+#[derive(Debug, Clone, Copy)]
+struct LockState {
+    revealed: bool,
+    unlocked: bool,
+    lock_until: u64,
+    claimable_after: u64,
+}
+
+/// Simulated chain: one block per state change, and a shared clock.
+#[derive(Debug)]
+struct Chain {
+    blocks: Vec<LockState>,
+    now: u64,
+}
+
+impl Chain {
+    fn latest(&self) -> LockState {
+        *self.blocks.last().unwrap()
+    }
+
+    fn reveal(&mut self, grace: u64) {
+        let mut state = self.latest();
+        state.revealed = true;
+        state.claimable_after = self.now + grace;
+        self.blocks.push(state);
+    }
+
+    /// `claim()`: after the grace period, by the taker.
+    fn claim(&mut self) -> bool {
+        let mut state = self.latest();
+        if !state.revealed || state.unlocked || self.now < state.claimable_after {
+            return false;
+        }
+        state.unlocked = true;
+        self.blocks.push(state);
+        true
+    }
+
+    /// `refund()`: the contract rejects it once `t` is revealed.
+    fn refund(&mut self) -> bool {
+        let mut state = self.latest();
+        if state.revealed || state.unlocked || self.now < state.lock_until {
+            return false;
+        }
+        state.unlocked = true;
+        self.blocks.push(state);
+        true
+    }
+
+    fn rpc(&self, method: &str, params: &Value) -> Value {
+        match method {
+            "starknet_blockNumber" => json!(format!("{:#x}", self.blocks.len() - 1)),
+            "starknet_call" => {
+                let state = match params["block_id"]["block_number"].as_u64() {
+                    Some(number) => self.blocks[number as usize],
+                    None => self.latest(),
+                };
+                let selector = params["request"]["entry_point_selector"].as_str().unwrap();
+                let value = [
+                    ("is_secret_revealed", state.revealed as u64),
+                    ("is_unlocked", state.unlocked as u64),
+                    ("get_lock_until", state.lock_until),
+                    ("get_claimable_after", state.claimable_after),
+                ]
+                .into_iter()
+                .find(|(name, _)| get_selector_from_name(name) == selector)
+                .map(|(_, value)| value)
+                .expect("unknown view");
+                json!([format!("{:#x}", value)])
+            }
+            other => panic!("unexpected RPC method {}", other),
+        }
+    }
+}
+
+/// Serve `chain` over HTTP JSON-RPC on a local port; returns the URL.
+async fn serve(chain: Arc<Mutex<Chain>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let chain = Arc::clone(&chain);
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let body_start = loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break i + 4;
+                    }
+                };
+                let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|value| value.trim().parse().unwrap())
+                    .unwrap_or(0);
+                while request.len() < body_start + length {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+
+                let call: Value = serde_json::from_slice(&request[body_start..]).unwrap();
+                let result = chain
+                    .lock()
+                    .unwrap()
+                    .rpc(call["method"].as_str().unwrap(), &call["params"]);
+                let body = json!({ "jsonrpc": "2.0", "id": call["id"], "result": result });
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    url
+}
+
+/// Taker behaviour in a scenario.
+#[derive(Debug, Clone, Copy)]
+enum Taker {
+    /// Reveal at `at`, claim once the `grace` period is over.
+    Reveals { at: u64, grace: u64 },
+    /// Disappears: the maker must refund.
+    Vanishes,
+}
+
+/// Run one maker swap under `config`; returns the maker's final phase and
+/// the chain's final state.
+async fn run_maker(config: ChaosConfig, taker: Taker) -> (SwapPhase, LockState, Arc<Chaos>) {
+    let lock_until = START + LOCK_DURATION;
+    let chain = Arc::new(Mutex::new(Chain {
+        blocks: vec![LockState {
+            revealed: false,
+            unlocked: false,
+            lock_until,
+            claimable_after: 0,
+        }],
+        now: START,
+    }));
+    let chaos = Arc::new(Chaos::new(config));
+    let client =
+        StarknetClient::new(serve(Arc::clone(&chain)).await).with_chaos(Arc::clone(&chaos));
+
+    let mut swap = SwapOrchestrator::new("ab12", SwapRole::Maker);
+    swap.deployed(CONTRACT, lock_until).unwrap();
+    swap.deposited().unwrap();
+
+    for _ in 0..MAX_TICKS {
+        let now = {
+            let mut chain = chain.lock().unwrap();
+            chain.now += TICK;
+            if let Taker::Reveals { at, grace } = taker {
+                if chain.now == at {
+                    chain.reveal(grace);
+                }
+                chain.claim();
+            }
+            chain.now
+        };
+
+        // Faulty reads are retried on the next tick
+        let Ok(status) = client.get_lock_status(CONTRACT).await else {
+            continue;
+        };
+        let reconciliation = reconcile(SwapRole::Maker, swap.phase(), Some(&status), now);
+        // A stale view may lag behind the local phase; the orchestrator
+        // rejects going back, so errors here are expected and ignored
+        let _ = match reconciliation.phase {
+            SwapPhase::Revealed => swap.revealed(status.claimable_after),
+            SwapPhase::Refunded => swap.refunded(),
+            _ => Ok(()),
+        };
+
+        match reconciliation.next_action {
+            NextAction::Refund if swap.phase() == SwapPhase::Deposited => {
+                // Recorded once the chain shows it
+                chain.lock().unwrap().refund();
+            }
+            NextAction::SweepMonero if swap.phase() == SwapPhase::Revealed => {
+                swap.finalized().unwrap();
+            }
+            _ => {}
+        }
+        if swap.phase().is_terminal() {
+            break;
+        }
+    }
+
+    let final_state = chain.lock().unwrap().latest();
+    (swap.phase(), final_state, chaos)
+}
+
+/// Both scenarios end safely under `config`, and `fault` was injected.
+async fn assert_safe(config: ChaosConfig, fault: Fault) {
+    for grace in [200, 0] {
+        let taker = Taker::Reveals {
+            at: START + 300,
+            grace,
+        };
+        let (phase, chain, chaos) = run_maker(config.clone(), taker).await;
+        assert!(chain.revealed, "taker revealed");
+        assert_eq!(
+            phase,
+            SwapPhase::Finalized,
+            "maker must sweep after a reveal"
+        );
+        assert!(chaos.injected(fault) > 0, "no {:?} injected", fault);
+    }
+
+    let (phase, chain, chaos) = run_maker(config, Taker::Vanishes).await;
+    assert!(
+        chain.unlocked && !chain.revealed,
+        "refund reached the chain"
+    );
+    assert_eq!(phase, SwapPhase::Refunded);
+    assert!(chaos.injected(fault) > 0, "no {:?} injected", fault);
+}
+
+#[tokio::test]
+async fn test_safe_under_latency() {
+    let config = ChaosConfig::default()
+        .with_latency(0.3, Duration::from_millis(10))
+        .with_seed(1);
+    assert_safe(config, Fault::Latency).await;
+}
+
+#[tokio::test]
+async fn test_safe_under_dropped_responses() {
+    let config = ChaosConfig::default()
+        .with_dropped_responses(0.3)
+        .with_seed(2);
+    assert_safe(config, Fault::DroppedResponse).await;
+}
+
+#[tokio::test]
+async fn test_safe_under_corrupted_json() {
+    let config = ChaosConfig::default().with_corrupted_json(0.3).with_seed(3);
+    assert_safe(config, Fault::CorruptedJson).await;
+}
+
+#[tokio::test]
+async fn test_safe_under_stale_reads() {
+    // A node lagging far behind: most reads replay an older answer
+    let config = ChaosConfig::default().with_stale_reads(0.7).with_seed(4);
+    assert_safe(config, Fault::StaleRead).await;
+}
+
+#[tokio::test]
+async fn test_safe_under_all_faults() {
+    let config = ChaosConfig::default()
+        .with_latency(0.2, Duration::from_millis(5))
+        .with_dropped_responses(0.2)
+        .with_corrupted_json(0.2)
+        .with_stale_reads(0.5)
+        .with_seed(5);
+    assert_safe(config, Fault::StaleRead).await;
+}
@@ -18,8 +18,8 @@ fn test_full_swap_round() {
     // This simulates Alice preparing for a swap
 
     // Generate swap secret (same as Cairo contract expects)
-    let swap_secret = generate_swap_secret();
-    let secret_bytes_vec = hex::decode(&swap_secret.secret_hex).unwrap();
+    let swap_secret = generate_swap_secret().expect("swap secret generation");
+    let secret_bytes_vec = hex::decode(swap_secret.expose_secret()).unwrap();
     let secret_bytes: [u8; 32] = secret_bytes_vec.clone().try_into().unwrap();
     let adaptor_scalar = Scalar::from_bytes_mod_order(secret_bytes);
 
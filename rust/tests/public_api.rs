@@ -0,0 +1,139 @@
+//! Public API stability checks.
+//!
+//! `tests/public-api.txt` is the crate's public surface as listed by
+//! cargo-public-api (nightly). The `public-api` CI job regenerates the
+//! listing and fails on any difference, so an intended change to the API is
+//! accepted by committing the new snapshot with it and every addition or
+//! removal shows up in review:
+//!
+//! ```bash
+//! cargo install --locked cargo-public-api
+//! cargo public-api --all-features > tests/public-api.txt
+//! ```
+//!
+//! `no_secrets_in_public_fields` rejects public fields of that snapshot that
+//! hand out key material directly:
+//!
+//! ```bash
+//! cargo test --test public_api -- --ignored
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+/// Field names that hold key material
+const SECRET_FIELD_NAMES: &[&str] = &[
+    "secret",
+    "private",
+    "spend_key",
+    "partial_key",
+    "base_key",
+    "adaptor_scalar",
+];
+
+/// Field types that carry a secret in the clear
+const SECRET_TYPES: &[&str] = &["Scalar", "String", "[u8; 32]"];
+
+/// Never a public field type: whoever holds the field can copy the secret
+/// out of the wrapper, and the crate can no longer switch to another one
+const DENIED_FIELD_TYPES: &[&str] = &["zeroize::Zeroizing<"];
+
+/// Never part of the public surface
+const DENIED_PATHS: &[&str] = &[
+    "xmr_secret_gen::zeroize::",
+    "xmr_secret_gen::curve25519_dalek::",
+];
+
+/// Secret fields that predate this check (`Type::field`). Shrink this list;
+/// never grow it.
+const KNOWN_SECRET_FIELDS: &[&str] = &[
+    "SwapKeyPair::partial_key",
+    "SwapKeyPair::adaptor_scalar",
+    "SwapKeyPair::full_spend_key",
+    "KeyPair::base_key",
+    "KeyPair::adaptor_scalar",
+    "StoredSwap::secret_hex",
+    "VectorInputs::secret",
+    "VectorInputs::monero_base_key",
+    "ProtocolVector::secret_scalar",
+];
+
+/// Lines of the committed cargo-public-api listing
+fn snapshot() -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/public-api.txt");
+    fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "{} missing: cargo public-api --all-features > tests/public-api.txt",
+            path.display()
+        )
+    })
+}
+
+#[test]
+#[ignore] // Run by the public-api CI job, after it checked the snapshot is current
+fn no_secrets_in_public_fields() {
+    let snapshot = snapshot();
+    let violations: Vec<&str> = snapshot.lines().filter(|item| is_denied(item)).collect();
+    assert!(
+        violations.is_empty(),
+        "Key material in the public API:\n  {}",
+        violations.join("\n  ")
+    );
+}
+
+/// Whether one cargo-public-api line (e.g.
+/// `pub xmr_secret_gen::SwapKeyPair::partial_key: curve25519_dalek::scalar::Scalar`)
+/// breaks the rules above
+fn is_denied(item: &str) -> bool {
+    if DENIED_PATHS.iter().any(|path| item.contains(path)) {
+        return true;
+    }
+    let Some((path, ty)) = field(item) else {
+        return false;
+    };
+    if DENIED_FIELD_TYPES.iter().any(|denied| ty.contains(denied)) {
+        return true;
+    }
+    let mut segments = path.rsplit("::");
+    let name = segments.next().unwrap_or(path);
+    let owner = segments.next().unwrap_or("");
+    SECRET_FIELD_NAMES
+        .iter()
+        .any(|secret| name.contains(secret))
+        && SECRET_TYPES.iter().any(|secret| ty.contains(secret))
+        && !KNOWN_SECRET_FIELDS.contains(&format!("{}::{}", owner, name).as_str())
+}
+
+/// (path, type) of a public field line; `None` for any other item
+fn field(item: &str) -> Option<(&str, &str)> {
+    let rest = item.strip_prefix("pub ")?;
+    let first = rest.split_whitespace().next()?;
+    if !first.starts_with("xmr_secret_gen::") {
+        // `pub fn`, `pub struct`, `pub const`, ...
+        return None;
+    }
+    rest.split_once(": ")
+}
+
+#[test]
+fn test_secret_field_rules() {
+    // Hand-written lines in cargo-public-api's format
+    let denied = [
+        "pub xmr_secret_gen::Wallet::spend_key: curve25519_dalek::scalar::Scalar",
+        "pub xmr_secret_gen::Account::private_key: alloc::string::String",
+        "pub xmr_secret_gen::Session::nonce: zeroize::Zeroizing<[u8; 32]>",
+        "pub use xmr_secret_gen::zeroize::Zeroizing",
+    ];
+    let allowed = [
+        "pub xmr_secret_gen::SwapKeyPair::partial_key: curve25519_dalek::scalar::Scalar",
+        "pub xmr_secret_gen::contracts::atomic_lock::SecretRevealed::secret_hash: u32",
+        "pub xmr_secret_gen::dleq::DleqProof::challenge: curve25519_dalek::scalar::Scalar",
+        "pub fn xmr_secret_gen::SwapKeyPair::recover(partial_key: zeroize::Zeroizing<curve25519_dalek::scalar::Scalar>, adaptor_scalar: curve25519_dalek::scalar::Scalar) -> zeroize::Zeroizing<curve25519_dalek::scalar::Scalar>",
+    ];
+    for item in denied {
+        assert!(is_denied(item), "{}", item);
+    }
+    for item in allowed {
+        assert!(!is_denied(item), "{}", item);
+    }
+}
@@ -5,11 +5,21 @@
 //! "funds locked forever" bug where hashlock mismatch causes verification failure.
 
 use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::EdwardsPoint;
 use curve25519_dalek::scalar::Scalar;
 use hex;
 use sha2::{Digest, Sha256};
+use xmr_secret_gen::dleq::{generate_dleq_proof, hash_to_curve_second_generator};
 use zeroize::Zeroizing;
-use xmr_secret_gen::dleq::generate_dleq_proof;
+
+/// Second generator Y this build uses (2·G unless `h2c-second-generator`).
+fn second_generator() -> EdwardsPoint {
+    if cfg!(feature = "h2c-second-generator") {
+        hash_to_curve_second_generator()
+    } else {
+        ED25519_BASEPOINT_POINT * Scalar::from(2u64)
+    }
+}
 
 /// Test that hashlock computation matches Cairo's implementation.
 ///
@@ -18,23 +28,22 @@ use xmr_secret_gen::dleq::generate_dleq_proof;
 fn test_hashlock_rust_cairo_match() {
     // Use canonical test vector secret
     let secret_bytes = [0x12u8; 32];
-    
+
     // Rust computation (Cairo-compatible)
     let rust_hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
-    
+
     // Expected hashlock from canonical test vectors (matches Cairo)
     let expected_hashlock_hex = "b6acca81a0939a856c35e4c4188e95b91731aab1d4629a4cee79dd09ded4fc94";
     let expected_hashlock: [u8; 32] = hex::decode(expected_hashlock_hex)
         .expect("Failed to decode expected hashlock")
         .try_into()
         .expect("Hashlock must be 32 bytes");
-    
+
     assert_eq!(
-        rust_hashlock,
-        expected_hashlock,
+        rust_hashlock, expected_hashlock,
         "CRITICAL: Rust and Cairo hashlock mismatch - this would cause fund loss!"
     );
-    
+
     println!("✅ Hashlock computation matches Cairo");
 }
 
@@ -47,29 +56,59 @@ fn test_dleq_challenge_rust_cairo_match() {
     let secret_bytes = [0x12u8; 32];
     let secret = Scalar::from_bytes_mod_order(secret_bytes);
     let secret_zeroizing = Zeroizing::new(secret);
-    
+
     // Compute hashlock (Cairo-compatible)
     let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
-    
+
     // Generate adaptor point
     let adaptor_point = ED25519_BASEPOINT_POINT * *secret_zeroizing;
-    
+
     // Generate DLEQ proof
     let proof = generate_dleq_proof(&secret_zeroizing, &secret_bytes, &adaptor_point, &hashlock)
         .expect("Proof generation should succeed");
-    
+
     // Verify proof structure
-    assert_ne!(proof.challenge.to_bytes(), [0u8; 32], "Challenge must be non-zero");
-    assert_ne!(proof.response.to_bytes(), [0u8; 32], "Response must be non-zero");
-    
+    assert_ne!(
+        proof.challenge.to_bytes(),
+        [0u8; 32],
+        "Challenge must be non-zero"
+    );
+    assert_ne!(
+        proof.response.to_bytes(),
+        [0u8; 32],
+        "Response must be non-zero"
+    );
+
     // Verify U = t·Y
-    let Y = ED25519_BASEPOINT_POINT * Scalar::from(2u64);
+    let Y = second_generator();
     let expected_U = Y * *secret_zeroizing;
     assert_eq!(proof.second_point, expected_U, "U must equal t·Y");
-    
+
     println!("✅ DLEQ proof structure is valid");
 }
 
+/// Known-answer vector for the hash-to-curve second generator.
+///
+/// Pins U = t·Y for the canonical secret so the Cairo side can check its
+/// constants against the same value when it switches away from 2·G.
+#[test]
+#[cfg(feature = "h2c-second-generator")]
+fn test_h2c_second_point_vector() {
+    let secret_bytes = [0x12u8; 32];
+    let secret = Zeroizing::new(Scalar::from_bytes_mod_order(secret_bytes));
+    let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
+    let adaptor_point = ED25519_BASEPOINT_POINT * *secret;
+
+    let proof = generate_dleq_proof(&secret, &secret_bytes, &adaptor_point, &hashlock)
+        .expect("Proof generation should succeed");
+
+    assert_eq!(
+        hex::encode(proof.second_point.compress().to_bytes()),
+        "32c8eaca7aa7b31bb4bdaf38d1629ea228e8ff2f81c494f69e2917c4d05d6096",
+        "U = t·Y changed for the h2c second generator"
+    );
+}
+
 /// Test that full proof verifies correctly.
 ///
 /// This is a sanity check that the proof can be verified using
@@ -80,44 +119,44 @@ fn test_full_proof_verifies() {
     let secret_bytes = [0x42u8; 32];
     let secret = Scalar::from_bytes_mod_order(secret_bytes);
     let secret_zeroizing = Zeroizing::new(secret);
-    
+
     // Compute hashlock (Cairo-compatible)
     let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
-    
+
     // Generate adaptor point
     let adaptor_point = ED25519_BASEPOINT_POINT * *secret_zeroizing;
-    
+
     // Generate DLEQ proof
     let proof = generate_dleq_proof(&secret_zeroizing, &secret_bytes, &adaptor_point, &hashlock)
         .expect("Proof generation should succeed");
-    
+
     // Verify DLEQ equations:
     // s·G = R1 + c·T
     // s·Y = R2 + c·U
-    
+
     let G = ED25519_BASEPOINT_POINT;
-    let Y = G * Scalar::from(2u64);
-    
+    let Y = second_generator();
+
     // Compute s·G
     let s_g = G * proof.response;
-    
+
     // Compute R1 + c·T
     let c_t = adaptor_point * proof.challenge;
     let r1_plus_ct = proof.r1 + c_t;
-    
+
     // Verify first equation
     assert_eq!(s_g, r1_plus_ct, "DLEQ equation 1 failed: s·G = R1 + c·T");
-    
+
     // Compute s·Y
     let s_y = Y * proof.response;
-    
+
     // Compute R2 + c·U
     let c_u = proof.second_point * proof.challenge;
     let r2_plus_cu = proof.r2 + c_u;
-    
+
     // Verify second equation
     assert_eq!(s_y, r2_plus_cu, "DLEQ equation 2 failed: s·Y = R2 + c·U");
-    
+
     println!("✅ Full DLEQ proof verifies correctly");
 }
 
@@ -128,12 +167,15 @@ fn test_full_proof_verifies() {
 fn test_hashlock_collision_resistance() {
     let secret1_bytes = [0x12u8; 32];
     let secret2_bytes = [0x34u8; 32];
-    
+
     let hashlock1: [u8; 32] = Sha256::digest(secret1_bytes).into();
     let hashlock2: [u8; 32] = Sha256::digest(secret2_bytes).into();
-    
-    assert_ne!(hashlock1, hashlock2, "Different secrets must produce different hashlocks");
-    
+
+    assert_ne!(
+        hashlock1, hashlock2,
+        "Different secrets must produce different hashlocks"
+    );
+
     println!("✅ Hashlock collision resistance verified");
 }
 
@@ -145,25 +187,28 @@ fn test_scalar_reduction_warning() {
     // Use secret that triggers scalar reduction
     let secret_bytes = [0x12u8; 32];
     let secret = Scalar::from_bytes_mod_order(secret_bytes);
-    
+
     // Check if scalar reduction changed bytes
     let scalar_bytes = secret.to_bytes();
     let reduction_changed = secret_bytes != scalar_bytes;
-    
+
     if reduction_changed {
         println!("⚠️  Scalar reduction changed bytes (expected for this test vector)");
         println!("    Raw:    {}", hex::encode(secret_bytes));
         println!("    Scalar: {}", hex::encode(scalar_bytes));
     }
-    
+
     // The function should still work with raw bytes
     let secret_zeroizing = Zeroizing::new(secret);
     let hashlock: [u8; 32] = Sha256::digest(secret_bytes).into();
     let adaptor_point = ED25519_BASEPOINT_POINT * *secret_zeroizing;
-    
+
     let result = generate_dleq_proof(&secret_zeroizing, &secret_bytes, &adaptor_point, &hashlock);
-    assert!(result.is_ok(), "Proof generation should succeed even if scalar reduction changed bytes");
-    
+    assert!(
+        result.is_ok(),
+        "Proof generation should succeed even if scalar reduction changed bytes"
+    );
+
     println!("✅ Scalar reduction handling verified");
 }
 
@@ -173,28 +218,34 @@ fn test_scalar_reduction_warning() {
 /// before attempting deployment.
 #[test]
 fn test_deployment_vector_is_valid() {
-    use std::fs;
     use serde_json::Value;
-    
+    use std::fs;
+
     let vector_path = "deployment_vector.json";
-    let vector = fs::read_to_string(vector_path)
-        .unwrap_or_else(|_| {
-            // Try canonical vectors as fallback
-            fs::read_to_string("canonical_test_vectors.json")
-                .expect("Neither deployment_vector.json nor canonical_test_vectors.json found")
-        });
-    
-    let json: Value = serde_json::from_str(&vector)
-        .expect("Invalid JSON");
-    
+    let vector = fs::read_to_string(vector_path).unwrap_or_else(|_| {
+        // Try canonical vectors as fallback
+        fs::read_to_string("canonical_test_vectors.json")
+            .expect("Neither deployment_vector.json nor canonical_test_vectors.json found")
+    });
+
+    let json: Value = serde_json::from_str(&vector).expect("Invalid JSON");
+
     // Required fields for deployment
     let required = [
-        "secret", "hashlock", "adaptor_point_compressed",
-        "dleq_second_point_compressed", "challenge", "response",
-        "g_compressed", "y_compressed", "r1_compressed", "r2_compressed",
-        "adaptor_point_sqrt_hint", "second_point_sqrt_hint"
+        "secret",
+        "hashlock",
+        "adaptor_point_compressed",
+        "dleq_second_point_compressed",
+        "challenge",
+        "response",
+        "g_compressed",
+        "y_compressed",
+        "r1_compressed",
+        "r2_compressed",
+        "adaptor_point_sqrt_hint",
+        "second_point_sqrt_hint",
     ];
-    
+
     for field in &required {
         assert!(
             json.get(field).is_some(),
@@ -202,7 +253,7 @@ fn test_deployment_vector_is_valid() {
             field
         );
     }
-    
+
     // Verify hashlock format (64 hex chars = 32 bytes)
     let hashlock = json["hashlock"].as_str().unwrap();
     assert_eq!(
@@ -210,19 +261,14 @@ fn test_deployment_vector_is_valid() {
         64,
         "Hashlock must be 64 hex chars (32 bytes)"
     );
-    
+
     // Verify all hex fields are valid hex
     for field in &["secret", "challenge", "response"] {
         let value = json[field].as_str().unwrap();
-        assert_eq!(
-            value.len(),
-            64,
-            "Field {} must be 64 hex chars",
-            field
-        );
+        assert_eq!(value.len(), 64, "Field {} must be 64 hex chars", field);
         hex::decode(value).expect(&format!("Field {} is not valid hex", field));
     }
-    
+
     println!("✅ Deployment vector is valid and complete");
 }
 
@@ -232,9 +278,9 @@ fn test_deployment_vector_is_valid() {
 #[test]
 #[ignore] // Ignore if Python tool not available
 fn test_hints_generation_succeeds() {
-    use std::process::Command;
     use std::path::Path;
-    
+    use std::process::Command;
+
     // Verify deployment vector exists
     let vector_path = if Path::new("deployment_vector.json").exists() {
         "deployment_vector.json"
@@ -243,16 +289,13 @@ fn test_hints_generation_succeeds() {
     } else {
         panic!("No test vector file found");
     };
-    
+
     // Try to run hint generation (may fail if Python tool not available)
     let output = Command::new("python3")
-        .args(&[
-            "tools/generate_hints_from_test_vectors.py",
-            vector_path
-        ])
+        .args(&["tools/generate_hints_from_test_vectors.py", vector_path])
         .current_dir("..")
         .output();
-    
+
     match output {
         Ok(result) => {
             if result.status.success() {
@@ -269,4 +312,3 @@ fn test_hints_generation_succeeds() {
         }
     }
 }
-
@@ -0,0 +1,77 @@
+//! `xmr_secret_gen` command line: argument validation and the files written
+//! by batch mode.
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+fn secret_gen() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_xmr_secret_gen"))
+}
+
+#[test]
+fn test_batch_writes_secrets_and_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    secret_gen()
+        .args(["--count", "3", "--out-dir"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    let manifest: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("manifest.json")).unwrap())
+            .unwrap();
+    assert_eq!(manifest["count"], 3);
+    let entries = manifest["secrets"].as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+
+    let mut hashlocks = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let file = format!("secret_{:04}.json", index);
+        assert_eq!(entry["index"], index);
+        assert_eq!(entry["file"], file.as_str());
+
+        let secret: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join(&file)).unwrap())
+                .unwrap();
+        let words: String = secret["hash_u32_words"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| format!("{:08x}", w.as_u64().unwrap()))
+            .collect();
+        assert_eq!(entry["hashlock"], words.as_str());
+        hashlocks.push(words);
+    }
+    hashlocks.sort();
+    hashlocks.dedup();
+    assert_eq!(hashlocks.len(), 3, "batch secrets must be independent");
+}
+
+#[test]
+fn test_batch_rejects_single_secret_options() {
+    let dir = tempfile::tempdir().unwrap();
+    for extra in [["--format", "json"], ["--out", "fixture.cairo"]] {
+        secret_gen()
+            .args(["--count", "2", "--out-dir"])
+            .arg(dir.path())
+            .args(extra)
+            .assert()
+            .failure();
+    }
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_out_dir_requires_count() {
+    let dir = tempfile::tempdir().unwrap();
+    secret_gen()
+        .arg("--out-dir")
+        .arg(dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_unknown_format_is_rejected() {
+    secret_gen().args(["--format", "yaml"]).assert().failure();
+}
@@ -32,7 +32,8 @@ async fn test_wallet_connection_and_balance() -> Result<()> {
         "http://localhost:38088/json_rpc".to_string(),
         "http://stagenet.xmr-tw.org:38081".to_string(),
         "atomic-swap-test".to_string(),
-    ).await?;
+    )
+    .await?;
 
     // Create or open wallet
     match wallet.create_wallet("test123").await {
@@ -60,6 +61,8 @@ async fn test_wallet_connection_and_balance() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "legacy-unlock-time")]
+#[allow(deprecated)]
 #[tokio::test]
 #[ignore]
 async fn test_locked_transaction_creation() -> Result<()> {
@@ -71,7 +74,8 @@ async fn test_locked_transaction_creation() -> Result<()> {
         "http://localhost:38088/json_rpc".to_string(),
         "http://stagenet.xmr-tw.org:38081".to_string(),
         "atomic-swap-test".to_string(),
-    ).await?;
+    )
+    .await?;
 
     wallet.open_wallet("test123").await?;
 
@@ -101,11 +105,9 @@ async fn test_locked_transaction_creation() -> Result<()> {
     println!("   Unlock time: {} blocks", unlock_height);
 
     // Create locked transaction (ATOMIC SWAP CORE FUNCTION)
-    let result = wallet.transfer_locked(
-        &destination,
-        amount_piconero,
-        unlock_height,
-    ).await?;
+    let result = wallet
+        .transfer_locked(&destination, amount_piconero, unlock_height)
+        .await?;
 
     println!("✅ Transaction created!");
     println!("   TX Hash: {}", result.tx_hash);
@@ -143,15 +145,19 @@ async fn test_ten_confirmation_safety() -> Result<()> {
         "http://localhost:38088/json_rpc".to_string(),
         "http://stagenet.xmr-tw.org:38081".to_string(),
         "atomic-swap-test".to_string(),
-    ).await?;
+    )
+    .await?;
 
     wallet.open_wallet("test123").await?;
 
     // Check balance first
     let (balance, unlocked_balance) = wallet.get_balance().await?;
-    println!("💰 Wallet balance: {} XMR (unlocked: {} XMR)", 
-             piconero_to_xmr(balance), piconero_to_xmr(unlocked_balance));
-    
+    println!(
+        "💰 Wallet balance: {} XMR (unlocked: {} XMR)",
+        piconero_to_xmr(balance),
+        piconero_to_xmr(unlocked_balance)
+    );
+
     if balance == 0 {
         println!("⚠️  Wallet has 0 balance. Skipping test.");
         println!("💡 Fund wallet via: https://stagenet-faucet.xmr-tw.org/");
@@ -162,21 +168,19 @@ async fn test_ten_confirmation_safety() -> Result<()> {
     // Create test transaction
     let destination = wallet.get_address().await?;
     let amount_piconero = xmr_to_piconero(0.01);
-    
+
     // Ensure we have enough balance
     if balance < amount_piconero {
-        println!("⚠️  Insufficient balance. Need {} XMR, have {} XMR", 
-                 piconero_to_xmr(amount_piconero), piconero_to_xmr(balance));
+        println!(
+            "⚠️  Insufficient balance. Need {} XMR, have {} XMR",
+            piconero_to_xmr(amount_piconero),
+            piconero_to_xmr(balance)
+        );
         return Ok(()); // Skip test if insufficient balance
     }
-    
-    let current_height = wallet.get_height().await?;
 
-    let result = wallet.transfer_locked(
-        &destination,
-        amount_piconero,
-        current_height + 10,
-    ).await?;
+    // Key splitting lock: no unlock_time
+    let result = wallet.transfer(&destination, amount_piconero).await?;
 
     println!("✅ Transaction created: {}", result.tx_hash);
     println!("⏳ Waiting for 10 confirmations (COMIT production standard)...");
@@ -187,13 +191,14 @@ async fn test_ten_confirmation_safety() -> Result<()> {
 
     println!("✅ 10 confirmations received!");
     println!("⏱️  Duration: {:.2} minutes", duration.as_secs_f64() / 60.0);
-    println!("📊 Average block time: {:.2} minutes", 
-             duration.as_secs_f64() / 60.0 / 10.0);
+    println!(
+        "📊 Average block time: {:.2} minutes",
+        duration.as_secs_f64() / 60.0 / 10.0
+    );
 
     // Should be ~20 minutes (2 min per block)
-    assert!(duration.as_secs() > 600);  // At least 10 minutes
+    assert!(duration.as_secs() > 600); // At least 10 minutes
     assert!(duration.as_secs() < 1800); // Less than 30 minutes
 
     Ok(())
 }
-
@@ -0,0 +1,323 @@
+// SMTP email channel
+//
+// Alerts are sent as plain-text mail, subject `[Critical] <title>`. Each
+// recipient list has a minimum level, so the on-call address can get only
+// Critical alerts while the team list gets everything:
+//
+//   SMTP_TO            every alert
+//   SMTP_TO_WARNING    Warning and Critical
+//   SMTP_TO_CRITICAL   Critical only
+//
+// An address on several lists gets one copy. Alerts no list covers are not
+// mailed. TLS is STARTTLS on port 587 by default (`SMTP_TLS=tls` for
+// implicit TLS on 465, `none` for a local relay on 25 only).
+
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::types::{Alert, AlertLevel};
+
+/// SMTP connection and command timeout
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Transport security
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (required)
+    #[default]
+    Starttls,
+    /// TLS from the first byte
+    Tls,
+    /// Unencrypted, for a relay on localhost
+    None,
+}
+
+impl SmtpTls {
+    pub fn default_port(self) -> u16 {
+        match self {
+            SmtpTls::Starttls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        }
+    }
+}
+
+impl std::str::FromStr for SmtpTls {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "starttls" => Ok(SmtpTls::Starttls),
+            "tls" => Ok(SmtpTls::Tls),
+            "none" => Ok(SmtpTls::None),
+            other => bail!("unknown SMTP TLS mode '{}' (starttls, tls, none)", other),
+        }
+    }
+}
+
+/// Unvalidated email settings, from the environment or watchtower.toml
+#[derive(Debug, Clone, Default)]
+pub struct EmailFields {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub tls: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub to_warning: Vec<String>,
+    pub to_critical: Vec<String>,
+}
+
+impl EmailFields {
+    /// `SMTP_HOST`, `SMTP_PORT`, `SMTP_TLS`, `SMTP_USERNAME`,
+    /// `SMTP_PASSWORD`, `SMTP_FROM` and the comma-separated `SMTP_TO*` lists
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let list = |name: &str| {
+            var(name)
+                .map(|list| list.split(',').map(|a| a.trim().to_string()).collect())
+                .unwrap_or_default()
+        };
+        Ok(Self {
+            host: var("SMTP_HOST"),
+            port: var("SMTP_PORT")
+                .map(|port| port.trim().parse())
+                .transpose()
+                .context("Invalid SMTP_PORT")?,
+            tls: var("SMTP_TLS"),
+            username: var("SMTP_USERNAME"),
+            password: var("SMTP_PASSWORD"),
+            from: var("SMTP_FROM"),
+            to: list("SMTP_TO"),
+            to_warning: list("SMTP_TO_WARNING"),
+            to_critical: list("SMTP_TO_CRITICAL"),
+        })
+    }
+}
+
+/// Validated email channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub credentials: Option<(String, String)>,
+    pub from: Mailbox,
+    /// Recipients with the lowest level they receive
+    pub recipients: Vec<(AlertLevel, Mailbox)>,
+}
+
+impl EmailConfig {
+    /// None without `host`; errors name the offending field
+    pub fn from_fields(fields: EmailFields) -> Result<Option<Self>> {
+        let Some(host) = fields.host else {
+            let lists = [&fields.to, &fields.to_warning, &fields.to_critical];
+            if fields.from.is_some() || lists.iter().any(|list| !list.is_empty()) {
+                bail!("SMTP recipients configured without an SMTP host");
+            }
+            return Ok(None);
+        };
+        let tls: SmtpTls = fields
+            .tls
+            .as_deref()
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let credentials = match (fields.username, fields.password) {
+            (Some(username), Some(password)) => Some((username, password)),
+            (None, None) => None,
+            _ => bail!("SMTP username and password must be set together"),
+        };
+        let from = fields
+            .from
+            .context("SMTP sender (from) is not set")?
+            .parse()
+            .context("Invalid SMTP sender")?;
+        let mut recipients: Vec<(AlertLevel, Mailbox)> = Vec::new();
+        for (level, list) in [
+            (AlertLevel::Info, fields.to),
+            (AlertLevel::Warning, fields.to_warning),
+            (AlertLevel::Critical, fields.to_critical),
+        ] {
+            for address in list {
+                let mailbox: Mailbox = address
+                    .parse()
+                    .with_context(|| format!("Invalid SMTP recipient '{}'", address))?;
+                // An address on several lists keeps its lowest level
+                if !recipients.iter().any(|(_, r)| r.email == mailbox.email) {
+                    recipients.push((level, mailbox));
+                }
+            }
+        }
+        if recipients.is_empty() {
+            bail!("SMTP host set but no recipients");
+        }
+        Ok(Some(Self {
+            port: fields.port.unwrap_or(tls.default_port()),
+            host,
+            tls,
+            credentials,
+            from,
+            recipients,
+        }))
+    }
+
+    /// Recipients of an alert of `level`
+    pub fn recipients(&self, level: AlertLevel) -> Vec<Mailbox> {
+        self.recipients
+            .iter()
+            .filter(|(min_level, _)| level >= *min_level)
+            .map(|(_, mailbox)| mailbox.clone())
+            .collect()
+    }
+
+    /// The mail for `alert`, or None if no list covers its level
+    pub fn message(&self, alert: &Alert) -> Result<Option<Message>> {
+        let recipients = self.recipients(alert.level);
+        if recipients.is_empty() {
+            return Ok(None);
+        }
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(format!("[{:?}] {}", alert.level, alert.title))
+            .header(ContentType::TEXT_PLAIN);
+        for recipient in recipients {
+            builder = builder.to(recipient);
+        }
+        let body = format!(
+            "{}\n\nContract: 0x{:x}\nLevel: {:?}\n",
+            alert.message, alert.contract_address, alert.level
+        );
+        Ok(Some(builder.body(body)?))
+    }
+
+    /// Send `alert` to the recipients of its level
+    pub async fn send(&self, alert: &Alert) -> Result<()> {
+        let Some(message) = self.message(alert)? else {
+            return Ok(());
+        };
+        let builder = match self.tls {
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host)?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host),
+        };
+        let mut builder = builder.port(self.port).timeout(Some(SMTP_TIMEOUT));
+        if let Some((username, password)) = &self.credentials {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        builder
+            .build()
+            .send(message)
+            .await
+            .with_context(|| format!("SMTP delivery via {}:{} failed", self.host, self.port))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_core::types::Felt;
+
+    fn fields() -> EmailFields {
+        EmailFields {
+            host: Some("smtp.example.com".to_string()),
+            from: Some("Watchtower <watchtower@example.com>".to_string()),
+            to: vec!["team@example.com".to_string()],
+            to_critical: vec![
+                "oncall@example.com".to_string(),
+                "team@example.com".to_string(),
+            ],
+            ..EmailFields::default()
+        }
+    }
+
+    #[test]
+    fn recipients_follow_the_alert_level() {
+        let config = EmailConfig::from_fields(fields()).unwrap().unwrap();
+        assert_eq!((config.tls, config.port), (SmtpTls::Starttls, 587));
+        let emails = |level| -> Vec<String> {
+            config
+                .recipients(level)
+                .iter()
+                .map(|m| m.email.to_string())
+                .collect()
+        };
+        assert_eq!(emails(AlertLevel::Info), ["team@example.com"]);
+        assert_eq!(
+            emails(AlertLevel::Critical),
+            ["team@example.com", "oncall@example.com"]
+        );
+
+        let alert = Alert {
+            level: AlertLevel::Critical,
+            title: "Key Image Spent Before Reveal".to_string(),
+            message: "XMR moved".to_string(),
+            contract_address: Felt::from(0x1au64),
+            timestamp: 0,
+        };
+        let mail = config.message(&alert).unwrap().unwrap().formatted();
+        let mail = String::from_utf8(mail).unwrap();
+        assert!(mail.contains("Subject: [Critical] Key Image Spent Before Reveal"));
+        assert!(mail.contains("oncall@example.com"), "{}", mail);
+        assert!(mail.contains("Contract: 0x1a"));
+
+        let only_critical = EmailFields {
+            to: Vec::new(),
+            ..fields()
+        };
+        let config = EmailConfig::from_fields(only_critical).unwrap().unwrap();
+        let info = Alert {
+            level: AlertLevel::Info,
+            ..alert
+        };
+        assert!(config.message(&info).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        assert_eq!(
+            EmailConfig::from_fields(EmailFields::default()).unwrap(),
+            None
+        );
+        let error =
+            |fields: EmailFields| format!("{:#}", EmailConfig::from_fields(fields).unwrap_err());
+
+        assert!(error(EmailFields {
+            tls: Some("ssl".to_string()),
+            ..fields()
+        })
+        .contains("ssl"));
+        assert!(error(EmailFields {
+            from: None,
+            ..fields()
+        })
+        .contains("sender"));
+        assert!(error(EmailFields {
+            to: vec!["not an address".to_string()],
+            ..fields()
+        })
+        .contains("not an address"));
+        assert!(error(EmailFields {
+            username: Some("u".to_string()),
+            ..fields()
+        })
+        .contains("password"));
+        assert!(error(EmailFields {
+            host: None,
+            ..fields()
+        })
+        .contains("host"));
+        let tls = EmailFields {
+            tls: Some("TLS".to_string()),
+            ..fields()
+        };
+        assert_eq!(EmailConfig::from_fields(tls).unwrap().unwrap().port, 465);
+    }
+}
@@ -1,2 +1,8 @@
+pub mod email;
 pub mod notifier;
+pub mod pagerduty;
+pub mod policy;
+pub mod queue;
+pub mod templates;
+pub mod webhook;
 
@@ -1,16 +1,185 @@
-use anyhow::Result;
-use reqwest::Client;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use anyhow::{Context, Result};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::{info, error};
+use starknet_core::types::Felt;
+use tracing::{info, error, warn};
 
+use super::email::{EmailConfig, EmailFields};
+use super::pagerduty::{trigger_event, PAGERDUTY_EVENTS_URL};
+use super::policy::{ChannelPolicy, LevelRoutes};
+use super::queue::{QueuedAlert, RetryQueue};
+use super::webhook::{WebhookConfig, WebhookPayload, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::metrics::METRICS;
 use crate::types::{Alert, AlertLevel};
 
+/// Discord embed description limit used by default (characters)
+pub const DISCORD_MAX_CHARS: usize = 2000;
+/// Telegram message limit (characters)
+pub const TELEGRAM_MAX_CHARS: usize = 4096;
+/// Default number of chunks before falling back to a file attachment
+pub const DEFAULT_MAX_CHUNKS: usize = 3;
+
+/// Per-channel message length limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageLimits {
+    /// Maximum characters per Discord message
+    pub discord_max_chars: usize,
+    /// Maximum characters per Telegram message
+    pub telegram_max_chars: usize,
+    /// Longer messages are sent as a text attachment instead of this many chunks
+    pub max_chunks: usize,
+}
+
+impl Default for MessageLimits {
+    fn default() -> Self {
+        Self {
+            discord_max_chars: DISCORD_MAX_CHARS,
+            telegram_max_chars: TELEGRAM_MAX_CHARS,
+            max_chunks: DEFAULT_MAX_CHUNKS,
+        }
+    }
+}
+
+/// Channel credentials (rotatable at runtime)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotifierCredentials {
+    pub discord_webhook: Option<String>,
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    /// Base URL, e.g. `https://matrix.org`
+    pub matrix_homeserver: Option<String>,
+    pub matrix_access_token: Option<String>,
+    /// Room id (`!abc:matrix.org`), not an alias
+    pub matrix_room_id: Option<String>,
+    /// Events API v2 integration key (Critical alerts only)
+    pub pagerduty_routing_key: Option<String>,
+    pub webhook: Option<WebhookConfig>,
+    pub email: Option<EmailConfig>,
+}
+
+impl NotifierCredentials {
+    /// Read `DISCORD_WEBHOOK`, `TELEGRAM_BOT_TOKEN`, `TELEGRAM_CHAT_ID` and
+    /// `PAGERDUTY_ROUTING_KEY` and the `MATRIX_*`, `WEBHOOK_*` and `SMTP_*`
+    /// variables
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            discord_webhook: std::env::var("DISCORD_WEBHOOK").ok(),
+            telegram_bot_token: std::env::var("TELEGRAM_BOT_TOKEN").ok(),
+            telegram_chat_id: std::env::var("TELEGRAM_CHAT_ID").ok(),
+            matrix_homeserver: std::env::var("MATRIX_HOMESERVER").ok(),
+            matrix_access_token: std::env::var("MATRIX_ACCESS_TOKEN").ok(),
+            matrix_room_id: std::env::var("MATRIX_ROOM_ID").ok(),
+            pagerduty_routing_key: std::env::var("PAGERDUTY_ROUTING_KEY").ok(),
+            webhook: WebhookConfig::from_env()?,
+            email: EmailConfig::from_fields(EmailFields::from_env()?)?,
+        })
+    }
+
+    /// Fill the credentials missing here from `fallback`
+    ///
+    /// The webhook and the email settings are taken as a whole: a URL from
+    /// here is never signed with a secret from `fallback`.
+    pub fn or(self, fallback: NotifierCredentials) -> Self {
+        Self {
+            discord_webhook: self.discord_webhook.or(fallback.discord_webhook),
+            telegram_bot_token: self.telegram_bot_token.or(fallback.telegram_bot_token),
+            telegram_chat_id: self.telegram_chat_id.or(fallback.telegram_chat_id),
+            matrix_homeserver: self.matrix_homeserver.or(fallback.matrix_homeserver),
+            matrix_access_token: self.matrix_access_token.or(fallback.matrix_access_token),
+            matrix_room_id: self.matrix_room_id.or(fallback.matrix_room_id),
+            pagerduty_routing_key: self.pagerduty_routing_key.or(fallback.pagerduty_routing_key),
+            webhook: self.webhook.or(fallback.webhook),
+            email: self.email.or(fallback.email),
+        }
+    }
+}
+
+/// Alert delivery channel
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Discord,
+    Telegram,
+    Matrix,
+    Webhook,
+    Email,
+    PagerDuty,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 6] = [
+        Channel::Discord,
+        Channel::Telegram,
+        Channel::Matrix,
+        Channel::Webhook,
+        Channel::Email,
+        Channel::PagerDuty,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Discord => "discord",
+            Channel::Telegram => "telegram",
+            Channel::Matrix => "matrix",
+            Channel::Webhook => "webhook",
+            Channel::Email => "email",
+            Channel::PagerDuty => "pagerduty",
+        }
+    }
+}
+
+impl std::str::FromStr for Channel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase();
+        Channel::ALL
+            .into_iter()
+            .find(|channel| channel.name() == name)
+            .with_context(|| {
+                let names: Vec<&str> = Channel::ALL.iter().map(|c| c.name()).collect();
+                format!("unknown channel '{}' ({})", s.trim(), names.join(", "))
+            })
+    }
+}
+
+/// Configured channels and the ones whose last alert failed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NotifierStatus {
+    pub channels: Vec<Channel>,
+    /// Error of the last delivery, per failing channel
+    pub failing: BTreeMap<Channel, String>,
+}
+
 /// Alert notifier supporting multiple channels
+///
+/// Clones share credentials, so a rotation also reaches alerts scheduled
+/// before it (e.g. grace-period warnings).
+#[derive(Clone)]
 pub struct Notifier {
     client: Client,
-    discord_webhook: Option<String>,
-    telegram_bot_token: Option<String>,
-    telegram_chat_id: Option<String>,
+    credentials: Arc<RwLock<NotifierCredentials>>,
+    limits: MessageLimits,
+    /// Minimum level, quiet hours and rate limit (default: deliver everything)
+    policies: Arc<HashMap<Channel, ChannelPolicy>>,
+    /// Recent send times per rate-limited channel
+    sent: Arc<Mutex<HashMap<Channel, VecDeque<u64>>>>,
+    /// Contracts whose alerts go to some channels only
+    contract_channels: Arc<HashMap<Felt, Vec<Channel>>>,
+    /// Levels whose alerts go to some channels only
+    level_channels: Arc<LevelRoutes>,
+    /// Channels whose last delivery failed, with the error
+    failing: Arc<RwLock<BTreeMap<Channel, String>>>,
+    /// Failed deliveries awaiting a retry
+    queue: RetryQueue,
 }
 
 impl Notifier {
@@ -19,57 +188,314 @@ impl Notifier {
         telegram_bot_token: Option<String>,
         telegram_chat_id: Option<String>,
     ) -> Self {
-        Self {
-            client: Client::new(),
+        Self::from_credentials(NotifierCredentials {
             discord_webhook,
             telegram_bot_token,
             telegram_chat_id,
+            matrix_homeserver: None,
+            matrix_access_token: None,
+            matrix_room_id: None,
+            pagerduty_routing_key: None,
+            webhook: None,
+            email: None,
+        })
+    }
+
+    pub fn from_credentials(credentials: NotifierCredentials) -> Self {
+        Self {
+            client: Client::new(),
+            credentials: Arc::new(RwLock::new(credentials)),
+            limits: MessageLimits::default(),
+            policies: Arc::default(),
+            sent: Arc::default(),
+            contract_channels: Arc::default(),
+            level_channels: Arc::default(),
+            failing: Arc::default(),
+            queue: RetryQueue::default(),
         }
     }
 
+    /// Replace the channel credentials; alerts already being sent finish
+    /// with the old ones
+    pub fn rotate_credentials(&self, credentials: NotifierCredentials) {
+        *self.credentials.write().unwrap_or_else(|e| e.into_inner()) = credentials;
+    }
+
+    /// Override the per-channel message limits
+    pub fn with_limits(mut self, limits: MessageLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Minimum level, quiet hours and rate limit for each channel
+    pub fn with_policies(mut self, policies: HashMap<Channel, ChannelPolicy>) -> Self {
+        self.policies = Arc::new(policies);
+        self
+    }
+
+    fn allows_now(&self, channel: Channel, level: AlertLevel) -> bool {
+        self.policies
+            .get(&channel)
+            .is_none_or(|policy| policy.allows_now(level))
+    }
+
+    /// Whether `channel` is under its rate limit; Critical alerts always are
+    fn within_rate_limit(&self, channel: Channel, level: AlertLevel) -> bool {
+        let Some(limit) = self.policies.get(&channel).and_then(|policy| policy.rate_limit) else {
+            return true;
+        };
+        if level == AlertLevel::Critical {
+            return true;
+        }
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        limit.admit(sent.entry(channel).or_default(), unix_now())
+    }
+
+    /// Queue for failed deliveries (default: in memory only)
+    pub fn with_retry_queue(mut self, queue: RetryQueue) -> Self {
+        self.queue = queue;
+        self
+    }
+
+    /// Restrict the alerts of some contracts to the given channels
+    pub fn with_contract_channels(mut self, channels: HashMap<Felt, Vec<Channel>>) -> Self {
+        self.contract_channels = Arc::new(channels);
+        self
+    }
+
+    fn routes(&self, contract: Felt, channel: Channel) -> bool {
+        self.contract_channels
+            .get(&contract)
+            .is_none_or(|channels| channels.contains(&channel))
+    }
+
+    /// Send alerts of some levels to the given channels only
+    pub fn with_level_channels(mut self, channels: LevelRoutes) -> Self {
+        self.level_channels = Arc::new(channels);
+        self
+    }
+
+    /// Without a route for `level`, every channel but PagerDuty takes it;
+    /// PagerDuty pages on Critical alerts only, the rest stays in chat
+    fn routes_level(&self, level: AlertLevel, channel: Channel) -> bool {
+        match self.level_channels.get(&level) {
+            Some(channels) => channels.contains(&channel),
+            None => channel != Channel::PagerDuty || level == AlertLevel::Critical,
+        }
+    }
+
+    /// Channels with credentials and their delivery state (for `/health`)
+    pub fn status(&self) -> NotifierStatus {
+        let credentials = self.credentials();
+        let channels = Channel::ALL
+            .into_iter()
+            .filter(|channel| credentials.configured(*channel))
+            .collect();
+        NotifierStatus {
+            channels,
+            failing: self.failing.read().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+
+    /// Count a failed delivery, and remember it until the channel succeeds
+    fn record_delivery(&self, channel: Channel, result: &Result<()>) {
+        let mut failing = self.failing.write().unwrap_or_else(|e| e.into_inner());
+        match result {
+            Ok(()) => {
+                failing.remove(&channel);
+            }
+            Err(e) => {
+                METRICS.alert_failures.with_label_values(&[channel.name()]).inc();
+                failing.insert(channel, format!("{:#}", e));
+            }
+        }
+    }
+
+    /// Deliver `alert` to every configured channel that takes it
+    ///
+    /// A failed channel does not hold up the others: its delivery is queued
+    /// and retried with backoff (see `queue`).
     pub async fn send_alert(&self, alert: &Alert) -> Result<()> {
         info!("Sending alert: {} - {}", alert.title, alert.message);
-        
-        // Send to Discord
-        if let Some(ref webhook) = self.discord_webhook {
-            self.send_discord(webhook, alert).await?;
-        }
-        
-        // Send to Telegram
-        if let (Some(ref token), Some(ref chat_id)) = 
-            (&self.telegram_bot_token, &self.telegram_chat_id) 
-        {
-            self.send_telegram(token, chat_id, alert).await?;
-        }
-        
+        // Snapshot so the lock is not held across sends
+        let credentials = self.credentials();
+
+        for channel in Channel::ALL {
+            if !credentials.configured(channel) {
+                continue;
+            }
+            if !self.routes_level(alert.level, channel) {
+                continue;
+            }
+            if !self.routes(alert.contract_address, channel) {
+                info!("{}: not a channel of contract {:x}", channel.name(), alert.contract_address);
+            } else if !self.allows_now(channel, alert.level) {
+                info!("{}: suppressed {:?} alert '{}'", channel.name(), alert.level, alert.title);
+            } else if !self.within_rate_limit(channel, alert.level) {
+                warn!("{}: rate limited, dropping {:?} alert '{}'", channel.name(), alert.level, alert.title);
+                METRICS.alerts_rate_limited.with_label_values(&[channel.name()]).inc();
+            } else if let Err(e) = self.deliver(&credentials, channel, alert).await {
+                warn!("{}: failed to send '{}', queued for retry: {:#}", channel.name(), alert.title, e);
+                self.queue.push(alert.clone(), channel, &e, unix_now());
+            }
+        }
+
         Ok(())
     }
 
+    /// Retry queued deliveries in the background
+    pub fn spawn_retries(&self) -> tokio::task::JoinHandle<()> {
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RETRY_POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                notifier.retry_due().await;
+            }
+        })
+    }
+
+    /// Try every queued delivery once more, due or not (e.g. at shutdown)
+    ///
+    /// Returns the number of deliveries still queued.
+    pub async fn flush_queue(&self) -> usize {
+        self.retry(self.queue.due(u64::MAX)).await;
+        self.queue.len()
+    }
+
+    async fn retry_due(&self) {
+        self.retry(self.queue.due(unix_now())).await;
+    }
+
+    async fn retry(&self, entries: Vec<QueuedAlert>) {
+        let credentials = self.credentials();
+        for entry in entries {
+            if !credentials.configured(entry.channel) {
+                info!(
+                    "{}: no longer configured, dropping queued alert '{}'",
+                    entry.channel.name(),
+                    entry.alert.title
+                );
+                self.queue.discard(entry.id);
+                continue;
+            }
+            let result = self.deliver(&credentials, entry.channel, &entry.alert).await;
+            self.queue.resolve(entry.id, &result, unix_now());
+        }
+    }
+
+    fn credentials(&self) -> NotifierCredentials {
+        self.credentials
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// One attempt to send `alert` to `channel`
+    async fn deliver(
+        &self,
+        credentials: &NotifierCredentials,
+        channel: Channel,
+        alert: &Alert,
+    ) -> Result<()> {
+        let result = match channel {
+            Channel::Discord => match &credentials.discord_webhook {
+                Some(webhook) => self.send_discord(webhook, alert).await,
+                None => Err(not_configured(channel)),
+            },
+            Channel::Telegram => {
+                match (&credentials.telegram_bot_token, &credentials.telegram_chat_id) {
+                    (Some(token), Some(chat_id)) => self.send_telegram(token, chat_id, alert).await,
+                    _ => Err(not_configured(channel)),
+                }
+            }
+            Channel::Matrix => match credentials.matrix() {
+                Some((homeserver, token, room_id)) => {
+                    self.send_matrix(homeserver, token, room_id, alert).await
+                }
+                None => Err(not_configured(channel)),
+            },
+            Channel::Webhook => match &credentials.webhook {
+                Some(webhook) => self.send_webhook(webhook, alert).await,
+                None => Err(not_configured(channel)),
+            },
+            Channel::Email => match &credentials.email {
+                Some(email) => email.send(alert).await,
+                None => Err(not_configured(channel)),
+            },
+            Channel::PagerDuty => match &credentials.pagerduty_routing_key {
+                Some(routing_key) => self.send_pagerduty(routing_key, alert).await,
+                None => Err(not_configured(channel)),
+            },
+        };
+        self.record_delivery(channel, &result);
+        result
+    }
+
     async fn send_discord(&self, webhook: &str, alert: &Alert) -> Result<()> {
         let color = match alert.level {
             AlertLevel::Info => 0x00FF00,     // Green
             AlertLevel::Warning => 0xFFFF00,  // Yellow
             AlertLevel::Critical => 0xFF0000, // Red
         };
-        let payload = json!({
-            "embeds": [{
-                "title": alert.title,
-                "description": alert.message,
-                "color": color,
-                "fields": [{
-                    "name": "Contract",
-                    "value": format!("0x{:x}", alert.contract_address),
-                    "inline": true
-                }],
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }]
-        });
-        self.client
-            .post(webhook)
-            .json(&payload)
-            .send()
-            .await?;
-        
+        let chunks = chunk_message(&alert.message, self.limits.discord_max_chars);
+
+        if chunks.len() > self.limits.max_chunks {
+            warn!("Alert too long for {} Discord messages, sending as attachment", self.limits.max_chunks);
+            let payload = json!({
+                "embeds": [{
+                    "title": alert.title,
+                    "description": attachment_preview(&chunks[0]),
+                    "color": color,
+                    "fields": [{
+                        "name": "Contract",
+                        "value": format!("0x{:x}", alert.contract_address),
+                        "inline": true
+                    }],
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                }]
+            });
+            let form = Form::new()
+                .text("payload_json", payload.to_string())
+                .part("files[0]", attachment_part(alert)?);
+            self.client.post(webhook).multipart(form).send().await?.error_for_status()?;
+            return Ok(());
+        }
+
+        let total = chunks.len();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            // Only the first message carries the contract field
+            let payload = if i == 0 {
+                json!({
+                    "embeds": [{
+                        "title": alert.title,
+                        "description": chunk,
+                        "color": color,
+                        "fields": [{
+                            "name": "Contract",
+                            "value": format!("0x{:x}", alert.contract_address),
+                            "inline": true
+                        }],
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    }]
+                })
+            } else {
+                json!({
+                    "embeds": [{
+                        "title": format!("{} ({}/{})", alert.title, i + 1, total),
+                        "description": chunk,
+                        "color": color
+                    }]
+                })
+            };
+            self.client
+                .post(webhook)
+                .json(&payload)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
         Ok(())
     }
 
@@ -84,28 +510,314 @@ impl Notifier {
             AlertLevel::Warning => "⚠️",
             AlertLevel::Critical => "🚨",
         };
-        let text = format!(
-            "{} *{}*\n\n{}\n\nContract: `0x{:x}`",
-            emoji,
-            alert.title,
-            alert.message,
-            alert.contract_address
-        );
+        let header = format!("{} *{}*\n\n", emoji, alert.title);
+        let footer = format!("\n\nContract: `0x{:x}`", alert.contract_address);
+        // Header and footer go on the first and last chunk respectively
+        let budget = self
+            .limits
+            .telegram_max_chars
+            .saturating_sub(header.chars().count() + footer.chars().count())
+            .max(1);
+        let chunks = chunk_message(&alert.message, budget);
+
+        if chunks.len() > self.limits.max_chunks {
+            warn!("Alert too long for {} Telegram messages, sending as attachment", self.limits.max_chunks);
+            let url = format!(
+                "https://api.telegram.org/bot{}/sendDocument",
+                token
+            );
+            let caption = format!("{}{}{}", header, attachment_preview(&chunks[0]), footer);
+            let form = Form::new()
+                .text("chat_id", chat_id.to_string())
+                .text("caption", truncate_chars(&caption, TELEGRAM_CAPTION_MAX_CHARS))
+                .part("document", attachment_part(alert)?);
+            self.client.post(&url).multipart(form).send().await?.error_for_status()?;
+            return Ok(());
+        }
+
         let url = format!(
             "https://api.telegram.org/bot{}/sendMessage",
             token
         );
+        let total = chunks.len();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let mut text = String::new();
+            if i == 0 {
+                text.push_str(&header);
+            } else {
+                text.push_str(&format!("({}/{})\n", i + 1, total));
+            }
+            text.push_str(&chunk);
+            if i + 1 == total {
+                text.push_str(&footer);
+            }
+            // Continuation chunks may split Markdown entities, send them as plain text
+            let body = if i == 0 {
+                json!({ "chat_id": chat_id, "text": text, "parse_mode": "Markdown" })
+            } else {
+                json!({ "chat_id": chat_id, "text": text })
+            };
+            self.client
+                .post(&url)
+                .json(&body)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
+        Ok(())
+    }
+
+    async fn send_matrix(
+        &self,
+        homeserver: &str,
+        token: &str,
+        room_id: &str,
+        alert: &Alert,
+    ) -> Result<()> {
+        let txn_id = format!(
+            "watchtower-{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            MATRIX_TXN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let url = matrix_send_url(homeserver, room_id, &txn_id)?;
+        self.client
+            .put(url)
+            .bearer_auth(token)
+            .json(&matrix_message(alert))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn send_pagerduty(&self, routing_key: &str, alert: &Alert) -> Result<()> {
         self.client
-            .post(&url)
-            .json(&json!({
-                "chat_id": chat_id,
-                "text": text,
-                "parse_mode": "Markdown"
-            }))
+            .post(PAGERDUTY_EVENTS_URL)
+            .json(&trigger_event(routing_key, alert))
             .send()
-            .await?;
-        
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn send_webhook(&self, webhook: &WebhookConfig, alert: &Alert) -> Result<()> {
+        let body = serde_json::to_vec(&WebhookPayload::from(alert))?;
+        let mut request = self
+            .client
+            .post(webhook.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in &webhook.headers {
+            request = request.header(name, value);
+        }
+        if let Some(ref secret) = webhook.secret {
+            let now = chrono::Utc::now().timestamp().max(0) as u64;
+            request = request
+                .header(TIMESTAMP_HEADER, now)
+                .header(SIGNATURE_HEADER, super::webhook::signature(secret, now, &body));
+        }
+        request.body(body).send().await?.error_for_status()?;
         Ok(())
     }
 }
 
+impl NotifierCredentials {
+    /// Whether `channel` has everything it needs to send
+    pub fn configured(&self, channel: Channel) -> bool {
+        match channel {
+            Channel::Discord => self.discord_webhook.is_some(),
+            Channel::Telegram => self.telegram_bot_token.is_some() && self.telegram_chat_id.is_some(),
+            Channel::Matrix => self.matrix().is_some(),
+            Channel::Webhook => self.webhook.is_some(),
+            Channel::Email => self.email.is_some(),
+            Channel::PagerDuty => self.pagerduty_routing_key.is_some(),
+        }
+    }
+
+    /// Homeserver, access token and room id, if all are set
+    fn matrix(&self) -> Option<(&str, &str, &str)> {
+        Some((
+            self.matrix_homeserver.as_deref()?,
+            self.matrix_access_token.as_deref()?,
+            self.matrix_room_id.as_deref()?,
+        ))
+    }
+}
+
+/// How often the retry queue is checked for due deliveries
+const RETRY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+fn not_configured(channel: Channel) -> anyhow::Error {
+    anyhow::anyhow!("{} is not configured", channel.name())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Makes Matrix transaction ids unique within a millisecond
+static MATRIX_TXN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// `PUT /_matrix/client/v3/rooms/{room}/send/m.room.message/{txn}`
+///
+/// The transaction id makes the homeserver drop retried duplicates.
+fn matrix_send_url(homeserver: &str, room_id: &str, txn_id: &str) -> Result<Url> {
+    let mut url = Url::parse(homeserver.trim())
+        .with_context(|| format!("Invalid Matrix homeserver '{}'", homeserver))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Invalid Matrix homeserver '{}'", homeserver))?
+        .pop_if_empty()
+        .extend([
+            "_matrix",
+            "client",
+            "v3",
+            "rooms",
+            room_id.trim(),
+            "send",
+            "m.room.message",
+            txn_id,
+        ]);
+    Ok(url)
+}
+
+/// `m.text` event with a plain body and an HTML rendering
+fn matrix_message(alert: &Alert) -> serde_json::Value {
+    let emoji = match alert.level {
+        AlertLevel::Info => "ℹ️",
+        AlertLevel::Warning => "⚠️",
+        AlertLevel::Critical => "🚨",
+    };
+    let contract = format!("0x{:x}", alert.contract_address);
+    json!({
+        "msgtype": "m.text",
+        "body": format!("{} {}\n\n{}\n\nContract: {}", emoji, alert.title, alert.message, contract),
+        "format": "org.matrix.custom.html",
+        "formatted_body": format!(
+            "{} <strong>{}</strong><br><br>{}<br><br>Contract: <code>{}</code>",
+            emoji,
+            html_escape(&alert.title),
+            html_escape(&alert.message).replace('\n', "<br>"),
+            contract
+        ),
+    })
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Telegram document caption limit (characters)
+const TELEGRAM_CAPTION_MAX_CHARS: usize = 1024;
+
+/// Characters of the message shown inline when the full text is attached
+const ATTACHMENT_PREVIEW_CHARS: usize = 500;
+
+/// Split `text` into chunks of at most `limit` characters.
+///
+/// Prefers breaking at a newline, then at a space, so hashes and addresses
+/// are only cut when a single token exceeds the limit.
+fn chunk_message(text: &str, limit: usize) -> Vec<String> {
+    let limit = limit.max(1);
+    let mut chunks = Vec::new();
+    let mut rest = text;
+
+    while rest.chars().count() > limit {
+        // Byte offset just past the `limit`-th character
+        let hard_end = rest
+            .char_indices()
+            .nth(limit)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let window = &rest[..hard_end];
+        let end = window
+            .rfind('\n')
+            .or_else(|| window.rfind(' '))
+            .filter(|&i| i > 0)
+            .unwrap_or(hard_end);
+
+        chunks.push(rest[..end].trim_end().to_string());
+        rest = rest[end..].trim_start_matches(['\n', ' ']);
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest.to_string());
+    }
+    chunks
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+fn attachment_preview(first_chunk: &str) -> String {
+    format!(
+        "{}…\n\n(full message attached)",
+        truncate_chars(first_chunk, ATTACHMENT_PREVIEW_CHARS)
+    )
+}
+
+/// Full alert text as a `.txt` attachment
+fn attachment_part(alert: &Alert) -> Result<Part> {
+    let body = format!(
+        "{}\n\n{}\n\nContract: 0x{:x}\n",
+        alert.title, alert.message, alert.contract_address
+    );
+    let filename = format!("alert-{}.txt", alert.timestamp);
+    Ok(Part::text(body)
+        .file_name(filename)
+        .mime_str("text/plain")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_requests_target_the_room_and_escape_markup() {
+        let url = matrix_send_url("https://matrix.example.org/", "!room:example.org", "t1").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://matrix.example.org/_matrix/client/v3/rooms/!room:example.org/send/m.room.message/t1"
+        );
+        assert!(matrix_send_url("matrix.example.org", "!room:example.org", "t1").is_err());
+
+        let alert = Alert {
+            level: AlertLevel::Warning,
+            title: "Grace <Period>".to_string(),
+            message: "a & b\nc".to_string(),
+            contract_address: Felt::from(0x1au64),
+            timestamp: 0,
+        };
+        let message = matrix_message(&alert);
+        assert_eq!(
+            message["formatted_body"],
+            "⚠️ <strong>Grace &lt;Period&gt;</strong><br><br>a &amp; b<br>c<br><br>Contract: <code>0x1a</code>"
+        );
+        assert_eq!(message["body"], "⚠️ Grace <Period>\n\na & b\nc\n\nContract: 0x1a");
+    }
+
+    #[test]
+    fn levels_go_to_their_routed_channels() {
+        let notifier = Notifier::new(None, None, None);
+        assert!(notifier.routes_level(AlertLevel::Info, Channel::Discord));
+        assert!(!notifier.routes_level(AlertLevel::Warning, Channel::PagerDuty));
+        assert!(notifier.routes_level(AlertLevel::Critical, Channel::PagerDuty));
+
+        let routed = notifier.with_level_channels(LevelRoutes::from([
+            (AlertLevel::Info, vec![Channel::Discord]),
+            (AlertLevel::Warning, vec![Channel::Telegram, Channel::PagerDuty]),
+        ]));
+        assert!(routed.routes_level(AlertLevel::Info, Channel::Discord));
+        assert!(!routed.routes_level(AlertLevel::Info, Channel::Telegram));
+        assert!(routed.routes_level(AlertLevel::Warning, Channel::PagerDuty));
+        assert!(routed.routes_level(AlertLevel::Critical, Channel::Email));
+
+        assert_eq!(" PagerDuty".parse::<Channel>().unwrap(), Channel::PagerDuty);
+        assert!("sms".parse::<Channel>().unwrap_err().to_string().contains("pagerduty"));
+    }
+}
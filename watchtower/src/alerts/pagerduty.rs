@@ -0,0 +1,90 @@
+// PagerDuty escalation (Events API v2)
+//
+// Critical alerts (funds at risk: grace period ending before the XMR lock
+// confirms, key image spent before the reveal, ...) open a PagerDuty
+// incident so someone is paged; Info and Warning alerts stay on the chat
+// channels unless routing sends them here too, with their own severity.
+// Repeats for the same contract and title share a dedup key, so
+// they are folded into the open incident instead of paging again.
+
+use serde_json::{json, Value};
+
+use crate::types::{Alert, AlertLevel};
+
+/// Events API v2 endpoint
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Incident source shown in PagerDuty
+const SOURCE: &str = "atomic-swap-watchtower";
+
+/// PagerDuty limits the summary to 1024 characters
+const SUMMARY_MAX_CHARS: usize = 1024;
+
+/// Incidents of the same contract and alert title are merged
+pub fn dedup_key(alert: &Alert) -> String {
+    format!("watchtower-0x{:x}-{}", alert.contract_address, alert.title)
+}
+
+/// PDS-CEF severity of an alert level
+fn severity(level: AlertLevel) -> &'static str {
+    match level {
+        AlertLevel::Info => "info",
+        AlertLevel::Warning => "warning",
+        AlertLevel::Critical => "critical",
+    }
+}
+
+/// `trigger` event for `alert`
+pub fn trigger_event(routing_key: &str, alert: &Alert) -> Value {
+    let contract = format!("0x{:x}", alert.contract_address);
+    let summary: String = format!("{} ({})", alert.title, contract)
+        .chars()
+        .take(SUMMARY_MAX_CHARS)
+        .collect();
+    let timestamp = chrono::DateTime::from_timestamp(alert.timestamp as i64, 0)
+        .unwrap_or_default()
+        .to_rfc3339();
+    json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": dedup_key(alert),
+        "payload": {
+            "summary": summary,
+            "source": SOURCE,
+            "severity": severity(alert.level),
+            "timestamp": timestamp,
+            "custom_details": {
+                "message": alert.message,
+                "contract": contract,
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_core::types::Felt;
+
+    #[test]
+    fn trigger_event_follows_events_api_v2() {
+        let alert = Alert {
+            level: AlertLevel::Critical,
+            title: "Grace Period Expiring".to_string(),
+            message: "XMR lock has 3/10 confirmations".to_string(),
+            contract_address: Felt::from(0xabcu64),
+            timestamp: 1_700_000_000,
+        };
+        let event = trigger_event("R0UT1NG", &alert);
+        assert_eq!(event["routing_key"], "R0UT1NG");
+        assert_eq!(event["event_action"], "trigger");
+        assert_eq!(event["dedup_key"], "watchtower-0xabc-Grace Period Expiring");
+        assert_eq!(event["payload"]["summary"], "Grace Period Expiring (0xabc)");
+        assert_eq!(event["payload"]["severity"], "critical");
+        assert_eq!(event["payload"]["timestamp"], "2023-11-14T22:13:20+00:00");
+        assert_eq!(
+            event["payload"]["custom_details"]["message"],
+            "XMR lock has 3/10 confirmations"
+        );
+    }
+}
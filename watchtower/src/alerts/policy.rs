@@ -0,0 +1,261 @@
+// Per-channel delivery policy
+//
+// Each channel drops alerts below its minimum level, and everything below
+// Critical during its quiet hours. Critical alerts (funds at risk) are always
+// delivered. Quiet hours are `HH:MM-HH:MM` windows in the host's local time
+// (set `TZ` to change it); a window may wrap past midnight (`22:00-07:00`).
+// Suppressed alerts are logged, not queued.
+//
+// A rate limit (`20/min`, `100/h`, `5/30s`) caps the non-Critical alerts a
+// channel sends in any sliding window of that length, so a burst (a reorg
+// replaying many blocks, a flapping node) cannot flood the channel.
+//
+// Routes send each level to a fixed set of channels instead
+// (ALERT_ROUTE_INFO=discord, ALERT_ROUTE_CRITICAL=pagerduty,webhook, ...);
+// levels without a route go to every channel.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveTime};
+
+use super::notifier::Channel;
+use crate::types::AlertLevel;
+
+/// Channels per alert level; levels without an entry go everywhere
+pub type LevelRoutes = BTreeMap<AlertLevel, Vec<Channel>>;
+
+/// `ALERT_ROUTE_INFO`, `ALERT_ROUTE_WARNING` and `ALERT_ROUTE_CRITICAL`
+/// (comma-separated channel names)
+pub fn level_routes_from_env() -> Result<LevelRoutes> {
+    let mut routes = LevelRoutes::new();
+    for level in [AlertLevel::Info, AlertLevel::Warning, AlertLevel::Critical] {
+        let name = format!("ALERT_ROUTE_{}", format!("{:?}", level).to_uppercase());
+        let Some(list) = std::env::var(&name).ok().filter(|v| !v.trim().is_empty()) else {
+            continue;
+        };
+        let channels = list
+            .split(',')
+            .filter(|c| !c.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Channel>>>()
+            .with_context(|| format!("Invalid {}", name))?;
+        routes.insert(level, channels);
+    }
+    Ok(routes)
+}
+
+/// `[start, end)` in local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .trim()
+            .split_once('-')
+            .with_context(|| format!("quiet hours '{}' are not HH:MM-HH:MM", s))?;
+        let time = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .with_context(|| format!("invalid time '{}' in quiet hours '{}'", t, s))
+        };
+        Ok(Self {
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+}
+
+/// At most `max` alerts per `period_secs`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max: usize,
+    pub period_secs: u64,
+}
+
+impl RateLimit {
+    /// Whether one more alert fits, given the send times in `sent`
+    /// (oldest first); records it at `now` if so
+    pub fn admit(&self, sent: &mut VecDeque<u64>, now: u64) -> bool {
+        while sent
+            .front()
+            .is_some_and(|&at| now.saturating_sub(at) >= self.period_secs)
+        {
+            sent.pop_front();
+        }
+        if sent.len() >= self.max {
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (max, period) = s
+            .trim()
+            .split_once('/')
+            .with_context(|| format!("rate limit '{}' is not <count>/<period>", s))?;
+        let max = max
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid count in rate limit '{}'", s))?;
+        let period = period.trim();
+        let split = period
+            .find(|c: char| !c.is_ascii_digit())
+            .with_context(|| format!("rate limit '{}' has no unit (s, min, h)", s))?;
+        let (count, unit) = period.split_at(split);
+        let count: u64 = match count {
+            "" => 1,
+            count => count
+                .parse()
+                .with_context(|| format!("invalid period in rate limit '{}'", s))?,
+        };
+        let unit_secs = match unit {
+            "s" => 1,
+            "min" => 60,
+            "h" => 3600,
+            other => bail!("unknown unit '{}' in rate limit '{}' (s, min, h)", other, s),
+        };
+        if max == 0 || count == 0 {
+            bail!("rate limit '{}' allows nothing", s);
+        }
+        Ok(Self {
+            max,
+            period_secs: count * unit_secs,
+        })
+    }
+}
+
+/// Which alerts one channel delivers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelPolicy {
+    pub min_level: Option<AlertLevel>,
+    pub quiet_hours: Vec<QuietHours>,
+    /// None: unlimited
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl ChannelPolicy {
+    /// `<CHANNEL>_MIN_LEVEL`, `<CHANNEL>_QUIET_HOURS` and
+    /// `<CHANNEL>_RATE_LIMIT`, falling back to `ALERT_MIN_LEVEL`,
+    /// `QUIET_HOURS` (comma-separated windows) and `ALERT_RATE_LIMIT`
+    pub fn from_env(channel: &str) -> Result<Self> {
+        let var = |name: &str, fallback: &str| {
+            std::env::var(format!("{}_{}", channel, name))
+                .or_else(|_| std::env::var(fallback))
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+        };
+        let min_level = var("MIN_LEVEL", "ALERT_MIN_LEVEL")
+            .map(|level| level.parse::<AlertLevel>())
+            .transpose()
+            .with_context(|| format!("Invalid {}_MIN_LEVEL", channel))?;
+        let quiet_hours = var("QUIET_HOURS", "QUIET_HOURS")
+            .map(|windows| {
+                windows
+                    .split(',')
+                    .filter(|w| !w.trim().is_empty())
+                    .map(str::parse)
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()
+            .with_context(|| format!("Invalid {}_QUIET_HOURS", channel))?
+            .unwrap_or_default();
+        let rate_limit = var("RATE_LIMIT", "ALERT_RATE_LIMIT")
+            .map(|limit| limit.parse::<RateLimit>())
+            .transpose()
+            .with_context(|| format!("Invalid {}_RATE_LIMIT", channel))?;
+        Ok(Self {
+            min_level,
+            quiet_hours,
+            rate_limit,
+        })
+    }
+
+    /// Whether an alert of `level` is delivered at local time `now`
+    pub fn allows(&self, level: AlertLevel, now: NaiveTime) -> bool {
+        if level == AlertLevel::Critical {
+            return true;
+        }
+        level >= self.min_level.unwrap_or(AlertLevel::Info)
+            && !self.quiet_hours.iter().any(|quiet| quiet.contains(now))
+    }
+
+    pub fn allows_now(&self, level: AlertLevel) -> bool {
+        self.allows(level, Local::now().time())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let night: QuietHours = "22:00-07:00".parse().unwrap();
+        assert!(night.contains(at("23:30")) && night.contains(at("03:00")));
+        assert!(!night.contains(at("07:00")) && !night.contains(at("12:00")));
+        assert!("22:00".parse::<QuietHours>().is_err());
+        assert!("25:00-07:00".parse::<QuietHours>().is_err());
+    }
+
+    #[test]
+    fn critical_breaks_through() {
+        let policy = ChannelPolicy {
+            min_level: Some(AlertLevel::Warning),
+            quiet_hours: vec!["22:00-07:00".parse().unwrap()],
+            rate_limit: None,
+        };
+        assert!(!policy.allows(AlertLevel::Info, at("12:00")));
+        assert!(policy.allows(AlertLevel::Warning, at("12:00")));
+        assert!(!policy.allows(AlertLevel::Warning, at("03:00")));
+        assert!(policy.allows(AlertLevel::Critical, at("03:00")));
+        assert!(ChannelPolicy::default().allows(AlertLevel::Info, at("03:00")));
+    }
+
+    #[test]
+    fn rate_limit_is_a_sliding_window() {
+        assert_eq!(
+            "20/min".parse::<RateLimit>().unwrap(),
+            RateLimit { max: 20, period_secs: 60 }
+        );
+        assert_eq!("5/30s".parse::<RateLimit>().unwrap().period_secs, 30);
+        assert_eq!("100 / h".parse::<RateLimit>().unwrap().period_secs, 3600);
+        for invalid in ["20", "20/", "20/10", "20/day", "0/min", "x/min"] {
+            assert!(invalid.parse::<RateLimit>().is_err(), "{}", invalid);
+        }
+
+        let limit: RateLimit = "2/10s".parse().unwrap();
+        let mut sent = VecDeque::new();
+        assert!(limit.admit(&mut sent, 100));
+        assert!(limit.admit(&mut sent, 105));
+        assert!(!limit.admit(&mut sent, 109));
+        assert!(limit.admit(&mut sent, 110));
+        assert!(!limit.admit(&mut sent, 114));
+        assert!(limit.admit(&mut sent, 115));
+    }
+}
@@ -0,0 +1,274 @@
+// Outbound alert queue with retries
+//
+// A channel that fails to deliver an alert (Discord outage, SMTP timeout,
+// ...) does not lose it: the (alert, channel) pair is queued and retried
+// with exponential backoff, 30s doubling up to 1h between attempts, for up
+// to MAX_ATTEMPTS attempts (about four hours). Other channels are not held
+// up by the failing one.
+//
+// The queue is rewritten to WATCHTOWER_ALERT_QUEUE (default
+// ./watchtower_alert_queue.json) after every change, so pending alerts
+// survive a restart. An entry stays in the file until it is delivered or
+// given up. A given-up alert is logged as an error and counted in
+// `watchtower_alerts_dropped_total{channel}`.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use super::notifier::Channel;
+use crate::metrics::METRICS;
+use crate::types::Alert;
+
+/// File used when WATCHTOWER_ALERT_QUEUE is not set
+pub const DEFAULT_QUEUE_PATH: &str = "watchtower_alert_queue.json";
+
+/// Attempts per (alert, channel), the first delivery included
+pub const MAX_ATTEMPTS: u32 = 10;
+
+const BASE_DELAY_SECS: u64 = 30;
+const MAX_DELAY_SECS: u64 = 3600;
+
+/// Delay before the next attempt, after `attempts` failed ones
+pub fn backoff(attempts: u32) -> u64 {
+    let doublings = attempts.saturating_sub(1).min(16);
+    (BASE_DELAY_SECS << doublings).min(MAX_DELAY_SECS)
+}
+
+/// An alert a channel failed to deliver
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedAlert {
+    pub id: u64,
+    pub alert: Alert,
+    pub channel: Channel,
+    /// Failed attempts so far
+    pub attempts: u32,
+    /// Unix time of the next attempt
+    pub next_attempt: u64,
+    pub last_error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueState {
+    next_id: u64,
+    entries: Vec<QueuedAlert>,
+}
+
+/// Shared retry queue, optionally backed by a file
+#[derive(Debug, Clone, Default)]
+pub struct RetryQueue {
+    state: Arc<Mutex<QueueState>>,
+    /// None: kept in memory only
+    path: Option<Arc<PathBuf>>,
+}
+
+impl RetryQueue {
+    /// `WATCHTOWER_ALERT_QUEUE`, or `watchtower_alert_queue.json`
+    pub fn from_env() -> Result<Self> {
+        let path = std::env::var("WATCHTOWER_ALERT_QUEUE")
+            .unwrap_or_else(|_| DEFAULT_QUEUE_PATH.to_string());
+        Self::open(path)
+    }
+
+    /// Resume the queue stored at `path` (empty if there is no file yet)
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let state: QueueState = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Corrupt alert queue {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => QueueState::default(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        if !state.entries.is_empty() {
+            info!(
+                "Resuming {} queued alert deliveries from {}",
+                state.entries.len(),
+                path.display()
+            );
+        }
+        METRICS.alert_queue_length.set(state.entries.len() as i64);
+        Ok(Self {
+            state: Arc::new(Mutex::new(state)),
+            path: Some(Arc::new(path)),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    /// Queue `alert` after its first delivery to `channel` failed at `now`
+    pub fn push(&self, alert: Alert, channel: Channel, error: &anyhow::Error, now: u64) {
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.entries.push(QueuedAlert {
+            id,
+            alert,
+            channel,
+            attempts: 1,
+            next_attempt: now + backoff(1),
+            last_error: format!("{:#}", error),
+        });
+        self.save(&state);
+    }
+
+    /// Entries whose next attempt is due at `now` (they stay queued)
+    pub fn due(&self, now: u64) -> Vec<QueuedAlert> {
+        self.lock()
+            .entries
+            .iter()
+            .filter(|entry| entry.next_attempt <= now)
+            .cloned()
+            .collect()
+    }
+
+    /// Record the outcome of retrying entry `id` at `now`
+    ///
+    /// Delivered entries are removed; failed ones are rescheduled, or given
+    /// up after MAX_ATTEMPTS.
+    pub fn resolve(&self, id: u64, result: &Result<()>, now: u64) {
+        let mut state = self.lock();
+        let Some(index) = state.entries.iter().position(|entry| entry.id == id) else {
+            return;
+        };
+        match result {
+            Ok(()) => {
+                let entry = state.entries.remove(index);
+                info!(
+                    "{}: delivered '{}' after {} failed attempt(s)",
+                    entry.channel.name(),
+                    entry.alert.title,
+                    entry.attempts
+                );
+            }
+            Err(e) => {
+                let entry = &mut state.entries[index];
+                entry.attempts += 1;
+                entry.last_error = format!("{:#}", e);
+                if entry.attempts >= MAX_ATTEMPTS {
+                    let entry = state.entries.remove(index);
+                    error!(
+                        "{}: giving up on {:?} alert '{}' for contract {:x} after {} attempts: {}",
+                        entry.channel.name(),
+                        entry.alert.level,
+                        entry.alert.title,
+                        entry.alert.contract_address,
+                        entry.attempts,
+                        entry.last_error
+                    );
+                    METRICS
+                        .alerts_dropped
+                        .with_label_values(&[entry.channel.name()])
+                        .inc();
+                } else {
+                    entry.next_attempt = now + backoff(entry.attempts);
+                    warn!(
+                        "{}: attempt {} of '{}' failed, retrying in {}s: {}",
+                        entry.channel.name(),
+                        entry.attempts,
+                        entry.alert.title,
+                        backoff(entry.attempts),
+                        entry.last_error
+                    );
+                }
+            }
+        }
+        self.save(&state);
+    }
+
+    /// Drop entry `id` without delivering it (e.g. channel removed)
+    pub fn discard(&self, id: u64) {
+        let mut state = self.lock();
+        state.entries.retain(|entry| entry.id != id);
+        self.save(&state);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the file atomically; a failed write only loses durability
+    fn save(&self, state: &QueueState) {
+        METRICS.alert_queue_length.set(state.entries.len() as i64);
+        let Some(path) = &self.path else {
+            return;
+        };
+        let tmp = path.with_extension("tmp");
+        let written = serde_json::to_vec_pretty(state)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(std::fs::write(&tmp, bytes)?))
+            .and_then(|()| Ok(std::fs::rename(&tmp, path.as_path())?));
+        if let Err(e) = written {
+            error!("Failed to persist alert queue {}: {:#}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AlertLevel;
+    use starknet_core::types::Felt;
+
+    fn alert() -> Alert {
+        Alert {
+            level: AlertLevel::Critical,
+            title: "Key Image Spent Before Reveal".to_string(),
+            message: "XMR moved".to_string(),
+            contract_address: Felt::from(0x1au64),
+            timestamp: 1_000,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        let delays: Vec<u64> = (1..=9).map(backoff).collect();
+        assert_eq!(delays, [30, 60, 120, 240, 480, 960, 1920, 3600, 3600]);
+        assert_eq!(backoff(u32::MAX), 3600);
+    }
+
+    #[test]
+    fn failed_deliveries_survive_a_restart_until_given_up() {
+        let path = std::env::temp_dir().join(format!("alert-queue-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let failure = Err(anyhow::anyhow!("502 Bad Gateway"));
+
+        let queue = RetryQueue::open(&path).unwrap();
+        queue.push(
+            alert(),
+            Channel::Discord,
+            &anyhow::anyhow!("timeout"),
+            1_000,
+        );
+        queue.push(alert(), Channel::Email, &anyhow::anyhow!("timeout"), 1_000);
+        assert!(queue.due(1_029).is_empty());
+        assert_eq!(queue.due(1_030).len(), 2);
+        queue.resolve(0, &failure, 1_030);
+        queue.resolve(1, &Ok(()), 1_030);
+
+        let reopened = RetryQueue::open(&path).unwrap();
+        assert_eq!(reopened.len(), 1);
+        let entry = &reopened.due(1_090)[0];
+        assert_eq!(
+            (entry.channel, entry.attempts, entry.next_attempt),
+            (Channel::Discord, 2, 1_090)
+        );
+        assert_eq!(entry.last_error, "502 Bad Gateway");
+        assert_eq!(entry.alert, alert());
+
+        for _ in 2..MAX_ATTEMPTS {
+            reopened.resolve(0, &failure, 1_090);
+        }
+        assert!(reopened.is_empty());
+        assert!(RetryQueue::open(&path).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
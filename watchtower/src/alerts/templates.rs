@@ -0,0 +1,351 @@
+// Alert message templates
+//
+// The built-in alerts are Handlebars templates, one title and one message per
+// alert kind. Operators replace any of them in a TOML file
+// (WATCHTOWER_TEMPLATES) keyed by kind:
+//
+//   [deposited]
+//   title = "Funded: {{amount}}"
+//   message = """
+//   {{amount}} from {{depositor}}
+//   {{contract_url}}
+//   """
+//
+// Every template gets `contract` (0x hex) and `contract_url` (the contract on
+// the block explorer, STARKNET_EXPLORER_URL or Voyager for the configured
+// chain); the other variables depend on the kind (see `KINDS`). `{{utc ts}}`
+// renders a unix timestamp as `2025-01-31 14:00:00 UTC`. Unknown kinds, syntax
+// errors and unknown variables are rejected at startup. Output is not
+// HTML-escaped: channels escape for their own markup.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use handlebars::{handlebars_helper, no_escape, Handlebars};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use starknet_core::types::Felt;
+use tracing::error;
+
+use crate::types::{Alert, AlertLevel};
+
+/// An alert kind: default templates and the variables they may use
+pub struct TemplateKind {
+    pub name: &'static str,
+    pub level: AlertLevel,
+    pub title: &'static str,
+    pub message: &'static str,
+    pub variables: &'static [&'static str],
+}
+
+/// Built-in alerts (variables besides `contract` and `contract_url`)
+pub const KINDS: &[TemplateKind] = &[
+    TemplateKind {
+        name: "timelock_mismatch",
+        level: AlertLevel::Critical,
+        title: "Timelock Mismatch",
+        message: "Deployed contract is locked until {{lock_until}}, but the config expects {{expected_lock_until}}.\n\
+                  Check the swap terms before funding or revealing.",
+        variables: &["lock_until", "expected_lock_until"],
+    },
+    TemplateKind {
+        name: "protocol_anomaly",
+        level: AlertLevel::Warning,
+        title: "Protocol Anomaly",
+        message: "{{event}} is not allowed by the swap protocol here.\n\
+                  Contract was in one of: {{phases}}",
+        variables: &["event", "phases"],
+    },
+    TemplateKind {
+        name: "invalid_transition",
+        level: AlertLevel::Warning,
+        title: "Invalid Swap Transition",
+        message: "{{event}} does not follow from the tracked swap state ({{state}}).\n\
+                  The state is unchanged; check the contract before acting on it.",
+        variables: &["event", "state"],
+    },
+    TemplateKind {
+        name: "deposited",
+        level: AlertLevel::Info,
+        title: "Tokens Deposited",
+        message: "Contract funded, waiting for the secret reveal.\n\
+                  Amount: {{amount}}\n\
+                  Depositor: {{depositor}}",
+        variables: &["amount", "amount_raw", "depositor"],
+    },
+    TemplateKind {
+        name: "unexpected_deposit_amount",
+        level: AlertLevel::Warning,
+        title: "Unexpected Deposit Amount",
+        message: "Deposited {{amount}}, but the config expects {{expected_amount}}.\n\
+                  Do not reveal the secret until the terms are settled.",
+        variables: &[
+            "amount",
+            "amount_raw",
+            "expected_amount",
+            "expected_amount_raw",
+            "depositor",
+        ],
+    },
+    TemplateKind {
+        name: "secret_revealed",
+        level: AlertLevel::Info,
+        title: "Secret Revealed - Grace Period Started",
+        message: "Secret has been revealed. Tokens claimable in {{minutes_until_claim}} minutes.\n\
+                  Monero transaction needs {{confirmations}} confirmations for this swap value.\n\
+                  Contract version: {{contract_version}}",
+        variables: &[
+            "revealer",
+            "claimable_after",
+            "minutes_until_claim",
+            "confirmations",
+            "contract_version",
+        ],
+    },
+    TemplateKind {
+        name: "legacy_unlock",
+        level: AlertLevel::Info,
+        title: "Legacy Unlock - Tokens Released",
+        message: "Secret has been revealed and tokens released immediately.\n\
+                  Monero transaction needs {{confirmations}} confirmations for this swap value.\n\
+                  Contract version: {{contract_version}}",
+        variables: &["revealer", "confirmations", "contract_version"],
+    },
+    TemplateKind {
+        name: "grace_period_expiring",
+        level: AlertLevel::Warning,
+        title: "Grace Period Expiring Soon",
+        message: "Grace period expires in ~30 minutes. Ensure Monero TX has {{confirmations}} confirmations.\n\
+                  Contract: {{contract}}",
+        variables: &["claimable_after", "confirmations"],
+    },
+    TemplateKind {
+        name: "swap_completed",
+        level: AlertLevel::Info,
+        title: "Swap Completed",
+        message: "Tokens successfully claimed. Swap complete.\n\
+                  Amount: {{amount}}\n\
+                  Contract version: {{contract_version}}",
+        variables: &["amount", "amount_raw", "claimer", "contract_version"],
+    },
+    TemplateKind {
+        name: "swap_refunded",
+        level: AlertLevel::Warning,
+        title: "Swap Refunded",
+        message: "Timelock expired without a reveal; the tokens went back to the depositor.\n\
+                  Amount: {{amount}}\n\
+                  Depositor: {{depositor}}\n\
+                  Any XMR locked for this swap must be recovered separately.",
+        variables: &["amount", "amount_raw", "depositor"],
+    },
+    TemplateKind {
+        name: "chain_reorganization",
+        level: AlertLevel::Warning,
+        title: "Chain Reorganization",
+        message: "{{event}} from block {{block}} is no longer on the canonical chain.\n\
+                  Blocks after {{fork_block}} are being processed again.",
+        variables: &["event", "block", "fork_block"],
+    },
+];
+
+/// Voyager, by STARKNET_CHAIN_ID (SN_MAIN or the default SN_SEPOLIA)
+const MAINNET_EXPLORER: &str = "https://voyager.online";
+const SEPOLIA_EXPLORER: &str = "https://sepolia.voyager.online";
+
+handlebars_helper!(utc: |timestamp: u64| {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+});
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTemplate {
+    title: Option<String>,
+    message: Option<String>,
+}
+
+/// Compiled alert templates, cheap to clone
+#[derive(Clone)]
+pub struct AlertTemplates {
+    registry: Arc<Handlebars<'static>>,
+    explorer_url: Arc<String>,
+}
+
+impl Default for AlertTemplates {
+    fn default() -> Self {
+        Self::from_toml("").expect("built-in templates are valid")
+    }
+}
+
+impl AlertTemplates {
+    /// Overrides from `WATCHTOWER_TEMPLATES` (if set), explorer from
+    /// `STARKNET_EXPLORER_URL` or `STARKNET_CHAIN_ID`
+    pub fn from_env() -> Result<Self> {
+        let templates = match std::env::var("WATCHTOWER_TEMPLATES") {
+            Ok(path) => Self::load(&path)?,
+            Err(_) => Self::default(),
+        };
+        let explorer_url = std::env::var("STARKNET_EXPLORER_URL").unwrap_or_else(|_| {
+            match std::env::var("STARKNET_CHAIN_ID").as_deref() {
+                Ok("SN_MAIN") => MAINNET_EXPLORER.to_string(),
+                _ => SEPOLIA_EXPLORER.to_string(),
+            }
+        });
+        Ok(templates.with_explorer_url(&explorer_url))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("Invalid templates in {}", path.display()))
+    }
+
+    /// Built-in templates, replaced by the kinds `text` sets
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let mut overrides: HashMap<String, RawTemplate> = toml::from_str(text)?;
+        let mut registry = Handlebars::new();
+        registry.set_strict_mode(true);
+        registry.register_escape_fn(no_escape);
+        registry.register_helper("utc", Box::new(utc));
+        for kind in KINDS {
+            let custom = overrides.remove(kind.name);
+            let (title, message) = match custom {
+                Some(custom) => (
+                    custom.title.unwrap_or_else(|| kind.title.to_string()),
+                    custom.message.unwrap_or_else(|| kind.message.to_string()),
+                ),
+                None => (kind.title.to_string(), kind.message.to_string()),
+            };
+            for (part, template) in [("title", title), ("message", message)] {
+                let name = format!("{}.{}", kind.name, part);
+                registry
+                    .register_template_string(&name, template)
+                    .with_context(|| name.clone())?;
+                // Strict mode: a misspelled variable fails here, not on an alert
+                registry
+                    .render(&name, &sample(kind))
+                    .with_context(|| name.clone())?;
+            }
+        }
+        if let Some(unknown) = overrides.keys().next() {
+            let known: Vec<&str> = KINDS.iter().map(|kind| kind.name).collect();
+            bail!("unknown alert kind '{}' (one of {})", unknown, known.join(", "));
+        }
+        Ok(Self {
+            registry: Arc::new(registry),
+            explorer_url: Arc::new(SEPOLIA_EXPLORER.to_string()),
+        })
+    }
+
+    /// Explorer base URL for `contract_url`
+    pub fn with_explorer_url(mut self, explorer_url: &str) -> Self {
+        self.explorer_url = Arc::new(explorer_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// The `kind` alert for `contract`, from `variables` (a JSON object)
+    ///
+    /// # Panics
+    /// If `kind` is not in `KINDS`.
+    pub fn alert(&self, kind: &str, contract: Felt, variables: Value, timestamp: u64) -> Alert {
+        let template = KINDS
+            .iter()
+            .find(|template| template.name == kind)
+            .unwrap_or_else(|| panic!("unknown alert kind {}", kind));
+        let mut context = match variables {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        context.insert("contract".into(), format!("0x{:x}", contract).into());
+        context.insert(
+            "contract_url".into(),
+            format!("{}/contract/0x{:x}", self.explorer_url, contract).into(),
+        );
+        let render = |part: &str, fallback: &str| {
+            let name = format!("{}.{}", kind, part);
+            self.registry
+                .render(&name, &context)
+                .map(|text| text.trim_end().to_string())
+                .unwrap_or_else(|e| {
+                    error!("Failed to render alert template {}: {}", name, e);
+                    fallback.to_string()
+                })
+        };
+        Alert {
+            level: template.level,
+            title: render("title", template.title),
+            message: render("message", template.message),
+            contract_address: contract,
+            timestamp,
+        }
+    }
+}
+
+/// Placeholder values for every variable of `kind`
+fn sample(kind: &TemplateKind) -> Value {
+    let mut context = Map::new();
+    for name in ["contract", "contract_url"].iter().chain(kind.variables) {
+        context.insert(name.to_string(), Value::from(0u64));
+    }
+    Value::Object(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn defaults_reproduce_the_built_in_alerts() {
+        let alert = AlertTemplates::default().alert(
+            "chain_reorganization",
+            Felt::from(0xabcu64),
+            json!({ "event": "SecretRevealed", "block": 7, "fork_block": 6 }),
+            1_700_000_000,
+        );
+        assert_eq!(alert.level, AlertLevel::Warning);
+        assert_eq!(alert.title, "Chain Reorganization");
+        assert_eq!(
+            alert.message,
+            "SecretRevealed from block 7 is no longer on the canonical chain.\n\
+             Blocks after 6 are being processed again."
+        );
+    }
+
+    #[test]
+    fn overrides_get_the_explorer_link_and_dates() {
+        let templates = AlertTemplates::from_toml(
+            r#"
+[grace_period_expiring]
+title = "Claim <{{contract}}> by {{utc claimable_after}}"
+message = """
+{{confirmations}} confirmations needed
+{{contract_url}}
+"""
+"#,
+        )
+        .unwrap()
+        .with_explorer_url("https://starkscan.co/");
+        let alert = templates.alert(
+            "grace_period_expiring",
+            Felt::from(0xabcu64),
+            json!({ "claimable_after": 1_700_000_000u64, "confirmations": 10 }),
+            0,
+        );
+        assert_eq!(alert.title, "Claim <0xabc> by 2023-11-14 22:13:20 UTC");
+        assert_eq!(
+            alert.message,
+            "10 confirmations needed\nhttps://starkscan.co/contract/0xabc"
+        );
+
+        let error = |toml: &str| format!("{:#}", AlertTemplates::from_toml(toml).err().unwrap());
+        assert!(error("[deposited]\ntitle = \"{{amount\"").contains("deposited.title"));
+        assert!(error("[deposited]\nmessage = \"{{amuont}}\"").contains("deposited.message"));
+        assert!(error("[deposit]\ntitle = \"x\"").contains("unknown alert kind 'deposit'"));
+        assert!(error("[deposited]\nbody = \"x\"").contains("body"));
+    }
+}
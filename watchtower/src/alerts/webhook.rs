@@ -0,0 +1,159 @@
+// Generic JSON webhook channel
+//
+// Every alert is POSTed as one JSON object, for automation that Discord and
+// Telegram cannot feed:
+//
+//   {"level": "critical", "title": "...", "message": "...",
+//    "contract_address": "0x...", "timestamp": 1700000000}
+//
+// With a secret, the request carries
+//
+//   X-Watchtower-Timestamp: <unix seconds>
+//   X-Watchtower-Signature: sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">
+//
+// Receivers recompute the HMAC over the raw body, compare in constant time
+// and reject stale timestamps, so a captured request cannot be replayed.
+// Extra headers (e.g. an API key) are sent as configured. Non-2xx answers
+// count as failed deliveries.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::Url;
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::types::{Alert, AlertLevel};
+
+pub const TIMESTAMP_HEADER: &str = "X-Watchtower-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Watchtower-Signature";
+
+/// Where and how to deliver webhook alerts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    pub url: Url,
+    /// HMAC key; requests are unsigned without it
+    pub secret: Option<String>,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl WebhookConfig {
+    pub fn new(
+        url: &str,
+        secret: Option<String>,
+        headers: BTreeMap<String, String>,
+    ) -> Result<Self> {
+        let url =
+            Url::parse(url.trim()).with_context(|| format!("Invalid webhook URL '{}'", url))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("Webhook URL must be http(s), got '{}'", url);
+        }
+        if secret.as_deref().is_some_and(|s| s.is_empty()) {
+            bail!("Webhook secret is empty (omit it to send unsigned requests)");
+        }
+        for (name, value) in &headers {
+            HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid webhook header name '{}'", name))?;
+            HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value of webhook header '{}'", name))?;
+        }
+        Ok(Self {
+            url,
+            secret,
+            headers,
+        })
+    }
+
+    /// `WEBHOOK_URL`, `WEBHOOK_SECRET` and `WEBHOOK_HEADERS`
+    /// (`Name: value` pairs separated by `;`); None without a URL
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(url) = std::env::var("WEBHOOK_URL") else {
+            return Ok(None);
+        };
+        let headers = std::env::var("WEBHOOK_HEADERS")
+            .map(|headers| parse_headers(&headers))
+            .unwrap_or_else(|_| Ok(BTreeMap::new()))
+            .context("Invalid WEBHOOK_HEADERS")?;
+        Self::new(&url, std::env::var("WEBHOOK_SECRET").ok(), headers).map(Some)
+    }
+}
+
+/// `Name: value; Name: value`
+pub fn parse_headers(headers: &str) -> Result<BTreeMap<String, String>> {
+    headers
+        .split(';')
+        .filter(|header| !header.trim().is_empty())
+        .map(|header| {
+            let (name, value) = header
+                .split_once(':')
+                .with_context(|| format!("header '{}' is not 'Name: value'", header.trim()))?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// JSON body of a webhook alert
+#[derive(Debug, Serialize)]
+pub struct WebhookPayload<'a> {
+    pub level: AlertLevel,
+    pub title: &'a str,
+    pub message: &'a str,
+    pub contract_address: String,
+    pub timestamp: u64,
+}
+
+impl<'a> From<&'a Alert> for WebhookPayload<'a> {
+    fn from(alert: &'a Alert) -> Self {
+        Self {
+            level: alert.level,
+            title: &alert.title,
+            message: &alert.message,
+            contract_address: format!("0x{:x}", alert.contract_address),
+            timestamp: alert.timestamp,
+        }
+    }
+}
+
+/// `sha256=<hex>` signature of `body` sent at `timestamp`
+pub fn signature(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        // Python: hmac.new(b"whsec", b'1700000000.{"title":"x"}', sha256)
+        let body = br#"{"title":"x"}"#;
+        assert_eq!(
+            signature("whsec", 1_700_000_000, body),
+            "sha256=1670a2e6627caa854548a6ab8d2c458a819ecf9f94bdf7836be609f893a10df0"
+        );
+        assert_ne!(
+            signature("whsec", 1_700_000_001, body),
+            signature("whsec", 1_700_000_000, body)
+        );
+    }
+
+    #[test]
+    fn config_is_validated() {
+        let headers = parse_headers("X-Api-Key: abc; Accept: application/json, */*;").unwrap();
+        assert_eq!(headers["Accept"], "application/json, */*");
+        assert!(WebhookConfig::new("https://hooks.example.com/in", None, headers).is_ok());
+
+        assert!(parse_headers("X-Api-Key abc").is_err());
+        assert!(WebhookConfig::new("hooks.example.com", None, BTreeMap::new()).is_err());
+        assert!(WebhookConfig::new("ftp://example.com", None, BTreeMap::new()).is_err());
+        assert!(WebhookConfig::new("https://a.io", Some(String::new()), BTreeMap::new()).is_err());
+        let bad_name = BTreeMap::from([("X Api".to_string(), "abc".to_string())]);
+        assert!(WebhookConfig::new("https://a.io", None, bad_name).is_err());
+    }
+}
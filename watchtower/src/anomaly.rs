@@ -0,0 +1,188 @@
+// Protocol anomaly detection
+//
+// Tracks the phase of each watched contract using the shared protocol spec
+// (spec/protocol.json, also used by the Rust swap tooling) and flags events
+// the spec does not allow, e.g. TokensClaimed without a prior SecretRevealed
+// or a second reveal. The watchtower only sees on-chain events, so
+// transitions triggered by unobservable actions (deposit) are assumed to
+// have happened whenever needed.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use starknet_core::types::Felt;
+
+/// Raw contents of `spec/protocol.json`
+pub const PROTOCOL_SPEC_JSON: &str = include_str!("../../spec/protocol.json");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    pub on: String,
+    pub observable: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConformanceCase {
+    pub name: String,
+    pub trace: Vec<String>,
+    pub valid: bool,
+    #[serde(default)]
+    pub watchtower_valid: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProtocolSpec {
+    pub phases: Vec<String>,
+    pub initial: String,
+    pub terminal: Vec<String>,
+    pub transitions: Vec<Transition>,
+    #[serde(default)]
+    pub conformance: Vec<ConformanceCase>,
+}
+
+/// An event the spec does not allow in any phase the contract could be in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anomaly {
+    pub contract: Felt,
+    pub event: String,
+    /// Phases the contract could have been in before the event
+    pub possible_phases: Vec<String>,
+}
+
+/// Per-contract protocol state tracker
+pub struct AnomalyDetector {
+    spec: ProtocolSpec,
+    /// Phases each contract may currently be in
+    contracts: HashMap<Felt, BTreeSet<String>>,
+}
+
+impl AnomalyDetector {
+    /// Detector for the embedded protocol spec
+    pub fn new() -> Result<Self> {
+        Self::from_json(PROTOCOL_SPEC_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let spec: ProtocolSpec = serde_json::from_str(json).context("Invalid protocol spec")?;
+        Ok(Self {
+            spec,
+            contracts: HashMap::new(),
+        })
+    }
+
+    pub fn spec(&self) -> &ProtocolSpec {
+        &self.spec
+    }
+
+    /// Track `contract` from the initial phase (its deployment was seen)
+    pub fn start(&mut self, contract: Felt) {
+        let initial = self.closure(BTreeSet::from([self.spec.initial.clone()]));
+        self.contracts.insert(contract, initial);
+    }
+
+    /// Stop tracking `contract` (its history was reverted by a reorg): its
+    /// next event is judged as if the contract had just been seen
+    pub fn forget(&mut self, contract: Felt) {
+        self.contracts.remove(&contract);
+    }
+
+    /// Record `event` for `contract`; returns an anomaly if the spec forbids it.
+    ///
+    /// A contract seen for the first time may be in any non-terminal phase.
+    /// Events the spec does not mention are ignored.
+    pub fn observe(&mut self, contract: Felt, event: &str) -> Option<Anomaly> {
+        if !self.is_tracked_event(event) {
+            return None;
+        }
+        let current = match self.contracts.get(&contract) {
+            Some(phases) => phases.clone(),
+            None => self
+                .spec
+                .phases
+                .iter()
+                .filter(|phase| !self.spec.terminal.contains(phase))
+                .cloned()
+                .collect(),
+        };
+
+        let next: BTreeSet<String> = self
+            .spec
+            .transitions
+            .iter()
+            .filter(|t| t.observable && t.on == event && current.contains(&t.from))
+            .map(|t| t.to.clone())
+            .collect();
+
+        if next.is_empty() {
+            // Keep the previous state so later events are still judged
+            self.contracts.insert(contract, current.clone());
+            return Some(Anomaly {
+                contract,
+                event: event.to_string(),
+                possible_phases: current.into_iter().collect(),
+            });
+        }
+        let next = self.closure(next);
+        self.contracts.insert(contract, next);
+        None
+    }
+
+    fn is_tracked_event(&self, event: &str) -> bool {
+        self.spec
+            .transitions
+            .iter()
+            .any(|t| t.observable && t.on == event)
+    }
+
+    /// Add every phase reachable through unobservable transitions
+    fn closure(&self, mut phases: BTreeSet<String>) -> BTreeSet<String> {
+        loop {
+            let reachable: Vec<String> = self
+                .spec
+                .transitions
+                .iter()
+                .filter(|t| !t.observable && phases.contains(&t.from) && !phases.contains(&t.to))
+                .map(|t| t.to.clone())
+                .collect();
+            if reachable.is_empty() {
+                return phases;
+            }
+            phases.extend(reachable);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conformance_traces() {
+        let cases = AnomalyDetector::new().unwrap().spec().conformance.clone();
+        assert!(!cases.is_empty());
+
+        for case in cases {
+            let mut detector = AnomalyDetector::new().unwrap();
+            detector.start(Felt::ONE);
+            let anomalies: Vec<Anomaly> = case
+                .trace
+                .iter()
+                .filter_map(|event| detector.observe(Felt::ONE, event))
+                .collect();
+            let expected_valid = case.watchtower_valid.unwrap_or(case.valid);
+            assert_eq!(anomalies.is_empty(), expected_valid, "case '{}': {:?}", case.name, anomalies);
+        }
+    }
+
+    #[test]
+    fn unknown_contract_joins_mid_swap() {
+        let mut detector = AnomalyDetector::new().unwrap();
+        // Watchtower started after the reveal: claim is fine, a second one is not
+        assert_eq!(detector.observe(Felt::TWO, "TokensClaimed"), None);
+        assert!(detector.observe(Felt::TWO, "TokensClaimed").is_some());
+        assert_eq!(detector.observe(Felt::TWO, "DleqVerified"), None);
+    }
+}
@@ -0,0 +1,306 @@
+// HTTP API: add and remove watched contracts while the watchtower runs
+//
+// Off unless WATCHTOWER_API_ADDR is set (e.g. 127.0.0.1:8090). Bind it to
+// localhost or a private network; with WATCHTOWER_API_TOKEN every request
+// must carry `Authorization: Bearer <token>`.
+//
+//   POST   /contracts            {"address": "0x..", "xmr_amount": "1.5", "from_block": 812000}
+//   DELETE /contracts/{address}
+//   GET    /swaps                watched contracts with their swap state
+//   GET    /swaps/{address}      state and event history of one swap
+//
+// Only `address` is required. Without `from_block` the contract is fetched
+// from the block the listener is at; pass the deployment block to catch
+// events emitted before the registration.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use starknet_core::types::Felt;
+use tracing::{error, info};
+
+use crate::confirmations::parse_xmr;
+use crate::registry::{ContractRegistry, WatchedContract};
+use crate::tracker::{SwapRecord, SwapTracker};
+use crate::types::SwapState;
+
+#[derive(Clone)]
+struct ApiState {
+    registry: ContractRegistry,
+    swaps: SwapTracker,
+    token: Option<Arc<str>>,
+}
+
+/// Entry of `GET /swaps`
+#[derive(Debug, Serialize)]
+struct SwapSummary {
+    #[serde(flatten)]
+    contract: WatchedContract,
+    /// None until the contract's first event
+    state: Option<SwapState>,
+}
+
+/// Body of `POST /contracts`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddContract {
+    address: String,
+    /// Swap value in XMR, as in WATCHED_CONTRACTS
+    xmr_amount: Option<String>,
+    from_block: Option<u64>,
+}
+
+impl AddContract {
+    fn parse(self) -> Result<WatchedContract> {
+        let address = Felt::from_hex(self.address.trim())
+            .with_context(|| format!("Invalid contract address '{}'", self.address))?;
+        let xmr_amount = self
+            .xmr_amount
+            .as_deref()
+            .map(|amount| parse_xmr(amount.trim()))
+            .transpose()?;
+        let contract = WatchedContract::new(address, xmr_amount);
+        Ok(match self.from_block {
+            Some(block) => contract.with_from_block(block),
+            None => contract,
+        })
+    }
+}
+
+/// Error response: status and a JSON `{"error": ...}` body
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// Routes over `registry` and `swaps`, requiring `token` as a bearer token
+/// if set
+pub fn router(registry: ContractRegistry, swaps: SwapTracker, token: Option<String>) -> Router {
+    let state = ApiState {
+        registry,
+        swaps,
+        token: token.map(Arc::from),
+    };
+    Router::new()
+        .route("/contracts", post(add_contract))
+        .route("/contracts/{address}", delete(remove_contract))
+        .route("/swaps", get(list_swaps))
+        .route("/swaps/{address}", get(get_swap))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+/// Bind `addr` and serve `router` in the background
+pub async fn spawn(addr: &str, router: Router) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind the HTTP API to {}", addr))?;
+    info!("HTTP API listening on {}", listener.local_addr()?);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            error!("HTTP API stopped: {}", e);
+        }
+    });
+    Ok(())
+}
+
+async fn authorize(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if let Some(token) = &state.token {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if presented != Some(&**token) {
+            return ApiError(
+                StatusCode::UNAUTHORIZED,
+                "missing or wrong token".to_string(),
+            )
+            .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+async fn add_contract(
+    State(state): State<ApiState>,
+    Json(body): Json<AddContract>,
+) -> Result<(StatusCode, Json<WatchedContract>), ApiError> {
+    let contract = body
+        .parse()
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    if state.registry.contains(contract.address) {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            format!("contract {:#x} is already watched", contract.address),
+        ));
+    }
+    state.registry.insert(contract.clone());
+    info!("API: watching contract {:x}", contract.address);
+    Ok((StatusCode::CREATED, Json(contract)))
+}
+
+/// Contract address from a path segment
+fn parse_address(address: &str) -> Result<Felt, ApiError> {
+    Felt::from_hex(address.trim()).map_err(|_| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid contract address '{}'", address),
+        )
+    })
+}
+
+async fn remove_contract(
+    State(state): State<ApiState>,
+    Path(address): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let address = parse_address(&address)?;
+    match state.registry.remove(address) {
+        Some(_) => {
+            info!("API: stopped watching contract {:x}", address);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        None => Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("contract {:#x} is not watched", address),
+        )),
+    }
+}
+
+async fn list_swaps(State(state): State<ApiState>) -> Json<Vec<SwapSummary>> {
+    let summaries = state
+        .registry
+        .snapshot()
+        .into_iter()
+        .map(|contract| SwapSummary {
+            state: state.swaps.get(contract.address).map(|swap| swap.state),
+            contract,
+        })
+        .collect();
+    Json(summaries)
+}
+
+async fn get_swap(
+    State(state): State<ApiState>,
+    Path(address): Path<String>,
+) -> Result<Json<SwapRecord>, ApiError> {
+    let address = parse_address(&address)?;
+    state.swaps.get(address).map(Json).ok_or_else(|| {
+        ApiError(
+            StatusCode::NOT_FOUND,
+            format!("no events seen for contract {:#x}", address),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Method;
+    use tower::ServiceExt;
+
+    use crate::starknet::listener::SwapEvent;
+    use crate::types::DepositedEvent;
+
+    const TOKEN: &str = "secret";
+
+    async fn call(router: &Router, method: Method, uri: &str, body: &str) -> (StatusCode, String) {
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn contracts_are_added_listed_and_removed() {
+        let registry = ContractRegistry::default();
+        let router = router(registry.clone(), SwapTracker::default(), Some(TOKEN.to_string()));
+        let add = r#"{"address": "0x1a", "xmr_amount": "0.5", "from_block": 100}"#;
+
+        let anonymous = axum::http::Request::builder()
+            .method(Method::POST)
+            .uri("/contracts")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(add))
+            .unwrap();
+        let response = router.clone().oneshot(anonymous).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(registry.is_empty());
+
+        let (status, _) = call(&router, Method::POST, "/contracts", add).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let watched = registry.get(Felt::from(0x1au64)).unwrap();
+        assert_eq!(watched.xmr_amount, Some(500_000_000_000));
+        assert_eq!(watched.from_block, Some(100));
+
+        let (status, _) = call(&router, Method::POST, "/contracts", add).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let bad = r#"{"address": "0x1b", "xmr_amount": "lots"}"#;
+        let (status, body) = call(&router, Method::POST, "/contracts", bad).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("lots"), "{}", body);
+
+        let (status, body) = call(&router, Method::GET, "/swaps", "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("\"from_block\":100"), "{}", body);
+
+        let (status, _) = call(&router, Method::DELETE, "/contracts/0x1a", "").await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = call(&router, Method::DELETE, "/contracts/0x1a", "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(registry.is_empty());
+    }
+
+    #[tokio::test]
+    async fn swap_states_are_served() {
+        let registry = ContractRegistry::default();
+        registry.insert(WatchedContract::new(Felt::from(0x1au64), None));
+        registry.insert(WatchedContract::new(Felt::from(0x1bu64), None));
+        let swaps = SwapTracker::default();
+        let deposited = SwapEvent::Deposited(DepositedEvent {
+            contract_address: Felt::from(0x1au64),
+            depositor: Felt::TWO,
+            amount: 1000,
+            block_number: 5,
+            transaction_hash: Felt::THREE,
+        });
+        swaps.apply(&deposited, 100).unwrap();
+        let router = router(registry, swaps, Some(TOKEN.to_string()));
+
+        let (status, body) = call(&router, Method::GET, "/swaps", "").await;
+        assert_eq!(status, StatusCode::OK);
+        let list: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(list[0]["state"], "Locked");
+        assert_eq!(list[1]["state"], serde_json::Value::Null);
+
+        let (status, body) = call(&router, Method::GET, "/swaps/0x1a", "").await;
+        assert_eq!(status, StatusCode::OK);
+        let swap: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(swap["amount"], 1000);
+        assert_eq!(swap["history"][0]["event"], "Deposited");
+
+        let (status, _) = call(&router, Method::GET, "/swaps/0x1b", "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = call(&router, Method::GET, "/swaps/zz", "").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,64 @@
+// Value-based confirmation requirements
+//
+// The tiers come from `swap_common::confirmations` (spec/confirmations.json),
+// the same code the maker/taker use, so the watchtower waits exactly as long
+// as they would. Watched contracts may carry their swap value (`0xaddr:1.5`
+// in WATCHED_CONTRACTS); contracts without one get the strictest tier.
+
+use anyhow::{Context, Result};
+use starknet_core::types::Felt;
+use swap_common::confirmations::required_confirmations;
+use swap_common::format::XMR;
+
+pub use swap_common::confirmations::ConfirmationRequirement;
+
+/// Requirements for a swap worth `amount_piconero` (None = unknown value)
+pub fn requirement(amount_piconero: Option<u64>) -> ConfirmationRequirement {
+    // Values above every bounded tier get the strictest one
+    required_confirmations(amount_piconero.unwrap_or(u64::MAX))
+}
+
+/// Parse a `WATCHED_CONTRACTS` entry: `0xaddr` or `0xaddr:<xmr amount>`
+pub fn parse_watched_contract(entry: &str) -> Result<(Felt, Option<u64>)> {
+    let (address, amount) = match entry.split_once(':') {
+        Some((address, amount)) => (address.trim(), Some(parse_xmr(amount.trim())?)),
+        None => (entry.trim(), None),
+    };
+    let address = Felt::from_hex(address)
+        .with_context(|| format!("Invalid contract address '{}'", address))?;
+    Ok((address, amount))
+}
+
+/// Decimal XMR amount to piconero, without going through floats
+pub fn parse_xmr(amount: &str) -> Result<u64> {
+    XMR.parse(amount)
+        .and_then(|piconero| u64::try_from(piconero).ok())
+        .with_context(|| format!("Invalid XMR amount '{}'", amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swap_common::confirmations::PICONERO_PER_XMR;
+
+    #[test]
+    fn tiers_match_rust_tooling() {
+        assert_eq!(requirement(Some(PICONERO_PER_XMR / 20)).monero_confirmations, 3);
+        assert_eq!(requirement(Some(PICONERO_PER_XMR / 2)).monero_confirmations, 6);
+        assert_eq!(requirement(Some(5 * PICONERO_PER_XMR)).monero_confirmations, 10);
+        assert_eq!(requirement(None), requirement(Some(u64::MAX)));
+    }
+
+    #[test]
+    fn watched_contract_entries() {
+        assert_eq!(parse_watched_contract("0x1a").unwrap(), (Felt::from(0x1au64), None));
+        assert_eq!(
+            parse_watched_contract("0x1a:1.5").unwrap(),
+            (Felt::from(0x1au64), Some(1_500_000_000_000))
+        );
+        assert_eq!(parse_watched_contract("0x1a:.05").unwrap().1, Some(50_000_000_000));
+        assert!(parse_watched_contract("0x1a:1e3").is_err());
+        assert!(parse_watched_contract("0x1a:0.0000000000001").is_err());
+        assert!(parse_watched_contract("0x1a:20000000").is_err());
+    }
+}
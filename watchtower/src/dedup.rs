@@ -0,0 +1,222 @@
+// Duplicate event suppression
+//
+// The same contract event can reach the event loop twice: a block processed
+// again after a restart from an older checkpoint, a journal replay
+// overlapping live blocks, or an RPC page returned twice. Events are
+// identified by (contract, event, transaction hash); a repeat seen within
+// the window (ALERT_DEDUP_WINDOW seconds, default one day) is dropped before
+// it reaches alerts, rules or auto-claims. A reorg forgets the reverted
+// event, so the same transaction included again on the new chain is handled
+// as new.
+//
+// The keys are rewritten to WATCHTOWER_DEDUP (default ./watchtower_dedup.json)
+// after every change and loaded at startup, so `--replay-journal` after a
+// restart does not fire alerts and actions again. Only events seen within
+// the window are remembered: keep ALERT_DEDUP_WINDOW at least as long as the
+// journal being replayed.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use starknet_core::types::Felt;
+use tracing::{error, info};
+
+use crate::starknet::listener::SwapEvent;
+
+/// Window when ALERT_DEDUP_WINDOW is not set
+pub const DEFAULT_DEDUP_WINDOW_SECS: u64 = 86_400;
+
+/// File used when WATCHTOWER_DEDUP is not set
+pub const DEFAULT_DEDUP_PATH: &str = "watchtower_dedup.json";
+
+type EventKey = (Felt, String, Felt);
+
+fn key(event: &SwapEvent) -> EventKey {
+    (
+        event.contract_address(),
+        event.name().to_string(),
+        event.transaction_hash(),
+    )
+}
+
+/// One persisted key
+#[derive(Debug, Serialize, Deserialize)]
+struct SeenEvent {
+    contract: Felt,
+    event: String,
+    transaction: Felt,
+    seen_at: u64,
+}
+
+/// Events seen recently, with the time they were first seen
+pub struct EventDedup {
+    window_secs: u64,
+    seen: HashMap<EventKey, u64>,
+    /// None: kept in memory only
+    path: Option<PathBuf>,
+}
+
+impl EventDedup {
+    /// In-memory only
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window_secs,
+            seen: HashMap::new(),
+            path: None,
+        }
+    }
+
+    /// `WATCHTOWER_DEDUP`, or `watchtower_dedup.json`
+    pub fn from_env(window_secs: u64) -> Result<Self> {
+        let path =
+            std::env::var("WATCHTOWER_DEDUP").unwrap_or_else(|_| DEFAULT_DEDUP_PATH.to_string());
+        Self::open(path, window_secs)
+    }
+
+    /// Resume the keys stored at `path` (none if there is no file yet)
+    pub fn open(path: impl AsRef<Path>, window_secs: u64) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let records: Vec<SeenEvent> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Corrupt dedup keys {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        if !records.is_empty() {
+            info!("Resuming {} dedup key(s) from {}", records.len(), path.display());
+        }
+        Ok(Self {
+            window_secs,
+            seen: records
+                .into_iter()
+                .map(|r| ((r.contract, r.event, r.transaction), r.seen_at))
+                .collect(),
+            path: Some(path),
+        })
+    }
+
+    /// Record `event` at `now`; false if it was already seen in the window.
+    ///
+    /// `Reverted` is never a duplicate: it forgets the reverted event.
+    pub fn first_seen(&mut self, event: &SwapEvent, now: u64) -> bool {
+        if let SwapEvent::Reverted { event, .. } = event {
+            if self.seen.remove(&key(event)).is_some() {
+                self.persist();
+            }
+            return true;
+        }
+        let window = self.window_secs;
+        self.seen
+            .retain(|_, seen_at| now.saturating_sub(*seen_at) < window);
+        // Repeats do not extend the window
+        match self.seen.entry(key(event)) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(now);
+                self.persist();
+                true
+            }
+        }
+    }
+
+    /// Replace the file atomically; a failed write only loses durability
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let tmp = path.with_extension("tmp");
+        let records: Vec<SeenEvent> = self
+            .seen
+            .iter()
+            .map(|((contract, event, transaction), seen_at)| SeenEvent {
+                contract: *contract,
+                event: event.clone(),
+                transaction: *transaction,
+                seen_at: *seen_at,
+            })
+            .collect();
+        let written = serde_json::to_vec(&records)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(std::fs::write(&tmp, bytes)?))
+            .and_then(|()| Ok(std::fs::rename(&tmp, path)?));
+        if let Err(e) = written {
+            error!("Failed to persist dedup keys {}: {:#}", path.display(), e);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+impl Default for EventDedup {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEDUP_WINDOW_SECS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DepositedEvent;
+
+    fn deposited(transaction_hash: u64, block_number: u64) -> SwapEvent {
+        SwapEvent::Deposited(DepositedEvent {
+            contract_address: Felt::ONE,
+            depositor: Felt::TWO,
+            amount: 1000,
+            block_number,
+            transaction_hash: Felt::from(transaction_hash),
+        })
+    }
+
+    #[test]
+    fn repeats_are_dropped_until_reverted_or_expired() {
+        let mut dedup = EventDedup::new(100);
+        assert!(dedup.first_seen(&deposited(0xa, 7), 1_000));
+        // Same transaction, even from another block fetch
+        assert!(!dedup.first_seen(&deposited(0xa, 7), 1_010));
+        assert!(dedup.first_seen(&deposited(0xb, 7), 1_010));
+
+        let reverted = SwapEvent::Reverted {
+            fork_block: 6,
+            event: Box::new(deposited(0xa, 7)),
+        };
+        assert!(dedup.first_seen(&reverted, 1_020));
+        assert!(dedup.first_seen(&deposited(0xa, 9), 1_030));
+
+        assert!(!dedup.first_seen(&deposited(0xb, 7), 1_109));
+        assert!(dedup.first_seen(&deposited(0xb, 7), 1_110));
+        assert_eq!(dedup.len(), 2);
+    }
+
+    #[test]
+    fn keys_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("wt-dedup-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut dedup = EventDedup::open(&path, 100).unwrap();
+        assert!(dedup.first_seen(&deposited(0xa, 7), 1_000));
+        assert!(dedup.first_seen(&deposited(0xb, 7), 1_000));
+        let reverted = SwapEvent::Reverted {
+            fork_block: 6,
+            event: Box::new(deposited(0xb, 7)),
+        };
+        assert!(dedup.first_seen(&reverted, 1_005));
+
+        // A replay after the restart is still recognised
+        let mut dedup = EventDedup::open(&path, 100).unwrap();
+        assert_eq!(dedup.len(), 1);
+        assert!(!dedup.first_seen(&deposited(0xa, 7), 1_010));
+        assert!(dedup.first_seen(&deposited(0xb, 7), 1_010));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,313 @@
+// Cursor store and instance lease for zero-downtime upgrades
+//
+// A small JSON file (WATCHTOWER_STATE) holds the last fully processed block
+// and a lease naming the instance allowed to process blocks and send alerts.
+// To upgrade, start the new binary next to the old one:
+//
+// 1. The new instance finds the lease held and records a handoff request.
+// 2. The old instance sees the request at its next renewal, finishes the
+//    batch in flight, stores the cursor, releases the lease and exits.
+// 3. The new instance takes the lease and resumes at cursor + 1.
+//
+// Only the lease holder processes blocks, so nothing is alerted twice, and
+// the cursor is stored before the lease is released, so no block is skipped.
+// If the old instance dies instead, its lease expires after the TTL.
+//
+// Next to the global cursor, the file keeps one cursor per contract. A
+// restarted watchtower backfills each contract from its own cursor, so a
+// block whose fetch failed, or a contract that was removed from
+// WATCHED_CONTRACTS and added back later, is fetched again instead of
+// skipped. Without a lease (no WATCHTOWER_STATE) the same file is still used
+// as a plain checkpoint.
+//
+// Read-modify-write cycles are serialized with a `<state>.lock` file created
+// with O_EXCL; the state itself is replaced atomically via rename.
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// Lease lifetime without renewal
+pub const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(60);
+
+/// A lock file older than this is left over from a crash
+const STALE_LOCK: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchtowerState {
+    /// Last block whose events were all forwarded
+    pub cursor: Option<u64>,
+    pub lease: Option<Lease>,
+    /// Instance waiting to take over
+    pub handoff_requested_by: Option<String>,
+    /// Last fully processed block per contract (hex address)
+    #[serde(default)]
+    pub contracts: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    pub holder: String,
+    /// Unix seconds
+    pub expires_at: u64,
+}
+
+/// Result of renewing a lease
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaseStatus {
+    /// Still ours, keep going
+    Held,
+    /// Another instance asked to take over: stop after this batch
+    HandoffRequested,
+    /// Expired and taken by someone else: stop immediately
+    Lost,
+}
+
+/// File-backed cursor and lease
+pub struct StateStore {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl StateStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            ttl: DEFAULT_LEASE_TTL,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self) -> Result<WatchtowerState> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Invalid watchtower state {}", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(WatchtowerState::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        }
+    }
+
+    /// Take the lease if it is free, expired or already ours
+    pub fn try_acquire(&self, id: &str) -> Result<bool> {
+        let now = unix_now();
+        let expires_at = now + self.ttl.as_secs();
+        self.update(|state| {
+            let free = match &state.lease {
+                None => true,
+                Some(lease) => lease.holder == id || lease.expires_at <= now,
+            };
+            if free {
+                state.lease = Some(Lease {
+                    holder: id.to_string(),
+                    expires_at,
+                });
+                if state.handoff_requested_by.as_deref() == Some(id) {
+                    state.handoff_requested_by = None;
+                }
+            }
+            free
+        })
+    }
+
+    /// Ask the current holder to hand over
+    pub fn request_handoff(&self, id: &str) -> Result<()> {
+        self.update(|state| state.handoff_requested_by = Some(id.to_string()))
+    }
+
+    /// Extend our lease, storing `cursor` if given
+    pub fn renew(&self, id: &str, cursor: Option<u64>) -> Result<LeaseStatus> {
+        let now = unix_now();
+        let expires_at = now + self.ttl.as_secs();
+        self.update(|state| {
+            match &state.lease {
+                Some(lease) if lease.holder == id => {}
+                _ => return LeaseStatus::Lost,
+            }
+            if cursor.is_some() {
+                state.cursor = cursor;
+            }
+            state.lease = Some(Lease {
+                holder: id.to_string(),
+                expires_at,
+            });
+            match &state.handoff_requested_by {
+                Some(other) if other != id => LeaseStatus::HandoffRequested,
+                _ => LeaseStatus::Held,
+            }
+        })
+    }
+
+    /// Store the final cursor and give up the lease
+    pub fn release(&self, id: &str, cursor: Option<u64>) -> Result<()> {
+        self.update(|state| {
+            if state.lease.as_ref().map(|l| l.holder.as_str()) != Some(id) {
+                return;
+            }
+            if cursor.is_some() {
+                state.cursor = cursor;
+            }
+            state.lease = None;
+        })
+    }
+
+    /// Store the cursors after a processed batch. With a `holder`, nothing is
+    /// written unless it still holds the lease.
+    pub fn save_checkpoint(
+        &self,
+        holder: Option<&str>,
+        cursor: u64,
+        contracts: impl IntoIterator<Item = (String, u64)>,
+    ) -> Result<()> {
+        self.update(|state| {
+            if let Some(id) = holder {
+                if state.lease.as_ref().map(|l| l.holder.as_str()) != Some(id) {
+                    return;
+                }
+            }
+            state.cursor = Some(cursor);
+            state.contracts.extend(contracts);
+        })
+    }
+
+    /// Wait until the lease is ours, requesting a handoff if it is held
+    pub async fn acquire(&self, id: &str) -> Result<()> {
+        let mut requested = false;
+        while !self.try_acquire(id)? {
+            if !requested {
+                info!("Lease held by another instance, requesting handoff");
+                self.request_handoff(id)?;
+                requested = true;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        info!("Acquired watchtower lease ({})", self.path.display());
+        Ok(())
+    }
+
+    fn update<R>(&self, f: impl FnOnce(&mut WatchtowerState) -> R) -> Result<R> {
+        let _lock = FileLock::acquire(&self.path.with_extension("lock"))?;
+        let mut state = self.load()?;
+        let result = f(&mut state);
+
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&state)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to replace {}", self.path.display()))?;
+        Ok(result)
+    }
+}
+
+/// Exclusive lock file, removed on drop
+struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    fn acquire(path: &Path) -> Result<Self> {
+        for _ in 0..500 {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(_) => return Ok(Self { path: path.to_path_buf() }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(path)
+                        .and_then(|m| m.modified())
+                        .map(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK)
+                        .unwrap_or(false);
+                    if stale {
+                        let _ = std::fs::remove_file(path);
+                    } else {
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+                }
+            }
+        }
+        bail!("Timed out waiting for {}", path.display())
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handoff_between_instances() {
+        let path = std::env::temp_dir().join(format!("wt-state-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = StateStore::new(&path);
+
+        assert!(store.try_acquire("old").unwrap());
+        assert_eq!(store.renew("old", Some(100)).unwrap(), LeaseStatus::Held);
+
+        // New instance cannot take a live lease, but can ask for it
+        assert!(!store.try_acquire("new").unwrap());
+        store.request_handoff("new").unwrap();
+        assert_eq!(store.renew("old", Some(101)).unwrap(), LeaseStatus::HandoffRequested);
+
+        store.release("old", Some(102)).unwrap();
+        assert!(store.try_acquire("new").unwrap());
+        let state = store.load().unwrap();
+        assert_eq!(state.cursor, Some(102));
+        assert_eq!(state.handoff_requested_by, None);
+        assert_eq!(store.renew("old", None).unwrap(), LeaseStatus::Lost);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn checkpoint_keeps_per_contract_cursors() {
+        let path = std::env::temp_dir().join(format!("wt-checkpoint-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = StateStore::new(&path);
+
+        // No lease needed without a holder
+        store.save_checkpoint(None, 10, [("0xa".to_string(), 10), ("0xb".to_string(), 12)]).unwrap();
+        // A contract no longer watched keeps its cursor
+        store.save_checkpoint(None, 20, [("0xa".to_string(), 20)]).unwrap();
+        let state = store.load().unwrap();
+        assert_eq!(state.cursor, Some(20));
+        assert_eq!(state.contracts["0xa"], 20);
+        assert_eq!(state.contracts["0xb"], 12);
+
+        // A holder that lost the lease must not move the cursors
+        assert!(store.try_acquire("new").unwrap());
+        store.save_checkpoint(Some("old"), 30, [("0xa".to_string(), 30)]).unwrap();
+        assert_eq!(store.load().unwrap().cursor, Some(20));
+        store.save_checkpoint(Some("new"), 30, [("0xa".to_string(), 30)]).unwrap();
+        assert_eq!(store.load().unwrap().contracts["0xa"], 30);
+
+        // State files written before per-contract cursors still load
+        std::fs::write(&path, r#"{"cursor": 5, "lease": null, "handoff_requested_by": null}"#).unwrap();
+        assert_eq!(store.load().unwrap().contracts.len(), 0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
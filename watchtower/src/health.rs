@@ -0,0 +1,229 @@
+// Health check for container orchestration
+//
+// `GET /health` on WATCHTOWER_API_ADDR (no token) runs every check and
+// answers 200 if all pass, 503 otherwise, with the details as JSON:
+//
+// - starknet: an RPC endpoint answers `starknet_blockNumber` (the active
+//   one, or the next that takes over from it)
+// - monero: monerod answers `get_height` (only with MONERO_DAEMON_URL)
+// - listener: the event listener made progress in the last
+//   WATCHTOWER_HEALTH_MAX_LAG seconds (default 300; until its first poll,
+//   counted from startup)
+// - notifier: a channel is configured and none failed its last alert
+//
+// A stalled listener (crashed task, stuck RPC) fails the probe, so a
+// liveness probe restarts the watchtower instead of letting it go blind.
+
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use reqwest::Client;
+use serde::Serialize;
+use starknet_providers::Provider;
+
+use crate::alerts::notifier::{Notifier, NotifierStatus};
+use crate::monero::tx_monitor::daemon_height;
+use crate::starknet::failover::RpcEndpoint;
+use crate::starknet::listener::ListenerProgress;
+
+/// Default WATCHTOWER_HEALTH_MAX_LAG (seconds)
+pub const DEFAULT_MAX_LAG_SECS: u64 = 300;
+
+/// Per-check timeout, so a hung node fails the check instead of the probe
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What `/health` checks
+#[derive(Clone)]
+pub struct HealthChecks {
+    rpc: RpcEndpoint,
+    monero_daemon: Option<String>,
+    client: Client,
+    progress: ListenerProgress,
+    notifier: Notifier,
+    max_lag_secs: u64,
+    started_at: u64,
+}
+
+/// Result of one node check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeCheck {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl NodeCheck {
+    fn from_result(result: anyhow::Result<u64>) -> Self {
+        match result {
+            Ok(height) => Self {
+                ok: true,
+                height: Some(height),
+                error: None,
+            },
+            Err(e) => Self {
+                ok: false,
+                height: None,
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListenerCheck {
+    pub ok: bool,
+    pub last_block: Option<u64>,
+    /// Seconds since the last progress (or since startup)
+    pub lag_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NotifierCheck {
+    pub ok: bool,
+    #[serde(flatten)]
+    pub status: NotifierStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub starknet: NodeCheck,
+    /// `None` without MONERO_DAEMON_URL
+    pub monero: Option<NodeCheck>,
+    pub listener: ListenerCheck,
+    pub notifier: NotifierCheck,
+}
+
+impl HealthChecks {
+    pub fn new(
+        rpc: RpcEndpoint,
+        monero_daemon: Option<String>,
+        progress: ListenerProgress,
+        notifier: Notifier,
+    ) -> Self {
+        Self {
+            rpc,
+            monero_daemon,
+            client: Client::new(),
+            progress,
+            notifier,
+            max_lag_secs: DEFAULT_MAX_LAG_SECS,
+            started_at: unix_now(),
+        }
+    }
+
+    pub fn with_max_lag(mut self, max_lag_secs: u64) -> Self {
+        self.max_lag_secs = max_lag_secs;
+        self
+    }
+
+    /// Run every check
+    pub async fn report(&self) -> HealthReport {
+        let starknet = async {
+            let height = self.rpc.request(|provider| async move {
+                Ok(provider.block_number().await?)
+            });
+            NodeCheck::from_result(match tokio::time::timeout(CHECK_TIMEOUT, height).await {
+                Ok(height) => height,
+                Err(_) => Err(anyhow::anyhow!("no answer in {:?}", CHECK_TIMEOUT)),
+            })
+        };
+        let monero = async {
+            let daemon = self.monero_daemon.as_deref()?;
+            let height = tokio::time::timeout(CHECK_TIMEOUT, daemon_height(&self.client, daemon));
+            Some(NodeCheck::from_result(match height.await {
+                Ok(height) => height,
+                Err(_) => Err(anyhow::anyhow!("no answer in {:?}", CHECK_TIMEOUT)),
+            }))
+        };
+        let (starknet, monero) = tokio::join!(starknet, monero);
+        let listener = listener_check(
+            self.progress.last_block(),
+            self.progress.last_progress().unwrap_or(self.started_at),
+            unix_now(),
+            self.max_lag_secs,
+        );
+        let status = self.notifier.status();
+        let notifier = NotifierCheck {
+            ok: !status.channels.is_empty() && status.failing.is_empty(),
+            status,
+        };
+        HealthReport {
+            healthy: starknet.ok
+                && monero.as_ref().is_none_or(|check| check.ok)
+                && listener.ok
+                && notifier.ok,
+            starknet,
+            monero,
+            listener,
+            notifier,
+        }
+    }
+}
+
+/// Whether the listener moved within `max_lag_secs` of `now`
+pub fn listener_check(
+    last_block: Option<u64>,
+    last_progress: u64,
+    now: u64,
+    max_lag_secs: u64,
+) -> ListenerCheck {
+    let lag_secs = now.saturating_sub(last_progress);
+    ListenerCheck {
+        ok: lag_secs <= max_lag_secs,
+        last_block,
+        lag_secs,
+    }
+}
+
+/// `GET /health`
+pub fn router(checks: HealthChecks) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .with_state(checks)
+}
+
+async fn health(State(checks): State<HealthChecks>) -> (StatusCode, Json<HealthReport>) {
+    let report = checks.report().await;
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listener_fails_once_it_stops_moving() {
+        let check = listener_check(Some(812_000), 1_000, 1_200, DEFAULT_MAX_LAG_SECS);
+        assert!(check.ok);
+        assert_eq!(check.lag_secs, 200);
+
+        let stalled = listener_check(Some(812_000), 1_000, 1_301, DEFAULT_MAX_LAG_SECS);
+        assert!(!stalled.ok);
+
+        // Before the first poll, the lag counts from startup
+        let starting = listener_check(None, 1_000, 1_010, DEFAULT_MAX_LAG_SECS);
+        assert!(starting.ok);
+        assert_eq!(
+            serde_json::to_value(&starting).unwrap(),
+            serde_json::json!({ "ok": true, "last_block": null, "lag_secs": 10 })
+        );
+    }
+}
@@ -0,0 +1,301 @@
+// Append-only journal of raw Starknet events
+//
+// Every event the listener receives is appended, before parsing, to a
+// memory-mapped file of CRC-framed binary records. On restart the journal
+// can be replayed through `parse_event` to rebuild grace-period tracking,
+// and `--dump-journal` prints it for forensic inspection. The journal is
+// independent of any other watchtower state.
+//
+// Layout:
+//
+//   header:  b"WTJRNL01"
+//   record:  len: u32 LE | crc32(payload): u32 LE | payload
+//   payload: block_number: u64 LE | from_address: [u8; 32] | transaction_hash: [u8; 32]
+//            | n_keys: u16 LE | keys: n_keys * [u8; 32]
+//            | n_data: u16 LE | data: n_data * [u8; 32]
+//
+// The file grows in `GROW_BYTES` steps and is zero-filled, so a zero length
+// marks the end of the journal. A record whose CRC does not match (torn
+// write after a crash) also ends the journal and is overwritten by the next
+// append.
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use memmap2::MmapMut;
+use serde::{Deserialize, Serialize};
+use starknet_core::types::{EmittedEvent, Felt};
+use tracing::warn;
+
+const MAGIC: &[u8; 8] = b"WTJRNL01";
+const RECORD_HEADER_LEN: usize = 8;
+const FELT_LEN: usize = 32;
+const GROW_BYTES: u64 = 1 << 20;
+
+/// A raw event as received from the node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub block_number: u64,
+    pub from_address: Felt,
+    pub transaction_hash: Felt,
+    pub keys: Vec<Felt>,
+    pub data: Vec<Felt>,
+}
+
+impl JournalRecord {
+    pub fn from_event(event: &EmittedEvent, block_number: u64) -> Self {
+        Self {
+            block_number,
+            from_address: event.from_address,
+            transaction_hash: event.transaction_hash,
+            keys: event.keys.clone(),
+            data: event.data.clone(),
+        }
+    }
+
+    /// Rebuild the event for replay through the normal parser
+    pub fn to_event(&self) -> EmittedEvent {
+        EmittedEvent {
+            from_address: self.from_address,
+            keys: self.keys.clone(),
+            data: self.data.clone(),
+            block_hash: None,
+            block_number: Some(self.block_number),
+            transaction_hash: self.transaction_hash,
+        }
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let n_keys = u16::try_from(self.keys.len()).context("Too many event keys to journal")?;
+        let n_data = u16::try_from(self.data.len()).context("Too much event data to journal")?;
+
+        let mut out = Vec::with_capacity(
+            8 + 2 * FELT_LEN + 4 + (self.keys.len() + self.data.len()) * FELT_LEN,
+        );
+        out.extend_from_slice(&self.block_number.to_le_bytes());
+        out.extend_from_slice(&self.from_address.to_bytes_be());
+        out.extend_from_slice(&self.transaction_hash.to_bytes_be());
+        out.extend_from_slice(&n_keys.to_le_bytes());
+        for key in &self.keys {
+            out.extend_from_slice(&key.to_bytes_be());
+        }
+        out.extend_from_slice(&n_data.to_le_bytes());
+        for felt in &self.data {
+            out.extend_from_slice(&felt.to_bytes_be());
+        }
+        Ok(out)
+    }
+
+    fn decode(mut payload: &[u8]) -> Option<Self> {
+        let block_number = u64::from_le_bytes(take(&mut payload, 8)?.try_into().ok()?);
+        let from_address = take_felt(&mut payload)?;
+        let transaction_hash = take_felt(&mut payload)?;
+        let keys = take_felts(&mut payload)?;
+        let data = take_felts(&mut payload)?;
+        payload.is_empty().then_some(Self {
+            block_number,
+            from_address,
+            transaction_hash,
+            keys,
+            data,
+        })
+    }
+}
+
+/// Memory-mapped append-only event journal
+pub struct EventJournal {
+    path: PathBuf,
+    file: File,
+    map: MmapMut,
+    /// Offset where the next record is written
+    tail: usize,
+}
+
+impl EventJournal {
+    /// Open (or create) the journal at `path`, positioned after the last
+    /// intact record
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open journal {}", path.display()))?;
+
+        let fresh = file.metadata()?.len() == 0;
+        if fresh {
+            file.set_len(GROW_BYTES)?;
+        }
+        let mut map = map_file(&file)?;
+        if fresh {
+            map[..MAGIC.len()].copy_from_slice(MAGIC);
+            map.flush()?;
+        } else if map.len() < MAGIC.len() || &map[..MAGIC.len()] != MAGIC {
+            bail!("{} is not a watchtower event journal", path.display());
+        }
+
+        let (tail, torn) = scan(&map);
+        if torn {
+            warn!("Journal {} has a torn record at offset {}; truncating", path.display(), tail);
+            map[tail..].fill(0);
+            map.flush()?;
+        }
+
+        Ok(Self {
+            path,
+            file,
+            map,
+            tail,
+        })
+    }
+
+    /// Append one record (flushed asynchronously)
+    pub fn append(&mut self, record: &JournalRecord) -> Result<()> {
+        let payload = record.encode()?;
+        let needed = RECORD_HEADER_LEN + payload.len();
+        if self.tail + needed > self.map.len() {
+            self.grow(needed)?;
+        }
+
+        let start = self.tail;
+        let body = start + RECORD_HEADER_LEN;
+        self.map[body..body + payload.len()].copy_from_slice(&payload);
+        self.map[start + 4..body].copy_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        // Length last: a zero length still marks the end if we crash mid-write
+        self.map[start..start + 4].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+        self.map.flush_async_range(start, needed)?;
+        self.tail += needed;
+        Ok(())
+    }
+
+    /// All intact records, in append order
+    pub fn records(&self) -> Vec<JournalRecord> {
+        let mut records = Vec::new();
+        let mut offset = MAGIC.len();
+        while let Some((payload, next)) = read_frame(&self.map, offset) {
+            match JournalRecord::decode(payload) {
+                Some(record) => records.push(record),
+                None => warn!("Skipping undecodable journal record at offset {}", offset),
+            }
+            offset = next;
+        }
+        records
+    }
+
+    /// Block until all appended records are on disk
+    pub fn sync(&self) -> Result<()> {
+        self.map.flush()?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn grow(&mut self, needed: usize) -> Result<()> {
+        self.map.flush()?;
+        let steps = (self.tail + needed) as u64 / GROW_BYTES + 1;
+        self.file.set_len(steps * GROW_BYTES)?;
+        self.map = map_file(&self.file)?;
+        Ok(())
+    }
+}
+
+fn map_file(file: &File) -> Result<MmapMut> {
+    // SAFETY: the journal file is only modified through this mapping; a
+    // concurrent external writer would at worst produce CRC failures, which
+    // end the scan instead of yielding bad records.
+    Ok(unsafe { MmapMut::map_mut(file)? })
+}
+
+/// Offset after the last intact record, and whether a torn record follows it
+fn scan(map: &[u8]) -> (usize, bool) {
+    let mut offset = MAGIC.len();
+    while let Some((_, next)) = read_frame(map, offset) {
+        offset = next;
+    }
+    let torn = map
+        .get(offset..offset + RECORD_HEADER_LEN)
+        .is_some_and(|header| header.iter().any(|b| *b != 0));
+    (offset, torn)
+}
+
+/// CRC-checked payload at `offset` and the offset of the next frame
+fn read_frame(map: &[u8], offset: usize) -> Option<(&[u8], usize)> {
+    let header = map.get(offset..offset + RECORD_HEADER_LEN)?;
+    let len = u32::from_le_bytes(header[..4].try_into().ok()?) as usize;
+    if len == 0 {
+        return None;
+    }
+    let crc = u32::from_le_bytes(header[4..].try_into().ok()?);
+    let body = offset + RECORD_HEADER_LEN;
+    let payload = map.get(body..body + len)?;
+    (crc32fast::hash(payload) == crc).then_some((payload, body + len))
+}
+
+fn take<'a>(buf: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if buf.len() < n {
+        return None;
+    }
+    let (head, rest) = buf.split_at(n);
+    *buf = rest;
+    Some(head)
+}
+
+fn take_felt(buf: &mut &[u8]) -> Option<Felt> {
+    let bytes: [u8; FELT_LEN] = take(buf, FELT_LEN)?.try_into().ok()?;
+    Some(Felt::from_bytes_be(&bytes))
+}
+
+fn take_felts(buf: &mut &[u8]) -> Option<Vec<Felt>> {
+    let count = u16::from_le_bytes(take(buf, 2)?.try_into().ok()?);
+    (0..count).map(|_| take_felt(buf)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(block_number: u64) -> JournalRecord {
+        JournalRecord {
+            block_number,
+            from_address: Felt::ONE,
+            transaction_hash: Felt::from(block_number),
+            keys: vec![Felt::TWO, Felt::THREE],
+            data: vec![Felt::from(1_700_000_000u64); 4],
+        }
+    }
+
+    #[test]
+    fn records_survive_reopen_and_torn_tail() {
+        let path = std::env::temp_dir().join(format!("wt-journal-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let mut journal = EventJournal::open(&path).unwrap();
+            journal.append(&record(1)).unwrap();
+            journal.append(&record(2)).unwrap();
+            journal.sync().unwrap();
+        }
+
+        // Simulate a crash halfway through a third record: length and
+        // garbage written, CRC never updated
+        {
+            let mut journal = EventJournal::open(&path).unwrap();
+            let tail = journal.tail;
+            journal.map[tail..tail + 4].copy_from_slice(&100u32.to_le_bytes());
+            journal.map[tail + RECORD_HEADER_LEN] = 0xff;
+            journal.sync().unwrap();
+        }
+
+        let mut journal = EventJournal::open(&path).unwrap();
+        assert_eq!(journal.records(), vec![record(1), record(2)]);
+        journal.append(&record(3)).unwrap();
+        assert_eq!(journal.records(), vec![record(1), record(2), record(3)]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
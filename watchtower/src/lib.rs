@@ -1,5 +1,20 @@
 pub mod alerts;
+pub mod api;
+pub mod anomaly;
+pub mod confirmations;
+pub mod dedup;
+pub mod handoff;
+pub mod health;
+pub mod journal;
+pub mod metrics;
 pub mod monero;
+pub mod registry;
+pub mod reload;
+pub mod rules;
+pub mod settings;
+pub mod shutdown;
 pub mod starknet;
+pub mod systemd;
+pub mod tracker;
 pub mod types;
 
@@ -1,19 +1,89 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use tokio::sync::mpsc;
-use tracing::{info, Level};
+use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 mod alerts;
+mod api;
+mod anomaly;
+mod confirmations;
+mod dedup;
+mod handoff;
+mod health;
+mod journal;
+mod metrics;
 mod starknet;
 mod monero;
+mod registry;
+mod reload;
+mod rules;
+mod settings;
+mod shutdown;
+mod systemd;
+mod tracker;
 mod types;
 
-use starknet::listener::{StarknetListener, SwapEvent};
-use alerts::notifier::Notifier;
-use types::{Alert, AlertLevel, SwapState};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anomaly::AnomalyDetector;
+use dedup::{EventDedup, DEFAULT_DEDUP_WINDOW_SECS};
+use handoff::StateStore;
+use health::HealthChecks;
+use confirmations::{parse_watched_contract, requirement, ConfirmationRequirement};
+use journal::EventJournal;
+use metrics::METRICS;
+use registry::{ContractRegistry, WatchedContract};
+use monero::tx_monitor::{
+    parse_watched_transaction, ConfirmedLocks, GraceDeadlines, MoneroTxMonitor,
+    WatchedTransaction,
+};
+use monero::watcher::{
+    parse_watched_key_image, KeyImageWatcher, RevealedContracts, WatchedKeyImage,
+};
+use starknet_core::types::Felt;
+use starknet::auto_claim::AutoClaimer;
+use starknet::auto_refund::{AutoRefunder, DEFAULT_REFUND_LOG};
+use starknet::client::{call_view, check_chain_ids, configured_chain_id, connect};
+use starknet::failover::{rpc_urls, DEFAULT_HEALTH_INTERVAL_SECS};
+use starknet::fee_payer::{load_presigned, spawn_presigned, FeePayer};
+use starknet::outside_execution::{OutsideExecutionSpec, SignedOutsideExecution};
+use starknet::reorg::DEFAULT_REORG_WINDOW;
+use starknet::subscription::EventSubscription;
+use starknet::listener::{
+    parse_events, StarknetListener, SwapEvent, DEFAULT_BLOCK_CHUNK, DEFAULT_MAX_CONCURRENT_FETCHES,
+};
+use alerts::notifier::{Channel, MessageLimits, Notifier, NotifierCredentials};
+use alerts::policy::{level_routes_from_env, ChannelPolicy};
+use alerts::queue::RetryQueue;
+use alerts::templates::AlertTemplates;
+use rules::{ActionRunner, RuleEngine};
+use settings::Settings;
+use swap_common::format::{AmountFormat, TOKEN};
+use shutdown::{Shutdown, DEFAULT_SHUTDOWN_TIMEOUT_SECS};
+use tracker::SwapTracker;
+use types::ContractVersion;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Forensics: print a journal as JSON lines and exit
+    if let Some(path) = arg_value("--dump-journal")? {
+        let journal = EventJournal::open(&path)?;
+        for record in journal.records() {
+            println!("{}", serde_json::to_string(&record)?);
+        }
+        return Ok(());
+    }
+
+    // Cold machine: sign calls for the fee payer to submit later, then exit
+    if let Some(path) = arg_value("--sign-outside-execution")? {
+        dotenvy::dotenv().ok();
+        let signed = sign_outside_execution(&path)?;
+        println!("{}", serde_json::to_string_pretty(&signed)?);
+        return Ok(());
+    }
+
     // Initialize logging
     let subscriber = FmtSubscriber::builder()
         .with_max_level(Level::INFO)
@@ -24,50 +94,484 @@ async fn main() -> Result<()> {
 
     // Load configuration
     dotenvy::dotenv().ok();
-    
-    let discord_webhook = std::env::var("DISCORD_WEBHOOK").ok();
-    let telegram_token = std::env::var("TELEGRAM_BOT_TOKEN").ok();
-    let telegram_chat = std::env::var("TELEGRAM_CHAT_ID").ok();
+    // Per-contract settings (WATCHTOWER_CONFIG or ./watchtower.toml)
+    let settings = Settings::from_env().context("Refusing to start the watchtower")?;
+    if !settings.contracts.is_empty() {
+        info!("Loaded {} contract(s) from the config file", settings.contracts.len());
+    }
+
+    // Environment credentials win over the file's [alerts]
+    let credentials = NotifierCredentials::from_env()?.or(settings.credentials.clone());
+
+    // Per-channel message limits (long alerts are chunked, then attached)
+    let defaults = MessageLimits::default();
+    let limits = MessageLimits {
+        discord_max_chars: env_usize("DISCORD_MAX_CHARS").unwrap_or(defaults.discord_max_chars),
+        telegram_max_chars: env_usize("TELEGRAM_MAX_CHARS").unwrap_or(defaults.telegram_max_chars),
+        max_chunks: env_usize("ALERT_MAX_CHUNKS").unwrap_or(defaults.max_chunks),
+    };
 
     // Initialize notifier
-    let notifier = Notifier::new(discord_webhook, telegram_token, telegram_chat);
+    let policies = Channel::ALL
+        .into_iter()
+        .map(|channel| Ok((channel, ChannelPolicy::from_env(&channel.name().to_uppercase())?)))
+        .collect::<Result<HashMap<_, _>>>()?;
+    // Level -> channels, ALERT_ROUTE_<LEVEL> overriding the file's [routing]
+    let mut routing = settings.routing.clone();
+    routing.extend(level_routes_from_env()?);
+    for (level, channels) in &routing {
+        for channel in channels.iter().filter(|c| !credentials.configured(**c)) {
+            warn!("{:?} alerts are routed to {}, which is not configured", level, channel.name());
+        }
+    }
+    let notifier = Notifier::from_credentials(credentials)
+        .with_limits(limits)
+        .with_policies(policies)
+        .with_level_channels(routing)
+        .with_retry_queue(RetryQueue::from_env()?)
+        .with_contract_channels(settings.contract_channels());
+    // Failed deliveries (e.g. a Discord outage) are retried with backoff
+    notifier.spawn_retries();
+    // Operator wording for the built-in alerts (WATCHTOWER_TEMPLATES)
+    let templates = AlertTemplates::from_env()?;
 
     // Create event channel
     let (event_tx, mut event_rx) = mpsc::channel::<SwapEvent>(100);
 
+    // Developer mode: feed synthetic events through the normal pipeline.
+    // Only notifiers see them: no auto claim/refund, rule commands or
+    // pre-signed executions, and nothing is written to WATCHTOWER_SWAPS
+    let injected = injected_events()?;
+    let test_mode = !injected.is_empty();
+    if test_mode {
+        warn!("Test mode: injecting {} synthetic event(s)", injected.len());
+        let inject_tx = event_tx.clone();
+        tokio::spawn(async move {
+            for event in injected {
+                info!("Injecting synthetic event: {:?}", event);
+                if inject_tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // Raw event journal: record everything received, optionally replay it
+    let journal = match std::env::var("WATCHTOWER_JOURNAL") {
+        Ok(path) => Some(EventJournal::open(&path)?),
+        Err(_) => None,
+    };
+    if std::env::args().any(|arg| arg == "--replay-journal") {
+        let journal = journal
+            .as_ref()
+            .context("--replay-journal requires WATCHTOWER_JOURNAL")?;
+        let events: Vec<_> = journal.records().iter().map(|record| record.to_event()).collect();
+        let replayed: Vec<SwapEvent> = events
+            .chunk_by(|a, b| a.block_number == b.block_number)
+            .flat_map(|block| parse_events(block, block[0].block_number.unwrap_or(0)))
+            .collect();
+        info!(
+            "Replaying {} event(s) from {}",
+            replayed.len(),
+            journal.path().display()
+        );
+        let replay_tx = event_tx.clone();
+        tokio::spawn(async move {
+            for event in replayed {
+                if replay_tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
     // Initialize Starknet listener
-    // Load RPC URL from env or use default (ZAN public endpoint)
-    let rpc_url = std::env::var("STARKNET_RPC_URL")
-        .unwrap_or_else(|_| "https://api.zan.top/public/starknet-sepolia".to_string());
+    // Load RPC URLs from env (in order of preference) or use default (ZAN public endpoint)
+    let rpc_urls = rpc_urls(
+        &std::env::var("STARKNET_RPC_URL")
+            .unwrap_or_else(|_| "https://api.zan.top/public/starknet-sepolia".to_string()),
+    );
+    // Refuse to watch a node on another network than STARKNET_CHAIN_ID
+    let chain_id = configured_chain_id()?;
+    let rpc_urls = check_chain_ids(&rpc_urls, chain_id)
+        .await
+        .context("Refusing to start the watchtower")?;
+    // Fee payer, timelock checks and auto claim/refund use the preferred endpoint
+    let rpc_url = rpc_urls[0].clone();
     
-    // Load watched contracts from env (comma-separated)
+    // Load watched contracts from env (comma-separated, optional `:<xmr>` value)
     let watched_contracts_str = std::env::var("WATCHED_CONTRACTS").unwrap_or_default();
-    let watched_contracts: Vec<starknet_core::types::Felt> = watched_contracts_str
-        .split(',')
-        .filter_map(|s| {
-            let s = s.trim();
-            if s.is_empty() {
-                None
-            } else {
-                // Parse hex address (with or without 0x prefix)
-                let hex = s.strip_prefix("0x").unwrap_or(s);
-                // Use Felt::from_hex_str or parse manually
-                hex.parse::<starknet_core::types::Felt>().ok()
+    let mut requirements: HashMap<Felt, ConfirmationRequirement> = HashMap::new();
+    let registry = ContractRegistry::default();
+    for entry in watched_contracts_str.split(',').filter(|s| !s.trim().is_empty()) {
+        match parse_watched_contract(entry) {
+            Ok((contract, amount)) => {
+                requirements.insert(contract, requirement(amount));
+                registry.insert(WatchedContract::new(contract, amount));
             }
-        })
+            Err(e) => warn!("Ignoring WATCHED_CONTRACTS entry '{}': {:#}", entry.trim(), e),
+        }
+    }
+    for contract in &settings.contracts {
+        if contract.xmr_amount.is_some() || !requirements.contains_key(&contract.address) {
+            requirements.insert(contract.address, requirement(contract.xmr_amount));
+            registry.insert(WatchedContract::new(contract.address, contract.xmr_amount));
+        }
+    }
+    let expected_amounts: HashMap<Felt, u128> = settings
+        .contracts
+        .iter()
+        .filter_map(|contract| Some((contract.address, contract.expected_amount?)))
         .collect();
+    // Unknown contracts (e.g. injected events) get the strictest tier
+    let default_requirement = requirement(None);
+    let finality_depth = requirements
+        .values()
+        .map(|r| r.starknet_depth)
+        .max()
+        .unwrap_or(default_requirement.starknet_depth);
     
-    if watched_contracts.is_empty() {
+    // Deployed timelocks must match the configured ones
+    let provider = connect(&rpc_url)?;
+    for contract in &settings.contracts {
+        let Some(expected) = contract.lock_until else {
+            continue;
+        };
+        match call_view(&provider, contract.address, "get_lock_until").await {
+            Ok(actual) if actual == Felt::from(expected) => {}
+            Ok(actual) => {
+                warn!(
+                    "Contract {:x} is locked until {}, expected {}",
+                    contract.address, actual, expected
+                );
+                notifier.send_alert(&templates.alert(
+                    "timelock_mismatch",
+                    contract.address,
+                    serde_json::json!({
+                        "lock_until": actual.to_string(),
+                        "expected_lock_until": expected,
+                    }),
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)?
+                        .as_secs(),
+                )).await?;
+            }
+            Err(e) => warn!("Could not check the timelock of {:x}: {:#}", contract.address, e),
+        }
+    }
+
+    let watched_count = registry.len();
+    if registry.is_empty() {
         info!("No contracts configured for monitoring. Add WATCHED_CONTRACTS to .env");
     } else {
-        info!("Monitoring {} contract(s)", watched_contracts.len());
+        info!("Monitoring {} contract(s)", watched_count);
     }
     
+    // Concurrent (contract, block) fetches when catching up after downtime
+    let max_concurrent_fetches =
+        env_usize("STARKNET_FETCH_CONCURRENCY").unwrap_or(DEFAULT_MAX_CONCURRENT_FETCHES);
+
     let listener = StarknetListener::new(
-        &rpc_url,
-        watched_contracts,
+        &rpc_urls,
+        registry.clone(),
         event_tx,
-    )?;
+    )?
+    .with_max_concurrent_fetches(max_concurrent_fetches)
+    .with_block_chunk(
+        env_usize("STARKNET_BLOCK_CHUNK").map_or(DEFAULT_BLOCK_CHUNK, |blocks| blocks as u64),
+    )
+    .with_finality_depth(finality_depth)
+    .with_reorg_window(
+        env_usize("STARKNET_REORG_WINDOW").map_or(DEFAULT_REORG_WINDOW, |window| window as u64),
+    );
+    info!("Waiting for {} Starknet block(s) before reporting events", finality_depth);
+    // Optional WebSocket subscription: poll as soon as a watched contract
+    // emits an event instead of every 10 s
+    let listener = match std::env::var("STARKNET_WS_URL") {
+        Ok(ws_url) => {
+            let subscription = EventSubscription::new(&ws_url, registry.clone());
+            let listener = listener.with_wakeup(subscription.wakeup());
+            subscription.spawn();
+            listener
+        }
+        Err(_) => listener,
+    };
+    let listener = match journal {
+        Some(journal) => listener.with_journal(journal),
+        None => listener,
+    };
+
+    // Cursor + lease: a second instance takes over without gaps or duplicates.
+    // Without a lease the cursors still survive restarts (WATCHTOWER_CHECKPOINT)
+    let listener = match std::env::var("WATCHTOWER_STATE") {
+        Ok(path) => {
+            let instance_id = format!(
+                "pid{}-{}",
+                std::process::id(),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_secs()
+            );
+            let store = StateStore::new(&path);
+            systemd::notify_status("Waiting for watchtower lease");
+            store.acquire(&instance_id).await?;
+            listener.with_state_store(store, instance_id)
+        }
+        Err(_) => {
+            let path = std::env::var("WATCHTOWER_CHECKPOINT")
+                .unwrap_or_else(|_| "watchtower_state.json".to_string());
+            info!("Checkpointing processed blocks to {}", path);
+            listener.with_checkpoint(StateStore::new(&path))
+        }
+    };
+
+    // Return to the preferred RPC endpoint once it answers again
+    listener.rpc_endpoint().spawn_health_checks(std::time::Duration::from_secs(
+        env_usize("STARKNET_RPC_HEALTH_INTERVAL")
+            .map_or(DEFAULT_HEALTH_INTERVAL_SECS, |secs| secs.max(1) as u64),
+    ));
+    if rpc_urls.len() > 1 {
+        info!("{} Starknet RPC endpoints configured, failing over in order", rpc_urls.len());
+    }
+
+    // SIGINT/SIGTERM: finish the current batch, flush alerts and save state
+    let shutdown = Shutdown::new();
+    shutdown::spawn_signal_handler(shutdown.clone())?;
+    let listener = listener.with_shutdown(shutdown);
+
+    // Rotate webhook/bot/RPC credentials on SIGHUP without a restart
+    reload::spawn_sighup_reload(notifier.clone(), listener.rpc_endpoint(), chain_id)?;
+
+    // Operator rules: extra actions on decoded events
+    let rules = match std::env::var("WATCHTOWER_RULES") {
+        Ok(path) => {
+            let rules = RuleEngine::load(&path)?;
+            info!("Loaded {} rule(s) from {}", rules.len(), path);
+            Some(rules)
+        }
+        Err(_) => None,
+    };
+    let mut actions = ActionRunner::new(notifier.clone());
+    if test_mode {
+        actions = actions.alerts_only();
+    }
+
+    // Gas account: submit pre-signed claims/unlocks for the main account
+    if test_mode && std::env::var("PRESIGNED_EXECUTIONS").is_ok() {
+        warn!("Test mode: ignoring PRESIGNED_EXECUTIONS");
+    } else if let Ok(path) = std::env::var("PRESIGNED_EXECUTIONS") {
+        let address = env_felt("FEE_PAYER_ADDRESS")?;
+        let private_key = env_felt("FEE_PAYER_PRIVATE_KEY")?;
+        let executions = load_presigned(&path)?;
+        let fee_payer = Arc::new(FeePayer::new(&rpc_url, address, private_key, chain_id).await?);
+        if rules.as_ref().is_some_and(RuleEngine::has_claim_actions) {
+            info!(
+                "Fee payer 0x{:x} holds {} pre-signed execution(s) for claim rules",
+                fee_payer.address(),
+                executions.len()
+            );
+            actions = actions.with_claims(fee_payer, executions);
+        } else {
+            info!(
+                "Fee payer 0x{:x} scheduled {} pre-signed execution(s)",
+                fee_payer.address(),
+                executions.len()
+            );
+            spawn_presigned(fee_payer, executions, notifier.clone());
+        }
+    }
+
+    // Taker safety: alert if a locked XMR output is spent before the reveal
+    let revealed = RevealedContracts::default();
+    let mut watched_key_images = Vec::new();
+    let watched_key_images_str = std::env::var("WATCHED_KEY_IMAGES").unwrap_or_default();
+    for entry in watched_key_images_str.split(',').filter(|s| !s.trim().is_empty()) {
+        match parse_watched_key_image(entry) {
+            Ok(watched) => watched_key_images.push(watched),
+            Err(e) => warn!("Ignoring WATCHED_KEY_IMAGES entry: {:#}", e),
+        }
+    }
+    for contract in &settings.contracts {
+        if let Some(key_image) = &contract.key_image {
+            watched_key_images.push(WatchedKeyImage {
+                contract: contract.address,
+                key_image: key_image.clone(),
+            });
+        }
+    }
+    if !watched_key_images.is_empty() {
+        let daemon_url = std::env::var("MONERO_DAEMON_URL")
+            .context("WATCHED_KEY_IMAGES requires MONERO_DAEMON_URL")?;
+        let watcher = KeyImageWatcher::new(
+            &daemon_url,
+            watched_key_images,
+            revealed.clone(),
+            notifier.clone(),
+        );
+        tokio::spawn(watcher.run());
+    }
+
+    // Confirmations of the XMR lock transactions, escalated at grace-period end
+    let grace_deadlines = GraceDeadlines::default();
+    let mut watched_txs = Vec::new();
+    let watched_txs_str = std::env::var("WATCHED_MONERO_TXS").unwrap_or_default();
+    for entry in watched_txs_str.split(',').filter(|s| !s.trim().is_empty()) {
+        let required = entry
+            .split_once(':')
+            .and_then(|(contract, _)| Felt::from_hex(contract.trim()).ok())
+            .and_then(|contract| requirements.get(&contract))
+            .unwrap_or(&default_requirement)
+            .monero_confirmations;
+        match parse_watched_transaction(entry, required) {
+            Ok(watched) => watched_txs.push(watched),
+            Err(e) => warn!("Ignoring WATCHED_MONERO_TXS entry: {:#}", e),
+        }
+    }
+    for contract in &settings.contracts {
+        if let Some(txid) = &contract.monero_txid {
+            watched_txs.push(WatchedTransaction {
+                contract: contract.address,
+                txid: txid.clone(),
+                required: requirements
+                    .get(&contract.address)
+                    .unwrap_or(&default_requirement)
+                    .monero_confirmations,
+            });
+        }
+    }
+    let confirmed_locks = ConfirmedLocks::default();
+    let locked_contracts: HashSet<Felt> = watched_txs.iter().map(|w| w.contract).collect();
+    if !watched_txs.is_empty() {
+        let daemon_url = std::env::var("MONERO_DAEMON_URL")
+            .context("WATCHED_MONERO_TXS requires MONERO_DAEMON_URL")?;
+        let monitor = MoneroTxMonitor::new(
+            &daemon_url,
+            watched_txs,
+            grace_deadlines.clone(),
+            notifier.clone(),
+        )
+        .with_confirmed_locks(confirmed_locks.clone());
+        tokio::spawn(monitor.run());
+    }
+
+    // Active mode: claim with the unlocker key once the XMR lock is confirmed
+    let auto_claimer = match std::env::var("AUTO_CLAIM_ADDRESS") {
+        Ok(_) if test_mode => {
+            warn!("Test mode: ignoring AUTO_CLAIM_ADDRESS");
+            None
+        }
+        Ok(_) => {
+            if locked_contracts.is_empty() {
+                bail!("AUTO_CLAIM_ADDRESS requires WATCHED_MONERO_TXS");
+            }
+            let claimer = AutoClaimer::new(
+                &rpc_url,
+                env_felt("AUTO_CLAIM_ADDRESS")?,
+                env_felt("AUTO_CLAIM_PRIVATE_KEY")?,
+                chain_id,
+                confirmed_locks.clone(),
+                notifier.clone(),
+            )
+            .await?;
+            warn!(
+                "Active mode: 0x{:x} claims {} contract(s) automatically",
+                claimer.address(),
+                locked_contracts.len()
+            );
+            Some(Arc::new(claimer))
+        }
+        Err(_) => None,
+    };
+
+    // Active mode: refund with the depositor key once a timelock expires unrevealed
+    let mut pending_refunds: HashMap<Felt, tokio::task::AbortHandle> = HashMap::new();
+    let auto_refunder = match std::env::var("AUTO_REFUND_ADDRESS") {
+        Ok(_) if test_mode => {
+            warn!("Test mode: ignoring AUTO_REFUND_ADDRESS");
+            None
+        }
+        Ok(_) => {
+            let log = std::env::var("AUTO_REFUND_LOG")
+                .unwrap_or_else(|_| DEFAULT_REFUND_LOG.to_string());
+            let refunder = AutoRefunder::new(
+                &rpc_url,
+                env_felt("AUTO_REFUND_ADDRESS")?,
+                env_felt("AUTO_REFUND_PRIVATE_KEY")?,
+                chain_id,
+                notifier.clone(),
+            )
+            .await?
+            .with_log(&log);
+            Some(Arc::new(refunder))
+        }
+        Err(_) => None,
+    };
+    // Contracts the depositor account funded (None: every contract the
+    // registry watches when the event arrives, including ones added later)
+    let refund_contracts: Option<HashSet<Felt>> = match std::env::var("AUTO_REFUND_CONTRACTS") {
+        Ok(list) => Some(
+            list.split(',')
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| {
+                    Felt::from_hex(s.trim()).with_context(|| {
+                        format!("Invalid AUTO_REFUND_CONTRACTS entry '{}'", s.trim())
+                    })
+                })
+                .collect::<Result<_>>()?,
+        ),
+        Err(_) => None,
+    };
+    let refunds = |contract: Felt| match &refund_contracts {
+        Some(contracts) => contracts.contains(&contract),
+        None => registry.contains(contract),
+    };
+    if let Some(refunder) = &auto_refunder {
+        let contracts = match &refund_contracts {
+            Some(contracts) => contracts.iter().copied().collect(),
+            None => registry.addresses(),
+        };
+        warn!(
+            "Active mode: 0x{:x} refunds {} contract(s) after their timelock",
+            refunder.address(),
+            contracts.len()
+        );
+        for contract in contracts {
+            pending_refunds.insert(contract, refunder.spawn(contract));
+        }
+    }
+
+    // State of each swap, updated from its events and served under /swaps
+    // (kept in memory in test mode so synthetic events are never persisted)
+    let swaps = if test_mode {
+        SwapTracker::default()
+    } else {
+        SwapTracker::from_env()?
+    };
+
+    // Runtime registration of contracts (POST/DELETE /contracts, GET /swaps),
+    // plus the unauthenticated /metrics and /health
+    if let Ok(addr) = std::env::var("WATCHTOWER_API_ADDR") {
+        let token = std::env::var("WATCHTOWER_API_TOKEN").ok();
+        if token.is_none() {
+            warn!(
+                "WATCHTOWER_API_TOKEN not set: anyone reaching {} can change the watched contracts",
+                addr
+            );
+        }
+        let mut checks = HealthChecks::new(
+            listener.rpc_endpoint(),
+            std::env::var("MONERO_DAEMON_URL").ok(),
+            listener.progress(),
+            notifier.clone(),
+        );
+        if let Some(max_lag) = env_usize("WATCHTOWER_HEALTH_MAX_LAG") {
+            checks = checks.with_max_lag(max_lag as u64);
+        }
+        let router = api::router(registry.clone(), swaps.clone(), token)
+            .merge(metrics::router())
+            .merge(health::router(checks));
+        api::spawn(&addr, router).await?;
+    }
 
     // Spawn listener task
     let listener_handle = tokio::spawn(async move {
@@ -76,10 +580,133 @@ async fn main() -> Result<()> {
         }
     });
 
+    // Service supervision (no-ops without the `systemd` feature)
+    systemd::spawn_watchdog();
+    systemd::notify_status(&format!("Monitoring {} contract(s)", watched_count));
+    systemd::notify_ready();
+
+    // Flags events the protocol spec does not allow (spec/protocol.json)
+    let mut anomalies = AnomalyDetector::new()?;
+    // Drops events delivered twice (same contract, event and transaction),
+    // across restarts so a journal replay does not fire actions again
+    let dedup_window =
+        env_usize("ALERT_DEDUP_WINDOW").map_or(DEFAULT_DEDUP_WINDOW_SECS, |window| window as u64);
+    let mut dedup = if test_mode {
+        EventDedup::new(dedup_window)
+    } else {
+        EventDedup::from_env(dedup_window)?
+    };
+    // Pending "Grace Period Expiring Soon" warnings, cancelled by a reorg
+    let mut grace_warnings: HashMap<Felt, tokio::task::AbortHandle> = HashMap::new();
+    // Pending automatic claims, cancelled once the contract settles or reorgs
+    let mut pending_claims: HashMap<Felt, tokio::task::AbortHandle> = HashMap::new();
+
     // Process events
     while let Some(event) = event_rx.recv().await {
+        let received_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        if !dedup.first_seen(&event, received_at) {
+            info!(
+                "Ignoring duplicate {} for contract {:x} (tx {:x})",
+                event.name(),
+                event.contract_address(),
+                event.transaction_hash()
+            );
+            continue;
+        }
+        let transition = swaps.apply(&event, received_at);
+        if let Some(anomaly) = anomalies.observe(event.contract_address(), event.name()) {
+            warn!("Protocol anomaly: {:?}", anomaly);
+            notifier.send_alert(&templates.alert(
+                "protocol_anomaly",
+                anomaly.contract,
+                serde_json::json!({
+                    "event": anomaly.event,
+                    "phases": anomaly.possible_phases.join(", "),
+                }),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_secs(),
+            )).await?;
+        } else if let Err(invalid) = transition {
+            // The spec cannot see deposits; the tracker can
+            warn!("Invalid swap transition: {:?}", invalid);
+            notifier.send_alert(&templates.alert(
+                "invalid_transition",
+                invalid.contract,
+                serde_json::json!({
+                    "event": invalid.event,
+                    "state": invalid.state.to_string(),
+                }),
+                received_at,
+            )).await?;
+        }
+
+        registry.record_event(event.contract_address(), event.name());
+        METRICS.events.with_label_values(&[event.name()]).inc();
+
+        if let Some(rules) = &rules {
+            actions.handle(rules, &event).await;
+        }
+
         match event {
+            // Contracts deployed before deposit() emitted Deposited never get
+            // here: no `deposited` alert and no expected_amount check for them
+            SwapEvent::Deposited(e) => {
+                info!("Tokens deposited into contract {:x}", e.contract_address);
+                let format = AmountFormat::from_env();
+                notifier.send_alert(&templates.alert(
+                    "deposited",
+                    e.contract_address,
+                    serde_json::json!({
+                        "amount": format.amount(e.amount, TOKEN),
+                        "amount_raw": e.amount.to_string(),
+                        "depositor": format!("0x{:x}", e.depositor),
+                    }),
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)?
+                        .as_secs(),
+                )).await?;
+                if let Some(&expected) = expected_amounts.get(&e.contract_address) {
+                    if e.amount != expected {
+                        notifier.send_alert(&templates.alert(
+                            "unexpected_deposit_amount",
+                            e.contract_address,
+                            serde_json::json!({
+                                "amount": format.amount(e.amount, TOKEN),
+                                "amount_raw": e.amount.to_string(),
+                                "expected_amount": format.amount(expected, TOKEN),
+                                "expected_amount_raw": expected.to_string(),
+                                "depositor": format!("0x{:x}", e.depositor),
+                            }),
+                            std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)?
+                                .as_secs(),
+                        )).await?;
+                    }
+                }
+                // Contracts registered after startup are refunded from their deposit
+                if let Some(refunder) = &auto_refunder {
+                    if refunds(e.contract_address) {
+                        if let Entry::Vacant(entry) = pending_refunds.entry(e.contract_address) {
+                            entry.insert(refunder.spawn(e.contract_address));
+                        }
+                    }
+                }
+            }
             SwapEvent::SecretRevealed(e) => {
+                if let Some(refund) = pending_refunds.remove(&e.contract_address) {
+                    refund.abort();
+                }
+                revealed
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(e.contract_address);
+                // Contracts registered at runtime carry their own swap value
+                let required = registry
+                    .get(e.contract_address)
+                    .map_or(default_requirement, |watched| requirement(watched.xmr_amount));
                 info!(
                     "Secret revealed for contract {:x}, claimable after {}",
                     e.contract_address, e.claimable_after
@@ -91,17 +718,23 @@ async fn main() -> Result<()> {
                 
                 let time_until_claim = e.claimable_after.saturating_sub(now);
                 
-                notifier.send_alert(&Alert {
-                    level: AlertLevel::Info,
-                    title: "Secret Revealed - Grace Period Started".to_string(),
-                    message: format!(
-                        "Secret has been revealed. Tokens claimable in {} minutes.\n\
-                         Monitor Monero transaction for confirmation.",
-                        time_until_claim / 60
-                    ),
-                    contract_address: e.contract_address,
-                    timestamp: now,
-                }).await?;
+                // Legacy contracts release the tokens together with the reveal
+                let kind = match e.contract_version {
+                    ContractVersion::Legacy => "legacy_unlock",
+                    ContractVersion::Current => "secret_revealed",
+                };
+                notifier.send_alert(&templates.alert(
+                    kind,
+                    e.contract_address,
+                    serde_json::json!({
+                        "revealer": format!("0x{:x}", e.revealer),
+                        "claimable_after": e.claimable_after,
+                        "minutes_until_claim": time_until_claim / 60,
+                        "confirmations": required.monero_confirmations,
+                        "contract_version": e.contract_version.to_string(),
+                    }),
+                    now,
+                )).await?;
 
                 // Schedule warning 30 min before grace period expires
                 let warning_time = e.claimable_after.saturating_sub(1800); // 30 min = 1800 sec
@@ -109,9 +742,12 @@ async fn main() -> Result<()> {
                 
                 if warning_delay > 0 && warning_delay < 86400 { // Only schedule if < 24 hours
                     let notifier_clone = notifier.clone();
+                    let templates = templates.clone();
                     let contract = e.contract_address;
+                    let claimable_after = e.claimable_after;
+                    let confirmations = required.monero_confirmations;
                     
-                    tokio::spawn(async move {
+                    let warning = tokio::spawn(async move {
                         tokio::time::sleep(std::time::Duration::from_secs(warning_delay)).await;
                         
                         let now = std::time::SystemTime::now()
@@ -119,44 +755,238 @@ async fn main() -> Result<()> {
                             .unwrap()
                             .as_secs();
                         
-                        notifier_clone.send_alert(&Alert {
-                            level: AlertLevel::Warning,
-                            title: "Grace Period Expiring Soon".to_string(),
-                            message: format!(
-                                "Grace period expires in ~30 minutes. Ensure Monero TX is confirmed.\n\
-                                 Contract: 0x{:x}",
-                                contract
-                            ),
-                            contract_address: contract,
-                            timestamp: now,
-                        }).await.ok();
+                        notifier_clone.send_alert(&templates.alert(
+                            "grace_period_expiring",
+                            contract,
+                            serde_json::json!({
+                                "claimable_after": claimable_after,
+                                "confirmations": confirmations,
+                            }),
+                            now,
+                        )).await.ok();
                     });
+                    grace_warnings.insert(contract, warning.abort_handle());
                 }
 
-                // TODO: Start monitoring Monero confirmations
+                // The lock tx must be confirmed before the grace period ends
+                if e.contract_version == ContractVersion::Current {
+                    grace_deadlines
+                        .write()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(e.contract_address, e.claimable_after);
+                    if let Some(claimer) = &auto_claimer {
+                        if locked_contracts.contains(&e.contract_address) {
+                            let claim = claimer.spawn(e.contract_address, e.claimable_after);
+                            if let Some(previous) =
+                                pending_claims.insert(e.contract_address, claim)
+                            {
+                                previous.abort();
+                            }
+                        } else {
+                            warn!(
+                                "Not auto-claiming {:x}: no WATCHED_MONERO_TXS entry",
+                                e.contract_address
+                            );
+                        }
+                    }
+                }
             }
             SwapEvent::TokensClaimed(e) => {
+                if let Some(claim) = pending_claims.remove(&e.contract_address) {
+                    claim.abort();
+                }
+                if let Some(refund) = pending_refunds.remove(&e.contract_address) {
+                    refund.abort();
+                }
                 info!(
                     "Tokens claimed for contract {:x}",
                     e.contract_address
                 );
-                notifier.send_alert(&Alert {
-                    level: AlertLevel::Info,
-                    title: "Swap Completed".to_string(),
-                    message: format!(
-                        "Tokens successfully claimed. Swap complete.\n\
-                         Amount: {} tokens",
-                        e.amount
-                    ),
-                    contract_address: e.contract_address,
-                    timestamp: e.claim_timestamp,
-                }).await?;
+                METRICS
+                    .reveal_to_claim
+                    .observe(e.claim_timestamp.saturating_sub(e.reveal_timestamp) as f64);
+                notifier.send_alert(&templates.alert(
+                    "swap_completed",
+                    e.contract_address,
+                    serde_json::json!({
+                        "amount": AmountFormat::from_env().amount(e.amount, TOKEN),
+                        "amount_raw": e.amount.to_string(),
+                        "claimer": format!("0x{:x}", e.claimer),
+                        "contract_version": ContractVersion::Current.to_string(),
+                    }),
+                    e.claim_timestamp,
+                )).await?;
+            }
+            SwapEvent::Refunded(e) => {
+                if let Some(claim) = pending_claims.remove(&e.contract_address) {
+                    claim.abort();
+                }
+                if let Some(refund) = pending_refunds.remove(&e.contract_address) {
+                    refund.abort();
+                }
+                warn!("Tokens refunded from contract {:x}", e.contract_address);
+                notifier.send_alert(&templates.alert(
+                    "swap_refunded",
+                    e.contract_address,
+                    serde_json::json!({
+                        "amount": AmountFormat::from_env().amount(e.amount, TOKEN),
+                        "amount_raw": e.amount.to_string(),
+                        "depositor": format!("0x{:x}", e.depositor),
+                    }),
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)?
+                        .as_secs(),
+                )).await?;
+            }
+            SwapEvent::Reverted { fork_block, event } => {
+                let contract = event.contract_address();
+                warn!(
+                    "{} for contract {:x} (block {}) reverted by a reorg after block {}",
+                    event.name(),
+                    contract,
+                    event.block_number(),
+                    fork_block
+                );
+                if matches!(*event, SwapEvent::SecretRevealed(_)) {
+                    revealed
+                        .write()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .remove(&contract);
+                    if let Some(warning) = grace_warnings.remove(&contract) {
+                        warning.abort();
+                    }
+                    if let Some(claim) = pending_claims.remove(&contract) {
+                        claim.abort();
+                    }
+                    // The reveal is gone: the timelock applies again
+                    if let Some(refunder) = &auto_refunder {
+                        if refunds(contract) {
+                            let refund = refunder.spawn(contract);
+                            if let Some(previous) = pending_refunds.insert(contract, refund) {
+                                previous.abort();
+                            }
+                        }
+                    }
+                    grace_deadlines
+                        .write()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .remove(&contract);
+                }
+                anomalies.forget(contract);
+                notifier.send_alert(&templates.alert(
+                    "chain_reorganization",
+                    contract,
+                    serde_json::json!({
+                        "event": event.name(),
+                        "block": event.block_number(),
+                        "fork_block": fork_block,
+                    }),
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)?
+                        .as_secs(),
+                )).await?;
             }
         }
     }
 
+    systemd::notify_stopping();
     listener_handle.await?;
 
+    // The listener saved its checkpoint: give queued alerts a last chance,
+    // then keep the swap states for the next start
+    let flush_timeout = std::time::Duration::from_secs(
+        env_usize("WATCHTOWER_SHUTDOWN_TIMEOUT")
+            .map_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS, |secs| secs as u64),
+    );
+    match tokio::time::timeout(flush_timeout, notifier.flush_queue()).await {
+        Ok(0) => {}
+        Ok(queued) => warn!("{} alert delivery(ies) still queued for the next start", queued),
+        Err(_) => warn!("Alert queue not flushed within {:?}, kept for the next start", flush_timeout),
+    }
+    swaps.save();
+    info!("Watchtower stopped");
+
     Ok(())
 }
 
+/// Numeric environment variable (unset or unparsable -> None)
+fn env_usize(name: &str) -> Option<usize> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+/// Required felt environment variable (hex or decimal)
+fn env_felt(name: &str) -> Result<Felt> {
+    let value = std::env::var(name).with_context(|| format!("{} is not set", name))?;
+    let value = value.trim();
+    let felt = if value.starts_with("0x") {
+        Felt::from_hex(value)
+    } else {
+        Felt::from_dec_str(value)
+    };
+    felt.with_context(|| format!("{} is not a valid felt", name))
+}
+
+/// Sign the `OutsideExecutionSpec` at `path` with the main account key
+/// (MAIN_ACCOUNT_ADDRESS, MAIN_ACCOUNT_PRIVATE_KEY, STARKNET_CHAIN_ID).
+/// Runs offline; FEE_PAYER_ADDRESS, if set, becomes the only allowed caller.
+fn sign_outside_execution(path: &str) -> Result<SignedOutsideExecution> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read outside execution spec {}", path))?;
+    let mut spec: OutsideExecutionSpec =
+        serde_json::from_str(&json).context("Invalid outside execution spec")?;
+    if spec.caller.is_none() && std::env::var("FEE_PAYER_ADDRESS").is_ok() {
+        spec.caller = Some(env_felt("FEE_PAYER_ADDRESS")?);
+    }
+
+    SignedOutsideExecution::sign(
+        spec.into_execution()?,
+        env_felt("MAIN_ACCOUNT_ADDRESS")?,
+        configured_chain_id()?,
+        env_felt("MAIN_ACCOUNT_PRIVATE_KEY")?,
+    )
+}
+
+/// Value of a single-use `--flag <value>` argument
+fn arg_value(flag: &str) -> Result<Option<String>> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args
+                .next()
+                .map(Some)
+                .with_context(|| format!("{} requires a value", flag));
+        }
+    }
+    Ok(None)
+}
+
+/// Parse `--inject-event <json>` arguments (repeatable).
+///
+/// The value is a serialized `SwapEvent`, or `@path` to read it from a file:
+///
+/// ```text
+/// --inject-event '{"type":"SecretRevealed","contract_address":"0x1","revealer":"0x2",
+///                  "secret_hash":0,"claimable_after":1700000000,"block_number":0,
+///                  "transaction_hash":"0x0"}'
+/// ```
+fn injected_events() -> Result<Vec<SwapEvent>> {
+    let mut events = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--inject-event" {
+            continue;
+        }
+        let value = args
+            .next()
+            .context("--inject-event requires a JSON event or @file")?;
+        let json = match value.strip_prefix('@') {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read injected event file {}", path))?,
+            None => value,
+        };
+        let event: SwapEvent =
+            serde_json::from_str(&json).context("Invalid injected SwapEvent JSON")?;
+        events.push(event);
+    }
+    Ok(events)
+}
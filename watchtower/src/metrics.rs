@@ -0,0 +1,212 @@
+// Prometheus metrics
+//
+// Served as `GET /metrics` on WATCHTOWER_API_ADDR (no token, so scrapers
+// and Grafana agents need no credentials). Process-wide: the listener,
+// the Monero monitors and the notifier update `METRICS` directly.
+//
+//   watchtower_blocks_processed_total          Starknet blocks fully processed
+//   watchtower_last_processed_block            newest block all contracts passed
+//   watchtower_rpc_errors_total{service}       failed starknet / monero requests
+//   watchtower_rpc_requests_total{endpoint,outcome}  Starknet requests per endpoint
+//   watchtower_rpc_active_endpoint{endpoint}   1 for the endpoint requests go to
+//   watchtower_events_total{event}             decoded events, by name
+//   watchtower_alert_failures_total{channel}   alerts a channel did not accept
+//   watchtower_alert_queue_length              deliveries waiting for a retry
+//   watchtower_alerts_dropped_total{channel}   deliveries given up after retries
+//   watchtower_reveal_to_claim_seconds         time from reveal to claim
+
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use lazy_static::lazy_static;
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+/// Reveal-to-claim buckets: the grace period is hours, claims take minutes
+const REVEAL_TO_CLAIM_BUCKETS: &[f64] = &[
+    60.0, 300.0, 900.0, 1800.0, 3600.0, 7200.0, 14400.0, 43200.0, 86400.0,
+];
+
+lazy_static! {
+    /// Metrics of this process
+    pub static ref METRICS: Metrics = Metrics::new();
+}
+
+pub struct Metrics {
+    registry: Registry,
+    pub blocks_processed: IntCounter,
+    pub last_processed_block: IntGauge,
+    pub rpc_errors: IntCounterVec,
+    pub rpc_requests: IntCounterVec,
+    pub rpc_active_endpoint: IntGaugeVec,
+    pub events: IntCounterVec,
+    pub alert_failures: IntCounterVec,
+    pub alert_queue_length: IntGauge,
+    pub alerts_dropped: IntCounterVec,
+    pub alerts_rate_limited: IntCounterVec,
+    pub reveal_to_claim: Histogram,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let blocks_processed = IntCounter::new(
+            "watchtower_blocks_processed_total",
+            "Starknet blocks processed for every watched contract",
+        )
+        .expect("valid metric");
+        let last_processed_block = IntGauge::new(
+            "watchtower_last_processed_block",
+            "Newest Starknet block processed for every watched contract",
+        )
+        .expect("valid metric");
+        let rpc_errors = IntCounterVec::new(
+            Opts::new("watchtower_rpc_errors_total", "Failed node requests"),
+            &["service"],
+        )
+        .expect("valid metric");
+        let rpc_requests = IntCounterVec::new(
+            Opts::new(
+                "watchtower_rpc_requests_total",
+                "Starknet RPC requests, by endpoint and outcome",
+            ),
+            &["endpoint", "outcome"],
+        )
+        .expect("valid metric");
+        let rpc_active_endpoint = IntGaugeVec::new(
+            Opts::new(
+                "watchtower_rpc_active_endpoint",
+                "1 for the Starknet RPC endpoint requests go to first",
+            ),
+            &["endpoint"],
+        )
+        .expect("valid metric");
+        let events = IntCounterVec::new(
+            Opts::new("watchtower_events_total", "Contract events received"),
+            &["event"],
+        )
+        .expect("valid metric");
+        let alert_failures = IntCounterVec::new(
+            Opts::new(
+                "watchtower_alert_failures_total",
+                "Alerts a channel failed to deliver",
+            ),
+            &["channel"],
+        )
+        .expect("valid metric");
+        let alert_queue_length = IntGauge::new(
+            "watchtower_alert_queue_length",
+            "Alert deliveries waiting for a retry",
+        )
+        .expect("valid metric");
+        let alerts_dropped = IntCounterVec::new(
+            Opts::new(
+                "watchtower_alerts_dropped_total",
+                "Alert deliveries given up after every retry failed",
+            ),
+            &["channel"],
+        )
+        .expect("valid metric");
+        let alerts_rate_limited = IntCounterVec::new(
+            Opts::new(
+                "watchtower_alerts_rate_limited_total",
+                "Alerts a channel dropped over its rate limit",
+            ),
+            &["channel"],
+        )
+        .expect("valid metric");
+        let reveal_to_claim = Histogram::with_opts(
+            HistogramOpts::new(
+                "watchtower_reveal_to_claim_seconds",
+                "Time from the secret reveal to the claim",
+            )
+            .buckets(REVEAL_TO_CLAIM_BUCKETS.to_vec()),
+        )
+        .expect("valid metric");
+
+        let registry = Registry::new();
+        let collectors: [Box<dyn Collector>; 11] = [
+            Box::new(blocks_processed.clone()),
+            Box::new(last_processed_block.clone()),
+            Box::new(rpc_errors.clone()),
+            Box::new(rpc_requests.clone()),
+            Box::new(rpc_active_endpoint.clone()),
+            Box::new(events.clone()),
+            Box::new(alert_failures.clone()),
+            Box::new(alert_queue_length.clone()),
+            Box::new(alerts_dropped.clone()),
+            Box::new(alerts_rate_limited.clone()),
+            Box::new(reveal_to_claim.clone()),
+        ];
+        for collector in collectors {
+            registry.register(collector).expect("unique metric");
+        }
+        Self {
+            registry,
+            blocks_processed,
+            last_processed_block,
+            rpc_errors,
+            rpc_requests,
+            rpc_active_endpoint,
+            events,
+            alert_failures,
+            alert_queue_length,
+            alerts_dropped,
+            alerts_rate_limited,
+            reveal_to_claim,
+        }
+    }
+
+    /// Prometheus text exposition of every metric
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding cannot fail");
+        String::from_utf8(buffer).expect("text encoding is UTF-8")
+    }
+}
+
+/// `GET /metrics`
+pub fn router() -> Router {
+    Router::new().route("/metrics", get(metrics))
+}
+
+async fn metrics() -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_TYPE,
+            TextEncoder::new().format_type().to_string(),
+        )],
+        METRICS.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_are_rendered_in_text_format() {
+        let metrics = Metrics::new();
+        metrics.events.with_label_values(&["SecretRevealed"]).inc();
+        metrics
+            .alert_failures
+            .with_label_values(&["discord"])
+            .inc_by(2);
+        metrics.reveal_to_claim.observe(3_700.0);
+
+        let text = metrics.render();
+        assert!(
+            text.contains("watchtower_events_total{event=\"SecretRevealed\"} 1"),
+            "{}",
+            text
+        );
+        assert!(text.contains("watchtower_alert_failures_total{channel=\"discord\"} 2"));
+        assert!(text.contains("watchtower_reveal_to_claim_seconds_bucket{le=\"7200\"} 1"));
+        assert!(text.contains("watchtower_reveal_to_claim_seconds_bucket{le=\"3600\"} 0"));
+    }
+}
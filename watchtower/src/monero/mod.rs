@@ -1,2 +1,3 @@
+pub mod tx_monitor;
 pub mod watcher;
 
@@ -0,0 +1,443 @@
+// Monero lock transaction monitor
+//
+// Follows the transaction that locks each swap's XMR (by txid, through
+// monerod's `get_transactions`) and counts its confirmations against the
+// daemon height. Operators get an alert at 1, 5 and 10 confirmations and
+// when the swap's required depth is reached. A transaction that monerod
+// flags with `double_spend_seen`, or that leaves the txpool without being
+// mined, is a Critical alert: the XMR the swap relies on may never arrive.
+//
+// Once the secret is revealed the contract's grace period bounds how long
+// the counterparty can wait; a lock still short of its required depth when
+// the period ends is escalated as "Monero Unconfirmed".
+//
+// Contracts whose lock reached its required depth are published in
+// `ConfirmedLocks` for the auto-claimer.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use starknet_core::types::Felt;
+use tracing::{error, info};
+
+use crate::alerts::notifier::Notifier;
+use crate::metrics::METRICS;
+use crate::types::{Alert, AlertLevel};
+
+/// Confirmation counts that raise an Info alert
+pub const CONFIRMATION_MILESTONES: [u64; 3] = [1, 5, 10];
+
+/// Default poll interval (about half a Monero block)
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// End of the grace period per contract (unix seconds), set on reveal
+pub type GraceDeadlines = Arc<RwLock<HashMap<Felt, u64>>>;
+
+/// Contracts whose lock transaction has the required confirmations
+pub type ConfirmedLocks = Arc<RwLock<HashSet<Felt>>>;
+
+/// Lock transaction of one swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedTransaction {
+    pub contract: Felt,
+    /// Hex-encoded txid
+    pub txid: String,
+    /// Confirmations the swap's value requires
+    pub required: u64,
+}
+
+/// Parse a `WATCHED_MONERO_TXS` entry: `0xcontract:<txid hex>`
+pub fn parse_watched_transaction(entry: &str, required: u64) -> Result<WatchedTransaction> {
+    let (contract, txid) = entry
+        .trim()
+        .split_once(':')
+        .with_context(|| format!("Expected 0xcontract:txid, got '{}'", entry.trim()))?;
+    let contract = Felt::from_hex(contract.trim())
+        .with_context(|| format!("Invalid contract address '{}'", contract))?;
+    let txid = txid.trim().to_lowercase();
+    if txid.len() != 64 || !txid.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Txid must be 32 bytes of hex, got '{}'", txid);
+    }
+    Ok(WatchedTransaction { contract, txid, required })
+}
+
+/// Current chain height reported by monerod
+pub async fn daemon_height(client: &Client, daemon_url: &str) -> Result<u64> {
+    #[derive(Deserialize)]
+    struct Response {
+        status: String,
+        height: u64,
+    }
+
+    let response: Response = client
+        .get(format!("{}/get_height", daemon_url))
+        .send()
+        .await
+        .context("Failed to call get_height")?
+        .json()
+        .await
+        .context("Failed to parse get_height response")?;
+    if response.status != "OK" {
+        bail!("get_height returned status '{}'", response.status);
+    }
+    Ok(response.height)
+}
+
+/// Where monerod currently has the transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Neither in the txpool nor in a block
+    Missing,
+    InPool { double_spend_seen: bool },
+    Mined { confirmations: u64 },
+}
+
+/// Something worth telling the operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxAlert {
+    Confirmations(u64),
+    RequiredDepth(u64),
+    DoubleSpend,
+    /// Seen earlier, now gone without being mined
+    Dropped,
+    /// Grace period over with fewer than `required` confirmations
+    Unconfirmed { confirmations: u64, required: u64 },
+}
+
+/// Alert state of one watched transaction
+#[derive(Debug, Clone, Default)]
+pub struct TxTracker {
+    seen: bool,
+    confirmations: u64,
+    double_spend_reported: bool,
+    dropped_reported: bool,
+    unconfirmed_reported: bool,
+}
+
+impl TxTracker {
+    /// Alerts due for `status`, each raised once
+    pub fn observe(
+        &mut self,
+        status: TxStatus,
+        required: u64,
+        deadline: Option<u64>,
+        now: u64,
+    ) -> Vec<TxAlert> {
+        let mut alerts = Vec::new();
+        match status {
+            TxStatus::Missing => {
+                if self.seen && self.confirmations == 0 && !self.dropped_reported {
+                    self.dropped_reported = true;
+                    alerts.push(TxAlert::Dropped);
+                }
+            }
+            TxStatus::InPool { double_spend_seen } => {
+                self.seen = true;
+                if double_spend_seen && !self.double_spend_reported {
+                    self.double_spend_reported = true;
+                    alerts.push(TxAlert::DoubleSpend);
+                }
+            }
+            TxStatus::Mined { confirmations } => {
+                self.seen = true;
+                let previous = self.confirmations;
+                // A Monero reorg can lower the count: milestones stay reported
+                self.confirmations = previous.max(confirmations);
+                alerts.extend(
+                    CONFIRMATION_MILESTONES
+                        .iter()
+                        .filter(|m| previous < **m && **m <= self.confirmations)
+                        .map(|m| TxAlert::Confirmations(*m)),
+                );
+                if previous < required && required <= self.confirmations {
+                    alerts.push(TxAlert::RequiredDepth(required));
+                }
+            }
+        }
+        if deadline.is_some_and(|deadline| now >= deadline)
+            && self.confirmations < required
+            && !self.unconfirmed_reported
+        {
+            self.unconfirmed_reported = true;
+            alerts.push(TxAlert::Unconfirmed {
+                confirmations: self.confirmations,
+                required,
+            });
+        }
+        alerts
+    }
+
+    /// Highest confirmation count seen
+    pub fn confirmations(&self) -> u64 {
+        self.confirmations
+    }
+
+    /// Nothing left to report
+    pub fn is_done(&self, required: u64) -> bool {
+        let last_milestone = CONFIRMATION_MILESTONES[CONFIRMATION_MILESTONES.len() - 1];
+        self.confirmations >= last_milestone.max(required)
+    }
+}
+
+/// Polls monerod for the confirmations of watched lock transactions
+pub struct MoneroTxMonitor {
+    client: Client,
+    daemon_url: String,
+    watched: Vec<WatchedTransaction>,
+    deadlines: GraceDeadlines,
+    confirmed: ConfirmedLocks,
+    notifier: Notifier,
+    poll_interval: Duration,
+}
+
+impl MoneroTxMonitor {
+    pub fn new(
+        daemon_url: &str,
+        watched: Vec<WatchedTransaction>,
+        deadlines: GraceDeadlines,
+        notifier: Notifier,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            daemon_url: daemon_url.trim_end_matches('/').to_string(),
+            watched,
+            deadlines,
+            confirmed: ConfirmedLocks::default(),
+            notifier,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Share the set of confirmed locks (e.g. with the auto-claimer)
+    pub fn with_confirmed_locks(mut self, confirmed: ConfirmedLocks) -> Self {
+        self.confirmed = confirmed;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Poll until every watched transaction is past its last alert
+    pub async fn run(mut self) {
+        info!("Watching {} Monero lock transaction(s)", self.watched.len());
+        let mut trackers: HashMap<String, TxTracker> = HashMap::new();
+
+        while !self.watched.is_empty() {
+            match self.poll().await {
+                Ok(statuses) => {
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0);
+                    for (watched, status) in self.watched.iter().zip(statuses) {
+                        let deadline = self
+                            .deadlines
+                            .read()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .get(&watched.contract)
+                            .copied();
+                        let tracker = trackers.entry(watched.txid.clone()).or_default();
+                        for alert in tracker.observe(status, watched.required, deadline, now) {
+                            self.send(watched, alert, now).await;
+                        }
+                        if tracker.confirmations() >= watched.required {
+                            self.confirmed
+                                .write()
+                                .unwrap_or_else(|e| e.into_inner())
+                                .insert(watched.contract);
+                        }
+                    }
+                    self.watched.retain(|watched| {
+                        !trackers
+                            .get(&watched.txid)
+                            .is_some_and(|tracker| tracker.is_done(watched.required))
+                    });
+                }
+                Err(e) => {
+                    METRICS.rpc_errors.with_label_values(&["monero"]).inc();
+                    error!("Monero transaction poll failed: {:#}", e)
+                }
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+        info!("All watched Monero lock transactions confirmed");
+    }
+
+    async fn poll(&self) -> Result<Vec<TxStatus>> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            txs_hashes: Vec<&'a str>,
+        }
+
+        #[derive(Deserialize)]
+        struct Entry {
+            tx_hash: String,
+            #[serde(default)]
+            in_pool: bool,
+            #[serde(default)]
+            double_spend_seen: bool,
+            #[serde(default)]
+            block_height: u64,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(default)]
+            txs: Vec<Entry>,
+        }
+
+        let height = self.height().await?;
+        let request = Request {
+            txs_hashes: self.watched.iter().map(|w| w.txid.as_str()).collect(),
+        };
+        let response: Response = self
+            .client
+            .post(format!("{}/get_transactions", self.daemon_url))
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to call get_transactions")?
+            .json()
+            .await
+            .context("Failed to parse get_transactions response")?;
+        if response.status != "OK" {
+            bail!("get_transactions returned status '{}'", response.status);
+        }
+
+        Ok(self
+            .watched
+            .iter()
+            .map(|watched| {
+                match response.txs.iter().find(|tx| tx.tx_hash == watched.txid) {
+                    None => TxStatus::Missing,
+                    Some(tx) if tx.in_pool => TxStatus::InPool {
+                        double_spend_seen: tx.double_spend_seen,
+                    },
+                    Some(tx) => TxStatus::Mined {
+                        confirmations: height.saturating_sub(tx.block_height),
+                    },
+                }
+            })
+            .collect())
+    }
+
+    async fn height(&self) -> Result<u64> {
+        daemon_height(&self.client, &self.daemon_url).await
+    }
+
+    async fn send(&self, watched: &WatchedTransaction, alert: TxAlert, now: u64) {
+        let (level, title, message) = match alert {
+            TxAlert::Confirmations(n) => (
+                AlertLevel::Info,
+                "Monero Confirmations".to_string(),
+                format!(
+                    "Lock transaction has {} confirmation(s) ({} required).\nTxid: {}",
+                    n, watched.required, watched.txid
+                ),
+            ),
+            TxAlert::RequiredDepth(n) => (
+                AlertLevel::Info,
+                "Monero Lock Confirmed".to_string(),
+                format!(
+                    "Lock transaction reached the {} confirmations this swap requires.\nTxid: {}",
+                    n, watched.txid
+                ),
+            ),
+            TxAlert::DoubleSpend => (
+                AlertLevel::Critical,
+                "Monero Double Spend".to_string(),
+                format!(
+                    "monerod saw a double spend of the lock transaction's inputs.\n\
+                     The XMR may never arrive - do NOT reveal.\nTxid: {}",
+                    watched.txid
+                ),
+            ),
+            TxAlert::Dropped => (
+                AlertLevel::Critical,
+                "Monero Lock Dropped".to_string(),
+                format!(
+                    "Lock transaction left the txpool without being mined \
+                     (replaced or double spent).\nTxid: {}",
+                    watched.txid
+                ),
+            ),
+            TxAlert::Unconfirmed { confirmations, required } => (
+                AlertLevel::Critical,
+                "Monero Unconfirmed".to_string(),
+                format!(
+                    "Grace period is over but the lock transaction has {} of {} confirmations.\n\
+                     Txid: {}",
+                    confirmations, required, watched.txid
+                ),
+            ),
+        };
+        let alert = Alert {
+            level,
+            title,
+            message,
+            contract_address: watched.contract,
+            timestamp: now,
+        };
+        if let Err(e) = self.notifier.send_alert(&alert).await {
+            error!("Failed to send Monero transaction alert: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn milestones_are_reported_once() {
+        let mut tracker = TxTracker::default();
+        let pool = TxStatus::InPool { double_spend_seen: false };
+        assert!(tracker.observe(pool, 10, None, 0).is_empty());
+
+        let mined = |confirmations| TxStatus::Mined { confirmations };
+        assert_eq!(tracker.observe(mined(1), 10, None, 0), [TxAlert::Confirmations(1)]);
+        assert!(tracker.observe(mined(1), 10, None, 0).is_empty());
+        // Skipped milestones are still reported
+        assert_eq!(
+            tracker.observe(mined(12), 10, None, 0),
+            [TxAlert::Confirmations(5), TxAlert::Confirmations(10), TxAlert::RequiredDepth(10)]
+        );
+        assert!(tracker.observe(mined(11), 10, None, 0).is_empty());
+        assert!(tracker.is_done(10) && !tracker.is_done(20));
+    }
+
+    #[test]
+    fn double_spend_drop_and_deadline_are_critical() {
+        let mut tracker = TxTracker::default();
+        let flagged = TxStatus::InPool { double_spend_seen: true };
+        assert_eq!(tracker.observe(flagged, 10, None, 0), [TxAlert::DoubleSpend]);
+        assert!(tracker.observe(flagged, 10, None, 0).is_empty());
+        assert_eq!(tracker.observe(TxStatus::Missing, 10, None, 0), [TxAlert::Dropped]);
+
+        assert!(tracker.observe(TxStatus::Missing, 10, Some(100), 99).is_empty());
+        assert_eq!(
+            tracker.observe(TxStatus::Missing, 10, Some(100), 100),
+            [TxAlert::Unconfirmed { confirmations: 0, required: 10 }]
+        );
+
+        // Never seen: not reported as dropped
+        let mut unseen = TxTracker::default();
+        assert!(unseen.observe(TxStatus::Missing, 10, None, 0).is_empty());
+    }
+
+    #[test]
+    fn parse_transaction_entries() {
+        let txid = "cd".repeat(32);
+        let watched = parse_watched_transaction(&format!("0x1a:{}", txid.to_uppercase()), 10).unwrap();
+        assert_eq!(watched.contract, Felt::from(0x1au64));
+        assert_eq!(watched.txid, txid);
+        assert!(parse_watched_transaction("0x1a:abcd", 10).is_err());
+    }
+}
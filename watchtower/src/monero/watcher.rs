@@ -1,4 +1,280 @@
-// Monero transaction watcher
-// TODO: Monitor Monero confirmations for swaps
-// Requires monero-rs integration
+// Monero key image watcher
+//
+// Polls monerod (`is_key_image_spent`, which covers both the txpool and the
+// chain) for the key image of each swap's locked XMR output. Before `t` is
+// revealed on Starknet nobody should be able to spend that output, so a
+// spend seen while the contract has no reveal means the maker (or a leaked
+// key) is moving the funds through another path. Spends after the reveal
+// are the expected sweep and only reported for information.
+//
+// Reveals reach us only after the Starknet finality depth, so a spend with
+// no known reveal is re-checked after `reveal_lag` before it is escalated.
 
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use starknet_core::types::Felt;
+use tracing::{error, info, warn};
+
+use crate::alerts::notifier::Notifier;
+use crate::metrics::METRICS;
+use crate::types::{Alert, AlertLevel};
+
+/// Default poll interval (a txpool spend is visible within seconds)
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Default wait for a reveal still below the finality depth
+pub const DEFAULT_REVEAL_LAG: Duration = Duration::from_secs(300);
+
+/// Contracts whose secret has been revealed on Starknet
+pub type RevealedContracts = Arc<RwLock<HashSet<Felt>>>;
+
+/// Key image of one swap's locked output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedKeyImage {
+    pub contract: Felt,
+    /// Hex-encoded key image
+    pub key_image: String,
+}
+
+/// Spend state reported by monerod
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpendStatus {
+    Unspent,
+    InPool,
+    InChain,
+}
+
+impl SpendStatus {
+    fn from_code(code: u8) -> Result<Self> {
+        match code {
+            0 => Ok(SpendStatus::Unspent),
+            1 => Ok(SpendStatus::InChain),
+            2 => Ok(SpendStatus::InPool),
+            other => bail!("Unknown key image status {}", other),
+        }
+    }
+}
+
+/// Parse a `WATCHED_KEY_IMAGES` entry: `0xcontract:<key image hex>`
+pub fn parse_watched_key_image(entry: &str) -> Result<WatchedKeyImage> {
+    let (contract, key_image) = entry
+        .trim()
+        .split_once(':')
+        .with_context(|| format!("Expected 0xcontract:keyimage, got '{}'", entry.trim()))?;
+    let contract = Felt::from_hex(contract.trim())
+        .with_context(|| format!("Invalid contract address '{}'", contract))?;
+    let key_image = key_image.trim().to_lowercase();
+    if key_image.len() != 64 || !key_image.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Key image must be 32 bytes of hex, got '{}'", key_image);
+    }
+    Ok(WatchedKeyImage { contract, key_image })
+}
+
+/// Polls monerod for spends of watched key images
+pub struct KeyImageWatcher {
+    client: Client,
+    daemon_url: String,
+    watched: Vec<WatchedKeyImage>,
+    revealed: RevealedContracts,
+    notifier: Notifier,
+    poll_interval: Duration,
+    reveal_lag: Duration,
+}
+
+impl KeyImageWatcher {
+    pub fn new(
+        daemon_url: &str,
+        watched: Vec<WatchedKeyImage>,
+        revealed: RevealedContracts,
+        notifier: Notifier,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            daemon_url: daemon_url.trim_end_matches('/').to_string(),
+            watched,
+            revealed,
+            notifier,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            reveal_lag: DEFAULT_REVEAL_LAG,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// How long to wait for a reveal before escalating an early spend
+    pub fn with_reveal_lag(mut self, reveal_lag: Duration) -> Self {
+        self.reveal_lag = reveal_lag;
+        self
+    }
+
+    /// Poll until every watched key image is spent on chain
+    pub async fn run(self) {
+        info!("Watching {} Monero key image(s)", self.watched.len());
+        let mut last: HashMap<String, SpendStatus> = HashMap::new();
+
+        while last.values().filter(|s| **s == SpendStatus::InChain).count() < self.watched.len() {
+            match self.poll().await {
+                Ok(statuses) => {
+                    for (watched, status) in self.watched.iter().zip(statuses) {
+                        // Alert once per spend: pool -> chain is the same spend
+                        let previous = last.insert(watched.key_image.clone(), status);
+                        let was_unspent = previous.is_none_or(|p| p == SpendStatus::Unspent);
+                        if status != SpendStatus::Unspent && was_unspent {
+                            tokio::spawn(report(
+                                watched.clone(),
+                                status,
+                                self.revealed.clone(),
+                                self.notifier.clone(),
+                                self.reveal_lag,
+                            ));
+                        }
+                    }
+                }
+                Err(e) => {
+                    METRICS.rpc_errors.with_label_values(&["monero"]).inc();
+                    error!("Key image poll failed: {:#}", e)
+                }
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+        info!("All watched key images spent on chain");
+    }
+
+    async fn poll(&self) -> Result<Vec<SpendStatus>> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            key_images: Vec<&'a str>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            status: String,
+            #[serde(default)]
+            spent_status: Vec<u8>,
+        }
+
+        let request = Request {
+            key_images: self.watched.iter().map(|w| w.key_image.as_str()).collect(),
+        };
+        let response: Response = self
+            .client
+            .post(format!("{}/is_key_image_spent", self.daemon_url))
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to call is_key_image_spent")?
+            .json()
+            .await
+            .context("Failed to parse is_key_image_spent response")?;
+
+        if response.status != "OK" {
+            bail!("is_key_image_spent returned status '{}'", response.status);
+        }
+        if response.spent_status.len() != self.watched.len() {
+            bail!(
+                "is_key_image_spent returned {} statuses for {} key images",
+                response.spent_status.len(),
+                self.watched.len()
+            );
+        }
+        response.spent_status.into_iter().map(SpendStatus::from_code).collect()
+    }
+}
+
+/// Alert on a spend; Critical only if no reveal arrives within `reveal_lag`
+async fn report(
+    watched: WatchedKeyImage,
+    status: SpendStatus,
+    revealed: RevealedContracts,
+    notifier: Notifier,
+    reveal_lag: Duration,
+) {
+    let is_revealed = |revealed: &RevealedContracts| {
+        revealed
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(&watched.contract)
+    };
+    let location = match status {
+        SpendStatus::InPool => "in the mempool",
+        _ => "on chain",
+    };
+
+    if !is_revealed(&revealed) {
+        warn!(
+            "Key image {} spent {} with no reveal yet, re-checking in {:?}",
+            watched.key_image, location, reveal_lag
+        );
+        tokio::time::sleep(reveal_lag).await;
+    }
+
+    let (level, title, message) = if is_revealed(&revealed) {
+        (
+            AlertLevel::Info,
+            "Locked XMR Swept",
+            format!(
+                "Locked output spent {} after the secret was revealed.\nKey image: {}",
+                location, watched.key_image
+            ),
+        )
+    } else {
+        (
+            AlertLevel::Critical,
+            "Locked XMR Spent Before Reveal",
+            format!(
+                "Locked output spent {} but no secret was revealed on Starknet.\n\
+                 The funds are moving through another path - do NOT reveal.\n\
+                 Key image: {}",
+                location, watched.key_image
+            ),
+        )
+    };
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let alert = Alert {
+        level,
+        title: title.to_string(),
+        message,
+        contract_address: watched.contract,
+        timestamp,
+    };
+    if let Err(e) = notifier.send_alert(&alert).await {
+        error!("Failed to send key image alert: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_key_image_entries() {
+        let key_image = "ab".repeat(32);
+        let watched = parse_watched_key_image(&format!(" 0x1a:{} ", key_image.to_uppercase())).unwrap();
+        assert_eq!(watched.contract, Felt::from(0x1au64));
+        assert_eq!(watched.key_image, key_image);
+
+        assert!(parse_watched_key_image("0x1a").is_err());
+        assert!(parse_watched_key_image("0x1a:abcd").is_err());
+        assert!(parse_watched_key_image(&format!("0x1a:{}", "zz".repeat(32))).is_err());
+    }
+
+    #[test]
+    fn spend_status_codes() {
+        assert_eq!(SpendStatus::from_code(0).unwrap(), SpendStatus::Unspent);
+        assert_eq!(SpendStatus::from_code(1).unwrap(), SpendStatus::InChain);
+        assert_eq!(SpendStatus::from_code(2).unwrap(), SpendStatus::InPool);
+        assert!(SpendStatus::from_code(3).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+// Watched contracts, changeable while the watchtower runs
+//
+// Contracts from WATCHED_CONTRACTS and watchtower.toml are registered at
+// startup; the HTTP API (see `api`) adds and removes contracts afterwards.
+// The listener re-reads the set before every poll: a new contract is
+// fetched from its `from_block` (default: where the listener currently
+// is), a removed one is no longer fetched. Runtime registrations are kept
+// in memory only, so long-lived swaps belong in watchtower.toml.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use serde::Serialize;
+use starknet_core::types::Felt;
+
+/// One watched contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WatchedContract {
+    pub address: Felt,
+    /// Swap value in piconero (selects the confirmation tier)
+    pub xmr_amount: Option<u64>,
+    /// First block to fetch, for contracts registered after deployment
+    pub from_block: Option<u64>,
+    /// Name of the last event seen for the contract
+    pub last_event: Option<&'static str>,
+}
+
+impl WatchedContract {
+    pub fn new(address: Felt, xmr_amount: Option<u64>) -> Self {
+        Self {
+            address,
+            xmr_amount,
+            from_block: None,
+            last_event: None,
+        }
+    }
+
+    pub fn with_from_block(mut self, from_block: u64) -> Self {
+        self.from_block = Some(from_block);
+        self
+    }
+}
+
+/// Shared set of watched contracts
+#[derive(Debug, Clone, Default)]
+pub struct ContractRegistry {
+    contracts: Arc<RwLock<BTreeMap<Felt, WatchedContract>>>,
+}
+
+impl ContractRegistry {
+    /// Add or replace a contract; true if it was not watched yet
+    pub fn insert(&self, contract: WatchedContract) -> bool {
+        self.write().insert(contract.address, contract).is_none()
+    }
+
+    pub fn remove(&self, address: Felt) -> Option<WatchedContract> {
+        self.write().remove(&address)
+    }
+
+    pub fn get(&self, address: Felt) -> Option<WatchedContract> {
+        self.read().get(&address).cloned()
+    }
+
+    pub fn contains(&self, address: Felt) -> bool {
+        self.read().contains_key(&address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Every watched contract, by address
+    pub fn snapshot(&self) -> Vec<WatchedContract> {
+        self.read().values().cloned().collect()
+    }
+
+    pub fn addresses(&self) -> Vec<Felt> {
+        self.read().keys().copied().collect()
+    }
+
+    /// Remember `event` as the latest one of a watched contract
+    pub fn record_event(&self, address: Felt, event: &'static str) {
+        if let Some(contract) = self.write().get_mut(&address) {
+            contract.last_event = Some(event);
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<Felt, WatchedContract>> {
+        self.contracts.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<Felt, WatchedContract>> {
+        self.contracts.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registrations_are_shared_between_clones() {
+        let registry = ContractRegistry::default();
+        let listener_view = registry.clone();
+        assert!(registry.insert(WatchedContract::new(Felt::from(2u8), None)));
+        assert!(registry.insert(WatchedContract::new(Felt::from(1u8), Some(5))));
+        assert!(!registry.insert(WatchedContract::new(Felt::from(1u8), Some(7))));
+        assert_eq!(
+            listener_view.addresses(),
+            vec![Felt::from(1u8), Felt::from(2u8)]
+        );
+        assert_eq!(
+            listener_view.get(Felt::from(1u8)).unwrap().xmr_amount,
+            Some(7)
+        );
+
+        registry.record_event(Felt::from(2u8), "Deposited");
+        // Events of contracts no longer watched are not recorded
+        registry.record_event(Felt::from(3u8), "Deposited");
+        assert_eq!(
+            listener_view.get(Felt::from(2u8)).unwrap().last_event,
+            Some("Deposited")
+        );
+        assert!(registry.remove(Felt::from(2u8)).is_some());
+        assert_eq!(listener_view.len(), 1);
+        assert!(!listener_view.contains(Felt::from(3u8)));
+    }
+}
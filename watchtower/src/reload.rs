@@ -0,0 +1,76 @@
+// Runtime credential rotation
+//
+// On SIGHUP the `.env` file is re-read (overriding the process environment),
+// as is the `[alerts]` table of watchtower.toml, and the notifier credentials
+// and Starknet RPC URLs are swapped in place. Contract settings in the file
+// need a restart.
+// The listener keeps running: blocks already being fetched finish on the old
+// endpoints, and alerts already scheduled pick up the new credentials. New
+// RPC URLs are only used if their node reports the configured chain id.
+
+use starknet_core::types::Felt;
+use tracing::{error, info};
+
+use crate::alerts::notifier::{Notifier, NotifierCredentials};
+use crate::settings::Settings;
+use crate::starknet::client::check_chain_ids;
+use crate::starknet::failover::{rpc_urls, RpcEndpoint};
+
+/// Re-read configuration and rotate credentials
+pub async fn reload_credentials(notifier: &Notifier, rpc: &RpcEndpoint, chain_id: Felt) {
+    if let Err(e) = dotenvy::dotenv_override() {
+        info!("No .env reloaded ({}), using process environment", e);
+    }
+
+    match Settings::from_env().and_then(|settings| {
+        Ok(NotifierCredentials::from_env()?.or(settings.credentials))
+    }) {
+        Ok(credentials) => {
+            notifier.rotate_credentials(credentials);
+            info!("Notifier credentials rotated");
+        }
+        Err(e) => error!("Keeping previous credentials, config rejected: {:#}", e),
+    }
+
+    if let Ok(list) = std::env::var("STARKNET_RPC_URL") {
+        let urls = match check_chain_ids(&rpc_urls(&list), chain_id).await {
+            Ok(urls) => urls,
+            Err(e) => {
+                error!("Keeping previous RPC endpoints, new URLs rejected: {:#}", e);
+                return;
+            }
+        };
+        match rpc.rotate(&urls) {
+            Ok(()) => info!("Starknet RPC endpoints rotated ({})", urls.len()),
+            Err(e) => error!("Keeping previous RPC endpoints, new URLs rejected: {}", e),
+        }
+    }
+}
+
+/// Rotate credentials whenever the process receives SIGHUP
+#[cfg(unix)]
+pub fn spawn_sighup_reload(
+    notifier: Notifier,
+    rpc: RpcEndpoint,
+    chain_id: Felt,
+) -> anyhow::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!("SIGHUP received, reloading credentials");
+            reload_credentials(&notifier, &rpc, chain_id).await;
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn spawn_sighup_reload(
+    _notifier: Notifier,
+    _rpc: RpcEndpoint,
+    _chain_id: Felt,
+) -> anyhow::Result<()> {
+    Ok(())
+}
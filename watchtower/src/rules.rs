@@ -0,0 +1,466 @@
+// Event-to-action rules
+//
+// Operators map decoded events to actions in a YAML file (WATCHTOWER_RULES)
+// instead of patching the event loop:
+//
+//   tags:
+//     mine: ["0x1234..."]
+//   rules:
+//     - name: finalize my swaps
+//       on: SecretRevealed
+//       tags: [mine]
+//       actions:
+//         - run: ["./finalize.sh", "{contract}", "{claimable_after}"]
+//         - alert: { level: critical, title: "Finalize {contract}" }
+//         - claim
+//
+// Every matching rule runs, in file order, in addition to the built-in
+// alerts. `{name}` placeholders in alerts and commands are filled from the
+// event (see `placeholders`).
+//
+// An event dropped by a chain reorganization arrives again as `Reverted`,
+// with the original event's placeholders plus `{reverted_event}` and
+// `{fork_block}`. `*` does not match it: only rules with `on: Reverted` run.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use starknet_core::types::Felt;
+use swap_common::format::{AmountFormat, TOKEN};
+use tracing::{info, warn};
+
+use crate::alerts::notifier::Notifier;
+use crate::starknet::fee_payer::{spawn_presigned, FeePayer};
+use crate::starknet::listener::SwapEvent;
+use crate::starknet::outside_execution::SignedOutsideExecution;
+use crate::types::{Alert, AlertLevel};
+
+/// Valid values of a rule's `on`
+const EVENT_NAMES: &[&str] = &[
+    "*",
+    "Deposited",
+    "SecretRevealed",
+    "TokensClaimed",
+    "Refunded",
+    "Reverted",
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RulesConfig {
+    /// Tag name -> contracts carrying it
+    #[serde(default)]
+    pub tags: HashMap<String, Vec<Felt>>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    /// Event name (see `EVENT_NAMES`) or `*` for any but `Reverted`
+    pub on: String,
+    /// Match contracts carrying any of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Match only these contracts
+    #[serde(default)]
+    pub contracts: Vec<Felt>,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Action {
+    /// Send an alert through the configured channels
+    Alert {
+        level: AlertLevel,
+        title: String,
+        #[serde(default)]
+        message: String,
+    },
+    /// Run a command (program and arguments, no shell)
+    Run(Vec<String>),
+    /// Submit the pre-signed executions for the event's contract
+    Claim,
+}
+
+/// Loaded rules with the tag index
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+    tags: HashMap<Felt, HashSet<String>>,
+}
+
+impl RuleEngine {
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        // Actions are written `run: [...]` / `alert: {...}`, not as YAML tags
+        let config: RulesConfig = serde_yaml::with::singleton_map_recursive::deserialize(
+            serde_yaml::Deserializer::from_str(yaml),
+        )
+        .context("Invalid rules YAML")?;
+        Self::new(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rules {}", path.display()))?;
+        Self::from_yaml(&yaml).with_context(|| format!("In rules file {}", path.display()))
+    }
+
+    pub fn new(config: RulesConfig) -> Result<Self> {
+        for rule in &config.rules {
+            if !EVENT_NAMES.contains(&rule.on.as_str()) {
+                bail!("Rule '{}': unknown event '{}'", rule.name, rule.on);
+            }
+            if let Some(tag) = rule.tags.iter().find(|tag| !config.tags.contains_key(*tag)) {
+                bail!("Rule '{}': unknown tag '{}'", rule.name, tag);
+            }
+            if rule
+                .actions
+                .iter()
+                .any(|action| matches!(action, Action::Run(command) if command.is_empty()))
+            {
+                bail!("Rule '{}': empty run command", rule.name);
+            }
+            if rule.on == "Reverted" && rule.actions.contains(&Action::Claim) {
+                bail!("Rule '{}': a reverted event cannot be claimed", rule.name);
+            }
+        }
+
+        let mut tags: HashMap<Felt, HashSet<String>> = HashMap::new();
+        for (tag, contracts) in &config.tags {
+            for contract in contracts {
+                tags.entry(*contract).or_default().insert(tag.clone());
+            }
+        }
+        Ok(Self {
+            rules: config.rules,
+            tags,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether any rule submits claims (they are then not scheduled up front)
+    pub fn has_claim_actions(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.actions.contains(&Action::Claim))
+    }
+
+    /// Rules matching `event`, in file order
+    pub fn matching<'a>(&'a self, event: &'a SwapEvent) -> impl Iterator<Item = &'a Rule> + 'a {
+        let contract = event.contract_address();
+        let tags = self.tags.get(&contract);
+        self.rules.iter().filter(move |rule| {
+            ((rule.on == "*" && !matches!(event, SwapEvent::Reverted { .. }))
+                || rule.on == event.name())
+                && (rule.contracts.is_empty() || rule.contracts.contains(&contract))
+                && (rule.tags.is_empty()
+                    || tags.is_some_and(|tags| rule.tags.iter().any(|tag| tags.contains(tag))))
+        })
+    }
+}
+
+/// Values for `{name}` placeholders
+pub fn placeholders(event: &SwapEvent) -> Vec<(&'static str, String)> {
+    let mut values = vec![
+        ("event", event.name().to_string()),
+        ("contract", format!("{:#x}", event.contract_address())),
+    ];
+    match event {
+        SwapEvent::Deposited(e) => values.extend(transfer_placeholders(
+            e.depositor,
+            e.amount,
+            e.block_number,
+            e.transaction_hash,
+        )),
+        SwapEvent::Refunded(e) => values.extend(transfer_placeholders(
+            e.depositor,
+            e.amount,
+            e.block_number,
+            e.transaction_hash,
+        )),
+        SwapEvent::SecretRevealed(e) => values.extend([
+            ("revealer", format!("{:#x}", e.revealer)),
+            ("claimable_after", e.claimable_after.to_string()),
+            ("block", e.block_number.to_string()),
+            ("transaction", format!("{:#x}", e.transaction_hash)),
+        ]),
+        SwapEvent::TokensClaimed(e) => values.extend([
+            ("claimer", format!("{:#x}", e.claimer)),
+            ("amount", e.amount.to_string()),
+            ("amount_formatted", AmountFormat::from_env().amount(e.amount, TOKEN)),
+            ("claim_timestamp", e.claim_timestamp.to_string()),
+            ("block", e.block_number.to_string()),
+        ]),
+        SwapEvent::Reverted { fork_block, event } => {
+            // The original event's values, under `reverted_event` for its name
+            for (name, value) in placeholders(event) {
+                match name {
+                    "event" => values.push(("reverted_event", value)),
+                    "contract" => {}
+                    _ => values.push((name, value)),
+                }
+            }
+            values.push(("fork_block", fork_block.to_string()));
+        }
+    }
+    values
+}
+
+/// Values shared by Deposited and Refunded
+fn transfer_placeholders(
+    depositor: Felt,
+    amount: u128,
+    block: u64,
+    transaction: Felt,
+) -> [(&'static str, String); 5] {
+    [
+        ("depositor", format!("{:#x}", depositor)),
+        ("amount", amount.to_string()),
+        ("amount_formatted", AmountFormat::from_env().amount(amount, TOKEN)),
+        ("block", block.to_string()),
+        ("transaction", format!("{:#x}", transaction)),
+    ]
+}
+
+/// Fill `{name}` placeholders; unknown ones are left as they are
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    values.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Executes rule actions
+pub struct ActionRunner {
+    notifier: Notifier,
+    fee_payer: Option<Arc<FeePayer>>,
+    /// Pre-signed executions by the contract of their first call
+    claims: HashMap<Felt, Vec<SignedOutsideExecution>>,
+    /// Skip `run` and `claim` actions (test mode)
+    alerts_only: bool,
+}
+
+impl ActionRunner {
+    pub fn new(notifier: Notifier) -> Self {
+        Self {
+            notifier,
+            fee_payer: None,
+            claims: HashMap::new(),
+            alerts_only: false,
+        }
+    }
+
+    /// Only send `alert` actions; `run` and `claim` are logged and skipped
+    pub fn alerts_only(mut self) -> Self {
+        self.alerts_only = true;
+        self
+    }
+
+    /// Executions submitted by `claim` actions instead of on a schedule
+    pub fn with_claims(
+        mut self,
+        fee_payer: Arc<FeePayer>,
+        executions: Vec<SignedOutsideExecution>,
+    ) -> Self {
+        for signed in executions {
+            let contract = signed
+                .execution
+                .calls
+                .first()
+                .map(|call| call.to)
+                .unwrap_or(signed.account);
+            self.claims.entry(contract).or_default().push(signed);
+        }
+        self.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Run every action of every rule matching `event`
+    pub async fn handle(&mut self, rules: &RuleEngine, event: &SwapEvent) {
+        let values = placeholders(event);
+        for rule in rules.matching(event) {
+            info!(
+                "Rule '{}' matched {} for {:#x}",
+                rule.name,
+                event.name(),
+                event.contract_address()
+            );
+            for action in &rule.actions {
+                if let Err(e) = self.run(action, event, &values).await {
+                    warn!("Rule '{}' action failed: {:#}", rule.name, e);
+                }
+            }
+        }
+    }
+
+    async fn run(
+        &mut self,
+        action: &Action,
+        event: &SwapEvent,
+        values: &[(&str, String)],
+    ) -> Result<()> {
+        let contract = event.contract_address();
+        if self.alerts_only && !matches!(action, Action::Alert { .. }) {
+            info!("Test mode: skipping {:?} for {:#x}", action, contract);
+            return Ok(());
+        }
+        match action {
+            Action::Alert {
+                level,
+                title,
+                message,
+            } => {
+                self.notifier
+                    .send_alert(&Alert {
+                        level: *level,
+                        title: render(title, values),
+                        message: render(message, values),
+                        contract_address: contract,
+                        timestamp: std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)?
+                            .as_secs(),
+                    })
+                    .await
+            }
+            Action::Run(command) => {
+                let args: Vec<String> = command.iter().map(|arg| render(arg, values)).collect();
+                let mut child = tokio::process::Command::new(&args[0])
+                    .args(&args[1..])
+                    .env("WATCHTOWER_EVENT", serde_json::to_string(event)?)
+                    .spawn()
+                    .with_context(|| format!("Failed to run {}", args[0]))?;
+                // Don't hold up the event loop on slow scripts
+                tokio::spawn(async move {
+                    match child.wait().await {
+                        Ok(status) if status.success() => info!("{} finished", args[0]),
+                        Ok(status) => warn!("{} exited with {}", args[0], status),
+                        Err(e) => warn!("Failed to wait for {}: {}", args[0], e),
+                    }
+                });
+                Ok(())
+            }
+            Action::Claim => {
+                let fee_payer = self
+                    .fee_payer
+                    .clone()
+                    .context("claim action requires FEE_PAYER_* and PRESIGNED_EXECUTIONS")?;
+                let executions = self
+                    .claims
+                    .remove(&contract)
+                    .with_context(|| format!("No pre-signed execution for {:#x}", contract))?;
+                spawn_presigned(fee_payer, executions, self.notifier.clone());
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ContractVersion, SecretRevealedEvent};
+
+    const RULES: &str = r#"
+tags:
+  mine: ["0xa"]
+rules:
+  - name: finalize my swaps
+    on: SecretRevealed
+    tags: [mine]
+    actions:
+      - run: ["./finalize.sh", "{contract}", "{claimable_after}"]
+      - claim
+  - name: log everything
+    on: "*"
+    actions:
+      - alert: { level: info, title: "{event} on {contract}" }
+"#;
+
+    fn revealed(contract: u64) -> SwapEvent {
+        SwapEvent::SecretRevealed(SecretRevealedEvent {
+            contract_address: Felt::from(contract),
+            revealer: Felt::from(2u64),
+            secret_hash: 0,
+            claimable_after: 1_700_000_000,
+            block_number: 7,
+            transaction_hash: Felt::from(3u64),
+            contract_version: ContractVersion::Current,
+        })
+    }
+
+    #[test]
+    fn rules_match_by_event_and_tag() {
+        let engine = RuleEngine::from_yaml(RULES).unwrap();
+        assert!(engine.has_claim_actions());
+
+        let names = |event: &SwapEvent| -> Vec<String> {
+            engine.matching(event).map(|rule| rule.name.clone()).collect()
+        };
+        assert_eq!(names(&revealed(0xa)), ["finalize my swaps", "log everything"]);
+        assert_eq!(names(&revealed(0xb)), ["log everything"]);
+        let reverted = SwapEvent::Reverted { fork_block: 6, event: Box::new(revealed(0xa)) };
+        assert!(names(&reverted).is_empty());
+
+        let event = revealed(0xa);
+        let rule = engine.matching(&event).next().unwrap();
+        assert_eq!(
+            rule.actions[0],
+            Action::Run(vec![
+                "./finalize.sh".into(),
+                "{contract}".into(),
+                "{claimable_after}".into()
+            ])
+        );
+    }
+
+    #[test]
+    fn placeholders_are_rendered() {
+        let values = placeholders(&revealed(0xa));
+        assert_eq!(
+            render("{event} {contract} {claimable_after} {unknown}", &values),
+            "SecretRevealed 0xa 1700000000 {unknown}"
+        );
+
+        let reverted = SwapEvent::Reverted { fork_block: 6, event: Box::new(revealed(0xa)) };
+        assert_eq!(
+            render("{event} {reverted_event} {contract} {block} {fork_block}", &placeholders(&reverted)),
+            "Reverted SecretRevealed 0xa 7 6"
+        );
+    }
+
+    #[tokio::test]
+    async fn alerts_only_skips_commands_and_claims() {
+        let event = revealed(0xa);
+        let run = Action::Run(vec!["/nonexistent/finalize.sh".into()]);
+
+        let mut runner = ActionRunner::new(Notifier::new(None, None, None));
+        assert!(runner.run(&run, &event, &[]).await.is_err());
+        assert!(runner.run(&Action::Claim, &event, &[]).await.is_err());
+
+        let mut runner = runner.alerts_only();
+        runner.run(&run, &event, &[]).await.unwrap();
+        runner.run(&Action::Claim, &event, &[]).await.unwrap();
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let unknown_tag =
+            "rules:\n  - name: x\n    on: SecretRevealed\n    tags: [nope]\n    actions: [claim]\n";
+        assert!(RuleEngine::from_yaml(unknown_tag).is_err());
+        let unknown_event = "rules:\n  - name: x\n    on: Deployed\n    actions: [claim]\n";
+        assert!(RuleEngine::from_yaml(unknown_event).is_err());
+    }
+}
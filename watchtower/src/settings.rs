@@ -0,0 +1,392 @@
+// watchtower.toml: per-contract settings
+//
+// Environment variables stay supported, but a list of swaps is easier to
+// keep in a file (WATCHTOWER_CONFIG, default `watchtower.toml` if present):
+//
+//   [alerts]
+//   discord_webhook = "https://discord.com/api/webhooks/..."
+//   matrix_homeserver = "https://matrix.org"  # with matrix_access_token
+//   matrix_room_id = "!abc:matrix.org"
+//   pagerduty_routing_key = "..."             # pages on Critical alerts
+//   webhook_url = "https://automation.example.com/hooks/watchtower"
+//   webhook_secret = "..."                    # HMAC-signs every request
+//   webhook_headers = { "X-Api-Key" = "..." }
+//   smtp_host = "smtp.example.com"            # smtp_port, smtp_tls optional
+//   smtp_username = "..."
+//   smtp_password = "..."
+//   smtp_from = "Watchtower <watchtower@example.com>"
+//   smtp_to = ["team@example.com"]            # every alert
+//   smtp_to_critical = ["oncall@example.com"] # also smtp_to_warning
+//
+//   [routing]                                 # default: every channel
+//   info = ["discord"]
+//   warning = ["telegram"]
+//   critical = ["pagerduty", "webhook"]
+//
+//   [[contracts]]
+//   address = "0x123..."
+//   xmr_amount = "1.5"                        # picks the confirmation tier
+//   expected_amount = "1000000000000000000"   # token base units
+//   lock_until = 1700090000                   # checked on chain at startup
+//   monero_txid = "<64 hex>"                  # as WATCHED_MONERO_TXS
+//   key_image = "<64 hex>"                    # as WATCHED_KEY_IMAGES
+//   channels = ["telegram", "webhook"]        # default: every channel
+//
+// Contracts are added to the ones from the environment, and environment
+// credentials and ALERT_ROUTE_<LEVEL> win over `[alerts]` and `[routing]`.
+// The whole file is validated before the
+// watchtower starts; errors name the contract entry and the field.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use starknet_core::types::Felt;
+
+use crate::alerts::notifier::{Channel, NotifierCredentials};
+use crate::alerts::email::{EmailConfig, EmailFields};
+use crate::alerts::policy::LevelRoutes;
+use crate::alerts::webhook::WebhookConfig;
+use crate::confirmations::parse_xmr;
+use crate::types::AlertLevel;
+
+/// File read when WATCHTOWER_CONFIG is not set
+pub const DEFAULT_CONFIG_PATH: &str = "watchtower.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSettings {
+    #[serde(default)]
+    alerts: RawAlerts,
+    #[serde(default)]
+    routing: RawRouting,
+    #[serde(default)]
+    contracts: Vec<RawContract>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRouting {
+    info: Option<Vec<Channel>>,
+    warning: Option<Vec<Channel>>,
+    critical: Option<Vec<Channel>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawAlerts {
+    discord_webhook: Option<String>,
+    telegram_bot_token: Option<String>,
+    telegram_chat_id: Option<String>,
+    matrix_homeserver: Option<String>,
+    matrix_access_token: Option<String>,
+    matrix_room_id: Option<String>,
+    pagerduty_routing_key: Option<String>,
+    webhook_url: Option<String>,
+    webhook_secret: Option<String>,
+    webhook_headers: Option<BTreeMap<String, String>>,
+    smtp_host: Option<String>,
+    smtp_port: Option<u16>,
+    smtp_tls: Option<String>,
+    smtp_username: Option<String>,
+    smtp_password: Option<String>,
+    smtp_from: Option<String>,
+    #[serde(default)]
+    smtp_to: Vec<String>,
+    #[serde(default)]
+    smtp_to_warning: Vec<String>,
+    #[serde(default)]
+    smtp_to_critical: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawContract {
+    address: String,
+    xmr_amount: Option<String>,
+    expected_amount: Option<String>,
+    lock_until: Option<u64>,
+    monero_txid: Option<String>,
+    key_image: Option<String>,
+    channels: Option<Vec<Channel>>,
+}
+
+/// One watched contract, validated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractSettings {
+    pub address: Felt,
+    /// Swap value in piconero
+    pub xmr_amount: Option<u64>,
+    /// Token amount the deposit must carry (base units)
+    pub expected_amount: Option<u128>,
+    /// Expected `get_lock_until`
+    pub lock_until: Option<u64>,
+    /// Lowercase hex
+    pub monero_txid: Option<String>,
+    /// Lowercase hex
+    pub key_image: Option<String>,
+    /// Channels this contract's alerts go to (None = all)
+    pub channels: Option<Vec<Channel>>,
+}
+
+/// Contents of watchtower.toml
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    pub credentials: NotifierCredentials,
+    /// Channels per alert level (`[routing]`)
+    pub routing: LevelRoutes,
+    pub contracts: Vec<ContractSettings>,
+}
+
+impl Settings {
+    /// `WATCHTOWER_CONFIG`, or `watchtower.toml` if it exists, or nothing
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("WATCHTOWER_CONFIG") {
+            Ok(path) => Self::load(path),
+            Err(_) if Path::new(DEFAULT_CONFIG_PATH).exists() => Self::load(DEFAULT_CONFIG_PATH),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("In config file {}", path.display()))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let raw: RawSettings = toml::from_str(text)?;
+        let alerts = raw.alerts;
+        let webhook = match alerts.webhook_url {
+            Some(url) => Some(
+                WebhookConfig::new(
+                    &url,
+                    alerts.webhook_secret,
+                    alerts.webhook_headers.unwrap_or_default(),
+                )
+                .context("alerts")?,
+            ),
+            None if alerts.webhook_secret.is_some() || alerts.webhook_headers.is_some() => {
+                bail!("alerts: webhook_secret and webhook_headers need a webhook_url")
+            }
+            None => None,
+        };
+        let email = EmailConfig::from_fields(EmailFields {
+            host: alerts.smtp_host,
+            port: alerts.smtp_port,
+            tls: alerts.smtp_tls,
+            username: alerts.smtp_username,
+            password: alerts.smtp_password,
+            from: alerts.smtp_from,
+            to: alerts.smtp_to,
+            to_warning: alerts.smtp_to_warning,
+            to_critical: alerts.smtp_to_critical,
+        })
+        .context("alerts")?;
+        let mut routing = LevelRoutes::new();
+        for (level, channels) in [
+            (AlertLevel::Info, raw.routing.info),
+            (AlertLevel::Warning, raw.routing.warning),
+            (AlertLevel::Critical, raw.routing.critical),
+        ] {
+            let Some(channels) = channels else {
+                continue;
+            };
+            if channels.is_empty() {
+                bail!(
+                    "routing.{}: empty list would silence these alerts (omit it for all channels)",
+                    format!("{:?}", level).to_lowercase()
+                );
+            }
+            routing.insert(level, channels);
+        }
+        let mut seen = HashSet::new();
+        let contracts = raw
+            .contracts
+            .into_iter()
+            .enumerate()
+            .map(|(index, contract)| {
+                let name = format!("contracts[{}] ({})", index, contract.address);
+                let contract = ContractSettings::parse(contract).context(name.clone())?;
+                if !seen.insert(contract.address) {
+                    bail!("{}: duplicate contract address", name);
+                }
+                Ok(contract)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            credentials: NotifierCredentials {
+                discord_webhook: alerts.discord_webhook,
+                telegram_bot_token: alerts.telegram_bot_token,
+                telegram_chat_id: alerts.telegram_chat_id,
+                matrix_homeserver: alerts.matrix_homeserver,
+                matrix_access_token: alerts.matrix_access_token,
+                matrix_room_id: alerts.matrix_room_id,
+                pagerduty_routing_key: alerts.pagerduty_routing_key,
+                webhook,
+                email,
+            },
+            routing,
+            contracts,
+        })
+    }
+
+    /// Alert routing for contracts that restrict their channels
+    pub fn contract_channels(&self) -> HashMap<Felt, Vec<Channel>> {
+        self.contracts
+            .iter()
+            .filter_map(|c| Some((c.address, c.channels.clone()?)))
+            .collect()
+    }
+}
+
+impl ContractSettings {
+    fn parse(raw: RawContract) -> Result<Self> {
+        let address = Felt::from_hex(raw.address.trim()).context("address: not a hex felt")?;
+        let xmr_amount = raw
+            .xmr_amount
+            .as_deref()
+            .map(|amount| parse_xmr(amount.trim()))
+            .transpose()
+            .context("xmr_amount")?;
+        let expected_amount = raw
+            .expected_amount
+            .as_deref()
+            .map(parse_amount)
+            .transpose()
+            .context("expected_amount")?;
+        if raw.lock_until == Some(0) {
+            bail!("lock_until: must be a unix timestamp");
+        }
+        let monero_txid = raw
+            .monero_txid
+            .as_deref()
+            .map(|txid| hex32("monero_txid", txid));
+        let key_image = raw
+            .key_image
+            .as_deref()
+            .map(|image| hex32("key_image", image));
+        if raw.channels.as_ref().is_some_and(Vec::is_empty) {
+            bail!("channels: empty list would silence every alert (omit it for all channels)");
+        }
+        Ok(Self {
+            address,
+            xmr_amount,
+            expected_amount,
+            lock_until: raw.lock_until,
+            monero_txid: monero_txid.transpose()?,
+            key_image: key_image.transpose()?,
+            channels: raw.channels,
+        })
+    }
+}
+
+/// Token base units, decimal or `0x` hex
+fn parse_amount(amount: &str) -> Result<u128> {
+    let amount = amount.trim();
+    let parsed = match amount.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16),
+        None => amount.parse(),
+    };
+    parsed.with_context(|| format!("'{}' is not an amount in base units", amount))
+}
+
+/// 32 bytes of hex, lowercased
+fn hex32(field: &str, value: &str) -> Result<String> {
+    let value = value.trim().to_lowercase();
+    if value.len() != 64 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("{}: must be 32 bytes of hex, got '{}'", field, value);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contracts_are_parsed_and_validated() {
+        let txid = "ab".repeat(32);
+        let settings = Settings::from_toml(&format!(
+            r#"
+            [alerts]
+            telegram_chat_id = "42"
+            webhook_url = "https://hooks.example.com/in"
+            webhook_headers = {{ "X-Api-Key" = "abc" }}
+            smtp_host = "smtp.example.com"
+            smtp_from = "watchtower@example.com"
+            smtp_to_critical = ["oncall@example.com"]
+
+            [routing]
+            warning = ["telegram"]
+            critical = ["pagerduty", "webhook"]
+
+            [[contracts]]
+            address = "0x1a"
+            xmr_amount = "1.5"
+            expected_amount = "0x10"
+            lock_until = 1700090000
+            monero_txid = "{}"
+            channels = ["telegram"]
+
+            [[contracts]]
+            address = "0x1b"
+            "#,
+            txid.to_uppercase()
+        ))
+        .unwrap();
+        assert_eq!(settings.credentials.telegram_chat_id.as_deref(), Some("42"));
+        let webhook = settings.credentials.webhook.as_ref().unwrap();
+        assert_eq!(webhook.headers["X-Api-Key"], "abc");
+        assert_eq!(webhook.secret, None);
+        let email = settings.credentials.email.as_ref().unwrap();
+        assert!(email.recipients(AlertLevel::Warning).is_empty());
+        assert_eq!(email.recipients(AlertLevel::Critical).len(), 1);
+        let first = &settings.contracts[0];
+        assert_eq!(first.xmr_amount, Some(1_500_000_000_000));
+        assert_eq!(first.expected_amount, Some(16));
+        assert_eq!(first.monero_txid.as_deref(), Some(txid.as_str()));
+        assert_eq!(
+            settings.contract_channels(),
+            HashMap::from([(Felt::from(0x1au64), vec![Channel::Telegram])])
+        );
+        assert_eq!(settings.contracts[1].channels, None);
+        assert_eq!(
+            settings.routing,
+            LevelRoutes::from([
+                (AlertLevel::Warning, vec![Channel::Telegram]),
+                (AlertLevel::Critical, vec![Channel::PagerDuty, Channel::Webhook]),
+            ])
+        );
+    }
+
+    #[test]
+    fn errors_name_the_entry_and_field() {
+        let error = |toml: &str| format!("{:#}", Settings::from_toml(toml).unwrap_err());
+
+        let bad_txid = error("[[contracts]]\naddress = \"0x1\"\n\n[[contracts]]\naddress = \"0x2\"\nmonero_txid = \"abcd\"");
+        assert!(
+            bad_txid.contains("contracts[1] (0x2)") && bad_txid.contains("monero_txid"),
+            "{}",
+            bad_txid
+        );
+
+        let duplicate =
+            error("[[contracts]]\naddress = \"0x1\"\n\n[[contracts]]\naddress = \"0x01\"");
+        assert!(duplicate.contains("duplicate"), "{}", duplicate);
+
+        assert!(
+            error("[[contracts]]\naddress = \"0x1\"\nxmr_amount = \"1e3\"").contains("xmr_amount")
+        );
+        assert!(error("[[contracts]]\naddress = \"0x1\"\nchannels = []").contains("channels"));
+        assert!(
+            error("[[contracts]]\naddress = \"0x1\"\nchannels = [\"sms\"]").contains("sms")
+        );
+        assert!(error("[[contracts]]\naddress = \"0x1\"\ntimelock = 5").contains("timelock"));
+        assert!(error("[alerts]\nwebhook_secret = \"s\"").contains("webhook_url"));
+        assert!(error("[routing]\ninfo = []").contains("routing.info"));
+        assert!(error("[routing]\nwarning = [\"sms\"]").contains("sms"));
+    }
+}
@@ -0,0 +1,100 @@
+// Graceful shutdown
+//
+// SIGINT or SIGTERM asks the watchtower to stop instead of killing it
+// mid-block: the listener finishes the batch it is processing, saves its
+// checkpoint (releasing the lease, if any) and returns; the event loop
+// handles the events already received; queued alert deliveries get a last
+// attempt (up to WATCHTOWER_SHUTDOWN_TIMEOUT seconds, default 10); and the
+// swap states are saved. Deliveries that still fail stay in the alert queue
+// file for the next start. A second signal exits immediately.
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Seconds allowed for the last alert deliveries when
+/// WATCHTOWER_SHUTDOWN_TIMEOUT is not set
+pub const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+
+/// Shared stop request
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    requested: Arc<watch::Sender<bool>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            requested: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Ask every holder of this handle to stop
+    pub fn request(&self) {
+        self.requested.send_replace(true);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// Resolves once a stop was requested (immediately if it already was)
+    pub async fn requested(&self) {
+        let mut requested = self.requested.subscribe();
+        // The sender lives in `self`, so this cannot fail
+        let _ = requested.wait_for(|stop| *stop).await;
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Request `shutdown` on the first SIGINT or SIGTERM, exit on the second
+pub fn spawn_signal_handler(shutdown: Shutdown) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+    tokio::spawn(async move {
+        for signals in 1.. {
+            #[cfg(unix)]
+            let name = tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            };
+            #[cfg(not(unix))]
+            let name = match tokio::signal::ctrl_c().await {
+                Ok(()) => "Ctrl-C",
+                Err(_) => return,
+            };
+            if signals > 1 {
+                warn!("{} received again, exiting without cleanup", name);
+                std::process::exit(130);
+            }
+            info!("{} received, shutting down after the current batch", name);
+            shutdown.request();
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stop_requests_reach_every_handle() {
+        let shutdown = Shutdown::new();
+        let listener = shutdown.clone();
+        assert!(!listener.is_requested());
+
+        let waiting = tokio::spawn(async move { listener.requested().await });
+        shutdown.request();
+        waiting.await.unwrap();
+        assert!(shutdown.is_requested());
+        // Late waiters return at once
+        shutdown.requested().await;
+    }
+}
@@ -0,0 +1,221 @@
+// Active mode: claim tokens without a human
+//
+// Off by default. With the unlocker account's key (AUTO_CLAIM_ADDRESS /
+// AUTO_CLAIM_PRIVATE_KEY) the watchtower sends `claim_tokens` itself once
+// the grace period after a reveal has elapsed and the contract's Monero
+// lock transaction has its required confirmations (see `ConfirmedLocks`).
+// Only contracts with a WATCHED_MONERO_TXS entry are claimed; the others
+// keep the alert-only flow.
+//
+// Unlike the fee payer, this account holds the swap's proceeds, so run
+// active mode only on a host trusted with that key.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ::starknet::accounts::Account;
+use anyhow::{Context, Result};
+use starknet_core::types::{Call, Felt};
+use starknet_core::utils::get_selector_from_name;
+use tokio::task::AbortHandle;
+use tracing::{info, warn};
+
+use super::fee_payer::{connect_account, LocalAccount};
+use crate::alerts::notifier::Notifier;
+use crate::monero::tx_monitor::ConfirmedLocks;
+use crate::types::{Alert, AlertLevel};
+
+/// Block timestamps trail the wall clock; wait this long past the deadline
+pub const CLOCK_MARGIN_SECS: u64 = 30;
+
+/// How often to re-check the Monero lock once the grace period is over
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Attempts before handing the claim back to the operator
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// What a pending claim does next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimStep {
+    /// Sleep this many seconds, then check again
+    Wait(u64),
+    Claim,
+}
+
+/// Next step for a contract claimable after `claimable_after`
+pub fn next_step(
+    claimable_after: u64,
+    now: u64,
+    monero_confirmed: bool,
+    poll_secs: u64,
+) -> ClaimStep {
+    let ready_at = claimable_after + CLOCK_MARGIN_SECS;
+    if now < ready_at {
+        ClaimStep::Wait(ready_at - now)
+    } else if !monero_confirmed {
+        ClaimStep::Wait(poll_secs)
+    } else {
+        ClaimStep::Claim
+    }
+}
+
+/// Unlocker account submitting `claim_tokens`
+pub struct AutoClaimer {
+    account: LocalAccount,
+    confirmed: ConfirmedLocks,
+    notifier: Notifier,
+    poll_interval: Duration,
+}
+
+impl AutoClaimer {
+    /// Connect the unlocker account; the node must be on `expected_chain_id`
+    pub async fn new(
+        rpc_url: &str,
+        address: Felt,
+        private_key: Felt,
+        expected_chain_id: Felt,
+        confirmed: ConfirmedLocks,
+        notifier: Notifier,
+    ) -> Result<Self> {
+        let account = connect_account(rpc_url, address, private_key, expected_chain_id)
+            .await
+            .context("Failed to connect the auto-claim account")?;
+        Ok(Self {
+            account,
+            confirmed,
+            notifier,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        })
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn address(&self) -> Felt {
+        self.account.address()
+    }
+
+    /// Claim `contract` once it is claimable and its lock is confirmed.
+    /// Abort the returned handle if the claim is no longer wanted.
+    pub fn spawn(self: &Arc<Self>, contract: Felt, claimable_after: u64) -> AbortHandle {
+        let claimer = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let confirmed = claimer
+                    .confirmed
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .contains(&contract);
+                let poll_secs = claimer.poll_interval.as_secs().max(1);
+                match next_step(claimable_after, unix_now(), confirmed, poll_secs) {
+                    ClaimStep::Wait(secs) => tokio::time::sleep(Duration::from_secs(secs)).await,
+                    ClaimStep::Claim => break,
+                }
+            }
+            claimer.claim_with_retries(contract).await;
+        })
+        .abort_handle()
+    }
+
+    async fn claim_with_retries(&self, contract: Felt) {
+        let mut attempt = 1;
+        let (level, title, message) = loop {
+            match self.claim(contract).await {
+                Ok(tx_hash) => {
+                    info!("Auto-claimed contract {:x}: 0x{:x}", contract, tx_hash);
+                    break (
+                        AlertLevel::Info,
+                        "Tokens Claimed Automatically",
+                        format!(
+                            "Grace period over and Monero lock confirmed; \
+                             0x{:x} sent claim_tokens.\nTransaction: 0x{:x}",
+                            self.address(),
+                            tx_hash
+                        ),
+                    );
+                }
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    warn!(
+                        "Auto-claim of {:x} failed (attempt {}/{}): {:#}",
+                        contract, attempt, MAX_ATTEMPTS, e
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+                Err(e) => {
+                    warn!("Auto-claim of {:x} failed: {:#}", contract, e);
+                    break (
+                        AlertLevel::Critical,
+                        "Auto-claim Failed",
+                        format!(
+                            "claim_tokens failed {} times: {:#}\nClaim the tokens manually.",
+                            MAX_ATTEMPTS, e
+                        ),
+                    );
+                }
+            }
+        };
+        self.notifier
+            .send_alert(&Alert {
+                level,
+                title: title.to_string(),
+                message,
+                contract_address: contract,
+                timestamp: unix_now(),
+            })
+            .await
+            .ok();
+    }
+
+    async fn claim(&self, contract: Felt) -> Result<Felt> {
+        let call = Call {
+            to: contract,
+            selector: get_selector_from_name("claim_tokens").expect("valid name"),
+            calldata: vec![],
+        };
+        let result = self
+            .account
+            .execute_v3(vec![call])
+            .send()
+            .await
+            .context("claim_tokens transaction rejected")?;
+        Ok(result.transaction_hash)
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_after_grace_period_and_confirmation() {
+        let claimable_after = 1_000;
+        assert_eq!(
+            next_step(claimable_after, 900, true, 60),
+            ClaimStep::Wait(100 + CLOCK_MARGIN_SECS)
+        );
+        // The margin absorbs block timestamps behind the wall clock
+        assert_eq!(
+            next_step(claimable_after, 1_000, true, 60),
+            ClaimStep::Wait(CLOCK_MARGIN_SECS)
+        );
+        let ready = claimable_after + CLOCK_MARGIN_SECS;
+        assert_eq!(
+            next_step(claimable_after, ready, false, 60),
+            ClaimStep::Wait(60)
+        );
+        assert_eq!(
+            next_step(claimable_after, ready, true, 60),
+            ClaimStep::Claim
+        );
+    }
+}
@@ -0,0 +1,301 @@
+// Active mode: refund expired swaps without a human
+//
+// Off by default. With the maker's (depositor's) account key
+// (AUTO_REFUND_ADDRESS / AUTO_REFUND_PRIVATE_KEY) the watchtower waits for
+// each contract's timelock (`get_lock_until`), checks on-chain that the
+// secret was never revealed and the tokens are still locked, and sends
+// `refund`. Every outcome is alerted and appended as a JSON line to the
+// refund log (AUTO_REFUND_LOG), so the operator can audit what was sent.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ::starknet::accounts::{Account, ConnectedAccount};
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use starknet_core::types::{Call, Felt};
+use starknet_core::utils::get_selector_from_name;
+use tokio::task::AbortHandle;
+use tracing::{error, info, warn};
+
+use super::auto_claim::{CLOCK_MARGIN_SECS, DEFAULT_POLL_INTERVAL, MAX_ATTEMPTS};
+use super::client::call_view;
+use super::fee_payer::{connect_account, LocalAccount};
+use crate::alerts::notifier::Notifier;
+use crate::types::{Alert, AlertLevel};
+
+/// Default refund log
+pub const DEFAULT_REFUND_LOG: &str = "watchtower_refunds.jsonl";
+
+/// What a pending refund does next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefundStep {
+    /// Sleep this many seconds, then check again
+    Wait(u64),
+    Refund,
+    /// The contract no longer needs a refund
+    Skip(&'static str),
+}
+
+/// Next step for a contract locked until `lock_until`
+pub fn next_step(lock_until: u64, now: u64, revealed: bool, unlocked: bool) -> RefundStep {
+    if unlocked {
+        RefundStep::Skip("tokens already released")
+    } else if revealed {
+        RefundStep::Skip("secret revealed; the counterparty claims")
+    } else if now < lock_until + CLOCK_MARGIN_SECS {
+        RefundStep::Wait(lock_until + CLOCK_MARGIN_SECS - now)
+    } else {
+        RefundStep::Refund
+    }
+}
+
+/// One line of the refund log
+#[derive(Debug, Clone, Serialize)]
+pub struct RefundRecord {
+    pub contract: Felt,
+    pub lock_until: u64,
+    /// `submitted`, `failed` or `skipped`
+    pub outcome: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<Felt>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub timestamp: u64,
+}
+
+/// Depositor account submitting `refund`
+pub struct AutoRefunder {
+    account: LocalAccount,
+    notifier: Notifier,
+    log_path: PathBuf,
+    poll_interval: Duration,
+}
+
+impl AutoRefunder {
+    /// Connect the depositor account; the node must be on `expected_chain_id`
+    pub async fn new(
+        rpc_url: &str,
+        address: Felt,
+        private_key: Felt,
+        expected_chain_id: Felt,
+        notifier: Notifier,
+    ) -> Result<Self> {
+        let account = connect_account(rpc_url, address, private_key, expected_chain_id)
+            .await
+            .context("Failed to connect the auto-refund account")?;
+        Ok(Self {
+            account,
+            notifier,
+            log_path: PathBuf::from(DEFAULT_REFUND_LOG),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        })
+    }
+
+    pub fn with_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_path = path.into();
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn address(&self) -> Felt {
+        self.account.address()
+    }
+
+    /// Refund `contract` once its timelock has passed without a reveal.
+    /// Abort the returned handle if the refund is no longer wanted.
+    pub fn spawn(self: &Arc<Self>, contract: Felt) -> AbortHandle {
+        let refunder = self.clone();
+        tokio::spawn(async move {
+            let (lock_until, step) = loop {
+                match refunder.check(contract).await {
+                    Ok((_, RefundStep::Wait(secs))) => {
+                        tokio::time::sleep(Duration::from_secs(secs)).await
+                    }
+                    Ok(checked) => break checked,
+                    Err(e) => {
+                        warn!("Auto-refund check of {:x} failed: {:#}", contract, e);
+                        tokio::time::sleep(refunder.poll_interval).await;
+                    }
+                }
+            };
+            match step {
+                RefundStep::Skip(reason) => {
+                    info!("Not refunding {:x}: {}", contract, reason);
+                    refunder.record(RefundRecord {
+                        contract,
+                        lock_until,
+                        outcome: "skipped",
+                        transaction_hash: None,
+                        detail: Some(reason.to_string()),
+                        timestamp: unix_now(),
+                    });
+                }
+                _ => refunder.refund_with_retries(contract, lock_until).await,
+            }
+        })
+        .abort_handle()
+    }
+
+    /// Timelock and next step from the contract's current state
+    async fn check(&self, contract: Felt) -> Result<(u64, RefundStep)> {
+        let lock_until = self.view(contract, "get_lock_until").await?;
+        let lock_until = u64::try_from(lock_until)
+            .map_err(|_| anyhow!("get_lock_until returned {:#x}", lock_until))?;
+        let revealed = self.view(contract, "is_secret_revealed").await? != Felt::ZERO;
+        let unlocked = self.view(contract, "is_unlocked").await? != Felt::ZERO;
+        Ok((
+            lock_until,
+            next_step(lock_until, unix_now(), revealed, unlocked),
+        ))
+    }
+
+    async fn view(&self, contract: Felt, name: &str) -> Result<Felt> {
+        call_view(self.account.provider(), contract, name).await
+    }
+
+    async fn refund_with_retries(&self, contract: Felt, lock_until: u64) {
+        let mut attempt = 1;
+        let (record, level, title, message) = loop {
+            match self.refund(contract).await {
+                Ok(tx_hash) => {
+                    info!("Auto-refunded contract {:x}: 0x{:x}", contract, tx_hash);
+                    break (
+                        RefundRecord {
+                            contract,
+                            lock_until,
+                            outcome: "submitted",
+                            transaction_hash: Some(tx_hash),
+                            detail: None,
+                            timestamp: unix_now(),
+                        },
+                        AlertLevel::Warning,
+                        "Refund Submitted Automatically",
+                        format!(
+                            "Timelock expired without a reveal; 0x{:x} sent refund.\n\
+                             Transaction: 0x{:x}",
+                            self.address(),
+                            tx_hash
+                        ),
+                    );
+                }
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    warn!(
+                        "Auto-refund of {:x} failed (attempt {}/{}): {:#}",
+                        contract, attempt, MAX_ATTEMPTS, e
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+                Err(e) => {
+                    warn!("Auto-refund of {:x} failed: {:#}", contract, e);
+                    break (
+                        RefundRecord {
+                            contract,
+                            lock_until,
+                            outcome: "failed",
+                            transaction_hash: None,
+                            detail: Some(format!("{:#}", e)),
+                            timestamp: unix_now(),
+                        },
+                        AlertLevel::Critical,
+                        "Auto-refund Failed",
+                        format!(
+                            "refund failed {} times: {:#}\nRefund the tokens manually.",
+                            MAX_ATTEMPTS, e
+                        ),
+                    );
+                }
+            }
+        };
+        let timestamp = record.timestamp;
+        self.record(record);
+        self.notifier
+            .send_alert(&Alert {
+                level,
+                title: title.to_string(),
+                message,
+                contract_address: contract,
+                timestamp,
+            })
+            .await
+            .ok();
+    }
+
+    async fn refund(&self, contract: Felt) -> Result<Felt> {
+        let call = Call {
+            to: contract,
+            selector: get_selector_from_name("refund").expect("valid name"),
+            calldata: vec![],
+        };
+        let result = self
+            .account
+            .execute_v3(vec![call])
+            .send()
+            .await
+            .context("refund transaction rejected")?;
+        Ok(result.transaction_hash)
+    }
+
+    /// Append `record` to the refund log
+    fn record(&self, record: RefundRecord) {
+        let written = serde_json::to_string(&record)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.log_path)?;
+                writeln!(file, "{}", line)?;
+                Ok(())
+            });
+        if let Err(e) = written {
+            error!(
+                "Failed to write refund log {}: {:#}",
+                self.log_path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refunds_only_expired_unrevealed_locks() {
+        let lock_until = 5_000;
+        assert_eq!(
+            next_step(lock_until, 4_000, false, false),
+            RefundStep::Wait(1_000 + CLOCK_MARGIN_SECS)
+        );
+        let expired = lock_until + CLOCK_MARGIN_SECS;
+        assert_eq!(
+            next_step(lock_until, expired, false, false),
+            RefundStep::Refund
+        );
+        // The contract rejects a refund after a reveal, even before any claim
+        assert!(matches!(
+            next_step(lock_until, expired, true, false),
+            RefundStep::Skip(_)
+        ));
+        assert!(matches!(
+            next_step(lock_until, 4_000, false, true),
+            RefundStep::Skip(_)
+        ));
+    }
+}
@@ -1,3 +1,114 @@
 // Starknet RPC client utilities
-// TODO: Implement helper functions for contract interaction
+//
+// Every endpoint the watchtower talks to must serve the chain configured by
+// STARKNET_CHAIN_ID: a mainnet URL on a Sepolia watchtower (or the reverse)
+// would watch contracts that do not exist there and report nothing.
 
+use anyhow::{anyhow, bail, Context, Result};
+use starknet_core::types::{BlockId, BlockTag, Felt, FunctionCall};
+use starknet_core::utils::{
+    cairo_short_string_to_felt, get_selector_from_name, parse_cairo_short_string,
+};
+use starknet_providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet_providers::{Provider, Url};
+use tracing::warn;
+
+/// Chain the watchtower is configured for (STARKNET_CHAIN_ID, default SN_SEPOLIA)
+pub fn configured_chain_id() -> Result<Felt> {
+    let chain_id = std::env::var("STARKNET_CHAIN_ID").unwrap_or_else(|_| "SN_SEPOLIA".to_string());
+    cairo_short_string_to_felt(&chain_id)
+        .with_context(|| format!("Invalid STARKNET_CHAIN_ID '{}'", chain_id))
+}
+
+/// JSON-RPC provider for `rpc_url`
+pub fn connect(rpc_url: &str) -> Result<JsonRpcClient<HttpTransport>> {
+    Ok(JsonRpcClient::new(HttpTransport::new(
+        Url::parse(rpc_url).context("Invalid Starknet RPC URL")?,
+    )))
+}
+
+/// Fail unless the node at `rpc_url` reports `expected` from `starknet_chainId`
+pub async fn check_chain_id(rpc_url: &str, expected: Felt) -> Result<()> {
+    let provider = connect(rpc_url)?;
+    let actual = provider
+        .chain_id()
+        .await
+        .with_context(|| format!("Failed to fetch chain id from {}", rpc_url))?;
+    ensure_chain_id(expected, actual)
+}
+
+/// Endpoints of `rpc_urls` that report `expected`, in order
+///
+/// An endpoint that is down or on another chain is left out with a warning;
+/// fails with the last error when none is left.
+pub async fn check_chain_ids(rpc_urls: &[String], expected: Felt) -> Result<Vec<String>> {
+    let mut verified = Vec::new();
+    let mut last_error = None;
+    for rpc_url in rpc_urls {
+        match check_chain_id(rpc_url, expected).await {
+            Ok(()) => verified.push(rpc_url.clone()),
+            Err(e) => {
+                warn!("Leaving out Starknet RPC endpoint: {:#}", e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if verified.is_empty() {
+        return Err(last_error.unwrap_or_else(|| anyhow!("No Starknet RPC URL configured")));
+    }
+    Ok(verified)
+}
+
+/// Compare chain ids, naming both networks on mismatch
+pub fn ensure_chain_id(expected: Felt, actual: Felt) -> Result<()> {
+    if actual != expected {
+        bail!(
+            "Starknet RPC node is on chain {}, but STARKNET_CHAIN_ID is {}",
+            chain_name(actual),
+            chain_name(expected)
+        );
+    }
+    Ok(())
+}
+
+/// First felt returned by the argument-less view function `name`
+pub async fn call_view<P: Provider + Sync>(
+    provider: &P,
+    contract: Felt,
+    name: &str,
+) -> Result<Felt> {
+    let result = provider
+        .call(
+            FunctionCall {
+                contract_address: contract,
+                entry_point_selector: get_selector_from_name(name).expect("valid name"),
+                calldata: vec![],
+            },
+            BlockId::Tag(BlockTag::Latest),
+        )
+        .await
+        .with_context(|| format!("Failed to call {} on 0x{:x}", name, contract))?;
+    result
+        .first()
+        .copied()
+        .with_context(|| format!("{} on 0x{:x} returned nothing", name, contract))
+}
+
+fn chain_name(chain_id: Felt) -> String {
+    parse_cairo_short_string(&chain_id).unwrap_or_else(|_| format!("{:#x}", chain_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_id_mismatch_names_both_networks() {
+        let sepolia = cairo_short_string_to_felt("SN_SEPOLIA").unwrap();
+        let mainnet = cairo_short_string_to_felt("SN_MAIN").unwrap();
+        assert!(ensure_chain_id(sepolia, sepolia).is_ok());
+
+        let err = ensure_chain_id(sepolia, mainnet).unwrap_err().to_string();
+        assert!(err.contains("SN_MAIN") && err.contains("SN_SEPOLIA"), "{}", err);
+    }
+}
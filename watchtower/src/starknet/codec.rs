@@ -0,0 +1,317 @@
+// AtomicLock event codec
+//
+// Decodes raw `EmittedEvent` keys and data into typed structs following the
+// contract's event declarations in cairo/src/lib.cairo. `#[key]` fields come
+// after the selector in `keys`, everything else is serialized in order into
+// `data`:
+//
+//   SecretRevealed  keys: [selector, revealer]   data: [secret_hash: u32, claimable_after: u64]
+//   TokensClaimed   keys: [selector, claimer]    data: [amount: u256 (low, high),
+//                                                       reveal_timestamp: u64, claim_timestamp: u64]
+//   Deposited       keys: [selector, depositor]  data: [amount: u256 (low, high)]
+//   Refunded        keys: [selector, depositor]  data: [amount: u256 (low, high)]
+//   Unlocked        keys: [selector, unlocker]   data: [secret_hash: u32]  (legacy contracts)
+//
+// A value that does not fit its Cairo type, or a missing or extra felt, is an
+// error rather than a zero: a layout change in the contract must not turn
+// into silently wrong timestamps.
+
+use anyhow::{bail, Result};
+use starknet_core::types::Felt;
+use starknet_core::utils::starknet_keccak;
+
+/// An AtomicLock event with a fixed layout
+pub trait AbiEvent: Sized {
+    /// Variant name in the contract's `Event` enum
+    const NAME: &'static str;
+
+    /// Decode from the keys after the selector, and the data
+    fn decode(keys: &[Felt], data: &[Felt]) -> Result<Self>;
+
+    /// `sn_keccak(NAME)`, the first key of the event
+    fn selector() -> Felt {
+        starknet_keccak(Self::NAME.as_bytes())
+    }
+
+    /// Decode a full event, checking its selector
+    fn decode_event(keys: &[Felt], data: &[Felt]) -> Result<Self> {
+        match keys.split_first() {
+            Some((selector, keys)) if *selector == Self::selector() => Self::decode(keys, data),
+            _ => bail!("not a {} event", Self::NAME),
+        }
+    }
+}
+
+/// Cairo `u256`, serialized as two felts (low, high)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct U256 {
+    pub low: u128,
+    pub high: u128,
+}
+
+impl U256 {
+    /// The value if it fits in a u128
+    pub fn to_u128(self) -> Option<u128> {
+        (self.high == 0).then_some(self.low)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRevealed {
+    pub revealer: Felt,
+    /// First hashlock word
+    pub secret_hash: u32,
+    pub claimable_after: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokensClaimed {
+    pub claimer: Felt,
+    pub amount: U256,
+    pub reveal_timestamp: u64,
+    pub claim_timestamp: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deposited {
+    pub depositor: Felt,
+    pub amount: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refunded {
+    pub depositor: Felt,
+    pub amount: U256,
+}
+
+/// Legacy one-step unlock (also emitted next to TokensClaimed by current
+/// contracts)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unlocked {
+    pub unlocker: Felt,
+    pub secret_hash: u32,
+}
+
+impl AbiEvent for SecretRevealed {
+    const NAME: &'static str = "SecretRevealed";
+
+    fn decode(keys: &[Felt], data: &[Felt]) -> Result<Self> {
+        let (mut keys, mut data) = (Reader::new(Self::NAME, keys), Reader::new(Self::NAME, data));
+        let event = Self {
+            revealer: keys.felt()?,
+            secret_hash: data.u32()?,
+            claimable_after: data.u64()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl AbiEvent for TokensClaimed {
+    const NAME: &'static str = "TokensClaimed";
+
+    fn decode(keys: &[Felt], data: &[Felt]) -> Result<Self> {
+        let (mut keys, mut data) = (Reader::new(Self::NAME, keys), Reader::new(Self::NAME, data));
+        let event = Self {
+            claimer: keys.felt()?,
+            amount: data.u256()?,
+            reveal_timestamp: data.u64()?,
+            claim_timestamp: data.u64()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl AbiEvent for Deposited {
+    const NAME: &'static str = "Deposited";
+
+    fn decode(keys: &[Felt], data: &[Felt]) -> Result<Self> {
+        let (mut keys, mut data) = (Reader::new(Self::NAME, keys), Reader::new(Self::NAME, data));
+        let event = Self {
+            depositor: keys.felt()?,
+            amount: data.u256()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl AbiEvent for Refunded {
+    const NAME: &'static str = "Refunded";
+
+    fn decode(keys: &[Felt], data: &[Felt]) -> Result<Self> {
+        let (mut keys, mut data) = (Reader::new(Self::NAME, keys), Reader::new(Self::NAME, data));
+        let event = Self {
+            depositor: keys.felt()?,
+            amount: data.u256()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+impl AbiEvent for Unlocked {
+    const NAME: &'static str = "Unlocked";
+
+    fn decode(keys: &[Felt], data: &[Felt]) -> Result<Self> {
+        let (mut keys, mut data) = (Reader::new(Self::NAME, keys), Reader::new(Self::NAME, data));
+        let event = Self {
+            unlocker: keys.felt()?,
+            secret_hash: data.u32()?,
+        };
+        keys.finish()?;
+        data.finish()?;
+        Ok(event)
+    }
+}
+
+/// Cursor over the keys or data of one event
+struct Reader<'a> {
+    event: &'static str,
+    felts: &'a [Felt],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(event: &'static str, felts: &'a [Felt]) -> Self {
+        Self { event, felts, position: 0 }
+    }
+
+    fn felt(&mut self) -> Result<Felt> {
+        let Some(felt) = self.felts.get(self.position) else {
+            bail!("{} event: expected more than {} felts", self.event, self.position);
+        };
+        self.position += 1;
+        Ok(*felt)
+    }
+
+    /// The next felt as an unsigned integer of `N` bytes
+    fn uint<const N: usize>(&mut self, ty: &str) -> Result<[u8; N]> {
+        let felt = self.felt()?;
+        let bytes = felt.to_bytes_be();
+        let (high, low) = bytes.split_at(32 - N);
+        if high.iter().any(|&b| b != 0) {
+            bail!("{} event: {:#x} does not fit in {}", self.event, felt, ty);
+        }
+        Ok(low.try_into().expect("N bytes"))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.uint("u32").map(u32::from_be_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        self.uint("u64").map(u64::from_be_bytes)
+    }
+
+    fn u256(&mut self) -> Result<U256> {
+        Ok(U256 {
+            low: self.uint("u128").map(u128::from_be_bytes)?,
+            high: self.uint("u128").map(u128::from_be_bytes)?,
+        })
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.position != self.felts.len() {
+            bail!(
+                "{} event: {} felts, expected {}",
+                self.event,
+                self.felts.len(),
+                self.position
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys<E: AbiEvent>(key: u64) -> Vec<Felt> {
+        vec![E::selector(), Felt::from(key)]
+    }
+
+    fn felts(values: &[u128]) -> Vec<Felt> {
+        values.iter().map(|&v| Felt::from(v)).collect()
+    }
+
+    #[test]
+    fn secret_revealed_layout() {
+        let event = SecretRevealed::decode_event(
+            &keys::<SecretRevealed>(0xb0b),
+            &felts(&[0xdeadbeef, 1_700_000_000]),
+        )
+        .unwrap();
+        assert_eq!(
+            event,
+            SecretRevealed {
+                revealer: Felt::from(0xb0bu64),
+                secret_hash: 0xdeadbeef,
+                claimable_after: 1_700_000_000,
+            }
+        );
+
+        // secret_hash is a u32
+        let too_wide = felts(&[0x1_0000_0000, 1]);
+        assert!(SecretRevealed::decode_event(&keys::<SecretRevealed>(1), &too_wide).is_err());
+        // claimable_after is missing
+        assert!(SecretRevealed::decode_event(&keys::<SecretRevealed>(1), &felts(&[1])).is_err());
+    }
+
+    #[test]
+    fn tokens_claimed_layout() {
+        let event = TokensClaimed::decode_event(
+            &keys::<TokensClaimed>(0xb0b),
+            &felts(&[u128::MAX, 2, 1_700_000_000, 1_700_000_300]),
+        )
+        .unwrap();
+        assert_eq!(event.claimer, Felt::from(0xb0bu64));
+        assert_eq!(event.amount, U256 { low: u128::MAX, high: 2 });
+        assert_eq!(event.amount.to_u128(), None);
+        assert_eq!(event.reveal_timestamp, 1_700_000_000);
+        assert_eq!(event.claim_timestamp, 1_700_000_300);
+
+        // The u256 takes two felts: three data felts are one short
+        assert!(TokensClaimed::decode_event(&keys::<TokensClaimed>(1), &felts(&[5, 1, 2])).is_err());
+    }
+
+    #[test]
+    fn deposited_layout() {
+        let event = Deposited::decode_event(&keys::<Deposited>(0xa11ce), &felts(&[1000, 0])).unwrap();
+        assert_eq!(event.depositor, Felt::from(0xa11ceu64));
+        assert_eq!(event.amount.to_u128(), Some(1000));
+
+        // Trailing data means the layout changed
+        assert!(Deposited::decode_event(&keys::<Deposited>(1), &felts(&[1000, 0, 7])).is_err());
+        // So does a missing key
+        assert!(Deposited::decode_event(&[Deposited::selector()], &felts(&[1000, 0])).is_err());
+    }
+
+    #[test]
+    fn refunded_layout() {
+        let event = Refunded::decode_event(&keys::<Refunded>(0xa11ce), &felts(&[1000, 0])).unwrap();
+        assert_eq!(
+            event,
+            Refunded {
+                depositor: Felt::from(0xa11ceu64),
+                amount: U256 { low: 1000, high: 0 },
+            }
+        );
+
+        // Same layout as Deposited, but the selector tells them apart
+        assert!(Refunded::decode_event(&keys::<Deposited>(0xa11ce), &felts(&[1000, 0])).is_err());
+    }
+
+    #[test]
+    fn unlocked_layout() {
+        let event = Unlocked::decode_event(&keys::<Unlocked>(3), &felts(&[0xdeadbeef])).unwrap();
+        assert_eq!(event.unlocker, Felt::THREE);
+        assert_eq!(event.secret_hash, 0xdeadbeef);
+    }
+}
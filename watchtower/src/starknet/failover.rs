@@ -0,0 +1,317 @@
+// Starknet RPC endpoints with failover
+//
+// STARKNET_RPC_URL takes an ordered, comma-separated list of endpoints, the
+// preferred one first (e.g. a paid node, then the public ZAN and Blast
+// endpoints). Every request goes to the active endpoint; when it fails, the
+// request is retried on the following endpoints in order and the first one
+// that answers becomes active. A background health check probes every
+// endpoint (STARKNET_RPC_HEALTH_INTERVAL seconds, default 30) and moves back
+// to the first healthy one, so traffic returns to the preferred endpoint once
+// it recovers.
+//
+// Endpoints are labelled by host in logs and metrics, keeping API keys in
+// the URL path or query out of them.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use starknet_providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet_providers::Provider;
+use tracing::{info, warn};
+
+use super::client::connect;
+use crate::metrics::METRICS;
+
+/// Health check interval when STARKNET_RPC_HEALTH_INTERVAL is not set
+pub const DEFAULT_HEALTH_INTERVAL_SECS: u64 = 30;
+
+/// Time a probed endpoint has to return its block number
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Non-empty entries of a comma-separated endpoint list, in order
+pub fn rpc_urls(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Host of each URL, numbered when several endpoints share a host
+fn endpoint_labels(urls: &[String]) -> Vec<String> {
+    let hosts: Vec<String> = urls
+        .iter()
+        .map(|url| {
+            url::Url::parse(url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| "invalid".to_string())
+        })
+        .collect();
+    hosts
+        .iter()
+        .enumerate()
+        .map(|(i, host)| {
+            if hosts.iter().filter(|other| *other == host).count() > 1 {
+                format!("{}#{}", host, i + 1)
+            } else {
+                host.clone()
+            }
+        })
+        .collect()
+}
+
+struct Endpoint {
+    label: String,
+    provider: Arc<JsonRpcClient<HttpTransport>>,
+    /// Outcome of the last request or probe
+    healthy: AtomicBool,
+}
+
+/// One configured list of endpoints
+struct EndpointSet {
+    endpoints: Vec<Endpoint>,
+    /// Index of the endpoint requests go to first
+    active: AtomicUsize,
+}
+
+impl EndpointSet {
+    fn new(urls: &[String]) -> Result<Self> {
+        if urls.is_empty() {
+            bail!("No Starknet RPC endpoint configured");
+        }
+        let endpoints = urls
+            .iter()
+            .zip(endpoint_labels(urls))
+            .map(|(url, label)| {
+                Ok(Endpoint {
+                    label,
+                    provider: Arc::new(connect(url)?),
+                    healthy: AtomicBool::new(true),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let set = Self {
+            endpoints,
+            active: AtomicUsize::new(0),
+        };
+        set.publish_active(0);
+        Ok(set)
+    }
+
+    /// Make `index` active unless another request already moved away from
+    /// `from`
+    fn switch(&self, from: usize, index: usize) {
+        if from == index
+            || self
+                .active
+                .compare_exchange(from, index, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        warn!(
+            "Starknet RPC failover: {} -> {}",
+            self.endpoints[from].label, self.endpoints[index].label
+        );
+        self.publish_active(index);
+    }
+
+    fn publish_active(&self, index: usize) {
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            METRICS
+                .rpc_active_endpoint
+                .with_label_values(&[&endpoint.label])
+                .set(i64::from(i == index));
+        }
+    }
+}
+
+/// Shared, swappable list of RPC endpoints with failover
+///
+/// Rotating the endpoints (e.g. a new API key in a URL) only affects
+/// requests started afterwards; requests already in flight finish on the old
+/// endpoints.
+#[derive(Clone)]
+pub struct RpcEndpoint {
+    set: Arc<RwLock<Arc<EndpointSet>>>,
+}
+
+impl RpcEndpoint {
+    /// Endpoints in order of preference
+    pub fn new(rpc_urls: &[String]) -> Result<Self> {
+        Ok(Self {
+            set: Arc::new(RwLock::new(Arc::new(EndpointSet::new(rpc_urls)?))),
+        })
+    }
+
+    /// Switch to `rpc_urls` for all subsequent requests, starting with the
+    /// first one
+    pub fn rotate(&self, rpc_urls: &[String]) -> Result<()> {
+        let set = Arc::new(EndpointSet::new(rpc_urls)?);
+        *self.set.write().unwrap_or_else(|e| e.into_inner()) = set;
+        Ok(())
+    }
+
+    /// Provider of the active endpoint
+    pub fn current(&self) -> Arc<JsonRpcClient<HttpTransport>> {
+        let set = self.snapshot();
+        let active = set.active.load(Ordering::Relaxed);
+        Arc::clone(&set.endpoints[active].provider)
+    }
+
+    /// Label of the active endpoint
+    pub fn active_label(&self) -> String {
+        let set = self.snapshot();
+        let active = set.active.load(Ordering::Relaxed);
+        set.endpoints[active].label.clone()
+    }
+
+    /// Run `request` on the active endpoint, then on the following ones in
+    /// order until one succeeds; that one becomes active.
+    ///
+    /// Fails with the last endpoint's error when every endpoint failed.
+    pub async fn request<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: Fn(Arc<JsonRpcClient<HttpTransport>>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let set = self.snapshot();
+        let start = set.active.load(Ordering::Relaxed);
+        let count = set.endpoints.len();
+        let mut last_error = None;
+        for index in (start..count).chain(0..start) {
+            let endpoint = &set.endpoints[index];
+            match request(Arc::clone(&endpoint.provider)).await {
+                Ok(value) => {
+                    METRICS
+                        .rpc_requests
+                        .with_label_values(&[&endpoint.label, "ok"])
+                        .inc();
+                    endpoint.healthy.store(true, Ordering::Relaxed);
+                    set.switch(start, index);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    METRICS
+                        .rpc_requests
+                        .with_label_values(&[&endpoint.label, "error"])
+                        .inc();
+                    endpoint.healthy.store(false, Ordering::Relaxed);
+                    if count > 1 {
+                        warn!("Starknet RPC request to {} failed: {:#}", endpoint.label, e);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow!("No Starknet RPC endpoint configured")))
+    }
+
+    /// Probe every endpoint and make the first healthy one active
+    pub async fn check_health(&self) {
+        let set = self.snapshot();
+        for endpoint in &set.endpoints {
+            let height =
+                tokio::time::timeout(PROBE_TIMEOUT, endpoint.provider.block_number()).await;
+            let healthy = matches!(height, Ok(Ok(_)));
+            if healthy != endpoint.healthy.swap(healthy, Ordering::Relaxed) {
+                if healthy {
+                    info!("Starknet RPC endpoint {} is back", endpoint.label);
+                } else {
+                    warn!("Starknet RPC endpoint {} is not answering", endpoint.label);
+                }
+            }
+        }
+        let active = set.active.load(Ordering::Relaxed);
+        if let Some(first_healthy) = set
+            .endpoints
+            .iter()
+            .position(|endpoint| endpoint.healthy.load(Ordering::Relaxed))
+        {
+            set.switch(active, first_healthy);
+        }
+    }
+
+    /// Run `check_health` every `interval` (nothing to do for one endpoint)
+    pub fn spawn_health_checks(&self, interval: Duration) {
+        if self.snapshot().endpoints.len() < 2 {
+            return;
+        }
+        let rpc = self.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                rpc.check_health().await;
+            }
+        });
+    }
+
+    fn snapshot(&self) -> Arc<EndpointSet> {
+        Arc::clone(&self.set.read().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(list: &str) -> Vec<String> {
+        rpc_urls(list)
+    }
+
+    #[test]
+    fn endpoints_are_labelled_by_host() {
+        let urls = urls(
+            " https://api.zan.top/node/v1/starknet/sepolia/KEY ,, \
+             https://starknet-sepolia.blastapi.io/KEY/rpc/v0_7,https://api.zan.top/public/starknet-sepolia",
+        );
+        assert_eq!(urls.len(), 3);
+        assert_eq!(
+            endpoint_labels(&urls),
+            [
+                "api.zan.top#1",
+                "starknet-sepolia.blastapi.io",
+                "api.zan.top#3"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_requests_move_to_the_next_endpoint() {
+        let rpc = RpcEndpoint::new(&urls(
+            "https://api.zan.top/public/starknet-sepolia,https://starknet-sepolia.public.blastapi.io",
+        ))
+        .unwrap();
+        let calls = AtomicUsize::new(0);
+        let answer = rpc
+            .request(|_| async {
+                match calls.fetch_add(1, Ordering::Relaxed) {
+                    0 => bail!("rate limited"),
+                    _ => Ok(42),
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(answer, 42);
+        assert_eq!(rpc.active_label(), "starknet-sepolia.public.blastapi.io");
+
+        // Every endpoint failing returns the last error, the active one stays
+        let err = rpc
+            .request(|_| async { Err::<(), _>(anyhow!("down")) })
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "down");
+        assert_eq!(rpc.active_label(), "starknet-sepolia.public.blastapi.io");
+
+        rpc.rotate(&urls("https://api.zan.top/public/starknet-sepolia"))
+            .unwrap();
+        assert_eq!(rpc.active_label(), "api.zan.top");
+        assert!(RpcEndpoint::new(&[]).is_err());
+        assert!(RpcEndpoint::new(&urls("api.zan.top/public/starknet-sepolia")).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+// Gas account submitting pre-signed outside executions
+//
+// The watchtower never holds the keys of the account that owns the swap
+// funds. That account signs claim/unlock calls offline as SNIP-9 outside
+// executions (`--sign-outside-execution`), and this low-value account
+// submits them once their window opens, paying the fee.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use ::starknet::accounts::{Account, ExecutionEncoding, SingleOwnerAccount};
+use ::starknet::signers::{LocalWallet, SigningKey};
+use starknet_core::types::Felt;
+use starknet_providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet_providers::{Provider, Url};
+use tracing::{info, warn};
+
+use super::client::ensure_chain_id;
+use super::outside_execution::SignedOutsideExecution;
+use crate::alerts::notifier::Notifier;
+use crate::types::{Alert, AlertLevel};
+
+/// Account signing with a key held by the watchtower
+pub type LocalAccount = SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>;
+
+/// Connect `address`; the node must be on `expected_chain_id`
+pub async fn connect_account(
+    rpc_url: &str,
+    address: Felt,
+    private_key: Felt,
+    expected_chain_id: Felt,
+) -> Result<LocalAccount> {
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(rpc_url).context("Invalid Starknet RPC URL")?,
+    ));
+    let chain_id = provider
+        .chain_id()
+        .await
+        .with_context(|| format!("Failed to fetch chain id for account 0x{:x}", address))?;
+    ensure_chain_id(expected_chain_id, chain_id)?;
+    let signer = LocalWallet::from(SigningKey::from_secret_scalar(private_key));
+    Ok(SingleOwnerAccount::new(
+        provider,
+        signer,
+        address,
+        chain_id,
+        ExecutionEncoding::New,
+    ))
+}
+
+/// Low-value account paying for outside executions
+pub struct FeePayer {
+    account: LocalAccount,
+}
+
+impl FeePayer {
+    /// Connect the gas account; the node must be on `expected_chain_id`
+    pub async fn new(
+        rpc_url: &str,
+        address: Felt,
+        private_key: Felt,
+        expected_chain_id: Felt,
+    ) -> Result<Self> {
+        let account = connect_account(rpc_url, address, private_key, expected_chain_id)
+            .await
+            .context("Failed to connect the fee payer")?;
+        Ok(Self { account })
+    }
+
+    pub fn address(&self) -> Felt {
+        self.account.address()
+    }
+
+    /// Submit `signed` through the signer's `execute_from_outside_v2`
+    pub async fn submit(&self, signed: &SignedOutsideExecution) -> Result<Felt> {
+        let caller = signed.execution.caller;
+        if caller != self.address() && caller != super::outside_execution::any_caller() {
+            bail!(
+                "Outside execution for account 0x{:x} names caller 0x{:x}, not the fee payer",
+                signed.account,
+                caller
+            );
+        }
+        let result = self
+            .account
+            .execute_v3(vec![signed.to_call()])
+            .send()
+            .await
+            .context("Fee payer transaction rejected")?;
+        Ok(result.transaction_hash)
+    }
+}
+
+/// Load pre-signed executions (JSON array) written by `--sign-outside-execution`
+pub fn load_presigned(path: impl AsRef<Path>) -> Result<Vec<SignedOutsideExecution>> {
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read pre-signed executions {}", path.display()))?;
+    serde_json::from_str(&json).context("Invalid pre-signed executions JSON")
+}
+
+/// Submit each execution once its window opens and alert on the outcome
+pub fn spawn_presigned(
+    fee_payer: Arc<FeePayer>,
+    executions: Vec<SignedOutsideExecution>,
+    notifier: Notifier,
+) {
+    for signed in executions {
+        let fee_payer = fee_payer.clone();
+        let notifier = notifier.clone();
+        tokio::spawn(async move {
+            let now = unix_now();
+            let window = &signed.execution;
+            if now >= window.execute_before {
+                warn!(
+                    "Skipping expired outside execution for 0x{:x} (expired at {})",
+                    signed.account, window.execute_before
+                );
+                return;
+            }
+            // The account checks `execute_after < block timestamp`
+            let delay = (window.execute_after + 1).saturating_sub(now);
+            if delay > 0 {
+                info!(
+                    "Outside execution for 0x{:x} scheduled in {}s",
+                    signed.account, delay
+                );
+                tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+            }
+
+            let (level, title, message) = match fee_payer.submit(&signed).await {
+                Ok(tx_hash) => {
+                    info!("Submitted outside execution: 0x{:x}", tx_hash);
+                    (
+                        AlertLevel::Info,
+                        "Pre-signed Transaction Submitted",
+                        format!(
+                            "Fee payer 0x{:x} submitted {} call(s) for 0x{:x}.\n\
+                             Transaction: 0x{:x}",
+                            fee_payer.address(),
+                            window.calls.len(),
+                            signed.account,
+                            tx_hash
+                        ),
+                    )
+                }
+                Err(e) => {
+                    warn!("Outside execution failed: {:#}", e);
+                    (
+                        AlertLevel::Critical,
+                        "Pre-signed Transaction Failed",
+                        format!(
+                            "Fee payer could not submit calls for 0x{:x}: {:#}\n\
+                             Submit them manually before {}.",
+                            signed.account, e, window.execute_before
+                        ),
+                    )
+                }
+            };
+            let contract_address = window
+                .calls
+                .first()
+                .map(|call| call.to)
+                .unwrap_or(signed.account);
+            notifier
+                .send_alert(&Alert {
+                    level,
+                    title: title.to_string(),
+                    message,
+                    contract_address,
+                    timestamp: unix_now(),
+                })
+                .await
+                .ok();
+        });
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
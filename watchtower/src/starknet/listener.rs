@@ -1,257 +1,968 @@
-use anyhow::Result;
-use starknet_core::types::{BlockId, BlockTag, EventFilter, Felt};
-use starknet_core::utils::starknet_keccak;
-use starknet_providers::{Provider, SequencerGatewayProvider};
-use tokio::sync::mpsc;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Result};
+use starknet_core::types::{
+    BlockId, EmittedEvent, EventFilter, Felt, MaybePendingBlockWithTxHashes,
+};
+use starknet_providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet_providers::Provider;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinSet;
 use tracing::{info, warn, error};
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use swap_common::format::{AmountFormat, TOKEN};
+
+use super::codec::{AbiEvent, Deposited, Refunded, SecretRevealed, TokensClaimed, Unlocked};
+use super::failover::RpcEndpoint;
+use super::reorg::{fork_point, ReorgTracker, DEFAULT_REORG_WINDOW};
+use super::sequencer::BlockSequencer;
+use crate::handoff::{LeaseStatus, StateStore};
+use crate::journal::{EventJournal, JournalRecord};
+use crate::metrics::METRICS;
+use crate::registry::ContractRegistry;
+use crate::shutdown::Shutdown;
+use crate::types::{
+    ContractVersion, DepositedEvent, RefundedEvent, SecretRevealedEvent, TokensClaimedEvent,
+};
+
+/// Default number of (contract, block) fetches in flight during catch-up
+pub const DEFAULT_MAX_CONCURRENT_FETCHES: usize = 16;
+
+/// Default number of blocks per `starknet_getEvents` range
+pub const DEFAULT_BLOCK_CHUNK: u64 = 20;
 
-use crate::types::{SecretRevealedEvent, TokensClaimedEvent};
+/// Events per `starknet_getEvents` page
+const EVENTS_PAGE_SIZE: u64 = 100;
 
-/// Starknet event listener for AtomicLock contracts
+/// Blocks per batch, so the lease is renewed regularly while catching up
+const MAX_BLOCKS_PER_BATCH: u64 = 100;
+
+/// Time between polls when nothing wakes the listener earlier
+const POLL_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(10);
+
+/// Starknet event listener for AtomicLock contracts (JSON-RPC)
 pub struct StarknetListener {
-    provider: SequencerGatewayProvider,
-    /// Contract addresses to monitor
-    watched_contracts: Vec<Felt>,
+    provider: RpcEndpoint,
+    /// Contracts to monitor (re-read before every poll)
+    watched_contracts: ContractRegistry,
     /// Channel to send events
     event_tx: mpsc::Sender<SwapEvent>,
+    /// Maximum concurrent event fetches
+    max_concurrent_fetches: usize,
+    /// Blocks per event fetch
+    block_chunk: u64,
+    /// Raw event journal (optional)
+    journal: Option<Mutex<EventJournal>>,
+    /// Blocks to wait on top of an event before forwarding it
+    finality_depth: u64,
+    /// Processed blocks re-checked for reorgs
+    reorg_window: u64,
+    /// Cursor store (optional)
+    store: Option<StateStore>,
+    /// This instance's lease id in `store` (optional)
+    instance_id: Option<String>,
+    /// Last processed block, for `/health`
+    progress: ListenerProgress,
+    /// Poll right away when notified (see `subscription`)
+    wakeup: Option<Arc<Notify>>,
+    /// Stop after the current batch when requested
+    shutdown: Shutdown,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum SwapEvent {
+    Deposited(DepositedEvent),
     SecretRevealed(SecretRevealedEvent),
     TokensClaimed(TokensClaimedEvent),
+    Refunded(RefundedEvent),
+    /// `event` was forwarded earlier but its block left the canonical chain;
+    /// blocks after `fork_block` are processed again
+    Reverted { fork_block: u64, event: Box<SwapEvent> },
 }
 
-// Event selector hashes (keccak256 of event signature)
+impl SwapEvent {
+    /// Contract event name (as in the ABI and spec/protocol.json)
+    pub fn name(&self) -> &'static str {
+        match self {
+            SwapEvent::Deposited(_) => "Deposited",
+            SwapEvent::SecretRevealed(_) => "SecretRevealed",
+            SwapEvent::TokensClaimed(_) => "TokensClaimed",
+            SwapEvent::Refunded(_) => "Refunded",
+            SwapEvent::Reverted { .. } => "Reverted",
+        }
+    }
+
+    pub fn contract_address(&self) -> Felt {
+        match self {
+            SwapEvent::Deposited(e) => e.contract_address,
+            SwapEvent::SecretRevealed(e) => e.contract_address,
+            SwapEvent::TokensClaimed(e) => e.contract_address,
+            SwapEvent::Refunded(e) => e.contract_address,
+            SwapEvent::Reverted { event, .. } => event.contract_address(),
+        }
+    }
+
+    /// Block the event was emitted in
+    pub fn block_number(&self) -> u64 {
+        match self {
+            SwapEvent::Deposited(e) => e.block_number,
+            SwapEvent::SecretRevealed(e) => e.block_number,
+            SwapEvent::TokensClaimed(e) => e.block_number,
+            SwapEvent::Refunded(e) => e.block_number,
+            SwapEvent::Reverted { event, .. } => event.block_number(),
+        }
+    }
+
+    /// Transaction that emitted the event
+    pub fn transaction_hash(&self) -> Felt {
+        match self {
+            SwapEvent::Deposited(e) => e.transaction_hash,
+            SwapEvent::SecretRevealed(e) => e.transaction_hash,
+            SwapEvent::TokensClaimed(e) => e.transaction_hash,
+            SwapEvent::Refunded(e) => e.transaction_hash,
+            SwapEvent::Reverted { event, .. } => event.transaction_hash(),
+        }
+    }
+}
+
+// Event selector hashes (sn_keccak of the event name, see `codec`)
 lazy_static! {
     /// Selector for SecretRevealed(revealer, secret_hash, claimable_after)
-    pub static ref SECRET_REVEALED_SELECTOR: Felt = 
-        starknet_keccak(b"SecretRevealed");
-    
+    pub static ref SECRET_REVEALED_SELECTOR: Felt = SecretRevealed::selector();
+
     /// Selector for TokensClaimed(claimer, amount, reveal_timestamp, claim_timestamp)
-    pub static ref TOKENS_CLAIMED_SELECTOR: Felt = 
-        starknet_keccak(b"TokensClaimed");
-    
+    pub static ref TOKENS_CLAIMED_SELECTOR: Felt = TokensClaimed::selector();
+
+    /// Selector for Deposited(depositor, amount)
+    pub static ref DEPOSITED_SELECTOR: Felt = Deposited::selector();
+
+    /// Selector for Refunded(depositor, amount)
+    pub static ref REFUNDED_SELECTOR: Felt = Refunded::selector();
+
     /// Selector for Unlocked(unlocker, secret_hash) - backward compatibility
-    pub static ref UNLOCKED_SELECTOR: Felt = 
-        starknet_keccak(b"Unlocked");
+    pub static ref UNLOCKED_SELECTOR: Felt = Unlocked::selector();
+}
+
+/// Key filter for `starknet_getEvents`: first key (the selector) must be one
+/// of the events `parse_event` decodes, so the node drops everything else
+/// (DleqVerified, ReentrancyGuard, ...) before it reaches us.
+pub fn event_key_filter() -> Vec<Vec<Felt>> {
+    vec![vec![
+        *DEPOSITED_SELECTOR,
+        *SECRET_REVEALED_SELECTOR,
+        *TOKENS_CLAIMED_SELECTOR,
+        *REFUNDED_SELECTOR,
+        *UNLOCKED_SELECTOR,
+    ]]
+}
+
+/// Last processed block and when the listener last moved (shared handle)
+///
+/// Progress is a batch of blocks processed, or a poll finding nothing new
+/// to process.
+#[derive(Debug, Clone, Default)]
+pub struct ListenerProgress {
+    block: Arc<AtomicU64>,
+    /// Unix time of the last progress (0: none yet)
+    at: Arc<AtomicU64>,
+}
+
+impl ListenerProgress {
+    fn record(&self, block: u64) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.block.store(block, Ordering::Relaxed);
+        self.at.store(now, Ordering::Relaxed);
+    }
+
+    pub fn last_block(&self) -> Option<u64> {
+        self.last_progress().map(|_| self.block.load(Ordering::Relaxed))
+    }
+
+    /// Unix time of the last progress, if any
+    pub fn last_progress(&self) -> Option<u64> {
+        Some(self.at.load(Ordering::Relaxed)).filter(|at| *at != 0)
+    }
 }
 
 impl StarknetListener {
+    /// Listen through `rpc_urls`, in order of preference (see `failover`)
     pub fn new(
-        rpc_url: &str,
-        watched_contracts: Vec<Felt>,
+        rpc_urls: &[String],
+        watched_contracts: ContractRegistry,
         event_tx: mpsc::Sender<SwapEvent>,
     ) -> Result<Self> {
-        // Use custom RPC URL if provided, otherwise default to Sepolia
-        let provider = if rpc_url.contains("zan.top") || rpc_url.contains("blastapi") || rpc_url.contains("nethermind") {
-            // Custom RPC endpoint
-            SequencerGatewayProvider::new(
-                starknet_core::chain_id::SEPOLIA,
-                url::Url::parse(rpc_url)?,
-            )
-        } else {
-            SequencerGatewayProvider::starknet_alpha_sepolia()
-        };
-        
         Ok(Self {
-            provider,
+            provider: RpcEndpoint::new(rpc_urls)?,
             watched_contracts,
             event_tx,
+            max_concurrent_fetches: DEFAULT_MAX_CONCURRENT_FETCHES,
+            block_chunk: DEFAULT_BLOCK_CHUNK,
+            journal: None,
+            finality_depth: 0,
+            reorg_window: DEFAULT_REORG_WINDOW,
+            store: None,
+            instance_id: None,
+            progress: ListenerProgress::default(),
+            wakeup: None,
+            shutdown: Shutdown::default(),
         })
     }
 
+    /// Set how many (contract, block range) fetches may run concurrently
+    pub fn with_max_concurrent_fetches(mut self, max: usize) -> Self {
+        self.max_concurrent_fetches = max.max(1);
+        self
+    }
+
+    /// Fetch each contract's events `blocks` blocks at a time
+    pub fn with_block_chunk(mut self, blocks: u64) -> Self {
+        self.block_chunk = blocks.max(1);
+        self
+    }
+
+    /// Append every received event to `journal` before parsing
+    pub fn with_journal(mut self, journal: EventJournal) -> Self {
+        self.journal = Some(Mutex::new(journal));
+        self
+    }
+
+    /// Only forward events from blocks with at least `depth` blocks on top
+    pub fn with_finality_depth(mut self, depth: u64) -> Self {
+        self.finality_depth = depth;
+        self
+    }
+
+    /// Re-check the hashes of the last `window` processed blocks before every
+    /// poll and revert the events of blocks that left the chain (0: never)
+    pub fn with_reorg_window(mut self, window: u64) -> Self {
+        self.reorg_window = window;
+        self
+    }
+
+    /// Resume from the stored cursor and process blocks only while holding
+    /// the lease `instance_id` (see `handoff`)
+    pub fn with_state_store(mut self, store: StateStore, instance_id: String) -> Self {
+        self.store = Some(store);
+        self.instance_id = Some(instance_id);
+        self
+    }
+
+    /// Resume from the cursors in `store` after a restart, without a lease
+    pub fn with_checkpoint(mut self, store: StateStore) -> Self {
+        self.store = Some(store);
+        self.instance_id = None;
+        self
+    }
+
+    /// Poll as soon as `wakeup` is notified instead of waiting for the next
+    /// interval
+    pub fn with_wakeup(mut self, wakeup: Arc<Notify>) -> Self {
+        self.wakeup = Some(wakeup);
+        self
+    }
+
+    /// Return from `run` after the current batch once `shutdown` is requested
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Handle for reading the listener's progress while it runs
+    pub fn progress(&self) -> ListenerProgress {
+        self.progress.clone()
+    }
+
+    /// Handle for rotating and health-checking the RPC endpoints while the
+    /// listener runs
+    pub fn rpc_endpoint(&self) -> RpcEndpoint {
+        self.provider.clone()
+    }
+
     /// Start listening for events
+    ///
+    /// Returns when another instance takes over the lease, or after saving
+    /// the checkpoint once a shutdown is requested.
     pub async fn run(&self) -> Result<()> {
         info!("Starting Starknet event listener");
         
-        let mut last_block = self.get_latest_block().await?;
+        let mut cursors = self.load_cursors().await?;
+        let mut last_block = self.lowest_cursor(&cursors).await?;
+        let mut reorgs = ReorgTracker::new(self.reorg_window);
         
         loop {
-            // Poll for new blocks
-            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-            
-            let current_block = self.get_latest_block().await?;
+            if self.shutdown.is_requested() {
+                return self.stop(last_block, &cursors);
+            }
+            if !self.renew_lease(last_block)? {
+                return Ok(());
+            }
+
+            if let Some(fork_block) = self.detect_reorg(&reorgs).await? {
+                self.roll_back(&mut reorgs, &mut cursors, fork_block).await?;
+                last_block = cursors.values().copied().min().unwrap_or(last_block.min(fork_block));
+                self.save_checkpoint(last_block, &cursors)?;
+            }
+
+            if self.sync_cursors(&mut cursors, last_block) {
+                last_block = cursors.values().copied().min().unwrap_or(last_block);
+            }
+
+            let current_block = self.get_final_block().await?;
             
             if current_block > last_block {
-                // Process new blocks
-                for block_num in (last_block + 1)..=current_block {
-                    if let Err(e) = self.process_block(block_num).await {
-                        error!("Failed to process block {}: {}", block_num, e);
-                    }
+                let to = current_block.min(last_block + MAX_BLOCKS_PER_BATCH);
+                self.process_blocks(&mut cursors, &mut reorgs, to).await?;
+                let processed_to = cursors.values().copied().min().unwrap_or(to);
+                METRICS.blocks_processed.inc_by(processed_to.saturating_sub(last_block));
+                METRICS.last_processed_block.set(processed_to as i64);
+                if processed_to > last_block {
+                    self.progress.record(processed_to);
                 }
-                last_block = current_block;
+                last_block = processed_to;
+                self.save_checkpoint(last_block, &cursors)?;
+                if last_block == to && to < current_block {
+                    // Still catching up (a failed block waits for the next poll)
+                    continue;
+                }
+            } else {
+                // Nothing new: caught up with the chain
+                self.progress.record(last_block);
+            }
+
+            // Poll for new blocks, sooner when the subscription reports an event
+            let poll = tokio::time::sleep(POLL_INTERVAL);
+            match &self.wakeup {
+                Some(wakeup) => tokio::select! {
+                    _ = poll => {}
+                    _ = wakeup.notified() => {}
+                    _ = self.shutdown.requested() => {}
+                },
+                None => tokio::select! {
+                    _ = poll => {}
+                    _ = self.shutdown.requested() => {}
+                },
             }
         }
     }
 
-    async fn get_latest_block(&self) -> Result<u64> {
-        let block = self.provider
-            .get_block_with_tx_hashes(BlockId::Tag(BlockTag::Latest))
-            .await?;
-        Ok(block.block_number())
+    /// Fork point, if a remembered block is no longer on the canonical chain
+    async fn detect_reorg(&self, reorgs: &ReorgTracker) -> Result<Option<u64>> {
+        let remembered = reorgs.newest_first();
+        if remembered.is_empty() {
+            return Ok(None);
+        }
+        let head = self.get_latest_block().await?;
+        let mut canonical = Vec::new();
+        for (number, hash) in &remembered {
+            // The chain got shorter: the block is gone
+            let current = if *number > head {
+                Felt::ZERO
+            } else {
+                self.block_hash(*number).await?
+            };
+            canonical.push(current);
+            if current == *hash {
+                break;
+            }
+        }
+        if canonical.last() != remembered.get(canonical.len() - 1).map(|(_, hash)| hash) {
+            error!(
+                "Reorg deeper than the {} remembered blocks: reprocessing all of them",
+                reorgs.window()
+            );
+        }
+        Ok(fork_point(&remembered, &canonical))
     }
 
-    async fn process_block(&self, block_number: u64) -> Result<()> {
-        info!("Processing block {}", block_number);
-        
-        for contract in &self.watched_contracts {
-            let filter = EventFilter {
-                from_block: Some(BlockId::Number(block_number)),
-                to_block: Some(BlockId::Number(block_number)),
-                address: Some(*contract),
-                keys: None,
-            };
-            let events = self.provider
-                .get_events(filter, None, 100)
+    /// Revert the events forwarded after `fork_block` and move the cursors
+    /// back, so the blocks after it are processed again
+    async fn roll_back(
+        &self,
+        reorgs: &mut ReorgTracker,
+        cursors: &mut HashMap<Felt, u64>,
+        fork_block: u64,
+    ) -> Result<()> {
+        let reverted = reorgs.rollback(fork_block);
+        warn!(
+            "Chain reorganization after block {}: reverting {} event(s)",
+            fork_block,
+            reverted.len()
+        );
+        for event in reverted {
+            self.event_tx
+                .send(SwapEvent::Reverted { fork_block, event: Box::new(event) })
                 .await?;
-            for event in events.events {
-                self.handle_event(event, block_number).await?;
+        }
+        for cursor in cursors.values_mut() {
+            *cursor = (*cursor).min(fork_block);
+        }
+        Ok(())
+    }
+
+    /// Last processed block of each watched contract.
+    ///
+    /// A contract without its own cursor starts at the global one; with no
+    /// checkpoint at all, everything starts at the current final block.
+    async fn load_cursors(&self) -> Result<HashMap<Felt, u64>> {
+        let state = match &self.store {
+            Some(store) => store.load()?,
+            None => Default::default(),
+        };
+        let default = match state.cursor {
+            Some(cursor) => cursor,
+            None => self.get_final_block().await?,
+        };
+        let cursors: HashMap<Felt, u64> = self
+            .watched_contracts
+            .addresses()
+            .into_iter()
+            .map(|contract| {
+                let stored = state.contracts.get(&format!("{:#x}", contract)).copied();
+                (contract, stored.unwrap_or(default))
+            })
+            .collect();
+        if let Some(from) = cursors.values().min().filter(|_| state.cursor.is_some()) {
+            info!("Resuming after block {} from the checkpoint", from);
+        }
+        Ok(cursors)
+    }
+
+    /// Follow registrations made since the last poll: a new contract starts
+    /// before its `from_block` (or at `last_block`), a removed one is
+    /// dropped. True if the set changed.
+    fn sync_cursors(&self, cursors: &mut HashMap<Felt, u64>, last_block: u64) -> bool {
+        let watched = self.watched_contracts.snapshot();
+        let before = cursors.len();
+        cursors.retain(|contract, _| watched.iter().any(|w| w.address == *contract));
+        let mut changed = cursors.len() != before;
+        for contract in watched {
+            if let Entry::Vacant(entry) = cursors.entry(contract.address) {
+                let cursor = contract
+                    .from_block
+                    .map_or(last_block, |block| block.saturating_sub(1));
+                info!("Watching contract {:x} after block {}", contract.address, cursor);
+                entry.insert(cursor);
+                changed = true;
             }
         }
-        
+        changed
+    }
+
+    /// Oldest cursor, or the current final block when nothing is watched
+    async fn lowest_cursor(&self, cursors: &HashMap<Felt, u64>) -> Result<u64> {
+        match cursors.values().min() {
+            Some(cursor) => Ok(*cursor),
+            None => self.get_final_block().await,
+        }
+    }
+
+    /// Persist the cursors after a batch, if there is a store
+    fn save_checkpoint(&self, cursor: u64, cursors: &HashMap<Felt, u64>) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let contracts = cursors
+            .iter()
+            .map(|(contract, block)| (format!("{:#x}", contract), *block));
+        store.save_checkpoint(self.instance_id.as_deref(), cursor, contracts)
+    }
+
+    /// Save the checkpoint and hand the lease back for a clean exit
+    fn stop(&self, cursor: u64, cursors: &HashMap<Felt, u64>) -> Result<()> {
+        self.save_checkpoint(cursor, cursors)?;
+        if let (Some(store), Some(id)) = (&self.store, &self.instance_id) {
+            store.release(id, Some(cursor))?;
+        }
+        info!("Listener stopped after block {}", cursor);
         Ok(())
     }
 
-    async fn handle_event(
+    /// Renew the lease and store the cursor; false when this instance must stop
+    fn renew_lease(&self, cursor: u64) -> Result<bool> {
+        let (Some(store), Some(id)) = (&self.store, &self.instance_id) else {
+            return Ok(true);
+        };
+        match store.renew(id, Some(cursor))? {
+            LeaseStatus::Held => Ok(true),
+            LeaseStatus::HandoffRequested => {
+                info!("Handing off to the new instance after block {}", cursor);
+                store.release(id, Some(cursor))?;
+                Ok(false)
+            }
+            LeaseStatus::Lost => {
+                warn!("Watchtower lease lost to another instance, stopping");
+                Ok(false)
+            }
+        }
+    }
+
+    async fn get_latest_block(&self) -> Result<u64> {
+        self.provider
+            .request(|provider| async move { Ok(provider.block_number().await?) })
+            .await
+    }
+
+    /// Hash of accepted block `number`, failing over between endpoints
+    async fn block_hash(&self, number: u64) -> Result<Felt> {
+        self.provider
+            .request(|provider| async move { block_hash(&provider, number).await })
+            .await
+    }
+
+    /// Newest block buried under `finality_depth` blocks
+    async fn get_final_block(&self) -> Result<u64> {
+        let latest = self.get_latest_block().await?;
+        Ok(latest.saturating_sub(self.finality_depth))
+    }
+
+    /// Fetch each watched contract's blocks after its cursor up to `to`
+    /// concurrently, in ranges of `block_chunk` blocks, and forward events in
+    /// block order per contract.
+    ///
+    /// Advances every cursor to the last block delivered in order: a range
+    /// whose fetch failed, and everything after it, is fetched again by the
+    /// next batch.
+    async fn process_blocks(
         &self,
-        event: starknet_core::types::EmittedEvent,
-        block_number: u64,
+        cursors: &mut HashMap<Felt, u64>,
+        reorgs: &mut ReorgTracker,
+        to: u64,
     ) -> Result<()> {
+        let Some(from) = cursors.values().min().map(|cursor| cursor + 1) else {
+            return Ok(());
+        };
+        info!("Processing blocks {}..={}", from, to);
+
+        let first_blocks: Vec<(Felt, u64)> = cursors
+            .iter()
+            .map(|(contract, cursor)| (*contract, cursor + 1))
+            .collect();
+        let mut ranges = block_ranges(&first_blocks, to, self.block_chunk).into_iter();
+        // Hashes before events: a reorg in between shows up at the next poll
+        for block in from.max((to + 1).saturating_sub(reorgs.window()))..=to {
+            reorgs.record_block(block, self.block_hash(block).await?);
+        }
+        let mut sequencer = BlockSequencer::new(first_blocks.iter().copied());
+        let mut in_flight = JoinSet::new();
+
+        loop {
+            while in_flight.len() < self.max_concurrent_fetches {
+                let Some((contract, first, last)) = ranges.next() else {
+                    break;
+                };
+                // Each fetch fails over on its own (continuation tokens are
+                // per node, so a failed range starts over on the next one)
+                let rpc = self.provider.clone();
+                in_flight.spawn(async move {
+                    let events = rpc
+                        .request(|provider| async move {
+                            fetch_events(&provider, contract, first, last).await
+                        })
+                        .await;
+                    (contract, first, last, events)
+                });
+            }
+
+            let Some(joined) = in_flight.join_next().await else {
+                break;
+            };
+            let (contract, first, last, events) = joined?;
+            let raw_events = match events {
+                Ok(events) => events,
+                Err(e) => {
+                    METRICS.rpc_errors.with_label_values(&["starknet"]).inc();
+                    error!(
+                        "Failed to process blocks {}..={} for contract {:x}, will retry: {}",
+                        first, last, contract, e
+                    );
+                    continue;
+                }
+            };
+            for (block, raw_events) in split_by_block(raw_events, first, last) {
+                self.journal_events(&raw_events, block);
+                let events = parse_events(&raw_events, block);
+
+                for event in sequencer.complete(contract, block, events) {
+                    reorgs.record_event(event.block_number(), event.clone());
+                    self.event_tx.send(event).await?;
+                }
+            }
+        }
+
+        if sequencer.buffered_blocks() > 0 {
+            warn!("{} fetched block(s) held back behind a failed block", sequencer.buffered_blocks());
+        }
+        for (contract, cursor) in cursors.iter_mut() {
+            if let Some(next) = sequencer.next_block(*contract) {
+                *cursor = next - 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn journal_events(&self, events: &[EmittedEvent], block_number: u64) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        let mut journal = journal.lock().unwrap_or_else(|e| e.into_inner());
+        for event in events {
+            if let Err(e) = journal.append(&JournalRecord::from_event(event, block_number)) {
+                error!("Failed to journal event in block {}: {}", block_number, e);
+            }
+        }
+    }
+}
+
+/// Hash of accepted block `number`
+async fn block_hash(provider: &JsonRpcClient<HttpTransport>, number: u64) -> Result<Felt> {
+    match provider.get_block_with_tx_hashes(BlockId::Number(number)).await? {
+        MaybePendingBlockWithTxHashes::Block(block) => Ok(block.block_hash),
+        MaybePendingBlockWithTxHashes::PendingBlock(_) => bail!("block {} is still pending", number),
+    }
+}
+
+/// (contract, first block, last block) fetches covering each contract's
+/// blocks up to `to`, in ranges of `chunk` blocks.
+///
+/// Ranges are aligned on the lowest first block and ordered range-major, so
+/// every contract moves forward together and the reorder buffer stays small.
+fn block_ranges(first_blocks: &[(Felt, u64)], to: u64, chunk: u64) -> Vec<(Felt, u64, u64)> {
+    let Some(from) = first_blocks.iter().map(|(_, first)| *first).min() else {
+        return Vec::new();
+    };
+    let chunk = chunk.max(1);
+    let mut ranges = Vec::new();
+    let mut start = from;
+    while start <= to {
+        let end = to.min(start.saturating_add(chunk - 1));
+        for (contract, first) in first_blocks {
+            if *first <= end {
+                ranges.push((*contract, start.max(*first), end));
+            }
+        }
+        start = end + 1;
+    }
+    ranges
+}
+
+/// Group the events of blocks `first..=last` by block, with an entry for
+/// every block (empty when nothing happened)
+fn split_by_block(
+    events: Vec<EmittedEvent>,
+    first: u64,
+    last: u64,
+) -> BTreeMap<u64, Vec<EmittedEvent>> {
+    let mut blocks: BTreeMap<u64, Vec<EmittedEvent>> =
+        (first..=last).map(|block| (block, Vec::new())).collect();
+    for event in events {
+        match event.block_number.and_then(|number| blocks.get_mut(&number)) {
+            Some(block) => block.push(event),
+            None => warn!(
+                "Dropping event of transaction {:#x} outside blocks {}..={}",
+                event.transaction_hash, first, last
+            ),
+        }
+    }
+    blocks
+}
+
+/// Fetch the raw events one contract emitted in blocks `first..=last`,
+/// following continuation tokens until the node has returned every page.
+///
+/// `starknet_getEvents` filters by address and selector on the node, so only
+/// AtomicLock events come back.
+async fn fetch_events(
+    provider: &JsonRpcClient<HttpTransport>,
+    contract: Felt,
+    first: u64,
+    last: u64,
+) -> Result<Vec<EmittedEvent>> {
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(first)),
+        to_block: Some(BlockId::Number(last)),
+        address: Some(contract),
+        keys: Some(event_key_filter()),
+    };
+    let mut events = Vec::new();
+    let mut continuation_token = None;
+    loop {
+        let page = provider
+            .get_events(filter.clone(), continuation_token.clone(), EVENTS_PAGE_SIZE)
+            .await?;
+        events.extend(page.events);
+        match page.continuation_token {
+            Some(next) if Some(&next) == continuation_token.as_ref() => {
+                bail!("node returned continuation token {} twice", next)
+            }
+            Some(next) => continuation_token = Some(next),
+            None => return Ok(events),
+        }
+    }
+}
+
+/// Decode the events of one block, dropping compatibility duplicates.
+///
+/// Current contracts emit Unlocked next to TokensClaimed/SecretRevealed in
+/// the same transaction; only an Unlocked on its own comes from a legacy
+/// contract.
+pub fn parse_events(events: &[EmittedEvent], block_number: u64) -> Vec<SwapEvent> {
+    let is_current = |event: &EmittedEvent| {
         let selector = event.keys.first().copied().unwrap_or(Felt::ZERO);
-        
-        if selector == *SECRET_REVEALED_SELECTOR {
-            // SecretRevealed event structure:
-            // Keys: [selector, revealer (indexed)]
-            // Data: [secret_hash (u32), claimable_after (u64)]
-            let revealer = event.keys.get(1).copied().unwrap_or(Felt::ZERO);
-            
-            // Parse data array
-            // secret_hash is u32, stored as Felt (low 32 bits)
-            let secret_hash = event.data.get(0)
-                .map(|f| {
-                    // Felt can be converted to u64, then truncated to u32
-                    // Use try_into or mask to get low 32 bits
-                    let val = f.to_bytes_be();
-                    if val.len() >= 4 {
-                        u32::from_be_bytes([
-                            val[val.len() - 4],
-                            val[val.len() - 3],
-                            val[val.len() - 2],
-                            val[val.len() - 1],
-                        ])
-                    } else {
-                        0
-                    }
-                })
-                .unwrap_or(0);
-            
-            // claimable_after is u64, stored as Felt
-            let claimable_after = event.data.get(1)
-                .map(|f| {
-                    let val = f.to_bytes_be();
-                    if val.len() >= 8 {
-                        u64::from_be_bytes([
-                            val[val.len() - 8], val[val.len() - 7],
-                            val[val.len() - 6], val[val.len() - 5],
-                            val[val.len() - 4], val[val.len() - 3],
-                            val[val.len() - 2], val[val.len() - 1],
-                        ])
-                    } else {
-                        0
-                    }
-                })
-                .unwrap_or(0);
-            
+        selector == *SECRET_REVEALED_SELECTOR || selector == *TOKENS_CLAIMED_SELECTOR
+    };
+    events
+        .iter()
+        .filter(|event| {
+            event.keys.first() != Some(&*UNLOCKED_SELECTOR)
+                || !events
+                    .iter()
+                    .any(|other| other.transaction_hash == event.transaction_hash && is_current(other))
+        })
+        .filter_map(|event| parse_event(event, block_number))
+        .collect()
+}
+
+/// Decode a raw AtomicLock event into a `SwapEvent` (`None` for other events)
+///
+/// A known event that does not match its layout is logged and dropped.
+pub fn parse_event(event: &EmittedEvent, block_number: u64) -> Option<SwapEvent> {
+    let selector = event.keys.first().copied().unwrap_or(Felt::ZERO);
+    let (keys, data) = (event.keys.get(1..).unwrap_or_default(), &event.data[..]);
+
+    let decoded = if selector == *SECRET_REVEALED_SELECTOR {
+        SecretRevealed::decode(keys, data).map(|revealed| {
             let evt = SecretRevealedEvent {
                 contract_address: event.from_address,
-                revealer,
-                secret_hash,
-                claimable_after,
+                revealer: revealed.revealer,
+                secret_hash: revealed.secret_hash,
+                claimable_after: revealed.claimable_after,
                 block_number,
                 transaction_hash: event.transaction_hash,
+                contract_version: ContractVersion::Current,
             };
-            
-            info!("SecretRevealed event detected: contract {:x}, claimable after {}", 
+            info!("SecretRevealed event detected: contract {:x}, claimable after {}",
                 evt.contract_address, evt.claimable_after);
-            
-            self.event_tx.send(SwapEvent::SecretRevealed(evt)).await?;
-            
-        } else if selector == *TOKENS_CLAIMED_SELECTOR {
-            // TokensClaimed event structure:
-            // Keys: [selector, claimer (indexed)]
-            // Data: [amount (u256 low, u256 high), reveal_timestamp (u64), claim_timestamp (u64)]
-            let claimer = event.keys.get(1).copied().unwrap_or(Felt::ZERO);
-            
-            // Parse amount (u256 = 2 Felts: low, high)
-            // For simplicity, we'll parse amount_low as u128
-            let amount_low = event.data.get(0)
-                .map(|f| {
-                    let val = f.to_bytes_be();
-                    if val.len() >= 16 {
-                        u128::from_be_bytes([
-                            val[val.len() - 16], val[val.len() - 15],
-                            val[val.len() - 14], val[val.len() - 13],
-                            val[val.len() - 12], val[val.len() - 11],
-                            val[val.len() - 10], val[val.len() - 9],
-                            val[val.len() - 8], val[val.len() - 7],
-                            val[val.len() - 6], val[val.len() - 5],
-                            val[val.len() - 4], val[val.len() - 3],
-                            val[val.len() - 2], val[val.len() - 1],
-                        ])
-                    } else {
-                        0
-                    }
-                })
-                .unwrap_or(0);
-            
-            let reveal_timestamp = event.data.get(2)
-                .map(|f| {
-                    let val = f.to_bytes_be();
-                    if val.len() >= 8 {
-                        u64::from_be_bytes([
-                            val[val.len() - 8], val[val.len() - 7],
-                            val[val.len() - 6], val[val.len() - 5],
-                            val[val.len() - 4], val[val.len() - 3],
-                            val[val.len() - 2], val[val.len() - 1],
-                        ])
-                    } else {
-                        0
-                    }
-                })
-                .unwrap_or(0);
-            
-            let claim_timestamp = event.data.get(3)
-                .map(|f| {
-                    let val = f.to_bytes_be();
-                    if val.len() >= 8 {
-                        u64::from_be_bytes([
-                            val[val.len() - 8], val[val.len() - 7],
-                            val[val.len() - 6], val[val.len() - 5],
-                            val[val.len() - 4], val[val.len() - 3],
-                            val[val.len() - 2], val[val.len() - 1],
-                        ])
-                    } else {
-                        0
-                    }
-                })
-                .unwrap_or(0);
-            
+            SwapEvent::SecretRevealed(evt)
+        })
+    } else if selector == *TOKENS_CLAIMED_SELECTOR {
+        TokensClaimed::decode(keys, data).map(|claimed| {
             let evt = TokensClaimedEvent {
                 contract_address: event.from_address,
-                claimer,
-                amount: amount_low,
-                reveal_timestamp,
-                claim_timestamp,
+                claimer: claimed.claimer,
+                amount: claimed.amount.to_u128().unwrap_or(u128::MAX),
+                reveal_timestamp: claimed.reveal_timestamp,
+                claim_timestamp: claimed.claim_timestamp,
+                block_number,
+                transaction_hash: event.transaction_hash,
             };
-            
-            info!("TokensClaimed event detected: contract {:x}, amount {}", 
-                evt.contract_address, evt.amount);
-            
-            self.event_tx.send(SwapEvent::TokensClaimed(evt)).await?;
+            info!("TokensClaimed event detected: contract {:x}, amount {}",
+                evt.contract_address, AmountFormat::from_env().amount(evt.amount, TOKEN));
+            SwapEvent::TokensClaimed(evt)
+        })
+    } else if selector == *DEPOSITED_SELECTOR {
+        Deposited::decode(keys, data).map(|deposited| {
+            let evt = DepositedEvent {
+                contract_address: event.from_address,
+                depositor: deposited.depositor,
+                amount: deposited.amount.to_u128().unwrap_or(u128::MAX),
+                block_number,
+                transaction_hash: event.transaction_hash,
+            };
+            info!("Deposited event detected: contract {:x}, amount {}",
+                evt.contract_address, AmountFormat::from_env().amount(evt.amount, TOKEN));
+            SwapEvent::Deposited(evt)
+        })
+    } else if selector == *REFUNDED_SELECTOR {
+        Refunded::decode(keys, data).map(|refunded| {
+            let evt = RefundedEvent {
+                contract_address: event.from_address,
+                depositor: refunded.depositor,
+                amount: refunded.amount.to_u128().unwrap_or(u128::MAX),
+                block_number,
+                transaction_hash: event.transaction_hash,
+            };
+            info!("Refunded event detected: contract {:x}, amount {}",
+                evt.contract_address, AmountFormat::from_env().amount(evt.amount, TOKEN));
+            SwapEvent::Refunded(evt)
+        })
+    } else if selector == *UNLOCKED_SELECTOR {
+        // Reveal and claim happen in one call, so there is no grace period
+        Unlocked::decode(keys, data).map(|unlocked| {
+            let evt = SecretRevealedEvent {
+                contract_address: event.from_address,
+                revealer: unlocked.unlocker,
+                secret_hash: unlocked.secret_hash,
+                claimable_after: 0,
+                block_number,
+                transaction_hash: event.transaction_hash,
+                contract_version: ContractVersion::Legacy,
+            };
+            info!("Legacy Unlocked event detected: contract {:x}", evt.contract_address);
+            SwapEvent::SecretRevealed(evt)
+        })
+    } else {
+        return None;
+    };
+
+    decoded
+        .map_err(|e| {
+            warn!("Dropping malformed event from {:x} in block {}: {}", event.from_address, block_number, e)
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_core::utils::starknet_keccak;
+
+    /// AtomicLock contract source, for checking the filter against its events
+    const CONTRACT_SOURCE: &str = include_str!("../../../cairo/src/lib.cairo");
+
+    /// Variant names of the contract's `#[event] enum Event`
+    fn contract_event_names() -> Vec<String> {
+        let start = CONTRACT_SOURCE
+            .find("pub enum Event {")
+            .expect("contract declares an Event enum");
+        let body = &CONTRACT_SOURCE[start..];
+        let body = &body[body.find('{').unwrap() + 1..body.find('}').unwrap()];
+        body.lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with("//") && !line.starts_with('#'))
+            .filter_map(|line| line.split(':').next())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn emitted(keys: Vec<Felt>, data: Vec<Felt>) -> EmittedEvent {
+        EmittedEvent {
+            from_address: Felt::ONE,
+            keys,
+            data,
+            block_hash: None,
+            block_number: Some(1),
+            transaction_hash: Felt::TWO,
         }
-        
-        Ok(())
     }
-}
 
+    #[test]
+    fn ranges_cover_each_contract_from_its_first_block() {
+        let (a, b) = (Felt::ONE, Felt::TWO);
+        // b was processed further than a before the restart
+        let ranges = block_ranges(&[(a, 10), (b, 25)], 52, 20);
+        assert_eq!(
+            ranges,
+            [(a, 10, 29), (b, 25, 29), (a, 30, 49), (b, 30, 49), (a, 50, 52), (b, 50, 52)]
+        );
+        assert_eq!(block_ranges(&[(a, 10)], 10, 20), [(a, 10, 10)]);
+        assert!(block_ranges(&[(a, 11)], 10, 20).is_empty());
+        assert!(block_ranges(&[], 10, 20).is_empty());
+    }
+
+    #[test]
+    fn range_events_are_split_by_block() {
+        let at = |block| EmittedEvent {
+            block_number: Some(block),
+            ..emitted(vec![*DEPOSITED_SELECTOR], vec![])
+        };
+        let blocks = split_by_block(vec![at(5), at(7), at(5), at(9)], 5, 8);
+        let counts: Vec<(u64, usize)> = blocks
+            .iter()
+            .map(|(block, events)| (*block, events.len()))
+            .collect();
+        // Every block is completed, the out-of-range event is dropped
+        assert_eq!(counts, [(5, 2), (6, 0), (7, 1), (8, 0)]);
+    }
+
+    #[test]
+    fn selectors_match_contract_abi() {
+        // sn_keccak of the event names, as emitted by the deployed contract
+        assert_eq!(
+            *SECRET_REVEALED_SELECTOR,
+            Felt::from_hex("0x012b00cc9424076f159ea2bfaf31f1623bbaf9eb50fb183d5f0e69899e764cf0").unwrap()
+        );
+        assert_eq!(
+            *TOKENS_CLAIMED_SELECTOR,
+            Felt::from_hex("0x03fb7926d81fdd796588df95e47590b4d9b2604ee11994401f1ad88e39cb95cb").unwrap()
+        );
+    }
+
+    #[test]
+    fn filter_only_contains_contract_events() {
+        let abi_selectors: Vec<Felt> = contract_event_names()
+            .iter()
+            .map(|name| starknet_keccak(name.as_bytes()))
+            .collect();
+        let filter = event_key_filter();
+
+        assert_eq!(filter.len(), 1, "only the selector position is filtered");
+        for selector in &filter[0] {
+            assert!(abi_selectors.contains(selector), "{:x} is not an AtomicLock event", selector);
+        }
+    }
+
+    #[test]
+    fn legacy_unlocked_maps_to_reveal_without_grace_period() {
+        let legacy = emitted(vec![*UNLOCKED_SELECTOR, Felt::THREE], vec![Felt::from(0xdeadbeefu32)]);
+        let events = parse_events(std::slice::from_ref(&legacy), 1);
+        match events.as_slice() {
+            [SwapEvent::SecretRevealed(e)] => {
+                assert_eq!(e.contract_version, ContractVersion::Legacy);
+                assert_eq!(e.revealer, Felt::THREE);
+                assert_eq!(e.secret_hash, 0xdeadbeef);
+                assert_eq!(e.claimable_after, 0);
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+
+        // Current contracts emit Unlocked next to TokensClaimed: not a reveal
+        let claimed = emitted(vec![*TOKENS_CLAIMED_SELECTOR, Felt::THREE], vec![Felt::ZERO; 4]);
+        let events = parse_events(&[legacy, claimed], 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name(), "TokensClaimed");
+    }
+
+    #[test]
+    fn deposit_and_refund_are_parsed() {
+        let amount = vec![Felt::from(1000u64), Felt::ZERO];
+        let deposited = emitted(vec![*DEPOSITED_SELECTOR, Felt::THREE], amount.clone());
+        let refunded = emitted(vec![*REFUNDED_SELECTOR, Felt::THREE], amount);
+        match parse_events(&[deposited, refunded], 5).as_slice() {
+            [SwapEvent::Deposited(d), SwapEvent::Refunded(r)] => {
+                assert_eq!((d.depositor, d.amount, d.block_number), (Felt::THREE, 1000, 5));
+                assert_eq!((r.depositor, r.amount, r.block_number), (Felt::THREE, 1000, 5));
+                assert_eq!(r.transaction_hash, Felt::TWO);
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn filter_covers_every_parsed_event() {
+        let filter = &event_key_filter()[0];
+        for name in contract_event_names() {
+            let selector = starknet_keccak(name.as_bytes());
+            // Each event has its own data length; any one that parses counts
+            let parsed = (0..=8).any(|len| {
+                parse_event(&emitted(vec![selector, Felt::THREE], vec![Felt::ZERO; len]), 1).is_some()
+            });
+            assert_eq!(
+                parsed,
+                filter.contains(&selector),
+                "filter and parser disagree on {}",
+                name
+            );
+        }
+    }
+}
@@ -1,3 +1,11 @@
 pub mod listener;
+pub mod codec;
 pub mod client;
-
+pub mod failover;
+pub mod reorg;
+pub mod sequencer;
+pub mod subscription;
+pub mod outside_execution;
+pub mod fee_payer;
+pub mod auto_claim;
+pub mod auto_refund;
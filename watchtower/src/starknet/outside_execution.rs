@@ -0,0 +1,282 @@
+// SNIP-9 outside execution (v2)
+//
+// The account holding value signs an `OutsideExecution` (a list of calls
+// plus a time window) offline; any account named as `caller` can then submit
+// it through `execute_from_outside_v2` on the signing account and pay the
+// fee. The always-online watchtower only holds a low-value gas account, and
+// the signed payload only authorizes the calls it contains.
+//
+// Hashing follows SNIP-12 revision 1 (Poseidon), domain
+// `Account.execute_from_outside` version 2, as implemented by the
+// OpenZeppelin and Argent account contracts.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use starknet_core::types::{Call, Felt};
+use starknet_core::utils::{cairo_short_string_to_felt, get_selector_from_name, starknet_keccak};
+use starknet_crypto::poseidon_hash_many;
+
+const STARKNET_DOMAIN_TYPE: &str = r#""StarknetDomain"("name":"shortstring","version":"shortstring","chainId":"shortstring","revision":"shortstring")"#;
+const OUTSIDE_EXECUTION_TYPE: &str = r#""OutsideExecution"("Caller":"ContractAddress","Nonce":"felt","Execute After":"u128","Execute Before":"u128","Calls":"Call*")"Call"("To":"ContractAddress","Selector":"selector","Calldata":"felt*")"#;
+const CALL_TYPE: &str = r#""Call"("To":"ContractAddress","Selector":"selector","Calldata":"felt*")"#;
+
+/// Domain name and version of SNIP-9 v2
+const DOMAIN_NAME: &str = "Account.execute_from_outside";
+const DOMAIN_VERSION: u64 = 2;
+const SNIP12_REVISION: u64 = 1;
+
+/// `caller` value accepting any submitter
+pub fn any_caller() -> Felt {
+    cairo_short_string_to_felt("ANY_CALLER").expect("short string")
+}
+
+/// One call authorized by the signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutsideCall {
+    pub to: Felt,
+    pub selector: Felt,
+    #[serde(default)]
+    pub calldata: Vec<Felt>,
+}
+
+impl OutsideCall {
+    /// Call to `entrypoint` by name
+    pub fn new(to: Felt, entrypoint: &str, calldata: Vec<Felt>) -> Result<Self> {
+        Ok(Self {
+            to,
+            selector: get_selector_from_name(entrypoint)
+                .with_context(|| format!("Invalid entrypoint name '{}'", entrypoint))?,
+            calldata,
+        })
+    }
+
+    fn struct_hash(&self) -> Felt {
+        poseidon_hash_many(&[
+            starknet_keccak(CALL_TYPE.as_bytes()),
+            self.to,
+            self.selector,
+            poseidon_hash_many(&self.calldata),
+        ])
+    }
+}
+
+/// Calls the signing account agrees to run when submitted by `caller`
+/// between `execute_after` and `execute_before` (unix seconds, exclusive)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutsideExecution {
+    pub caller: Felt,
+    /// Single-use; the account rejects a nonce it has seen
+    pub nonce: Felt,
+    pub execute_after: u64,
+    pub execute_before: u64,
+    pub calls: Vec<OutsideCall>,
+}
+
+impl OutsideExecution {
+    /// SNIP-12 message hash the account signs
+    pub fn message_hash(&self, chain_id: Felt, account: Felt) -> Felt {
+        poseidon_hash_many(&[
+            cairo_short_string_to_felt("StarkNet Message").expect("short string"),
+            domain_hash(chain_id),
+            account,
+            self.struct_hash(),
+        ])
+    }
+
+    fn struct_hash(&self) -> Felt {
+        let calls: Vec<Felt> = self.calls.iter().map(OutsideCall::struct_hash).collect();
+        poseidon_hash_many(&[
+            starknet_keccak(OUTSIDE_EXECUTION_TYPE.as_bytes()),
+            self.caller,
+            self.nonce,
+            Felt::from(self.execute_after),
+            Felt::from(self.execute_before),
+            poseidon_hash_many(&calls),
+        ])
+    }
+
+    /// Cairo serialization of the struct
+    fn serialize(&self, out: &mut Vec<Felt>) {
+        out.extend([
+            self.caller,
+            self.nonce,
+            Felt::from(self.execute_after),
+            Felt::from(self.execute_before),
+            Felt::from(self.calls.len()),
+        ]);
+        for call in &self.calls {
+            out.extend([call.to, call.selector, Felt::from(call.calldata.len())]);
+            out.extend_from_slice(&call.calldata);
+        }
+    }
+}
+
+fn domain_hash(chain_id: Felt) -> Felt {
+    poseidon_hash_many(&[
+        starknet_keccak(STARKNET_DOMAIN_TYPE.as_bytes()),
+        cairo_short_string_to_felt(DOMAIN_NAME).expect("short string"),
+        Felt::from(DOMAIN_VERSION),
+        chain_id,
+        Felt::from(SNIP12_REVISION),
+    ])
+}
+
+/// Human-written request for `--sign-outside-execution`
+///
+/// ```text
+/// {"execute_after": 1700003600, "execute_before": 1700090000,
+///  "calls": [{"to": "0x...", "entrypoint": "claim_tokens", "calldata": []}]}
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutsideExecutionSpec {
+    /// Defaults to `ANY_CALLER`
+    #[serde(default)]
+    pub caller: Option<Felt>,
+    /// Defaults to the current time in nanoseconds
+    #[serde(default)]
+    pub nonce: Option<Felt>,
+    #[serde(default)]
+    pub execute_after: u64,
+    pub execute_before: u64,
+    pub calls: Vec<CallSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallSpec {
+    pub to: Felt,
+    pub entrypoint: String,
+    #[serde(default)]
+    pub calldata: Vec<Felt>,
+}
+
+impl OutsideExecutionSpec {
+    pub fn into_execution(self) -> Result<OutsideExecution> {
+        if self.execute_before <= self.execute_after {
+            bail!("execute_before must be later than execute_after");
+        }
+        let calls = self
+            .calls
+            .into_iter()
+            .map(|call| OutsideCall::new(call.to, &call.entrypoint, call.calldata))
+            .collect::<Result<Vec<_>>>()?;
+        let nonce = match self.nonce {
+            Some(nonce) => nonce,
+            None => Felt::from(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_nanos(),
+            ),
+        };
+        Ok(OutsideExecution {
+            caller: self.caller.unwrap_or_else(any_caller),
+            nonce,
+            execute_after: self.execute_after,
+            execute_before: self.execute_before,
+            calls,
+        })
+    }
+}
+
+/// An outside execution with the signing account's signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedOutsideExecution {
+    /// Account that signed and whose calls are executed
+    pub account: Felt,
+    pub execution: OutsideExecution,
+    pub signature: Vec<Felt>,
+}
+
+impl SignedOutsideExecution {
+    /// Sign `execution` with the account's private key (offline)
+    pub fn sign(
+        execution: OutsideExecution,
+        account: Felt,
+        chain_id: Felt,
+        private_key: Felt,
+    ) -> Result<Self> {
+        let hash = execution.message_hash(chain_id, account);
+        let signature = ::starknet::signers::SigningKey::from_secret_scalar(private_key)
+            .sign(&hash)
+            .context("Failed to sign outside execution")?;
+        Ok(Self {
+            account,
+            execution,
+            signature: vec![signature.r, signature.s],
+        })
+    }
+
+    /// The `execute_from_outside_v2` call the fee payer submits
+    pub fn to_call(&self) -> Call {
+        let mut calldata = Vec::new();
+        self.execution.serialize(&mut calldata);
+        calldata.push(Felt::from(self.signature.len()));
+        calldata.extend_from_slice(&self.signature);
+        Call {
+            to: self.account,
+            selector: get_selector_from_name("execute_from_outside_v2").expect("valid name"),
+            calldata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_hashes_match_account_contracts() {
+        // Constants from the OpenZeppelin/Argent SNIP-9 implementations
+        assert_eq!(
+            starknet_keccak(STARKNET_DOMAIN_TYPE.as_bytes()),
+            Felt::from_hex("0x1ff2f602e42168014d405a94f75e8a93d640751d71d16311266e140d8b0a210").unwrap()
+        );
+        assert_eq!(
+            starknet_keccak(OUTSIDE_EXECUTION_TYPE.as_bytes()),
+            Felt::from_hex("0x312b56c05a7965066ddbda31c016d8d05afc305071c0ca3cdc2192c3c2f1f0f").unwrap()
+        );
+        assert_eq!(
+            starknet_keccak(CALL_TYPE.as_bytes()),
+            Felt::from_hex("0x3635c7f2a7ba93844c0d064e18e487f35ab90f7c39d00f186a781fc3f0c2ca9").unwrap()
+        );
+    }
+
+    #[test]
+    fn execute_from_outside_calldata_layout() {
+        let execution = OutsideExecution {
+            caller: Felt::from(0xfeeu64),
+            nonce: Felt::from(7u64),
+            execute_after: 100,
+            execute_before: 200,
+            calls: vec![OutsideCall::new(Felt::from(0xc0u64), "claim_tokens", vec![]).unwrap()],
+        };
+        let signed = SignedOutsideExecution {
+            account: Felt::from(0xaaau64),
+            execution,
+            signature: vec![Felt::from(1u64), Felt::from(2u64)],
+        };
+
+        let call = signed.to_call();
+        assert_eq!(call.to, Felt::from(0xaaau64));
+        let selector = get_selector_from_name("claim_tokens").unwrap();
+        assert_eq!(
+            call.calldata,
+            vec![
+                Felt::from(0xfeeu64),
+                Felt::from(7u64),
+                Felt::from(100u64),
+                Felt::from(200u64),
+                Felt::ONE,
+                Felt::from(0xc0u64),
+                selector,
+                Felt::ZERO,
+                Felt::TWO,
+                Felt::ONE,
+                Felt::TWO,
+            ]
+        );
+
+        // Round-trips through the presigned file format
+        let json = serde_json::to_string(&signed).unwrap();
+        assert_eq!(serde_json::from_str::<SignedOutsideExecution>(&json).unwrap(), signed);
+    }
+}
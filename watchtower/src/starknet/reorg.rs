@@ -0,0 +1,170 @@
+// Chain reorganization tracking
+//
+// The listener remembers the hash of each of the last `window` processed
+// blocks and the events it forwarded from them. Before every poll it asks
+// the node for the hash of the newest remembered block; if it changed, it
+// walks back to the newest block whose hash still matches (the fork point).
+// Every event forwarded from a later block is then reverted, the cursors
+// move back to the fork point, and the blocks after it are processed again
+// from the new canonical chain.
+//
+// A reorg deeper than the window cannot be located exactly: the listener
+// rolls back the whole window and logs an error. `finality_depth` keeps such
+// reorgs from reaching the listener in the first place.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use starknet_core::types::Felt;
+
+use super::listener::SwapEvent;
+
+/// Processed blocks whose hashes are re-checked for reorgs
+pub const DEFAULT_REORG_WINDOW: u64 = 64;
+
+/// Recent block hashes and the events forwarded from those blocks
+pub struct ReorgTracker {
+    window: u64,
+    hashes: BTreeMap<u64, Felt>,
+    /// (block, event), oldest first
+    forwarded: VecDeque<(u64, SwapEvent)>,
+}
+
+impl ReorgTracker {
+    pub fn new(window: u64) -> Self {
+        Self {
+            window,
+            hashes: BTreeMap::new(),
+            forwarded: VecDeque::new(),
+        }
+    }
+
+    pub fn window(&self) -> u64 {
+        self.window
+    }
+
+    /// Remember the hash of processed block `number`
+    pub fn record_block(&mut self, number: u64, hash: Felt) {
+        if self.window == 0 {
+            return;
+        }
+        self.hashes.insert(number, hash);
+        let oldest = number.saturating_sub(self.window - 1);
+        self.hashes.retain(|block, _| *block >= oldest);
+        while self
+            .forwarded
+            .front()
+            .is_some_and(|(block, _)| *block < oldest)
+        {
+            self.forwarded.pop_front();
+        }
+    }
+
+    /// Remember an event forwarded from block `number`
+    pub fn record_event(&mut self, number: u64, event: SwapEvent) {
+        if self.window > 0 {
+            self.forwarded.push_back((number, event));
+        }
+    }
+
+    /// Remembered (block, hash) pairs, newest first
+    pub fn newest_first(&self) -> Vec<(u64, Felt)> {
+        self.hashes.iter().rev().map(|(n, h)| (*n, *h)).collect()
+    }
+
+    /// Forget everything after `fork_block`; returns the events forwarded
+    /// from those blocks, newest first (the order to undo them in)
+    pub fn rollback(&mut self, fork_block: u64) -> Vec<SwapEvent> {
+        self.hashes.retain(|block, _| *block <= fork_block);
+        let mut reverted = Vec::new();
+        while self
+            .forwarded
+            .back()
+            .is_some_and(|(block, _)| *block > fork_block)
+        {
+            if let Some((_, event)) = self.forwarded.pop_back() {
+                reverted.push(event);
+            }
+        }
+        reverted
+    }
+}
+
+/// Fork point given the remembered hashes (newest first) and the canonical
+/// hash of each, in the same order: `None` if the newest block still
+/// matches, else the newest matching block (or the block before the window)
+pub fn fork_point(remembered: &[(u64, Felt)], canonical: &[Felt]) -> Option<u64> {
+    let newest = remembered.first()?;
+    if canonical.first() == Some(&newest.1) {
+        return None;
+    }
+    let matching = remembered
+        .iter()
+        .zip(canonical)
+        .find(|((_, hash), canonical)| hash == *canonical)
+        .map(|((block, _), _)| *block);
+    Some(matching.unwrap_or_else(|| {
+        remembered
+            .last()
+            .map_or(0, |(block, _)| block.saturating_sub(1))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TokensClaimedEvent;
+
+    fn claimed(block_number: u64) -> SwapEvent {
+        SwapEvent::TokensClaimed(TokensClaimedEvent {
+            contract_address: Felt::ONE,
+            claimer: Felt::TWO,
+            amount: 1000,
+            reveal_timestamp: 0,
+            claim_timestamp: 0,
+            block_number,
+            transaction_hash: Felt::THREE,
+        })
+    }
+
+    #[test]
+    fn rollback_reverts_events_after_the_fork() {
+        let mut tracker = ReorgTracker::new(3);
+        for block in 10..=13 {
+            tracker.record_block(block, Felt::from(block));
+            tracker.record_event(block, claimed(block));
+        }
+        // Only the last 3 blocks are remembered
+        let remembered: Vec<u64> = tracker.newest_first().iter().map(|(n, _)| *n).collect();
+        assert_eq!(remembered, [13, 12, 11]);
+
+        let reverted = tracker.rollback(11);
+        let blocks: Vec<u64> = reverted
+            .iter()
+            .map(|event| match event {
+                SwapEvent::TokensClaimed(e) => e.block_number,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(blocks, [13, 12]);
+        assert_eq!(tracker.newest_first(), [(11, Felt::from(11u64))]);
+    }
+
+    #[test]
+    fn fork_point_is_the_newest_matching_block() {
+        let remembered = [
+            (13, Felt::from(13u64)),
+            (12, Felt::from(12u64)),
+            (11, Felt::from(11u64)),
+        ];
+        let same = [Felt::from(13u64), Felt::from(12u64), Felt::from(11u64)];
+        assert_eq!(fork_point(&remembered, &same), None);
+
+        let forked = [Felt::from(99u64), Felt::from(98u64), Felt::from(11u64)];
+        assert_eq!(fork_point(&remembered, &forked), Some(11));
+
+        // Deeper than the window: roll back all of it
+        let deep = [Felt::from(99u64), Felt::from(98u64), Felt::from(97u64)];
+        assert_eq!(fork_point(&remembered, &deep), Some(10));
+        assert_eq!(fork_point(&[], &[]), None);
+    }
+}
@@ -0,0 +1,92 @@
+// Ordered delivery for concurrently fetched blocks
+//
+// Fetch tasks for (contract, block) pairs complete in any order. The
+// sequencer buffers out-of-order results and releases each contract's events
+// strictly in block order, so downstream state (Revealed -> Completed) never
+// sees a later block before an earlier one. A block that is never completed
+// (failed fetch) holds back everything after it for that contract, and
+// `next_block` tells the listener where to retry.
+
+use std::collections::{BTreeMap, HashMap};
+
+use starknet_core::types::Felt;
+
+use super::listener::SwapEvent;
+
+/// Per-contract reordering buffer
+pub struct BlockSequencer {
+    /// Next block to release, per contract
+    next_block: HashMap<Felt, u64>,
+    /// Completed blocks waiting for an earlier block, per contract
+    pending: HashMap<Felt, BTreeMap<u64, Vec<SwapEvent>>>,
+}
+
+impl BlockSequencer {
+    /// Start sequencing each contract from its own first block
+    pub fn new(first_blocks: impl IntoIterator<Item = (Felt, u64)>) -> Self {
+        Self {
+            next_block: first_blocks.into_iter().collect(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record the events of a completed (contract, block) fetch.
+    ///
+    /// Returns every event that is now deliverable, in block order.
+    pub fn complete(&mut self, contract: Felt, block: u64, events: Vec<SwapEvent>) -> Vec<SwapEvent> {
+        let buffered = self.pending.entry(contract).or_default();
+        buffered.insert(block, events);
+
+        let next = self.next_block.entry(contract).or_insert(block);
+        let mut ready = Vec::new();
+        while let Some(events) = buffered.remove(next) {
+            ready.extend(events);
+            *next += 1;
+        }
+        ready
+    }
+
+    /// First block of `contract` not released yet
+    pub fn next_block(&self, contract: Felt) -> Option<u64> {
+        self.next_block.get(&contract).copied()
+    }
+
+    /// Number of completed blocks held back waiting for earlier ones
+    pub fn buffered_blocks(&self) -> usize {
+        self.pending.values().map(BTreeMap::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ContractVersion, SecretRevealedEvent};
+
+    fn revealed(contract: Felt, block_number: u64) -> SwapEvent {
+        SwapEvent::SecretRevealed(SecretRevealedEvent {
+            contract_address: contract,
+            revealer: Felt::ZERO,
+            secret_hash: 0,
+            claimable_after: 0,
+            block_number,
+            transaction_hash: Felt::ZERO,
+            contract_version: ContractVersion::Current,
+        })
+    }
+
+    #[test]
+    fn failed_block_holds_back_later_blocks() {
+        let (a, b) = (Felt::from(0xau64), Felt::from(0xbu64));
+        // b was processed further than a before the restart
+        let mut sequencer = BlockSequencer::new([(a, 10), (b, 12)]);
+
+        assert_eq!(sequencer.complete(a, 11, vec![revealed(a, 11)]).len(), 0);
+        assert_eq!(sequencer.complete(a, 10, vec![revealed(a, 10)]).len(), 2);
+        assert_eq!(sequencer.next_block(a), Some(12));
+
+        // Block 12 of b failed: 13 is fetched but not released
+        assert_eq!(sequencer.complete(b, 13, vec![revealed(b, 13)]).len(), 0);
+        assert_eq!(sequencer.next_block(b), Some(12));
+        assert_eq!(sequencer.buffered_blocks(), 1);
+    }
+}
@@ -0,0 +1,236 @@
+// WebSocket event subscription
+//
+// Polling every 10 seconds delays alerts, which matters inside a grace
+// period. With STARKNET_WS_URL set (a pathfinder or juno node serving
+// JSON-RPC 0.8 over WebSocket), the watchtower calls `starknet_subscribeEvents`
+// for every watched contract and wakes the listener as soon as one of them
+// emits an AtomicLock event, or the node reports a reorg. A notification only
+// triggers a poll: events are still fetched with `starknet_getEvents`, so the
+// finality depth, reorg checks and cursors work as before.
+//
+// Polling never stops. While the WebSocket cannot connect, the node rejects
+// the subscription, or the connection drops, the listener keeps its 10 s
+// interval and the subscription reconnects with backoff (5 s doubling up to
+// 5 min). Registering or removing a contract renews the subscriptions.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use starknet_core::types::Felt;
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+use super::listener::event_key_filter;
+use crate::registry::ContractRegistry;
+
+/// First reconnect delay, doubled after every failed session
+const MIN_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// How often the watched contracts are compared with the subscribed ones
+const REGISTRY_CHECK: Duration = Duration::from_secs(30);
+
+/// `starknet_subscribeEvents` request for the AtomicLock events of `contract`
+pub fn subscribe_request(id: u64, contract: Felt) -> Value {
+    let keys: Vec<Vec<String>> = event_key_filter()
+        .iter()
+        .map(|position| position.iter().map(|key| format!("{:#x}", key)).collect())
+        .collect();
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "starknet_subscribeEvents",
+        "params": {
+            "from_address": format!("{:#x}", contract),
+            "keys": keys,
+        },
+    })
+}
+
+/// What a message from the node means for the listener
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// `contract` emitted a subscribed event
+    Event { contract: Felt },
+    /// The node switched to another chain
+    Reorg,
+    /// Answer to a request (e.g. the subscription id)
+    Response,
+    /// The node rejected a request
+    Error(String),
+    /// Anything else
+    Other,
+}
+
+/// Classify one text message of the WebSocket
+pub fn parse_notification(text: &str) -> Notification {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return Notification::Other;
+    };
+    if let Some(error) = message.get("error") {
+        let reason = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map_or_else(|| error.to_string(), str::to_string);
+        return Notification::Error(reason);
+    }
+    match message.get("method").and_then(Value::as_str) {
+        Some("starknet_subscriptionEvents") => message
+            .pointer("/params/result/from_address")
+            .and_then(Value::as_str)
+            .and_then(|address| Felt::from_hex(address).ok())
+            .map_or(Notification::Other, |contract| Notification::Event {
+                contract,
+            }),
+        Some("starknet_subscriptionReorg") => Notification::Reorg,
+        Some(_) => Notification::Other,
+        None if message.get("result").is_some() => Notification::Response,
+        None => Notification::Other,
+    }
+}
+
+/// Subscription that wakes the listener when a watched contract emits an event
+pub struct EventSubscription {
+    ws_url: String,
+    registry: ContractRegistry,
+    wakeup: Arc<Notify>,
+}
+
+impl EventSubscription {
+    pub fn new(ws_url: &str, registry: ContractRegistry) -> Self {
+        Self {
+            ws_url: ws_url.to_string(),
+            registry,
+            wakeup: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Notified on every relevant notification (see `StarknetListener::with_wakeup`)
+    pub fn wakeup(&self) -> Arc<Notify> {
+        Arc::clone(&self.wakeup)
+    }
+
+    /// Keep the subscription up in the background, reconnecting on failure
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            let mut backoff = MIN_BACKOFF;
+            loop {
+                let started = Instant::now();
+                match self.session().await {
+                    Ok(()) => {
+                        info!("Renewing Starknet event subscriptions");
+                        backoff = MIN_BACKOFF;
+                        continue;
+                    }
+                    Err(e) => warn!(
+                        "Starknet WebSocket subscription down, polling only: {:#}",
+                        e
+                    ),
+                }
+                // A session that lasted a while was healthy: start over
+                if started.elapsed() > MAX_BACKOFF {
+                    backoff = MIN_BACKOFF;
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+    }
+
+    /// Subscribe to every watched contract and forward notifications until
+    /// the connection fails (error) or the watched contracts change (Ok)
+    async fn session(&self) -> Result<()> {
+        let contracts = self.registry.addresses();
+        let (mut ws, _) = tokio_tungstenite::connect_async(self.ws_url.as_str())
+            .await
+            .context("Failed to connect to STARKNET_WS_URL")?;
+        for (id, contract) in contracts.iter().enumerate() {
+            let request = subscribe_request(id as u64 + 1, *contract);
+            ws.send(Message::text(request.to_string())).await?;
+        }
+        info!(
+            "Subscribed to events of {} contract(s) over WebSocket",
+            contracts.len()
+        );
+        // Catch up with anything emitted while disconnected
+        self.wakeup.notify_one();
+
+        let mut registry_check = tokio::time::interval(REGISTRY_CHECK);
+        registry_check.tick().await;
+        loop {
+            tokio::select! {
+                message = ws.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None => bail!("connection closed by the node"),
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(e.into()),
+                    };
+                    match parse_notification(&text) {
+                        Notification::Event { contract } if contracts.contains(&contract) => {
+                            self.wakeup.notify_one();
+                        }
+                        Notification::Reorg => self.wakeup.notify_one(),
+                        Notification::Error(reason) => {
+                            bail!("node rejected starknet_subscribeEvents: {}", reason)
+                        }
+                        _ => {}
+                    }
+                }
+                _ = registry_check.tick() => {
+                    if self.registry.addresses() != contracts {
+                        let _ = ws.close(None).await;
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscription_filters_on_contract_and_selectors() {
+        let request = subscribe_request(3, Felt::from(0xabcu64));
+        assert_eq!(request["method"], "starknet_subscribeEvents");
+        assert_eq!(request["id"], 3);
+        assert_eq!(request["params"]["from_address"], "0xabc");
+        let keys = request["params"]["keys"].as_array().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(
+            keys[0].as_array().unwrap().len(),
+            event_key_filter()[0].len()
+        );
+    }
+
+    #[test]
+    fn notifications_are_classified() {
+        let event = r#"{"jsonrpc":"2.0","method":"starknet_subscriptionEvents","params":{"subscription_id":"42","result":{"from_address":"0xabc","keys":["0x1"],"data":[],"block_number":7,"transaction_hash":"0x2"}}}"#;
+        assert_eq!(
+            parse_notification(event),
+            Notification::Event {
+                contract: Felt::from(0xabcu64)
+            }
+        );
+        let reorg = r#"{"jsonrpc":"2.0","method":"starknet_subscriptionReorg","params":{"subscription_id":"42","result":{}}}"#;
+        assert_eq!(parse_notification(reorg), Notification::Reorg);
+        assert_eq!(
+            parse_notification(r#"{"jsonrpc":"2.0","id":1,"result":"42"}"#),
+            Notification::Response
+        );
+        assert_eq!(
+            parse_notification(
+                r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"Method not found"}}"#
+            ),
+            Notification::Error("Method not found".to_string())
+        );
+        assert_eq!(parse_notification("not json"), Notification::Other);
+    }
+}
@@ -0,0 +1,57 @@
+// systemd service integration (sd_notify readiness and watchdog)
+//
+// Built with `--features systemd`. Without the feature, or when not started
+// by systemd (no NOTIFY_SOCKET), every call is a no-op.
+
+#[cfg(feature = "systemd")]
+use tracing::{info, warn};
+
+/// Tell systemd startup is complete (`Type=notify`)
+pub fn notify_ready() {
+    #[cfg(feature = "systemd")]
+    if let Err(e) = sd_notify::notify(false, &[sd_notify::NotifyState::Ready]) {
+        warn!("sd_notify READY failed: {}", e);
+    }
+}
+
+/// Update the status line shown by `systemctl status`
+pub fn notify_status(status: &str) {
+    #[cfg(feature = "systemd")]
+    if let Err(e) = sd_notify::notify(false, &[sd_notify::NotifyState::Status(status)]) {
+        warn!("sd_notify STATUS failed: {}", e);
+    }
+    #[cfg(not(feature = "systemd"))]
+    let _ = status;
+}
+
+/// Tell systemd the service is shutting down
+pub fn notify_stopping() {
+    #[cfg(feature = "systemd")]
+    if let Err(e) = sd_notify::notify(false, &[sd_notify::NotifyState::Stopping]) {
+        warn!("sd_notify STOPPING failed: {}", e);
+    }
+}
+
+/// Ping the watchdog at half of `WatchdogSec` while the runtime is alive.
+///
+/// Does nothing unless the unit sets `WatchdogSec=`.
+pub fn spawn_watchdog() {
+    #[cfg(feature = "systemd")]
+    {
+        let mut usec = 0u64;
+        if !sd_notify::watchdog_enabled(false, &mut usec) || usec == 0 {
+            return;
+        }
+        let period = std::time::Duration::from_micros(usec / 2);
+        info!("systemd watchdog enabled, pinging every {:?}", period);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = sd_notify::notify(false, &[sd_notify::NotifyState::Watchdog]) {
+                    warn!("sd_notify WATCHDOG failed: {}", e);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,413 @@
+// Per-swap state
+//
+// Follows every contract through `types::SwapState` as its events arrive:
+//
+//   Deposited       -> Locked
+//   SecretRevealed  Locked -> Revealed (legacy Unlocked: -> Completed)
+//   TokensClaimed   Revealed -> Completed
+//   Refunded        Locked -> Expired
+//
+// A contract first seen mid-swap (registered late, or a restart) starts
+// from whatever its first event implies. The same goes for AtomicLock
+// deployments older than the `Deposited` event: they are followed from their
+// reveal, claim or refund, and their `depositor` and `amount` stay unknown. An event that does not follow from
+// the tracked state (claim without reveal, second deposit, refund after the
+// reveal, ...) leaves the state unchanged and is reported; an event already
+// in the swap's history (blocks processed again after a restart) is ignored.
+// A reorg rolls the swap back to its state before the reverted event. The
+// HTTP API serves the state under `/swaps`.
+//
+// The states are rewritten to WATCHTOWER_SWAPS (default
+// ./watchtower_swaps.json) after every change and loaded at startup, so a
+// restart from the checkpoint does not forget swaps whose events are behind
+// it.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use starknet_core::types::Felt;
+use tracing::{error, info};
+
+use crate::starknet::listener::SwapEvent;
+use crate::types::{ContractVersion, SwapState};
+
+/// File used when WATCHTOWER_SWAPS is not set
+pub const DEFAULT_SWAPS_PATH: &str = "watchtower_swaps.json";
+
+/// One applied event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapTransition {
+    pub event: String,
+    pub block: u64,
+    pub transaction: Felt,
+    /// State before the event (None: first event seen)
+    pub from: Option<SwapState>,
+    /// Unix time the event was processed
+    pub at: u64,
+}
+
+/// Tracked state of one swap contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapRecord {
+    pub contract: Felt,
+    pub state: SwapState,
+    pub depositor: Option<Felt>,
+    /// Deposited token amount (base units)
+    pub amount: Option<u128>,
+    pub history: Vec<SwapTransition>,
+}
+
+/// An event the tracked state does not allow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub contract: Felt,
+    pub event: &'static str,
+    pub state: SwapState,
+}
+
+/// State after `event`, or None if `current` does not allow it
+fn next_state(current: Option<&SwapState>, event: &SwapEvent) -> Option<SwapState> {
+    match (current, event) {
+        (None, SwapEvent::Deposited(_)) => Some(SwapState::Locked),
+        (None | Some(SwapState::Locked), SwapEvent::SecretRevealed(e)) => {
+            Some(match e.contract_version {
+                ContractVersion::Legacy => SwapState::Completed,
+                ContractVersion::Current => SwapState::Revealed {
+                    revealer: e.revealer,
+                    claimable_after: e.claimable_after,
+                },
+            })
+        }
+        (None | Some(SwapState::Revealed { .. }), SwapEvent::TokensClaimed(_)) => {
+            Some(SwapState::Completed)
+        }
+        (None | Some(SwapState::Locked), SwapEvent::Refunded(_)) => Some(SwapState::Expired),
+        _ => None,
+    }
+}
+
+/// Swap state of every contract with events, shared with the API
+#[derive(Debug, Clone, Default)]
+pub struct SwapTracker {
+    swaps: Arc<RwLock<BTreeMap<Felt, SwapRecord>>>,
+    /// None: kept in memory only
+    path: Option<Arc<PathBuf>>,
+}
+
+impl SwapTracker {
+    /// `WATCHTOWER_SWAPS`, or `watchtower_swaps.json`
+    pub fn from_env() -> Result<Self> {
+        let path =
+            std::env::var("WATCHTOWER_SWAPS").unwrap_or_else(|_| DEFAULT_SWAPS_PATH.to_string());
+        Self::open(path)
+    }
+
+    /// Resume the states stored at `path` (none if there is no file yet)
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let records: Vec<SwapRecord> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Corrupt swap states {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        if !records.is_empty() {
+            info!("Resuming {} swap state(s) from {}", records.len(), path.display());
+        }
+        Ok(Self {
+            swaps: Arc::new(RwLock::new(
+                records
+                    .into_iter()
+                    .map(|record| (record.contract, record))
+                    .collect(),
+            )),
+            path: Some(Arc::new(path)),
+        })
+    }
+
+    /// Apply `event` processed at `now`
+    pub fn apply(&self, event: &SwapEvent, now: u64) -> Result<(), InvalidTransition> {
+        let contract = event.contract_address();
+        let mut swaps = self.write();
+        if let SwapEvent::Reverted { event, .. } = event {
+            Self::revert(&mut swaps, event);
+            self.persist(&swaps);
+            return Ok(());
+        }
+        let applied = swaps.get(&contract).is_some_and(|record| {
+            record.history.iter().any(|transition| {
+                transition.event == event.name()
+                    && transition.transaction == event.transaction_hash()
+            })
+        });
+        if applied {
+            return Ok(());
+        }
+        let current = swaps.get(&contract).map(|record| &record.state);
+        let Some(state) = next_state(current, event) else {
+            return Err(InvalidTransition {
+                contract,
+                event: event.name(),
+                state: current.cloned().expect("every event is valid first"),
+            });
+        };
+        let record = swaps.entry(contract).or_insert_with(|| SwapRecord {
+            contract,
+            state: state.clone(),
+            depositor: None,
+            amount: None,
+            history: Vec::new(),
+        });
+        let from = (!record.history.is_empty()).then(|| record.state.clone());
+        record.history.push(SwapTransition {
+            event: event.name().to_string(),
+            block: event.block_number(),
+            transaction: event.transaction_hash(),
+            from,
+            at: now,
+        });
+        record.state = state;
+        if let SwapEvent::Deposited(e) = event {
+            record.depositor = Some(e.depositor);
+            record.amount = Some(e.amount);
+        }
+        self.persist(&swaps);
+        Ok(())
+    }
+
+    /// Undo `event` and everything applied after it
+    fn revert(swaps: &mut BTreeMap<Felt, SwapRecord>, event: &SwapEvent) {
+        let contract = event.contract_address();
+        let Some(record) = swaps.get_mut(&contract) else {
+            return;
+        };
+        let Some(index) = record.history.iter().rposition(|transition| {
+            transition.event == event.name() && transition.transaction == event.transaction_hash()
+        }) else {
+            return;
+        };
+        let undone = record.history.split_off(index);
+        match undone
+            .into_iter()
+            .next()
+            .and_then(|transition| transition.from)
+        {
+            Some(state) => {
+                record.state = state;
+                if !record.history.iter().any(|t| t.event == "Deposited") {
+                    record.depositor = None;
+                    record.amount = None;
+                }
+            }
+            None => {
+                swaps.remove(&contract);
+            }
+        }
+    }
+
+    pub fn get(&self, contract: Felt) -> Option<SwapRecord> {
+        self.read().get(&contract).cloned()
+    }
+
+    /// Every tracked swap, by contract
+    pub fn snapshot(&self) -> Vec<SwapRecord> {
+        self.read().values().cloned().collect()
+    }
+
+    /// Write the states to the file now (e.g. at shutdown)
+    pub fn save(&self) {
+        let swaps = self.read();
+        self.persist(&swaps);
+        if let Some(path) = &self.path {
+            info!("Saved {} swap state(s) to {}", swaps.len(), path.display());
+        }
+    }
+
+    /// Replace the file atomically; a failed write only loses durability
+    fn persist(&self, swaps: &BTreeMap<Felt, SwapRecord>) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let tmp = path.with_extension("tmp");
+        let records: Vec<&SwapRecord> = swaps.values().collect();
+        let written = serde_json::to_vec_pretty(&records)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(std::fs::write(&tmp, bytes)?))
+            .and_then(|()| Ok(std::fs::rename(&tmp, path.as_path())?));
+        if let Err(e) = written {
+            error!("Failed to persist swap states {}: {:#}", path.display(), e);
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<Felt, SwapRecord>> {
+        self.swaps.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<Felt, SwapRecord>> {
+        self.swaps.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DepositedEvent, RefundedEvent, SecretRevealedEvent, TokensClaimedEvent};
+
+    const CONTRACT: Felt = Felt::ONE;
+
+    fn deposited() -> SwapEvent {
+        SwapEvent::Deposited(DepositedEvent {
+            contract_address: CONTRACT,
+            depositor: Felt::TWO,
+            amount: 1000,
+            block_number: 5,
+            transaction_hash: Felt::from(0xd0u64),
+        })
+    }
+
+    fn revealed() -> SwapEvent {
+        SwapEvent::SecretRevealed(SecretRevealedEvent {
+            contract_address: CONTRACT,
+            revealer: Felt::THREE,
+            secret_hash: 0,
+            claimable_after: 1_700_000_000,
+            block_number: 7,
+            transaction_hash: Felt::from(0xe0u64),
+            contract_version: ContractVersion::Current,
+        })
+    }
+
+    fn claimed() -> SwapEvent {
+        SwapEvent::TokensClaimed(TokensClaimedEvent {
+            contract_address: CONTRACT,
+            claimer: Felt::THREE,
+            amount: 1000,
+            reveal_timestamp: 0,
+            claim_timestamp: 0,
+            block_number: 9,
+            transaction_hash: Felt::from(0xf0u64),
+        })
+    }
+
+    #[test]
+    fn swaps_follow_their_events() {
+        let tracker = SwapTracker::default();
+        tracker.apply(&deposited(), 1).unwrap();
+        assert_eq!(
+            tracker.apply(&claimed(), 2),
+            Err(InvalidTransition {
+                contract: CONTRACT,
+                event: "TokensClaimed",
+                state: SwapState::Locked,
+            })
+        );
+        tracker.apply(&revealed(), 3).unwrap();
+        let record = tracker.get(CONTRACT).unwrap();
+        assert_eq!(
+            record.state,
+            SwapState::Revealed {
+                revealer: Felt::THREE,
+                claimable_after: 1_700_000_000
+            }
+        );
+        assert_eq!(
+            (record.depositor, record.amount),
+            (Some(Felt::TWO), Some(1000))
+        );
+        let second_deposit = match deposited() {
+            SwapEvent::Deposited(e) => SwapEvent::Deposited(DepositedEvent {
+                transaction_hash: Felt::from(0xd1u64),
+                ..e
+            }),
+            _ => unreachable!(),
+        };
+        assert!(tracker.apply(&second_deposit, 4).is_err());
+
+        tracker.apply(&claimed(), 5).unwrap();
+        assert_eq!(tracker.get(CONTRACT).unwrap().state, SwapState::Completed);
+        let events: Vec<_> = tracker.snapshot()[0]
+            .history
+            .iter()
+            .map(|t| t.event.clone())
+            .collect();
+        assert_eq!(events, ["Deposited", "SecretRevealed", "TokensClaimed"]);
+        // The same events processed again after a restart change nothing
+        tracker.apply(&deposited(), 6).unwrap();
+        tracker.apply(&claimed(), 6).unwrap();
+        assert_eq!(tracker.get(CONTRACT).unwrap().history.len(), 3);
+
+        // A contract first seen mid-swap starts from its first event
+        let late = SwapTracker::default();
+        late.apply(&claimed(), 1).unwrap();
+        assert_eq!(late.get(CONTRACT).unwrap().state, SwapState::Completed);
+    }
+
+    #[test]
+    fn contracts_without_deposited_events_are_tracked() {
+        // Deployed before deposit() emitted Deposited: reveal and claim only
+        let tracker = SwapTracker::default();
+        tracker.apply(&revealed(), 1).unwrap();
+        tracker.apply(&claimed(), 2).unwrap();
+        let record = tracker.get(CONTRACT).unwrap();
+        assert_eq!(record.state, SwapState::Completed);
+        assert_eq!((record.depositor, record.amount), (None, None));
+
+        // ... or straight to the refund
+        let refunded = SwapTracker::default();
+        refunded
+            .apply(
+                &SwapEvent::Refunded(RefundedEvent {
+                    contract_address: CONTRACT,
+                    depositor: Felt::TWO,
+                    amount: 1000,
+                    block_number: 8,
+                    transaction_hash: Felt::from(0xa0u64),
+                }),
+                1,
+            )
+            .unwrap();
+        let record = refunded.get(CONTRACT).unwrap();
+        assert_eq!(record.state, SwapState::Expired);
+        assert_eq!((record.depositor, record.amount), (None, None));
+    }
+
+    #[test]
+    fn reorgs_roll_the_swap_back() {
+        let tracker = SwapTracker::default();
+        tracker.apply(&deposited(), 1).unwrap();
+        tracker.apply(&revealed(), 2).unwrap();
+        tracker.apply(&claimed(), 3).unwrap();
+
+        let reverted = |event: SwapEvent| SwapEvent::Reverted {
+            fork_block: 6,
+            event: Box::new(event),
+        };
+        tracker.apply(&reverted(revealed()), 4).unwrap();
+        let record = tracker.get(CONTRACT).unwrap();
+        assert_eq!(record.state, SwapState::Locked);
+        assert_eq!(record.history.len(), 1);
+
+        tracker.apply(&reverted(deposited()), 5).unwrap();
+        assert_eq!(tracker.get(CONTRACT), None);
+    }
+
+    #[test]
+    fn states_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("wt-swaps-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let tracker = SwapTracker::open(&path).unwrap();
+        tracker.apply(&deposited(), 1).unwrap();
+        tracker.apply(&revealed(), 2).unwrap();
+
+        let resumed = SwapTracker::open(&path).unwrap();
+        assert_eq!(resumed.snapshot(), tracker.snapshot());
+        // A claim after the restart follows the resumed reveal
+        resumed.apply(&claimed(), 3).unwrap();
+        assert_eq!(resumed.get(CONTRACT).unwrap().state, SwapState::Completed);
+        let _ = std::fs::remove_file(&path);
+    }
+}
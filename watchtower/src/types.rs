@@ -1,6 +1,29 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 use starknet_core::types::Felt;
 
+/// AtomicLock contract generation that emitted an event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractVersion {
+    /// Pre-grace-period contracts: a single Unlocked(unlocker, secret_hash)
+    /// releases the tokens immediately
+    Legacy,
+    /// reveal_secret + claim_tokens, with SecretRevealed / TokensClaimed
+    #[default]
+    Current,
+}
+
+impl fmt::Display for ContractVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractVersion::Legacy => write!(f, "legacy (Unlocked event, no grace period)"),
+            ContractVersion::Current => write!(f, "current (grace period)"),
+        }
+    }
+}
+
 /// Event emitted when secret is revealed (Phase 1)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecretRevealedEvent {
@@ -10,6 +33,8 @@ pub struct SecretRevealedEvent {
     pub claimable_after: u64,
     pub block_number: u64,
     pub transaction_hash: Felt,
+    #[serde(default)]
+    pub contract_version: ContractVersion,
 }
 
 /// Event emitted when tokens are claimed (Phase 2)
@@ -20,6 +45,30 @@ pub struct TokensClaimedEvent {
     pub amount: u128,
     pub reveal_timestamp: u64,
     pub claim_timestamp: u64,
+    #[serde(default)]
+    pub block_number: u64,
+    #[serde(default)]
+    pub transaction_hash: Felt,
+}
+
+/// Event emitted when the depositor funds the contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositedEvent {
+    pub contract_address: Felt,
+    pub depositor: Felt,
+    pub amount: u128,
+    pub block_number: u64,
+    pub transaction_hash: Felt,
+}
+
+/// Event emitted when the depositor takes the tokens back after the timelock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundedEvent {
+    pub contract_address: Felt,
+    pub depositor: Felt,
+    pub amount: u128,
+    pub block_number: u64,
+    pub transaction_hash: Felt,
 }
 
 /// Swap state tracked by watchtower
@@ -38,16 +87,43 @@ pub enum SwapState {
     Expired,
 }
 
-/// Alert severity levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl fmt::Display for SwapState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapState::Locked => write!(f, "locked, waiting for the reveal"),
+            SwapState::Revealed { claimable_after, .. } => {
+                write!(f, "revealed, claimable after {}", claimable_after)
+            }
+            SwapState::Completed => write!(f, "completed"),
+            SwapState::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+/// Alert severity levels, in increasing order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum AlertLevel {
     Info,
     Warning,
     Critical,
 }
 
+impl std::str::FromStr for AlertLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "info" => Ok(AlertLevel::Info),
+            "warning" => Ok(AlertLevel::Warning),
+            "critical" => Ok(AlertLevel::Critical),
+            other => anyhow::bail!("unknown alert level '{}' (info, warning, critical)", other),
+        }
+    }
+}
+
 /// Alert to send to operators
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Alert {
     pub level: AlertLevel,
     pub title: String,